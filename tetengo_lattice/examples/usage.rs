@@ -0,0 +1,216 @@
+#![doc = "The usage of tetengo_lattice."]
+
+/*
+ * Builds a word dictionary as a trie, serializes it into a file, loads it with an mmap storage,
+ * and segments an input with a lattice whose vocabulary is backed by the trie.
+ *
+ * Copyright (C) 2023-2025 kaoru  <https://www.tetengo.org/>
+ */
+
+use std::fs::File;
+use std::io::{Seek, SeekFrom};
+use std::process::exit;
+use std::rc::Rc;
+
+use anyhow::Result;
+
+use tetengo_lattice::{
+    Connection, Constraint, Entry, Input, InputError, LatticeError, NBestIterator, Node, Path,
+    StringInput, Vocabulary,
+};
+use tetengo_trie::{
+    Deserializer, FileMapping, IntegerDeserializer, IntegerSerializer, MmapStorage, Serializer,
+    StringSerializer, Trie, ValueDeserializer, ValueSerializer,
+};
+
+fn main() {
+    if let Err(e) = main_core() {
+        eprintln!("Error: {}", e);
+        exit(1);
+    }
+}
+
+fn main_core() -> Result<()> {
+    // Builds a word dictionary as a trie, and serializes it into a file.
+    let mut file = tempfile::tempfile()?;
+    serialize_trie(&build_trie()?, &mut file)?;
+
+    // Loads the trie from the file with an mmap storage, and makes a vocabulary of it.
+    let vocabulary = TrieVocabulary::new(load_trie(file)?);
+
+    // Creates an object for a lattice.
+    let mut lattice = tetengo_lattice::lattice::Lattice::new(&vocabulary);
+
+    // Enters the syllables to construct the lattice.
+    for syllable in ["ku", "ma", "mo", "to", "e", "ki", "ma", "e"] {
+        lattice.push_back(Box::new(StringInput::new(String::from(syllable))))?;
+    }
+
+    // Finishes the lattice construction.
+    let eos = lattice.settle()?;
+
+    // Enumerates the three best paths in the lattice.
+    let iterator = NBestIterator::new(&lattice, eos, Box::new(Constraint::new()));
+    let paths = iterator
+        .take(3)
+        .map(|path| to_string(&path))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        paths,
+        [
+            String::from("[BOS]-[kumamoto]-[ekimae]-[EOS] (5)"),
+            String::from("[BOS]-[kumamoto]-[eki]-[mae]-[EOS] (8)"),
+            String::from("[BOS]-[kuma]-[moto]-[ekimae]-[EOS] (10)"),
+        ]
+        .to_vec()
+    );
+    paths.iter().for_each(|path| println!("{}", path));
+
+    // Finds the words starting at the beginning of a text with one common prefix search.
+    let words = vocabulary
+        .find_entries_at(&StringInput::new(String::from("kumamotoekimae")), 0)?
+        .into_iter()
+        .map(|(length, _)| length)
+        .collect::<Vec<_>>();
+    assert_eq!(words, [2, 4, 8].to_vec());
+
+    Ok(())
+}
+
+type WordTrie = Trie<String, i32>;
+
+fn build_trie() -> Result<WordTrie> {
+    // The keys are the words, and the values are their costs.
+    // The single syllables are also registered with a high cost
+    // so that the lattice always has a node at each step.
+    let trie = WordTrie::builder()
+        .elements(
+            [
+                (String::from("kumamoto"), 2),
+                (String::from("kuma"), 3),
+                (String::from("moto"), 4),
+                (String::from("ekimae"), 3),
+                (String::from("eki"), 2),
+                (String::from("mae"), 4),
+                (String::from("ku"), 10),
+                (String::from("ma"), 10),
+                (String::from("mo"), 10),
+                (String::from("to"), 10),
+                (String::from("e"), 10),
+                (String::from("ki"), 10),
+            ]
+            .to_vec(),
+        )
+        .key_serializer(StringSerializer::new(true))
+        .build()?;
+    Ok(trie)
+}
+
+fn serialize_trie(trie: &WordTrie, file: &mut File) -> Result<()> {
+    // An mmap storage requires the values to be serialized in a fixed size.
+    let integer_serializer = IntegerSerializer::<i32>::new(false);
+    let mut value_serializer = ValueSerializer::new(
+        Box::new(move |value: &i32| integer_serializer.serialize(value)),
+        size_of::<i32>(),
+    );
+    trie.storage().serialize(file, &mut value_serializer)?;
+    let _ = file.seek(SeekFrom::Start(0))?;
+    Ok(())
+}
+
+fn load_trie(file: File) -> Result<WordTrie> {
+    let file_size = file.metadata()?.len() as usize;
+    let file_mapping = Rc::new(FileMapping::new(file)?);
+    let integer_deserializer = IntegerDeserializer::<i32>::new(false);
+    let value_deserializer = ValueDeserializer::new(Box::new(move |serialized: &[u8]| {
+        integer_deserializer.deserialize(serialized)
+    }));
+    let storage = MmapStorage::builder(file_mapping, 0, file_size, value_deserializer).build()?;
+    Ok(WordTrie::builder_with_storage(Box::new(storage)).build()?)
+}
+
+#[derive(Debug)]
+struct TrieVocabulary {
+    trie: WordTrie,
+}
+
+impl TrieVocabulary {
+    const fn new(trie: WordTrie) -> Self {
+        Self { trie }
+    }
+}
+
+impl Vocabulary for TrieVocabulary {
+    fn find_entries(&self, key: &dyn Input) -> tetengo_lattice::Result<Vec<Rc<Entry>>> {
+        let Some(key) = key.as_any().downcast_ref::<StringInput>() else {
+            return Ok(Vec::new());
+        };
+        let found = self
+            .trie
+            .find(&String::from(key.value()))
+            .map_err(|e| LatticeError::Other(Box::new(e)))?;
+        Ok(found
+            .map(|cost| Rc::new(make_entry(key.value(), *cost)))
+            .into_iter()
+            .collect())
+    }
+
+    fn find_entries_at(
+        &self,
+        input: &dyn Input,
+        offset: usize,
+    ) -> tetengo_lattice::Result<Vec<(usize, Rc<Entry>)>> {
+        let Some(input) = input.downcast_ref::<StringInput>() else {
+            return Ok(Vec::new());
+        };
+        let Some(text) = input.value().get(offset..) else {
+            return Err(InputError::RangeOutOfBounds.into());
+        };
+        // One common prefix search finds all the words starting at the offset.
+        let prefixes = self
+            .trie
+            .find_prefixes(&String::from(text))
+            .map_err(|e| LatticeError::Other(Box::new(e)))?;
+        Ok(prefixes
+            .into_iter()
+            .map(|(length, cost)| (length, Rc::new(make_entry(&text[..length], *cost))))
+            .collect())
+    }
+
+    fn find_connection(&self, _from: &Node, _to: &Entry) -> tetengo_lattice::Result<Connection> {
+        // All the connections cost nothing in this example.
+        Ok(Connection::new(0))
+    }
+}
+
+// The entry value is the word itself.
+fn make_entry(word: &str, cost: i32) -> Entry {
+    Entry::new(
+        Box::new(StringInput::new(String::from(word))),
+        Box::new(String::from(word)),
+        cost,
+    )
+}
+
+fn to_string(path: &Path) -> String {
+    let mut result = String::new();
+    for node in path.nodes() {
+        if !result.is_empty() {
+            result += "-";
+        }
+        result += format!("[{}]", value_of_node(node, result.is_empty())).as_str();
+    }
+    result += format!(" ({})", path.cost()).as_str();
+    result
+}
+
+fn value_of_node(node: &Node, first: bool) -> String {
+    if let Some(value) = node.value() {
+        // The value is stored in the Any object.
+        value.downcast_ref::<String>().unwrap().clone()
+    } else if first {
+        String::from("BOS")
+    } else {
+        String::from("EOS")
+    }
+}
@@ -0,0 +1,203 @@
+/*! \file
+    \brief A C interface of the lattice.
+
+    Build the library with the "ffi" feature enabled.
+
+    Copyright (C) 2023-2025 kaoru  https://www.tetengo.org/
+*/
+
+#if !defined(TETENGO_LATTICE_H)
+#define TETENGO_LATTICE_H
+
+#include <stdbool.h>
+#include <stddef.h>
+
+#if defined(__cplusplus)
+extern "C" {
+#endif
+
+/*!
+    \brief A string view.
+*/
+typedef struct tetengo_lattice_stringView_tag
+{
+    /*! A pointer to the head. */
+    const char* p_head;
+
+    /*! A length. */
+    size_t length;
+} tetengo_lattice_stringView_t;
+
+/*!
+    \brief An entry view.
+*/
+typedef struct tetengo_lattice_entryView_tag
+{
+    /*! A key. */
+    tetengo_lattice_stringView_t key;
+
+    /*! A value handle. */
+    const void* value_handle;
+
+    /*! A cost. */
+    int cost;
+} tetengo_lattice_entryView_t;
+
+/*!
+    \brief A pair of a key and entries.
+*/
+typedef struct tetengo_lattice_keyEntriesPair_tag
+{
+    /*! A key. */
+    tetengo_lattice_stringView_t key;
+
+    /*! A pointer to the entries. */
+    const tetengo_lattice_entryView_t* p_entries;
+
+    /*! An entry count. */
+    size_t entry_count;
+} tetengo_lattice_keyEntriesPair_t;
+
+/*!
+    \brief A pair of entries and a connection cost.
+
+    A NULL entry pointer stands for the BOS/EOS entry.
+*/
+typedef struct tetengo_lattice_entriesConnectionCostPair_tag
+{
+    /*! A pointer to the origin entry. */
+    const tetengo_lattice_entryView_t* p_from;
+
+    /*! A pointer to the destination entry. */
+    const tetengo_lattice_entryView_t* p_to;
+
+    /*! A cost. */
+    int cost;
+} tetengo_lattice_entriesConnectionCostPair_t;
+
+/*!
+    \brief A node.
+*/
+typedef struct tetengo_lattice_node_tag
+{
+    /*! A key. The head is NULL for BOS and EOS. */
+    tetengo_lattice_stringView_t key;
+
+    /*! A value handle. NULL for BOS and EOS. */
+    const void* value_handle;
+
+    /*! An index in the step. */
+    size_t index_in_step;
+
+    /*! A preceding step. */
+    size_t preceding_step;
+
+    /*! A pointer to the preceding edge costs. */
+    const int* p_preceding_edge_costs;
+
+    /*! A preceding edge cost count. */
+    size_t preceding_edge_cost_count;
+
+    /*! A best preceding node index. */
+    size_t best_preceding_node;
+
+    /*! A node cost. */
+    int node_cost;
+
+    /*! A path cost. */
+    int path_cost;
+} tetengo_lattice_node_t;
+
+/*!
+    \brief A function returning the hash value of an entry.
+
+    The entry pointer is NULL for the BOS/EOS entry.
+*/
+typedef size_t (*tetengo_lattice_entryHash_t)(const tetengo_lattice_entryView_t* p_entry);
+
+/*!
+    \brief A function returning true when two entries are equal.
+
+    An entry pointer is NULL for the BOS/EOS entry.
+*/
+typedef bool (*tetengo_lattice_entryEqualTo_t)(
+    const tetengo_lattice_entryView_t* p_one,
+    const tetengo_lattice_entryView_t* p_another);
+
+typedef struct tetengo_lattice_vocabulary_tag tetengo_lattice_vocabulary_t;
+
+typedef struct tetengo_lattice_input_tag tetengo_lattice_input_t;
+
+typedef struct tetengo_lattice_lattice_tag tetengo_lattice_lattice_t;
+
+typedef struct tetengo_lattice_constraintElement_tag tetengo_lattice_constraintElement_t;
+
+typedef struct tetengo_lattice_constraint_tag tetengo_lattice_constraint_t;
+
+typedef struct tetengo_lattice_nBestIterator_tag tetengo_lattice_nBestIterator_t;
+
+tetengo_lattice_vocabulary_t* tetengo_lattice_vocabulary_createHashMapVocabulary(
+    const tetengo_lattice_keyEntriesPair_t*            p_entries,
+    size_t                                             entry_count,
+    const tetengo_lattice_entriesConnectionCostPair_t* p_connections,
+    size_t                                             connection_count,
+    tetengo_lattice_entryHash_t                        p_entry_hash,
+    tetengo_lattice_entryEqualTo_t                     p_entry_equal_to);
+
+void tetengo_lattice_vocabulary_destroy(tetengo_lattice_vocabulary_t* p_vocabulary);
+
+tetengo_lattice_input_t* tetengo_lattice_input_createStringInput(const char* value);
+
+void tetengo_lattice_input_destroy(tetengo_lattice_input_t* p_input);
+
+size_t tetengo_lattice_input_length(const tetengo_lattice_input_t* p_input);
+
+tetengo_lattice_lattice_t* tetengo_lattice_lattice_create(const tetengo_lattice_vocabulary_t* p_vocabulary);
+
+void tetengo_lattice_lattice_destroy(tetengo_lattice_lattice_t* p_lattice);
+
+size_t tetengo_lattice_lattice_stepCount(const tetengo_lattice_lattice_t* p_lattice);
+
+bool tetengo_lattice_lattice_pushBack(tetengo_lattice_lattice_t* p_lattice, tetengo_lattice_input_t* p_input);
+
+bool tetengo_lattice_lattice_settle(tetengo_lattice_lattice_t* p_lattice, tetengo_lattice_node_t* p_eos_node);
+
+tetengo_lattice_constraintElement_t*
+tetengo_lattice_constraintElement_createNodeConstraintElement(const tetengo_lattice_node_t* p_node);
+
+tetengo_lattice_constraintElement_t*
+tetengo_lattice_constraintElement_createWildcardConstraintElement(size_t preceding_step);
+
+void tetengo_lattice_constraintElement_destroy(tetengo_lattice_constraintElement_t* p_constraint_element);
+
+tetengo_lattice_constraint_t* tetengo_lattice_constraint_createEmpty(void);
+
+tetengo_lattice_constraint_t* tetengo_lattice_constraint_create(
+    tetengo_lattice_constraintElement_t* const* pp_pattern,
+    size_t                                      pattern_length);
+
+void tetengo_lattice_constraint_destroy(tetengo_lattice_constraint_t* p_constraint);
+
+tetengo_lattice_nBestIterator_t* tetengo_lattice_nBestIterator_create(
+    const tetengo_lattice_lattice_t* p_lattice,
+    const tetengo_lattice_node_t*    p_eos_node,
+    tetengo_lattice_constraint_t*    p_constraint);
+
+void tetengo_lattice_nBestIterator_destroy(tetengo_lattice_nBestIterator_t* p_iterator);
+
+bool tetengo_lattice_nBestIterator_hasNext(const tetengo_lattice_nBestIterator_t* p_iterator);
+
+int tetengo_lattice_nBestIterator_pathCost(const tetengo_lattice_nBestIterator_t* p_iterator);
+
+size_t tetengo_lattice_nBestIterator_pathNodes(
+    const tetengo_lattice_nBestIterator_t* p_iterator,
+    tetengo_lattice_node_t*                p_nodes,
+    size_t                                 capacity);
+
+void tetengo_lattice_nBestIterator_next(tetengo_lattice_nBestIterator_t* p_iterator);
+
+#if defined(__cplusplus)
+}
+#endif
+
+#endif
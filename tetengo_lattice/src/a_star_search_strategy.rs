@@ -0,0 +1,144 @@
+/*!
+ * An A* search strategy.
+ *
+ * Copyright (C) 2023-2025 kaoru  <https://www.tetengo.org/>
+ */
+
+use std::any::type_name_of_val;
+use std::fmt::{self, Debug, Formatter};
+
+use crate::connection::Connection;
+use crate::exact_search_strategy::ExactSearchStrategy;
+use crate::node::Node;
+use crate::search_strategy::SearchStrategy;
+
+type Heuristic = dyn Fn(&Node) -> i32;
+
+/**
+ * An A* search strategy.
+ *
+ * It keeps only the nodes with the lowest sums of the path costs and the estimated costs from
+ * the nodes to the EOS in each step. Unlike the beam search, a node whose path cost is high only
+ * because it covers a costly part of the input survives when the rest of the input is estimated
+ * to be cheap. The nodes with lower indices in the step are kept among the nodes with the same
+ * sum.
+ *
+ * With a heuristic returning 0 for any node, it is the same as the beam search.
+ */
+pub struct AStarSearchStrategy {
+    heuristic: Box<Heuristic>,
+    beam_width: usize,
+}
+
+impl AStarSearchStrategy {
+    /**
+     * Creates an A* search strategy.
+     *
+     * # Arguments
+     * * `heuristic`  - A function returning the estimated cost from a node to the EOS.
+     * * `beam_width` - A beam width. Zero is treated as one.
+     */
+    pub fn new(heuristic: impl Fn(&Node) -> i32 + 'static, beam_width: usize) -> Self {
+        Self {
+            heuristic: Box::new(heuristic),
+            beam_width: if beam_width == 0 { 1 } else { beam_width },
+        }
+    }
+
+    /**
+     * Returns the beam width.
+     *
+     * # Returns
+     * The beam width.
+     */
+    pub const fn beam_width(&self) -> usize {
+        self.beam_width
+    }
+
+    fn estimated_cost(&self, node: &Node) -> i32 {
+        Connection::add_cost(node.path_cost(), (self.heuristic)(node))
+    }
+}
+
+impl SearchStrategy for AStarSearchStrategy {
+    fn best_preceding_node(&self, preceding_nodes: &[Node], preceding_edge_costs: &[i32]) -> usize {
+        ExactSearchStrategy::new().best_preceding_node(preceding_nodes, preceding_edge_costs)
+    }
+
+    fn nodes_to_keep(&self, nodes: &[Node]) -> Vec<usize> {
+        let estimated_costs = nodes
+            .iter()
+            .map(|node| self.estimated_cost(node))
+            .collect::<Vec<_>>();
+        let mut indices = (0..nodes.len()).collect::<Vec<_>>();
+        indices.sort_by_key(|&i| estimated_costs[i]);
+        indices.truncate(self.beam_width);
+        indices.sort_unstable();
+        indices
+    }
+}
+
+impl Debug for AStarSearchStrategy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AStarSearchStrategy")
+            .field("heuristic", &type_name_of_val(&self.heuristic))
+            .field("beam_width", &self.beam_width)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use super::*;
+
+    #[test]
+    fn new() {
+        let _strategy = AStarSearchStrategy::new(|_| 0, 3);
+    }
+
+    #[test]
+    fn beam_width() {
+        assert_eq!(AStarSearchStrategy::new(|_| 0, 3).beam_width(), 3);
+        assert_eq!(AStarSearchStrategy::new(|_| 0, 0).beam_width(), 1);
+    }
+
+    #[test]
+    fn nodes_to_keep() {
+        let nodes = [
+            Node::eos(0, Rc::new(Vec::new()), 0, 30),
+            Node::eos(1, Rc::new(Vec::new()), 0, 10),
+            Node::eos(2, Rc::new(Vec::new()), 0, 20),
+        ];
+
+        {
+            let strategy = AStarSearchStrategy::new(|_| 0, 2);
+            assert_eq!(strategy.nodes_to_keep(&nodes), vec![1, 2]);
+        }
+        {
+            let strategy = AStarSearchStrategy::new(|node| node.preceding_step() as i32 * 15, 2);
+            assert_eq!(strategy.nodes_to_keep(&nodes), vec![0, 1]);
+        }
+        {
+            let strategy = AStarSearchStrategy::new(|node| node.preceding_step() as i32 * 15, 1);
+            assert_eq!(strategy.nodes_to_keep(&nodes), vec![1]);
+        }
+        {
+            let strategy = AStarSearchStrategy::new(|_| Connection::INFINITE_COST, 5);
+            assert_eq!(strategy.nodes_to_keep(&nodes), vec![0, 1, 2]);
+        }
+    }
+
+    #[test]
+    fn best_preceding_node() {
+        let nodes = [
+            Node::eos(0, Rc::new(Vec::new()), 0, 30),
+            Node::eos(1, Rc::new(Vec::new()), 0, 10),
+        ];
+
+        let strategy = AStarSearchStrategy::new(|node| node.preceding_step() as i32 * 100, 2);
+        assert_eq!(strategy.best_preceding_node(&nodes, &[0, 5]), 1);
+        assert_eq!(strategy.best_preceding_node(&nodes, &[0, 25]), 0);
+    }
+}
@@ -0,0 +1,108 @@
+/*!
+ * An approximate entry.
+ *
+ * Copyright (C) 2023-2025 kaoru  <https://www.tetengo.org/>
+ */
+
+use std::any::Any;
+use std::rc::Rc;
+
+use crate::entry::Entry;
+use crate::error::Result;
+use crate::input::Input;
+
+/**
+ * An approximate hit.
+ *
+ * A value, its cost and the edit distance between the key and the input span.
+ */
+pub type ApproximateHit = (Box<dyn Any>, i32, usize);
+
+/**
+ * Makes entries from approximate hits.
+ *
+ * The cost of each entry is the cost of the hit plus the penalty proportional to the edit
+ * distance. All the entries have the input span as their keys, so that they can be returned from
+ * `Vocabulary::find_entries()` for noisy inputs. `TrieVocabulary` makes such entries from its
+ * trie with `TrieVocabulary::find_approximate_entries()`.
+ *
+ * # Arguments
+ * * `key`               - An input span.
+ * * `hits`              - Approximate hits.
+ * * `cost_per_distance` - A cost penalty per edit distance.
+ *
+ * # Returns
+ * Entries.
+ *
+ * # Errors
+ * * When it fails to copy the key.
+ */
+pub fn make_approximate_entries(
+    key: &dyn Input,
+    hits: Vec<ApproximateHit>,
+    cost_per_distance: i32,
+) -> Result<Vec<Rc<Entry>>> {
+    let mut entries = Vec::with_capacity(hits.len());
+    for (value, cost, distance) in hits {
+        entries.push(Rc::new(Entry::new(
+            key.create_subrange(0, key.length())?,
+            value,
+            cost.saturating_add(distance_penalty(distance, cost_per_distance)),
+        )));
+    }
+    Ok(entries)
+}
+
+pub(crate) fn distance_penalty(distance: usize, cost_per_distance: i32) -> i32 {
+    cost_per_distance.saturating_mul(i32::try_from(distance).unwrap_or(i32::MAX))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::string_input::StringInput;
+
+    use super::*;
+
+    #[test]
+    fn make_approximate_entries() {
+        {
+            let key = StringInput::new(String::from("mizuho"));
+
+            let entries = super::make_approximate_entries(&key, Vec::new(), 10).unwrap();
+            assert!(entries.is_empty());
+        }
+        {
+            let key = StringInput::new(String::from("mizuho"));
+            let hits: Vec<ApproximateHit> = vec![
+                (Box::new(String::from("瑞穂")), 42, 0),
+                (Box::new(String::from("瑞穂町")), 24, 3),
+                (Box::new(String::from("水穂")), i32::MAX - 1, 1),
+            ];
+
+            let entries = super::make_approximate_entries(&key, hits, 10).unwrap();
+            assert_eq!(entries.len(), 3);
+            assert!(entries
+                .iter()
+                .all(|entry| entry.key().unwrap().equal_to(&key)));
+            assert_eq!(
+                entries[0]
+                    .value()
+                    .unwrap()
+                    .downcast_ref::<String>()
+                    .unwrap(),
+                "瑞穂"
+            );
+            assert_eq!(entries[0].cost(), 42);
+            assert_eq!(
+                entries[1]
+                    .value()
+                    .unwrap()
+                    .downcast_ref::<String>()
+                    .unwrap(),
+                "瑞穂町"
+            );
+            assert_eq!(entries[1].cost(), 54);
+            assert_eq!(entries[2].cost(), i32::MAX);
+        }
+    }
+}
@@ -0,0 +1,92 @@
+/*!
+ * A beam search strategy.
+ *
+ * Copyright (C) 2023-2025 kaoru  <https://www.tetengo.org/>
+ */
+
+use crate::exact_search_strategy::ExactSearchStrategy;
+use crate::node::Node;
+use crate::search_strategy::SearchStrategy;
+
+/**
+ * A beam search strategy.
+ *
+ * It keeps only the nodes with the lowest path costs in each step. The nodes with lower indices
+ * in the step are kept among the nodes with the same path cost.
+ */
+#[derive(Clone, Copy, Debug)]
+pub struct BeamSearchStrategy {
+    beam_width: usize,
+}
+
+impl BeamSearchStrategy {
+    /**
+     * Creates a beam search strategy.
+     *
+     * # Arguments
+     * * `beam_width` - A beam width. Zero is treated as one.
+     */
+    pub const fn new(beam_width: usize) -> Self {
+        Self {
+            beam_width: if beam_width == 0 { 1 } else { beam_width },
+        }
+    }
+
+    /**
+     * Returns the beam width.
+     *
+     * # Returns
+     * The beam width.
+     */
+    pub const fn beam_width(&self) -> usize {
+        self.beam_width
+    }
+}
+
+impl SearchStrategy for BeamSearchStrategy {
+    fn best_preceding_node(&self, preceding_nodes: &[Node], preceding_edge_costs: &[i32]) -> usize {
+        ExactSearchStrategy::new().best_preceding_node(preceding_nodes, preceding_edge_costs)
+    }
+
+    fn nodes_to_keep(&self, nodes: &[Node]) -> Vec<usize> {
+        let mut indices = (0..nodes.len()).collect::<Vec<_>>();
+        indices.sort_by_key(|&i| nodes[i].path_cost());
+        indices.truncate(self.beam_width);
+        indices.sort_unstable();
+        indices
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use super::*;
+
+    #[test]
+    const fn new() {
+        let _ = BeamSearchStrategy::new(3);
+    }
+
+    #[test]
+    const fn beam_width() {
+        assert!(BeamSearchStrategy::new(3).beam_width() == 3);
+        assert!(BeamSearchStrategy::new(0).beam_width() == 1);
+    }
+
+    #[test]
+    fn nodes_to_keep() {
+        let nodes = [
+            Node::eos(0, Rc::new(Vec::new()), 0, 30),
+            Node::eos(0, Rc::new(Vec::new()), 0, 10),
+            Node::eos(0, Rc::new(Vec::new()), 0, 20),
+        ];
+
+        assert_eq!(BeamSearchStrategy::new(2).nodes_to_keep(&nodes), vec![1, 2]);
+        assert_eq!(BeamSearchStrategy::new(1).nodes_to_keep(&nodes), vec![1]);
+        assert_eq!(
+            BeamSearchStrategy::new(5).nodes_to_keep(&nodes),
+            vec![0, 1, 2]
+        );
+    }
+}
@@ -0,0 +1,314 @@
+/*!
+ * UniFFI bindings.
+ *
+ * Copyright (C) 2023-2025 kaoru  <https://www.tetengo.org/>
+ */
+
+use std::fmt::Debug;
+use std::sync::{Arc, Mutex};
+
+use crate::constraint::Constraint;
+use crate::entry::Entry;
+use crate::error::LatticeError;
+use crate::hash_map_vocabulary::HashMapVocabulary;
+use crate::lattice::Lattice;
+use crate::n_best_iterator::NBestIterator;
+use crate::string_input::StringInput;
+
+/**
+ * A binding error.
+ */
+#[derive(Debug, thiserror::Error, uniffi::Error)]
+#[uniffi(flat_error)]
+pub enum BindingError {
+    /**
+     * Decoding fails.
+     */
+    #[error("decoding fails: {0}")]
+    DecodingFailure(LatticeError),
+}
+
+/**
+ * A binding entry.
+ */
+#[derive(Clone, Debug, uniffi::Record)]
+pub struct BindingEntry {
+    /**
+     * A key.
+     */
+    pub key: String,
+
+    /**
+     * A value.
+     */
+    pub value: String,
+
+    /**
+     * A cost.
+     */
+    pub cost: i32,
+}
+
+/**
+ * A pair of an input and entries.
+ */
+#[derive(Clone, Debug, uniffi::Record)]
+pub struct BindingInputEntries {
+    /**
+     * An input.
+     */
+    pub input: String,
+
+    /**
+     * Entries.
+     */
+    pub entries: Vec<BindingEntry>,
+}
+
+/**
+ * A connection between entry keys.
+ *
+ * `None` stands for BOS or EOS.
+ */
+#[derive(Clone, Debug, uniffi::Record)]
+pub struct BindingConnection {
+    /**
+     * An origin entry key.
+     */
+    pub from: Option<String>,
+
+    /**
+     * A destination entry key.
+     */
+    pub to: Option<String>,
+
+    /**
+     * A cost.
+     */
+    pub cost: i32,
+}
+
+/**
+ * A decoded path.
+ */
+#[derive(Clone, Debug, uniffi::Record)]
+pub struct BindingPath {
+    /**
+     * The values of the nodes except BOS and EOS.
+     */
+    pub values: Vec<String>,
+
+    /**
+     * A cost.
+     */
+    pub cost: i32,
+}
+
+struct SendVocabulary(HashMapVocabulary<'static>);
+
+// SAFETY: The reference counts inside the vocabulary are touched only while the mutex is locked,
+// and no `Rc` derived from it escapes a decoding.
+unsafe impl Send for SendVocabulary {}
+
+/**
+ * A lattice decoder.
+ */
+#[derive(uniffi::Object)]
+pub struct LatticeDecoder {
+    vocabulary: Mutex<SendVocabulary>,
+}
+
+impl Debug for LatticeDecoder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LatticeDecoder").finish_non_exhaustive()
+    }
+}
+
+#[uniffi::export]
+impl LatticeDecoder {
+    /**
+     * Creates a lattice decoder.
+     *
+     * # Arguments
+     * * `input_entries` - Pairs of an input and entries.
+     * * `connections`   - Connections.
+     */
+    #[uniffi::constructor]
+    pub fn new(
+        input_entries: Vec<BindingInputEntries>,
+        connections: Vec<BindingConnection>,
+    ) -> Arc<Self> {
+        let entry_mappings = input_entries
+            .into_iter()
+            .map(|input_entries| {
+                let entries = input_entries
+                    .entries
+                    .into_iter()
+                    .map(|entry| to_entry(entry.key, entry.value, entry.cost))
+                    .collect::<Vec<_>>();
+                (input_entries.input, entries)
+            })
+            .collect::<Vec<_>>();
+        let connections = connections
+            .into_iter()
+            .map(|connection| {
+                let from = to_key_entry(connection.from);
+                let to = to_key_entry(connection.to);
+                ((from, to), connection.cost)
+            })
+            .collect::<Vec<_>>();
+        let vocabulary =
+            HashMapVocabulary::new(entry_mappings, connections, &entry_hash_value, &entry_equal);
+        Arc::new(Self {
+            vocabulary: Mutex::new(SendVocabulary(vocabulary)),
+        })
+    }
+
+    /**
+     * Decodes inputs into the N-best paths.
+     *
+     * The first path is the best path.
+     *
+     * # Arguments
+     * * `inputs` - Inputs. Each input makes a step.
+     * * `count`  - The maximum path count.
+     *
+     * # Returns
+     * The paths.
+     *
+     * # Errors
+     * * When decoding fails.
+     */
+    pub fn n_best(
+        &self,
+        inputs: Vec<String>,
+        count: u32,
+    ) -> Result<Vec<BindingPath>, BindingError> {
+        let vocabulary = self
+            .vocabulary
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let mut lattice = Lattice::new(&vocabulary.0);
+        for input in inputs {
+            lattice
+                .push_back(Box::new(StringInput::new(input)))
+                .map_err(BindingError::DecodingFailure)?;
+        }
+        let eos_node = lattice.settle().map_err(BindingError::DecodingFailure)?;
+        let paths = NBestIterator::new(&lattice, eos_node, Box::new(Constraint::new()))
+            .take(count as usize)
+            .map(|path| BindingPath {
+                values: path
+                    .nodes()
+                    .iter()
+                    .filter_map(|node| node.value())
+                    .filter_map(|value| value.downcast_ref::<String>())
+                    .cloned()
+                    .collect(),
+                cost: path.cost(),
+            })
+            .collect();
+        Ok(paths)
+    }
+}
+
+fn to_entry(key: String, value: String, cost: i32) -> Entry {
+    Entry::new(Box::new(StringInput::new(key)), Box::new(value), cost)
+}
+
+fn to_key_entry(key: Option<String>) -> Entry {
+    key.map_or(Entry::BosEos, |key| to_entry(key, String::new(), 0))
+}
+
+fn entry_hash_value(entry: &Entry) -> u64 {
+    entry.key().map_or(0, |key| key.hash_value())
+}
+
+fn entry_equal(one: &Entry, other: &Entry) -> bool {
+    match (one.key(), other.key()) {
+        (Some(one_key), Some(other_key)) => one_key.equal_to(other_key),
+        (None, None) => true,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_decoder() -> Arc<LatticeDecoder> {
+        let entry = |key: &str, value: &str, cost: i32| BindingEntry {
+            key: key.to_string(),
+            value: value.to_string(),
+            cost,
+        };
+        let connection = |from: Option<&str>, to: Option<&str>, cost: i32| BindingConnection {
+            from: from.map(str::to_string),
+            to: to.map(str::to_string),
+            cost,
+        };
+        LatticeDecoder::new(
+            vec![
+                BindingInputEntries {
+                    input: "[HakataTosu]".to_string(),
+                    entries: vec![entry("Hakata-Tosu", "kamome", 840)],
+                },
+                BindingInputEntries {
+                    input: "[TosuOmuta]".to_string(),
+                    entries: vec![entry("Tosu-Omuta", "local813", 860)],
+                },
+                BindingInputEntries {
+                    input: "[HakataTosu][TosuOmuta]".to_string(),
+                    entries: vec![entry("Hakata-Tosu-Omuta", "ariake", 2150)],
+                },
+            ],
+            vec![
+                connection(None, Some("Hakata-Tosu"), 800),
+                connection(None, Some("Hakata-Tosu-Omuta"), 700),
+                connection(Some("Hakata-Tosu"), Some("Tosu-Omuta"), 600),
+                connection(Some("Tosu-Omuta"), None, 3000),
+                connection(Some("Hakata-Tosu-Omuta"), None, 2000),
+            ],
+        )
+    }
+
+    #[test]
+    fn new() {
+        let _decoder = create_decoder();
+    }
+
+    #[test]
+    fn n_best() {
+        let decoder = create_decoder();
+
+        {
+            let paths = decoder
+                .n_best(
+                    vec!["[HakataTosu]".to_string(), "[TosuOmuta]".to_string()],
+                    5,
+                )
+                .unwrap();
+            assert_eq!(paths.len(), 2);
+            assert_eq!(paths[0].values, vec!["ariake".to_string()]);
+            assert_eq!(paths[0].cost, 4850);
+            assert_eq!(
+                paths[1].values,
+                vec!["kamome".to_string(), "local813".to_string()]
+            );
+            assert_eq!(paths[1].cost, 6100);
+        }
+        {
+            let paths = decoder
+                .n_best(
+                    vec!["[HakataTosu]".to_string(), "[TosuOmuta]".to_string()],
+                    1,
+                )
+                .unwrap();
+            assert_eq!(paths.len(), 1);
+        }
+        {
+            let result = decoder.n_best(vec!["[OmutaKumamoto]".to_string()], 5);
+            assert!(result.is_err());
+        }
+    }
+}
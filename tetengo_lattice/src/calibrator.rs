@@ -0,0 +1,627 @@
+/*!
+ * A calibrator.
+ *
+ * Copyright (C) 2023-2025 kaoru  <https://www.tetengo.org/>
+ */
+
+use std::any::{type_name_of_val, Any};
+use std::collections::{BTreeMap, HashSet};
+use std::fmt::{self, Debug, Formatter};
+use std::rc::Rc;
+
+use crate::connection::Connection;
+use crate::decoder::Decoder;
+use crate::error::Result;
+use crate::input::Input;
+use crate::lattice::Lattice;
+use crate::node::Node;
+use crate::path::{Path, PathDifference};
+
+type ValueMatcher<'a> = Box<dyn Fn(&dyn Any) -> bool + 'a>;
+
+/**
+ * A gold segment.
+ *
+ * It is a segment of a gold segmentation, which spans from the end step of the preceding segment
+ * (or the BOS) to its end step.
+ */
+pub struct GoldSegment<'a> {
+    end_step: usize,
+    value_matcher: Option<ValueMatcher<'a>>,
+}
+
+impl<'a> GoldSegment<'a> {
+    /**
+     * Creates a gold segment.
+     *
+     * The segment matches any value.
+     *
+     * # Arguments
+     * * `end_step` - An index of the step where the segment ends.
+     */
+    pub const fn new(end_step: usize) -> Self {
+        Self {
+            end_step,
+            value_matcher: None,
+        }
+    }
+
+    /**
+     * Sets a value matcher.
+     *
+     * The segment matches only the nodes whose values satisfy it.
+     *
+     * # Arguments
+     * * `value_matcher` - A value matcher.
+     */
+    pub fn value_matcher(mut self, value_matcher: impl Fn(&dyn Any) -> bool + 'a) -> Self {
+        self.value_matcher = Some(Box::new(value_matcher));
+        self
+    }
+
+    /**
+     * Returns the end step.
+     *
+     * # Returns
+     * The end step.
+     */
+    pub const fn end_step(&self) -> usize {
+        self.end_step
+    }
+
+    fn matches(&self, node: &Node, start_step: usize) -> bool {
+        node.step() == self.end_step
+            && node.preceding_step() == start_step
+            && self
+                .value_matcher
+                .as_ref()
+                .is_none_or(|value_matcher| node.value().is_some_and(value_matcher))
+    }
+}
+
+impl Debug for GoldSegment<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GoldSegment")
+            .field("end_step", &self.end_step)
+            .field("value_matcher", &type_name_of_val(&self.value_matcher))
+            .finish()
+    }
+}
+
+/**
+ * A lost sample.
+ *
+ * It is a sample of a corpus whose best path does not satisfy its gold segmentation.
+ */
+#[derive(Debug)]
+pub struct LostSample {
+    index: usize,
+    gold_path: Option<Path>,
+    best_path: Path,
+}
+
+impl LostSample {
+    /**
+     * Returns the index of the sample in the corpus.
+     *
+     * # Returns
+     * The index.
+     */
+    pub const fn index(&self) -> usize {
+        self.index
+    }
+
+    /**
+     * Returns the gold path.
+     *
+     * It is the best path among the ones satisfying the gold segmentation.
+     *
+     * # Returns
+     * The gold path. Or None when the lattice has no path satisfying the gold segmentation.
+     */
+    pub const fn gold_path(&self) -> Option<&Path> {
+        self.gold_path.as_ref()
+    }
+
+    /**
+     * Returns the best path.
+     *
+     * # Returns
+     * The best path.
+     */
+    pub const fn best_path(&self) -> &Path {
+        &self.best_path
+    }
+
+    /**
+     * Returns the differences between the best path and the gold path.
+     *
+     * # Returns
+     * The differences. Empty when the lattice has no gold path.
+     */
+    pub fn differences(&self) -> Vec<PathDifference> {
+        self.gold_path
+            .as_ref()
+            .map_or_else(Vec::new, |gold_path| self.best_path.differences(gold_path))
+    }
+}
+
+/**
+ * Connection statistics.
+ *
+ * They count the connections of a pair of context IDs only in the gold paths or only in the best
+ * paths of the lost samples. A connection counted often only in the gold paths is a candidate to
+ * make cheaper, and one counted often only in the best paths is a candidate to make costlier.
+ */
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ConnectionStatistics {
+    right_id: Option<usize>,
+    left_id: Option<usize>,
+    gold_only_count: usize,
+    best_only_count: usize,
+}
+
+impl ConnectionStatistics {
+    /**
+     * Returns the right context ID of the origin entry.
+     *
+     * # Returns
+     * The right context ID. Or None when the entry has no context ID.
+     */
+    pub const fn right_id(&self) -> Option<usize> {
+        self.right_id
+    }
+
+    /**
+     * Returns the left context ID of the destination entry.
+     *
+     * # Returns
+     * The left context ID. Or None when the entry has no context ID.
+     */
+    pub const fn left_id(&self) -> Option<usize> {
+        self.left_id
+    }
+
+    /**
+     * Returns the count of the connections only in the gold paths.
+     *
+     * # Returns
+     * The count.
+     */
+    pub const fn gold_only_count(&self) -> usize {
+        self.gold_only_count
+    }
+
+    /**
+     * Returns the count of the connections only in the best paths.
+     *
+     * # Returns
+     * The count.
+     */
+    pub const fn best_only_count(&self) -> usize {
+        self.best_only_count
+    }
+}
+
+/**
+ * A calibration report.
+ */
+#[derive(Debug)]
+pub struct CalibrationReport {
+    sample_count: usize,
+    lost_samples: Vec<LostSample>,
+    connection_statistics: Vec<ConnectionStatistics>,
+}
+
+impl CalibrationReport {
+    /**
+     * Returns the sample count.
+     *
+     * # Returns
+     * The sample count.
+     */
+    pub const fn sample_count(&self) -> usize {
+        self.sample_count
+    }
+
+    /**
+     * Returns the lost samples.
+     *
+     * # Returns
+     * The lost samples in the order in the corpus.
+     */
+    pub fn lost_samples(&self) -> &[LostSample] {
+        self.lost_samples.as_slice()
+    }
+
+    /**
+     * Returns the connection statistics.
+     *
+     * # Returns
+     * The connection statistics in ascending order of the pairs of the context IDs.
+     */
+    pub fn connection_statistics(&self) -> &[ConnectionStatistics] {
+        self.connection_statistics.as_slice()
+    }
+}
+
+type EdgeIdentity = (usize, usize, usize, usize);
+
+type ContextIdPair = (Option<usize>, Option<usize>);
+
+/**
+ * A calibrator.
+ *
+ * It decodes a corpus of the pairs of input sequences and their gold segmentations, and reports
+ * where the gold paths lose to the best paths. It is a tool to tune the costs of a hand-built
+ * vocabulary.
+ */
+#[derive(Debug)]
+pub struct Calibrator<'a> {
+    decoder: Decoder<'a>,
+}
+
+impl<'a> Calibrator<'a> {
+    /**
+     * Creates a calibrator.
+     *
+     * # Arguments
+     * * `lattice` - A lattice.
+     */
+    pub const fn new(lattice: Lattice<'a>) -> Self {
+        Self {
+            decoder: Decoder::new(lattice),
+        }
+    }
+
+    /**
+     * Calibrates with a corpus.
+     *
+     * # Arguments
+     * * `corpus` - The pairs of input sequences and their gold segmentations.
+     *
+     * # Returns
+     * A calibration report.
+     *
+     * # Errors
+     * * When the lattice fails to decode an input sequence.
+     */
+    pub fn calibrate(
+        &mut self,
+        corpus: impl IntoIterator<Item = (Vec<Box<dyn Input>>, Vec<GoldSegment<'a>>)>,
+    ) -> Result<CalibrationReport> {
+        let mut sample_count = 0usize;
+        let mut lost_samples = Vec::new();
+        let mut counts = BTreeMap::<ContextIdPair, (usize, usize)>::new();
+        for (index, (inputs, gold_segments)) in corpus.into_iter().enumerate() {
+            sample_count += 1;
+            let best_path = self.decoder.decode(inputs)?;
+            if Self::satisfies(best_path.nodes(), &gold_segments) {
+                continue;
+            }
+
+            let gold_path = Self::gold_path(self.decoder.lattice(), &best_path, &gold_segments)?;
+            if let Some(gold_path) = &gold_path {
+                let gold_edges = Self::edges(gold_path);
+                let best_edges = Self::edges(&best_path);
+                let gold_edge_identities = gold_edges
+                    .iter()
+                    .map(|(identity, _)| *identity)
+                    .collect::<HashSet<_>>();
+                let best_edge_identities = best_edges
+                    .iter()
+                    .map(|(identity, _)| *identity)
+                    .collect::<HashSet<_>>();
+                for (identity, pair) in gold_edges {
+                    if !best_edge_identities.contains(&identity) {
+                        counts.entry(pair).or_default().0 += 1;
+                    }
+                }
+                for (identity, pair) in best_edges {
+                    if !gold_edge_identities.contains(&identity) {
+                        counts.entry(pair).or_default().1 += 1;
+                    }
+                }
+            }
+            lost_samples.push(LostSample {
+                index,
+                gold_path,
+                best_path,
+            });
+        }
+
+        let connection_statistics = counts
+            .into_iter()
+            .map(
+                |((right_id, left_id), (gold_only_count, best_only_count))| ConnectionStatistics {
+                    right_id,
+                    left_id,
+                    gold_only_count,
+                    best_only_count,
+                },
+            )
+            .collect();
+        Ok(CalibrationReport {
+            sample_count,
+            lost_samples,
+            connection_statistics,
+        })
+    }
+
+    fn satisfies(nodes: &[Node], gold_segments: &[GoldSegment<'_>]) -> bool {
+        if nodes.len() != gold_segments.len() + 2 {
+            return false;
+        }
+        let mut start_step = 0usize;
+        for (node, gold_segment) in nodes[1..nodes.len() - 1].iter().zip(gold_segments) {
+            if !gold_segment.matches(node, start_step) {
+                return false;
+            }
+            start_step = gold_segment.end_step();
+        }
+        true
+    }
+
+    fn gold_path(
+        lattice: &Lattice<'_>,
+        best_path: &Path,
+        gold_segments: &[GoldSegment<'_>],
+    ) -> Result<Option<Path>> {
+        let Some(eos_node) = best_path.nodes().last() else {
+            return Ok(None);
+        };
+        if gold_segments.last().map_or(0, GoldSegment::end_step) != eos_node.preceding_step() {
+            return Ok(None);
+        }
+
+        // Each layer has the tuples of a node, its path cost and the index of its best
+        // preceding node in the preceding layer.
+        let mut layers = vec![vec![(&lattice.nodes_at(0)?[0], 0, 0)]];
+        let mut start_step = 0usize;
+        for gold_segment in gold_segments {
+            let Ok(nodes) = lattice.nodes_at(gold_segment.end_step()) else {
+                return Ok(None);
+            };
+            let preceding_layer = &layers[layers.len() - 1];
+            let mut layer = Vec::new();
+            for node in nodes
+                .iter()
+                .filter(|node| gold_segment.matches(node, start_step))
+            {
+                let Some((best_preceding_index, path_cost)) =
+                    Self::best_preceding(preceding_layer, node.preceding_edge_costs())
+                else {
+                    continue;
+                };
+                layer.push((
+                    node,
+                    Connection::add_cost(path_cost, node.node_cost()),
+                    best_preceding_index,
+                ));
+            }
+            if layer.is_empty() {
+                return Ok(None);
+            }
+            layers.push(layer);
+            start_step = gold_segment.end_step();
+        }
+        let Some((mut index, path_cost)) =
+            Self::best_preceding(&layers[layers.len() - 1], eos_node.preceding_edge_costs())
+        else {
+            return Ok(None);
+        };
+
+        let mut nodes = vec![Node::eos(
+            eos_node.preceding_step(),
+            Rc::new(eos_node.preceding_edge_costs().to_vec()),
+            layers[layers.len() - 1][index].0.index_in_step(),
+            path_cost,
+        )];
+        for layer in layers.iter().rev() {
+            let (node, _, best_preceding_index) = layer[index];
+            nodes.push(node.clone());
+            index = best_preceding_index;
+        }
+        nodes.reverse();
+        Ok(Some(Path::new(nodes, path_cost)))
+    }
+
+    fn best_preceding(
+        preceding_layer: &[(&Node, i32, usize)],
+        preceding_edge_costs: &[i32],
+    ) -> Option<(usize, i32)> {
+        preceding_layer
+            .iter()
+            .enumerate()
+            .map(|(i, (preceding_node, path_cost, _))| {
+                let edge_cost = preceding_edge_costs[preceding_node.index_in_step()];
+                (i, Connection::add_cost(*path_cost, edge_cost))
+            })
+            .filter(|(_, path_cost)| *path_cost != Connection::INFINITE_COST)
+            .min_by_key(|(_, path_cost)| *path_cost)
+    }
+
+    fn edges(path: &Path) -> Vec<(EdgeIdentity, ContextIdPair)> {
+        path.nodes()
+            .windows(2)
+            .map(|pair| {
+                let (from, to) = (&pair[0], &pair[1]);
+                (
+                    (
+                        from.step(),
+                        from.index_in_step(),
+                        to.step(),
+                        to.index_in_step(),
+                    ),
+                    (from.entry().right_id(), to.entry().left_id()),
+                )
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::entry::Entry;
+    use crate::hash_map_vocabulary::HashMapVocabulary;
+    use crate::string_input::StringInput;
+
+    use super::*;
+
+    fn entry_hash(entry: &Entry) -> u64 {
+        entry.key().map_or(0, |key| key.hash_value())
+    }
+
+    fn entry_equal_to(one: &Entry, other: &Entry) -> bool {
+        match (one.key(), other.key()) {
+            (Some(one_key), Some(other_key)) => one_key.equal_to(other_key),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+
+    fn create_vocabulary() -> HashMapVocabulary<'static> {
+        let entry = |key: &str, value: &'static str, cost| {
+            Entry::new(
+                Box::new(StringInput::new(String::from(key))),
+                Box::new(value),
+                cost,
+            )
+        };
+        HashMapVocabulary::new(
+            vec![
+                (
+                    String::from("a"),
+                    vec![entry("a", "A", 10).with_context_ids(1, 1)],
+                ),
+                (
+                    String::from("b"),
+                    vec![
+                        entry("b", "B", 20).with_context_ids(2, 2),
+                        entry("b", "B'", 40).with_context_ids(4, 4),
+                    ],
+                ),
+                (
+                    String::from("ab"),
+                    vec![entry("ab", "AB", 50).with_context_ids(3, 3)],
+                ),
+            ],
+            vec![
+                ((Entry::BosEos, entry("a", "", 0)), 1),
+                ((Entry::BosEos, entry("b", "", 0)), 1),
+                ((Entry::BosEos, entry("ab", "", 0)), 1),
+                ((entry("a", "", 0), entry("b", "", 0)), 1),
+                ((entry("a", "", 0), Entry::BosEos), 1),
+                ((entry("b", "", 0), Entry::BosEos), 1),
+                ((entry("ab", "", 0), Entry::BosEos), 1),
+            ],
+            &entry_hash,
+            &entry_equal_to,
+        )
+    }
+
+    fn to_inputs(inputs: &[&str]) -> Vec<Box<dyn Input>> {
+        inputs
+            .iter()
+            .map(|&input| {
+                let input: Box<dyn Input> = Box::new(StringInput::new(String::from(input)));
+                input
+            })
+            .collect()
+    }
+
+    fn values_of(path: &Path) -> Vec<&'static str> {
+        path.iter()
+            .map(|(_, value, _, _)| *value.downcast_ref::<&str>().unwrap())
+            .collect()
+    }
+
+    fn value_is(expected: &'static str) -> impl Fn(&dyn Any) -> bool {
+        move |value| value.downcast_ref::<&str>() == Some(&expected)
+    }
+
+    #[test]
+    fn new() {
+        let vocabulary = create_vocabulary();
+        let _calibrator = Calibrator::new(Lattice::new(&vocabulary));
+    }
+
+    #[test]
+    fn calibrate() {
+        let vocabulary = create_vocabulary();
+        let mut calibrator = Calibrator::new(Lattice::new(&vocabulary));
+
+        let corpus = vec![
+            (
+                to_inputs(&["a", "b"]),
+                vec![GoldSegment::new(1), GoldSegment::new(2)],
+            ),
+            (to_inputs(&["a", "b"]), vec![GoldSegment::new(2)]),
+            (
+                to_inputs(&["a", "b"]),
+                vec![
+                    GoldSegment::new(1),
+                    GoldSegment::new(2).value_matcher(value_is("B'")),
+                ],
+            ),
+            (
+                to_inputs(&["a", "b"]),
+                vec![GoldSegment::new(2).value_matcher(value_is("X"))],
+            ),
+        ];
+        let report = calibrator.calibrate(corpus).unwrap();
+
+        assert_eq!(report.sample_count(), 4);
+
+        let lost_samples = report.lost_samples();
+        assert_eq!(lost_samples.len(), 3);
+        {
+            let lost_sample = &lost_samples[0];
+            assert_eq!(lost_sample.index(), 1);
+            assert_eq!(values_of(lost_sample.best_path()), vec!["A", "B"]);
+            assert_eq!(lost_sample.best_path().cost(), 33);
+            let gold_path = lost_sample.gold_path().unwrap();
+            assert_eq!(values_of(gold_path), vec!["AB"]);
+            assert_eq!(gold_path.cost(), 52);
+            assert_eq!(lost_sample.differences().len(), 1);
+        }
+        {
+            let lost_sample = &lost_samples[1];
+            assert_eq!(lost_sample.index(), 2);
+            let gold_path = lost_sample.gold_path().unwrap();
+            assert_eq!(values_of(gold_path), vec!["A", "B'"]);
+            assert_eq!(gold_path.cost(), 53);
+        }
+        {
+            let lost_sample = &lost_samples[2];
+            assert_eq!(lost_sample.index(), 3);
+            assert!(lost_sample.gold_path().is_none());
+            assert!(lost_sample.differences().is_empty());
+        }
+
+        let statistics = report
+            .connection_statistics()
+            .iter()
+            .map(|s| {
+                (
+                    s.right_id(),
+                    s.left_id(),
+                    s.gold_only_count(),
+                    s.best_only_count(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            statistics,
+            vec![
+                (Some(0), Some(1), 0, 1),
+                (Some(0), Some(3), 1, 0),
+                (Some(1), Some(2), 0, 2),
+                (Some(1), Some(4), 1, 0),
+                (Some(2), Some(0), 0, 2),
+                (Some(3), Some(0), 1, 0),
+                (Some(4), Some(0), 1, 0),
+            ]
+        );
+    }
+}
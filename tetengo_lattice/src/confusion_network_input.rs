@@ -0,0 +1,190 @@
+/*!
+ * A confusion network input.
+ *
+ * Copyright (C) 2023-2025 kaoru  <https://www.tetengo.org/>
+ */
+
+use std::any::Any;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use crate::error::Result;
+use crate::input::{Input, InputError};
+
+/**
+ * An alternative unit at a position.
+ *
+ * A unit and its cost. The lower the cost is, the more likely the unit is.
+ */
+pub type Alternative = (String, i32);
+
+/**
+ * A confusion network input.
+ *
+ * Each position offers several alternative units, such as OCR hypotheses.
+ */
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct ConfusionNetworkInput {
+    positions: Vec<Vec<Alternative>>,
+}
+
+impl ConfusionNetworkInput {
+    /**
+     * Creates a confusion network input.
+     *
+     * # Arguments
+     * * `positions` - Alternatives at the positions.
+     */
+    pub const fn new(positions: Vec<Vec<Alternative>>) -> Self {
+        Self { positions }
+    }
+
+    /**
+     * Returns the alternatives at the positions.
+     *
+     * # Returns
+     * The alternatives at the positions.
+     */
+    pub fn positions(&self) -> &[Vec<Alternative>] {
+        self.positions.as_slice()
+    }
+
+    /**
+     * Returns the concatenations of the alternatives.
+     *
+     * The cost of a concatenation is the sum of the costs of its units.
+     * Only the `max_count` cheapest concatenations are kept at each position to bound the
+     * combinations.
+     *
+     * # Arguments
+     * * `max_count` - A maximum count of the concatenations.
+     *
+     * # Returns
+     * The concatenations in ascending order of their costs.
+     */
+    pub fn concatenations(&self, max_count: usize) -> Vec<Alternative> {
+        let mut concatenations: Vec<Alternative> = vec![(String::new(), 0)];
+        for alternatives in &self.positions {
+            let mut next = Vec::with_capacity(concatenations.len() * alternatives.len());
+            for (prefix, prefix_cost) in &concatenations {
+                for (unit, cost) in alternatives {
+                    next.push((prefix.clone() + unit, prefix_cost.saturating_add(*cost)));
+                }
+            }
+            next.sort_by_key(|(_, cost)| *cost);
+            next.truncate(max_count);
+            concatenations = next;
+        }
+        concatenations
+    }
+}
+
+impl Input for ConfusionNetworkInput {
+    fn equal_to(&self, other: &dyn Input) -> bool {
+        other.eq_concrete(self)
+    }
+
+    fn hash_value(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    fn length(&self) -> usize {
+        self.positions.len()
+    }
+
+    fn create_subrange(&self, offset: usize, length: usize) -> Result<Box<dyn Input>> {
+        if offset + length > self.positions.len() {
+            return Err(InputError::RangeOutOfBounds.into());
+        }
+
+        Ok(Box::new(ConfusionNetworkInput::new(
+            self.positions[offset..offset + length].to_vec(),
+        )))
+    }
+
+    fn append(&mut self, another: Box<dyn Input>) -> Result<()> {
+        let Ok(another) = another.downcast::<ConfusionNetworkInput>() else {
+            return Err(InputError::MismatchConcreteType.into());
+        };
+
+        self.positions.extend(another.positions);
+
+        Ok(())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::string_input::StringInput;
+
+    use super::*;
+
+    fn make_input() -> ConfusionNetworkInput {
+        ConfusionNetworkInput::new(vec![
+            vec![(String::from("c"), 0), (String::from("e"), 5)],
+            vec![(String::from("l"), 0), (String::from("1"), 3)],
+        ])
+    }
+
+    #[test]
+    fn concatenations() {
+        let input = make_input();
+        assert_eq!(
+            input.concatenations(usize::MAX),
+            vec![
+                (String::from("cl"), 0),
+                (String::from("c1"), 3),
+                (String::from("el"), 5),
+                (String::from("e1"), 8),
+            ]
+        );
+        assert_eq!(
+            input.concatenations(2),
+            vec![(String::from("cl"), 0), (String::from("c1"), 3)]
+        );
+        assert_eq!(
+            ConfusionNetworkInput::new(Vec::new()).concatenations(usize::MAX),
+            vec![(String::new(), 0)]
+        );
+    }
+
+    #[test]
+    fn equal_to() {
+        let input = make_input();
+        assert!(input.equal_to(&make_input()));
+        assert!(!input.equal_to(&ConfusionNetworkInput::new(Vec::new())));
+        assert!(!input.equal_to(&StringInput::new(String::from("cl"))));
+    }
+
+    #[test]
+    fn create_subrange() {
+        let input = make_input();
+
+        let subrange = input.create_subrange(1, 1).unwrap();
+        let subrange = subrange.downcast_ref::<ConfusionNetworkInput>().unwrap();
+        assert_eq!(subrange.positions(), &input.positions()[1..]);
+
+        assert!(input.create_subrange(1, 2).is_err());
+    }
+
+    #[test]
+    fn append() {
+        let mut input = ConfusionNetworkInput::new(Vec::new());
+        input.append(Box::new(make_input())).unwrap();
+        assert_eq!(input, make_input());
+
+        assert!(input
+            .append(Box::new(StringInput::new(String::from("cl"))))
+            .is_err());
+    }
+}
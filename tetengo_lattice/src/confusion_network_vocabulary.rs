@@ -0,0 +1,222 @@
+/*!
+ * A confusion network vocabulary.
+ *
+ * Copyright (C) 2023-2025 kaoru  <https://www.tetengo.org/>
+ */
+
+use std::rc::Rc;
+
+use crate::confusion_network_input::ConfusionNetworkInput;
+use crate::connection::Connection;
+use crate::entry::Entry;
+use crate::error::Result;
+use crate::input::Input;
+use crate::node::Node;
+use crate::string_input::StringInput;
+use crate::vocabulary::Vocabulary;
+
+/**
+ * A confusion network vocabulary.
+ *
+ * Looks up a vocabulary of string inputs with each concatenation of the alternatives of a
+ * confusion network input, and folds the cost of the concatenation into the costs of the found
+ * entries.
+ */
+#[derive(Debug)]
+pub struct ConfusionNetworkVocabulary<'a> {
+    vocabulary: &'a dyn Vocabulary,
+    max_concatenation_count: usize,
+}
+
+impl<'a> ConfusionNetworkVocabulary<'a> {
+    /**
+     * Creates a confusion network vocabulary.
+     *
+     * # Arguments
+     * * `vocabulary`              - A vocabulary of string inputs.
+     * * `max_concatenation_count` - A maximum count of the concatenations looked up per key.
+     */
+    pub const fn new(vocabulary: &'a dyn Vocabulary, max_concatenation_count: usize) -> Self {
+        Self {
+            vocabulary,
+            max_concatenation_count,
+        }
+    }
+}
+
+impl Vocabulary for ConfusionNetworkVocabulary<'_> {
+    fn find_entries(&self, key: &dyn Input) -> Result<Vec<Rc<Entry>>> {
+        let Some(key) = key.downcast_ref::<ConfusionNetworkInput>() else {
+            return self.vocabulary.find_entries(key);
+        };
+
+        let mut entries = Vec::new();
+        for (concatenation, cost) in key.concatenations(self.max_concatenation_count) {
+            let found = self
+                .vocabulary
+                .find_entries(&StringInput::new(concatenation))?;
+            entries.extend(
+                found
+                    .into_iter()
+                    .map(|entry| Rc::new(entry.with_additional_cost(cost))),
+            );
+        }
+        Ok(entries)
+    }
+
+    fn prefetch(&self, keys: &[&dyn Input]) -> Result<()> {
+        let mut concatenations = Vec::new();
+        let mut other_keys = Vec::new();
+        for &key in keys {
+            match key.downcast_ref::<ConfusionNetworkInput>() {
+                Some(key) => concatenations.extend(
+                    key.concatenations(self.max_concatenation_count)
+                        .into_iter()
+                        .map(|(concatenation, _)| StringInput::new(concatenation)),
+                ),
+                None => other_keys.push(key),
+            }
+        }
+        other_keys.extend(concatenations.iter().map(|key| {
+            let key: &dyn Input = key;
+            key
+        }));
+        self.vocabulary.prefetch(&other_keys)
+    }
+
+    fn generation(&self) -> u64 {
+        self.vocabulary.generation()
+    }
+
+    fn find_connection(&self, from: &Node, to: &Entry) -> Result<Connection> {
+        self.vocabulary.find_connection(from, to)
+    }
+
+    fn find_trigram_connection(
+        &self,
+        preceding: Option<&Node>,
+        from: &Node,
+        to: &Entry,
+    ) -> Result<Connection> {
+        self.vocabulary.find_trigram_connection(preceding, from, to)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::hash::{DefaultHasher, Hash, Hasher};
+
+    use crate::constraint::Constraint;
+    use crate::hash_map_vocabulary::HashMapVocabulary;
+    use crate::lattice::Lattice;
+    use crate::n_best_iterator::NBestIterator;
+
+    use super::*;
+
+    fn to_input(string: &str) -> Box<dyn Input> {
+        Box::new(StringInput::new(string.to_string()))
+    }
+
+    fn value_of(entry: &Entry) -> &str {
+        entry
+            .value()
+            .and_then(|v| v.downcast_ref::<&str>())
+            .unwrap_or(&"")
+    }
+
+    fn entry_hash(entry: &Entry) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        value_of(entry).hash(&mut hasher);
+        hasher.finish()
+    }
+
+    fn entry_equal_to(one: &Entry, other: &Entry) -> bool {
+        value_of(one) == value_of(other)
+    }
+
+    fn create_vocabulary() -> HashMapVocabulary<'static> {
+        let cl = Entry::new(to_input("cl"), Box::new("CL"), 10);
+        let el = Entry::new(to_input("el"), Box::new("EL"), 1);
+        let c = Entry::new(to_input("c"), Box::new("C"), 7);
+        let l = Entry::new(to_input("l"), Box::new("L"), 7);
+        HashMapVocabulary::new(
+            vec![
+                (String::from("cl"), vec![cl.clone()]),
+                (String::from("el"), vec![el.clone()]),
+                (String::from("c"), vec![c.clone()]),
+                (String::from("l"), vec![l.clone()]),
+            ],
+            vec![
+                ((Entry::BosEos, cl.clone()), 0),
+                ((Entry::BosEos, el.clone()), 0),
+                ((Entry::BosEos, c.clone()), 0),
+                ((c, l.clone()), 0),
+                ((cl, Entry::BosEos), 0),
+                ((el, Entry::BosEos), 0),
+                ((l, Entry::BosEos), 0),
+            ],
+            &entry_hash,
+            &entry_equal_to,
+        )
+    }
+
+    fn create_input() -> ConfusionNetworkInput {
+        ConfusionNetworkInput::new(vec![
+            vec![(String::from("c"), 0), (String::from("e"), 8)],
+            vec![(String::from("l"), 0), (String::from("1"), 3)],
+        ])
+    }
+
+    #[test]
+    fn find_entries() {
+        let vocabulary = create_vocabulary();
+        {
+            let vocabulary = ConfusionNetworkVocabulary::new(&vocabulary, usize::MAX);
+            let entries = vocabulary.find_entries(&create_input()).unwrap();
+            let values_and_costs = entries
+                .iter()
+                .map(|entry| (value_of(entry), entry.cost()))
+                .collect::<Vec<_>>();
+            assert_eq!(values_and_costs, vec![("CL", 10), ("EL", 9)]);
+        }
+        {
+            let vocabulary = ConfusionNetworkVocabulary::new(&vocabulary, 2);
+            let entries = vocabulary.find_entries(&create_input()).unwrap();
+            assert_eq!(entries.len(), 1);
+        }
+        {
+            let vocabulary = ConfusionNetworkVocabulary::new(&vocabulary, usize::MAX);
+            let entries = vocabulary.find_entries(to_input("cl").as_ref()).unwrap();
+            assert_eq!(entries.len(), 1);
+        }
+    }
+
+    #[test]
+    fn lattice() {
+        let vocabulary = create_vocabulary();
+        let vocabulary = ConfusionNetworkVocabulary::new(&vocabulary, usize::MAX);
+        let mut lattice = Lattice::new(&vocabulary);
+        for position in create_input().positions() {
+            lattice
+                .push_back(Box::new(ConfusionNetworkInput::new(vec![position.clone()])))
+                .unwrap();
+        }
+        let eos_node = lattice.settle().unwrap();
+
+        let paths = NBestIterator::new(&lattice, eos_node, Box::new(Constraint::new()))
+            .map(|path| {
+                let values = path
+                    .nodes()
+                    .iter()
+                    .filter_map(|node| node.value().and_then(|v| v.downcast_ref::<&str>()))
+                    .copied()
+                    .collect::<Vec<_>>();
+                (values, path.cost())
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            paths,
+            vec![(vec!["EL"], 9), (vec!["CL"], 10), (vec!["C", "L"], 14),]
+        );
+    }
+}
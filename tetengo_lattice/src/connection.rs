@@ -6,13 +6,22 @@
 
 /**
  * A connection.
+ *
+ * The default connection has the cost 0.
  */
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
 pub struct Connection {
     cost: i32,
 }
 
 impl Connection {
+    /**
+     * The infinite cost.
+     *
+     * A connection of the infinite cost means that there is no edge.
+     */
+    pub const INFINITE_COST: i32 = i32::MAX;
+
     /**
      * Creates a connection.
      *
@@ -23,6 +32,15 @@ impl Connection {
         Self { cost }
     }
 
+    /**
+     * Creates a connection of the infinite cost.
+     *
+     * It means that there is no edge.
+     */
+    pub const fn infinity() -> Self {
+        Self::new(Self::INFINITE_COST)
+    }
+
     /**
      * Returns the cost.
      *
@@ -32,6 +50,35 @@ impl Connection {
     pub const fn cost(&self) -> i32 {
         self.cost
     }
+
+    /**
+     * Returns true when the cost is infinite.
+     *
+     * # Returns
+     * True when the cost is infinite.
+     */
+    pub const fn is_infinite(&self) -> bool {
+        self.cost == Self::INFINITE_COST
+    }
+
+    /**
+     * Adds costs.
+     *
+     * # Arguments
+     * * `one`     - A cost.
+     * * `another` - Another cost.
+     *
+     * # Returns
+     * The sum of the costs. The infinite cost when either is infinite. The sum saturates at the
+     * bounds of `i32`, where the upper bound is the infinite cost.
+     */
+    pub const fn add_cost(one: i32, another: i32) -> i32 {
+        if one == Self::INFINITE_COST || another == Self::INFINITE_COST {
+            Self::INFINITE_COST
+        } else {
+            one.saturating_add(another)
+        }
+    }
 }
 
 #[cfg(test)]
@@ -43,10 +90,45 @@ mod tests {
         let _ = Connection::new(42);
     }
 
+    #[test]
+    fn default() {
+        assert_eq!(Connection::default().cost(), 0);
+    }
+
+    #[test]
+    fn infinity() {
+        assert_eq!(Connection::infinity().cost(), Connection::INFINITE_COST);
+    }
+
     #[test]
     fn cost() {
         let connection_ = Connection::new(42);
 
         assert_eq!(connection_.cost(), 42);
     }
+
+    #[test]
+    fn is_infinite() {
+        assert!(!Connection::new(42).is_infinite());
+        assert!(Connection::infinity().is_infinite());
+    }
+
+    #[test]
+    fn add_cost() {
+        assert_eq!(Connection::add_cost(42, 24), 66);
+        assert_eq!(Connection::add_cost(42, -24), 18);
+        assert_eq!(
+            Connection::add_cost(Connection::INFINITE_COST, -24),
+            Connection::INFINITE_COST
+        );
+        assert_eq!(
+            Connection::add_cost(42, Connection::INFINITE_COST),
+            Connection::INFINITE_COST
+        );
+        assert_eq!(
+            Connection::add_cost(i32::MAX - 1, 2),
+            Connection::INFINITE_COST
+        );
+        assert_eq!(Connection::add_cost(i32::MIN, -1), i32::MIN);
+    }
 }
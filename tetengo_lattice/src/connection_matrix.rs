@@ -0,0 +1,539 @@
+/*!
+ * A connection matrix.
+ *
+ * Copyright (C) 2023-2025 kaoru  <https://www.tetengo.org/>
+ */
+
+use std::io::{self, BufRead, Read, Write};
+
+use crate::connection::Connection;
+use crate::entry::Entry;
+use crate::error::Result;
+
+/**
+ * A connection matrix error.
+ */
+#[derive(Debug, thiserror::Error)]
+pub enum ConnectionMatrixError {
+    /**
+     * The context ID is out of the bounds.
+     */
+    #[error("the context ID is out of the bounds")]
+    ContextIdOutOfBounds,
+
+    /**
+     * The matrix is too large.
+     */
+    #[error("the matrix is too large")]
+    TooLarge,
+
+    /**
+     * The serialized content is invalid.
+     */
+    #[error("the serialized content is invalid")]
+    InvalidSerializedContent,
+
+    /**
+     * An I/O error.
+     */
+    #[error("I/O error")]
+    Io(#[from] io::Error),
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum Costs {
+    Narrow(Vec<i16>),
+    Wide(Vec<i32>),
+}
+
+/**
+ * A connection matrix.
+ *
+ * It is a dense matrix of the connection costs indexed by a pair of context IDs, which is the
+ * standard model of the dictionaries for morphological analysis. The left context ID is the
+ * right context ID of a preceding entry, and the right context ID is the left context ID of a
+ * following entry.
+ *
+ * The costs are stored in 16 bits each while they fit, and in 32 bits each otherwise.
+ */
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ConnectionMatrix {
+    left_size: usize,
+    right_size: usize,
+    costs: Costs,
+}
+
+impl ConnectionMatrix {
+    /**
+     * Creates a connection matrix filled with zero.
+     *
+     * # Arguments
+     * * `left_size`  - The number of the left context IDs.
+     * * `right_size` - The number of the right context IDs.
+     *
+     * # Errors
+     * * When the matrix is too large.
+     */
+    pub fn new(left_size: usize, right_size: usize) -> Result<Self> {
+        let Some(size) = left_size.checked_mul(right_size) else {
+            return Err(ConnectionMatrixError::TooLarge.into());
+        };
+        Ok(Self {
+            left_size,
+            right_size,
+            costs: Costs::Narrow(vec![0; size]),
+        })
+    }
+
+    /**
+     * Reads a connection matrix written by `ConnectionMatrix::write()`.
+     *
+     * # Arguments
+     * * `reader` - A reader.
+     *
+     * # Errors
+     * * When it fails to read.
+     * * When the content is broken.
+     */
+    pub fn read(reader: &mut dyn Read) -> Result<Self> {
+        let mut header = [0u8; Self::HEADER_SIZE];
+        reader
+            .read_exact(&mut header)
+            .map_err(ConnectionMatrixError::from)?;
+        let left_size = read_u32(&header[0..4]) as usize;
+        let right_size = read_u32(&header[4..8]) as usize;
+        let width = read_u32(&header[8..12]) as usize;
+        let Some(body_size) = left_size
+            .checked_mul(right_size)
+            .and_then(|size| size.checked_mul(width))
+        else {
+            return Err(ConnectionMatrixError::TooLarge.into());
+        };
+
+        let mut body = Vec::new();
+        let _length = reader
+            .take(body_size as u64)
+            .read_to_end(&mut body)
+            .map_err(ConnectionMatrixError::from)?;
+        if body.len() != body_size {
+            return Err(ConnectionMatrixError::InvalidSerializedContent.into());
+        }
+        let costs = match width {
+            2 => Costs::Narrow(
+                body.chunks_exact(2)
+                    .map(|chunk| i16::from_be_bytes([chunk[0], chunk[1]]))
+                    .collect(),
+            ),
+            4 => Costs::Wide(body.chunks_exact(4).map(read_i32).collect()),
+            _ => return Err(ConnectionMatrixError::InvalidSerializedContent.into()),
+        };
+        Ok(Self {
+            left_size,
+            right_size,
+            costs,
+        })
+    }
+
+    /**
+     * Creates a connection matrix from bytes written by `ConnectionMatrix::write()`.
+     *
+     * It loads a prebuilt matrix embedded in a binary or fetched by a browser, where no file is
+     * available.
+     *
+     * # Arguments
+     * * `bytes` - Bytes.
+     *
+     * # Errors
+     * * When the bytes are broken or have extra bytes after the matrix.
+     */
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let mut reader = bytes;
+        let matrix = Self::read(&mut reader)?;
+        if !reader.is_empty() {
+            return Err(ConnectionMatrixError::InvalidSerializedContent.into());
+        }
+        Ok(matrix)
+    }
+
+    /**
+     * Reads a connection matrix in the text format of MeCab.
+     *
+     * The first line has the numbers of the left and right context IDs, and each of the
+     * following lines has a left context ID, a right context ID and a cost, separated by spaces.
+     * The costs not listed are zero.
+     *
+     * # Arguments
+     * * `reader` - A reader.
+     *
+     * # Errors
+     * * When it fails to read.
+     * * When the content is broken.
+     */
+    pub fn read_text(reader: &mut dyn BufRead) -> Result<Self> {
+        let mut lines = reader.lines();
+        let Some(first_line) = lines.next() else {
+            return Err(ConnectionMatrixError::InvalidSerializedContent.into());
+        };
+        let [left_size, right_size] =
+            parse_numbers::<usize, 2>(&first_line.map_err(ConnectionMatrixError::from)?)?;
+        let mut matrix = Self::new(left_size, right_size)?;
+        for line in lines {
+            let line = line.map_err(ConnectionMatrixError::from)?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let [left_id, right_id, cost] = parse_numbers::<i64, 3>(&line)?;
+            let (Ok(left_id), Ok(right_id), Ok(cost)) = (
+                usize::try_from(left_id),
+                usize::try_from(right_id),
+                i32::try_from(cost),
+            ) else {
+                return Err(ConnectionMatrixError::InvalidSerializedContent.into());
+            };
+            matrix.set_cost(left_id, right_id, cost)?;
+        }
+        Ok(matrix)
+    }
+
+    /**
+     * Returns the number of the left context IDs.
+     *
+     * # Returns
+     * The number of the left context IDs.
+     */
+    pub const fn left_size(&self) -> usize {
+        self.left_size
+    }
+
+    /**
+     * Returns the number of the right context IDs.
+     *
+     * # Returns
+     * The number of the right context IDs.
+     */
+    pub const fn right_size(&self) -> usize {
+        self.right_size
+    }
+
+    /**
+     * Returns the cost.
+     *
+     * # Arguments
+     * * `left_id`  - A left context ID.
+     * * `right_id` - A right context ID.
+     *
+     * # Returns
+     * The cost.
+     *
+     * # Errors
+     * * When the context IDs are out of the bounds.
+     */
+    pub fn cost(&self, left_id: usize, right_id: usize) -> Result<i32> {
+        let index = self.index_of(left_id, right_id)?;
+        Ok(match &self.costs {
+            Costs::Narrow(costs) => costs[index] as i32,
+            Costs::Wide(costs) => costs[index],
+        })
+    }
+
+    /**
+     * Returns the connection.
+     *
+     * # Arguments
+     * * `left_id`  - A left context ID.
+     * * `right_id` - A right context ID.
+     *
+     * # Returns
+     * The connection.
+     *
+     * # Errors
+     * * When the context IDs are out of the bounds.
+     */
+    pub fn connection(&self, left_id: usize, right_id: usize) -> Result<Connection> {
+        Ok(Connection::new(self.cost(left_id, right_id)?))
+    }
+
+    /**
+     * Returns the connection between entries.
+     *
+     * The right context ID of the preceding entry and the left context ID of the following entry
+     * are looked up.
+     *
+     * # Arguments
+     * * `from` - A preceding entry.
+     * * `to`   - A following entry.
+     *
+     * # Returns
+     * The connection. Or `None` when either of the entries has no context IDs.
+     *
+     * # Errors
+     * * When the context IDs are out of the bounds.
+     */
+    pub fn connection_between(&self, from: &Entry, to: &Entry) -> Result<Option<Connection>> {
+        let (Some(left_id), Some(right_id)) = (from.right_id(), to.left_id()) else {
+            return Ok(None);
+        };
+        Ok(Some(self.connection(left_id, right_id)?))
+    }
+
+    /**
+     * Sets a cost.
+     *
+     * The costs are widened to 32 bits each when the cost does not fit in 16 bits.
+     *
+     * # Arguments
+     * * `left_id`  - A left context ID.
+     * * `right_id` - A right context ID.
+     * * `cost`     - A cost.
+     *
+     * # Errors
+     * * When the context IDs are out of the bounds.
+     */
+    pub fn set_cost(&mut self, left_id: usize, right_id: usize, cost: i32) -> Result<()> {
+        let index = self.index_of(left_id, right_id)?;
+        if let Costs::Narrow(costs) = &mut self.costs {
+            if let Ok(cost) = i16::try_from(cost) {
+                costs[index] = cost;
+                return Ok(());
+            }
+            self.costs = Costs::Wide(costs.iter().map(|&cost| cost as i32).collect());
+        }
+        if let Costs::Wide(costs) = &mut self.costs {
+            costs[index] = cost;
+        }
+        Ok(())
+    }
+
+    /**
+     * Writes this matrix.
+     *
+     * # Arguments
+     * * `writer` - A writer.
+     *
+     * # Errors
+     * * When it fails to write.
+     * * When the matrix is too large to be written.
+     */
+    pub fn write(&self, writer: &mut dyn Write) -> Result<()> {
+        let (Ok(left_size), Ok(right_size)) = (
+            u32::try_from(self.left_size),
+            u32::try_from(self.right_size),
+        ) else {
+            return Err(ConnectionMatrixError::TooLarge.into());
+        };
+        let mut bytes = Vec::with_capacity(Self::HEADER_SIZE);
+        bytes.extend_from_slice(&left_size.to_be_bytes());
+        bytes.extend_from_slice(&right_size.to_be_bytes());
+        match &self.costs {
+            Costs::Narrow(costs) => {
+                bytes.extend_from_slice(&(size_of::<i16>() as u32).to_be_bytes());
+                costs
+                    .iter()
+                    .for_each(|cost| bytes.extend_from_slice(&cost.to_be_bytes()));
+            }
+            Costs::Wide(costs) => {
+                bytes.extend_from_slice(&(size_of::<i32>() as u32).to_be_bytes());
+                costs
+                    .iter()
+                    .for_each(|cost| bytes.extend_from_slice(&cost.to_be_bytes()));
+            }
+        }
+        writer
+            .write_all(&bytes)
+            .map_err(ConnectionMatrixError::from)?;
+        Ok(())
+    }
+
+    const HEADER_SIZE: usize = size_of::<u32>() * 3;
+
+    fn index_of(&self, left_id: usize, right_id: usize) -> Result<usize> {
+        if left_id >= self.left_size || right_id >= self.right_size {
+            return Err(ConnectionMatrixError::ContextIdOutOfBounds.into());
+        }
+        Ok(left_id * self.right_size + right_id)
+    }
+}
+
+fn read_u32(bytes: &[u8]) -> u32 {
+    u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+fn read_i32(bytes: &[u8]) -> i32 {
+    i32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+fn parse_numbers<T: std::str::FromStr, const N: usize>(line: &str) -> Result<[T; N]> {
+    let numbers = line
+        .split_whitespace()
+        .map(str::parse::<T>)
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .map_err(|_| ConnectionMatrixError::InvalidSerializedContent)?;
+    numbers
+        .try_into()
+        .map_err(|_| ConnectionMatrixError::InvalidSerializedContent.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::LatticeError;
+
+    use super::*;
+
+    #[test]
+    fn new() {
+        let matrix = ConnectionMatrix::new(2, 3).unwrap();
+
+        assert_eq!(matrix.left_size(), 2);
+        assert_eq!(matrix.right_size(), 3);
+        assert_eq!(matrix.cost(1, 2).unwrap(), 0);
+
+        assert!(matches!(
+            ConnectionMatrix::new(usize::MAX, 2),
+            Err(LatticeError::ConnectionMatrix(
+                ConnectionMatrixError::TooLarge
+            ))
+        ));
+    }
+
+    #[test]
+    fn cost() {
+        let mut matrix = ConnectionMatrix::new(2, 3).unwrap();
+
+        matrix.set_cost(0, 1, 42).unwrap();
+        matrix.set_cost(1, 2, -24).unwrap();
+
+        assert_eq!(matrix.cost(0, 1).unwrap(), 42);
+        assert_eq!(matrix.cost(1, 2).unwrap(), -24);
+        assert_eq!(matrix.connection(0, 1).unwrap().cost(), 42);
+        assert!(matches!(matrix.costs, Costs::Narrow(_)));
+
+        matrix.set_cost(1, 0, i32::MAX).unwrap();
+
+        assert_eq!(matrix.cost(1, 0).unwrap(), i32::MAX);
+        assert_eq!(matrix.cost(0, 1).unwrap(), 42);
+        assert!(matches!(matrix.costs, Costs::Wide(_)));
+
+        assert!(matrix.cost(2, 0).is_err());
+        assert!(matrix.cost(0, 3).is_err());
+        assert!(matrix.set_cost(2, 0, 42).is_err());
+    }
+
+    #[test]
+    fn connection_between() {
+        use crate::string_input::StringInput;
+
+        let mut matrix = ConnectionMatrix::new(3, 3).unwrap();
+        matrix.set_cost(0, 1, 10).unwrap();
+        matrix.set_cost(2, 0, 20).unwrap();
+        let entry = Entry::new(
+            Box::new(StringInput::new(String::from("みずほ"))),
+            Box::new(String::from("瑞穂")),
+            42,
+        );
+        let entry_with_ids = entry.with_context_ids(1, 2);
+
+        assert_eq!(
+            matrix
+                .connection_between(&Entry::BosEos, &entry_with_ids)
+                .unwrap()
+                .unwrap()
+                .cost(),
+            10
+        );
+        assert_eq!(
+            matrix
+                .connection_between(&entry_with_ids, &Entry::BosEos)
+                .unwrap()
+                .unwrap()
+                .cost(),
+            20
+        );
+        assert!(matrix
+            .connection_between(&Entry::BosEos, &entry)
+            .unwrap()
+            .is_none());
+        assert!(matrix
+            .connection_between(&entry.with_context_ids(3, 3), &Entry::BosEos)
+            .is_err());
+    }
+
+    #[test]
+    fn write() {
+        {
+            let mut matrix = ConnectionMatrix::new(1, 2).unwrap();
+            matrix.set_cost(0, 1, -2).unwrap();
+
+            let mut written = Vec::new();
+            matrix.write(&mut written).unwrap();
+
+            assert_eq!(
+                written,
+                vec![0, 0, 0, 1, 0, 0, 0, 2, 0, 0, 0, 2, 0, 0, 0xFF, 0xFE]
+            );
+            assert_eq!(ConnectionMatrix::read(&mut &written[..]).unwrap(), matrix);
+        }
+        {
+            let mut matrix = ConnectionMatrix::new(2, 2).unwrap();
+            matrix.set_cost(1, 1, 100000).unwrap();
+
+            let mut written = Vec::new();
+            matrix.write(&mut written).unwrap();
+
+            assert_eq!(written.len(), 12 + 4 * 4);
+            assert_eq!(ConnectionMatrix::read(&mut &written[..]).unwrap(), matrix);
+        }
+    }
+
+    #[test]
+    fn read() {
+        assert!(ConnectionMatrix::read(&mut &[0u8, 0, 0, 1][..]).is_err());
+        assert!(
+            ConnectionMatrix::read(&mut &[0u8, 0, 0, 1, 0, 0, 0, 1, 0, 0, 0, 2, 0][..]).is_err()
+        );
+        assert!(
+            ConnectionMatrix::read(&mut &[0u8, 0, 0, 1, 0, 0, 0, 1, 0, 0, 0, 3, 0, 0, 0][..])
+                .is_err()
+        );
+        assert!(ConnectionMatrix::read(
+            &mut &[0xFFu8, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0, 0, 0, 4][..]
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn from_bytes() {
+        let mut matrix = ConnectionMatrix::new(2, 3).unwrap();
+        matrix.set_cost(1, 2, 42).unwrap();
+        let mut bytes = Vec::new();
+        matrix.write(&mut bytes).unwrap();
+
+        assert_eq!(ConnectionMatrix::from_bytes(&bytes).unwrap(), matrix);
+
+        bytes.push(0);
+        assert!(ConnectionMatrix::from_bytes(&bytes).is_err());
+        assert!(ConnectionMatrix::from_bytes(&bytes[..bytes.len() - 2]).is_err());
+    }
+
+    #[test]
+    fn read_text() {
+        {
+            let text = "2 3\n0 0 -5\n1 2 42\n\n0 2 100000\n";
+
+            let matrix = ConnectionMatrix::read_text(&mut text.as_bytes()).unwrap();
+
+            assert_eq!(matrix.left_size(), 2);
+            assert_eq!(matrix.right_size(), 3);
+            assert_eq!(matrix.cost(0, 0).unwrap(), -5);
+            assert_eq!(matrix.cost(1, 2).unwrap(), 42);
+            assert_eq!(matrix.cost(0, 2).unwrap(), 100000);
+            assert_eq!(matrix.cost(1, 1).unwrap(), 0);
+        }
+        {
+            assert!(ConnectionMatrix::read_text(&mut "".as_bytes()).is_err());
+            assert!(ConnectionMatrix::read_text(&mut "2\n".as_bytes()).is_err());
+            assert!(ConnectionMatrix::read_text(&mut "2 3\n0 0\n".as_bytes()).is_err());
+            assert!(ConnectionMatrix::read_text(&mut "2 3\n2 0 1\n".as_bytes()).is_err());
+            assert!(ConnectionMatrix::read_text(&mut "2 3\n-1 0 1\n".as_bytes()).is_err());
+        }
+    }
+}
@@ -7,12 +7,28 @@
 use crate::constraint_element::ConstraintElement;
 use crate::node::Node;
 
+#[derive(Debug)]
+enum Kind<'a> {
+    Pattern(Vec<Box<dyn ConstraintElement + 'a>>),
+    AllOf(Vec<Constraint<'a>>),
+    AnyOf(Vec<Constraint<'a>>),
+    Negation(Box<Constraint<'a>>),
+}
+
 /**
  * A constraint.
+ *
+ * A constraint is either a pattern or a composition of other constraints.
  */
-#[derive(Debug, Default)]
+#[derive(Debug)]
 pub struct Constraint<'a> {
-    pattern: Vec<Box<dyn ConstraintElement + 'a>>,
+    kind: Kind<'a>,
+}
+
+impl Default for Constraint<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl<'a> Constraint<'a> {
@@ -23,7 +39,7 @@ impl<'a> Constraint<'a> {
      */
     pub const fn new() -> Self {
         Self {
-            pattern: Vec::new(),
+            kind: Kind::Pattern(Vec::new()),
         }
     }
 
@@ -34,7 +50,52 @@ impl<'a> Constraint<'a> {
      * * `pattern` - A pattern.
      */
     pub const fn new_with_pattern(pattern: Vec<Box<dyn ConstraintElement + 'a>>) -> Self {
-        Self { pattern }
+        Self {
+            kind: Kind::Pattern(pattern),
+        }
+    }
+
+    /**
+     * Creates a constraint matching a path which all the constraints match.
+     *
+     * It matches any path when the constraints are empty.
+     *
+     * # Arguments
+     * * `constraints` - Constraints.
+     */
+    pub const fn all_of(constraints: Vec<Constraint<'a>>) -> Self {
+        Self {
+            kind: Kind::AllOf(constraints),
+        }
+    }
+
+    /**
+     * Creates a constraint matching a path which any of the constraints matches.
+     *
+     * It matches no path when the constraints are empty.
+     *
+     * # Arguments
+     * * `constraints` - Constraints.
+     */
+    pub const fn any_of(constraints: Vec<Constraint<'a>>) -> Self {
+        Self {
+            kind: Kind::AnyOf(constraints),
+        }
+    }
+
+    /**
+     * Creates a constraint matching a path which the constraint does not match.
+     *
+     * A negation cannot reject a path until the path is complete, so the N-best search with it
+     * may visit more paths than with a pattern.
+     *
+     * # Arguments
+     * * `constraint` - A constraint.
+     */
+    pub fn negation(constraint: Constraint<'a>) -> Self {
+        Self {
+            kind: Kind::Negation(Box::new(constraint)),
+        }
     }
 
     /**
@@ -47,7 +108,16 @@ impl<'a> Constraint<'a> {
      * `true` if the path matches the pattern.
      */
     pub fn matches(&self, reverse_path: &[Node]) -> bool {
-        self.matches_impl(reverse_path) == 0
+        match &self.kind {
+            Kind::Pattern(pattern) => Self::matches_pattern(pattern, reverse_path) == 0,
+            Kind::AllOf(constraints) => constraints
+                .iter()
+                .all(|constraint| constraint.matches(reverse_path)),
+            Kind::AnyOf(constraints) => constraints
+                .iter()
+                .any(|constraint| constraint.matches(reverse_path)),
+            Kind::Negation(constraint) => !constraint.matches(reverse_path),
+        }
     }
 
     /**
@@ -60,21 +130,38 @@ impl<'a> Constraint<'a> {
      * `true` if the tail path matches the tail of the pattern.
      */
     pub fn matches_tail(&self, reverse_tail_path: &[Node]) -> bool {
-        self.matches_impl(reverse_tail_path) != usize::MAX
+        match &self.kind {
+            Kind::Pattern(pattern) => {
+                Self::matches_pattern(pattern, reverse_tail_path) != usize::MAX
+            }
+            _ if reverse_tail_path.last().is_some_and(Node::is_bos) => {
+                self.matches(reverse_tail_path)
+            }
+            Kind::AllOf(constraints) => constraints
+                .iter()
+                .all(|constraint| constraint.matches_tail(reverse_tail_path)),
+            Kind::AnyOf(constraints) => constraints
+                .iter()
+                .any(|constraint| constraint.matches_tail(reverse_tail_path)),
+            Kind::Negation(_) => true,
+        }
     }
 
-    fn matches_impl(&self, reverse_path: &[Node]) -> usize {
-        if self.pattern.is_empty() {
+    fn matches_pattern(
+        pattern: &[Box<dyn ConstraintElement + 'a>],
+        reverse_path: &[Node],
+    ) -> usize {
+        if pattern.is_empty() {
             return 0;
         }
 
-        let mut pattern_index = self.pattern.len();
+        let mut pattern_index = pattern.len();
         for node in reverse_path {
             if pattern_index == 0 {
                 break;
             }
 
-            let element_match = self.pattern[pattern_index - 1].matches(node);
+            let element_match = pattern[pattern_index - 1].matches(node);
             match element_match {
                 m if m < 0 => return usize::MAX,
                 0 => {
@@ -426,6 +513,83 @@ mod tests {
         }
     }
 
+    #[test]
+    fn matches_with_composition() {
+        {
+            let constraint = Constraint::all_of(vec![
+                Constraint::new_with_pattern(make_pattern_b_w_t_e()),
+                Constraint::new_with_pattern(make_pattern_b_m_w_t_e()),
+            ]);
+
+            assert!(!constraint.matches(&reverse_path(make_path_b_e())));
+            assert!(constraint.matches(&reverse_path(make_path_b_m_s_t_e())));
+            assert!(constraint.matches(&reverse_path(make_path_b_m_a_t_e())));
+            assert!(!constraint.matches(&reverse_path(make_path_b_h_t_e())));
+            assert!(!constraint.matches(&reverse_path(make_path_b_k_s_k_e())));
+        }
+        {
+            let constraint = Constraint::any_of(vec![
+                Constraint::new_with_pattern(make_pattern_b_e()),
+                Constraint::new_with_pattern(make_pattern_b_w_s_w_e()),
+            ]);
+
+            assert!(constraint.matches(&reverse_path(make_path_b_e())));
+            assert!(constraint.matches(&reverse_path(make_path_b_m_s_t_e())));
+            assert!(!constraint.matches(&reverse_path(make_path_b_m_a_t_e())));
+            assert!(!constraint.matches(&reverse_path(make_path_b_h_t_e())));
+            assert!(constraint.matches(&reverse_path(make_path_b_k_s_k_e())));
+        }
+        {
+            let constraint =
+                Constraint::negation(Constraint::new_with_pattern(make_pattern_b_w_s_w_e()));
+
+            assert!(constraint.matches(&reverse_path(make_path_b_e())));
+            assert!(!constraint.matches(&reverse_path(make_path_b_m_s_t_e())));
+            assert!(constraint.matches(&reverse_path(make_path_b_m_a_t_e())));
+            assert!(constraint.matches(&reverse_path(make_path_b_h_t_e())));
+            assert!(!constraint.matches(&reverse_path(make_path_b_k_s_k_e())));
+        }
+        {
+            assert!(Constraint::all_of(Vec::new()).matches(&reverse_path(make_path_b_e())));
+            assert!(!Constraint::any_of(Vec::new()).matches(&reverse_path(make_path_b_e())));
+        }
+    }
+
+    #[test]
+    fn matches_tail_with_composition() {
+        {
+            let constraint = Constraint::all_of(vec![
+                Constraint::new_with_pattern(make_pattern_b_w_e()),
+                Constraint::new_with_pattern(make_pattern_b_w_s_w_e()),
+            ]);
+
+            assert!(!constraint.matches_tail(&reverse_path(make_tail(make_path_b_e(), 1))));
+            assert!(constraint.matches_tail(&reverse_path(make_tail(make_path_b_m_s_t_e(), 2))));
+            assert!(constraint.matches_tail(&reverse_path(make_tail(make_path_b_m_s_t_e(), 5))));
+            assert!(constraint.matches_tail(&reverse_path(make_tail(make_path_b_m_a_t_e(), 2))));
+            assert!(!constraint.matches_tail(&reverse_path(make_tail(make_path_b_m_a_t_e(), 5))));
+        }
+        {
+            let constraint = Constraint::any_of(vec![
+                Constraint::new_with_pattern(make_pattern_b_e()),
+                Constraint::new_with_pattern(make_pattern_b_w_s_w_e()),
+            ]);
+
+            assert!(constraint.matches_tail(&reverse_path(make_tail(make_path_b_e(), 1))));
+            assert!(constraint.matches_tail(&reverse_path(make_tail(make_path_b_e(), 2))));
+            assert!(constraint.matches_tail(&reverse_path(make_tail(make_path_b_m_a_t_e(), 2))));
+            assert!(!constraint.matches_tail(&reverse_path(make_tail(make_path_b_m_a_t_e(), 5))));
+        }
+        {
+            let constraint =
+                Constraint::negation(Constraint::new_with_pattern(make_pattern_b_w_s_w_e()));
+
+            assert!(constraint.matches_tail(&reverse_path(make_tail(make_path_b_m_s_t_e(), 4))));
+            assert!(!constraint.matches_tail(&reverse_path(make_tail(make_path_b_m_s_t_e(), 5))));
+            assert!(constraint.matches_tail(&reverse_path(make_tail(make_path_b_m_a_t_e(), 5))));
+        }
+    }
+
     #[test]
     fn matches_tail() {
         {
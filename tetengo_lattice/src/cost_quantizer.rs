@@ -0,0 +1,297 @@
+/*!
+ * A cost quantizer.
+ *
+ * Copyright (C) 2023-2025 kaoru  <https://www.tetengo.org/>
+ */
+
+use crate::connection::Connection;
+use crate::error::Result;
+
+/**
+ * A cost quantizer error.
+ */
+#[derive(Clone, Copy, Debug, thiserror::Error)]
+pub enum CostQuantizerError {
+    /**
+     * The scale is not a positive finite number.
+     */
+    #[error("the scale is not a positive finite number")]
+    InvalidScale,
+
+    /**
+     * The minimum cost is greater than the maximum cost, or the maximum cost is infinite.
+     */
+    #[error("the cost range is invalid")]
+    InvalidCostRange,
+}
+
+/**
+ * A cost quantizer.
+ *
+ * It converts the floating-point scores, such as the negative log probabilities of a language
+ * model, into the integer costs of the entries and the connections with one scale, so that the
+ * node costs and the connection costs are comparable.
+ *
+ * A score is multiplied by the scale, rounded to the nearest integer, with the halves rounded
+ * away from zero, and clamped into the cost range. So, for a score whose scaled value is within
+ * the cost range, the score restored by `dequantize()` differs from it by at most `precision()`,
+ * which is a half over the scale, up to the rounding errors of the floating-point arithmetic.
+ *
+ * The default cost range is from `i32::MIN` to `Connection::INFINITE_COST - 1`, so that a
+ * quantized cost is never the infinite cost which means no edge.
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CostQuantizer {
+    scale: f64,
+    min_cost: i32,
+    max_cost: i32,
+}
+
+impl CostQuantizer {
+    /**
+     * Creates a cost quantizer.
+     *
+     * # Arguments
+     * * `scale` - A scale, the cost per unit score.
+     *
+     * # Errors
+     * * When the scale is not a positive finite number.
+     */
+    pub fn new(scale: f64) -> Result<Self> {
+        Self::with_cost_range(scale, i32::MIN, Connection::INFINITE_COST - 1)
+    }
+
+    /**
+     * Creates a cost quantizer with a cost range.
+     *
+     * # Arguments
+     * * `scale`    - A scale, the cost per unit score.
+     * * `min_cost` - A minimum cost.
+     * * `max_cost` - A maximum cost.
+     *
+     * # Errors
+     * * When the scale is not a positive finite number.
+     * * When `min_cost` is greater than `max_cost`, or `max_cost` is the infinite cost.
+     */
+    pub fn with_cost_range(scale: f64, min_cost: i32, max_cost: i32) -> Result<Self> {
+        if !scale.is_finite() || scale <= 0.0 {
+            return Err(CostQuantizerError::InvalidScale.into());
+        }
+        if min_cost > max_cost || max_cost == Connection::INFINITE_COST {
+            return Err(CostQuantizerError::InvalidCostRange.into());
+        }
+        Ok(Self {
+            scale,
+            min_cost,
+            max_cost,
+        })
+    }
+
+    /**
+     * Returns the scale.
+     *
+     * # Returns
+     * The scale.
+     */
+    pub const fn scale(&self) -> f64 {
+        self.scale
+    }
+
+    /**
+     * Returns the minimum cost.
+     *
+     * # Returns
+     * The minimum cost.
+     */
+    pub const fn min_cost(&self) -> i32 {
+        self.min_cost
+    }
+
+    /**
+     * Returns the maximum cost.
+     *
+     * # Returns
+     * The maximum cost.
+     */
+    pub const fn max_cost(&self) -> i32 {
+        self.max_cost
+    }
+
+    /**
+     * Returns the precision.
+     *
+     * # Returns
+     * The maximum difference between a score within the cost range and the score restored from
+     * its cost.
+     */
+    pub fn precision(&self) -> f64 {
+        0.5 / self.scale
+    }
+
+    /**
+     * Quantizes a score.
+     *
+     * # Arguments
+     * * `score` - A score.
+     *
+     * # Returns
+     * The cost. The maximum cost for the positive infinity and NaN, and the minimum cost for the
+     * negative infinity.
+     */
+    pub fn quantize(&self, score: f64) -> i32 {
+        if score.is_nan() {
+            return self.max_cost;
+        }
+        // The conversion saturates, so that the scaled values out of i32 are clamped too.
+        ((score * self.scale).round() as i32).clamp(self.min_cost, self.max_cost)
+    }
+
+    /**
+     * Quantizes a log probability.
+     *
+     * # Arguments
+     * * `log_probability` - A log probability, which is not greater than 0.
+     *
+     * # Returns
+     * The cost of the negated log probability.
+     */
+    pub fn quantize_log_probability(&self, log_probability: f64) -> i32 {
+        self.quantize(-log_probability)
+    }
+
+    /**
+     * Quantizes a score into a connection.
+     *
+     * # Arguments
+     * * `score` - A score.
+     *
+     * # Returns
+     * The connection of the cost.
+     */
+    pub fn connection(&self, score: f64) -> Connection {
+        Connection::new(self.quantize(score))
+    }
+
+    /**
+     * Dequantizes a cost.
+     *
+     * # Arguments
+     * * `cost` - A cost.
+     *
+     * # Returns
+     * The score.
+     */
+    pub fn dequantize(&self, cost: i32) -> f64 {
+        f64::from(cost) / self.scale
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::LatticeError;
+
+    use super::*;
+
+    #[test]
+    fn new() {
+        {
+            let quantizer = CostQuantizer::new(100.0).unwrap();
+
+            assert_eq!(quantizer.scale(), 100.0);
+            assert_eq!(quantizer.min_cost(), i32::MIN);
+            assert_eq!(quantizer.max_cost(), Connection::INFINITE_COST - 1);
+        }
+        for scale in [0.0, -1.0, f64::INFINITY, f64::NAN] {
+            let error = CostQuantizer::new(scale).unwrap_err();
+            assert!(matches!(
+                error,
+                LatticeError::CostQuantizer(CostQuantizerError::InvalidScale)
+            ));
+        }
+    }
+
+    #[test]
+    fn with_cost_range() {
+        {
+            let quantizer = CostQuantizer::with_cost_range(100.0, 0, 1000).unwrap();
+
+            assert_eq!(quantizer.min_cost(), 0);
+            assert_eq!(quantizer.max_cost(), 1000);
+        }
+        {
+            let error = CostQuantizer::with_cost_range(100.0, 1000, 0).unwrap_err();
+            assert!(matches!(
+                error,
+                LatticeError::CostQuantizer(CostQuantizerError::InvalidCostRange)
+            ));
+        }
+        {
+            let error =
+                CostQuantizer::with_cost_range(100.0, 0, Connection::INFINITE_COST).unwrap_err();
+            assert!(matches!(
+                error,
+                LatticeError::CostQuantizer(CostQuantizerError::InvalidCostRange)
+            ));
+        }
+    }
+
+    #[test]
+    fn precision() {
+        let quantizer = CostQuantizer::new(100.0).unwrap();
+
+        assert_eq!(quantizer.precision(), 0.005);
+        for score in [0.0, 0.004, 1.234_5, -2.468_1, 12_345.678] {
+            let restored = quantizer.dequantize(quantizer.quantize(score));
+            assert!((restored - score).abs() <= quantizer.precision());
+        }
+    }
+
+    #[test]
+    fn quantize() {
+        {
+            let quantizer = CostQuantizer::new(100.0).unwrap();
+
+            assert_eq!(quantizer.quantize(1.234), 123);
+            assert_eq!(quantizer.quantize(0.015), 2);
+            assert_eq!(quantizer.quantize(-0.015), -2);
+            assert_eq!(quantizer.quantize(f64::INFINITY), quantizer.max_cost());
+            assert_eq!(quantizer.quantize(f64::NAN), quantizer.max_cost());
+            assert_eq!(quantizer.quantize(f64::NEG_INFINITY), i32::MIN);
+            assert_eq!(quantizer.quantize(1e300), quantizer.max_cost());
+        }
+        {
+            let quantizer = CostQuantizer::with_cost_range(10.0, -5, 50).unwrap();
+
+            assert_eq!(quantizer.quantize(3.0), 30);
+            assert_eq!(quantizer.quantize(6.0), 50);
+            assert_eq!(quantizer.quantize(-1.0), -5);
+        }
+    }
+
+    #[test]
+    fn quantize_log_probability() {
+        let quantizer = CostQuantizer::new(1000.0).unwrap();
+
+        assert_eq!(quantizer.quantize_log_probability(0.0), 0);
+        assert_eq!(quantizer.quantize_log_probability(-2.5), 2500);
+        assert_eq!(
+            quantizer.quantize_log_probability(f64::NEG_INFINITY),
+            quantizer.max_cost()
+        );
+    }
+
+    #[test]
+    fn connection() {
+        let quantizer = CostQuantizer::new(100.0).unwrap();
+
+        assert_eq!(quantizer.connection(1.5), Connection::new(150));
+        assert!(!quantizer.connection(f64::INFINITY).is_infinite());
+    }
+
+    #[test]
+    fn dequantize() {
+        let quantizer = CostQuantizer::new(100.0).unwrap();
+
+        assert_eq!(quantizer.dequantize(150), 1.5);
+    }
+}
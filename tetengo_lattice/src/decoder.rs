@@ -0,0 +1,356 @@
+/*!
+ * A decoder.
+ *
+ * Copyright (C) 2023-2025 kaoru  <https://www.tetengo.org/>
+ */
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::VecDeque;
+use std::hash::Hasher;
+
+use crate::decoding_mode::DecodingMode;
+use crate::error::Result;
+use crate::input::Input;
+use crate::lattice::Lattice;
+use crate::path::Path;
+
+/**
+ * A decoder.
+ *
+ * It decodes one input sequence after another with one lattice, which retains its buffers
+ * between the decodes. It suits a server decoding many short sentences, where the allocation of
+ * the buffers would dominate the decoding.
+ *
+ * It can cache the best paths, so that an input sequence decoded again, e.g. retyped in an IME,
+ * is not decoded.
+ */
+#[derive(Debug)]
+pub struct Decoder<'a> {
+    lattice: Lattice<'a>,
+    decoding_mode: DecodingMode,
+    cache_capacity: usize,
+    cache: VecDeque<CachedPath>,
+}
+
+#[derive(Debug)]
+struct CachedPath {
+    input_hash: u64,
+    generation: u64,
+    path: Path,
+}
+
+impl<'a> Decoder<'a> {
+    /**
+     * Creates a decoder.
+     *
+     * The decoding mode is `DecodingMode::Viterbi`.
+     *
+     * # Arguments
+     * * `lattice` - A lattice.
+     */
+    pub const fn new(lattice: Lattice<'a>) -> Self {
+        Self {
+            lattice,
+            decoding_mode: DecodingMode::Viterbi,
+            cache_capacity: 0,
+            cache: VecDeque::new(),
+        }
+    }
+
+    /**
+     * Sets a decoding mode.
+     *
+     * # Arguments
+     * * `decoding_mode` - A decoding mode.
+     */
+    pub const fn decoding_mode(mut self, decoding_mode: DecodingMode) -> Self {
+        self.decoding_mode = decoding_mode;
+        self
+    }
+
+    /**
+     * Sets a cache capacity.
+     *
+     * The best paths of the last `cache_capacity` input sequences are cached. A cached path is
+     * keyed by the hash of the input sequence and the generation of the vocabulary, and the
+     * least recently used one is discarded when the cache is full.
+     * The default capacity is 0, which disables the cache.
+     *
+     * # Arguments
+     * * `cache_capacity` - A cache capacity.
+     */
+    pub fn cache_capacity(mut self, cache_capacity: usize) -> Self {
+        self.cache_capacity = cache_capacity;
+        self.cache.truncate(cache_capacity);
+        self
+    }
+
+    /**
+     * Returns the lattice.
+     *
+     * It has the steps of the last decode not served from the cache.
+     *
+     * # Returns
+     * The lattice.
+     */
+    pub const fn lattice(&self) -> &Lattice<'a> {
+        &self.lattice
+    }
+
+    /**
+     * Decodes inputs.
+     *
+     * The lattice is reset, the inputs are pushed back and the lattice is settled. When the best
+     * path for the inputs is cached, it is returned and the lattice is left untouched.
+     * The inputs are pushed back with `Lattice::push_back_with_next()`, so the vocabulary gets
+     * the prefetch hint for each following input.
+     *
+     * # Arguments
+     * * `inputs` - Inputs.
+     *
+     * # Returns
+     * The best path.
+     *
+     * # Errors
+     * * When the lattice fails to push back the inputs or to settle.
+     * * When the lattice has no path.
+     */
+    pub fn decode(&mut self, inputs: impl IntoIterator<Item = Box<dyn Input>>) -> Result<Path> {
+        if self.cache_capacity == 0 {
+            return self.decode_without_cache(inputs);
+        }
+
+        let inputs = inputs.into_iter().collect::<Vec<_>>();
+        let input_hash = Self::hash_inputs(&inputs);
+        let generation = self.lattice.vocabulary().generation();
+        if let Some(index) = self
+            .cache
+            .iter()
+            .position(|cached| cached.input_hash == input_hash && cached.generation == generation)
+        {
+            if let Some(cached) = self.cache.remove(index) {
+                let path = cached.path.clone();
+                self.cache.push_front(cached);
+                return Ok(path);
+            }
+        }
+
+        let path = self.decode_without_cache(inputs)?;
+        self.cache.truncate(self.cache_capacity - 1);
+        self.cache.push_front(CachedPath {
+            input_hash,
+            generation,
+            path: path.clone(),
+        });
+        Ok(path)
+    }
+
+    fn decode_without_cache(
+        &mut self,
+        inputs: impl IntoIterator<Item = Box<dyn Input>>,
+    ) -> Result<Path> {
+        self.lattice.reset();
+        let mut inputs = inputs.into_iter().peekable();
+        while let Some(input) = inputs.next() {
+            match inputs.peek() {
+                Some(next_input) => self
+                    .lattice
+                    .push_back_with_next(input, next_input.as_ref())?,
+                None => self.lattice.push_back(input)?,
+            }
+        }
+        let eos_node = self.lattice.settle()?;
+        self.decoding_mode.decode(&self.lattice, eos_node)
+    }
+
+    fn hash_inputs(inputs: &[Box<dyn Input>]) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        hasher.write_usize(inputs.len());
+        for input in inputs {
+            hasher.write_u64(input.hash_value());
+        }
+        hasher.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    use crate::connection::Connection;
+    use crate::entry::Entry;
+    use crate::hash_map_vocabulary::HashMapVocabulary;
+    use crate::node::Node;
+    use crate::string_input::StringInput;
+    use crate::vocabulary::Vocabulary;
+
+    use super::*;
+
+    fn entry_hash(entry: &Entry) -> u64 {
+        entry.key().map_or(0, |key| key.hash_value())
+    }
+
+    fn entry_equal_to(one: &Entry, other: &Entry) -> bool {
+        match (one.key(), other.key()) {
+            (Some(one_key), Some(other_key)) => one_key.equal_to(other_key),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+
+    fn create_vocabulary() -> HashMapVocabulary<'static> {
+        let entry = |key: &str, value: &'static str, cost| {
+            Entry::new(
+                Box::new(StringInput::new(String::from(key))),
+                Box::new(value),
+                cost,
+            )
+        };
+        HashMapVocabulary::new(
+            vec![
+                (String::from("a"), vec![entry("a", "A", 10)]),
+                (String::from("b"), vec![entry("b", "B", 20)]),
+                (String::from("ab"), vec![entry("ab", "AB", 50)]),
+            ],
+            vec![
+                ((Entry::BosEos, entry("a", "", 0)), 1),
+                ((Entry::BosEos, entry("b", "", 0)), 1),
+                ((Entry::BosEos, entry("ab", "", 0)), 1),
+                ((entry("a", "", 0), entry("b", "", 0)), 1),
+                ((entry("a", "", 0), Entry::BosEos), 1),
+                ((entry("b", "", 0), Entry::BosEos), 1),
+                ((entry("ab", "", 0), Entry::BosEos), 1),
+            ],
+            &entry_hash,
+            &entry_equal_to,
+        )
+    }
+
+    #[derive(Debug)]
+    struct GenerationalVocabulary {
+        vocabulary: HashMapVocabulary<'static>,
+        generation: Cell<u64>,
+    }
+
+    impl Vocabulary for GenerationalVocabulary {
+        fn find_entries(&self, key: &dyn Input) -> Result<Vec<Rc<Entry>>> {
+            self.vocabulary.find_entries(key)
+        }
+
+        fn generation(&self) -> u64 {
+            self.generation.get()
+        }
+
+        fn find_connection(&self, from: &Node, to: &Entry) -> Result<Connection> {
+            self.vocabulary.find_connection(from, to)
+        }
+    }
+
+    fn to_inputs(inputs: &[&str]) -> Vec<Box<dyn Input>> {
+        inputs
+            .iter()
+            .map(|&input| {
+                let input: Box<dyn Input> = Box::new(StringInput::new(String::from(input)));
+                input
+            })
+            .collect()
+    }
+
+    fn values_of(path: &Path) -> Vec<&'static str> {
+        path.iter()
+            .map(|(_, value, _, _)| *value.downcast_ref::<&str>().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn new() {
+        let vocabulary = create_vocabulary();
+        let _decoder = Decoder::new(Lattice::new(&vocabulary));
+    }
+
+    #[test]
+    fn decode() {
+        let vocabulary = create_vocabulary();
+        let mut decoder = Decoder::new(Lattice::new(&vocabulary));
+
+        {
+            let path = decoder.decode(to_inputs(&["a", "b"])).unwrap();
+            assert_eq!(values_of(&path), vec!["A", "B"]);
+            assert_eq!(path.cost(), 33);
+        }
+        {
+            let path = decoder.decode(to_inputs(&["b"])).unwrap();
+            assert_eq!(values_of(&path), vec!["B"]);
+            assert_eq!(decoder.lattice().step_count(), 2);
+        }
+        {
+            let path = decoder.decode(to_inputs(&["a", "b"])).unwrap();
+            assert_eq!(values_of(&path), vec!["A", "B"]);
+        }
+        {
+            let result = decoder.decode(to_inputs(&["c"]));
+            assert!(result.is_err());
+
+            let path = decoder.decode(to_inputs(&["a"])).unwrap();
+            assert_eq!(values_of(&path), vec!["A"]);
+            assert_eq!(path.cost(), 12);
+        }
+    }
+
+    #[test]
+    fn decoding_mode() {
+        let vocabulary = create_vocabulary();
+        let mut decoder = Decoder::new(Lattice::new(&vocabulary))
+            .decoding_mode(DecodingMode::MinimumBayesRisk { cost_scale: 0.01 });
+
+        let path = decoder.decode(to_inputs(&["a", "b"])).unwrap();
+        assert_eq!(path.nodes().len(), 4);
+    }
+
+    #[test]
+    fn cache_capacity() {
+        let vocabulary = GenerationalVocabulary {
+            vocabulary: create_vocabulary(),
+            generation: Cell::new(0),
+        };
+        let mut decoder = Decoder::new(Lattice::new(&vocabulary)).cache_capacity(2);
+
+        {
+            let path = decoder.decode(to_inputs(&["a", "b"])).unwrap();
+            assert_eq!(values_of(&path), vec!["A", "B"]);
+            assert_eq!(decoder.lattice().step_count(), 3);
+        }
+        {
+            let path = decoder.decode(to_inputs(&["b"])).unwrap();
+            assert_eq!(values_of(&path), vec!["B"]);
+            assert_eq!(decoder.lattice().step_count(), 2);
+        }
+        {
+            let path = decoder.decode(to_inputs(&["a", "b"])).unwrap();
+            assert_eq!(values_of(&path), vec!["A", "B"]);
+            assert_eq!(path.cost(), 33);
+            assert_eq!(decoder.lattice().step_count(), 2);
+        }
+        {
+            assert!(decoder.decode(to_inputs(&["a"])).is_ok());
+            assert_eq!(decoder.lattice().step_count(), 2);
+
+            let path = decoder.decode(to_inputs(&["b"])).unwrap();
+            assert_eq!(values_of(&path), vec!["B"]);
+            assert_eq!(decoder.lattice().step_count(), 2);
+
+            assert!(decoder.decode(to_inputs(&["a"])).is_ok());
+            let path = decoder.decode(to_inputs(&["a", "b"])).unwrap();
+            assert_eq!(values_of(&path), vec!["A", "B"]);
+            assert_eq!(decoder.lattice().step_count(), 3);
+        }
+        {
+            vocabulary.generation.set(1);
+
+            let path = decoder.decode(to_inputs(&["a"])).unwrap();
+            assert_eq!(values_of(&path), vec!["A"]);
+            assert_eq!(decoder.lattice().step_count(), 2);
+        }
+    }
+}
@@ -0,0 +1,316 @@
+/*!
+ * A decoding mode.
+ *
+ * Copyright (C) 2023-2025 kaoru  <https://www.tetengo.org/>
+ */
+
+use crate::connection::Connection;
+use crate::constraint::Constraint;
+use crate::error::{LatticeError, Result};
+use crate::lattice::Lattice;
+use crate::n_best_iterator::NBestIterator;
+use crate::node::Node;
+use crate::path::Path;
+
+/**
+ * A decoding mode.
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DecodingMode {
+    /// The Viterbi decoding, which selects the path with the minimum cost.
+    Viterbi,
+
+    /**
+     * The minimum Bayes risk decoding, which selects the path with the maximum expected count of
+     * correct nodes.
+     *
+     * The probability of a path is proportional to `exp(-cost * cost_scale)`.
+     */
+    MinimumBayesRisk {
+        /// A scale to convert costs into log probabilities.
+        cost_scale: f64,
+    },
+}
+
+impl DecodingMode {
+    /**
+     * Decodes a lattice.
+     *
+     * # Arguments
+     * * `lattice`  - A lattice.
+     * * `eos_node` - An EOS node.
+     *
+     * # Returns
+     * The best path.
+     *
+     * # Errors
+     * * When the lattice has no path.
+     */
+    pub fn decode<'a>(&self, lattice: &'a Lattice<'a>, eos_node: Node) -> Result<Path> {
+        match self {
+            DecodingMode::Viterbi => {
+                NBestIterator::new(lattice, eos_node, Box::new(Constraint::new()))
+                    .try_next()?
+                    .ok_or(LatticeError::ConstraintUnsatisfiable)
+            }
+            DecodingMode::MinimumBayesRisk { cost_scale } => {
+                Self::decode_mbr(lattice, eos_node, *cost_scale)
+            }
+        }
+    }
+
+    fn decode_mbr(lattice: &Lattice<'_>, eos_node: Node, cost_scale: f64) -> Result<Path> {
+        let mut steps = Vec::with_capacity(lattice.step_count());
+        for step in 0..lattice.step_count() {
+            steps.push(lattice.nodes_at(step)?);
+        }
+        let log_weight = |edge_cost: i32, node_cost: i32| {
+            if edge_cost == Connection::INFINITE_COST || node_cost == Connection::INFINITE_COST {
+                f64::NEG_INFINITY
+            } else {
+                -(edge_cost as f64 + node_cost as f64) * cost_scale
+            }
+        };
+
+        // Forward
+        let mut alphas = steps
+            .iter()
+            .map(|nodes| vec![f64::NEG_INFINITY; nodes.len()])
+            .collect::<Vec<_>>();
+        alphas[0][0] = 0.0;
+        for step in 1..steps.len() {
+            for (i, node) in steps[step].iter().enumerate() {
+                let preceding_alphas = &alphas[node.preceding_step()];
+                let alpha = log_sum_exp(node.preceding_edge_costs().iter().enumerate().map(
+                    |(j, &edge_cost)| preceding_alphas[j] + log_weight(edge_cost, node.node_cost()),
+                ));
+                alphas[step][i] = alpha;
+            }
+        }
+        let eos_alpha = log_sum_exp(eos_node.preceding_edge_costs().iter().enumerate().map(
+            |(j, &edge_cost)| alphas[eos_node.preceding_step()][j] + log_weight(edge_cost, 0),
+        ));
+
+        // Backward
+        let mut betas = steps
+            .iter()
+            .map(|nodes| vec![f64::NEG_INFINITY; nodes.len()])
+            .collect::<Vec<_>>();
+        for (j, &edge_cost) in eos_node.preceding_edge_costs().iter().enumerate() {
+            betas[eos_node.preceding_step()][j] = log_weight(edge_cost, 0);
+        }
+        for step in (1..steps.len()).rev() {
+            for (i, node) in steps[step].iter().enumerate() {
+                let beta = betas[step][i];
+                for (j, &edge_cost) in node.preceding_edge_costs().iter().enumerate() {
+                    let preceding_beta = &mut betas[node.preceding_step()][j];
+                    *preceding_beta = log_sum_exp(
+                        [
+                            *preceding_beta,
+                            beta + log_weight(edge_cost, node.node_cost()),
+                        ]
+                        .into_iter(),
+                    );
+                }
+            }
+        }
+
+        // Maximizes the sum of the posterior probabilities of the nodes.
+        let mut scores = steps
+            .iter()
+            .map(|nodes| vec![(f64::NEG_INFINITY, 0usize); nodes.len()])
+            .collect::<Vec<_>>();
+        scores[0][0] = (0.0, 0);
+        for step in 1..steps.len() {
+            for (i, node) in steps[step].iter().enumerate() {
+                let posterior = if eos_alpha.is_finite() {
+                    (alphas[step][i] + betas[step][i] - eos_alpha).exp()
+                } else {
+                    0.0
+                };
+                let (best_score, best_preceding_node) =
+                    Self::best_preceding(&scores[node.preceding_step()], node);
+                scores[step][i] = (best_score + posterior, best_preceding_node);
+            }
+        }
+        let (_, eos_best_preceding_node) =
+            Self::best_preceding(&scores[eos_node.preceding_step()], &eos_node);
+
+        let mut reversed_nodes = vec![eos_node.clone()];
+        let mut cost = 0;
+        let mut node = &eos_node;
+        let mut best_preceding_node = eos_best_preceding_node;
+        while !node.is_bos() {
+            let preceding = &steps[node.preceding_step()][best_preceding_node];
+            cost = Connection::add_cost(
+                cost,
+                Connection::add_cost(
+                    node.node_cost(),
+                    node.preceding_edge_costs()[best_preceding_node],
+                ),
+            );
+            reversed_nodes.push(preceding.clone());
+            best_preceding_node = scores[node.preceding_step()][preceding.index_in_step()].1;
+            node = preceding;
+        }
+
+        Ok(Path::new(reversed_nodes.into_iter().rev().collect(), cost))
+    }
+
+    fn best_preceding(preceding_scores: &[(f64, usize)], node: &Node) -> (f64, usize) {
+        let mut best = (f64::NEG_INFINITY, 0);
+        for (j, &edge_cost) in node.preceding_edge_costs().iter().enumerate() {
+            if edge_cost == Connection::INFINITE_COST {
+                continue;
+            }
+            if preceding_scores[j].0 > best.0 {
+                best = (preceding_scores[j].0, j);
+            }
+        }
+        best
+    }
+}
+
+fn log_sum_exp(values: impl Iterator<Item = f64>) -> f64 {
+    let values = values.collect::<Vec<_>>();
+    let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    if max == f64::NEG_INFINITY {
+        return max;
+    }
+    max + values.iter().map(|v| (v - max).exp()).sum::<f64>().ln()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::hash::{DefaultHasher, Hash, Hasher};
+
+    use crate::entry::Entry;
+    use crate::hash_map_vocabulary::HashMapVocabulary;
+    use crate::input::Input;
+    use crate::path::CostContribution;
+    use crate::string_input::StringInput;
+
+    use super::*;
+
+    fn to_input(string: &str) -> Box<dyn Input> {
+        Box::new(StringInput::new(string.to_string()))
+    }
+
+    fn value_of(entry: &Entry) -> &str {
+        entry
+            .value()
+            .and_then(|v| v.downcast_ref::<&str>())
+            .unwrap_or(&"")
+    }
+
+    fn entry_hash(entry: &Entry) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        entry
+            .key()
+            .map_or(0, |key| key.hash_value())
+            .hash(&mut hasher);
+        value_of(entry).hash(&mut hasher);
+        hasher.finish()
+    }
+
+    fn entry_equal_to(one: &Entry, other: &Entry) -> bool {
+        let equal_keys = match (one.key(), other.key()) {
+            (Some(one_key), Some(other_key)) => one_key.equal_to(other_key),
+            (None, None) => true,
+            _ => false,
+        };
+        equal_keys && value_of(one) == value_of(other)
+    }
+
+    /*
+            /---------[ab:AB]---------\
+           /  (0)      (10)      (0)   \
+        [BOS]                          [EOS]
+           \ (0)               (0)    /
+            +--[a:A1]--\     /-------+
+            |    (5)    +-(8)-[b:B]
+            +--[a:A2]--/       (0)
+                 (5)
+
+        Path                Cost
+        [BOS]-[AB]-[EOS]    10
+        [BOS]-[A1]-[B]-[EOS] 13
+        [BOS]-[A2]-[B]-[EOS] 13
+    */
+    fn create_vocabulary() -> HashMapVocabulary<'static> {
+        let ab = Entry::new(to_input("ab"), Box::new("AB"), 10);
+        let a1 = Entry::new(to_input("a"), Box::new("A1"), 5);
+        let a2 = Entry::new(to_input("a"), Box::new("A2"), 5);
+        let b = Entry::new(to_input("b"), Box::new("B"), 0);
+        HashMapVocabulary::new(
+            vec![
+                (String::from("ab"), vec![ab.clone()]),
+                (String::from("a"), vec![a1.clone(), a2.clone()]),
+                (String::from("b"), vec![b.clone()]),
+            ],
+            vec![
+                ((Entry::BosEos, ab.clone()), 0),
+                ((Entry::BosEos, a1.clone()), 0),
+                ((Entry::BosEos, a2.clone()), 0),
+                ((a1, b.clone()), 8),
+                ((a2, b.clone()), 8),
+                ((ab, Entry::BosEos), 0),
+                ((b, Entry::BosEos), 0),
+            ],
+            &entry_hash,
+            &entry_equal_to,
+        )
+    }
+
+    fn values_of(path: &Path) -> Vec<&str> {
+        path.nodes()
+            .iter()
+            .filter_map(|node| node.value().and_then(|v| v.downcast_ref::<&str>()))
+            .copied()
+            .collect()
+    }
+
+    #[test]
+    fn decode() {
+        let vocabulary = create_vocabulary();
+        let mut lattice = Lattice::new(&vocabulary);
+        lattice.push_back(to_input("a")).unwrap();
+        lattice.push_back(to_input("b")).unwrap();
+        let eos_node = lattice.settle().unwrap();
+
+        {
+            let path = DecodingMode::Viterbi
+                .decode(&lattice, eos_node.clone())
+                .unwrap();
+            assert_eq!(values_of(&path), vec!["AB"]);
+            assert_eq!(path.cost(), 10);
+        }
+        {
+            // P(AB) = 0.40, P(A1) = P(A2) = 0.30, P(B) = 0.60
+            let path = DecodingMode::MinimumBayesRisk { cost_scale: 0.1 }
+                .decode(&lattice, eos_node.clone())
+                .unwrap();
+            assert_eq!(values_of(&path), vec!["A1", "B"]);
+            assert!(path.nodes().first().unwrap().is_bos());
+            assert_eq!(path.nodes().last().unwrap(), &eos_node);
+            assert_eq!(path.cost(), 13);
+            assert_eq!(
+                path.cost_breakdown(),
+                vec![
+                    CostContribution::new(0, 0),
+                    CostContribution::new(0, 5),
+                    CostContribution::new(8, 0),
+                    CostContribution::new(0, 0),
+                ]
+            );
+        }
+        {
+            // P(AB) ~= 1.0
+            let path = DecodingMode::MinimumBayesRisk { cost_scale: 10.0 }
+                .decode(&lattice, eos_node)
+                .unwrap();
+            assert_eq!(values_of(&path), vec!["AB"]);
+            assert_eq!(path.cost(), 10);
+        }
+    }
+}
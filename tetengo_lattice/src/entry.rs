@@ -6,9 +6,12 @@
 
 use std::any::Any;
 use std::fmt::Debug;
+use std::hash::{Hash, Hasher};
+use std::ops::Deref;
 use std::rc::Rc;
 
 use crate::input::Input;
+use crate::node::NodeError;
 
 /**
  * A middle entry.
@@ -18,6 +21,8 @@ pub struct Middle {
     key: Rc<dyn Input>,
     value: Rc<dyn Any>,
     cost: i32,
+    value_identity: Option<u64>,
+    context_ids: Option<(usize, usize)>,
 }
 
 impl Clone for Middle {
@@ -26,12 +31,16 @@ impl Clone for Middle {
             key: self.key.clone(),
             value: self.value.clone(),
             cost: self.cost,
+            value_identity: self.value_identity,
+            context_ids: self.context_ids,
         }
     }
 }
 
 /**
  * An entry.
+ *
+ * Entries are equal when their keys, costs and value identities are equal.
  */
 #[derive(Clone, Debug)]
 pub enum Entry {
@@ -56,6 +65,35 @@ impl Entry {
             key: Rc::from(key),
             value: Rc::from(value),
             cost,
+            value_identity: None,
+            context_ids: None,
+        })
+    }
+
+    /**
+     * Creates an entry with a value identity.
+     *
+     * Entries with the same key, cost and value identity are equal even if their values are
+     * different objects.
+     *
+     * # Arguments
+     * * `key`            - A box of a key.
+     * * `value`          - A box of a value.
+     * * `cost`           - A cost.
+     * * `value_identity` - A value identity.
+     */
+    pub fn new_with_value_identity(
+        key: Box<dyn Input>,
+        value: Box<dyn Any>,
+        cost: i32,
+        value_identity: u64,
+    ) -> Self {
+        Entry::Middle(Middle {
+            key: Rc::from(key),
+            value: Rc::from(value),
+            cost,
+            value_identity: Some(value_identity),
+            context_ids: None,
         })
     }
 
@@ -92,6 +130,13 @@ impl Entry {
         }
     }
 
+    pub(crate) fn shared_value(&self) -> Option<Rc<dyn Any>> {
+        match self {
+            Entry::BosEos => None,
+            Entry::Middle(entry) => Some(entry.value.clone()),
+        }
+    }
+
     /**
      * Returns the cost.
      *
@@ -104,10 +149,260 @@ impl Entry {
             Entry::Middle(entry) => entry.cost,
         }
     }
+
+    /**
+     * Creates a view of this entry with another cost.
+     *
+     * The key and the value are shared with this entry without being cloned, and so is the
+     * value identity.
+     *
+     * # Arguments
+     * * `cost` - A cost.
+     *
+     * # Returns
+     * The entry with the cost. Or the BOS/EOS entry when this entry is the BOS/EOS entry.
+     */
+    pub fn with_cost(&self, cost: i32) -> Self {
+        match self {
+            Entry::BosEos => Entry::BosEos,
+            Entry::Middle(entry) => Entry::Middle(Middle {
+                cost,
+                ..entry.clone()
+            }),
+        }
+    }
+
+    /**
+     * Creates a view of this entry with a key.
+     *
+     * The value is shared with this entry without being cloned, and so is the value identity.
+     *
+     * # Arguments
+     * * `key` - A box of a key.
+     *
+     * # Returns
+     * The entry with the key. Or the BOS/EOS entry when this entry is the BOS/EOS entry.
+     */
+    pub fn with_key(&self, key: Box<dyn Input>) -> Self {
+        match self {
+            Entry::BosEos => Entry::BosEos,
+            Entry::Middle(entry) => Entry::Middle(Middle {
+                key: Rc::from(key),
+                ..entry.clone()
+            }),
+        }
+    }
+
+    /**
+     * Creates a view of this entry with an additional cost.
+     *
+     * The key and the value are shared with this entry without being cloned, and so is the
+     * value identity. The cost saturates instead of overflowing.
+     *
+     * # Arguments
+     * * `additional_cost` - An additional cost. It may be negative.
+     *
+     * # Returns
+     * The entry with the adjusted cost. Or the BOS/EOS entry when this entry is the BOS/EOS
+     * entry.
+     */
+    pub fn with_additional_cost(&self, additional_cost: i32) -> Self {
+        self.with_cost(self.cost().saturating_add(additional_cost))
+    }
+
+    /**
+     * Creates a view of this entry with context IDs.
+     *
+     * The key and the value are shared with this entry without being cloned, and so is the
+     * value identity.
+     *
+     * # Arguments
+     * * `left_id`  - A left context ID.
+     * * `right_id` - A right context ID.
+     *
+     * # Returns
+     * The entry with the context IDs. Or the BOS/EOS entry when this entry is the BOS/EOS entry.
+     */
+    pub fn with_context_ids(&self, left_id: usize, right_id: usize) -> Self {
+        match self {
+            Entry::BosEos => Entry::BosEos,
+            Entry::Middle(entry) => Entry::Middle(Middle {
+                context_ids: Some((left_id, right_id)),
+                ..entry.clone()
+            }),
+        }
+    }
+
+    /**
+     * Returns the left context ID.
+     *
+     * It is the ID used to look up the connection from a preceding entry in a connection matrix.
+     *
+     * # Returns
+     * The left context ID. Or 0 for the BOS/EOS entry, as in the dictionaries of MeCab. Or
+     * `None` when the entry has no context IDs.
+     */
+    pub const fn left_id(&self) -> Option<usize> {
+        match self {
+            Entry::BosEos => Some(0),
+            Entry::Middle(entry) => match entry.context_ids {
+                Some((left_id, _)) => Some(left_id),
+                None => None,
+            },
+        }
+    }
+
+    /**
+     * Returns the right context ID.
+     *
+     * It is the ID used to look up the connection to a following entry in a connection matrix.
+     *
+     * # Returns
+     * The right context ID. Or 0 for the BOS/EOS entry, as in the dictionaries of MeCab. Or
+     * `None` when the entry has no context IDs.
+     */
+    pub const fn right_id(&self) -> Option<usize> {
+        match self {
+            Entry::BosEos => Some(0),
+            Entry::Middle(entry) => match entry.context_ids {
+                Some((_, right_id)) => Some(right_id),
+                None => None,
+            },
+        }
+    }
+
+    /**
+     * Returns the value identity.
+     *
+     * When the entry is not created with a value identity, the address of the value object,
+     * which is shared among the clones of the entry, is used.
+     *
+     * # Returns
+     * The value identity.
+     */
+    pub fn value_identity(&self) -> u64 {
+        match self {
+            Entry::BosEos => 0,
+            Entry::Middle(entry) => entry
+                .value_identity
+                .unwrap_or(Rc::as_ptr(&entry.value) as *const () as usize as u64),
+        }
+    }
+}
+
+impl Eq for Entry {}
+
+impl Hash for Entry {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        match self {
+            Entry::BosEos => 0u64.hash(state),
+            Entry::Middle(entry) => {
+                entry.key.hash_value().hash(state);
+                entry.cost.hash(state);
+            }
+        }
+        self.value_identity().hash(state);
+    }
+}
+
+impl PartialEq for Entry {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Entry::BosEos, Entry::BosEos) => true,
+            (Entry::Middle(one), Entry::Middle(another)) => {
+                one.key.equal_to(another.key.as_ref())
+                    && one.cost == another.cost
+                    && self.value_identity() == other.value_identity()
+            }
+            _ => false,
+        }
+    }
+}
+
+/**
+ * A middle entry.
+ *
+ * It is an entry which is never the BOS/EOS entry, so that a node created with it cannot fail.
+ */
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct MiddleEntry {
+    entry: Rc<Entry>,
+}
+
+impl MiddleEntry {
+    /**
+     * Creates a middle entry.
+     *
+     * # Arguments
+     * * `key`   - A box of a key.
+     * * `value` - A box of a value.
+     * * `cost`  - A cost.
+     */
+    pub fn new(key: Box<dyn Input>, value: Box<dyn Any>, cost: i32) -> Self {
+        Self {
+            entry: Rc::new(Entry::new(key, value, cost)),
+        }
+    }
+
+    /**
+     * Returns the entry.
+     *
+     * # Returns
+     * The entry.
+     */
+    pub const fn entry(&self) -> &Rc<Entry> {
+        &self.entry
+    }
+
+    /**
+     * Returns the entry consuming this middle entry.
+     *
+     * # Returns
+     * The entry.
+     */
+    pub fn into_entry(self) -> Rc<Entry> {
+        self.entry
+    }
+}
+
+impl Deref for MiddleEntry {
+    type Target = Entry;
+
+    fn deref(&self) -> &Self::Target {
+        self.entry.as_ref()
+    }
+}
+
+impl TryFrom<Rc<Entry>> for MiddleEntry {
+    type Error = NodeError;
+
+    fn try_from(entry: Rc<Entry>) -> Result<Self, Self::Error> {
+        if entry.is_bos_eos() {
+            return Err(NodeError::BosOrEosEntryNotAllowed);
+        }
+        Ok(Self { entry })
+    }
+}
+
+impl TryFrom<Entry> for MiddleEntry {
+    type Error = NodeError;
+
+    fn try_from(entry: Entry) -> Result<Self, Self::Error> {
+        Self::try_from(Rc::new(entry))
+    }
+}
+
+impl From<MiddleEntry> for Rc<Entry> {
+    fn from(middle_entry: MiddleEntry) -> Self {
+        middle_entry.into_entry()
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::collections::hash_map::DefaultHasher;
+    use std::collections::HashSet;
+
     use crate::string_input::StringInput;
 
     use super::*;
@@ -197,4 +492,240 @@ mod tests {
 
         assert_eq!(entry.cost(), 42);
     }
+
+    #[test]
+    fn with_cost() {
+        let entry = Entry::new(
+            Box::new(StringInput::new(String::from("みずほ"))),
+            Box::new(String::from("瑞穂")),
+            42,
+        );
+
+        let adjusted = entry.with_cost(24);
+
+        assert_eq!(adjusted.cost(), 24);
+        assert!(std::ptr::addr_eq(
+            adjusted.key().unwrap(),
+            entry.key().unwrap()
+        ));
+        assert!(std::ptr::addr_eq(
+            adjusted.value().unwrap(),
+            entry.value().unwrap()
+        ));
+        assert_eq!(adjusted.value_identity(), entry.value_identity());
+        assert_ne!(adjusted, entry);
+        assert_eq!(Entry::BosEos.with_cost(24), Entry::BosEos);
+    }
+
+    #[test]
+    fn with_key() {
+        let entry = Entry::new(
+            Box::new(StringInput::new(String::from("みずほ"))),
+            Box::new(String::from("瑞穂")),
+            42,
+        );
+
+        let rekeyed = entry.with_key(Box::new(StringInput::new(String::from("みづほ"))));
+
+        assert!(rekeyed
+            .key()
+            .unwrap()
+            .equal_to(&StringInput::new(String::from("みづほ"))));
+        assert_eq!(rekeyed.cost(), 42);
+        assert!(std::ptr::addr_eq(
+            rekeyed.value().unwrap(),
+            entry.value().unwrap()
+        ));
+        assert_eq!(rekeyed.value_identity(), entry.value_identity());
+        assert_ne!(rekeyed, entry);
+        assert_eq!(
+            Entry::BosEos.with_key(Box::new(StringInput::new(String::from("みずほ")))),
+            Entry::BosEos
+        );
+    }
+
+    #[test]
+    fn with_additional_cost() {
+        let entry = Entry::new_with_value_identity(
+            Box::new(StringInput::new(String::from("みずほ"))),
+            Box::new(String::from("瑞穂")),
+            42,
+            1,
+        );
+
+        assert_eq!(entry.with_additional_cost(-12).cost(), 30);
+        assert_eq!(entry.with_additional_cost(-12).value_identity(), 1);
+        assert_eq!(entry.with_additional_cost(i32::MAX).cost(), i32::MAX);
+        assert_eq!(entry.with_additional_cost(0), entry);
+        assert_eq!(Entry::BosEos.with_additional_cost(42).cost(), 0);
+    }
+
+    #[test]
+    fn context_ids() {
+        let entry = Entry::new(
+            Box::new(StringInput::new(String::from("みずほ"))),
+            Box::new(String::from("瑞穂")),
+            42,
+        );
+
+        assert!(entry.left_id().is_none());
+        assert!(entry.right_id().is_none());
+
+        let entry_with_ids = entry.with_context_ids(12, 34);
+
+        assert_eq!(entry_with_ids.left_id(), Some(12));
+        assert_eq!(entry_with_ids.right_id(), Some(34));
+        assert_eq!(entry_with_ids.cost(), 42);
+        assert_eq!(entry_with_ids.value_identity(), entry.value_identity());
+        assert_eq!(entry_with_ids.with_cost(24).left_id(), Some(12));
+        assert_eq!(Entry::BosEos.left_id(), Some(0));
+        assert_eq!(Entry::BosEos.with_context_ids(12, 34).right_id(), Some(0));
+    }
+
+    #[test]
+    fn value_identity() {
+        {
+            let entry = Entry::BosEos;
+
+            assert_eq!(entry.value_identity(), 0);
+        }
+        {
+            let entry1 = Entry::new(
+                Box::new(StringInput::new(String::from("みずほ"))),
+                Box::new(String::from("瑞穂")),
+                42,
+            );
+            let entry2 = entry1.clone();
+            let entry3 = Entry::new(
+                Box::new(StringInput::new(String::from("みずほ"))),
+                Box::new(String::from("瑞穂")),
+                42,
+            );
+
+            assert_eq!(entry1.value_identity(), entry2.value_identity());
+            assert_ne!(entry1.value_identity(), entry3.value_identity());
+        }
+        {
+            let entry = Entry::new_with_value_identity(
+                Box::new(StringInput::new(String::from("みずほ"))),
+                Box::new(String::from("瑞穂")),
+                42,
+                24,
+            );
+
+            assert_eq!(entry.value_identity(), 24);
+        }
+    }
+
+    #[test]
+    fn eq() {
+        let entry1 = Entry::new_with_value_identity(
+            Box::new(StringInput::new(String::from("みずほ"))),
+            Box::new(String::from("瑞穂")),
+            42,
+            1,
+        );
+        let entry2 = Entry::new_with_value_identity(
+            Box::new(StringInput::new(String::from("みずほ"))),
+            Box::new(String::from("瑞穂")),
+            42,
+            1,
+        );
+        let entry3 = Entry::new_with_value_identity(
+            Box::new(StringInput::new(String::from("みずほ"))),
+            Box::new(String::from("瑞穂")),
+            24,
+            1,
+        );
+        let entry4 = Entry::new_with_value_identity(
+            Box::new(StringInput::new(String::from("さくら"))),
+            Box::new(String::from("桜")),
+            42,
+            1,
+        );
+        let entry5 = Entry::new(
+            Box::new(StringInput::new(String::from("みずほ"))),
+            Box::new(String::from("瑞穂")),
+            42,
+        );
+
+        assert_eq!(Entry::BosEos, Entry::BosEos);
+        assert_eq!(entry1, entry2);
+        assert_ne!(entry1, entry3);
+        assert_ne!(entry1, entry4);
+        assert_eq!(entry5, entry5.clone());
+        assert_ne!(entry1, entry5);
+        assert_ne!(entry1, Entry::BosEos);
+    }
+
+    #[test]
+    fn hash() {
+        let entry1 = Entry::new_with_value_identity(
+            Box::new(StringInput::new(String::from("みずほ"))),
+            Box::new(String::from("瑞穂")),
+            42,
+            1,
+        );
+        let entry2 = Entry::new_with_value_identity(
+            Box::new(StringInput::new(String::from("みずほ"))),
+            Box::new(String::from("瑞穂")),
+            42,
+            1,
+        );
+        let hash_value_of = |entry: &Entry| {
+            let mut hasher = DefaultHasher::new();
+            entry.hash(&mut hasher);
+            hasher.finish()
+        };
+        assert_eq!(hash_value_of(&entry1), hash_value_of(&entry2));
+
+        let entry3 = Entry::new(
+            Box::new(StringInput::new(String::from("さくら"))),
+            Box::new(String::from("桜")),
+            24,
+        );
+        let entries = HashSet::from([entry1, entry2, entry3.clone(), Entry::BosEos]);
+        assert_eq!(entries.len(), 3);
+        assert!(entries.contains(&entry3));
+        assert!(entries.contains(&Entry::BosEos));
+    }
+
+    #[test]
+    fn middle_entry_new() {
+        let middle_entry = MiddleEntry::new(
+            Box::new(StringInput::new(String::from("みずほ"))),
+            Box::new(String::from("瑞穂")),
+            42,
+        );
+
+        assert_eq!(middle_entry.cost(), 42);
+        assert_eq!(
+            middle_entry
+                .value()
+                .unwrap()
+                .downcast_ref::<String>()
+                .unwrap(),
+            "瑞穂"
+        );
+    }
+
+    #[test]
+    fn middle_entry_try_from() {
+        {
+            let entry = Rc::new(Entry::new(
+                Box::new(StringInput::new(String::from("みずほ"))),
+                Box::new(String::from("瑞穂")),
+                42,
+            ));
+            let middle_entry = MiddleEntry::try_from(entry.clone()).unwrap();
+
+            assert!(Rc::ptr_eq(middle_entry.entry(), &entry));
+            assert!(Rc::ptr_eq(&Rc::<Entry>::from(middle_entry), &entry));
+        }
+        {
+            let result = MiddleEntry::try_from(Entry::BosEos);
+
+            assert!(matches!(result, Err(NodeError::BosOrEosEntryNotAllowed)));
+        }
+    }
 }
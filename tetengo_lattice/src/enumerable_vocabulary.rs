@@ -0,0 +1,27 @@
+/*!
+ * An enumerable vocabulary.
+ *
+ * Copyright (C) 2023-2025 kaoru  <https://www.tetengo.org/>
+ */
+
+use std::rc::Rc;
+
+use crate::entry::Entry;
+use crate::error::Result;
+use crate::vocabulary::Vocabulary;
+
+/**
+ * An enumerable vocabulary.
+ */
+pub trait EnumerableVocabulary: Vocabulary {
+    /**
+     * Returns all the entries.
+     *
+     * # Returns
+     * All the entries.
+     *
+     * # Errors
+     * * When enumerating entries fails.
+     */
+    fn entries(&self) -> Result<Vec<Rc<Entry>>>;
+}
@@ -0,0 +1,104 @@
+/*!
+ * An error.
+ *
+ * Copyright (C) 2023-2025 kaoru  <https://www.tetengo.org/>
+ */
+
+use std::error;
+use std::result;
+
+use crate::connection_matrix::ConnectionMatrixError;
+use crate::cost_quantizer::CostQuantizerError;
+use crate::input::InputError;
+use crate::node::NodeError;
+
+/**
+ * A lattice error.
+ *
+ * Variants may be added in future versions, so a match on it needs a wildcard arm.
+ */
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum LatticeError {
+    /**
+     * The step is too large.
+     */
+    #[error("the step is too large")]
+    StepIsTooLarge,
+
+    /**
+     * No node is found for the input.
+     *
+     * No vocabulary entry has a key ending at the step.
+     */
+    #[error("no node is found for the input")]
+    NoNodeIsFoundForTheInput,
+
+    /**
+     * No input.
+     *
+     * The lattice is settled before any input is pushed.
+     */
+    #[error("no input")]
+    NoInput,
+
+    /**
+     * The constraint is unsatisfiable.
+     *
+     * No path in the lattice conforms to the constraint.
+     */
+    #[error("the constraint is unsatisfiable")]
+    ConstraintUnsatisfiable,
+
+    /**
+     * The node capacity is exceeded.
+     *
+     * The nodes in a step exceed the node capacity under `NodeOverflowPolicy::Error`.
+     */
+    #[error("the node capacity is exceeded")]
+    NodeCapacityExceeded,
+
+    /**
+     * The vocabulary fails.
+     */
+    #[error("the vocabulary fails")]
+    VocabularyFailure(#[source] Box<LatticeError>),
+
+    /**
+     * A connection matrix error.
+     */
+    #[error("connection matrix error")]
+    ConnectionMatrix(#[from] ConnectionMatrixError),
+
+    /**
+     * A cost quantizer error.
+     */
+    #[error("cost quantizer error")]
+    CostQuantizer(#[from] CostQuantizerError),
+
+    /**
+     * An input error.
+     */
+    #[error("input error")]
+    Input(#[from] InputError),
+
+    /**
+     * A node error.
+     */
+    #[error("node error")]
+    Node(#[from] NodeError),
+
+    /**
+     * An error from a user-defined input or vocabulary.
+     */
+    #[error("other error")]
+    Other(#[source] Box<dyn error::Error + Send + Sync>),
+}
+
+/**
+ * A result type.
+ *
+ * # Type Parameters
+ * * `T` - A value type.
+ */
+pub type Result<T> = result::Result<T, LatticeError>;
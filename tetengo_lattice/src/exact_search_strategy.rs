@@ -0,0 +1,95 @@
+/*!
+ * An exact search strategy.
+ *
+ * Copyright (C) 2023-2025 kaoru  <https://www.tetengo.org/>
+ */
+
+use crate::connection::Connection;
+use crate::node::Node;
+use crate::search_strategy::SearchStrategy;
+
+/**
+ * An exact search strategy.
+ *
+ * It is the exact Viterbi search, which keeps all the nodes. The preceding node with the lowest
+ * index is chosen among the ones with the same cost.
+ */
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ExactSearchStrategy;
+
+impl ExactSearchStrategy {
+    /**
+     * Creates an exact search strategy.
+     */
+    pub const fn new() -> Self {
+        Self
+    }
+}
+
+impl SearchStrategy for ExactSearchStrategy {
+    fn best_preceding_node(&self, preceding_nodes: &[Node], preceding_edge_costs: &[i32]) -> usize {
+        assert!(!preceding_nodes.is_empty());
+        let mut min_index = 0;
+        for i in 1..preceding_nodes.len() {
+            if Connection::add_cost(preceding_nodes[i].path_cost(), preceding_edge_costs[i])
+                < Connection::add_cost(
+                    preceding_nodes[min_index].path_cost(),
+                    preceding_edge_costs[min_index],
+                )
+            {
+                min_index = i;
+            }
+        }
+        min_index
+    }
+
+    fn nodes_to_keep(&self, nodes: &[Node]) -> Vec<usize> {
+        (0..nodes.len()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use super::*;
+
+    #[test]
+    const fn new() {
+        let _ = ExactSearchStrategy::new();
+    }
+
+    #[test]
+    fn best_preceding_node() {
+        let strategy = ExactSearchStrategy::new();
+        let preceding_nodes = [
+            Node::eos(0, Rc::new(Vec::new()), 0, 10),
+            Node::eos(0, Rc::new(Vec::new()), 0, 20),
+            Node::eos(0, Rc::new(Vec::new()), 0, i32::MAX),
+        ];
+
+        assert_eq!(
+            strategy.best_preceding_node(&preceding_nodes, &[5, 1, 0]),
+            0
+        );
+        assert_eq!(
+            strategy.best_preceding_node(&preceding_nodes, &[15, 1, 0]),
+            1
+        );
+        assert_eq!(
+            strategy.best_preceding_node(&preceding_nodes, &[i32::MAX, 1, 0]),
+            1
+        );
+    }
+
+    #[test]
+    fn nodes_to_keep() {
+        let strategy = ExactSearchStrategy::new();
+        let nodes = [
+            Node::eos(0, Rc::new(Vec::new()), 0, 10),
+            Node::eos(0, Rc::new(Vec::new()), 0, 20),
+        ];
+
+        assert_eq!(strategy.nodes_to_keep(&nodes), vec![0, 1]);
+    }
+}
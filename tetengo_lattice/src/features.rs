@@ -0,0 +1,397 @@
+/*!
+ * A feature string.
+ *
+ * Copyright (C) 2023-2025 kaoru  <https://www.tetengo.org/>
+ */
+
+use std::cell::OnceCell;
+use std::hash::{Hash, Hasher};
+use std::ops::Range;
+
+/**
+ * A feature layout.
+ *
+ * It tells where the part of speech, the reading and the lemma are in the fields of a feature
+ * string.
+ */
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum FeatureLayout {
+    /// The IPADIC layout. The part of speech is in the fields 0-3, the lemma in 6 and the reading
+    /// in 7.
+    Ipadic,
+
+    /// The UniDic layout. The part of speech is in the fields 0-3, the reading in 6 and the lemma
+    /// in 7.
+    Unidic,
+
+    /// A custom layout.
+    Custom {
+        /// The field count of the part of speech, which starts at the field 0.
+        pos_field_count: usize,
+
+        /// The field index of the reading.
+        reading_index: usize,
+
+        /// The field index of the lemma.
+        lemma_index: usize,
+    },
+}
+
+impl FeatureLayout {
+    const fn pos_field_count(&self) -> usize {
+        match self {
+            FeatureLayout::Ipadic | FeatureLayout::Unidic => 4,
+            FeatureLayout::Custom {
+                pos_field_count, ..
+            } => *pos_field_count,
+        }
+    }
+
+    const fn reading_index(&self) -> usize {
+        match self {
+            FeatureLayout::Ipadic => 7,
+            FeatureLayout::Unidic => 6,
+            FeatureLayout::Custom { reading_index, .. } => *reading_index,
+        }
+    }
+
+    const fn lemma_index(&self) -> usize {
+        match self {
+            FeatureLayout::Ipadic => 6,
+            FeatureLayout::Unidic => 7,
+            FeatureLayout::Custom { lemma_index, .. } => *lemma_index,
+        }
+    }
+}
+
+/**
+ * A feature string.
+ *
+ * It carries the raw comma-separated features of a dictionary entry, and splits them into fields
+ * on the first access to a field. A field enclosed in double quotes may contain commas; the
+ * quotes are not included in the field. A field of `*` is treated as empty by `pos`, `reading`
+ * and `lemma`.
+ *
+ * It can be used as the value of an entry.
+ */
+#[derive(Clone, Debug)]
+pub struct Features {
+    raw: String,
+    layout: FeatureLayout,
+    field_ranges: OnceCell<Vec<Range<usize>>>,
+}
+
+impl Features {
+    /**
+     * Creates a feature string.
+     *
+     * The layout is `FeatureLayout::Ipadic`.
+     *
+     * # Arguments
+     * * `raw` - A raw feature string.
+     */
+    pub const fn new(raw: String) -> Self {
+        Self::with_layout(raw, FeatureLayout::Ipadic)
+    }
+
+    /**
+     * Creates a feature string with a layout.
+     *
+     * # Arguments
+     * * `raw`    - A raw feature string.
+     * * `layout` - A layout.
+     */
+    pub const fn with_layout(raw: String, layout: FeatureLayout) -> Self {
+        Self {
+            raw,
+            layout,
+            field_ranges: OnceCell::new(),
+        }
+    }
+
+    /**
+     * Returns the raw feature string.
+     *
+     * # Returns
+     * The raw feature string.
+     */
+    pub fn raw(&self) -> &str {
+        self.raw.as_str()
+    }
+
+    /**
+     * Returns the layout.
+     *
+     * # Returns
+     * The layout.
+     */
+    pub const fn layout(&self) -> FeatureLayout {
+        self.layout
+    }
+
+    /**
+     * Returns the field count.
+     *
+     * # Returns
+     * The field count.
+     */
+    pub fn len(&self) -> usize {
+        self.field_ranges().len()
+    }
+
+    /**
+     * Returns true when the feature string has no field.
+     *
+     * An empty raw feature string still has one empty field.
+     *
+     * # Returns
+     * Always false.
+     */
+    pub fn is_empty(&self) -> bool {
+        self.field_ranges().is_empty()
+    }
+
+    /**
+     * Returns a field.
+     *
+     * # Arguments
+     * * `index` - An index.
+     *
+     * # Returns
+     * The field. Or None when `index` is out of the range.
+     */
+    pub fn field(&self, index: usize) -> Option<&str> {
+        self.field_ranges()
+            .get(index)
+            .map(|range| &self.raw[range.clone()])
+    }
+
+    /**
+     * Returns the fields.
+     *
+     * # Returns
+     * An iterator to the fields.
+     */
+    pub fn fields(&self) -> impl Iterator<Item = &str> {
+        self.field_ranges()
+            .iter()
+            .map(|range| &self.raw[range.clone()])
+    }
+
+    /**
+     * Returns the part of speech.
+     *
+     * The fields of `*` and the empty fields are excluded.
+     *
+     * # Returns
+     * The fields of the part of speech.
+     */
+    pub fn pos(&self) -> Vec<&str> {
+        self.fields()
+            .take(self.layout.pos_field_count())
+            .filter(|&field| !is_absent(field))
+            .collect()
+    }
+
+    /**
+     * Returns the reading.
+     *
+     * # Returns
+     * The reading. Or None when the feature string has no reading.
+     */
+    pub fn reading(&self) -> Option<&str> {
+        self.field(self.layout.reading_index())
+            .filter(|&field| !is_absent(field))
+    }
+
+    /**
+     * Returns the lemma.
+     *
+     * # Returns
+     * The lemma. Or None when the feature string has no lemma.
+     */
+    pub fn lemma(&self) -> Option<&str> {
+        self.field(self.layout.lemma_index())
+            .filter(|&field| !is_absent(field))
+    }
+
+    fn field_ranges(&self) -> &[Range<usize>] {
+        self.field_ranges.get_or_init(|| split(&self.raw))
+    }
+}
+
+impl From<String> for Features {
+    fn from(raw: String) -> Self {
+        Self::new(raw)
+    }
+}
+
+impl PartialEq for Features {
+    fn eq(&self, other: &Self) -> bool {
+        self.raw == other.raw && self.layout == other.layout
+    }
+}
+
+impl Eq for Features {}
+
+impl Hash for Features {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.raw.hash(state);
+        self.layout.hash(state);
+    }
+}
+
+fn is_absent(field: &str) -> bool {
+    field.is_empty() || field == "*"
+}
+
+fn split(raw: &str) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+
+    let mut first = 0usize;
+    loop {
+        if raw[first..].starts_with('"') {
+            if let Some(length) = raw[first + 1..].find('"') {
+                let last = first + 1 + length;
+                ranges.push(first + 1..last);
+                match raw[last + 1..].find(',') {
+                    Some(offset) => first = last + 1 + offset + 1,
+                    None => break,
+                }
+            } else {
+                ranges.push(first + 1..raw.len());
+                break;
+            }
+        } else if let Some(length) = raw[first..].find(',') {
+            ranges.push(first..first + length);
+            first += length + 1;
+        } else {
+            ranges.push(first..raw.len());
+            break;
+        }
+    }
+
+    ranges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const IPADIC_FEATURES: &str = "名詞,固有名詞,地域,国,*,*,日本,ニッポン,ニッポン";
+
+    const UNIDIC_FEATURES: &str = "名詞,固有名詞,地名,国,*,*,ニッポン,日本,日本,ニッポン";
+
+    #[test]
+    fn new() {
+        let features = Features::new(String::from(IPADIC_FEATURES));
+
+        assert_eq!(features.raw(), IPADIC_FEATURES);
+        assert_eq!(features.layout(), FeatureLayout::Ipadic);
+    }
+
+    #[test]
+    fn with_layout() {
+        let features = Features::with_layout(String::from(UNIDIC_FEATURES), FeatureLayout::Unidic);
+
+        assert_eq!(features.raw(), UNIDIC_FEATURES);
+        assert_eq!(features.layout(), FeatureLayout::Unidic);
+    }
+
+    #[test]
+    fn len() {
+        assert_eq!(Features::new(String::from(IPADIC_FEATURES)).len(), 9);
+        assert_eq!(Features::new(String::new()).len(), 1);
+        assert_eq!(Features::new(String::from("a,")).len(), 2);
+        assert!(!Features::new(String::new()).is_empty());
+    }
+
+    #[test]
+    fn field() {
+        {
+            let features = Features::new(String::from(IPADIC_FEATURES));
+
+            assert_eq!(features.field(0), Some("名詞"));
+            assert_eq!(features.field(4), Some("*"));
+            assert_eq!(features.field(8), Some("ニッポン"));
+            assert!(features.field(9).is_none());
+        }
+        {
+            let features = Features::new(String::from("\"a,b\",c,\"d"));
+
+            assert_eq!(features.fields().collect::<Vec<_>>(), vec!["a,b", "c", "d"]);
+        }
+        {
+            let features = Features::new(String::from("\"a,b\""));
+
+            assert_eq!(features.fields().collect::<Vec<_>>(), vec!["a,b"]);
+        }
+    }
+
+    #[test]
+    fn pos() {
+        {
+            let features = Features::new(String::from(IPADIC_FEATURES));
+
+            assert_eq!(features.pos(), vec!["名詞", "固有名詞", "地域", "国"]);
+        }
+        {
+            let features = Features::new(String::from("助詞,格助詞,一般,*,*,*,が,ガ,ガ"));
+
+            assert_eq!(features.pos(), vec!["助詞", "格助詞", "一般"]);
+        }
+        {
+            let features = Features::with_layout(
+                String::from("noun,common,dog"),
+                FeatureLayout::Custom {
+                    pos_field_count: 2,
+                    reading_index: 5,
+                    lemma_index: 2,
+                },
+            );
+
+            assert_eq!(features.pos(), vec!["noun", "common"]);
+        }
+    }
+
+    #[test]
+    fn reading() {
+        assert_eq!(
+            Features::new(String::from(IPADIC_FEATURES)).reading(),
+            Some("ニッポン")
+        );
+        assert_eq!(
+            Features::with_layout(String::from(UNIDIC_FEATURES), FeatureLayout::Unidic).reading(),
+            Some("ニッポン")
+        );
+        assert!(Features::new(String::from("記号,一般,*,*,*,*,*"))
+            .reading()
+            .is_none());
+    }
+
+    #[test]
+    fn lemma() {
+        assert_eq!(
+            Features::new(String::from(IPADIC_FEATURES)).lemma(),
+            Some("日本")
+        );
+        assert_eq!(
+            Features::with_layout(String::from(UNIDIC_FEATURES), FeatureLayout::Unidic).lemma(),
+            Some("日本")
+        );
+        assert!(Features::new(String::from("記号,一般,*,*,*,*,*"))
+            .lemma()
+            .is_none());
+    }
+
+    #[test]
+    fn eq() {
+        let features1 = Features::new(String::from(IPADIC_FEATURES));
+        let features2 = Features::new(String::from(IPADIC_FEATURES));
+        let _ = features2.field(0);
+        let features3 = Features::with_layout(String::from(IPADIC_FEATURES), FeatureLayout::Unidic);
+
+        assert_eq!(features1, features2);
+        assert_ne!(features1, features3);
+    }
+}
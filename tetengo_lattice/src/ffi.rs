@@ -0,0 +1,1395 @@
+/*!
+ * A C ABI.
+ *
+ * The functions and types follow the naming of the tetengo C interface.
+ * The declarations are available in `include/tetengo_lattice.h`.
+ *
+ * Copyright (C) 2023-2025 kaoru  <https://www.tetengo.org/>
+ */
+
+#![allow(non_camel_case_types, non_snake_case)]
+
+use std::any::Any;
+use std::ffi::{c_char, c_int, c_void, CStr};
+use std::mem::ManuallyDrop;
+use std::ptr;
+use std::rc::Rc;
+use std::slice;
+use std::str;
+
+use crate::constraint::Constraint;
+use crate::constraint_element::ConstraintElement;
+use crate::entry::Entry;
+use crate::hash_map_vocabulary::HashMapVocabulary;
+use crate::input::Input;
+use crate::lattice::Lattice;
+use crate::n_best_iterator::NBestIterator;
+use crate::node::Node;
+use crate::path::Path;
+use crate::string_input::StringInput;
+use crate::vocabulary::Vocabulary;
+use crate::wildcard_constraint_element::WildcardConstraintElement;
+
+/**
+ * A string view.
+ */
+#[derive(Clone, Copy, Debug)]
+#[repr(C)]
+pub struct tetengo_lattice_stringView_t {
+    /**
+     * A pointer to the head.
+     */
+    pub p_head: *const c_char,
+
+    /**
+     * A length.
+     */
+    pub length: usize,
+}
+
+/**
+ * An entry view.
+ */
+#[derive(Clone, Copy, Debug)]
+#[repr(C)]
+pub struct tetengo_lattice_entryView_t {
+    /**
+     * A key.
+     */
+    pub key: tetengo_lattice_stringView_t,
+
+    /**
+     * A value handle.
+     */
+    pub value_handle: *const c_void,
+
+    /**
+     * A cost.
+     */
+    pub cost: c_int,
+}
+
+/**
+ * A pair of a key and entries.
+ */
+#[derive(Clone, Copy, Debug)]
+#[repr(C)]
+pub struct tetengo_lattice_keyEntriesPair_t {
+    /**
+     * A key.
+     */
+    pub key: tetengo_lattice_stringView_t,
+
+    /**
+     * A pointer to the entries.
+     */
+    pub p_entries: *const tetengo_lattice_entryView_t,
+
+    /**
+     * An entry count.
+     */
+    pub entry_count: usize,
+}
+
+/**
+ * A pair of entries and a connection cost.
+ *
+ * A NULL entry pointer stands for the BOS/EOS entry.
+ */
+#[derive(Clone, Copy, Debug)]
+#[repr(C)]
+pub struct tetengo_lattice_entriesConnectionCostPair_t {
+    /**
+     * A pointer to the origin entry.
+     */
+    pub p_from: *const tetengo_lattice_entryView_t,
+
+    /**
+     * A pointer to the destination entry.
+     */
+    pub p_to: *const tetengo_lattice_entryView_t,
+
+    /**
+     * A cost.
+     */
+    pub cost: c_int,
+}
+
+/**
+ * A node.
+ *
+ * The pointers in a node are valid while the object which stores the node is alive.
+ */
+#[derive(Clone, Copy, Debug)]
+#[repr(C)]
+pub struct tetengo_lattice_node_t {
+    /**
+     * A key. The head is NULL for BOS and EOS.
+     */
+    pub key: tetengo_lattice_stringView_t,
+
+    /**
+     * A value handle. NULL for BOS and EOS.
+     */
+    pub value_handle: *const c_void,
+
+    /**
+     * An index in the step.
+     */
+    pub index_in_step: usize,
+
+    /**
+     * A preceding step.
+     */
+    pub preceding_step: usize,
+
+    /**
+     * A pointer to the preceding edge costs.
+     */
+    pub p_preceding_edge_costs: *const c_int,
+
+    /**
+     * A preceding edge cost count.
+     */
+    pub preceding_edge_cost_count: usize,
+
+    /**
+     * A best preceding node index.
+     */
+    pub best_preceding_node: usize,
+
+    /**
+     * A node cost.
+     */
+    pub node_cost: c_int,
+
+    /**
+     * A path cost.
+     */
+    pub path_cost: c_int,
+}
+
+/**
+ * A function returning the hash value of an entry.
+ *
+ * The entry view is NULL for the BOS/EOS entry.
+ */
+pub type tetengo_lattice_entryHash_t =
+    unsafe extern "C" fn(p_entry: *const tetengo_lattice_entryView_t) -> usize;
+
+/**
+ * A function returning `true` when two entries are equal.
+ *
+ * An entry view is NULL for the BOS/EOS entry.
+ */
+pub type tetengo_lattice_entryEqualTo_t = unsafe extern "C" fn(
+    p_one: *const tetengo_lattice_entryView_t,
+    p_another: *const tetengo_lattice_entryView_t,
+) -> bool;
+
+/**
+ * A vocabulary.
+ */
+#[derive(Debug)]
+pub struct tetengo_lattice_vocabulary_t {
+    vocabulary: ManuallyDrop<Box<dyn Vocabulary>>,
+    p_entry_functions: *mut EntryFunctions,
+}
+
+impl Drop for tetengo_lattice_vocabulary_t {
+    fn drop(&mut self) {
+        // SAFETY: The vocabulary referring to the entry functions is dropped before them, and
+        // the entry functions are created with `Box::into_raw()`.
+        unsafe {
+            ManuallyDrop::drop(&mut self.vocabulary);
+            drop(Box::from_raw(self.p_entry_functions));
+        }
+    }
+}
+
+type EntryHashValue = dyn Fn(&Entry) -> u64;
+
+type EntryEqual = dyn Fn(&Entry, &Entry) -> bool;
+
+struct EntryFunctions {
+    hash_value: Box<EntryHashValue>,
+    equal: Box<EntryEqual>,
+}
+
+/**
+ * An input.
+ */
+#[derive(Debug)]
+pub struct tetengo_lattice_input_t {
+    input: Box<dyn Input>,
+}
+
+/**
+ * A lattice.
+ */
+#[derive(Debug)]
+pub struct tetengo_lattice_lattice_t {
+    lattice: Lattice<'static>,
+    eos_node: Option<Node>,
+}
+
+/**
+ * A constraint element.
+ */
+#[derive(Debug)]
+pub struct tetengo_lattice_constraintElement_t {
+    element: Box<dyn ConstraintElement>,
+}
+
+/**
+ * A constraint.
+ */
+#[derive(Debug)]
+pub struct tetengo_lattice_constraint_t {
+    constraint: Constraint<'static>,
+}
+
+/**
+ * An N-best lattice path iterator.
+ */
+#[derive(Debug)]
+pub struct tetengo_lattice_nBestIterator_t {
+    iterator: NBestIterator<'static>,
+    current: Option<Path>,
+}
+
+/**
+ * Creates a hash map vocabulary.
+ *
+ * # Arguments
+ * * `p_entries`        - A pointer to the key-entries pairs.
+ * * `entry_count`      - A key-entries pair count.
+ * * `p_connections`    - A pointer to the entries-connection cost pairs.
+ * * `connection_count` - An entries-connection cost pair count.
+ * * `p_entry_hash`     - A hash function for the entries. Can be NULL.
+ * * `p_entry_equal_to` - An equality function for the entries. Can be NULL.
+ *
+ * The entries are hashed and compared by their keys when `p_entry_hash` and `p_entry_equal_to`
+ * are NULL.
+ *
+ * # Returns
+ * A pointer to a vocabulary. Or NULL when a key is not valid UTF-8.
+ *
+ * # Safety
+ * * `p_entries` must point to `entry_count` valid pairs, or be NULL when `entry_count` is 0.
+ * * `p_connections` must point to `connection_count` valid pairs, or be NULL when
+ *   `connection_count` is 0.
+ * * The functions must be callable while the vocabulary is alive.
+ */
+#[no_mangle]
+pub unsafe extern "C" fn tetengo_lattice_vocabulary_createHashMapVocabulary(
+    p_entries: *const tetengo_lattice_keyEntriesPair_t,
+    entry_count: usize,
+    p_connections: *const tetengo_lattice_entriesConnectionCostPair_t,
+    connection_count: usize,
+    p_entry_hash: Option<tetengo_lattice_entryHash_t>,
+    p_entry_equal_to: Option<tetengo_lattice_entryEqualTo_t>,
+) -> *mut tetengo_lattice_vocabulary_t {
+    unsafe {
+        let mut entry_mappings = Vec::with_capacity(entry_count);
+        for pair in as_slice(p_entries, entry_count) {
+            let Some(key) = to_str(&pair.key) else {
+                return ptr::null_mut();
+            };
+            let Some(entries) = as_slice(pair.p_entries, pair.entry_count)
+                .iter()
+                .map(|entry_view| to_entry(entry_view))
+                .collect::<Option<Vec<_>>>()
+            else {
+                return ptr::null_mut();
+            };
+            entry_mappings.push((key.to_string(), entries));
+        }
+
+        let mut connections = Vec::with_capacity(connection_count);
+        for pair in as_slice(p_connections, connection_count) {
+            let (Some(from), Some(to)) = (
+                to_entry_or_bos_eos(pair.p_from),
+                to_entry_or_bos_eos(pair.p_to),
+            ) else {
+                return ptr::null_mut();
+            };
+            connections.push(((from, to), pair.cost));
+        }
+
+        let entry_functions = EntryFunctions {
+            hash_value: match p_entry_hash {
+                Some(p_entry_hash) => Box::new(move |entry| {
+                    let entry_view = to_entry_view(entry);
+                    p_entry_hash(entry_view.as_ref().map_or(ptr::null(), ptr::from_ref)) as u64
+                }),
+                None => Box::new(entry_hash_value),
+            },
+            equal: match p_entry_equal_to {
+                Some(p_entry_equal_to) => Box::new(move |one, another| {
+                    let one_view = to_entry_view(one);
+                    let another_view = to_entry_view(another);
+                    p_entry_equal_to(
+                        one_view.as_ref().map_or(ptr::null(), ptr::from_ref),
+                        another_view.as_ref().map_or(ptr::null(), ptr::from_ref),
+                    )
+                }),
+                None => Box::new(entry_equal),
+            },
+        };
+        let p_entry_functions = Box::into_raw(Box::new(entry_functions));
+        // The entry functions are freed after the vocabulary in the destructor.
+        let entry_functions = &*p_entry_functions;
+        let vocabulary = HashMapVocabulary::new(
+            entry_mappings,
+            connections,
+            entry_functions.hash_value.as_ref(),
+            entry_functions.equal.as_ref(),
+        );
+        Box::into_raw(Box::new(tetengo_lattice_vocabulary_t {
+            vocabulary: ManuallyDrop::new(Box::new(vocabulary)),
+            p_entry_functions,
+        }))
+    }
+}
+
+/**
+ * Destroys a vocabulary.
+ *
+ * # Arguments
+ * * `p_vocabulary` - A pointer to a vocabulary.
+ *
+ * # Safety
+ * * `p_vocabulary` must be created by this library, or be NULL.
+ * * No lattice using the vocabulary may be alive.
+ */
+#[no_mangle]
+pub unsafe extern "C" fn tetengo_lattice_vocabulary_destroy(
+    p_vocabulary: *mut tetengo_lattice_vocabulary_t,
+) {
+    unsafe {
+        if !p_vocabulary.is_null() {
+            drop(Box::from_raw(p_vocabulary));
+        }
+    }
+}
+
+/**
+ * Creates a string input.
+ *
+ * # Arguments
+ * * `value` - A NUL-terminated UTF-8 value.
+ *
+ * # Returns
+ * A pointer to an input. Or NULL when `value` is NULL or not valid UTF-8.
+ *
+ * # Safety
+ * * `value` must be a valid NUL-terminated string, or be NULL.
+ */
+#[no_mangle]
+pub unsafe extern "C" fn tetengo_lattice_input_createStringInput(
+    value: *const c_char,
+) -> *mut tetengo_lattice_input_t {
+    unsafe {
+        if value.is_null() {
+            return ptr::null_mut();
+        }
+        let Ok(value) = CStr::from_ptr(value).to_str() else {
+            return ptr::null_mut();
+        };
+        Box::into_raw(Box::new(tetengo_lattice_input_t {
+            input: Box::new(StringInput::new(value.to_string())),
+        }))
+    }
+}
+
+/**
+ * Destroys an input.
+ *
+ * # Arguments
+ * * `p_input` - A pointer to an input.
+ *
+ * # Safety
+ * * `p_input` must be created by this library and not be pushed back to a lattice, or be NULL.
+ */
+#[no_mangle]
+pub unsafe extern "C" fn tetengo_lattice_input_destroy(p_input: *mut tetengo_lattice_input_t) {
+    unsafe {
+        if !p_input.is_null() {
+            drop(Box::from_raw(p_input));
+        }
+    }
+}
+
+/**
+ * Returns the length of an input.
+ *
+ * # Arguments
+ * * `p_input` - A pointer to an input.
+ *
+ * # Returns
+ * The length. Or 0 when `p_input` is NULL.
+ *
+ * # Safety
+ * * `p_input` must be created by this library, or be NULL.
+ */
+#[no_mangle]
+pub unsafe extern "C" fn tetengo_lattice_input_length(
+    p_input: *const tetengo_lattice_input_t,
+) -> usize {
+    unsafe { p_input.as_ref().map_or(0, |input| input.input.length()) }
+}
+
+/**
+ * Creates a lattice.
+ *
+ * # Arguments
+ * * `p_vocabulary` - A pointer to a vocabulary.
+ *
+ * # Returns
+ * A pointer to a lattice. Or NULL when `p_vocabulary` is NULL.
+ *
+ * # Safety
+ * * `p_vocabulary` must be created by this library, or be NULL.
+ * * The vocabulary must outlive the lattice.
+ */
+#[no_mangle]
+pub unsafe extern "C" fn tetengo_lattice_lattice_create(
+    p_vocabulary: *const tetengo_lattice_vocabulary_t,
+) -> *mut tetengo_lattice_lattice_t {
+    unsafe {
+        let Some(vocabulary) = p_vocabulary.as_ref() else {
+            return ptr::null_mut();
+        };
+        Box::into_raw(Box::new(tetengo_lattice_lattice_t {
+            lattice: Lattice::new(vocabulary.vocabulary.as_ref()),
+            eos_node: None,
+        }))
+    }
+}
+
+/**
+ * Destroys a lattice.
+ *
+ * # Arguments
+ * * `p_lattice` - A pointer to a lattice.
+ *
+ * # Safety
+ * * `p_lattice` must be created by this library, or be NULL.
+ * * No N-best iterator using the lattice may be alive.
+ */
+#[no_mangle]
+pub unsafe extern "C" fn tetengo_lattice_lattice_destroy(
+    p_lattice: *mut tetengo_lattice_lattice_t,
+) {
+    unsafe {
+        if !p_lattice.is_null() {
+            drop(Box::from_raw(p_lattice));
+        }
+    }
+}
+
+/**
+ * Returns the step count.
+ *
+ * # Arguments
+ * * `p_lattice` - A pointer to a lattice.
+ *
+ * # Returns
+ * The step count. Or 0 when `p_lattice` is NULL.
+ *
+ * # Safety
+ * * `p_lattice` must be created by this library, or be NULL.
+ */
+#[no_mangle]
+pub unsafe extern "C" fn tetengo_lattice_lattice_stepCount(
+    p_lattice: *const tetengo_lattice_lattice_t,
+) -> usize {
+    unsafe {
+        p_lattice
+            .as_ref()
+            .map_or(0, |lattice| lattice.lattice.step_count())
+    }
+}
+
+/**
+ * Pushes back an input.
+ *
+ * The lattice takes the ownership of the input, even when it fails.
+ *
+ * # Arguments
+ * * `p_lattice` - A pointer to a lattice.
+ * * `p_input`   - A pointer to an input.
+ *
+ * # Returns
+ * `true` on success.
+ *
+ * # Safety
+ * * `p_lattice` must be created by this library, or be NULL.
+ * * `p_input` must be created by this library and not be pushed back yet, or be NULL.
+ */
+#[no_mangle]
+pub unsafe extern "C" fn tetengo_lattice_lattice_pushBack(
+    p_lattice: *mut tetengo_lattice_lattice_t,
+    p_input: *mut tetengo_lattice_input_t,
+) -> bool {
+    unsafe {
+        if p_input.is_null() {
+            return false;
+        }
+        let input = Box::from_raw(p_input);
+        let Some(lattice) = p_lattice.as_mut() else {
+            return false;
+        };
+        lattice.lattice.push_back(input.input).is_ok()
+    }
+}
+
+/**
+ * Settles a lattice.
+ *
+ * The lattice keeps the EOS node until the next settlement.
+ *
+ * # Arguments
+ * * `p_lattice`  - A pointer to a lattice.
+ * * `p_eos_node` - The storage for an EOS node. Can be NULL.
+ *
+ * # Returns
+ * `true` on success.
+ *
+ * # Safety
+ * * `p_lattice` must be created by this library, or be NULL.
+ * * `p_eos_node` must be valid for writes, or be NULL.
+ */
+#[no_mangle]
+pub unsafe extern "C" fn tetengo_lattice_lattice_settle(
+    p_lattice: *mut tetengo_lattice_lattice_t,
+    p_eos_node: *mut tetengo_lattice_node_t,
+) -> bool {
+    unsafe {
+        let Some(lattice) = p_lattice.as_mut() else {
+            return false;
+        };
+        let Ok(eos_node) = lattice.lattice.settle() else {
+            return false;
+        };
+        let eos_node = lattice.eos_node.insert(eos_node);
+        if let Some(p_eos_node) = p_eos_node.as_mut() {
+            *p_eos_node = to_c_node(eos_node);
+        }
+        true
+    }
+}
+
+/**
+ * Creates a node constraint element.
+ *
+ * The element matches the node equal to the specified one, whatever step it belongs to.
+ *
+ * # Arguments
+ * * `p_node` - A pointer to a node.
+ *
+ * # Returns
+ * A pointer to a constraint element. Or NULL when `p_node` is NULL or its key is not valid
+ * UTF-8.
+ *
+ * # Safety
+ * * `p_node` must point to a valid node, or be NULL.
+ */
+#[no_mangle]
+pub unsafe extern "C" fn tetengo_lattice_constraintElement_createNodeConstraintElement(
+    p_node: *const tetengo_lattice_node_t,
+) -> *mut tetengo_lattice_constraintElement_t {
+    unsafe {
+        let Some(node) = p_node.as_ref().and_then(|node| from_c_node(node)) else {
+            return ptr::null_mut();
+        };
+        Box::into_raw(Box::new(tetengo_lattice_constraintElement_t {
+            element: Box::new(StepFreeNodeConstraintElement { node }),
+        }))
+    }
+}
+
+/**
+ * Creates a wildcard constraint element.
+ *
+ * # Arguments
+ * * `preceding_step` - An index of a preceding step.
+ *
+ * # Returns
+ * A pointer to a constraint element.
+ */
+#[no_mangle]
+pub extern "C" fn tetengo_lattice_constraintElement_createWildcardConstraintElement(
+    preceding_step: usize,
+) -> *mut tetengo_lattice_constraintElement_t {
+    Box::into_raw(Box::new(tetengo_lattice_constraintElement_t {
+        element: Box::new(WildcardConstraintElement::new(preceding_step)),
+    }))
+}
+
+/**
+ * Destroys a constraint element.
+ *
+ * # Arguments
+ * * `p_constraint_element` - A pointer to a constraint element.
+ *
+ * # Safety
+ * * `p_constraint_element` must be created by this library and not be passed to a constraint,
+ *   or be NULL.
+ */
+#[no_mangle]
+pub unsafe extern "C" fn tetengo_lattice_constraintElement_destroy(
+    p_constraint_element: *mut tetengo_lattice_constraintElement_t,
+) {
+    unsafe {
+        if !p_constraint_element.is_null() {
+            drop(Box::from_raw(p_constraint_element));
+        }
+    }
+}
+
+/**
+ * Creates an empty constraint.
+ *
+ * It matches any path.
+ *
+ * # Returns
+ * A pointer to a constraint.
+ */
+#[no_mangle]
+pub extern "C" fn tetengo_lattice_constraint_createEmpty() -> *mut tetengo_lattice_constraint_t {
+    Box::into_raw(Box::new(tetengo_lattice_constraint_t {
+        constraint: Constraint::new(),
+    }))
+}
+
+/**
+ * Creates a constraint.
+ *
+ * The constraint takes the ownership of the elements of the pattern, even when it fails.
+ *
+ * # Arguments
+ * * `pp_pattern`     - A pointer to the pointers to the constraint elements of a pattern.
+ * * `pattern_length` - A pattern length.
+ *
+ * # Returns
+ * A pointer to a constraint. Or NULL when an element is NULL.
+ *
+ * # Safety
+ * * `pp_pattern` must point to `pattern_length` pointers to the constraint elements created by
+ *   this library and not passed to another constraint, or be NULL when `pattern_length` is 0.
+ */
+#[no_mangle]
+pub unsafe extern "C" fn tetengo_lattice_constraint_create(
+    pp_pattern: *const *mut tetengo_lattice_constraintElement_t,
+    pattern_length: usize,
+) -> *mut tetengo_lattice_constraint_t {
+    unsafe {
+        let elements = as_slice(pp_pattern, pattern_length)
+            .iter()
+            .map(|&p_element| (!p_element.is_null()).then(|| Box::from_raw(p_element).element))
+            .collect::<Vec<_>>();
+        let Some(pattern) = elements.into_iter().collect::<Option<Vec<_>>>() else {
+            return ptr::null_mut();
+        };
+        Box::into_raw(Box::new(tetengo_lattice_constraint_t {
+            constraint: Constraint::new_with_pattern(pattern),
+        }))
+    }
+}
+
+/**
+ * Destroys a constraint.
+ *
+ * # Arguments
+ * * `p_constraint` - A pointer to a constraint.
+ *
+ * # Safety
+ * * `p_constraint` must be created by this library and not be passed to an N-best iterator, or
+ *   be NULL.
+ */
+#[no_mangle]
+pub unsafe extern "C" fn tetengo_lattice_constraint_destroy(
+    p_constraint: *mut tetengo_lattice_constraint_t,
+) {
+    unsafe {
+        if !p_constraint.is_null() {
+            drop(Box::from_raw(p_constraint));
+        }
+    }
+}
+
+/**
+ * Creates an N-best lattice path iterator.
+ *
+ * The iterator takes the ownership of the constraint, even when it fails.
+ * The first path is the best path.
+ *
+ * # Arguments
+ * * `p_lattice`    - A pointer to a lattice.
+ * * `p_eos_node`   - A pointer to the EOS node of the lattice.
+ * * `p_constraint` - A pointer to a constraint.
+ *
+ * # Returns
+ * A pointer to an N-best lattice path iterator. Or NULL when an argument is NULL or
+ * `p_eos_node` is not an EOS node.
+ *
+ * # Safety
+ * * `p_lattice` must be created by this library, or be NULL.
+ * * `p_eos_node` must point to a valid node, or be NULL.
+ * * `p_constraint` must be created by this library and not be passed to another N-best
+ *   iterator, or be NULL.
+ * * The lattice must outlive the iterator and must not be modified while the iterator is alive.
+ */
+#[no_mangle]
+pub unsafe extern "C" fn tetengo_lattice_nBestIterator_create(
+    p_lattice: *const tetengo_lattice_lattice_t,
+    p_eos_node: *const tetengo_lattice_node_t,
+    p_constraint: *mut tetengo_lattice_constraint_t,
+) -> *mut tetengo_lattice_nBestIterator_t {
+    unsafe {
+        if p_constraint.is_null() {
+            return ptr::null_mut();
+        }
+        let constraint = Box::from_raw(p_constraint).constraint;
+        let Some(lattice) = p_lattice.as_ref() else {
+            return ptr::null_mut();
+        };
+        let Some(eos_node) = p_eos_node
+            .as_ref()
+            .and_then(|eos_node| from_c_node(eos_node))
+            .filter(Node::is_eos)
+        else {
+            return ptr::null_mut();
+        };
+        let mut iterator = NBestIterator::new(&lattice.lattice, eos_node, Box::new(constraint));
+        let current = iterator.next();
+        Box::into_raw(Box::new(tetengo_lattice_nBestIterator_t {
+            iterator,
+            current,
+        }))
+    }
+}
+
+/**
+ * Destroys an N-best lattice path iterator.
+ *
+ * # Arguments
+ * * `p_iterator` - A pointer to an N-best lattice path iterator.
+ *
+ * # Safety
+ * * `p_iterator` must be created by this library, or be NULL.
+ */
+#[no_mangle]
+pub unsafe extern "C" fn tetengo_lattice_nBestIterator_destroy(
+    p_iterator: *mut tetengo_lattice_nBestIterator_t,
+) {
+    unsafe {
+        if !p_iterator.is_null() {
+            drop(Box::from_raw(p_iterator));
+        }
+    }
+}
+
+/**
+ * Returns `true` when the iterator has the current path.
+ *
+ * # Arguments
+ * * `p_iterator` - A pointer to an N-best lattice path iterator.
+ *
+ * # Returns
+ * `true` when the iterator has the current path.
+ *
+ * # Safety
+ * * `p_iterator` must be created by this library, or be NULL.
+ */
+#[no_mangle]
+pub unsafe extern "C" fn tetengo_lattice_nBestIterator_hasNext(
+    p_iterator: *const tetengo_lattice_nBestIterator_t,
+) -> bool {
+    unsafe {
+        p_iterator
+            .as_ref()
+            .is_some_and(|iterator| iterator.current.is_some())
+    }
+}
+
+/**
+ * Returns the cost of the current path.
+ *
+ * # Arguments
+ * * `p_iterator` - A pointer to an N-best lattice path iterator.
+ *
+ * # Returns
+ * The cost of the current path. Or INT_MAX when there is no current path.
+ *
+ * # Safety
+ * * `p_iterator` must be created by this library, or be NULL.
+ */
+#[no_mangle]
+pub unsafe extern "C" fn tetengo_lattice_nBestIterator_pathCost(
+    p_iterator: *const tetengo_lattice_nBestIterator_t,
+) -> c_int {
+    unsafe {
+        p_iterator
+            .as_ref()
+            .and_then(|iterator| iterator.current.as_ref())
+            .map_or(c_int::MAX, Path::cost)
+    }
+}
+
+/**
+ * Returns the nodes of the current path.
+ *
+ * The nodes are valid until the iterator is advanced or destroyed.
+ *
+ * # Arguments
+ * * `p_iterator` - A pointer to an N-best lattice path iterator.
+ * * `p_nodes`    - The storage for the nodes. Can be NULL.
+ * * `capacity`   - The capacity of the storage.
+ *
+ * # Returns
+ * The node count of the current path. Or 0 when there is no current path.
+ *
+ * # Safety
+ * * `p_iterator` must be created by this library, or be NULL.
+ * * `p_nodes` must be valid for `capacity` writes, or be NULL.
+ */
+#[no_mangle]
+pub unsafe extern "C" fn tetengo_lattice_nBestIterator_pathNodes(
+    p_iterator: *const tetengo_lattice_nBestIterator_t,
+    p_nodes: *mut tetengo_lattice_node_t,
+    capacity: usize,
+) -> usize {
+    unsafe {
+        let Some(path) = p_iterator
+            .as_ref()
+            .and_then(|iterator| iterator.current.as_ref())
+        else {
+            return 0;
+        };
+        if !p_nodes.is_null() {
+            for (i, node) in path.nodes().iter().take(capacity).enumerate() {
+                *p_nodes.add(i) = to_c_node(node);
+            }
+        }
+        path.nodes().len()
+    }
+}
+
+/**
+ * Advances the iterator.
+ *
+ * # Arguments
+ * * `p_iterator` - A pointer to an N-best lattice path iterator.
+ *
+ * # Safety
+ * * `p_iterator` must be created by this library, or be NULL.
+ */
+#[no_mangle]
+pub unsafe extern "C" fn tetengo_lattice_nBestIterator_next(
+    p_iterator: *mut tetengo_lattice_nBestIterator_t,
+) {
+    unsafe {
+        if let Some(iterator) = p_iterator.as_mut() {
+            iterator.current = iterator.iterator.next();
+        }
+    }
+}
+
+/**
+ * A node constraint element ignoring the steps of the nodes.
+ *
+ * A node passed through the C ABI has no step, since the tetengo C interface does not have it.
+ */
+#[derive(Debug)]
+struct StepFreeNodeConstraintElement {
+    node: Node,
+}
+
+impl ConstraintElement for StepFreeNodeConstraintElement {
+    fn matches(&self, node: &Node) -> i32 {
+        if *node == self.node.with_step(node.step()) {
+            0
+        } else {
+            -1
+        }
+    }
+}
+
+unsafe fn as_slice<'a, T>(p_head: *const T, length: usize) -> &'a [T] {
+    unsafe {
+        if p_head.is_null() || length == 0 {
+            &[]
+        } else {
+            slice::from_raw_parts(p_head, length)
+        }
+    }
+}
+
+unsafe fn to_str<'a>(string_view: &tetengo_lattice_stringView_t) -> Option<&'a str> {
+    unsafe {
+        let bytes = as_slice(string_view.p_head.cast::<u8>(), string_view.length);
+        str::from_utf8(bytes).ok()
+    }
+}
+
+unsafe fn to_entry(entry_view: &tetengo_lattice_entryView_t) -> Option<Entry> {
+    unsafe {
+        Some(Entry::new(
+            Box::new(StringInput::new(to_str(&entry_view.key)?.to_string())),
+            Box::new(entry_view.value_handle),
+            entry_view.cost,
+        ))
+    }
+}
+
+unsafe fn to_entry_or_bos_eos(p_entry_view: *const tetengo_lattice_entryView_t) -> Option<Entry> {
+    unsafe {
+        p_entry_view
+            .as_ref()
+            .map_or(Some(Entry::BosEos), |entry_view| to_entry(entry_view))
+    }
+}
+
+fn to_entry_view(entry: &Entry) -> Option<tetengo_lattice_entryView_t> {
+    let key = entry.key()?;
+    Some(tetengo_lattice_entryView_t {
+        key: to_string_view(key),
+        value_handle: to_value_handle(entry.value()),
+        cost: entry.cost(),
+    })
+}
+
+fn to_string_view(key: &dyn Input) -> tetengo_lattice_stringView_t {
+    key.downcast_ref::<StringInput>().map_or(
+        tetengo_lattice_stringView_t {
+            p_head: ptr::null(),
+            length: 0,
+        },
+        |key| tetengo_lattice_stringView_t {
+            p_head: key.value().as_ptr().cast(),
+            length: key.value().len(),
+        },
+    )
+}
+
+fn to_value_handle(value: Option<&dyn Any>) -> *const c_void {
+    value
+        .and_then(|value| value.downcast_ref::<*const c_void>())
+        .map_or(ptr::null(), |&value_handle| value_handle)
+}
+
+fn to_c_node(node: &Node) -> tetengo_lattice_node_t {
+    tetengo_lattice_node_t {
+        key: node.key().map_or(
+            tetengo_lattice_stringView_t {
+                p_head: ptr::null(),
+                length: 0,
+            },
+            to_string_view,
+        ),
+        value_handle: to_value_handle(node.value()),
+        index_in_step: node.index_in_step(),
+        preceding_step: node.preceding_step(),
+        p_preceding_edge_costs: node.preceding_edge_costs().as_ptr(),
+        preceding_edge_cost_count: node.preceding_edge_costs().len(),
+        best_preceding_node: node.best_preceding_node(),
+        node_cost: node.node_cost(),
+        path_cost: node.path_cost(),
+    }
+}
+
+unsafe fn from_c_node(node: &tetengo_lattice_node_t) -> Option<Node> {
+    unsafe {
+        let preceding_edge_costs =
+            Rc::new(as_slice(node.p_preceding_edge_costs, node.preceding_edge_cost_count).to_vec());
+        if node.key.p_head.is_null() {
+            return Some(if node.preceding_step == usize::MAX {
+                Node::bos(preceding_edge_costs)
+            } else {
+                Node::eos(
+                    node.preceding_step,
+                    preceding_edge_costs,
+                    node.best_preceding_node,
+                    node.path_cost,
+                )
+            });
+        }
+        Some(Node::new(
+            Box::new(StringInput::new(to_str(&node.key)?.to_string())),
+            Box::new(node.value_handle),
+            node.index_in_step,
+            node.preceding_step,
+            preceding_edge_costs,
+            node.best_preceding_node,
+            node.node_cost,
+            node.path_cost,
+        ))
+    }
+}
+
+fn entry_hash_value(entry: &Entry) -> u64 {
+    entry.key().map_or(0, |key| key.hash_value())
+}
+
+fn entry_equal(one: &Entry, other: &Entry) -> bool {
+    match (one.key(), other.key()) {
+        (Some(one_key), Some(other_key)) => one_key.equal_to(other_key),
+        (None, None) => true,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::CString;
+    use std::hash::{DefaultHasher, Hash, Hasher};
+    use std::rc::Rc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    fn to_string_view(string: &str) -> tetengo_lattice_stringView_t {
+        tetengo_lattice_stringView_t {
+            p_head: string.as_ptr().cast(),
+            length: string.len(),
+        }
+    }
+
+    fn to_entry_view(key: &str, cost: c_int) -> tetengo_lattice_entryView_t {
+        tetengo_lattice_entryView_t {
+            key: to_string_view(key),
+            value_handle: key.as_ptr().cast(),
+            cost,
+        }
+    }
+
+    /*
+        BOS--(Hakata)--kamome/local415--(Tosu)--local813--(Omuta)--EOS
+                  |                                            |
+                  +---------------------ariake-----------------+
+    */
+    fn create_vocabulary(
+        p_entry_hash: Option<tetengo_lattice_entryHash_t>,
+        p_entry_equal_to: Option<tetengo_lattice_entryEqualTo_t>,
+    ) -> *mut tetengo_lattice_vocabulary_t {
+        let kamome = [to_entry_view("Hakata-Tosu", 840)];
+        let local813 = [to_entry_view("Tosu-Omuta", 860)];
+        let ariake = [to_entry_view("Hakata-Tosu-Omuta", 2150)];
+        let entries = [
+            tetengo_lattice_keyEntriesPair_t {
+                key: to_string_view("[HakataTosu]"),
+                p_entries: kamome.as_ptr(),
+                entry_count: kamome.len(),
+            },
+            tetengo_lattice_keyEntriesPair_t {
+                key: to_string_view("[TosuOmuta]"),
+                p_entries: local813.as_ptr(),
+                entry_count: local813.len(),
+            },
+            tetengo_lattice_keyEntriesPair_t {
+                key: to_string_view("[HakataTosu][TosuOmuta]"),
+                p_entries: ariake.as_ptr(),
+                entry_count: ariake.len(),
+            },
+        ];
+        let connections = [
+            tetengo_lattice_entriesConnectionCostPair_t {
+                p_from: ptr::null(),
+                p_to: &kamome[0],
+                cost: 800,
+            },
+            tetengo_lattice_entriesConnectionCostPair_t {
+                p_from: ptr::null(),
+                p_to: &ariake[0],
+                cost: 700,
+            },
+            tetengo_lattice_entriesConnectionCostPair_t {
+                p_from: &kamome[0],
+                p_to: &local813[0],
+                cost: 600,
+            },
+            tetengo_lattice_entriesConnectionCostPair_t {
+                p_from: &local813[0],
+                p_to: ptr::null(),
+                cost: 3000,
+            },
+            tetengo_lattice_entriesConnectionCostPair_t {
+                p_from: &ariake[0],
+                p_to: ptr::null(),
+                cost: 2000,
+            },
+        ];
+        unsafe {
+            tetengo_lattice_vocabulary_createHashMapVocabulary(
+                entries.as_ptr(),
+                entries.len(),
+                connections.as_ptr(),
+                connections.len(),
+                p_entry_hash,
+                p_entry_equal_to,
+            )
+        }
+    }
+
+    static ENTRY_HASH_CALL_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+    static ENTRY_EQUAL_TO_CALL_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+    unsafe extern "C" fn entry_hash(p_entry: *const tetengo_lattice_entryView_t) -> usize {
+        let _ = ENTRY_HASH_CALL_COUNT.fetch_add(1, Ordering::Relaxed);
+        unsafe {
+            let mut hasher = DefaultHasher::new();
+            p_entry
+                .as_ref()
+                .and_then(|entry| to_str(&entry.key))
+                .hash(&mut hasher);
+            hasher.finish() as usize
+        }
+    }
+
+    unsafe extern "C" fn entry_equal_to(
+        p_one: *const tetengo_lattice_entryView_t,
+        p_another: *const tetengo_lattice_entryView_t,
+    ) -> bool {
+        let _ = ENTRY_EQUAL_TO_CALL_COUNT.fetch_add(1, Ordering::Relaxed);
+        unsafe {
+            p_one.as_ref().and_then(|one| to_str(&one.key))
+                == p_another.as_ref().and_then(|another| to_str(&another.key))
+        }
+    }
+
+    unsafe fn push_back(p_lattice: *mut tetengo_lattice_lattice_t, input: &str) -> bool {
+        unsafe {
+            let input = CString::new(input).unwrap();
+            let p_input = tetengo_lattice_input_createStringInput(input.as_ptr());
+            assert!(!p_input.is_null());
+            tetengo_lattice_lattice_pushBack(p_lattice, p_input)
+        }
+    }
+
+    #[test]
+    fn lattice() {
+        unsafe {
+            let p_vocabulary = create_vocabulary(None, None);
+            assert!(!p_vocabulary.is_null());
+            let p_lattice = tetengo_lattice_lattice_create(p_vocabulary);
+            assert!(!p_lattice.is_null());
+            assert_eq!(tetengo_lattice_lattice_stepCount(p_lattice), 1);
+
+            assert!(push_back(p_lattice, "[HakataTosu]"));
+            assert!(push_back(p_lattice, "[TosuOmuta]"));
+            assert_eq!(tetengo_lattice_lattice_stepCount(p_lattice), 3);
+            assert!(!push_back(p_lattice, "[OmutaKumamoto]"));
+
+            let mut eos_node = to_c_node(&Node::bos(Rc::new(Vec::new())));
+            assert!(tetengo_lattice_lattice_settle(p_lattice, &mut eos_node));
+            assert!(eos_node.key.p_head.is_null());
+            assert_eq!(eos_node.preceding_step, 2);
+            assert_eq!(eos_node.path_cost, 4850);
+
+            let p_iterator = tetengo_lattice_nBestIterator_create(
+                p_lattice,
+                &eos_node,
+                tetengo_lattice_constraint_createEmpty(),
+            );
+            assert!(!p_iterator.is_null());
+            {
+                assert!(tetengo_lattice_nBestIterator_hasNext(p_iterator));
+                assert_eq!(tetengo_lattice_nBestIterator_pathCost(p_iterator), 4850);
+                let node_count =
+                    tetengo_lattice_nBestIterator_pathNodes(p_iterator, ptr::null_mut(), 0);
+                assert_eq!(node_count, 3);
+                let mut nodes = vec![eos_node; node_count];
+                let _ = tetengo_lattice_nBestIterator_pathNodes(
+                    p_iterator,
+                    nodes.as_mut_ptr(),
+                    nodes.len(),
+                );
+                assert_eq!(to_str(&nodes[1].key), Some("Hakata-Tosu-Omuta"));
+                assert_eq!(nodes[1].node_cost, 2150);
+                assert!(!nodes[1].value_handle.is_null());
+            }
+            tetengo_lattice_nBestIterator_next(p_iterator);
+            {
+                assert!(tetengo_lattice_nBestIterator_hasNext(p_iterator));
+                assert_eq!(tetengo_lattice_nBestIterator_pathCost(p_iterator), 6100);
+                assert_eq!(
+                    tetengo_lattice_nBestIterator_pathNodes(p_iterator, ptr::null_mut(), 0),
+                    4
+                );
+            }
+            tetengo_lattice_nBestIterator_next(p_iterator);
+            assert!(!tetengo_lattice_nBestIterator_hasNext(p_iterator));
+            assert_eq!(
+                tetengo_lattice_nBestIterator_pathCost(p_iterator),
+                c_int::MAX
+            );
+
+            tetengo_lattice_nBestIterator_destroy(p_iterator);
+            tetengo_lattice_lattice_destroy(p_lattice);
+            tetengo_lattice_vocabulary_destroy(p_vocabulary);
+        }
+    }
+
+    #[test]
+    fn entry_functions() {
+        unsafe {
+            let p_vocabulary = create_vocabulary(Some(entry_hash), Some(entry_equal_to));
+            assert!(!p_vocabulary.is_null());
+            let p_lattice = tetengo_lattice_lattice_create(p_vocabulary);
+            assert!(push_back(p_lattice, "[HakataTosu]"));
+            assert!(push_back(p_lattice, "[TosuOmuta]"));
+            let mut eos_node = to_c_node(&Node::bos(Rc::new(Vec::new())));
+            assert!(tetengo_lattice_lattice_settle(p_lattice, &mut eos_node));
+            assert_eq!(eos_node.path_cost, 4850);
+
+            assert!(ENTRY_HASH_CALL_COUNT.load(Ordering::Relaxed) > 0);
+            assert!(ENTRY_EQUAL_TO_CALL_COUNT.load(Ordering::Relaxed) > 0);
+
+            tetengo_lattice_lattice_destroy(p_lattice);
+            tetengo_lattice_vocabulary_destroy(p_vocabulary);
+        }
+    }
+
+    #[test]
+    fn constraint() {
+        unsafe {
+            let p_vocabulary = create_vocabulary(None, None);
+            let p_lattice = tetengo_lattice_lattice_create(p_vocabulary);
+            assert!(push_back(p_lattice, "[HakataTosu]"));
+            assert!(push_back(p_lattice, "[TosuOmuta]"));
+            let mut eos_node = to_c_node(&Node::bos(Rc::new(Vec::new())));
+            assert!(tetengo_lattice_lattice_settle(p_lattice, &mut eos_node));
+
+            let mut pattern = {
+                let p_iterator = tetengo_lattice_nBestIterator_create(
+                    p_lattice,
+                    &eos_node,
+                    tetengo_lattice_constraint_createEmpty(),
+                );
+                tetengo_lattice_nBestIterator_next(p_iterator);
+                let mut nodes = vec![eos_node; 4];
+                assert_eq!(
+                    tetengo_lattice_nBestIterator_pathNodes(
+                        p_iterator,
+                        nodes.as_mut_ptr(),
+                        nodes.len()
+                    ),
+                    4
+                );
+                let pattern = vec![
+                    tetengo_lattice_constraintElement_createNodeConstraintElement(&nodes[0]),
+                    tetengo_lattice_constraintElement_createNodeConstraintElement(&nodes[1]),
+                    tetengo_lattice_constraintElement_createWildcardConstraintElement(1),
+                    tetengo_lattice_constraintElement_createNodeConstraintElement(&nodes[3]),
+                ];
+                tetengo_lattice_nBestIterator_destroy(p_iterator);
+                pattern
+            };
+            assert!(pattern.iter().all(|p_element| !p_element.is_null()));
+            let p_constraint = tetengo_lattice_constraint_create(pattern.as_ptr(), pattern.len());
+            assert!(!p_constraint.is_null());
+            pattern.clear();
+
+            let p_iterator =
+                tetengo_lattice_nBestIterator_create(p_lattice, &eos_node, p_constraint);
+            assert!(!p_iterator.is_null());
+            assert!(tetengo_lattice_nBestIterator_hasNext(p_iterator));
+            assert_eq!(tetengo_lattice_nBestIterator_pathCost(p_iterator), 6100);
+            tetengo_lattice_nBestIterator_next(p_iterator);
+            assert!(!tetengo_lattice_nBestIterator_hasNext(p_iterator));
+
+            tetengo_lattice_nBestIterator_destroy(p_iterator);
+            tetengo_lattice_lattice_destroy(p_lattice);
+            tetengo_lattice_vocabulary_destroy(p_vocabulary);
+        }
+    }
+
+    #[test]
+    fn invalid_utf8() {
+        unsafe {
+            let invalid = CString::new(vec![0xE3, 0x81]).unwrap();
+            assert!(tetengo_lattice_input_createStringInput(invalid.as_ptr()).is_null());
+
+            let invalid_key = tetengo_lattice_stringView_t {
+                p_head: invalid.as_ptr(),
+                length: invalid.as_bytes().len(),
+            };
+            let entries = [tetengo_lattice_entryView_t {
+                key: invalid_key,
+                value_handle: ptr::null(),
+                cost: 0,
+            }];
+            let key_entries_pairs = [tetengo_lattice_keyEntriesPair_t {
+                key: to_string_view("key"),
+                p_entries: entries.as_ptr(),
+                entry_count: entries.len(),
+            }];
+            assert!(tetengo_lattice_vocabulary_createHashMapVocabulary(
+                key_entries_pairs.as_ptr(),
+                key_entries_pairs.len(),
+                ptr::null(),
+                0,
+                None,
+                None,
+            )
+            .is_null());
+        }
+    }
+
+    #[test]
+    fn input() {
+        unsafe {
+            let value = CString::new("Hakata").unwrap();
+            let p_input = tetengo_lattice_input_createStringInput(value.as_ptr());
+            assert!(!p_input.is_null());
+            assert_eq!(tetengo_lattice_input_length(p_input), 6);
+            tetengo_lattice_input_destroy(p_input);
+        }
+    }
+
+    #[test]
+    fn null_handles() {
+        unsafe {
+            assert!(tetengo_lattice_lattice_create(ptr::null()).is_null());
+            assert_eq!(tetengo_lattice_lattice_stepCount(ptr::null()), 0);
+            assert!(tetengo_lattice_input_createStringInput(ptr::null()).is_null());
+            assert_eq!(tetengo_lattice_input_length(ptr::null()), 0);
+            assert!(!tetengo_lattice_lattice_pushBack(
+                ptr::null_mut(),
+                ptr::null_mut()
+            ));
+            assert!(!tetengo_lattice_lattice_settle(
+                ptr::null_mut(),
+                ptr::null_mut()
+            ));
+            assert!(
+                tetengo_lattice_constraintElement_createNodeConstraintElement(ptr::null())
+                    .is_null()
+            );
+            assert!(tetengo_lattice_constraint_create(&ptr::null_mut(), 1).is_null());
+            assert!(tetengo_lattice_nBestIterator_create(
+                ptr::null(),
+                ptr::null(),
+                tetengo_lattice_constraint_createEmpty()
+            )
+            .is_null());
+            assert!(tetengo_lattice_nBestIterator_create(
+                ptr::null(),
+                ptr::null(),
+                ptr::null_mut()
+            )
+            .is_null());
+            assert!(!tetengo_lattice_nBestIterator_hasNext(ptr::null()));
+            tetengo_lattice_nBestIterator_destroy(ptr::null_mut());
+            tetengo_lattice_constraint_destroy(ptr::null_mut());
+            tetengo_lattice_constraintElement_destroy(ptr::null_mut());
+            tetengo_lattice_input_destroy(ptr::null_mut());
+            tetengo_lattice_lattice_destroy(ptr::null_mut());
+            tetengo_lattice_vocabulary_destroy(ptr::null_mut());
+        }
+    }
+}
@@ -10,10 +10,11 @@ use std::fmt::Debug;
 use std::hash::{Hash, Hasher};
 use std::rc::Rc;
 
-use anyhow::Result;
-
 use crate::connection::Connection;
+use crate::connection_matrix::ConnectionMatrix;
 use crate::entry::Entry;
+use crate::enumerable_vocabulary::EnumerableVocabulary;
+use crate::error::Result;
 use crate::node::Node;
 use crate::string_input::StringInput;
 use crate::vocabulary::Vocabulary;
@@ -75,6 +76,7 @@ type ConnectionMap<'a> = HashMap<(HashableEntry<'a>, HashableEntry<'a>), i32>;
 pub struct HashMapVocabulary<'a> {
     entry_map: EntryMap,
     connection_map: ConnectionMap<'a>,
+    connection_matrix: Option<ConnectionMatrix>,
     entry_hash_value: &'a dyn Fn(&Entry) -> u64,
     entry_equal: &'a dyn Fn(&Entry, &Entry) -> bool,
 }
@@ -84,6 +86,7 @@ impl Debug for HashMapVocabulary<'_> {
         f.debug_struct("HashMapVocabulary")
             .field("entry_map", &self.entry_map)
             .field("connection_map", &self.connection_map)
+            .field("connection_matrix", &self.connection_matrix)
             .field(
                 "entry_hash_value",
                 &type_name_of_val(&self.entry_hash_value),
@@ -114,11 +117,26 @@ impl<'a> HashMapVocabulary<'a> {
         HashMapVocabulary {
             entry_map,
             connection_map,
+            connection_matrix: None,
             entry_hash_value,
             entry_equal,
         }
     }
 
+    /**
+     * Sets a connection matrix.
+     *
+     * The connection between entries both with context IDs is looked up in the matrix instead
+     * of the connections.
+     *
+     * # Arguments
+     * * `connection_matrix` - A connection matrix.
+     */
+    pub fn connection_matrix(mut self, connection_matrix: ConnectionMatrix) -> Self {
+        self.connection_matrix = Some(connection_matrix);
+        self
+    }
+
     fn make_entry_map(entry_mappings: Vec<(String, Vec<Entry>)>) -> EntryMap {
         let mut entry_map = EntryMap::new();
         for (key, entries) in entry_mappings {
@@ -157,21 +175,39 @@ impl Vocabulary for HashMapVocabulary<'_> {
 
     fn find_connection(&self, from: &Node, to: &Entry) -> Result<Connection> {
         let from_entry = from.entry().as_ref().clone();
+        if let Some(connection_matrix) = &self.connection_matrix {
+            if let Some(connection) = connection_matrix.connection_between(&from_entry, to)? {
+                return Ok(connection);
+            }
+        }
         let key = (
             HashableEntry::new(from_entry, self.entry_hash_value, self.entry_equal),
             HashableEntry::new(to.clone(), self.entry_hash_value, self.entry_equal),
         );
         let Some(found) = self.connection_map.get(&key) else {
-            return Ok(Connection::new(i32::MAX));
+            return Ok(Connection::infinity());
         };
         Ok(Connection::new(*found))
     }
 }
 
+impl EnumerableVocabulary for HashMapVocabulary<'_> {
+    fn entries(&self) -> Result<Vec<Rc<Entry>>> {
+        let mut keys = self.entry_map.keys().collect::<Vec<_>>();
+        keys.sort();
+        Ok(keys
+            .into_iter()
+            .flat_map(|key| self.entry_map[key].iter().cloned())
+            .collect())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::rc::Rc;
 
+    use crate::entry::MiddleEntry;
+
     use super::*;
 
     fn entry_hash_value(entry: &Entry) -> u64 {
@@ -191,17 +227,16 @@ mod tests {
 
     fn make_node(entry: Rc<Entry>) -> Node {
         static PRECEDING_EDGE_COSTS: Vec<i32> = Vec::new();
-        match entry.as_ref() {
-            Entry::BosEos => Node::bos(Rc::new(PRECEDING_EDGE_COSTS.clone())),
-            Entry::Middle(_) => Node::new_with_entry(
+        match MiddleEntry::try_from(entry) {
+            Ok(entry) => Node::new_with_middle_entry(
                 entry,
                 0,
                 usize::MAX,
                 Rc::new(PRECEDING_EDGE_COSTS.clone()),
                 usize::MAX,
                 i32::MAX,
-            )
-            .unwrap(),
+            ),
+            Err(_) => Node::bos(Rc::new(PRECEDING_EDGE_COSTS.clone())),
         }
     }
 
@@ -397,6 +432,46 @@ mod tests {
         }
     }
 
+    #[test]
+    fn find_entries_at() {
+        let entry_mappings = ["みず", "みずほ", "ほ"]
+            .iter()
+            .map(|&key| {
+                (
+                    String::from(key),
+                    vec![Entry::new(
+                        Box::new(StringInput::new(String::from(key))),
+                        Box::new(key),
+                        42,
+                    )],
+                )
+            })
+            .collect::<Vec<_>>();
+        let vocaburary =
+            HashMapVocabulary::new(entry_mappings, Vec::new(), &entry_hash_value, &entry_equal);
+        let input = StringInput::new(String::from("みずほさくら"));
+
+        let found = vocaburary
+            .find_entries_at(&input, 0)
+            .unwrap()
+            .into_iter()
+            .map(|(length, entry)| {
+                (
+                    length,
+                    *entry.value().unwrap().downcast_ref::<&str>().unwrap(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(found, vec![(6, "みず"), (9, "みずほ")]);
+
+        let found = vocaburary.find_entries_at(&input, 6).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].0, 3);
+
+        assert!(vocaburary.find_entries_at(&input, 18).unwrap().is_empty());
+        assert!(vocaburary.find_entries_at(&input, 19).is_err());
+    }
+
     #[test]
     fn find_connection() {
         {
@@ -481,5 +556,125 @@ mod tests {
                 assert_eq!(connection.cost(), i32::MAX);
             }
         }
+        {
+            let mizuho = Entry::new(
+                Box::new(StringInput::new(String::from("みずほ"))),
+                Box::new(String::from("瑞穂")),
+                42,
+            );
+            let entry_mappings = vec![
+                (String::from("みずほ"), vec![mizuho.with_context_ids(1, 2)]),
+                (String::from("さくら"), vec![mizuho.clone()]),
+            ];
+            let connections = vec![((Entry::BosEos, mizuho), 4242)];
+            let mut connection_matrix = ConnectionMatrix::new(3, 3).unwrap();
+            connection_matrix.set_cost(0, 1, 100).unwrap();
+            connection_matrix.set_cost(2, 0, 200).unwrap();
+            let vocaburary = HashMapVocabulary::new(
+                entry_mappings,
+                connections,
+                &entry_hash_value,
+                &entry_equal,
+            )
+            .connection_matrix(connection_matrix);
+
+            let entries_mizuho = vocaburary
+                .find_entries(&StringInput::new(String::from("みずほ")))
+                .unwrap();
+            let entries_sakura = vocaburary
+                .find_entries(&StringInput::new(String::from("さくら")))
+                .unwrap();
+
+            {
+                let connection = vocaburary
+                    .find_connection(&Node::bos(Rc::new(Vec::new())), &entries_mizuho[0])
+                    .unwrap();
+
+                assert_eq!(connection.cost(), 100);
+            }
+            {
+                let connection = vocaburary
+                    .find_connection(&make_node(entries_mizuho[0].clone()), &Entry::BosEos)
+                    .unwrap();
+
+                assert_eq!(connection.cost(), 200);
+            }
+            {
+                let connection = vocaburary
+                    .find_connection(&Node::bos(Rc::new(Vec::new())), &entries_sakura[0])
+                    .unwrap();
+
+                assert_eq!(connection.cost(), 4242);
+            }
+        }
+    }
+
+    #[test]
+    fn entries() {
+        {
+            let entry_mappings = Vec::<(String, Vec<Entry>)>::new();
+            let connections = Vec::<((Entry, Entry), i32)>::new();
+            let vocaburary = HashMapVocabulary::new(
+                entry_mappings,
+                connections,
+                &entry_hash_value,
+                &entry_equal,
+            );
+
+            let entries = vocaburary.entries().unwrap();
+            assert!(entries.is_empty());
+        }
+        {
+            let entry_mappings = vec![
+                (
+                    String::from("みずほ"),
+                    vec![Entry::new(
+                        Box::new(StringInput::new(String::from("みずほ"))),
+                        Box::new(String::from("瑞穂")),
+                        42,
+                    )],
+                ),
+                (
+                    String::from("さくら"),
+                    vec![
+                        Entry::new(
+                            Box::new(StringInput::new(String::from("さくら"))),
+                            Box::new(String::from("桜")),
+                            24,
+                        ),
+                        Entry::new(
+                            Box::new(StringInput::new(String::from("さくら"))),
+                            Box::new(String::from("さくら")),
+                            2424,
+                        ),
+                    ],
+                ),
+            ];
+            let connections = Vec::<((Entry, Entry), i32)>::new();
+            let vocaburary = HashMapVocabulary::new(
+                entry_mappings,
+                connections,
+                &entry_hash_value,
+                &entry_equal,
+            );
+
+            let entries = vocaburary.entries().unwrap();
+            let values = entries
+                .iter()
+                .map(|entry| {
+                    entry
+                        .value()
+                        .unwrap()
+                        .downcast_ref::<String>()
+                        .unwrap()
+                        .as_str()
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(values, vec!["桜", "さくら", "瑞穂"]);
+            assert_eq!(
+                entries.iter().map(|entry| entry.cost()).collect::<Vec<_>>(),
+                vec![24, 2424, 42]
+            );
+        }
     }
 }
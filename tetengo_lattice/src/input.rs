@@ -7,7 +7,7 @@
 use std::any::Any;
 use std::fmt::Debug;
 
-use anyhow::Result;
+use crate::error::Result;
 
 /**
  * An input error.
@@ -131,18 +131,58 @@ impl dyn Input {
     pub fn downcast_mut<T: Input>(&mut self) -> Option<&mut T> {
         self.as_any_mut().downcast_mut::<T>()
     }
+
+    /**
+     * Downcasts a box of this object to a concrete type.
+     *
+     * # Returns
+     * The box of the object of the concrete type. Or the original box when the concrete type is
+     * not `T`.
+     */
+    pub fn downcast<T: Input>(self: Box<Self>) -> std::result::Result<Box<T>, Box<dyn Input>> {
+        if !self.is::<T>() {
+            return Err(self);
+        }
+        // SAFETY: The concrete type of the object is confirmed to be `T`.
+        Ok(unsafe { Box::from_raw(Box::into_raw(self).cast::<T>()) })
+    }
+
+    /**
+     * Returns `true` if the concrete type of this input is `T` and this input is equal to an
+     * object.
+     *
+     * It helps to implement `Input::equal_to()` with `PartialEq`.
+     *
+     * # Arguments
+     * * `object` - An object.
+     *
+     * # Returns
+     * `true` if the concrete type of this input is `T` and this input is equal to the object.
+     */
+    pub fn eq_concrete<T: Input + PartialEq>(&self, object: &T) -> bool {
+        self.downcast_ref::<T>() == Some(object)
+    }
+}
+
+/**
+ * Inputs are compared with `Input::equal_to()`.
+ */
+impl PartialEq for dyn Input {
+    fn eq(&self, other: &Self) -> bool {
+        self.equal_to(other)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    #[derive(Debug)]
+    #[derive(Debug, PartialEq)]
     struct ConcreteInput1;
 
     impl Input for ConcreteInput1 {
-        fn equal_to(&self, _: &dyn Input) -> bool {
-            unimplemented!()
+        fn equal_to(&self, other: &dyn Input) -> bool {
+            other.eq_concrete(self)
         }
 
         fn hash_value(&self) -> u64 {
@@ -229,4 +269,33 @@ mod tests {
         assert!(input_ref.downcast_mut::<ConcreteInput1>().is_some());
         assert!(input_ref.downcast_mut::<ConcreteInput2>().is_none());
     }
+
+    #[test]
+    fn downcast() {
+        let input: Box<dyn Input> = Box::new(ConcreteInput1);
+
+        let input = input.downcast::<ConcreteInput2>().unwrap_err();
+        let input = input.downcast::<ConcreteInput1>().unwrap();
+
+        assert_eq!(*input, ConcreteInput1);
+    }
+
+    #[test]
+    fn eq_concrete() {
+        let input1: &dyn Input = &ConcreteInput1;
+        let input2: &dyn Input = &ConcreteInput2;
+
+        assert!(input1.eq_concrete(&ConcreteInput1));
+        assert!(!input2.eq_concrete(&ConcreteInput1));
+    }
+
+    #[test]
+    fn eq() {
+        let input1: Box<dyn Input> = Box::new(ConcreteInput1);
+        let input2: Box<dyn Input> = Box::new(ConcreteInput1);
+        let input3: Box<dyn Input> = Box::new(ConcreteInput2);
+
+        assert!(input1 == input2);
+        assert!(input1 != input3);
+    }
 }
@@ -4,49 +4,121 @@
  * Copyright (C) 2023-2025 kaoru  <https://www.tetengo.org/>
  */
 
-use std::fmt::Debug;
+use std::any::{type_name_of_val, Any};
+use std::fmt::{self, Debug, Formatter};
+use std::iter;
+use std::mem;
+use std::ops::Range;
 use std::rc::Rc;
 
-use anyhow::Result;
-
+use crate::beam_search_strategy::BeamSearchStrategy;
+use crate::connection::Connection;
 use crate::entry::Entry;
+use crate::error::{LatticeError, Result};
+use crate::exact_search_strategy::ExactSearchStrategy;
 use crate::input::Input;
 use crate::node::Node;
+use crate::search_strategy::SearchStrategy;
+use crate::string_input::StringInput;
 use crate::vocabulary::Vocabulary;
 
 /**
- * A lattice error.
+ * A node overflow policy.
+ *
+ * It determines what to do when the nodes in a step exceed the node capacity.
+ */
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum NodeOverflowPolicy {
+    /**
+     * Drops the nodes with the highest path costs.
+     */
+    DropWorst,
+
+    /**
+     * Fails with an error.
+     */
+    Error,
+}
+
+/**
+ * A skipped input.
+ *
+ * It is the value of the entries of the nodes skipping inputs, which are placed when a skip cost
+ * is set to the lattice builder.
+ */
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct SkippedInput;
+
+/**
+ * A reason why a node is dropped.
  */
-#[derive(Clone, Copy, Debug, thiserror::Error)]
-pub enum LatticeError {
+#[cfg(feature = "debug")]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DropReason {
+    /**
+     * The search strategy, e.g. the beam, did not keep the node.
+     */
+    SearchStrategy,
+
     /**
-     * The step is too large.
+     * The node exceeded the node capacity under `NodeOverflowPolicy::DropWorst`.
      */
-    #[error("The step is too large.")]
-    StepIsTooLarge,
+    NodeCapacity,
+}
+
+/**
+ * A dropped node.
+ *
+ * It is a node whose entry was found but which was dropped from its step.
+ */
+#[cfg(feature = "debug")]
+#[derive(Clone, Debug)]
+pub struct DroppedNode {
+    node: Node,
+    reason: DropReason,
+}
 
+#[cfg(feature = "debug")]
+impl DroppedNode {
     /**
-     * No node is found for the input.
+     * Returns the node.
+     *
+     * Its index in the step is the one before it was dropped.
+     *
+     * # Returns
+     * The node.
      */
-    #[error("No node is found for the input.")]
-    NoNodeIsFoundForTheInput,
+    pub const fn node(&self) -> &Node {
+        &self.node
+    }
 
     /**
-     * No input.
+     * Returns the reason.
+     *
+     * # Returns
+     * The reason.
      */
-    #[error("No input.")]
-    NoInput,
+    pub const fn reason(&self) -> DropReason {
+        self.reason
+    }
 }
 
 #[derive(Debug)]
 struct GraphStep {
     input_tail: usize,
     nodes: Vec<Node>,
+    #[cfg(feature = "debug")]
+    dropped_nodes: Vec<DroppedNode>,
 }
 
 impl GraphStep {
     const fn new(input_tail: usize, nodes: Vec<Node>) -> Self {
-        Self { input_tail, nodes }
+        Self {
+            input_tail,
+            nodes,
+            #[cfg(feature = "debug")]
+            dropped_nodes: Vec::new(),
+        }
     }
 
     const fn input_tail(&self) -> usize {
@@ -58,17 +130,338 @@ impl GraphStep {
     }
 }
 
+type UnknownEntries<'a> = Rc<dyn Fn(&dyn Input) -> Vec<Rc<Entry>> + 'a>;
+
+type EntriesOf<'a> = Box<dyn Fn(&dyn Input) -> Vec<Rc<Entry>> + 'a>;
+
+struct CharacterClass<'a> {
+    is_member: Box<dyn Fn(char) -> bool + 'a>,
+    entries: EntriesOf<'a>,
+    max_split_length: usize,
+}
+
+type CharacterClassEntries<'a> = Rc<CharacterClass<'a>>;
+
+type StepObserver<'a> = Rc<dyn Fn(usize, &[Node]) + 'a>;
+
+type PendingNode = (Rc<Entry>, usize, Range<usize>, usize, i32);
+
+/**
+ * Buffers recycled by `Lattice::reset()`.
+ */
+#[derive(Debug, Default)]
+struct Buffers {
+    arenas: Vec<Vec<i32>>,
+    node_vectors: Vec<Vec<Node>>,
+    pending_nodes: Vec<PendingNode>,
+}
+
+/**
+ * A lattice builder.
+ */
+pub struct LatticeBuilder<'a> {
+    vocabulary: &'a dyn Vocabulary,
+    search_strategy: Box<dyn SearchStrategy>,
+    node_capacity: usize,
+    node_overflow_policy: NodeOverflowPolicy,
+    unknown_entries: Option<UnknownEntries<'a>>,
+    character_class_entries: Vec<CharacterClassEntries<'a>>,
+    skip_cost: Option<i32>,
+    bos_cost: i32,
+    eos_cost: i32,
+    trigram_states: bool,
+    step_observer: Option<StepObserver<'a>>,
+}
+
+impl<'a> LatticeBuilder<'a> {
+    /**
+     * Sets a search strategy.
+     *
+     * # Arguments
+     * * `search_strategy` - A search strategy.
+     */
+    pub fn search_strategy(mut self, search_strategy: Box<dyn SearchStrategy>) -> Self {
+        self.search_strategy = search_strategy;
+        self
+    }
+
+    /**
+     * Sets a beam search strategy with a beam width.
+     *
+     * # Arguments
+     * * `beam_width` - A beam width. Zero is treated as one.
+     */
+    pub fn beam_width(self, beam_width: usize) -> Self {
+        self.search_strategy(Box::new(BeamSearchStrategy::new(beam_width)))
+    }
+
+    /**
+     * Sets a node capacity per step.
+     *
+     * It is applied after the search strategy selects the nodes to keep.
+     *
+     * # Arguments
+     * * `node_capacity` - A node capacity per step.
+     */
+    pub fn node_capacity(mut self, node_capacity: usize) -> Self {
+        self.node_capacity = node_capacity;
+        self
+    }
+
+    /**
+     * Sets a node overflow policy.
+     *
+     * # Arguments
+     * * `node_overflow_policy` - A node overflow policy.
+     */
+    pub fn node_overflow_policy(mut self, node_overflow_policy: NodeOverflowPolicy) -> Self {
+        self.node_overflow_policy = node_overflow_policy;
+        self
+    }
+
+    /**
+     * Sets an unknown word handler.
+     *
+     * When the vocabulary has no entry for any key ending at a pushed input, the handler is
+     * called with the pushed input, and its entries are placed after the preceding step instead
+     * of failing with `LatticeError::NoNodeIsFoundForTheInput`.
+     *
+     * # Arguments
+     * * `unknown_entries` - A function returning the entries for an unknown input.
+     */
+    pub fn unknown_entries(
+        mut self,
+        unknown_entries: impl Fn(&dyn Input) -> Vec<Rc<Entry>> + 'a,
+    ) -> Self {
+        self.unknown_entries = Some(Rc::new(unknown_entries));
+        self
+    }
+
+    /**
+     * Adds a character class with its entries.
+     *
+     * When the key of a `StringInput` ending at a pushed input consists only of the characters
+     * in the class, the entries for the key are placed in addition to the ones from the
+     * vocabulary. The keys are the runs of the characters starting at the steps, such as digits
+     * for numbers or Latin letters for words. The unknown word handler is not called when any
+     * entry of the character classes is placed.
+     *
+     * # Arguments
+     * * `is_member` - A function returning `true` when a character is in the class.
+     * * `entries`   - A function returning the entries for a key.
+     */
+    pub fn character_class_entries(
+        self,
+        is_member: impl Fn(char) -> bool + 'a,
+        entries: impl Fn(&dyn Input) -> Vec<Rc<Entry>> + 'a,
+    ) -> Self {
+        self.merged_character_class_entries(is_member, entries, usize::MAX)
+    }
+
+    /**
+     * Adds a character class with its entries, merging long runs.
+     *
+     * It is the same as `character_class_entries()` except that the keys longer than the
+     * maximum split length are merged into the longest run, as the unknown word processing of
+     * MeCab does. So the entries are placed for the runs of up to the maximum split length
+     * characters and for the longest run, which keeps the width of the steps manageable.
+     *
+     * # Arguments
+     * * `is_member`        - A function returning `true` when a character is in the class.
+     * * `entries`          - A function returning the entries for a key.
+     * * `max_split_length` - A maximum split length in characters.
+     */
+    pub fn merged_character_class_entries(
+        mut self,
+        is_member: impl Fn(char) -> bool + 'a,
+        entries: impl Fn(&dyn Input) -> Vec<Rc<Entry>> + 'a,
+        max_split_length: usize,
+    ) -> Self {
+        self.character_class_entries.push(Rc::new(CharacterClass {
+            is_member: Box::new(is_member),
+            entries: Box::new(entries),
+            max_split_length,
+        }));
+        self
+    }
+
+    /**
+     * Sets a skip cost.
+     *
+     * When it is set, a node skipping each pushed input is placed after the preceding step in
+     * addition to the nodes from the vocabulary, so that a path reaches the EOS even through the
+     * inputs the vocabulary does not recognize. The entry of the node has the pushed input as its
+     * key, `SkippedInput` as its value and the skip cost as its cost. The connections to and
+     * from the node cost 0, so the skip cost should exceed the costs of the ordinary nodes and
+     * connections for an input.
+     *
+     * # Arguments
+     * * `skip_cost` - A skip cost.
+     */
+    pub fn skip_cost(mut self, skip_cost: i32) -> Self {
+        self.skip_cost = Some(skip_cost);
+        self
+    }
+
+    /**
+     * Sets a BOS cost.
+     *
+     * It is added to the costs of the connections from the BOS found in the vocabulary, so that
+     * the costs of starting a path can be tuned without modifying the vocabulary. It is 0 by
+     * default.
+     *
+     * # Arguments
+     * * `bos_cost` - A BOS cost.
+     */
+    pub fn bos_cost(mut self, bos_cost: i32) -> Self {
+        self.bos_cost = bos_cost;
+        self
+    }
+
+    /**
+     * Sets an EOS cost.
+     *
+     * It is added to the costs of the connections to the EOS found in the vocabulary, so that
+     * the costs of ending a path can be tuned without modifying the vocabulary. It is 0 by
+     * default.
+     *
+     * # Arguments
+     * * `eos_cost` - An EOS cost.
+     */
+    pub fn eos_cost(mut self, eos_cost: i32) -> Self {
+        self.eos_cost = eos_cost;
+        self
+    }
+
+    /**
+     * Makes the lattice keep trigram states.
+     *
+     * When it is set, a node is placed for each pair of an entry and a preceding node, and the
+     * node is reached only from the preceding node. So `Vocabulary::find_trigram_connection()`
+     * is called with the actual two preceding nodes on each path, and the best path and the path
+     * costs of the N-best paths are the ones of the trigram connections. The nodes are
+     * multiplied by the preceding nodes, so a beam or a node capacity should be set as well.
+     *
+     * When it is not set, a node is placed for each entry, and the preceding node passed to
+     * `Vocabulary::find_trigram_connection()` is the best preceding node of the origin node,
+     * which only approximates the trigram connections.
+     *
+     * # Arguments
+     * * `trigram_states` - `true` to keep trigram states.
+     */
+    pub fn trigram_states(mut self, trigram_states: bool) -> Self {
+        self.trigram_states = trigram_states;
+        self
+    }
+
+    /**
+     * Sets a step observer.
+     *
+     * It is called with the index and the nodes of each step pushed back.
+     *
+     * # Arguments
+     * * `step_observer` - A step observer.
+     */
+    pub fn step_observer(mut self, step_observer: impl Fn(usize, &[Node]) + 'a) -> Self {
+        self.step_observer = Some(Rc::new(step_observer));
+        self
+    }
+
+    /**
+     * Builds a lattice.
+     *
+     * # Returns
+     * A lattice.
+     */
+    pub fn build(self) -> Lattice<'a> {
+        let mut lattice = Lattice {
+            vocabulary: self.vocabulary,
+            search_strategy: Rc::from(self.search_strategy),
+            node_capacity: self.node_capacity,
+            node_overflow_policy: self.node_overflow_policy,
+            unknown_entries: self.unknown_entries,
+            character_class_entries: self.character_class_entries,
+            skip_cost: self.skip_cost,
+            bos_cost: self.bos_cost,
+            eos_cost: self.eos_cost,
+            trigram_states: self.trigram_states,
+            step_observer: self.step_observer,
+            input: None,
+            graph: Vec::new(),
+            buffers: Buffers::default(),
+        };
+        lattice.graph.push(Rc::new(Lattice::bos_step()));
+        lattice
+    }
+}
+
+impl Debug for LatticeBuilder<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LatticeBuilder")
+            .field("vocabulary", &self.vocabulary)
+            .field("search_strategy", &self.search_strategy)
+            .field("node_capacity", &self.node_capacity)
+            .field("node_overflow_policy", &self.node_overflow_policy)
+            .field("unknown_entries", &type_name_of_val(&self.unknown_entries))
+            .field(
+                "character_class_entries",
+                &type_name_of_val(&self.character_class_entries),
+            )
+            .field("skip_cost", &self.skip_cost)
+            .field("bos_cost", &self.bos_cost)
+            .field("eos_cost", &self.eos_cost)
+            .field("trigram_states", &self.trigram_states)
+            .field("step_observer", &type_name_of_val(&self.step_observer))
+            .finish()
+    }
+}
+
 /**
  * A lattice.
  */
-#[derive(Debug)]
 pub struct Lattice<'a> {
     vocabulary: &'a dyn Vocabulary,
+    search_strategy: Rc<dyn SearchStrategy>,
+    node_capacity: usize,
+    node_overflow_policy: NodeOverflowPolicy,
+    unknown_entries: Option<UnknownEntries<'a>>,
+    character_class_entries: Vec<CharacterClassEntries<'a>>,
+    skip_cost: Option<i32>,
+    bos_cost: i32,
+    eos_cost: i32,
+    trigram_states: bool,
+    step_observer: Option<StepObserver<'a>>,
     input: Option<Box<dyn Input>>,
-    graph: Vec<GraphStep>,
+    graph: Vec<Rc<GraphStep>>,
+    buffers: Buffers,
 }
 
 impl<'a> Lattice<'a> {
+    /**
+     * Returns a lattice builder.
+     *
+     * # Arguments
+     * * `vocabulary` - A vocabulary.
+     *
+     * # Returns
+     * A lattice builder.
+     */
+    pub fn builder(vocabulary: &'a dyn Vocabulary) -> LatticeBuilder<'a> {
+        LatticeBuilder {
+            vocabulary,
+            search_strategy: Box::new(ExactSearchStrategy::new()),
+            node_capacity: usize::MAX,
+            node_overflow_policy: NodeOverflowPolicy::DropWorst,
+            unknown_entries: None,
+            character_class_entries: Vec::new(),
+            skip_cost: None,
+            bos_cost: 0,
+            eos_cost: 0,
+            trigram_states: false,
+            step_observer: None,
+        }
+    }
+
     /**
      * Creates a lattice.
      *
@@ -76,13 +469,152 @@ impl<'a> Lattice<'a> {
      * * `vocabulary` - A vocabulary.
      */
     pub fn new(vocabulary: &'a dyn Vocabulary) -> Self {
-        let mut self_ = Self {
-            vocabulary,
-            input: None,
-            graph: Vec::new(),
+        Self::builder(vocabulary).build()
+    }
+
+    /**
+     * Creates a lattice with a search strategy.
+     *
+     * # Arguments
+     * * `vocabulary`      - A vocabulary.
+     * * `search_strategy` - A search strategy.
+     */
+    pub fn new_with_search_strategy(
+        vocabulary: &'a dyn Vocabulary,
+        search_strategy: Box<dyn SearchStrategy>,
+    ) -> Self {
+        Self::builder(vocabulary)
+            .search_strategy(search_strategy)
+            .build()
+    }
+
+    /**
+     * Forks this lattice.
+     *
+     * The fork shares the steps already pushed back with this lattice, and the steps pushed back
+     * afterward to either lattice do not affect the other.
+     *
+     * # Returns
+     * A fork of this lattice.
+     *
+     * # Errors
+     * * When the input cannot be copied.
+     */
+    pub fn fork(&self) -> Result<Self> {
+        let input = match &self.input {
+            Some(input) => Some(input.create_subrange(0, input.length())?),
+            None => None,
+        };
+        Ok(Self {
+            vocabulary: self.vocabulary,
+            search_strategy: self.search_strategy.clone(),
+            node_capacity: self.node_capacity,
+            node_overflow_policy: self.node_overflow_policy,
+            unknown_entries: self.unknown_entries.clone(),
+            character_class_entries: self.character_class_entries.clone(),
+            skip_cost: self.skip_cost,
+            bos_cost: self.bos_cost,
+            eos_cost: self.eos_cost,
+            trigram_states: self.trigram_states,
+            step_observer: self.step_observer.clone(),
+            input,
+            graph: self.graph.clone(),
+            buffers: Buffers::default(),
+        })
+    }
+
+    /**
+     * Resets this lattice.
+     *
+     * The inputs and the steps pushed back are removed, and the lattice returns to the state
+     * just after it is built. The buffers of the removed steps are retained and reused by the
+     * following pushes, so that decoding many inputs with one lattice does not allocate them
+     * for each input. The buffers still referred to by nodes, such as the ones in paths alive
+     * or in forks, are not reused.
+     */
+    pub fn reset(&mut self) {
+        self.input = None;
+        self.recycle_steps(1);
+    }
+
+    /**
+     * Replaces inputs pushed back.
+     *
+     * The inputs from `first` to `first + count` in the order pushed back are replaced with
+     * `inputs`. The steps before the replaced inputs are kept, and the steps after them are
+     * recomputed, since the paths through them change. An edit in the middle of a long input,
+     * e.g. in an editor, recomputes only the steps from the edit.
+     * When it fails, the lattice keeps the steps successfully recomputed.
+     *
+     * # Arguments
+     * * `first`  - The index of the first input to replace.
+     * * `count`  - The count of the inputs to replace.
+     * * `inputs` - Inputs.
+     *
+     * # Errors
+     * * When `first + count` exceeds the count of the inputs pushed back.
+     * * When the inputs cannot be split or pushed back.
+     */
+    pub fn replace_inputs(
+        &mut self,
+        first: usize,
+        count: usize,
+        inputs: impl IntoIterator<Item = Box<dyn Input>>,
+    ) -> Result<()> {
+        let Some(last) = first.checked_add(count) else {
+            return Err(LatticeError::StepIsTooLarge);
+        };
+        if last >= self.graph.len() {
+            return Err(LatticeError::StepIsTooLarge);
+        }
+
+        let (head, following_inputs) = match &self.input {
+            Some(input) => {
+                let following_inputs = self
+                    .graph
+                    .windows(2)
+                    .skip(last)
+                    .map(|pair| {
+                        input.create_subrange(
+                            pair[0].input_tail(),
+                            pair[1].input_tail() - pair[0].input_tail(),
+                        )
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                let head = if first == 0 {
+                    None
+                } else {
+                    Some(input.create_subrange(0, self.graph[first].input_tail())?)
+                };
+                (head, following_inputs)
+            }
+            None => (None, Vec::new()),
         };
-        self_.graph.push(Self::bos_step());
-        self_
+        self.input = head;
+        self.recycle_steps(first + 1);
+
+        for input in inputs.into_iter().chain(following_inputs) {
+            self.push_back(input)?;
+        }
+        Ok(())
+    }
+
+    fn recycle_steps(&mut self, first: usize) {
+        for step in self.graph.drain(first..) {
+            let Ok(step) = Rc::try_unwrap(step) else {
+                continue;
+            };
+            let mut nodes = step.nodes;
+            let arena = nodes
+                .first()
+                .map(|node| node.preceding_edge_cost_arena().clone());
+            nodes.clear();
+            self.buffers.node_vectors.push(nodes);
+            if let Some(Ok(mut arena)) = arena.map(Rc::try_unwrap) {
+                arena.clear();
+                self.buffers.arenas.push(arena);
+            }
+        }
     }
 
     fn bos_step() -> GraphStep {
@@ -90,6 +622,16 @@ impl<'a> Lattice<'a> {
         GraphStep::new(0, nodes)
     }
 
+    /**
+     * Returns the vocabulary.
+     *
+     * # Returns
+     * The vocabulary.
+     */
+    pub fn vocabulary(&self) -> &'a dyn Vocabulary {
+        self.vocabulary
+    }
+
     /**
      * Returns the step count.
      *
@@ -114,12 +656,35 @@ impl<'a> Lattice<'a> {
      */
     pub fn nodes_at(&self, step: usize) -> Result<&[Node]> {
         if step >= self.graph.len() {
-            Err(LatticeError::StepIsTooLarge.into())
+            Err(LatticeError::StepIsTooLarge)
         } else {
             Ok(self.graph[step].nodes.as_slice())
         }
     }
 
+    /**
+     * Returns the nodes dropped at the specified step.
+     *
+     * It tells why an entry found in the vocabulary does not appear in the lattice.
+     *
+     * # Arguments
+     * * `step` - A step.
+     *
+     * # Returns
+     * The dropped nodes in the order dropped.
+     *
+     * # Errors
+     * * When step is too large.
+     */
+    #[cfg(feature = "debug")]
+    pub fn dropped_nodes_at(&self, step: usize) -> Result<&[DroppedNode]> {
+        if step >= self.graph.len() {
+            Err(LatticeError::StepIsTooLarge)
+        } else {
+            Ok(self.graph[step].dropped_nodes.as_slice())
+        }
+    }
+
     /**
      * Pushes back an input.
      *
@@ -128,8 +693,48 @@ impl<'a> Lattice<'a> {
      *
      * # Errors
      * * When no node is found for the input.
+     * * When the nodes exceed the node capacity under `NodeOverflowPolicy::Error`.
+     * * When the vocabulary fails.
      */
     pub fn push_back(&mut self, input: Box<dyn Input>) -> Result<()> {
+        self.push_back_impl(input, None)
+    }
+
+    /**
+     * Pushes back an input, knowing the next input.
+     *
+     * It is the same as `push_back()` except that `Vocabulary::prefetch()` is called with the
+     * keys the next input will look up. The hint is issued after the entries for the input are
+     * found and before the nodes for them are computed, so that a vocabulary can fetch the
+     * entries for the next input while the lattice computes the nodes.
+     *
+     * # Arguments
+     * * `input`      - An input.
+     * * `next_input` - The next input.
+     *
+     * # Errors
+     * * When no node is found for the input.
+     * * When the nodes exceed the node capacity under `NodeOverflowPolicy::Error`.
+     * * When the next input cannot be appended to the input.
+     * * When the vocabulary fails.
+     */
+    pub fn push_back_with_next(
+        &mut self,
+        input: Box<dyn Input>,
+        next_input: &dyn Input,
+    ) -> Result<()> {
+        self.push_back_impl(input, Some(next_input))
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(step = self.graph.len()))
+    )]
+    fn push_back_impl(
+        &mut self,
+        input: Box<dyn Input>,
+        next_input: Option<&dyn Input>,
+    ) -> Result<()> {
         if let Some(self_input) = &mut self.input {
             self_input.append(input)?;
         } else {
@@ -140,59 +745,267 @@ impl<'a> Lattice<'a> {
             None => unreachable!(),
         };
 
-        let mut nodes = Vec::new();
-        let mut node_preceding_edge_costs = Vec::new();
-        for i in 0..self.graph.len() {
-            let step = &self.graph[i];
-
-            let node_key = match self_input
-                .create_subrange(step.input_tail(), self_input.length() - step.input_tail())
-            {
-                Ok(node_key) => node_key,
-                Err(e) => return Err(e),
-            };
-            let found = self.vocabulary.find_entries(node_key.as_ref())?;
-
-            let mut preceding_edge_cost_indexes = Vec::new();
-            for e in &found {
-                let preceding_edge_costs = self.preceding_edge_costs(step, e)?;
-                preceding_edge_cost_indexes.push(node_preceding_edge_costs.len());
-                node_preceding_edge_costs.push(preceding_edge_costs);
+        // The preceding edge costs of all the nodes in the step are stored in one arena.
+        let mut arena = self.buffers.arenas.pop().unwrap_or_default();
+        let mut pending_nodes = mem::take(&mut self.buffers.pending_nodes);
+        let node_keys = self
+            .graph
+            .iter()
+            .map(|step| {
+                self_input
+                    .create_subrange(step.input_tail(), self_input.length() - step.input_tail())
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let founds = node_keys
+            .iter()
+            .map(|node_key| {
+                self.vocabulary
+                    .find_entries(node_key.as_ref())
+                    .map_err(|e| LatticeError::VocabularyFailure(Box::new(e)))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        if let Some(next_input) = next_input {
+            self.prefetch_next(self_input.as_ref(), next_input)?;
+        }
+        for (i, found) in founds.into_iter().enumerate() {
+            self.pend_nodes(i, found, &mut arena, &mut pending_nodes)?;
+        }
+        if let Some(string_input) = self_input.downcast_ref::<StringInput>() {
+            for character_class in &self.character_class_entries {
+                let mut run_tail = string_input.value().len();
+                let mut run_length = 0usize;
+                let mut merged_step = None;
+                for i in (0..self.graph.len()).rev() {
+                    let input_tail = self.graph[i].input_tail();
+                    let Some(run_head) = string_input.value().get(input_tail..run_tail) else {
+                        break;
+                    };
+                    if !run_head.chars().all(&character_class.is_member) {
+                        break;
+                    }
+                    run_length = run_length.saturating_add(run_head.chars().count());
+                    run_tail = input_tail;
+                    if run_length > character_class.max_split_length {
+                        merged_step = Some(i);
+                        continue;
+                    }
+                    let node_key =
+                        self_input.create_subrange(input_tail, self_input.length() - input_tail)?;
+                    let found = (character_class.entries)(node_key.as_ref());
+                    self.pend_nodes(i, found, &mut arena, &mut pending_nodes)?;
+                }
+                if let Some(i) = merged_step {
+                    let input_tail = self.graph[i].input_tail();
+                    let node_key =
+                        self_input.create_subrange(input_tail, self_input.length() - input_tail)?;
+                    let found = (character_class.entries)(node_key.as_ref());
+                    self.pend_nodes(i, found, &mut arena, &mut pending_nodes)?;
+                }
             }
-
-            for j in 0..found.len() {
-                let entry = &found[j];
-                let preceding_edge_costs =
-                    &node_preceding_edge_costs[preceding_edge_cost_indexes[j]];
-                let best_preceding_node_index_ =
-                    Self::best_preceding_node_index(step, preceding_edge_costs.as_slice());
-                let best_preceding_path_cost = Self::add_cost(
-                    step.nodes[best_preceding_node_index_].path_cost(),
-                    preceding_edge_costs[best_preceding_node_index_],
-                );
-                let new_node = match Node::new_with_entry(
-                    entry.clone(),
-                    nodes.len(),
-                    i,
-                    preceding_edge_costs.clone(),
-                    best_preceding_node_index_,
-                    Self::add_cost(best_preceding_path_cost, entry.cost()),
-                ) {
-                    Ok(new_node) => new_node,
-                    Err(e) => return Err(e),
-                };
-                nodes.push(new_node);
+        }
+        if pending_nodes.is_empty() {
+            if let Some(unknown_entries) = &self.unknown_entries {
+                let i = self.graph.len() - 1;
+                let input_tail = self.graph[i].input_tail();
+                let node_key =
+                    self_input.create_subrange(input_tail, self_input.length() - input_tail)?;
+                let found = unknown_entries(node_key.as_ref());
+                self.pend_nodes(i, found, &mut arena, &mut pending_nodes)?;
             }
         }
+        if let Some(skip_cost) = self.skip_cost {
+            let i = self.graph.len() - 1;
+            let input_tail = self.graph[i].input_tail();
+            let node_key =
+                self_input.create_subrange(input_tail, self_input.length() - input_tail)?;
+            let skip_entry = Rc::new(Entry::new(node_key, Box::new(SkippedInput), skip_cost));
+            self.pend_nodes(i, vec![skip_entry], &mut arena, &mut pending_nodes)?;
+        }
+        let arena = Rc::new(arena);
+        let mut nodes = self.buffers.node_vectors.pop().unwrap_or_default();
+        nodes.reserve(pending_nodes.len());
+        for (entry, preceding_step, range, best_preceding_node_index_, path_cost) in
+            pending_nodes.drain(..)
+        {
+            let new_node = Node::new_with_entry_in_arena(
+                entry,
+                self.graph.len(),
+                nodes.len(),
+                preceding_step,
+                arena.clone(),
+                range,
+                best_preceding_node_index_,
+                path_cost,
+            )?;
+            nodes.push(new_node);
+        }
+        self.buffers.pending_nodes = pending_nodes;
         if nodes.is_empty() {
-            return Err(LatticeError::NoNodeIsFoundForTheInput.into());
+            return Err(LatticeError::NoNodeIsFoundForTheInput);
+        }
+        #[cfg(feature = "debug")]
+        let mut dropped_nodes = Vec::new();
+        let nodes_to_keep = self.search_strategy.nodes_to_keep(&nodes);
+        if nodes_to_keep.len() < nodes.len() {
+            #[cfg(feature = "debug")]
+            Self::append_dropped_nodes(
+                &nodes,
+                &nodes_to_keep,
+                DropReason::SearchStrategy,
+                &mut dropped_nodes,
+            );
+            nodes = Self::keep_nodes(&nodes, nodes_to_keep);
+        }
+        if nodes.len() > self.node_capacity {
+            match self.node_overflow_policy {
+                NodeOverflowPolicy::DropWorst => {
+                    let mut nodes_to_keep = (0..nodes.len()).collect::<Vec<_>>();
+                    nodes_to_keep.sort_by_key(|&i| nodes[i].path_cost());
+                    nodes_to_keep.truncate(self.node_capacity.max(1));
+                    nodes_to_keep.sort_unstable();
+                    #[cfg(feature = "debug")]
+                    Self::append_dropped_nodes(
+                        &nodes,
+                        &nodes_to_keep,
+                        DropReason::NodeCapacity,
+                        &mut dropped_nodes,
+                    );
+                    nodes = Self::keep_nodes(&nodes, nodes_to_keep);
+                }
+                NodeOverflowPolicy::Error => {
+                    return Err(LatticeError::NodeCapacityExceeded);
+                }
+            }
+        }
+        #[cfg(feature = "tracing")]
+        tracing::debug!(node_count = nodes.len(), "pushed back a step");
+        if let Some(step_observer) = &self.step_observer {
+            step_observer(self.graph.len(), &nodes);
         }
 
-        self.graph.push(GraphStep::new(self_input.length(), nodes));
+        #[cfg(not(feature = "debug"))]
+        let step = GraphStep::new(self_input.length(), nodes);
+        #[cfg(feature = "debug")]
+        let step = GraphStep {
+            dropped_nodes,
+            ..GraphStep::new(self_input.length(), nodes)
+        };
+        self.graph.push(Rc::new(step));
 
         Ok(())
     }
 
+    fn prefetch_next(&self, self_input: &dyn Input, next_input: &dyn Input) -> Result<()> {
+        let mut whole_input = self_input.create_subrange(0, self_input.length())?;
+        whole_input.append(next_input.create_subrange(0, next_input.length())?)?;
+        let input_tails = self
+            .graph
+            .iter()
+            .map(|step| step.input_tail())
+            .chain(iter::once(self_input.length()));
+        let next_node_keys = input_tails
+            .map(|input_tail| {
+                whole_input.create_subrange(input_tail, whole_input.length() - input_tail)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        self.vocabulary
+            .prefetch(&next_node_keys.iter().map(AsRef::as_ref).collect::<Vec<_>>())
+            .map_err(|e| LatticeError::VocabularyFailure(Box::new(e)))
+    }
+
+    fn pend_nodes(
+        &self,
+        preceding_step: usize,
+        entries: Vec<Rc<Entry>>,
+        arena: &mut Vec<i32>,
+        pending_nodes: &mut Vec<PendingNode>,
+    ) -> Result<()> {
+        let step = &self.graph[preceding_step];
+        for entry in entries {
+            if self.trigram_states {
+                for preceding_node_index in 0..step.nodes().len() {
+                    let range = self.append_preceding_edge_cost_from(
+                        step,
+                        preceding_node_index,
+                        &entry,
+                        arena,
+                    )?;
+                    self.pend_node(
+                        step,
+                        preceding_step,
+                        entry.clone(),
+                        range,
+                        arena,
+                        pending_nodes,
+                    );
+                }
+            } else {
+                let range = self.append_preceding_edge_costs(step, &entry, arena)?;
+                self.pend_node(step, preceding_step, entry, range, arena, pending_nodes);
+            }
+        }
+        Ok(())
+    }
+
+    fn pend_node(
+        &self,
+        step: &GraphStep,
+        preceding_step: usize,
+        entry: Rc<Entry>,
+        range: Range<usize>,
+        arena: &mut Vec<i32>,
+        pending_nodes: &mut Vec<PendingNode>,
+    ) {
+        let preceding_edge_costs = &arena[range.clone()];
+        let best_preceding_node_index_ = self
+            .search_strategy
+            .best_preceding_node(step.nodes(), preceding_edge_costs);
+        let best_preceding_path_cost = Connection::add_cost(
+            step.nodes[best_preceding_node_index_].path_cost(),
+            preceding_edge_costs[best_preceding_node_index_],
+        );
+        if best_preceding_path_cost == Connection::INFINITE_COST {
+            // No edge reaches the entry.
+            arena.truncate(range.start);
+            return;
+        }
+        let path_cost = Connection::add_cost(best_preceding_path_cost, entry.cost());
+        pending_nodes.push((
+            entry,
+            preceding_step,
+            range,
+            best_preceding_node_index_,
+            path_cost,
+        ));
+    }
+
+    #[cfg(feature = "debug")]
+    fn append_dropped_nodes(
+        nodes: &[Node],
+        nodes_to_keep: &[usize],
+        reason: DropReason,
+        dropped_nodes: &mut Vec<DroppedNode>,
+    ) {
+        let mut nodes_to_keep = nodes_to_keep.iter().peekable();
+        for (i, node) in nodes.iter().enumerate() {
+            if nodes_to_keep.next_if_eq(&&i).is_some() {
+                continue;
+            }
+            dropped_nodes.push(DroppedNode {
+                node: node.clone(),
+                reason,
+            });
+        }
+    }
+
+    fn keep_nodes(nodes: &[Node], nodes_to_keep: Vec<usize>) -> Vec<Node> {
+        nodes_to_keep
+            .into_iter()
+            .enumerate()
+            .map(|(index_in_step, i)| nodes[i].with_index_in_step(index_in_step))
+            .collect()
+    }
+
     /**
      * Settles this lattice.
      *
@@ -204,69 +1017,139 @@ impl<'a> Lattice<'a> {
      *
      * # Errors
      * * When no input pushed yet.
+     * * When the vocabulary fails.
      */
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(step = self.graph.len()))
+    )]
     pub fn settle(&mut self) -> Result<Node> {
         let Some(graph_last) = self.graph.last() else {
-            return Err(LatticeError::NoInput.into());
+            return Err(LatticeError::NoInput);
         };
-        let preceding_edge_costs = self.preceding_edge_costs(graph_last, &Entry::BosEos)?;
-        let best_preceding_node_index =
-            Self::best_preceding_node_index(graph_last, preceding_edge_costs.as_slice());
-        let best_preceding_path_cost = Self::add_cost(
+        let mut preceding_edge_costs = Vec::with_capacity(graph_last.nodes().len());
+        let _ = self.append_preceding_edge_costs(
+            graph_last,
+            &Entry::BosEos,
+            &mut preceding_edge_costs,
+        )?;
+        let best_preceding_node_index = self
+            .search_strategy
+            .best_preceding_node(graph_last.nodes(), preceding_edge_costs.as_slice());
+        let best_preceding_path_cost = Connection::add_cost(
             graph_last.nodes()[best_preceding_node_index].path_cost(),
             preceding_edge_costs[best_preceding_node_index],
         );
 
         let eos_node = Node::eos(
             self.graph.len() - 1,
-            preceding_edge_costs,
+            Rc::new(preceding_edge_costs),
             best_preceding_node_index,
             best_preceding_path_cost,
         );
         Ok(eos_node)
     }
 
-    fn preceding_edge_costs(&self, step: &GraphStep, next_entry: &Entry) -> Result<Rc<Vec<i32>>> {
+    fn append_preceding_edge_costs(
+        &self,
+        step: &GraphStep,
+        next_entry: &Entry,
+        arena: &mut Vec<i32>,
+    ) -> Result<Range<usize>> {
         assert!(!step.nodes().is_empty());
-        let mut costs = Vec::with_capacity(step.nodes().len());
+        let start = arena.len();
+        arena.reserve(step.nodes().len());
         for node in step.nodes() {
-            let cost = self.vocabulary.find_connection(node, next_entry)?.cost();
-            costs.push(cost);
+            let cost = self.preceding_edge_cost(node, next_entry)?;
+            arena.push(cost);
         }
-        Ok(Rc::new(costs))
+        Ok(start..arena.len())
     }
 
-    fn best_preceding_node_index(step: &GraphStep, edge_costs: &[i32]) -> usize {
-        assert!(!step.nodes().is_empty());
-        let mut min_index = 0;
-        for i in 1..step.nodes().len() {
-            if Self::add_cost(step.nodes()[i].path_cost(), edge_costs[i])
-                < Self::add_cost(step.nodes()[min_index].path_cost(), edge_costs[min_index])
-            {
-                min_index = i;
-            }
-        }
-        min_index
+    fn append_preceding_edge_cost_from(
+        &self,
+        step: &GraphStep,
+        preceding_node_index: usize,
+        next_entry: &Entry,
+        arena: &mut Vec<i32>,
+    ) -> Result<Range<usize>> {
+        let start = arena.len();
+        arena.resize(start + step.nodes().len(), Connection::INFINITE_COST);
+        arena[start + preceding_node_index] =
+            self.preceding_edge_cost(&step.nodes()[preceding_node_index], next_entry)?;
+        Ok(start..arena.len())
     }
 
-    const fn add_cost(one: i32, another: i32) -> i32 {
-        if one == i32::MAX || another == i32::MAX {
-            i32::MAX
+    fn preceding_edge_cost(&self, node: &Node, next_entry: &Entry) -> Result<i32> {
+        let mut cost = if Self::is_skipping(next_entry.value()) || Self::is_skipping(node.value()) {
+            0
         } else {
-            one + another
+            self.vocabulary
+                .find_trigram_connection(self.best_preceding_node_of(node), node, next_entry)
+                .map_err(|e| LatticeError::VocabularyFailure(Box::new(e)))?
+                .cost()
+        };
+        if node.is_bos() {
+            cost = Connection::add_cost(cost, self.bos_cost);
+        }
+        if next_entry.is_bos_eos() {
+            cost = Connection::add_cost(cost, self.eos_cost);
+        }
+        Ok(cost)
+    }
+
+    fn is_skipping(value: Option<&dyn Any>) -> bool {
+        value.is_some_and(|value| value.is::<SkippedInput>())
+    }
+
+    fn best_preceding_node_of(&self, node: &Node) -> Option<&Node> {
+        if node.is_bos() {
+            return None;
         }
+        self.graph
+            .get(node.preceding_step())
+            .and_then(|step| step.nodes().get(node.best_preceding_node()))
+    }
+}
+
+impl Debug for Lattice<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Lattice")
+            .field("vocabulary", &self.vocabulary)
+            .field("search_strategy", &self.search_strategy)
+            .field("node_capacity", &self.node_capacity)
+            .field("node_overflow_policy", &self.node_overflow_policy)
+            .field("unknown_entries", &type_name_of_val(&self.unknown_entries))
+            .field(
+                "character_class_entries",
+                &type_name_of_val(&self.character_class_entries),
+            )
+            .field("skip_cost", &self.skip_cost)
+            .field("bos_cost", &self.bos_cost)
+            .field("eos_cost", &self.eos_cost)
+            .field("trigram_states", &self.trigram_states)
+            .field("step_observer", &type_name_of_val(&self.step_observer))
+            .field("input", &self.input)
+            .field("graph", &self.graph)
+            .finish()
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::cell::RefCell;
+
+    use crate::connection::Connection;
+    use crate::constraint::Constraint;
     use crate::entry::Entry;
     use crate::hash_map_vocabulary::HashMapVocabulary;
+    use crate::n_best_iterator::NBestIterator;
+    use crate::path::Path;
 
     use super::*;
 
     fn to_input(string: &str) -> Box<dyn Input> {
-        Box::new(crate::string_input::StringInput::new(string.to_string()))
+        Box::new(StringInput::new(string.to_string()))
     }
 
     /*
@@ -488,13 +1371,136 @@ mod tests {
         ))
     }
 
+    #[derive(Debug)]
+    struct EmptyVocabulary;
+
+    impl Vocabulary for EmptyVocabulary {
+        fn find_entries(&self, _key: &dyn Input) -> Result<Vec<Rc<Entry>>> {
+            Ok(Vec::new())
+        }
+
+        fn find_connection(&self, _from: &Node, _to: &Entry) -> Result<Connection> {
+            Ok(Connection::default())
+        }
+    }
+
     fn create_empty_vocabulary() -> Box<dyn Vocabulary> {
-        Box::new(HashMapVocabulary::new(
-            Vec::new(),
-            Vec::new(),
-            &entry_hash,
-            &entry_equal_to,
-        ))
+        Box::new(EmptyVocabulary)
+    }
+
+    #[derive(Debug)]
+    struct FailingVocabulary;
+
+    impl Vocabulary for FailingVocabulary {
+        fn find_entries(&self, _key: &dyn Input) -> Result<Vec<Rc<Entry>>> {
+            Err(LatticeError::Other("failed".into()))
+        }
+
+        fn find_connection(&self, _from: &Node, _to: &Entry) -> Result<Connection> {
+            Err(LatticeError::Other("failed".into()))
+        }
+    }
+
+    #[derive(Debug)]
+    struct PrefetchingVocabulary {
+        vocabulary: Box<dyn Vocabulary>,
+        prefetched_keys: RefCell<Vec<Vec<String>>>,
+    }
+
+    impl Vocabulary for PrefetchingVocabulary {
+        fn find_entries(&self, key: &dyn Input) -> Result<Vec<Rc<Entry>>> {
+            self.vocabulary.find_entries(key)
+        }
+
+        fn find_connection(&self, from: &Node, to: &Entry) -> Result<Connection> {
+            self.vocabulary.find_connection(from, to)
+        }
+
+        fn prefetch(&self, keys: &[&dyn Input]) -> Result<()> {
+            self.prefetched_keys.borrow_mut().push(
+                keys.iter()
+                    .map(|key| {
+                        key.downcast_ref::<StringInput>()
+                            .unwrap()
+                            .value()
+                            .to_string()
+                    })
+                    .collect(),
+            );
+            Ok(())
+        }
+    }
+
+    #[derive(Debug)]
+    struct TrigramVocabulary {
+        bigram: Box<dyn Vocabulary>,
+    }
+
+    impl Vocabulary for TrigramVocabulary {
+        fn find_entries(&self, key: &dyn Input) -> Result<Vec<Rc<Entry>>> {
+            self.bigram.find_entries(key)
+        }
+
+        fn find_connection(&self, from: &Node, to: &Entry) -> Result<Connection> {
+            self.bigram.find_connection(from, to)
+        }
+
+        fn find_trigram_connection(
+            &self,
+            preceding: Option<&Node>,
+            from: &Node,
+            to: &Entry,
+        ) -> Result<Connection> {
+            let cost = self.find_connection(from, to)?.cost();
+            let from_value = from.value().and_then(|v| v.downcast_ref::<&str>());
+            let to_value = to.value().and_then(|v| v.downcast_ref::<&str>());
+            if preceding.is_some_and(|p| p.is_bos())
+                && from_value == Some(&"local415")
+                && to_value == Some(&"local815")
+            {
+                Ok(Connection::new(cost + 1000))
+            } else {
+                Ok(Connection::new(cost))
+            }
+        }
+    }
+
+    #[derive(Debug)]
+    struct EosTrigramVocabulary {
+        bigram: Box<dyn Vocabulary>,
+    }
+
+    impl Vocabulary for EosTrigramVocabulary {
+        fn find_entries(&self, key: &dyn Input) -> Result<Vec<Rc<Entry>>> {
+            self.bigram.find_entries(key)
+        }
+
+        fn find_connection(&self, from: &Node, to: &Entry) -> Result<Connection> {
+            self.bigram.find_connection(from, to)
+        }
+
+        fn find_trigram_connection(
+            &self,
+            preceding: Option<&Node>,
+            from: &Node,
+            to: &Entry,
+        ) -> Result<Connection> {
+            let cost = self.find_connection(from, to)?.cost();
+            if !matches!(to, Entry::BosEos) {
+                return Ok(Connection::new(cost));
+            }
+            let preceding_value = preceding
+                .and_then(Node::value)
+                .and_then(|v| v.downcast_ref::<&str>());
+            let from_value = from.value().and_then(|v| v.downcast_ref::<&str>());
+            let penalty = match (preceding_value, from_value) {
+                (None, Some(&"mizuho" | &"sakura" | &"tsubame")) => 2000,
+                (Some(&"rapid811"), Some(&"local817")) => 1000,
+                (_, Some(&"local815")) => 1000,
+                _ => 0,
+            };
+            Ok(Connection::new(cost + penalty))
+        }
     }
 
     #[test]
@@ -503,6 +1509,427 @@ mod tests {
         let _lattice = Lattice::new(vocabulary.as_ref());
     }
 
+    #[test]
+    fn new_with_search_strategy() {
+        let vocabulary = create_vocabulary();
+        let mut lattice = Lattice::new_with_search_strategy(
+            vocabulary.as_ref(),
+            Box::new(BeamSearchStrategy::new(1)),
+        );
+        lattice.push_back(to_input("[HakataTosu]")).unwrap();
+        lattice.push_back(to_input("[TosuOmuta]")).unwrap();
+        lattice.push_back(to_input("[OmutaKumamoto]")).unwrap();
+
+        for step in 1..lattice.step_count() {
+            let nodes = lattice.nodes_at(step).unwrap();
+            assert_eq!(nodes.len(), 1);
+            assert_eq!(nodes[0].index_in_step(), 0);
+        }
+        assert_eq!(
+            lattice.nodes_at(1).unwrap()[0]
+                .value()
+                .unwrap()
+                .downcast_ref::<&str>()
+                .unwrap(),
+            &"local415"
+        );
+        assert_eq!(
+            lattice.nodes_at(2).unwrap()[0]
+                .value()
+                .unwrap()
+                .downcast_ref::<&str>()
+                .unwrap(),
+            &"rapid811"
+        );
+        assert_eq!(
+            lattice.nodes_at(3).unwrap()[0]
+                .value()
+                .unwrap()
+                .downcast_ref::<&str>()
+                .unwrap(),
+            &"tsubame"
+        );
+
+        let eos_node = lattice.settle().unwrap();
+        assert_eq!(eos_node.path_cost(), 3390);
+    }
+
+    #[test]
+    fn node_capacity() {
+        {
+            let vocabulary = create_vocabulary();
+            let mut lattice = Lattice::builder(vocabulary.as_ref())
+                .node_capacity(2)
+                .node_overflow_policy(NodeOverflowPolicy::DropWorst)
+                .build();
+            lattice.push_back(to_input("[HakataTosu]")).unwrap();
+            lattice.push_back(to_input("[TosuOmuta]")).unwrap();
+            lattice.push_back(to_input("[OmutaKumamoto]")).unwrap();
+
+            let values = lattice
+                .nodes_at(3)
+                .unwrap()
+                .iter()
+                .map(|node| *node.value().unwrap().downcast_ref::<&str>().unwrap())
+                .collect::<Vec<_>>();
+            assert_eq!(values, vec!["tsubame", "local817"]);
+
+            let eos_node = lattice.settle().unwrap();
+            assert_eq!(eos_node.path_cost(), 3390);
+        }
+        {
+            let vocabulary = create_vocabulary();
+            let mut lattice = Lattice::builder(vocabulary.as_ref())
+                .node_capacity(2)
+                .node_overflow_policy(NodeOverflowPolicy::Error)
+                .build();
+            lattice.push_back(to_input("[HakataTosu]")).unwrap();
+
+            let result = lattice.push_back(to_input("[TosuOmuta]"));
+            assert!(matches!(result, Err(LatticeError::NodeCapacityExceeded)));
+            assert_eq!(lattice.step_count(), 2);
+        }
+    }
+
+    #[cfg(feature = "debug")]
+    #[test]
+    fn dropped_nodes_at() {
+        let values_of = |dropped_nodes: &[DroppedNode]| {
+            dropped_nodes
+                .iter()
+                .map(|dropped_node| {
+                    (
+                        *dropped_node
+                            .node()
+                            .value()
+                            .unwrap()
+                            .downcast_ref::<&str>()
+                            .unwrap(),
+                        dropped_node.reason(),
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+        {
+            let vocabulary = create_vocabulary();
+            let mut lattice = Lattice::new(vocabulary.as_ref());
+            lattice.push_back(to_input("[HakataTosu]")).unwrap();
+
+            assert!(lattice.dropped_nodes_at(0).unwrap().is_empty());
+            assert!(lattice.dropped_nodes_at(1).unwrap().is_empty());
+            assert!(matches!(
+                lattice.dropped_nodes_at(2),
+                Err(LatticeError::StepIsTooLarge)
+            ));
+        }
+        {
+            let vocabulary = create_vocabulary();
+            let mut lattice = Lattice::builder(vocabulary.as_ref()).beam_width(1).build();
+            lattice.push_back(to_input("[HakataTosu]")).unwrap();
+
+            assert_eq!(
+                values_of(lattice.dropped_nodes_at(1).unwrap()),
+                vec![("kamome", DropReason::SearchStrategy)]
+            );
+        }
+        {
+            let vocabulary = create_vocabulary();
+            let mut lattice = Lattice::builder(vocabulary.as_ref())
+                .node_capacity(2)
+                .node_overflow_policy(NodeOverflowPolicy::DropWorst)
+                .build();
+            lattice.push_back(to_input("[HakataTosu]")).unwrap();
+            lattice.push_back(to_input("[TosuOmuta]")).unwrap();
+            lattice.push_back(to_input("[OmutaKumamoto]")).unwrap();
+
+            let dropped_nodes = lattice.dropped_nodes_at(3).unwrap();
+            assert!(!dropped_nodes.is_empty());
+            assert!(dropped_nodes
+                .iter()
+                .all(|dropped_node| dropped_node.reason() == DropReason::NodeCapacity));
+            assert!(!values_of(dropped_nodes)
+                .iter()
+                .any(|(value, _)| *value == "tsubame" || *value == "local817"));
+        }
+    }
+
+    #[test]
+    fn builder() {
+        {
+            let vocabulary = create_vocabulary();
+            let step_sizes = RefCell::new(Vec::new());
+            let mut lattice = Lattice::builder(vocabulary.as_ref())
+                .beam_width(1)
+                .step_observer(|step, nodes| step_sizes.borrow_mut().push((step, nodes.len())))
+                .build();
+            lattice.push_back(to_input("[HakataTosu]")).unwrap();
+            lattice.push_back(to_input("[TosuOmuta]")).unwrap();
+            lattice.push_back(to_input("[OmutaKumamoto]")).unwrap();
+
+            assert_eq!(*step_sizes.borrow(), vec![(1, 1), (2, 1), (3, 1)]);
+        }
+        {
+            let vocabulary = create_vocabulary();
+            let mut lattice = Lattice::builder(vocabulary.as_ref())
+                .node_capacity(2)
+                .node_overflow_policy(NodeOverflowPolicy::Error)
+                .build();
+            lattice.push_back(to_input("[HakataTosu]")).unwrap();
+
+            let result = lattice.push_back(to_input("[TosuOmuta]"));
+            assert!(matches!(result, Err(LatticeError::NodeCapacityExceeded)));
+        }
+        {
+            let vocabulary = create_vocabulary();
+            // The key of the unknown entry has the connections of the one of local813.
+            let mut lattice = Lattice::builder(vocabulary.as_ref())
+                .unknown_entries(|_| {
+                    vec![Rc::new(Entry::new(
+                        to_input("Tosu-Omuta"),
+                        Box::new("unknown"),
+                        9999,
+                    ))]
+                })
+                .build();
+            lattice.push_back(to_input("[HakataTosu]")).unwrap();
+            lattice.push_back(to_input("[Unknown]")).unwrap();
+
+            let nodes = lattice.nodes_at(2).unwrap();
+            assert_eq!(nodes.len(), 1);
+            assert_eq!(nodes[0].preceding_step(), 1);
+            assert_eq!(
+                nodes[0].value().unwrap().downcast_ref::<&str>().unwrap(),
+                &"unknown"
+            );
+            assert!(lattice.settle().is_ok());
+        }
+        {
+            let vocabulary = create_vocabulary();
+            let mut lattice = Lattice::builder(vocabulary.as_ref()).build();
+
+            let result = lattice.push_back(to_input("[Unknown]"));
+            assert!(matches!(
+                result,
+                Err(LatticeError::NoNodeIsFoundForTheInput)
+            ));
+        }
+        {
+            let vocabulary = create_vocabulary();
+            let mut lattice = Lattice::builder(vocabulary.as_ref())
+                .skip_cost(5000)
+                .build();
+            lattice.push_back(to_input("[HakataTosu]")).unwrap();
+            lattice.push_back(to_input("[Unknown]")).unwrap();
+            lattice.push_back(to_input("[OmutaKumamoto]")).unwrap();
+
+            let nodes = lattice.nodes_at(2).unwrap();
+            assert_eq!(nodes.len(), 1);
+            assert!(nodes[0].value().unwrap().is::<SkippedInput>());
+            assert_eq!(nodes[0].node_cost(), 5000);
+            assert_eq!(nodes[0].preceding_edge_costs(), &[0, 0, 0]);
+            assert_eq!(lattice.nodes_at(1).unwrap().len(), 3);
+
+            let eos_node = lattice.settle().unwrap();
+            assert_eq!(eos_node.path_cost(), 800 + 570 + 5000 + 950 + 600);
+        }
+        {
+            let vocabulary = create_empty_vocabulary();
+            let class_entries = |value: &'static str| {
+                move |key: &dyn Input| {
+                    vec![Rc::new(Entry::new(
+                        key.create_subrange(0, key.length()).unwrap(),
+                        Box::new(value),
+                        100,
+                    ))]
+                }
+            };
+            let mut lattice = Lattice::builder(vocabulary.as_ref())
+                .character_class_entries(|c| c.is_ascii_digit(), class_entries("number"))
+                .character_class_entries(|c| c.is_ascii_alphabetic(), class_entries("word"))
+                .unknown_entries(|_| panic!("the unknown word handler must not be called"))
+                .build();
+            lattice.push_back(to_input("12")).unwrap();
+            lattice.push_back(to_input("3")).unwrap();
+            lattice.push_back(to_input("ab")).unwrap();
+
+            let keys_and_values = |step| {
+                lattice
+                    .nodes_at(step)
+                    .unwrap()
+                    .iter()
+                    .map(|node| {
+                        (
+                            node.key()
+                                .unwrap()
+                                .downcast_ref::<StringInput>()
+                                .unwrap()
+                                .value()
+                                .to_string(),
+                            *node.value().unwrap().downcast_ref::<&str>().unwrap(),
+                        )
+                    })
+                    .collect::<Vec<_>>()
+            };
+            assert_eq!(
+                keys_and_values(2),
+                vec![
+                    (String::from("3"), "number"),
+                    (String::from("123"), "number")
+                ]
+            );
+            assert_eq!(keys_and_values(3), vec![(String::from("ab"), "word")]);
+        }
+        {
+            let vocabulary = create_empty_vocabulary();
+            let mut lattice = Lattice::builder(vocabulary.as_ref())
+                .merged_character_class_entries(
+                    |c| c.is_ascii_digit(),
+                    |key| {
+                        vec![Rc::new(Entry::new(
+                            key.create_subrange(0, key.length()).unwrap(),
+                            Box::new(key.length()),
+                            100,
+                        ))]
+                    },
+                    2,
+                )
+                .build();
+            for input in ["1", "2", "3", "4", "5"] {
+                lattice.push_back(to_input(input)).unwrap();
+            }
+
+            let lengths = lattice
+                .nodes_at(5)
+                .unwrap()
+                .iter()
+                .map(|node| *node.value().unwrap().downcast_ref::<usize>().unwrap())
+                .collect::<Vec<_>>();
+            assert_eq!(lengths, vec![1, 2, 5]);
+            assert_eq!(lattice.nodes_at(2).unwrap().len(), 2);
+        }
+        {
+            let vocabulary = create_vocabulary();
+            let mut lattice = Lattice::builder(vocabulary.as_ref())
+                .bos_cost(100)
+                .eos_cost(200)
+                .build();
+            lattice.push_back(to_input("[HakataTosu]")).unwrap();
+            lattice.push_back(to_input("[TosuOmuta]")).unwrap();
+            lattice.push_back(to_input("[OmutaKumamoto]")).unwrap();
+
+            let eos_node = lattice.settle().unwrap();
+            assert_eq!(eos_node.path_cost(), 3390 + 300);
+            let path_costs = NBestIterator::new(&lattice, eos_node, Box::new(Constraint::new()))
+                .take(3)
+                .map(|path| path.cost())
+                .collect::<Vec<_>>();
+            assert_eq!(path_costs, vec![3390 + 300, 3620 + 300, 3760 + 300]);
+        }
+    }
+
+    #[test]
+    fn fork() {
+        let vocabulary = create_vocabulary();
+        let mut lattice = Lattice::new(vocabulary.as_ref());
+        lattice.push_back(to_input("[HakataTosu]")).unwrap();
+
+        let mut fork = lattice.fork().unwrap();
+        assert_eq!(fork.step_count(), 2);
+        assert_eq!(fork.nodes_at(1).unwrap(), lattice.nodes_at(1).unwrap());
+
+        fork.push_back(to_input("[TosuOmuta]")).unwrap();
+        fork.push_back(to_input("[OmutaKumamoto]")).unwrap();
+        assert_eq!(fork.step_count(), 4);
+        assert_eq!(lattice.step_count(), 2);
+
+        let fork_eos_node = fork.settle().unwrap();
+        lattice.push_back(to_input("[TosuOmuta]")).unwrap();
+        lattice.push_back(to_input("[OmutaKumamoto]")).unwrap();
+        let eos_node = lattice.settle().unwrap();
+        assert_eq!(fork_eos_node, eos_node);
+    }
+
+    #[test]
+    fn reset() {
+        let vocabulary = create_vocabulary();
+        let mut lattice = Lattice::new(vocabulary.as_ref());
+        lattice.push_back(to_input("[HakataTosu]")).unwrap();
+        lattice.push_back(to_input("[TosuOmuta]")).unwrap();
+        let eos_node = lattice.settle().unwrap();
+
+        lattice.reset();
+
+        assert_eq!(lattice.step_count(), 1);
+        assert_eq!(lattice.buffers.node_vectors.len(), 2);
+        assert_eq!(lattice.buffers.arenas.len(), 2);
+        assert_eq!(eos_node.preceding_step(), 2);
+
+        lattice.push_back(to_input("[HakataTosu]")).unwrap();
+
+        assert_eq!(lattice.step_count(), 2);
+        assert_eq!(lattice.nodes_at(1).unwrap().len(), 2);
+        assert_eq!(lattice.buffers.node_vectors.len(), 1);
+        assert_eq!(lattice.buffers.arenas.len(), 1);
+
+        let fork = lattice.fork().unwrap();
+        lattice.reset();
+
+        assert_eq!(lattice.buffers.node_vectors.len(), 1);
+        assert_eq!(fork.step_count(), 2);
+    }
+
+    #[test]
+    fn replace_inputs() {
+        let vocabulary = create_vocabulary();
+        let mut lattice = Lattice::new(vocabulary.as_ref());
+        lattice.push_back(to_input("[HakataTosu]")).unwrap();
+        lattice.push_back(to_input("[TosuOmuta]")).unwrap();
+        lattice.push_back(to_input("[OmutaKumamoto]")).unwrap();
+        let first_step = lattice.graph[1].clone();
+
+        {
+            lattice
+                .replace_inputs(1, 1, vec![to_input("[TosuOmuta]")])
+                .unwrap();
+
+            assert_eq!(lattice.step_count(), 4);
+            assert!(Rc::ptr_eq(&lattice.graph[1], &first_step));
+            assert_eq!(lattice.settle().unwrap().path_cost(), 3390);
+        }
+        {
+            lattice.replace_inputs(2, 1, Vec::new()).unwrap();
+
+            assert_eq!(lattice.step_count(), 3);
+            assert_eq!(lattice.settle().unwrap().path_cost(), 4010);
+        }
+        {
+            lattice
+                .replace_inputs(2, 0, vec![to_input("[OmutaKumamoto]")])
+                .unwrap();
+
+            assert_eq!(lattice.step_count(), 4);
+            assert_eq!(lattice.settle().unwrap().path_cost(), 3390);
+        }
+        {
+            lattice
+                .replace_inputs(
+                    0,
+                    3,
+                    vec![to_input("[HakataTosu]"), to_input("[TosuOmuta]")],
+                )
+                .unwrap();
+
+            assert_eq!(lattice.step_count(), 3);
+            assert!(!Rc::ptr_eq(&lattice.graph[1], &first_step));
+            assert_eq!(lattice.settle().unwrap().path_cost(), 4010);
+        }
+        {
+            let result = lattice.replace_inputs(2, 1, Vec::new());
+            assert!(matches!(result, Err(LatticeError::StepIsTooLarge)));
+            assert_eq!(lattice.step_count(), 3);
+        }
+    }
+
     #[test]
     fn step_count() {
         let vocabulary = create_vocabulary();
@@ -543,6 +1970,7 @@ mod tests {
             );
             for (i, n) in nodes.iter().enumerate() {
                 assert_eq!(n.index_in_step(), i);
+                assert_eq!(n.step(), 0);
             }
         }
         {
@@ -561,6 +1989,7 @@ mod tests {
             );
             for (i, n) in nodes.iter().enumerate() {
                 assert_eq!(n.index_in_step(), i);
+                assert_eq!(n.step(), 1);
             }
         }
         {
@@ -583,6 +2012,7 @@ mod tests {
             );
             for (i, n) in nodes.iter().enumerate() {
                 assert_eq!(n.index_in_step(), i);
+                assert_eq!(n.step(), 2);
             }
         }
         {
@@ -613,6 +2043,7 @@ mod tests {
             );
             for (i, n) in nodes.iter().enumerate() {
                 assert_eq!(n.index_in_step(), i);
+                assert_eq!(n.step(), 3);
             }
         }
         {
@@ -639,10 +2070,76 @@ mod tests {
             let mut lattice = Lattice::new(vocabulary.as_ref());
 
             let result = lattice.push_back(to_input("[HakataTosu]"));
-            assert!(result.is_err());
+            assert!(matches!(
+                result,
+                Err(LatticeError::NoNodeIsFoundForTheInput)
+            ));
+        }
+        {
+            let vocabulary = FailingVocabulary;
+            let mut lattice = Lattice::new(&vocabulary);
+
+            let result = lattice.push_back(to_input("[HakataTosu]"));
+            assert!(matches!(result, Err(LatticeError::VocabularyFailure(_))));
         }
     }
 
+    #[test]
+    fn push_back_without_edges() {
+        let vocabulary = create_vocabulary();
+        let mut lattice = Lattice::builder(vocabulary.as_ref())
+            .unknown_entries(|key| {
+                vec![Rc::new(Entry::new(
+                    key.create_subrange(0, key.length()).unwrap(),
+                    Box::new("unknown"),
+                    9999,
+                ))]
+            })
+            .build();
+        lattice.push_back(to_input("[HakataTosu]")).unwrap();
+
+        let result = lattice.push_back(to_input("[Unknown]"));
+        assert!(matches!(
+            result,
+            Err(LatticeError::NoNodeIsFoundForTheInput)
+        ));
+    }
+
+    #[test]
+    fn push_back_with_next() {
+        let vocabulary = PrefetchingVocabulary {
+            vocabulary: create_vocabulary(),
+            prefetched_keys: RefCell::new(Vec::new()),
+        };
+        let mut lattice = Lattice::new(&vocabulary);
+        lattice
+            .push_back_with_next(to_input("[HakataTosu]"), to_input("[TosuOmuta]").as_ref())
+            .unwrap();
+        lattice
+            .push_back_with_next(
+                to_input("[TosuOmuta]"),
+                to_input("[OmutaKumamoto]").as_ref(),
+            )
+            .unwrap();
+        lattice.push_back(to_input("[OmutaKumamoto]")).unwrap();
+
+        assert_eq!(
+            *vocabulary.prefetched_keys.borrow(),
+            vec![
+                vec![
+                    String::from("[HakataTosu][TosuOmuta]"),
+                    String::from("[TosuOmuta]")
+                ],
+                vec![
+                    String::from("[HakataTosu][TosuOmuta][OmutaKumamoto]"),
+                    String::from("[TosuOmuta][OmutaKumamoto]"),
+                    String::from("[OmutaKumamoto]")
+                ],
+            ]
+        );
+        assert_eq!(lattice.settle().unwrap().path_cost(), 3390);
+    }
+
     #[test]
     fn settle() {
         {
@@ -673,6 +2170,8 @@ mod tests {
                 let result = lattice.settle();
                 let eos_node = result.unwrap();
 
+                assert!(eos_node.is_eos());
+                assert_eq!(eos_node.step(), 3);
                 assert_eq!(eos_node.preceding_step(), 2);
                 assert_eq!(eos_node.best_preceding_node(), 1);
                 assert_eq!(eos_node.path_cost(), 4010);
@@ -689,4 +2188,82 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn push_back_with_trigram_connections() {
+        let path_cost_of_local815 = |vocabulary: &dyn Vocabulary| {
+            let mut lattice = Lattice::new(vocabulary);
+            lattice.push_back(to_input("[HakataTosu]")).unwrap();
+            lattice.push_back(to_input("[TosuOmuta]")).unwrap();
+            lattice.push_back(to_input("[OmutaKumamoto]")).unwrap();
+            lattice
+                .nodes_at(3)
+                .unwrap()
+                .iter()
+                .find(|node| {
+                    node.value().and_then(|v| v.downcast_ref::<&str>()) == Some(&"local815")
+                })
+                .unwrap()
+                .path_cost()
+        };
+
+        {
+            let vocabulary = create_vocabulary();
+
+            // BOS - local415 - local815
+            assert_eq!(path_cost_of_local815(vocabulary.as_ref()), 3550);
+        }
+        {
+            let vocabulary = TrigramVocabulary {
+                bigram: create_vocabulary(),
+            };
+
+            // BOS - kamome - local815
+            assert_eq!(path_cost_of_local815(&vocabulary), 3820);
+        }
+    }
+
+    #[test]
+    fn trigram_states() {
+        let vocabulary = EosTrigramVocabulary {
+            bigram: create_vocabulary(),
+        };
+        let best_path_and_costs = |trigram_states: bool| {
+            let mut lattice = Lattice::builder(&vocabulary)
+                .trigram_states(trigram_states)
+                .build();
+            lattice.push_back(to_input("[HakataTosu]")).unwrap();
+            lattice.push_back(to_input("[TosuOmuta]")).unwrap();
+            lattice.push_back(to_input("[OmutaKumamoto]")).unwrap();
+            let eos_node = lattice.settle().unwrap();
+            let paths = NBestIterator::new(&lattice, eos_node, Box::new(Constraint::new()))
+                .collect::<Vec<_>>();
+            let best_path = paths[0]
+                .nodes()
+                .iter()
+                .filter_map(|node| node.value().and_then(|v| v.downcast_ref::<&str>()))
+                .copied()
+                .collect::<Vec<_>>();
+            let costs = paths.iter().map(Path::cost).collect::<Vec<_>>();
+            (best_path, costs)
+        };
+
+        {
+            // The EOS is connected from local817 with the history of rapid811, its best
+            // preceding node, so BOS - ariake - local817 - EOS is missed.
+            let (best_path, costs) = best_path_and_costs(false);
+
+            assert_eq!(best_path, ["rapid811", "local817"]);
+            assert_eq!(costs[0], 4760);
+        }
+        {
+            let (best_path, costs) = best_path_and_costs(true);
+
+            assert_eq!(best_path, ["ariake", "local817"]);
+            assert_eq!(
+                costs,
+                [4600, 4680, 4760, 4950, 5050, 5320, 5390, 5620, 6670]
+            );
+        }
+    }
 }
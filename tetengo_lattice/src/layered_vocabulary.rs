@@ -0,0 +1,280 @@
+/*!
+ * A layered vocabulary.
+ *
+ * Copyright (C) 2023-2025 kaoru  <https://www.tetengo.org/>
+ */
+
+use std::rc::Rc;
+
+use crate::connection::Connection;
+use crate::entry::Entry;
+use crate::error::Result;
+use crate::input::Input;
+use crate::node::Node;
+use crate::vocabulary::Vocabulary;
+
+#[derive(Debug)]
+struct Layer<'a> {
+    vocabulary: &'a dyn Vocabulary,
+    priority: i32,
+    cost_offset: i32,
+}
+
+/**
+ * A layered vocabulary.
+ *
+ * Layers vocabularies such as user and system dictionaries. Each layer has a priority and an
+ * additive cost offset applied to its entries.
+ *
+ * A connection is taken from the first layer in which it is found.
+ */
+#[derive(Debug, Default)]
+pub struct LayeredVocabulary<'a> {
+    layers: Vec<Layer<'a>>,
+}
+
+impl<'a> LayeredVocabulary<'a> {
+    /**
+     * Creates a layered vocabulary with no layer.
+     */
+    pub const fn new() -> Self {
+        Self { layers: Vec::new() }
+    }
+
+    /**
+     * Adds a layer.
+     *
+     * The layers with higher priorities are looked up first. The layers with the same priority
+     * are looked up in the order they are added.
+     *
+     * # Arguments
+     * * `vocabulary`  - A vocabulary.
+     * * `priority`    - A priority.
+     * * `cost_offset` - A cost offset added to the costs of the entries in the vocabulary.
+     */
+    pub fn layer(
+        mut self,
+        vocabulary: &'a dyn Vocabulary,
+        priority: i32,
+        cost_offset: i32,
+    ) -> Self {
+        let index = self
+            .layers
+            .partition_point(|layer| layer.priority >= priority);
+        self.layers.insert(
+            index,
+            Layer {
+                vocabulary,
+                priority,
+                cost_offset,
+            },
+        );
+        self
+    }
+}
+
+impl Vocabulary for LayeredVocabulary<'_> {
+    fn find_entries(&self, key: &dyn Input) -> Result<Vec<Rc<Entry>>> {
+        let mut entries = Vec::new();
+        for layer in &self.layers {
+            let found = layer.vocabulary.find_entries(key)?;
+            if layer.cost_offset == 0 {
+                entries.extend(found);
+            } else {
+                entries.extend(
+                    found
+                        .into_iter()
+                        .map(|entry| Rc::new(entry.with_additional_cost(layer.cost_offset))),
+                );
+            }
+        }
+        Ok(entries)
+    }
+
+    fn find_entries_at(&self, input: &dyn Input, offset: usize) -> Result<Vec<(usize, Rc<Entry>)>> {
+        let mut entries = Vec::new();
+        for layer in &self.layers {
+            let found = layer.vocabulary.find_entries_at(input, offset)?;
+            if layer.cost_offset == 0 {
+                entries.extend(found);
+            } else {
+                entries.extend(found.into_iter().map(|(length, entry)| {
+                    (
+                        length,
+                        Rc::new(entry.with_additional_cost(layer.cost_offset)),
+                    )
+                }));
+            }
+        }
+        // The sort is stable, so that the entries of the same length keep the order of layers.
+        entries.sort_by_key(|(length, _)| *length);
+        Ok(entries)
+    }
+
+    fn prefetch(&self, keys: &[&dyn Input]) -> Result<()> {
+        for layer in &self.layers {
+            layer.vocabulary.prefetch(keys)?;
+        }
+        Ok(())
+    }
+
+    fn generation(&self) -> u64 {
+        self.layers.iter().fold(0, |generation, layer| {
+            generation.wrapping_add(layer.vocabulary.generation())
+        })
+    }
+
+    fn find_connection(&self, from: &Node, to: &Entry) -> Result<Connection> {
+        for layer in &self.layers {
+            let connection = layer.vocabulary.find_connection(from, to)?;
+            if !connection.is_infinite() {
+                return Ok(connection);
+            }
+        }
+        Ok(Connection::infinity())
+    }
+
+    fn find_trigram_connection(
+        &self,
+        preceding: Option<&Node>,
+        from: &Node,
+        to: &Entry,
+    ) -> Result<Connection> {
+        for layer in &self.layers {
+            let connection = layer
+                .vocabulary
+                .find_trigram_connection(preceding, from, to)?;
+            if !connection.is_infinite() {
+                return Ok(connection);
+            }
+        }
+        Ok(Connection::infinity())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::hash::{DefaultHasher, Hash, Hasher};
+
+    use crate::hash_map_vocabulary::HashMapVocabulary;
+    use crate::lattice::Lattice;
+    use crate::string_input::StringInput;
+
+    use super::*;
+
+    fn to_input(string: &str) -> Box<dyn Input> {
+        Box::new(StringInput::new(string.to_string()))
+    }
+
+    fn value_of(entry: &Entry) -> &str {
+        entry
+            .value()
+            .and_then(|v| v.downcast_ref::<&str>())
+            .unwrap_or(&"")
+    }
+
+    fn entry_hash(entry: &Entry) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        value_of(entry).hash(&mut hasher);
+        hasher.finish()
+    }
+
+    fn entry_equal_to(one: &Entry, other: &Entry) -> bool {
+        value_of(one) == value_of(other)
+    }
+
+    fn create_vocabulary(value: &'static str, cost: i32) -> HashMapVocabulary<'static> {
+        let entry = Entry::new(to_input("mizuho"), Box::new(value), cost);
+        HashMapVocabulary::new(
+            vec![(String::from("mizuho"), vec![entry.clone()])],
+            vec![
+                ((Entry::BosEos, entry.clone()), 1),
+                ((entry, Entry::BosEos), 2),
+            ],
+            &entry_hash,
+            &entry_equal_to,
+        )
+    }
+
+    #[test]
+    fn new() {
+        let vocabulary = LayeredVocabulary::new();
+        let entries = vocabulary
+            .find_entries(to_input("mizuho").as_ref())
+            .unwrap();
+        assert!(entries.is_empty());
+    }
+
+    #[test]
+    fn layer() {
+        let system_vocabulary = create_vocabulary("system", 20);
+        let user_vocabulary = create_vocabulary("user", 60);
+        let vocabulary = LayeredVocabulary::new()
+            .layer(&system_vocabulary, 0, 0)
+            .layer(&user_vocabulary, 1, -50);
+
+        let entries = vocabulary
+            .find_entries(to_input("mizuho").as_ref())
+            .unwrap();
+        let values_and_costs = entries
+            .iter()
+            .map(|entry| (value_of(entry), entry.cost()))
+            .collect::<Vec<_>>();
+        assert_eq!(values_and_costs, vec![("user", 10), ("system", 20)]);
+    }
+
+    #[test]
+    fn find_entries_at() {
+        let system_vocabulary = create_vocabulary("system", 20);
+        let user_vocabulary = create_vocabulary("user", 60);
+        let vocabulary = LayeredVocabulary::new()
+            .layer(&system_vocabulary, 0, 0)
+            .layer(&user_vocabulary, 1, -50);
+
+        let input = to_input("[mizuho]");
+        let found = vocabulary.find_entries_at(input.as_ref(), 1).unwrap();
+        let lengths_values_and_costs = found
+            .iter()
+            .map(|(length, entry)| (*length, value_of(entry), entry.cost()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            lengths_values_and_costs,
+            vec![(6, "user", 10), (6, "system", 20)]
+        );
+
+        assert!(vocabulary
+            .find_entries_at(input.as_ref(), 0)
+            .unwrap()
+            .is_empty());
+        assert!(vocabulary.find_entries_at(input.as_ref(), 9).is_err());
+    }
+
+    #[test]
+    fn find_connection() {
+        let system_vocabulary = create_vocabulary("system", 20);
+        let user_vocabulary = create_vocabulary("user", 60);
+        let vocabulary = LayeredVocabulary::new()
+            .layer(&system_vocabulary, 0, 0)
+            .layer(&user_vocabulary, 1, -50);
+
+        let mut lattice = Lattice::new(&vocabulary);
+        lattice.push_back(to_input("mizuho")).unwrap();
+        let eos_node = lattice.settle().unwrap();
+
+        let nodes = lattice.nodes_at(1).unwrap();
+        assert_eq!(nodes.len(), 2);
+        assert_eq!(nodes[0].preceding_edge_costs(), &[1]);
+        assert_eq!(nodes[1].preceding_edge_costs(), &[1]);
+        assert_eq!(eos_node.preceding_edge_costs(), &[2, 2]);
+        assert_eq!(eos_node.best_preceding_node(), 0);
+        assert_eq!(eos_node.path_cost(), 13);
+
+        let connection = vocabulary
+            .find_connection(
+                &nodes[0],
+                &Entry::new(to_input("sakura"), Box::new("sakura"), 0),
+            )
+            .unwrap();
+        assert_eq!(connection.cost(), i32::MAX);
+    }
+}
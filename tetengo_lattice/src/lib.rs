@@ -1,35 +1,88 @@
 #![doc = include_str!("../README.md")]
 #![doc = "# Examples"]
 #![doc = "```rust"]
-#![doc = include_str!("../tests/viterbi.rs")]
+#![doc = include_str!("../examples/usage.rs")]
 #![doc = "```"]
 
+pub mod a_star_search_strategy;
+pub mod approximate_entry;
+pub mod beam_search_strategy;
+#[cfg(feature = "uniffi")]
+pub mod bindings;
+pub mod calibrator;
+pub mod confusion_network_input;
+pub mod confusion_network_vocabulary;
 pub mod connection;
+pub mod connection_matrix;
 pub mod constraint;
 pub mod constraint_element;
+pub mod cost_quantizer;
+pub mod decoder;
+pub mod decoding_mode;
 pub mod entry;
+pub mod enumerable_vocabulary;
+pub mod error;
+pub mod exact_search_strategy;
+pub mod features;
+#[cfg(feature = "ffi")]
+pub mod ffi;
 pub mod hash_map_vocabulary;
 pub mod input;
 pub mod lattice;
+pub mod layered_vocabulary;
 pub mod n_best_iterator;
 pub mod node;
 pub mod node_constraint_element;
 pub mod path;
+pub mod search_strategy;
+pub mod segment_collector;
 pub mod string_input;
+#[cfg(feature = "test-util")]
+pub mod test_util;
+#[cfg(feature = "trie")]
+pub mod trie_vocabulary;
 pub mod vocabulary;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 pub mod wildcard_constraint_element;
 
+pub use a_star_search_strategy::AStarSearchStrategy;
+pub use approximate_entry::{make_approximate_entries, ApproximateHit};
+pub use beam_search_strategy::BeamSearchStrategy;
+pub use calibrator::{
+    CalibrationReport, Calibrator, ConnectionStatistics, GoldSegment, LostSample,
+};
+pub use confusion_network_input::{Alternative, ConfusionNetworkInput};
+pub use confusion_network_vocabulary::ConfusionNetworkVocabulary;
 pub use connection::Connection;
+pub use connection_matrix::{ConnectionMatrix, ConnectionMatrixError};
 pub use constraint::Constraint;
 pub use constraint_element::ConstraintElement;
-pub use entry::Entry;
+pub use cost_quantizer::{CostQuantizer, CostQuantizerError};
+pub use decoder::Decoder;
+pub use decoding_mode::DecodingMode;
+pub use entry::{Entry, MiddleEntry};
+pub use enumerable_vocabulary::EnumerableVocabulary;
+pub use error::{LatticeError, Result};
+pub use exact_search_strategy::ExactSearchStrategy;
+pub use features::{FeatureLayout, Features};
 pub use hash_map_vocabulary::HashMapVocabulary;
 pub use input::{Input, InputError};
-pub use lattice::Lattice;
+#[cfg(feature = "debug")]
+pub use lattice::{DropReason, DroppedNode};
+pub use lattice::{Lattice, LatticeBuilder, NodeOverflowPolicy, SkippedInput};
+pub use layered_vocabulary::LayeredVocabulary;
 pub use n_best_iterator::NBestIterator;
-pub use node::{Node, NodeError};
+pub use node::{Node, NodeError, OwnedNode};
 pub use node_constraint_element::NodeConstraintElement;
-pub use path::Path;
+pub use path::{CostContribution, Path, PathDifference};
+pub use search_strategy::SearchStrategy;
+pub use segment_collector::SegmentCollector;
 pub use string_input::StringInput;
+#[cfg(feature = "trie")]
+pub use trie_vocabulary::{TrieVocabulary, TrieVocabularyBuilder};
 pub use vocabulary::Vocabulary;
 pub use wildcard_constraint_element::WildcardConstraintElement;
+
+#[cfg(feature = "uniffi")]
+uniffi::setup_scaffolding!();
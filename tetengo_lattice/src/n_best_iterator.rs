@@ -7,19 +7,32 @@
 use std::cmp::{Ordering, Reverse};
 use std::collections::BinaryHeap;
 
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+use crate::connection::Connection;
 use crate::constraint::Constraint;
+use crate::error::{LatticeError, Result};
 use crate::lattice::Lattice;
 use crate::node::Node;
 use crate::path::Path;
 
 /**
  * An N-best lattice path iterator.
+ *
+ * The paths are enumerated in the ascending order of their costs. The paths with the same cost
+ * are enumerated in the lexicographic order of the steps and the indices in the steps of their
+ * nodes compared from the EOS toward the BOS, so that the order is reproducible.
  */
 #[derive(Debug)]
 pub struct NBestIterator<'a> {
     lattice: &'a Lattice<'a>,
+    cost_graph: CostGraph<'a>,
     caps: BinaryHeap<Reverse<Cap>>,
+    opened_paths: BinaryHeap<Reverse<OpenedPath>>,
     constraint: Box<Constraint<'a>>,
+    batch_size: usize,
+    path_found: bool,
 }
 
 impl<'a> NBestIterator<'a> {
@@ -32,6 +45,7 @@ impl<'a> NBestIterator<'a> {
      * * `constraint` - A constraint.
      */
     pub fn new(lattice: &'a Lattice<'a>, eos_node: Node, constraint: Box<Constraint<'a>>) -> Self {
+        let cost_graph = CostGraph::new(lattice, &eos_node);
         let mut caps = BinaryHeap::new();
         let tail_path_cost = eos_node.node_cost();
         let whole_path_cost = eos_node.path_cost();
@@ -42,97 +56,123 @@ impl<'a> NBestIterator<'a> {
         )));
         Self {
             lattice,
+            cost_graph,
             caps,
+            opened_paths: BinaryHeap::new(),
             constraint,
+            batch_size: 1,
+            path_found: false,
         }
     }
 
-    fn open_cap(
-        lattice: &Lattice<'a>,
-        caps: &mut BinaryHeap<Reverse<Cap>>,
-        constraint: &Constraint<'a>,
-    ) -> Option<Path> {
-        let mut path = None;
-        while !caps.is_empty() {
-            let Some(opened) = caps.pop() else {
-                unreachable!("caps must not be empty.");
-            };
-            let opened = opened.0;
+    /**
+     * Sets a batch size.
+     *
+     * The iterator opens up to the batch size of the cheapest caps at once on the threads of
+     * rayon, and merges the paths found in them with the remaining caps, so that the paths are
+     * still enumerated in the same order as one cap at a time. A large batch size suits
+     * enumerating many paths on a long input.
+     *
+     * # Arguments
+     * * `batch_size` - A batch size. Zero is treated as one.
+     */
+    #[cfg(feature = "rayon")]
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /**
+     * Returns the next path.
+     *
+     * # Returns
+     * The next path. Or `None` when all the paths conforming to the constraint are enumerated.
+     *
+     * # Errors
+     * * When no path in the lattice conforms to the constraint.
+     */
+    pub fn try_next(&mut self) -> Result<Option<Path>> {
+        match self.next() {
+            Some(path) => Ok(Some(path)),
+            None if self.path_found => Ok(None),
+            None => Err(LatticeError::ConstraintUnsatisfiable),
+        }
+    }
 
-            let mut next_path = opened.tail_path().to_vec();
-            let mut tail_path_cost = opened.tail_path_cost();
-            let mut nonconforming_path = false;
-            let Some(mut node) = opened.tail_path().last() else {
-                unreachable!("tail_path must not be empty.");
+    fn open_caps(&mut self) {
+        let mut opened_caps = Vec::with_capacity(self.batch_size.min(self.caps.len()));
+        while opened_caps.len() < self.batch_size {
+            let Some(opened) = self.caps.pop() else {
+                break;
             };
-            while !node.is_bos() {
-                let Ok(preceding_nodes) = lattice.nodes_at(node.preceding_step()) else {
-                    unreachable!("preceding_step must be within the preceding steps in lattice.");
-                };
-                for (i, preceding_node) in preceding_nodes.iter().enumerate() {
-                    if i == node.best_preceding_node() {
-                        continue;
-                    }
-                    let mut cap_tail_path = next_path.clone();
-                    cap_tail_path.push(preceding_node.clone());
-                    if !constraint.matches_tail(&cap_tail_path) {
-                        continue;
-                    }
-                    let preceding_edge_cost = node.preceding_edge_costs()[i];
-                    let cap_tail_path_cost = Self::add_cost(
-                        Self::add_cost(tail_path_cost, preceding_edge_cost),
-                        preceding_node.node_cost(),
-                    );
-                    if cap_tail_path_cost == i32::MAX {
-                        continue;
-                    }
-                    let cap_whole_path_cost = Self::add_cost(
-                        Self::add_cost(tail_path_cost, preceding_edge_cost),
-                        preceding_node.path_cost(),
-                    );
-                    if cap_whole_path_cost == i32::MAX {
-                        continue;
-                    }
-                    caps.push(Reverse(Cap::new(
-                        cap_tail_path,
-                        cap_tail_path_cost,
-                        cap_whole_path_cost,
-                    )));
-                }
+            opened_caps.push(opened.0);
+        }
+        #[cfg(feature = "tracing")]
+        tracing::trace!(
+            opened_cap_count = opened_caps.len(),
+            remaining_cap_count = self.caps.len(),
+            "opened caps"
+        );
+
+        // The caps are expanded on their index paths, which can be sent to other threads, and
+        // the nodes and the constraint are applied to the expansions on this thread.
+        let index_paths = opened_caps.iter().map(IndexPath::of).collect::<Vec<_>>();
+        #[cfg(feature = "rayon")]
+        let expansions = index_paths
+            .par_iter()
+            .map(|index_path| self.cost_graph.expand(index_path))
+            .collect::<Vec<_>>();
+        #[cfg(not(feature = "rayon"))]
+        let expansions = index_paths
+            .iter()
+            .map(|index_path| self.cost_graph.expand(index_path))
+            .collect::<Vec<_>>();
+
+        // The paths found in the caps are merged with the remaining caps, since a cap derived
+        // from a cheaper cap in the batch may precede a path found in a more expensive one.
+        for (opened, expansion) in opened_caps.into_iter().zip(expansions) {
+            if let Some(path) = self.apply_expansion(&opened, expansion) {
+                self.opened_paths
+                    .push(Reverse(OpenedPath { cap: opened, path }));
+            }
+        }
+    }
 
-                let best_preceding_edge_cost =
-                    node.preceding_edge_costs()[node.best_preceding_node()];
-                let best_preceding_node = &preceding_nodes[node.best_preceding_node()];
-                next_path.push(best_preceding_node.clone());
-                if !constraint.matches_tail(&next_path) {
-                    nonconforming_path = true;
-                    break;
+    fn apply_expansion(&mut self, opened: &Cap, expansion: Expansion) -> Option<Path> {
+        let mut next_path = opened.tail_path().to_vec();
+        let mut derived_caps = expansion.derived_caps.into_iter().peekable();
+        for &position in &expansion.best_path[opened.tail_path().len() - 1..] {
+            while let Some(derived_cap) =
+                derived_caps.next_if(|derived_cap| derived_cap.tail_length == next_path.len() - 1)
+            {
+                let mut cap_tail_path = next_path.clone();
+                cap_tail_path.push(self.node_at(derived_cap.position));
+                if !self.constraint.matches_tail(&cap_tail_path) {
+                    continue;
                 }
-                tail_path_cost = Self::add_cost(
-                    tail_path_cost,
-                    Self::add_cost(best_preceding_edge_cost, best_preceding_node.node_cost()),
-                );
-
-                node = best_preceding_node;
+                self.caps.push(Reverse(Cap::new(
+                    cap_tail_path,
+                    derived_cap.tail_path_cost,
+                    derived_cap.whole_path_cost,
+                )));
             }
 
-            if !nonconforming_path {
-                assert!(constraint.matches(&next_path));
-                let reversed_next_path = next_path.iter().rev().cloned().collect();
-                path = Some(Path::new(reversed_next_path, opened.whole_path_cost()));
-                break;
+            next_path.push(self.node_at(position));
+            if !self.constraint.matches_tail(&next_path) {
+                return None;
             }
         }
 
-        path
+        assert!(self.constraint.matches(&next_path));
+        let reversed_next_path = next_path.iter().rev().cloned().collect();
+        Some(Path::new(reversed_next_path, opened.whole_path_cost()))
     }
 
-    const fn add_cost(one: i32, another: i32) -> i32 {
-        if one == i32::MAX || another == i32::MAX {
-            i32::MAX
-        } else {
-            one + another
-        }
+    fn node_at(&self, (step, index_in_step): (usize, usize)) -> Node {
+        let Ok(nodes) = self.lattice.nodes_at(step) else {
+            unreachable!("step must be within the steps in lattice.");
+        };
+        nodes[index_in_step].clone()
     }
 }
 
@@ -140,14 +180,231 @@ impl Iterator for NBestIterator<'_> {
     type Item = Path;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.caps.is_empty() {
-            None
-        } else {
-            Self::open_cap(self.lattice, &mut self.caps, self.constraint.as_ref())
+        loop {
+            if let Some(opened_path) = self.opened_paths.peek() {
+                if self
+                    .caps
+                    .peek()
+                    .is_none_or(|cap| opened_path.0.cap <= cap.0)
+                {
+                    self.path_found = true;
+                    return self
+                        .opened_paths
+                        .pop()
+                        .map(|opened_path| opened_path.0.path);
+                }
+            }
+            if self.caps.is_empty() {
+                return None;
+            }
+            self.open_caps();
+        }
+    }
+}
+
+/**
+ * The costs of the nodes in a lattice.
+ *
+ * It holds no node but the costs and the links of the nodes, so that it can be shared among
+ * threads to expand the caps.
+ */
+#[derive(Debug)]
+struct CostGraph<'a> {
+    steps: Vec<Vec<NodeCosts<'a>>>,
+    eos_preceding_step: usize,
+    eos_preceding_edge_costs: Vec<i32>,
+    eos_best_preceding_node: usize,
+    eos_node_cost: i32,
+    eos_path_cost: i32,
+}
+
+impl<'a> CostGraph<'a> {
+    fn new(lattice: &'a Lattice<'a>, eos_node: &Node) -> Self {
+        let steps = (0..lattice.step_count())
+            .map(|step| {
+                let Ok(nodes) = lattice.nodes_at(step) else {
+                    unreachable!("step must be within the steps in lattice.");
+                };
+                nodes.iter().map(NodeCosts::of).collect()
+            })
+            .collect();
+        Self {
+            steps,
+            eos_preceding_step: eos_node.preceding_step(),
+            eos_preceding_edge_costs: eos_node.preceding_edge_costs().to_vec(),
+            eos_best_preceding_node: eos_node.best_preceding_node(),
+            eos_node_cost: eos_node.node_cost(),
+            eos_path_cost: eos_node.path_cost(),
+        }
+    }
+
+    fn node_at(&self, position: Option<(usize, usize)>) -> NodeCosts<'_> {
+        match position {
+            Some((step, index_in_step)) => self.steps[step][index_in_step],
+            None => NodeCosts {
+                is_bos: false,
+                preceding_step: self.eos_preceding_step,
+                preceding_edge_costs: &self.eos_preceding_edge_costs,
+                best_preceding_node: self.eos_best_preceding_node,
+                node_cost: self.eos_node_cost,
+                path_cost: self.eos_path_cost,
+            },
+        }
+    }
+
+    fn expand(&self, index_path: &IndexPath) -> Expansion {
+        let mut best_path = index_path.positions.clone();
+        let mut derived_caps = Vec::new();
+        let mut tail_path_cost = index_path.tail_path_cost;
+        let mut node = self.node_at(best_path.last().copied());
+        while !node.is_bos {
+            let preceding_nodes = &self.steps[node.preceding_step];
+            for (i, preceding_node) in preceding_nodes.iter().enumerate() {
+                if i == node.best_preceding_node {
+                    continue;
+                }
+                let preceding_edge_cost = node.preceding_edge_costs[i];
+                let cap_tail_path_cost = Connection::add_cost(
+                    Connection::add_cost(tail_path_cost, preceding_edge_cost),
+                    preceding_node.node_cost,
+                );
+                if cap_tail_path_cost == Connection::INFINITE_COST {
+                    continue;
+                }
+                let cap_whole_path_cost = Connection::add_cost(
+                    Connection::add_cost(tail_path_cost, preceding_edge_cost),
+                    preceding_node.path_cost,
+                );
+                if cap_whole_path_cost == Connection::INFINITE_COST {
+                    continue;
+                }
+                derived_caps.push(DerivedCap {
+                    tail_length: best_path.len(),
+                    position: (node.preceding_step, i),
+                    tail_path_cost: cap_tail_path_cost,
+                    whole_path_cost: cap_whole_path_cost,
+                });
+            }
+
+            let best_preceding_edge_cost = node.preceding_edge_costs[node.best_preceding_node];
+            let best_preceding_node = preceding_nodes[node.best_preceding_node];
+            best_path.push((node.preceding_step, node.best_preceding_node));
+            tail_path_cost = Connection::add_cost(
+                tail_path_cost,
+                Connection::add_cost(best_preceding_edge_cost, best_preceding_node.node_cost),
+            );
+
+            node = best_preceding_node;
+        }
+
+        Expansion {
+            best_path,
+            derived_caps,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct NodeCosts<'a> {
+    is_bos: bool,
+    preceding_step: usize,
+    preceding_edge_costs: &'a [i32],
+    best_preceding_node: usize,
+    node_cost: i32,
+    path_cost: i32,
+}
+
+impl<'a> NodeCosts<'a> {
+    fn of(node: &'a Node) -> Self {
+        Self {
+            is_bos: node.is_bos(),
+            preceding_step: node.preceding_step(),
+            preceding_edge_costs: node.preceding_edge_costs(),
+            best_preceding_node: node.best_preceding_node(),
+            node_cost: node.node_cost(),
+            path_cost: node.path_cost(),
         }
     }
 }
 
+/**
+ * A tail path of a cap as the positions of its nodes following the EOS.
+ */
+#[derive(Debug)]
+struct IndexPath {
+    positions: Vec<(usize, usize)>,
+    tail_path_cost: i32,
+}
+
+impl IndexPath {
+    fn of(cap: &Cap) -> Self {
+        Self {
+            positions: cap.tail_path()[1..]
+                .iter()
+                .map(|node| (node.step(), node.index_in_step()))
+                .collect(),
+            tail_path_cost: cap.tail_path_cost(),
+        }
+    }
+}
+
+/**
+ * An expansion of a cap.
+ *
+ * It holds the best path completing the tail path of the cap and the caps derived on the way,
+ * in the order of the lengths of their tail paths.
+ */
+#[derive(Debug)]
+struct Expansion {
+    best_path: Vec<(usize, usize)>,
+    derived_caps: Vec<DerivedCap>,
+}
+
+/**
+ * A cap derived from a cap.
+ *
+ * Its tail path is the EOS, the first `tail_length` nodes of the best path and the node at the
+ * position.
+ */
+#[derive(Debug)]
+struct DerivedCap {
+    tail_length: usize,
+    position: (usize, usize),
+    tail_path_cost: i32,
+    whole_path_cost: i32,
+}
+
+/**
+ * A path found in an opened cap.
+ *
+ * It is ordered by the cap, in which order the paths are enumerated.
+ */
+#[derive(Debug)]
+struct OpenedPath {
+    cap: Cap,
+    path: Path,
+}
+
+impl Ord for OpenedPath {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.cap.cmp(&other.cap)
+    }
+}
+
+impl PartialEq for OpenedPath {
+    fn eq(&self, other: &Self) -> bool {
+        self.cap == other.cap
+    }
+}
+
+impl Eq for OpenedPath {}
+
+impl PartialOrd for OpenedPath {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
 #[derive(Debug, Eq)]
 struct Cap {
     tail_path: Vec<Node>,
@@ -179,19 +436,27 @@ impl Cap {
 
 impl Ord for Cap {
     fn cmp(&self, other: &Self) -> Ordering {
-        self.whole_path_cost.cmp(&other.whole_path_cost)
+        self.whole_path_cost
+            .cmp(&other.whole_path_cost)
+            .then_with(|| {
+                let position = |node: &Node| (node.step(), node.index_in_step());
+                self.tail_path
+                    .iter()
+                    .map(position)
+                    .cmp(other.tail_path.iter().map(position))
+            })
     }
 }
 
 impl PartialEq for Cap {
     fn eq(&self, other: &Self) -> bool {
-        self.whole_path_cost == other.whole_path_cost
+        self.cmp(other) == Ordering::Equal
     }
 }
 
 impl PartialOrd for Cap {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.whole_path_cost.cmp(&other.whole_path_cost))
+        Some(self.cmp(other))
     }
 }
 
@@ -463,6 +728,69 @@ mod tests {
         let _iterator = NBestIterator::new(&lattice, eos_node, Box::new(Constraint::new()));
     }
 
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn batch_size() {
+        let vocabulary = create_vocabulary();
+        let mut lattice = Lattice::new(vocabulary.as_ref());
+        let _result = lattice.push_back(to_input("[HakataTosu]"));
+        let _result = lattice.push_back(to_input("[TosuOmuta]"));
+        let _result = lattice.push_back(to_input("[OmutaKumamoto]"));
+
+        let eos_node = lattice.settle().unwrap();
+        let enumerate = |constraint: Constraint<'static>, batch_size: usize| {
+            NBestIterator::new(&lattice, eos_node.clone(), Box::new(constraint))
+                .batch_size(batch_size)
+                .map(|path| {
+                    let values = path
+                        .nodes()
+                        .iter()
+                        .filter_map(|node| node.value().and_then(|v| v.downcast_ref::<&str>()))
+                        .copied()
+                        .collect::<Vec<_>>();
+                    (values, path.cost())
+                })
+                .collect::<Vec<_>>()
+        };
+
+        {
+            let expected = enumerate(Constraint::new(), 1);
+            assert_eq!(expected.len(), 9);
+            assert_eq!(
+                expected[2..5],
+                [
+                    (vec!["rapid811", "local817"], 3760),
+                    (vec!["local415", "local815"], 4050),
+                    (vec!["kamome", "local815"], 4320),
+                ]
+            );
+            assert_eq!(enumerate(Constraint::new(), 0), expected);
+            assert_eq!(enumerate(Constraint::new(), 3), expected);
+            assert_eq!(enumerate(Constraint::new(), 100), expected);
+        }
+        {
+            let path = NBestIterator::new(&lattice, eos_node.clone(), Box::new(Constraint::new()))
+                .nth(2)
+                .unwrap();
+            let make_constraint = || {
+                let pattern: Vec<Box<dyn ConstraintElement>> = vec![
+                    Box::new(NodeConstraintElement::new(path.nodes()[0].clone())),
+                    Box::new(WildcardConstraintElement::new(0)),
+                    Box::new(NodeConstraintElement::new(path.nodes()[2].clone())),
+                    Box::new(NodeConstraintElement::new(path.nodes()[3].clone())),
+                ];
+                Constraint::new_with_pattern(pattern)
+            };
+            let expected = enumerate(make_constraint(), 1);
+            assert_eq!(
+                expected.iter().map(|(_, cost)| *cost).collect::<Vec<_>>(),
+                [3760, 4600, 4680, 4950]
+            );
+            assert_eq!(enumerate(make_constraint(), 2), expected);
+            assert_eq!(enumerate(make_constraint(), 100), expected);
+        }
+    }
+
     #[test]
     fn next() {
         {
@@ -859,6 +1187,110 @@ mod tests {
         }
     }
 
+    #[test]
+    fn try_next() {
+        let vocabulary = create_vocabulary();
+        let mut lattice = Lattice::new(vocabulary.as_ref());
+        let _result = lattice.push_back(to_input("[HakataTosu]"));
+        let _result = lattice.push_back(to_input("[TosuOmuta]"));
+        let _result = lattice.push_back(to_input("[OmutaKumamoto]"));
+
+        let eos_node = lattice.settle().unwrap();
+        {
+            let mut iterator =
+                NBestIterator::new(&lattice, eos_node.clone(), Box::new(Constraint::new()));
+
+            let path = iterator.try_next().unwrap().unwrap();
+            assert_eq!(path.cost(), 3390);
+
+            let mut count = 1;
+            while iterator.try_next().unwrap().is_some() {
+                count += 1;
+            }
+            let expected_count =
+                NBestIterator::new(&lattice, eos_node.clone(), Box::new(Constraint::new())).count();
+            assert_eq!(count, expected_count);
+            assert!(iterator.try_next().unwrap().is_none());
+        }
+        {
+            let mut iterator =
+                NBestIterator::new(&lattice, eos_node.clone(), Box::new(Constraint::new()));
+            let path = iterator.next().unwrap();
+
+            let pattern: Vec<Box<dyn ConstraintElement>> = vec![
+                Box::new(NodeConstraintElement::new(path.nodes()[0].clone())),
+                Box::new(NodeConstraintElement::new(path.nodes()[2].clone())),
+            ];
+            let constraint = Box::new(Constraint::new_with_pattern(pattern));
+            let mut constrained_iterator = NBestIterator::new(&lattice, eos_node, constraint);
+
+            let error = constrained_iterator.try_next().unwrap_err();
+            assert!(matches!(error, LatticeError::ConstraintUnsatisfiable));
+        }
+    }
+
+    #[test]
+    fn next_with_composed_constraint() {
+        let vocabulary = create_vocabulary();
+        let mut lattice = Lattice::new(vocabulary.as_ref());
+        let _result = lattice.push_back(to_input("[HakataTosu]"));
+        let _result = lattice.push_back(to_input("[TosuOmuta]"));
+        let _result = lattice.push_back(to_input("[OmutaKumamoto]"));
+
+        let eos_node = lattice.settle().unwrap();
+        let paths = NBestIterator::new(&lattice, eos_node.clone(), Box::new(Constraint::new()))
+            .take(2)
+            .collect::<Vec<_>>();
+        let make_constraint = |path: &Path| {
+            let pattern = path
+                .nodes()
+                .iter()
+                .map(|node| {
+                    let element: Box<dyn ConstraintElement> =
+                        Box::new(NodeConstraintElement::new(node.clone()));
+                    element
+                })
+                .collect();
+            Constraint::new_with_pattern(pattern)
+        };
+        {
+            let constraint = Box::new(Constraint::any_of(vec![
+                make_constraint(&paths[0]),
+                make_constraint(&paths[1]),
+            ]));
+            let costs = NBestIterator::new(&lattice, eos_node.clone(), constraint)
+                .map(|path| path.cost())
+                .collect::<Vec<_>>();
+
+            assert_eq!(costs, vec![3390, 3620]);
+        }
+        {
+            let constraint = Box::new(Constraint::negation(make_constraint(&paths[0])));
+            let mut iterator = NBestIterator::new(&lattice, eos_node.clone(), constraint);
+
+            assert_eq!(iterator.next().unwrap().cost(), 3620);
+            assert_eq!(iterator.count(), 7);
+        }
+        {
+            let constraint = Box::new(Constraint::all_of(vec![
+                Constraint::negation(make_constraint(&paths[0])),
+                Constraint::negation(make_constraint(&paths[1])),
+            ]));
+            let mut iterator = NBestIterator::new(&lattice, eos_node.clone(), constraint);
+
+            assert_eq!(iterator.next().unwrap().cost(), 3760);
+        }
+        {
+            let constraint = Box::new(Constraint::all_of(vec![
+                make_constraint(&paths[0]),
+                make_constraint(&paths[1]),
+            ]));
+            let mut iterator = NBestIterator::new(&lattice, eos_node, constraint);
+
+            assert!(iterator.next().is_none());
+        }
+    }
+
     mod cap {
         use super::*;
 
@@ -889,6 +1321,26 @@ mod tests {
 
             assert!(cap1 == cap2);
             assert!(cap1 < cap3);
+
+            let make_tied_cap = |index_in_step| {
+                let node = Node::new(
+                    Box::new(StringInput::new(String::from("mizuho"))),
+                    Box::new(42),
+                    index_in_step,
+                    0,
+                    Rc::new(vec![0]),
+                    0,
+                    0,
+                    0,
+                );
+                Cap::new(vec![Node::eos(1, Rc::new(vec![0, 0]), 0, 0), node], 24, 42)
+            };
+            let cap4 = make_tied_cap(0);
+            let cap5 = make_tied_cap(1);
+
+            assert!(cap4 < cap5);
+            assert!(cap4 != cap5);
+            assert!(cap4 == make_tied_cap(0));
         }
 
         #[test]
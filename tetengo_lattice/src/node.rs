@@ -4,14 +4,23 @@
  * Copyright (C) 2023-2025 kaoru  <https://www.tetengo.org/>
  */
 
-use std::any::Any;
-use std::fmt::Debug;
+use std::any::{Any, TypeId};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt::{self, Debug, Formatter};
+use std::ops::Range;
 use std::rc::Rc;
+#[cfg(feature = "serde")]
+use std::result;
 
-use anyhow::Result;
+#[cfg(feature = "serde")]
+use serde::ser::{Serialize, SerializeStruct, Serializer};
 
-use crate::entry::Entry;
+use crate::entry::{Entry, MiddleEntry};
+use crate::error::Result;
 use crate::input::Input;
+#[cfg(feature = "serde")]
+use crate::string_input::StringInput;
 
 /**
  * A node error.
@@ -25,12 +34,90 @@ pub enum NodeError {
     BosOrEosEntryNotAllowed,
 }
 
+/**
+ * Preceding edge costs.
+ *
+ * The costs are a range of an arena shared among the nodes in a step, so that creating a node
+ * does not allocate its own cost vector.
+ */
+#[derive(Clone)]
+struct PrecedingEdgeCosts {
+    arena: Rc<Vec<i32>>,
+    range: Option<Range<usize>>,
+}
+
+impl PrecedingEdgeCosts {
+    const fn new(costs: Rc<Vec<i32>>) -> Self {
+        Self {
+            arena: costs,
+            range: None,
+        }
+    }
+
+    const fn new_in_arena(arena: Rc<Vec<i32>>, range: Range<usize>) -> Self {
+        Self {
+            arena,
+            range: Some(range),
+        }
+    }
+
+    fn as_slice(&self) -> &[i32] {
+        match &self.range {
+            Some(range) => &self.arena[range.clone()],
+            None => self.arena.as_slice(),
+        }
+    }
+}
+
+impl Debug for PrecedingEdgeCosts {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.as_slice().fmt(f)
+    }
+}
+
+impl Eq for PrecedingEdgeCosts {}
+
+impl PartialEq for PrecedingEdgeCosts {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+/**
+ * User data.
+ *
+ * The data are shared among the clones of a node, so that the data attached to a node in a
+ * lattice can be retrieved from the nodes of the paths. They do not take part in the equality of
+ * nodes.
+ */
+#[derive(Clone, Default)]
+struct UserData {
+    slots: Rc<RefCell<HashMap<TypeId, Rc<dyn Any>>>>,
+}
+
+impl Debug for UserData {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UserData")
+            .field("slot_count", &self.slots.borrow().len())
+            .finish()
+    }
+}
+
+impl Eq for UserData {}
+
+impl PartialEq for UserData {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
 /**
  * A BOS (Beginning of Sequence) node.
  */
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Bos {
-    preceding_edge_costs: Rc<Vec<i32>>,
+    preceding_edge_costs: PrecedingEdgeCosts,
+    user_data: UserData,
 }
 
 /**
@@ -39,21 +126,25 @@ pub struct Bos {
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Eos {
     preceding_step: usize,
-    preceding_edge_costs: Rc<Vec<i32>>,
+    preceding_edge_costs: PrecedingEdgeCosts,
     best_preceding_node: usize,
     path_cost: i32,
+    user_data: UserData,
 }
+
 /**
  * A middle node.
  */
 #[derive(Clone, Debug)]
 pub struct Middle {
     entry: Rc<Entry>,
+    step: usize,
     index_in_step: usize,
     preceding_step: usize,
-    preceding_edge_costs: Rc<Vec<i32>>,
+    preceding_edge_costs: PrecedingEdgeCosts,
     best_preceding_node: usize,
     path_cost: i32,
+    user_data: UserData,
 }
 
 impl Eq for Middle {}
@@ -69,6 +160,7 @@ impl PartialEq for Middle {
             .key()
             .unwrap_or_else(|| unreachable!("Middle entry must have a key."));
         self_key.equal_to(other_key)
+            && self.step == other.step
             && self.index_in_step == other.index_in_step
             && self.preceding_step == other.preceding_step
             && self.preceding_edge_costs == other.preceding_edge_costs
@@ -100,9 +192,10 @@ impl Node {
      * # Arguments
      * * preceding_edge_costs - Preceding edge costs.
      */
-    pub const fn bos(preceding_edge_costs: Rc<Vec<i32>>) -> Self {
+    pub fn bos(preceding_edge_costs: Rc<Vec<i32>>) -> Self {
         Node::Bos(Bos {
-            preceding_edge_costs,
+            preceding_edge_costs: PrecedingEdgeCosts::new(preceding_edge_costs),
+            user_data: UserData::default(),
         })
     }
 
@@ -115,7 +208,7 @@ impl Node {
      * * best_preceding_node  - An index of a best preceding node.
      * * path_cost            - A path cost.
      */
-    pub const fn eos(
+    pub fn eos(
         preceding_step: usize,
         preceding_edge_costs: Rc<Vec<i32>>,
         best_preceding_node: usize,
@@ -123,15 +216,18 @@ impl Node {
     ) -> Self {
         Node::Eos(Eos {
             preceding_step,
-            preceding_edge_costs,
+            preceding_edge_costs: PrecedingEdgeCosts::new(preceding_edge_costs),
             best_preceding_node,
             path_cost,
+            user_data: UserData::default(),
         })
     }
 
     /**
      * Creates a node.
      *
+     * The node belongs to the step next to the preceding step.
+     *
      * # Arguments
      * * key                  - A key.
      * * value                - A value.
@@ -155,17 +251,54 @@ impl Node {
         let entry = Rc::new(Entry::new(key, value, node_cost));
         Node::Middle(Middle {
             entry,
+            step: preceding_step.saturating_add(1),
             index_in_step,
             preceding_step,
-            preceding_edge_costs,
+            preceding_edge_costs: PrecedingEdgeCosts::new(preceding_edge_costs),
             best_preceding_node,
             path_cost,
+            user_data: UserData::default(),
         })
     }
 
+    /**
+     * Creates a node with a middle entry.
+     *
+     * The node belongs to the step next to the preceding step.
+     * Unlike `new_with_entry()`, it cannot fail since a middle entry is never BOS or EOS.
+     *
+     * # Arguments
+     * * entry                - A middle entry.
+     * * index_in_step        - An index in the step.
+     * * preceding_step       - An index of a preceding step.
+     * * preceding_edge_costs - Preceding edge costs.
+     * * best_preceding_node  - An index of a best preceding node.
+     * * path_cost            - A path cost.
+     */
+    pub fn new_with_middle_entry(
+        entry: MiddleEntry,
+        index_in_step: usize,
+        preceding_step: usize,
+        preceding_edge_costs: Rc<Vec<i32>>,
+        best_preceding_node: usize,
+        path_cost: i32,
+    ) -> Self {
+        Self::new_middle(
+            entry,
+            preceding_step.saturating_add(1),
+            index_in_step,
+            preceding_step,
+            PrecedingEdgeCosts::new(preceding_edge_costs),
+            best_preceding_node,
+            path_cost,
+        )
+    }
+
     /**
      * Creates a node with a vocabulary entry.
      *
+     * The node belongs to the step next to the preceding step.
+     *
      * # Errors
      * * When `entry` is BOS or EOS.
      */
@@ -177,17 +310,98 @@ impl Node {
         best_preceding_node: usize,
         path_cost: i32,
     ) -> Result<Self> {
-        if entry.is_bos_eos() {
-            return Err(NodeError::BosOrEosEntryNotAllowed.into());
+        Ok(Self::new_middle(
+            MiddleEntry::try_from(entry)?,
+            preceding_step.saturating_add(1),
+            index_in_step,
+            preceding_step,
+            PrecedingEdgeCosts::new(preceding_edge_costs),
+            best_preceding_node,
+            path_cost,
+        ))
+    }
+
+    /**
+     * Returns a copy of this node belonging to a step.
+     *
+     * The constructors place a node in the step next to the preceding step. Use it for a node
+     * whose entry spans more than one input. A BOS and an EOS are returned as they are, since
+     * their steps are determined by their preceding steps.
+     *
+     * # Arguments
+     * * `step` - An index of the step to which the node belongs.
+     *
+     * # Returns
+     * A copy of this node belonging to the step.
+     */
+    pub fn with_step(&self, step: usize) -> Self {
+        match self {
+            Node::Middle(middle) => Node::Middle(Middle {
+                step,
+                ..middle.clone()
+            }),
+            _ => self.clone(),
         }
-        Ok(Node::Middle(Middle {
-            entry,
+    }
+
+    pub(crate) fn new_with_entry_in_arena(
+        entry: Rc<Entry>,
+        step: usize,
+        index_in_step: usize,
+        preceding_step: usize,
+        arena: Rc<Vec<i32>>,
+        range: Range<usize>,
+        best_preceding_node: usize,
+        path_cost: i32,
+    ) -> Result<Self> {
+        Ok(Self::new_middle(
+            MiddleEntry::try_from(entry)?,
+            step,
+            index_in_step,
+            preceding_step,
+            PrecedingEdgeCosts::new_in_arena(arena, range),
+            best_preceding_node,
+            path_cost,
+        ))
+    }
+
+    fn new_middle(
+        entry: MiddleEntry,
+        step: usize,
+        index_in_step: usize,
+        preceding_step: usize,
+        preceding_edge_costs: PrecedingEdgeCosts,
+        best_preceding_node: usize,
+        path_cost: i32,
+    ) -> Self {
+        Node::Middle(Middle {
+            entry: entry.into_entry(),
+            step,
             index_in_step,
             preceding_step,
             preceding_edge_costs,
             best_preceding_node,
             path_cost,
-        }))
+            user_data: UserData::default(),
+        })
+    }
+
+    pub(crate) fn preceding_edge_cost_arena(&self) -> &Rc<Vec<i32>> {
+        match self {
+            Node::Bos(bos) => &bos.preceding_edge_costs.arena,
+            Node::Eos(eos) => &eos.preceding_edge_costs.arena,
+            Node::Middle(middle) => &middle.preceding_edge_costs.arena,
+        }
+    }
+
+    pub(crate) fn with_index_in_step(&self, index_in_step: usize) -> Self {
+        match self {
+            Node::Middle(middle) => Node::Middle(Middle {
+                index_in_step,
+                ..middle.clone()
+            }),
+            _ => self.clone(),
+        }
     }
 
     pub(crate) fn entry(&self) -> Rc<Entry> {
@@ -226,6 +440,22 @@ impl Node {
         }
     }
 
+    /**
+     * Returns the step to which this node belongs.
+     *
+     * The BOS node belongs to the step 0, and the EOS node to the step next to the last one.
+     *
+     * # Returns
+     * The step.
+     */
+    pub const fn step(&self) -> usize {
+        match self {
+            Node::Bos(_) => 0,
+            Node::Eos(eos) => eos.preceding_step.saturating_add(1),
+            Node::Middle(middle) => middle.step,
+        }
+    }
+
     /**
      * Returns the index in the step.
      *
@@ -260,11 +490,11 @@ impl Node {
      * # Returns
      * The preceding edge costs.
      */
-    pub fn preceding_edge_costs(&self) -> &Vec<i32> {
+    pub fn preceding_edge_costs(&self) -> &[i32] {
         match self {
-            Node::Bos(bos) => bos.preceding_edge_costs.as_ref(),
-            Node::Eos(eos) => eos.preceding_edge_costs.as_ref(),
-            Node::Middle(middle) => middle.preceding_edge_costs.as_ref(),
+            Node::Bos(bos) => bos.preceding_edge_costs.as_slice(),
+            Node::Eos(eos) => eos.preceding_edge_costs.as_slice(),
+            Node::Middle(middle) => middle.preceding_edge_costs.as_slice(),
         }
     }
 
@@ -319,6 +549,275 @@ impl Node {
     pub const fn is_bos(&self) -> bool {
         matches!(self, Node::Bos(_))
     }
+
+    /**
+     * Returns `true` if this node is the EOS.
+     *
+     * # Returns
+     * `true` if this node is the EOS.
+     */
+    pub const fn is_eos(&self) -> bool {
+        matches!(self, Node::Eos(_))
+    }
+
+    /**
+     * Attaches user data.
+     *
+     * The data are attached by their type, replacing the data of the same type. They are shared
+     * among the clones of this node, so the data attached to a node in a lattice can be
+     * retrieved from the nodes of the paths.
+     *
+     * # Type Parameters
+     * * `T` - A data type.
+     *
+     * # Arguments
+     * * `data` - Data.
+     */
+    pub fn set_user_data<T: Any>(&self, data: T) {
+        let _prev_data = self
+            .user_data_slots()
+            .slots
+            .borrow_mut()
+            .insert(TypeId::of::<T>(), Rc::new(data));
+    }
+
+    /**
+     * Returns the attached user data.
+     *
+     * # Type Parameters
+     * * `T` - A data type.
+     *
+     * # Returns
+     * The data. Or `None` when no data of the type are attached.
+     */
+    pub fn user_data<T: Any>(&self) -> Option<Rc<T>> {
+        self.user_data_slots()
+            .slots
+            .borrow()
+            .get(&TypeId::of::<T>())
+            .and_then(|data| data.clone().downcast::<T>().ok())
+    }
+
+    /**
+     * Removes the attached user data.
+     *
+     * # Type Parameters
+     * * `T` - A data type.
+     *
+     * # Returns
+     * The removed data. Or `None` when no data of the type are attached.
+     */
+    pub fn remove_user_data<T: Any>(&self) -> Option<Rc<T>> {
+        self.user_data_slots()
+            .slots
+            .borrow_mut()
+            .remove(&TypeId::of::<T>())
+            .and_then(|data| data.downcast::<T>().ok())
+    }
+
+    const fn user_data_slots(&self) -> &UserData {
+        match self {
+            Node::Bos(bos) => &bos.user_data,
+            Node::Eos(eos) => &eos.user_data,
+            Node::Middle(middle) => &middle.user_data,
+        }
+    }
+
+    /**
+     * Creates an owned node.
+     *
+     * # Returns
+     * An owned node.
+     */
+    pub fn to_owned(&self) -> OwnedNode {
+        OwnedNode {
+            key: self.key().map(|key| {
+                key.create_subrange(0, key.length())
+                    .unwrap_or_else(|_| unreachable!("The whole range must be valid."))
+            }),
+            value: self.entry().shared_value(),
+            step: self.step(),
+            index_in_step: self.index_in_step(),
+            preceding_step: self.preceding_step(),
+            preceding_edge_costs: self.preceding_edge_costs().to_vec(),
+            best_preceding_node: self.best_preceding_node(),
+            node_cost: self.node_cost(),
+            path_cost: self.path_cost(),
+            is_bos: self.is_bos(),
+            is_eos: self.is_eos(),
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    pub(crate) fn surface(&self) -> Option<&str> {
+        self.key()
+            .and_then(|key| key.downcast_ref::<StringInput>())
+            .map(|key| key.value())
+    }
+
+    #[cfg(feature = "serde")]
+    pub(crate) const fn kind(&self) -> &'static str {
+        match self {
+            Node::Bos(_) => "bos",
+            Node::Eos(_) => "eos",
+            Node::Middle(_) => "middle",
+        }
+    }
+}
+
+/**
+ * Serializes a node.
+ *
+ * The surface is the value of the key when the key is a `StringInput`, and `None` otherwise.
+ * The value is not serialized since it cannot be inspected through `Any`.
+ */
+#[cfg(feature = "serde")]
+impl Serialize for Node {
+    fn serialize<S: Serializer>(&self, serializer: S) -> result::Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("Node", 8)?;
+        state.serialize_field("kind", self.kind())?;
+        state.serialize_field("surface", &self.surface())?;
+        state.serialize_field("index_in_step", &self.index_in_step())?;
+        state.serialize_field("preceding_step", &self.preceding_step())?;
+        state.serialize_field("preceding_edge_costs", self.preceding_edge_costs())?;
+        state.serialize_field("best_preceding_node", &self.best_preceding_node())?;
+        state.serialize_field("node_cost", &self.node_cost())?;
+        state.serialize_field("path_cost", &self.path_cost())?;
+        state.end()
+    }
+}
+
+/**
+ * An owned node.
+ *
+ * An owned node has its own copies of the key and the preceding edge costs.
+ * The value is shared with the vocabulary entry since it cannot be copied through `Any`.
+ */
+#[derive(Debug)]
+pub struct OwnedNode {
+    key: Option<Box<dyn Input>>,
+    value: Option<Rc<dyn Any>>,
+    step: usize,
+    index_in_step: usize,
+    preceding_step: usize,
+    preceding_edge_costs: Vec<i32>,
+    best_preceding_node: usize,
+    node_cost: i32,
+    path_cost: i32,
+    is_bos: bool,
+    is_eos: bool,
+}
+
+impl OwnedNode {
+    /**
+     * Returns the key.
+     *
+     * # Returns
+     * The key.
+     */
+    pub fn key(&self) -> Option<&dyn Input> {
+        self.key.as_deref()
+    }
+
+    /**
+     * Returns the value.
+     *
+     * # Returns
+     * The value.
+     */
+    pub fn value(&self) -> Option<&dyn Any> {
+        self.value.as_deref()
+    }
+
+    /**
+     * Returns the step to which this node belongs.
+     *
+     * # Returns
+     * The step.
+     */
+    pub const fn step(&self) -> usize {
+        self.step
+    }
+
+    /**
+     * Returns the index in the step.
+     *
+     * # Returns
+     * The index in the step.
+     */
+    pub const fn index_in_step(&self) -> usize {
+        self.index_in_step
+    }
+
+    /**
+     * Returns the preceding step.
+     *
+     * # Returns
+     * The preceding step.
+     */
+    pub const fn preceding_step(&self) -> usize {
+        self.preceding_step
+    }
+
+    /**
+     * Returns the preceding edge costs.
+     *
+     * # Returns
+     * The preceding edge costs.
+     */
+    pub const fn preceding_edge_costs(&self) -> &Vec<i32> {
+        &self.preceding_edge_costs
+    }
+
+    /**
+     * Returns the index of the best preceding node.
+     *
+     * # Returns
+     * The index of the best preceding node.
+     */
+    pub const fn best_preceding_node(&self) -> usize {
+        self.best_preceding_node
+    }
+
+    /**
+     * Returns the node cost.
+     *
+     * # Returns
+     * The node cost.
+     */
+    pub const fn node_cost(&self) -> i32 {
+        self.node_cost
+    }
+
+    /**
+     * Returns the path cost.
+     *
+     * # Returns
+     * The path cost.
+     */
+    pub const fn path_cost(&self) -> i32 {
+        self.path_cost
+    }
+
+    /**
+     * Returns `true` if this node is the BOS.
+     *
+     * # Returns
+     * `true` if this node is the BOS.
+     */
+    pub const fn is_bos(&self) -> bool {
+        self.is_bos
+    }
+
+    /**
+     * Returns `true` if this node is the EOS.
+     *
+     * # Returns
+     * `true` if this node is the EOS.
+     */
+    pub const fn is_eos(&self) -> bool {
+        self.is_eos
+    }
 }
 
 #[cfg(test)]
@@ -409,6 +908,51 @@ mod tests {
         }
     }
 
+    #[test]
+    fn new_with_middle_entry() {
+        let entry = MiddleEntry::new(
+            Box::new(StringInput::new(String::from("mizuho"))),
+            Box::new(42),
+            24,
+        );
+        let preceding_edge_costs = Rc::new(vec![3, 1, 4, 1, 5, 9, 2, 6]);
+        let node = Node::new_with_middle_entry(
+            entry.clone(),
+            53,
+            1,
+            preceding_edge_costs.clone(),
+            5,
+            2424,
+        );
+
+        assert_eq!(
+            node,
+            Node::new_with_entry(entry.into_entry(), 53, 1, preceding_edge_costs, 5, 2424).unwrap()
+        );
+        assert_eq!(node.step(), 2);
+        assert_eq!(node.node_cost(), 24);
+    }
+
+    #[test]
+    fn new_with_entry_in_arena() {
+        let entry = Rc::new(Entry::new(
+            Box::new(StringInput::new(String::from("mizuho"))),
+            Box::new(42),
+            24,
+        ));
+        let arena = Rc::new(vec![2, 7, 1, 8, 3, 1, 4, 1, 5, 9, 2, 6]);
+        let node =
+            Node::new_with_entry_in_arena(entry.clone(), 2, 53, 1, arena.clone(), 4..12, 5, 2424)
+                .unwrap();
+
+        assert_eq!(node.preceding_edge_costs(), &[3, 1, 4, 1, 5, 9, 2, 6]);
+        assert_eq!(
+            node,
+            Node::new_with_entry(entry, 53, 1, Rc::new(vec![3, 1, 4, 1, 5, 9, 2, 6]), 5, 2424)
+                .unwrap()
+        );
+    }
+
     #[test]
     fn key() {
         let key = StringInput::new(String::from("mizuho"));
@@ -596,6 +1140,134 @@ mod tests {
         }
     }
 
+    #[test]
+    fn user_data() {
+        let node = Node::new(
+            Box::new(StringInput::new(String::from("mizuho"))),
+            Box::new(42),
+            53,
+            1,
+            Rc::new(vec![3, 1, 4]),
+            5,
+            24,
+            2424,
+        );
+        let cloned = node.clone();
+
+        assert!(node.user_data::<String>().is_none());
+
+        node.set_user_data(String::from("noun"));
+        node.set_user_data(42usize);
+
+        assert_eq!(cloned.user_data::<String>().unwrap().as_str(), "noun");
+        assert_eq!(*cloned.user_data::<usize>().unwrap(), 42);
+        assert!(cloned.user_data::<i32>().is_none());
+
+        cloned.set_user_data(String::from("verb"));
+
+        assert_eq!(node.user_data::<String>().unwrap().as_str(), "verb");
+        assert_eq!(node, cloned.with_index_in_step(53));
+
+        assert_eq!(*node.remove_user_data::<usize>().unwrap(), 42);
+        assert!(cloned.user_data::<usize>().is_none());
+        assert!(node.remove_user_data::<usize>().is_none());
+
+        let bos = Node::bos(Rc::new(Vec::new()));
+        bos.set_user_data(24);
+        assert_eq!(*bos.clone().user_data::<i32>().unwrap(), 24);
+    }
+
+    #[test]
+    fn is_eos() {
+        assert!(!Node::bos(Rc::new(Vec::new())).is_eos());
+        assert!(Node::eos(1, Rc::new(vec![3, 1, 4]), 2, 42).is_eos());
+        assert!(!Node::new(
+            Box::new(StringInput::new(String::from("mizuho"))),
+            Box::new(42),
+            53,
+            1,
+            Rc::new(vec![3, 1, 4]),
+            2,
+            24,
+            2424
+        )
+        .is_eos());
+    }
+
+    #[test]
+    fn step() {
+        assert_eq!(Node::bos(Rc::new(Vec::new())).step(), 0);
+        assert_eq!(Node::eos(3, Rc::new(vec![3, 1, 4]), 2, 42).step(), 4);
+
+        let entry = Rc::new(Entry::new(
+            Box::new(StringInput::new(String::from("mizuho"))),
+            Box::new(42),
+            24,
+        ));
+        let node =
+            Node::new_with_entry(entry.clone(), 53, 1, Rc::new(vec![3, 1, 4]), 2, 2424).unwrap();
+        assert_eq!(node.step(), 2);
+        let node =
+            Node::new_with_entry_in_arena(entry, 5, 53, 1, Rc::new(vec![3, 1, 4]), 0..3, 2, 2424)
+                .unwrap();
+        assert_eq!(node.step(), 5);
+        assert_eq!(node.to_owned().step(), 5);
+    }
+
+    #[test]
+    fn with_step() {
+        let bos = Node::bos(Rc::new(Vec::new()));
+        assert_eq!(bos.with_step(4), bos);
+        assert_eq!(bos.with_step(4).step(), 0);
+
+        // The entry spans the three inputs "mi", "zu" and "ho" pushed after the step 1.
+        let entry = Rc::new(Entry::new(
+            Box::new(StringInput::new(String::from("mizuho"))),
+            Box::new(42),
+            24,
+        ));
+        let node = Node::new_with_entry(entry, 53, 1, Rc::new(vec![3, 1, 4]), 2, 2424)
+            .unwrap()
+            .with_step(4);
+        assert_eq!(node.step(), 4);
+        assert_eq!(node.index_in_step(), 53);
+        assert_eq!(node.preceding_step(), 1);
+        assert_eq!(node.path_cost(), 2424);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serialize() {
+        let node = Node::new(
+            Box::new(StringInput::new(String::from("mizuho"))),
+            Box::new(42),
+            53,
+            1,
+            Rc::new(vec![3, 1, 4]),
+            2,
+            24,
+            2424,
+        );
+        let serialized = serde_json::to_value(&node).unwrap();
+        assert_eq!(
+            serialized,
+            serde_json::json!({
+                "kind": "middle",
+                "surface": "mizuho",
+                "index_in_step": 53,
+                "preceding_step": 1,
+                "preceding_edge_costs": [3, 1, 4],
+                "best_preceding_node": 2,
+                "node_cost": 24,
+                "path_cost": 2424,
+            })
+        );
+
+        let serialized = serde_json::to_value(Node::bos(Rc::new(Vec::new()))).unwrap();
+        assert_eq!(serialized["kind"], "bos");
+        assert!(serialized["surface"].is_null());
+    }
+
     #[test]
     fn eq() {
         let key = StringInput::new(String::from("mizuho"));
@@ -637,4 +1309,56 @@ mod tests {
         assert_ne!(bos, node1);
         assert_eq!(node1, node2);
     }
+
+    #[test]
+    fn to_owned() {
+        {
+            let preceding_edge_costs = Rc::new(vec![3, 1, 4, 1, 5, 9, 2, 6]);
+            let bos = Node::bos(preceding_edge_costs.clone());
+
+            let owned = bos.to_owned();
+            assert!(owned.key().is_none());
+            assert!(owned.value().is_none());
+            assert_eq!(owned.preceding_step(), usize::MAX);
+            assert_eq!(owned.preceding_edge_costs(), preceding_edge_costs.as_ref());
+            assert!(owned.is_bos());
+            assert!(!owned.is_eos());
+        }
+        {
+            let owned = {
+                let key = StringInput::new(String::from("mizuho"));
+                let value = 42;
+                let preceding_edge_costs = Rc::new(vec![3, 1, 4, 1, 5, 9, 2, 6]);
+                let node = Node::new(
+                    Box::new(key),
+                    Box::new(value),
+                    53,
+                    1,
+                    preceding_edge_costs,
+                    5,
+                    24,
+                    2424,
+                );
+                node.to_owned()
+            };
+
+            assert_eq!(
+                owned
+                    .key()
+                    .unwrap()
+                    .downcast_ref::<StringInput>()
+                    .unwrap()
+                    .value(),
+                "mizuho"
+            );
+            assert_eq!(owned.value().unwrap().downcast_ref::<i32>().unwrap(), &42);
+            assert_eq!(owned.index_in_step(), 53);
+            assert_eq!(owned.preceding_step(), 1);
+            assert_eq!(owned.preceding_edge_costs(), &vec![3, 1, 4, 1, 5, 9, 2, 6]);
+            assert_eq!(owned.best_preceding_node(), 5);
+            assert_eq!(owned.node_cost(), 24);
+            assert_eq!(owned.path_cost(), 2424);
+            assert!(!owned.is_bos());
+        }
+    }
 }
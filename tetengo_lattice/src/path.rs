@@ -4,12 +4,121 @@
  * Copyright (C) 2023-2025 kaoru  <https://www.tetengo.org/>
  */
 
+use std::any::Any;
+use std::ops::Range;
+
+#[cfg(feature = "serde")]
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+
+use crate::connection::Connection;
+use crate::input::Input;
 use crate::node::Node;
 
+/**
+ * A cost contribution of a node in a path.
+ */
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct CostContribution {
+    connection_cost: i32,
+    node_cost: i32,
+}
+
+impl CostContribution {
+    /**
+     * Creates a cost contribution.
+     *
+     * # Arguments
+     * * `connection_cost` - A cost of the connection from the preceding node.
+     * * `node_cost`       - A node cost.
+     */
+    pub const fn new(connection_cost: i32, node_cost: i32) -> Self {
+        Self {
+            connection_cost,
+            node_cost,
+        }
+    }
+
+    /**
+     * Returns the cost of the connection from the preceding node.
+     *
+     * # Returns
+     * The connection cost.
+     */
+    pub const fn connection_cost(&self) -> i32 {
+        self.connection_cost
+    }
+
+    /**
+     * Returns the node cost.
+     *
+     * # Returns
+     * The node cost.
+     */
+    pub const fn node_cost(&self) -> i32 {
+        self.node_cost
+    }
+
+    /**
+     * Returns the total cost.
+     *
+     * # Returns
+     * The sum of the connection cost and the node cost, or the infinite cost when either is
+     * infinite.
+     */
+    pub const fn total(&self) -> i32 {
+        Connection::add_cost(self.connection_cost, self.node_cost)
+    }
+}
+
+/**
+ * A difference between paths.
+ *
+ * It is a span of the input which the paths segment differently or in which the paths have
+ * different entries.
+ */
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PathDifference {
+    span: (usize, usize),
+    one_nodes: Range<usize>,
+    another_nodes: Range<usize>,
+}
+
+impl PathDifference {
+    /**
+     * Returns the span.
+     *
+     * # Returns
+     * The pair of the indices of the steps where the difference starts and ends.
+     */
+    pub const fn span(&self) -> (usize, usize) {
+        self.span
+    }
+
+    /**
+     * Returns the range of the nodes in one path.
+     *
+     * # Returns
+     * The range of the indices of the nodes.
+     */
+    pub fn one_nodes(&self) -> Range<usize> {
+        self.one_nodes.clone()
+    }
+
+    /**
+     * Returns the range of the nodes in another path.
+     *
+     * # Returns
+     * The range of the indices of the nodes.
+     */
+    pub fn another_nodes(&self) -> Range<usize> {
+        self.another_nodes.clone()
+    }
+}
+
 /**
  * A path.
  */
-#[derive(Debug, Default)]
+#[derive(Clone, Debug, Default)]
 pub struct Path {
     nodes: Vec<Node>,
     cost: i32,
@@ -56,6 +165,208 @@ impl Path {
     pub const fn cost(&self) -> i32 {
         self.cost
     }
+
+    /**
+     * Returns an iterator over the nodes between the BOS and the EOS.
+     *
+     * The items are tuples of the key, the value, the node cost and the cost of the edge from
+     * the preceding node. The key is the subrange of the input covered by the node.
+     *
+     * # Returns
+     * An iterator.
+     */
+    pub fn iter(&self) -> impl Iterator<Item = (&dyn Input, &dyn Any, i32, i32)> + '_ {
+        self.nodes.windows(2).filter_map(|pair| {
+            let (preceding, node) = (&pair[0], &pair[1]);
+            Some((
+                node.key()?,
+                node.value()?,
+                node.node_cost(),
+                node.preceding_edge_costs()[preceding.index_in_step()],
+            ))
+        })
+    }
+
+    /**
+     * Returns the cost breakdown.
+     *
+     * The first element is for the BOS node and its connection cost is 0.
+     * The sum of the totals of the elements is equal to the cost of the path.
+     *
+     * # Returns
+     * The cost contributions of the nodes.
+     */
+    pub fn cost_breakdown(&self) -> Vec<CostContribution> {
+        let mut breakdown = Vec::with_capacity(self.nodes.len());
+        for (i, node) in self.nodes.iter().enumerate() {
+            let connection_cost = if i == 0 {
+                0
+            } else {
+                node.preceding_edge_costs()[self.nodes[i - 1].index_in_step()]
+            };
+            breakdown.push(CostContribution::new(connection_cost, node.node_cost()));
+        }
+        breakdown
+    }
+
+    /**
+     * Returns the spans of the nodes.
+     *
+     * A span is a pair of the indices of the steps where the node starts and ends.
+     * The spans of the BOS and EOS nodes are empty.
+     *
+     * # Returns
+     * The spans of the nodes.
+     */
+    pub fn spans(&self) -> Vec<(usize, usize)> {
+        let mut spans = Vec::with_capacity(self.nodes.len());
+        for (i, node) in self.nodes.iter().enumerate() {
+            let start = if node.is_bos() {
+                0
+            } else {
+                node.preceding_step()
+            };
+            let end = self
+                .nodes
+                .get(i + 1)
+                .map_or(start, |next| next.preceding_step());
+            spans.push((start, end));
+        }
+        spans
+    }
+
+    /**
+     * Returns the differences from another path.
+     *
+     * The paths are aligned by the spans of their nodes. The nodes between two steps where both
+     * paths have node boundaries are compared by their entries, and the spans where they differ
+     * are returned in the order of the steps.
+     *
+     * # Arguments
+     * * `another` - Another path.
+     *
+     * # Returns
+     * The differences. Or an empty vector when the paths have the same segmentation and
+     * entries.
+     */
+    pub fn differences(&self, another: &Path) -> Vec<PathDifference> {
+        let one_spans = self.spans();
+        let another_spans = another.spans();
+        let one_eos = self.nodes.len().saturating_sub(1);
+        let another_eos = another.nodes.len().saturating_sub(1);
+
+        let mut differences = Vec::new();
+        let (mut one_index, mut another_index) = (one_eos.min(1), another_eos.min(1));
+        let (mut one_step, mut another_step) = (0, 0);
+        let (mut one_first, mut another_first, mut start) = (one_index, another_index, 0);
+        while one_index < one_eos || another_index < another_eos {
+            if another_index >= another_eos || (one_index < one_eos && one_step <= another_step) {
+                one_step = one_spans[one_index].1;
+                one_index += 1;
+            } else {
+                another_step = another_spans[another_index].1;
+                another_index += 1;
+            }
+            if one_step != another_step && (one_index < one_eos || another_index < another_eos) {
+                continue;
+            }
+
+            let one_nodes = one_first..one_index;
+            let another_nodes = another_first..another_index;
+            if !Self::same_entries(
+                &self.nodes[one_nodes.clone()],
+                &another.nodes[another_nodes.clone()],
+            ) {
+                differences.push(PathDifference {
+                    span: (start, one_step.max(another_step)),
+                    one_nodes,
+                    another_nodes,
+                });
+            }
+            (one_first, another_first, start) = (one_index, another_index, one_step);
+        }
+        differences
+    }
+
+    fn same_entries(one: &[Node], another: &[Node]) -> bool {
+        one.len() == another.len()
+            && one
+                .iter()
+                .zip(another)
+                .all(|(one_node, another_node)| one_node.entry() == another_node.entry())
+    }
+
+    /**
+     * Returns the total cost of the cost breakdown.
+     *
+     * # Returns
+     * The total cost.
+     */
+    pub fn cost_breakdown_total(&self) -> i32 {
+        self.cost_breakdown().iter().fold(0, |total, contribution| {
+            Connection::add_cost(total, contribution.total())
+        })
+    }
+}
+
+/**
+ * Serializes a cost contribution.
+ */
+#[cfg(feature = "serde")]
+impl Serialize for CostContribution {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("CostContribution", 2)?;
+        state.serialize_field("connection_cost", &self.connection_cost)?;
+        state.serialize_field("node_cost", &self.node_cost)?;
+        state.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+struct SerializedPathNode<'a> {
+    node: &'a Node,
+    span: (usize, usize),
+    contribution: CostContribution,
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for SerializedPathNode<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("PathNode", 6)?;
+        state.serialize_field("kind", self.node.kind())?;
+        state.serialize_field("surface", &self.node.surface())?;
+        state.serialize_field("span", &self.span)?;
+        state.serialize_field("connection_cost", &self.contribution.connection_cost())?;
+        state.serialize_field("node_cost", &self.contribution.node_cost())?;
+        state.serialize_field("path_cost", &self.node.path_cost())?;
+        state.end()
+    }
+}
+
+/**
+ * Serializes a path.
+ *
+ * Each node is serialized with its surface, span and costs.
+ */
+#[cfg(feature = "serde")]
+impl Serialize for Path {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let nodes = self
+            .nodes
+            .iter()
+            .zip(self.spans())
+            .zip(self.cost_breakdown())
+            .map(|((node, span), contribution)| SerializedPathNode {
+                node,
+                span,
+                contribution,
+            })
+            .collect::<Vec<_>>();
+        let mut state = serializer.serialize_struct("Path", 2)?;
+        state.serialize_field("nodes", &nodes)?;
+        state.serialize_field("cost", &self.cost)?;
+        state.end()
+    }
 }
 
 #[cfg(test)]
@@ -63,6 +374,7 @@ mod tests {
     use std::rc::Rc;
     use std::sync::LazyLock;
 
+    use crate::entry::Entry;
     use crate::string_input::StringInput;
 
     use super::*;
@@ -147,4 +459,184 @@ mod tests {
         let path = Path::new(make_nodes(), 42);
         assert_eq!(path.cost(), 42);
     }
+
+    #[test]
+    fn spans() {
+        {
+            let path = Path::new(Vec::new(), 0);
+            assert!(path.spans().is_empty());
+        }
+        {
+            let path = Path::new(make_nodes(), 42);
+            assert_eq!(path.spans(), vec![(0, 0), (0, 1), (1, 2), (2, 3), (3, 3)]);
+        }
+    }
+
+    #[test]
+    fn differences() {
+        let entry = |key: &str| {
+            Rc::new(Entry::new(
+                Box::new(StringInput::new(String::from(key))),
+                Box::new(NODE_VALUE),
+                0,
+            ))
+        };
+        let make_path = |entries: &[(&Rc<Entry>, usize)]| {
+            let mut nodes = vec![Node::bos(Rc::new(BOS_PRECEDING_EDGE_COSTS))];
+            for &(entry, preceding_step) in entries {
+                nodes.push(
+                    Node::new_with_entry(
+                        entry.clone(),
+                        0,
+                        preceding_step,
+                        Rc::new(PRECEDING_EDGE_COSTS.clone()),
+                        0,
+                        0,
+                    )
+                    .unwrap(),
+                );
+            }
+            nodes.push(Node::eos(4, Rc::new(PRECEDING_EDGE_COSTS.clone()), 0, 0));
+            Path::new(nodes, 0)
+        };
+        let (kamome, ariake, local813, local815, local817) = (
+            entry("kamome"),
+            entry("ariake"),
+            entry("local813"),
+            entry("local815"),
+            entry("local817"),
+        );
+        let one = make_path(&[(&kamome, 0), (&local813, 2), (&local817, 3)]);
+        let another = make_path(&[(&ariake, 0), (&local817, 3)]);
+        let the_other = make_path(&[(&kamome, 0), (&local815, 2)]);
+
+        assert!(one.differences(&one).is_empty());
+        assert_eq!(
+            one.differences(&another),
+            vec![PathDifference {
+                span: (0, 3),
+                one_nodes: 1..3,
+                another_nodes: 1..2,
+            }]
+        );
+        assert_eq!(
+            one.differences(&the_other),
+            vec![PathDifference {
+                span: (2, 4),
+                one_nodes: 2..4,
+                another_nodes: 2..3,
+            }]
+        );
+        assert_eq!(another.differences(&the_other).len(), 1);
+        assert_eq!(another.differences(&the_other)[0].span(), (0, 4));
+        assert_eq!(
+            Path::new(Vec::new(), 0).differences(&one)[0].another_nodes(),
+            1..4
+        );
+        assert!(Path::new(Vec::new(), 0)
+            .differences(&Path::new(Vec::new(), 0))
+            .is_empty());
+    }
+
+    #[test]
+    fn iter() {
+        {
+            let path = Path::new(Vec::new(), 0);
+            assert_eq!(path.iter().count(), 0);
+        }
+        {
+            let path = Path::new(make_nodes(), 42);
+            let items = path
+                .iter()
+                .map(|(key, value, node_cost, edge_cost)| {
+                    (
+                        key.downcast_ref::<StringInput>().unwrap().value(),
+                        *value.downcast_ref::<i32>().unwrap(),
+                        node_cost,
+                        edge_cost,
+                    )
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(
+                items,
+                vec![
+                    ("mizuho", NODE_VALUE, 0, 1),
+                    ("sakura", NODE_VALUE, 0, 1),
+                    ("tsubame", NODE_VALUE, 0, 1),
+                ]
+            );
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serialize() {
+        let path = Path::new(make_nodes()[..3].to_vec(), 4);
+        let serialized = serde_json::to_value(&path).unwrap();
+        assert_eq!(
+            serialized,
+            serde_json::json!({
+                "nodes": [
+                    {
+                        "kind": "bos",
+                        "surface": null,
+                        "span": [0, 0],
+                        "connection_cost": 0,
+                        "node_cost": 0,
+                        "path_cost": 0,
+                    },
+                    {
+                        "kind": "middle",
+                        "surface": "mizuho",
+                        "span": [0, 1],
+                        "connection_cost": 1,
+                        "node_cost": 0,
+                        "path_cost": 0,
+                    },
+                    {
+                        "kind": "middle",
+                        "surface": "sakura",
+                        "span": [1, 1],
+                        "connection_cost": 1,
+                        "node_cost": 0,
+                        "path_cost": 0,
+                    },
+                ],
+                "cost": 4,
+            })
+        );
+    }
+
+    #[test]
+    fn cost_contribution() {
+        let contribution = CostContribution::new(3, 14);
+        assert_eq!(contribution.connection_cost(), 3);
+        assert_eq!(contribution.node_cost(), 14);
+        assert_eq!(contribution.total(), 17);
+
+        assert_eq!(CostContribution::new(i32::MAX, 14).total(), i32::MAX);
+    }
+
+    #[test]
+    fn cost_breakdown() {
+        {
+            let path = Path::new(Vec::new(), 0);
+            assert!(path.cost_breakdown().is_empty());
+            assert_eq!(path.cost_breakdown_total(), 0);
+        }
+        {
+            let path = Path::new(make_nodes(), 4);
+            assert_eq!(
+                path.cost_breakdown(),
+                vec![
+                    CostContribution::new(0, 0),
+                    CostContribution::new(1, 0),
+                    CostContribution::new(1, 0),
+                    CostContribution::new(1, 0),
+                    CostContribution::new(1, 0),
+                ]
+            );
+            assert_eq!(path.cost_breakdown_total(), path.cost());
+        }
+    }
 }
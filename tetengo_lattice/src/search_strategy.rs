@@ -0,0 +1,42 @@
+/*!
+ * A search strategy.
+ *
+ * Copyright (C) 2023-2025 kaoru  <https://www.tetengo.org/>
+ */
+
+use std::fmt::Debug;
+
+use crate::node::Node;
+
+/**
+ * A search strategy.
+ *
+ * A search strategy drives the forward pass of a lattice.
+ */
+pub trait SearchStrategy: Debug {
+    /**
+     * Selects the best preceding node.
+     *
+     * The selection among the preceding nodes with the same cost must depend only on their
+     * order, so that the best path is reproducible.
+     *
+     * # Arguments
+     * * `preceding_nodes`      - Preceding nodes.
+     * * `preceding_edge_costs` - Preceding edge costs, one for each preceding node.
+     *
+     * # Returns
+     * The index of the best preceding node.
+     */
+    fn best_preceding_node(&self, preceding_nodes: &[Node], preceding_edge_costs: &[i32]) -> usize;
+
+    /**
+     * Selects the nodes kept in a step.
+     *
+     * # Arguments
+     * * `nodes` - The nodes of a step.
+     *
+     * # Returns
+     * The indices of the nodes to keep, in ascending order. It must not be empty.
+     */
+    fn nodes_to_keep(&self, nodes: &[Node]) -> Vec<usize>;
+}
@@ -0,0 +1,253 @@
+/*!
+ * A segment collector.
+ *
+ * Copyright (C) 2023-2025 kaoru  <https://www.tetengo.org/>
+ */
+
+use std::collections::HashMap;
+
+use crate::decoder::Decoder;
+use crate::error::Result;
+use crate::input::Input;
+use crate::lattice::{Lattice, SkippedInput};
+use crate::path::Path;
+use crate::string_input::StringInput;
+
+/**
+ * A segment collector.
+ *
+ * It counts the segments of the best paths, and emits the surfaces of the segments with their
+ * frequencies as the elements of a trie. It is a tool to build a user dictionary learned from
+ * the decode history.
+ *
+ * Only the segments whose keys are `StringInput`s are counted. The segments skipping an input
+ * are not counted.
+ */
+#[derive(Debug)]
+pub struct SegmentCollector<'a> {
+    decoder: Decoder<'a>,
+    frequencies: HashMap<String, usize>,
+}
+
+impl<'a> SegmentCollector<'a> {
+    /**
+     * Creates a segment collector.
+     *
+     * # Arguments
+     * * `lattice` - A lattice.
+     */
+    pub fn new(lattice: Lattice<'a>) -> Self {
+        Self {
+            decoder: Decoder::new(lattice),
+            frequencies: HashMap::new(),
+        }
+    }
+
+    /**
+     * Collects the segments of the best paths of a corpus.
+     *
+     * # Arguments
+     * * `corpus` - Input sequences.
+     *
+     * # Errors
+     * * When the lattice fails to decode an input sequence.
+     */
+    pub fn collect(&mut self, corpus: impl IntoIterator<Item = Vec<Box<dyn Input>>>) -> Result<()> {
+        for inputs in corpus {
+            let path = self.decoder.decode(inputs)?;
+            self.add_path(&path);
+        }
+        Ok(())
+    }
+
+    /**
+     * Adds the segments of a path.
+     *
+     * # Arguments
+     * * `path` - A path, e.g. a best path in the decode history.
+     */
+    pub fn add_path(&mut self, path: &Path) {
+        for (key, value, _, _) in path.iter() {
+            if value.is::<SkippedInput>() {
+                continue;
+            }
+            let Some(key) = key.downcast_ref::<StringInput>() else {
+                continue;
+            };
+            *self
+                .frequencies
+                .entry(String::from(key.value()))
+                .or_default() += 1;
+        }
+    }
+
+    /**
+     * Returns the frequency of a surface.
+     *
+     * # Arguments
+     * * `surface` - A surface.
+     *
+     * # Returns
+     * The frequency of the surface.
+     */
+    pub fn frequency(&self, surface: &str) -> usize {
+        self.frequencies.get(surface).copied().unwrap_or(0)
+    }
+
+    /**
+     * Returns the elements of a trie.
+     *
+     * They can be passed to `TrieBuilder::elements()` of tetengo_trie with
+     * `TrieBuilder::elements_sorted(true)`.
+     *
+     * # Arguments
+     * * `min_frequency` - A minimum frequency. The surfaces less frequent than it are excluded.
+     *
+     * # Returns
+     * The pairs of the surfaces and their frequencies, in the ascending order of the surfaces.
+     */
+    pub fn elements(&self, min_frequency: usize) -> Vec<(String, usize)> {
+        let mut elements = self
+            .frequencies
+            .iter()
+            .filter(|(_, &frequency)| frequency >= min_frequency)
+            .map(|(surface, &frequency)| (surface.clone(), frequency))
+            .collect::<Vec<_>>();
+        elements.sort();
+        elements
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tetengo_trie::Trie;
+
+    use crate::decoding_mode::DecodingMode;
+    use crate::entry::Entry;
+    use crate::hash_map_vocabulary::HashMapVocabulary;
+
+    use super::*;
+
+    fn entry_hash(entry: &Entry) -> u64 {
+        entry.key().map_or(0, |key| key.hash_value())
+    }
+
+    fn entry_equal_to(one: &Entry, other: &Entry) -> bool {
+        match (one.key(), other.key()) {
+            (Some(one_key), Some(other_key)) => one_key.equal_to(other_key),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+
+    fn create_vocabulary() -> HashMapVocabulary<'static> {
+        let entry = |key: &str, value: &'static str, cost| {
+            Entry::new(
+                Box::new(StringInput::new(String::from(key))),
+                Box::new(value),
+                cost,
+            )
+        };
+        HashMapVocabulary::new(
+            vec![
+                (String::from("a"), vec![entry("a", "A", 10)]),
+                (String::from("b"), vec![entry("b", "B", 20)]),
+                (String::from("ab"), vec![entry("ab", "AB", 25)]),
+            ],
+            vec![
+                ((Entry::BosEos, entry("a", "", 0)), 1),
+                ((Entry::BosEos, entry("b", "", 0)), 1),
+                ((Entry::BosEos, entry("ab", "", 0)), 1),
+                ((entry("a", "", 0), entry("b", "", 0)), 1),
+                ((entry("b", "", 0), entry("a", "", 0)), 1),
+                ((entry("a", "", 0), Entry::BosEos), 1),
+                ((entry("b", "", 0), Entry::BosEos), 1),
+                ((entry("ab", "", 0), Entry::BosEos), 1),
+            ],
+            &entry_hash,
+            &entry_equal_to,
+        )
+    }
+
+    fn to_inputs(inputs: &[&str]) -> Vec<Box<dyn Input>> {
+        inputs
+            .iter()
+            .map(|&input| {
+                let input: Box<dyn Input> = Box::new(StringInput::new(String::from(input)));
+                input
+            })
+            .collect()
+    }
+
+    #[test]
+    fn new() {
+        let vocabulary = create_vocabulary();
+        let _collector = SegmentCollector::new(Lattice::new(&vocabulary));
+    }
+
+    #[test]
+    fn collect() {
+        let vocabulary = create_vocabulary();
+        let mut collector = SegmentCollector::new(Lattice::new(&vocabulary));
+
+        collector
+            .collect(vec![
+                to_inputs(&["a", "b"]),
+                to_inputs(&["b", "a"]),
+                to_inputs(&["a"]),
+            ])
+            .unwrap();
+
+        assert_eq!(collector.frequency("ab"), 1);
+        assert_eq!(collector.frequency("a"), 2);
+        assert_eq!(collector.frequency("b"), 1);
+        assert_eq!(collector.frequency("c"), 0);
+    }
+
+    #[test]
+    fn add_path() {
+        let vocabulary = create_vocabulary();
+        let mut lattice = Lattice::builder(&vocabulary).skip_cost(100).build();
+        for input in to_inputs(&["a", "c"]) {
+            lattice.push_back(input).unwrap();
+        }
+        let eos_node = lattice.settle().unwrap();
+        let path = DecodingMode::Viterbi.decode(&lattice, eos_node).unwrap();
+
+        let mut collector = SegmentCollector::new(Lattice::new(&vocabulary));
+        collector.add_path(&path);
+
+        assert_eq!(collector.frequency("a"), 1);
+        assert_eq!(collector.frequency("c"), 0);
+    }
+
+    #[test]
+    fn elements() {
+        let vocabulary = create_vocabulary();
+        let mut collector = SegmentCollector::new(Lattice::new(&vocabulary));
+        collector
+            .collect(vec![
+                to_inputs(&["a", "b"]),
+                to_inputs(&["b", "a"]),
+                to_inputs(&["a"]),
+            ])
+            .unwrap();
+
+        assert_eq!(
+            collector.elements(1),
+            vec![
+                (String::from("a"), 2),
+                (String::from("ab"), 1),
+                (String::from("b"), 1),
+            ]
+        );
+        assert_eq!(collector.elements(2), vec![(String::from("a"), 2)]);
+
+        let trie = Trie::<String, usize>::builder()
+            .elements(collector.elements(1))
+            .elements_sorted(true)
+            .build()
+            .unwrap();
+        assert_eq!(*trie.find(&String::from("a")).unwrap().unwrap(), 2);
+    }
+}
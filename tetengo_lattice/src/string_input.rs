@@ -8,8 +8,7 @@ use std::any::Any;
 use std::collections::hash_map::DefaultHasher;
 use std::hash::{Hash, Hasher};
 
-use anyhow::Result;
-
+use crate::error::Result;
 use crate::input::{Input, InputError};
 
 /**
@@ -50,14 +49,41 @@ impl StringInput {
     pub fn value_mut(&mut self) -> &mut String {
         &mut self.value
     }
+
+    /**
+     * Replaces a byte range with a string.
+     *
+     * It is for an input growing or being corrected as a user types.
+     *
+     * # Arguments
+     * * `offset`      - An offset in bytes.
+     * * `length`      - A length in bytes.
+     * * `replacement` - A replacement string.
+     *
+     * # Errors
+     * * When `offset` and/or `length` are out of the range of the input, or not on character
+     *   boundaries.
+     */
+    pub fn replace_range(&mut self, offset: usize, length: usize, replacement: &str) -> Result<()> {
+        let Some(end) = offset.checked_add(length) else {
+            return Err(InputError::RangeOutOfBounds.into());
+        };
+        if end > self.value.len()
+            || !self.value.is_char_boundary(offset)
+            || !self.value.is_char_boundary(end)
+        {
+            return Err(InputError::RangeOutOfBounds.into());
+        }
+
+        self.value.replace_range(offset..end, replacement);
+
+        Ok(())
+    }
 }
 
 impl Input for StringInput {
     fn equal_to(&self, other: &dyn Input) -> bool {
-        let Some(other) = other.downcast_ref::<StringInput>() else {
-            return false;
-        };
-        self == other
+        other.eq_concrete(self)
     }
 
     fn hash_value(&self) -> u64 {
@@ -71,21 +97,22 @@ impl Input for StringInput {
     }
 
     fn create_subrange(&self, offset: usize, length: usize) -> Result<Box<dyn Input>> {
-        if offset + length > self.value.len() {
+        let Some(subrange) = offset
+            .checked_add(length)
+            .and_then(|end| self.value.get(offset..end))
+        else {
             return Err(InputError::RangeOutOfBounds.into());
-        }
+        };
 
-        Ok(Box::new(StringInput::new(
-            self.value[offset..offset + length].to_string(),
-        )))
+        Ok(Box::new(StringInput::new(subrange.to_string())))
     }
 
     fn append(&mut self, another: Box<dyn Input>) -> Result<()> {
-        let Some(another) = another.downcast_ref::<StringInput>() else {
+        let Ok(another) = another.downcast::<StringInput>() else {
             return Err(InputError::MismatchConcreteType.into());
         };
 
-        self.value += another.value();
+        self.value += &another.value;
 
         Ok(())
     }
@@ -156,6 +183,35 @@ mod tests {
         assert_eq!(input.value_mut(), "fuga");
     }
 
+    #[test]
+    fn replace_range() {
+        {
+            let mut input = StringInput::new(String::from("hoge"));
+
+            input.replace_range(1, 2, "iyo").unwrap();
+
+            assert_eq!(input.value(), "hiyoe");
+        }
+        {
+            let mut input = StringInput::new(String::from("hoge"));
+
+            input.replace_range(4, 0, "fuga").unwrap();
+            input.replace_range(0, 4, "").unwrap();
+
+            assert_eq!(input.value(), "fuga");
+        }
+        {
+            let mut input = StringInput::new(String::from("ほげ"));
+
+            assert!(input.replace_range(1, 1, "ふ").is_err());
+            assert!(input.replace_range(3, 4, "ふ").is_err());
+            assert!(input.replace_range(usize::MAX, 1, "ふ").is_err());
+            input.replace_range(3, 3, "が").unwrap();
+
+            assert_eq!(input.value(), "ほが");
+        }
+    }
+
     #[test]
     fn equal_to() {
         {
@@ -244,6 +300,12 @@ mod tests {
             let subrange = input.create_subrange(5, 0);
             assert!(subrange.is_err());
         }
+        {
+            let input = StringInput::new(String::from("ほげ"));
+
+            assert!(input.create_subrange(1, 2).is_err());
+            assert!(input.create_subrange(usize::MAX, 1).is_err());
+        }
     }
 
     #[test]
@@ -0,0 +1,233 @@
+/*!
+ * A test support.
+ *
+ * It is available with the feature `test-util`. It provides the classic vocabulary of the
+ * trains between the stations from Hakata to Kumamoto, so that the decoding logic can be
+ * unit-tested without building a dictionary.
+ *
+ * Copyright (C) 2023-2025 kaoru  <https://www.tetengo.org/>
+ */
+
+use crate::entry::Entry;
+use crate::hash_map_vocabulary::HashMapVocabulary;
+use crate::input::Input;
+use crate::string_input::StringInput;
+
+/*
+               +------------------mizuho/sakura/tsubame-------------------+
+               |                path cost: 4270/3220/2990                 |
+               |                                                          |
+               +------------ariake/rapid811------------+                  |
+               |          path cost: 2850/2010         |                  |
+               |                                       |                  |
+    BOS--(Hakata)--kamome/local415--(Tosu)--local813--(Omuta)--local817--(Kumamoto)--EOS
+                 path cost: 1640/1370   |   pc: 2830           pc: 3160   |     path cost:3390
+                                        |                                 |
+                                        +------------local815-------------+
+                                                  path cost: 3550
+*/
+
+/**
+ * The best paths of the station vocabulary.
+ *
+ * They are the values of the nodes between BOS and EOS, and the costs, in the order enumerated
+ * by an N-best iterator over the station inputs.
+ */
+pub const STATION_BEST_PATHS: &[(&[&str], i32)] = &[
+    (&["tsubame"], 3390),
+    (&["sakura"], 3620),
+    (&["rapid811", "local817"], 3760),
+    (&["local415", "local815"], 4050),
+    (&["kamome", "local815"], 4320),
+    (&["ariake", "local817"], 4600),
+    (&["mizuho"], 4670),
+    (&["local415", "local813", "local817"], 4680),
+    (&["kamome", "local813", "local817"], 4950),
+];
+
+/**
+ * Returns the entries of the station vocabulary.
+ *
+ * The keys are the concatenations of the station inputs, and the values are `&'static str`
+ * train names.
+ *
+ * # Returns
+ * The entries.
+ */
+pub fn station_entries() -> Vec<(String, Vec<Entry>)> {
+    vec![
+        (
+            String::from("[HakataTosu][TosuOmuta][OmutaKumamoto]"),
+            vec![
+                station_entry("Hakata-Tosu-Omuta-Kumamoto", "mizuho", 3670),
+                station_entry("Hakata-Tosu-Omuta-Kumamoto", "sakura", 2620),
+                station_entry("Hakata-Tosu-Omuta-Kumamoto", "tsubame", 2390),
+            ],
+        ),
+        (
+            String::from("[HakataTosu][TosuOmuta]"),
+            vec![
+                station_entry("Hakata-Tosu-Omuta", "ariake", 2150),
+                station_entry("Hakata-Tosu-Omuta", "rapid811", 1310),
+            ],
+        ),
+        (
+            String::from("[HakataTosu]"),
+            vec![
+                station_entry("Hakata-Tosu", "kamome", 840),
+                station_entry("Hakata-Tosu", "local415", 570),
+            ],
+        ),
+        (
+            String::from("[TosuOmuta]"),
+            vec![station_entry("Tosu-Omuta", "local813", 860)],
+        ),
+        (
+            String::from("[TosuOmuta][OmutaKumamoto]"),
+            vec![station_entry("Tosu-Omuta-Kumamoto", "local815", 1680)],
+        ),
+        (
+            String::from("[OmutaKumamoto]"),
+            vec![station_entry("Omuta-Kumamoto", "local817", 950)],
+        ),
+    ]
+}
+
+/**
+ * Returns the connections of the station vocabulary.
+ *
+ * It is a connection matrix between the sections of the trains. The entries are compared by
+ * their keys with `entry_hash()` and `entry_equal_to()`.
+ *
+ * # Returns
+ * The connections.
+ */
+pub fn station_connections() -> Vec<((Entry, Entry), i32)> {
+    let section = |key: &str| station_entry(key, "", 0);
+    vec![
+        ((Entry::BosEos, section("Hakata-Tosu-Omuta-Kumamoto")), 600),
+        ((Entry::BosEos, section("Hakata-Tosu-Omuta")), 700),
+        ((Entry::BosEos, section("Hakata-Tosu")), 800),
+        ((Entry::BosEos, Entry::BosEos), 8000),
+        (
+            (section("Hakata-Tosu"), section("Tosu-Omuta-Kumamoto")),
+            500,
+        ),
+        ((section("Hakata-Tosu"), section("Tosu-Omuta")), 600),
+        ((section("Hakata-Tosu"), Entry::BosEos), 6000),
+        (
+            (section("Hakata-Tosu-Omuta"), section("Omuta-Kumamoto")),
+            200,
+        ),
+        ((section("Hakata-Tosu-Omuta"), Entry::BosEos), 2000),
+        ((section("Tosu-Omuta"), section("Omuta-Kumamoto")), 300),
+        ((section("Tosu-Omuta"), Entry::BosEos), 3000),
+        ((section("Hakata-Tosu-Omuta-Kumamoto"), Entry::BosEos), 400),
+        ((section("Tosu-Omuta-Kumamoto"), Entry::BosEos), 500),
+        ((section("Omuta-Kumamoto"), Entry::BosEos), 600),
+    ]
+}
+
+/**
+ * Returns the inputs for the station vocabulary.
+ *
+ * # Returns
+ * The inputs to push into a lattice in order.
+ */
+pub fn station_inputs() -> Vec<Box<dyn Input>> {
+    ["[HakataTosu]", "[TosuOmuta]", "[OmutaKumamoto]"]
+        .iter()
+        .map(|&input| {
+            let input: Box<dyn Input> = Box::new(StringInput::new(String::from(input)));
+            input
+        })
+        .collect()
+}
+
+/**
+ * Creates the station vocabulary.
+ *
+ * # Returns
+ * A vocabulary with the station entries and connections.
+ */
+pub fn station_vocabulary() -> HashMapVocabulary<'static> {
+    HashMapVocabulary::new(
+        station_entries(),
+        station_connections(),
+        &entry_hash,
+        &entry_equal_to,
+    )
+}
+
+/**
+ * Returns the hash value of an entry by its key.
+ *
+ * # Arguments
+ * * `entry` - An entry.
+ *
+ * # Returns
+ * The hash value.
+ */
+pub fn entry_hash(entry: &Entry) -> u64 {
+    entry.key().map_or(0, |key| key.hash_value())
+}
+
+/**
+ * Returns `true` when the keys of two entries are equal.
+ *
+ * # Arguments
+ * * `one`   - An entry.
+ * * `other` - Another entry.
+ *
+ * # Returns
+ * `true` when the keys are equal, or when both entries have no key.
+ */
+pub fn entry_equal_to(one: &Entry, other: &Entry) -> bool {
+    match (one.key(), other.key()) {
+        (Some(one_key), Some(other_key)) => one_key.equal_to(other_key),
+        (None, None) => true,
+        _ => false,
+    }
+}
+
+fn station_entry(key: &str, value: &'static str, cost: i32) -> Entry {
+    Entry::new(
+        Box::new(StringInput::new(String::from(key))),
+        Box::new(value),
+        cost,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::constraint::Constraint;
+    use crate::lattice::Lattice;
+    use crate::n_best_iterator::NBestIterator;
+
+    use super::*;
+
+    #[test]
+    fn station_vocabulary() {
+        let vocabulary = super::station_vocabulary();
+        let mut lattice = Lattice::new(&vocabulary);
+        for input in station_inputs() {
+            lattice.push_back(input).unwrap();
+        }
+        let eos_node = lattice.settle().unwrap();
+
+        let paths = NBestIterator::new(&lattice, eos_node, Box::new(Constraint::new()))
+            .map(|path| {
+                let values = path.nodes()[1..path.nodes().len() - 1]
+                    .iter()
+                    .map(|node| *node.value().unwrap().downcast_ref::<&str>().unwrap())
+                    .collect::<Vec<_>>();
+                (values, path.cost())
+            })
+            .collect::<Vec<_>>();
+        let expected = STATION_BEST_PATHS
+            .iter()
+            .map(|&(values, cost)| (values.to_vec(), cost))
+            .collect::<Vec<_>>();
+        assert_eq!(paths, expected);
+    }
+}
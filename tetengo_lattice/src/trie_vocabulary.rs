@@ -0,0 +1,448 @@
+/*!
+ * A trie vocabulary.
+ *
+ * Copyright (C) 2023-2025 kaoru  <https://www.tetengo.org/>
+ */
+
+use std::any::type_name_of_val;
+use std::collections::HashMap;
+use std::fmt::{self, Debug, Formatter};
+use std::rc::Rc;
+
+use tetengo_trie::{Serializer, StringSerializer, Trie};
+
+use crate::approximate_entry;
+use crate::connection::Connection;
+use crate::entry::Entry;
+use crate::error::{LatticeError, Result};
+use crate::input::{Input, InputError};
+use crate::node::Node;
+use crate::string_input::StringInput;
+use crate::vocabulary::Vocabulary;
+
+type ConnectionProvider = Box<dyn Fn(&Node, &Entry) -> Connection>;
+
+/**
+ * A trie vocabulary builder.
+ */
+pub struct TrieVocabularyBuilder {
+    entries: Vec<(String, Vec<Entry>)>,
+    connection: ConnectionProvider,
+    approximate_search: Option<(usize, i32)>,
+}
+
+impl TrieVocabularyBuilder {
+    /**
+     * Adds entries.
+     *
+     * The entries of the same surface are merged in the order they are added.
+     *
+     * # Arguments
+     * * `entries` - Pairs of surfaces and their entries.
+     */
+    pub fn entries(mut self, entries: impl IntoIterator<Item = (String, Vec<Entry>)>) -> Self {
+        self.entries.extend(entries);
+        self
+    }
+
+    /**
+     * Sets a connection provider.
+     *
+     * When it is not set, all the connections cost 0.
+     *
+     * # Arguments
+     * * `connection` - A function returning a connection from an origin node to an entry.
+     */
+    pub fn connection(
+        mut self,
+        connection: impl Fn(&Node, &Entry) -> Connection + 'static,
+    ) -> Self {
+        self.connection = Box::new(connection);
+        self
+    }
+
+    /**
+     * Makes the vocabulary find entries approximately.
+     *
+     * When it is set, `find_entries()` returns the entries of the surfaces within the maximum
+     * edit distance from the key, as `TrieVocabulary::find_approximate_entries()` does, instead
+     * of the ones of the key only, so that a lattice decodes noisy inputs such as OCR results or
+     * typos.
+     *
+     * # Arguments
+     * * `max_distance`      - A maximum edit distance in characters.
+     * * `cost_per_distance` - A cost penalty per edit distance.
+     */
+    pub fn approximate_search(mut self, max_distance: usize, cost_per_distance: i32) -> Self {
+        self.approximate_search = Some((max_distance, cost_per_distance));
+        self
+    }
+
+    /**
+     * Builds a trie vocabulary.
+     *
+     * # Returns
+     * A trie vocabulary.
+     *
+     * # Errors
+     * * When it fails to build the trie.
+     */
+    pub fn build(self) -> Result<TrieVocabulary> {
+        let mut indices = HashMap::<String, usize>::new();
+        let mut entries = Vec::<Vec<Rc<Entry>>>::new();
+        for (surface, surface_entries) in self.entries {
+            let index = *indices.entry(surface).or_insert_with(|| {
+                entries.push(Vec::new());
+                entries.len() - 1
+            });
+            entries[index].extend(surface_entries.into_iter().map(Rc::new));
+        }
+
+        let trie = Trie::<String, usize>::builder()
+            .elements(indices.into_iter().collect())
+            .key_serializer(StringSerializer::new(true))
+            .build()
+            .map_err(|e| LatticeError::Other(Box::new(e)))?;
+        Ok(TrieVocabulary {
+            trie,
+            entries,
+            connection: self.connection,
+            approximate_search: self.approximate_search,
+        })
+    }
+}
+
+impl Debug for TrieVocabularyBuilder {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TrieVocabularyBuilder")
+            .field("entries", &self.entries)
+            .field("connection", &type_name_of_val(&self.connection))
+            .field("approximate_search", &self.approximate_search)
+            .finish()
+    }
+}
+
+/**
+ * A trie vocabulary.
+ *
+ * Finds the entries of string inputs with a trie whose keys are the surfaces. The entries
+ * starting at an offset of an input are found with one common prefix search.
+ */
+pub struct TrieVocabulary {
+    trie: Trie<String, usize>,
+    entries: Vec<Vec<Rc<Entry>>>,
+    connection: ConnectionProvider,
+    approximate_search: Option<(usize, i32)>,
+}
+
+impl TrieVocabulary {
+    /**
+     * Returns a trie vocabulary builder.
+     *
+     * # Returns
+     * A trie vocabulary builder.
+     */
+    pub fn builder() -> TrieVocabularyBuilder {
+        TrieVocabularyBuilder {
+            entries: Vec::new(),
+            connection: Box::new(|_, _| Connection::default()),
+            approximate_search: None,
+        }
+    }
+
+    /**
+     * Finds the entries of the surfaces within an edit distance from a key.
+     *
+     * The entries have the key as their keys, and their costs are added the penalties
+     * proportional to the edit distances in characters between their surfaces and the key, as
+     * `make_approximate_entries()` makes. The entries of the key itself are found with no
+     * penalty.
+     *
+     * # Arguments
+     * * `key`               - A key.
+     * * `max_distance`      - A maximum edit distance in characters.
+     * * `cost_per_distance` - A cost penalty per edit distance.
+     *
+     * # Returns
+     * The entries, in ascending order of the edit distances.
+     *
+     * # Errors
+     * * When it fails to search the trie.
+     * * When it fails to copy the key.
+     */
+    pub fn find_approximate_entries(
+        &self,
+        key: &dyn Input,
+        max_distance: usize,
+        cost_per_distance: i32,
+    ) -> Result<Vec<Rc<Entry>>> {
+        let Some(string_key) = key.downcast_ref::<StringInput>() else {
+            return Ok(Vec::new());
+        };
+        let found = self
+            .trie
+            .find_approximately(&String::from(string_key.value()), max_distance)
+            .map_err(|e| LatticeError::Other(Box::new(e)))?;
+        let mut entries = Vec::new();
+        for (index, distance) in found {
+            let penalty = approximate_entry::distance_penalty(distance, cost_per_distance);
+            for entry in &self.entries[*index] {
+                entries.push(Rc::new(
+                    entry
+                        .with_key(key.create_subrange(0, key.length())?)
+                        .with_additional_cost(penalty),
+                ));
+            }
+        }
+        Ok(entries)
+    }
+
+    fn entries_of(&self, index: Option<Rc<usize>>) -> &[Rc<Entry>] {
+        index.map_or(&[], |index| self.entries[*index].as_slice())
+    }
+}
+
+impl Vocabulary for TrieVocabulary {
+    fn find_entries(&self, key: &dyn Input) -> Result<Vec<Rc<Entry>>> {
+        if let Some((max_distance, cost_per_distance)) = self.approximate_search {
+            return self.find_approximate_entries(key, max_distance, cost_per_distance);
+        }
+        let Some(key) = key.downcast_ref::<StringInput>() else {
+            return Ok(Vec::new());
+        };
+        let index = self
+            .trie
+            .find(&String::from(key.value()))
+            .map_err(|e| LatticeError::Other(Box::new(e)))?;
+        Ok(self.entries_of(index).to_vec())
+    }
+
+    fn find_entries_at(&self, input: &dyn Input, offset: usize) -> Result<Vec<(usize, Rc<Entry>)>> {
+        let Some(input) = input.downcast_ref::<StringInput>() else {
+            return Ok(Vec::new());
+        };
+        let Some(text) = input.value().get(offset..) else {
+            return Err(InputError::RangeOutOfBounds.into());
+        };
+        let prefixes = self
+            .trie
+            .find_prefixes(&String::from(text))
+            .map_err(|e| LatticeError::Other(Box::new(e)))?;
+        Ok(prefixes
+            .into_iter()
+            .flat_map(|(length, index)| {
+                self.entries[*index]
+                    .iter()
+                    .map(move |entry| (length, entry.clone()))
+            })
+            .collect())
+    }
+
+    fn find_connection(&self, from: &Node, to: &Entry) -> Result<Connection> {
+        Ok((self.connection)(from, to))
+    }
+}
+
+impl Debug for TrieVocabulary {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TrieVocabulary")
+            .field("trie", &self.trie)
+            .field("entries", &self.entries)
+            .field("connection", &type_name_of_val(&self.connection))
+            .field("approximate_search", &self.approximate_search)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::constraint::Constraint;
+    use crate::lattice::Lattice;
+    use crate::n_best_iterator::NBestIterator;
+
+    use super::*;
+
+    fn make_entry(surface: &str, value: &'static str, cost: i32) -> Entry {
+        Entry::new(
+            Box::new(StringInput::new(String::from(surface))),
+            Box::new(value),
+            cost,
+        )
+    }
+
+    fn value_of(entry: &Entry) -> &'static str {
+        entry
+            .value()
+            .and_then(|v| v.downcast_ref::<&str>())
+            .copied()
+            .unwrap_or("")
+    }
+
+    fn create_vocabulary() -> TrieVocabulary {
+        TrieVocabulary::builder()
+            .entries([
+                (
+                    String::from("kuma"),
+                    vec![make_entry("kuma", "bear", 3), make_entry("kuma", "nook", 5)],
+                ),
+                (
+                    String::from("kumamoto"),
+                    vec![make_entry("kumamoto", "Kumamoto", 2)],
+                ),
+                (String::from("moto"), vec![make_entry("moto", "origin", 4)]),
+            ])
+            .entries([(String::from("kuma"), vec![make_entry("kuma", "Kuma", 6)])])
+            .connection(|from, to| {
+                Connection::new(if from.is_bos() && value_of(to) == "nook" {
+                    -4
+                } else {
+                    0
+                })
+            })
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn builder() {
+        let _vocabulary = TrieVocabulary::builder().build().unwrap();
+    }
+
+    #[test]
+    fn find_entries() {
+        let vocabulary = create_vocabulary();
+
+        {
+            let entries = vocabulary
+                .find_entries(&StringInput::new(String::from("kuma")))
+                .unwrap();
+            let values = entries.iter().map(|e| value_of(e)).collect::<Vec<_>>();
+            assert_eq!(values, vec!["bear", "nook", "Kuma"]);
+        }
+        {
+            let entries = vocabulary
+                .find_entries(&StringInput::new(String::from("kum")))
+                .unwrap();
+            assert!(entries.is_empty());
+        }
+    }
+
+    #[test]
+    fn find_approximate_entries() {
+        let vocabulary = create_vocabulary();
+
+        {
+            let key = StringInput::new(String::from("kima"));
+            let entries = vocabulary.find_approximate_entries(&key, 1, 10).unwrap();
+            let found = entries
+                .iter()
+                .map(|e| (value_of(e), e.cost()))
+                .collect::<Vec<_>>();
+            assert_eq!(found, vec![("bear", 13), ("nook", 15), ("Kuma", 16)]);
+            assert!(entries.iter().all(|e| e.key().unwrap().equal_to(&key)));
+        }
+        {
+            let key = StringInput::new(String::from("kuma"));
+            let entries = vocabulary.find_approximate_entries(&key, 0, 10).unwrap();
+            assert_eq!(entries.len(), 3);
+            assert_eq!(entries[0].cost(), 3);
+        }
+        {
+            let key = StringInput::new(String::from("ku"));
+            let entries = vocabulary.find_approximate_entries(&key, 1, 10).unwrap();
+            assert!(entries.is_empty());
+        }
+    }
+
+    #[test]
+    fn approximate_search() {
+        let vocabulary = TrieVocabulary::builder()
+            .entries([
+                (
+                    String::from("くまもと"),
+                    vec![make_entry("くまもと", "熊本", 2)],
+                ),
+                (String::from("うと"), vec![make_entry("うと", "宇土", 3)]),
+            ])
+            .approximate_search(1, 100)
+            .build()
+            .unwrap();
+        let mut lattice = Lattice::new(&vocabulary);
+        lattice
+            .push_back(Box::new(StringInput::new(String::from("くまもど"))))
+            .unwrap();
+        let eos_node = lattice.settle().unwrap();
+
+        let path = NBestIterator::new(&lattice, eos_node, Box::new(Constraint::new()))
+            .next()
+            .unwrap();
+        let values = path
+            .nodes()
+            .iter()
+            .filter_map(|node| node.value().and_then(|v| v.downcast_ref::<&str>()))
+            .copied()
+            .collect::<Vec<_>>();
+        assert_eq!(values, vec!["熊本"]);
+        assert_eq!(path.cost(), 102);
+    }
+
+    #[test]
+    fn find_entries_at() {
+        let vocabulary = create_vocabulary();
+
+        {
+            let input = StringInput::new(String::from("kumamoto"));
+            let entries = vocabulary.find_entries_at(&input, 0).unwrap();
+            let found = entries
+                .iter()
+                .map(|(length, e)| (*length, value_of(e)))
+                .collect::<Vec<_>>();
+            assert_eq!(
+                found,
+                vec![(4, "bear"), (4, "nook"), (4, "Kuma"), (8, "Kumamoto")]
+            );
+        }
+        {
+            let input = StringInput::new(String::from("kumamoto"));
+            let entries = vocabulary.find_entries_at(&input, 4).unwrap();
+            assert_eq!(entries.len(), 1);
+        }
+        {
+            let input = StringInput::new(String::from("kumamoto"));
+            assert!(vocabulary.find_entries_at(&input, 9).is_err());
+        }
+    }
+
+    #[test]
+    fn lattice() {
+        let vocabulary = create_vocabulary();
+        let mut lattice = Lattice::new(&vocabulary);
+        for syllable in ["kuma", "moto"] {
+            lattice
+                .push_back(Box::new(StringInput::new(String::from(syllable))))
+                .unwrap();
+        }
+        let eos_node = lattice.settle().unwrap();
+
+        let paths = NBestIterator::new(&lattice, eos_node, Box::new(Constraint::new()))
+            .map(|path| {
+                let values = path
+                    .nodes()
+                    .iter()
+                    .filter_map(|node| node.value().and_then(|v| v.downcast_ref::<&str>()))
+                    .copied()
+                    .collect::<Vec<_>>();
+                (values, path.cost())
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            paths,
+            vec![
+                (vec!["Kumamoto"], 2),
+                (vec!["nook", "origin"], 5),
+                (vec!["bear", "origin"], 7),
+                (vec!["Kuma", "origin"], 10),
+            ]
+        );
+    }
+}
@@ -1,49 +1,154 @@
-/*!
- * A vocabulary.
- *
- * Copyright (C) 2023-2025 kaoru  <https://www.tetengo.org/>
- */
-
-use std::fmt::Debug;
-use std::rc::Rc;
-
-use anyhow::Result;
-
-use crate::connection::Connection;
-use crate::entry::Entry;
-use crate::input::Input;
-use crate::node::Node;
-
-/**
- * A vocabulary.
- */
-pub trait Vocabulary: Debug {
-    /**
-     * Finds entries.
-     *
-     * # Arguments
-     * * `key` - A key.
-     *
-     * # Returns
-     * Entries.
-     *
-     * # Errors
-     * * When finding entries fails.
-     */
-    fn find_entries(&self, key: &dyn Input) -> Result<Vec<Rc<Entry>>>;
-
-    /**
-     * Finds a connection between an origin node and a destination entry.
-     *
-     * # Arguments
-     * * `from` - An origin node.
-     * * `to`   - A destination entry.
-     *
-     * # Returns
-     * A connection between the origin node and the destination entry.
-     *
-     * # Errors
-     * * When finding a connection fails.
-     */
-    fn find_connection(&self, from: &Node, to: &Entry) -> Result<Connection>;
-}
+/*!
+ * A vocabulary.
+ *
+ * Copyright (C) 2023-2025 kaoru  <https://www.tetengo.org/>
+ */
+
+use std::fmt::Debug;
+use std::rc::Rc;
+
+use crate::connection::Connection;
+use crate::entry::Entry;
+use crate::error::Result;
+use crate::input::{Input, InputError};
+use crate::node::Node;
+
+/**
+ * A vocabulary.
+ */
+pub trait Vocabulary: Debug {
+    /**
+     * Finds entries.
+     *
+     * # Arguments
+     * * `key` - A key.
+     *
+     * # Returns
+     * Entries.
+     *
+     * # Errors
+     * * When finding entries fails.
+     */
+    fn find_entries(&self, key: &dyn Input) -> Result<Vec<Rc<Entry>>>;
+
+    /**
+     * Finds entries whose keys start at an offset of an input.
+     *
+     * A vocabulary backed by a trie can override it with one common prefix search, instead of
+     * one search per subrange.
+     * The default implementation calls `find_entries()` with every nonempty subrange starting at
+     * the offset. The subranges which cannot be created, e.g. splitting a character, are skipped.
+     *
+     * # Arguments
+     * * `input`  - An input.
+     * * `offset` - An offset.
+     *
+     * # Returns
+     * The pairs of the lengths of the keys in the input and the entries, in ascending order of
+     * the lengths.
+     *
+     * # Errors
+     * * When `offset` is out of the range of the input.
+     * * When finding entries fails.
+     */
+    fn find_entries_at(&self, input: &dyn Input, offset: usize) -> Result<Vec<(usize, Rc<Entry>)>> {
+        if offset > input.length() {
+            return Err(InputError::RangeOutOfBounds.into());
+        }
+        let mut entries = Vec::new();
+        for length in 1..=input.length() - offset {
+            let Ok(key) = input.create_subrange(offset, length) else {
+                continue;
+            };
+            entries.extend(
+                self.find_entries(key.as_ref())?
+                    .into_iter()
+                    .map(|entry| (length, entry)),
+            );
+        }
+        Ok(entries)
+    }
+
+    /**
+     * Prefetches the entries for keys.
+     *
+     * `Lattice::push_back_with_next()` calls it with all the keys ending at the next input,
+     * while the nodes for the current input are still to be computed, so that a vocabulary
+     * backed by a disk or a database can fetch them in one batch in the background. It is a
+     * hint, and `find_entries()` must find the entries even when they are not prefetched.
+     * The default implementation does nothing.
+     *
+     * # Arguments
+     * * `keys` - Keys.
+     *
+     * # Errors
+     * * When prefetching entries fails.
+     */
+    fn prefetch(&self, keys: &[&dyn Input]) -> Result<()> {
+        let _ = keys;
+        Ok(())
+    }
+
+    /**
+     * Returns the generation.
+     *
+     * A vocabulary whose entries or connections can change must return a different generation
+     * after each change, so that the decoding results cached with an older generation are not
+     * used.
+     * The default implementation returns 0.
+     *
+     * # Returns
+     * The generation.
+     */
+    fn generation(&self) -> u64 {
+        0
+    }
+
+    /**
+     * Finds a connection between an origin node and a destination entry.
+     *
+     * The lattice treats a connection of the infinite cost as no edge, and drops an entry which
+     * no edge reaches.
+     *
+     * # Arguments
+     * * `from` - An origin node.
+     * * `to`   - A destination entry.
+     *
+     * # Returns
+     * A connection between the origin node and the destination entry.
+     *
+     * # Errors
+     * * When finding a connection fails.
+     */
+    fn find_connection(&self, from: &Node, to: &Entry) -> Result<Connection>;
+
+    /**
+     * Finds a connection between two origin nodes and a destination entry.
+     *
+     * The lattice passes the node preceding the origin node as `preceding`, so that the
+     * connection cost can be conditioned on a trigram. It is the actual preceding node on the
+     * paths when the lattice keeps trigram states (see `LatticeBuilder::trigram_states()`), and
+     * otherwise the best preceding node of the origin node, which approximates the trigram.
+     * The default implementation ignores `preceding` and calls `find_connection()`.
+     *
+     * # Arguments
+     * * `preceding` - A node preceding the origin node. Or `None` when the origin node is the BOS.
+     * * `from`      - An origin node.
+     * * `to`        - A destination entry.
+     *
+     * # Returns
+     * A connection among the nodes and the destination entry.
+     *
+     * # Errors
+     * * When finding a connection fails.
+     */
+    fn find_trigram_connection(
+        &self,
+        preceding: Option<&Node>,
+        from: &Node,
+        to: &Entry,
+    ) -> Result<Connection> {
+        let _ = preceding;
+        self.find_connection(from, to)
+    }
+}
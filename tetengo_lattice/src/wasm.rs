@@ -0,0 +1,475 @@
+/*!
+ * WebAssembly bindings.
+ *
+ * Copyright (C) 2023-2025 kaoru  <https://www.tetengo.org/>
+ */
+
+use std::collections::HashMap;
+
+use wasm_bindgen::prelude::{wasm_bindgen, JsError};
+
+use crate::connection_matrix::ConnectionMatrix;
+use crate::constraint::Constraint;
+use crate::entry::Entry;
+use crate::hash_map_vocabulary::HashMapVocabulary;
+use crate::lattice::Lattice;
+use crate::n_best_iterator::NBestIterator;
+use crate::path::Path;
+use crate::string_input::StringInput;
+
+/**
+ * A decoded path.
+ */
+#[derive(Clone, Debug)]
+#[wasm_bindgen]
+pub struct WasmPath {
+    values: Vec<String>,
+    cost: i32,
+}
+
+#[wasm_bindgen]
+impl WasmPath {
+    /**
+     * Returns the values of the nodes except BOS and EOS.
+     *
+     * # Returns
+     * The values.
+     */
+    #[wasm_bindgen(getter)]
+    pub fn values(&self) -> Vec<String> {
+        self.values.clone()
+    }
+
+    /**
+     * Returns the cost.
+     *
+     * # Returns
+     * The cost.
+     */
+    #[wasm_bindgen(getter)]
+    pub fn cost(&self) -> i32 {
+        self.cost
+    }
+}
+
+impl From<Path> for WasmPath {
+    fn from(path: Path) -> Self {
+        Self {
+            values: path
+                .nodes()
+                .iter()
+                .filter_map(|node| node.value())
+                .filter_map(|value| value.downcast_ref::<String>())
+                .cloned()
+                .collect(),
+            cost: path.cost(),
+        }
+    }
+}
+
+type EntryValues = (String, String, i32, Option<(usize, usize)>);
+
+/**
+ * A lattice decoder.
+ *
+ * A prebuilt dictionary is loaded from bytes, such as the ones fetched by a browser, since no
+ * file is available on WebAssembly.
+ */
+#[derive(Debug, Default)]
+#[wasm_bindgen]
+pub struct WasmLatticeDecoder {
+    entries: HashMap<String, Vec<EntryValues>>,
+    connections: Vec<(Option<String>, Option<String>, i32)>,
+    connection_matrix: Option<ConnectionMatrix>,
+    vocabulary: Option<HashMapVocabulary<'static>>,
+}
+
+#[wasm_bindgen]
+impl WasmLatticeDecoder {
+    /**
+     * Creates a lattice decoder.
+     */
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /**
+     * Adds an entry.
+     *
+     * # Arguments
+     * * `input` - An input.
+     * * `key`   - A key.
+     * * `value` - A value.
+     * * `cost`  - A cost.
+     */
+    #[wasm_bindgen(js_name = addEntry)]
+    pub fn add_entry(&mut self, input: String, key: String, value: String, cost: i32) {
+        self.entries
+            .entry(input)
+            .or_default()
+            .push((key, value, cost, None));
+        self.vocabulary = None;
+    }
+
+    /**
+     * Loads entries from a lexicon in the CSV format of MeCab.
+     *
+     * Each line has a surface, a left context ID, a right context ID, a cost and optional
+     * features, separated by commas. The surface is used as both the input and the key, and the
+     * features are used as the value. The value is the surface when there are no features.
+     * Quoted fields are not supported.
+     *
+     * # Arguments
+     * * `bytes` - The bytes of a lexicon in UTF-8.
+     *
+     * # Errors
+     * * When the bytes are not valid UTF-8.
+     * * When a line is broken.
+     */
+    #[wasm_bindgen(js_name = loadEntries)]
+    pub fn load_entries(&mut self, bytes: &[u8]) -> Result<(), JsError> {
+        let entries = parse_entries(bytes).map_err(|e| JsError::new(&e))?;
+        for (input, entry_values) in entries {
+            self.entries.entry(input).or_default().push(entry_values);
+        }
+        self.vocabulary = None;
+        Ok(())
+    }
+
+    /**
+     * Adds a connection.
+     *
+     * # Arguments
+     * * `from` - An origin entry key. Or `undefined` for BOS.
+     * * `to`   - A destination entry key. Or `undefined` for EOS.
+     * * `cost` - A cost.
+     */
+    #[wasm_bindgen(js_name = addConnection)]
+    pub fn add_connection(&mut self, from: Option<String>, to: Option<String>, cost: i32) {
+        self.connections.push((from, to, cost));
+        self.vocabulary = None;
+    }
+
+    /**
+     * Loads a connection matrix written by `ConnectionMatrix::write()`.
+     *
+     * The connections between the entries loaded by `loadEntries()` are looked up in it. The
+     * context IDs of BOS and EOS are 0.
+     *
+     * # Arguments
+     * * `bytes` - The bytes of a connection matrix.
+     *
+     * # Errors
+     * * When the bytes are broken.
+     */
+    #[wasm_bindgen(js_name = loadConnectionMatrix)]
+    pub fn load_connection_matrix(&mut self, bytes: &[u8]) -> Result<(), JsError> {
+        let connection_matrix =
+            ConnectionMatrix::from_bytes(bytes).map_err(|e| JsError::new(&e.to_string()))?;
+        self.connection_matrix = Some(connection_matrix);
+        self.vocabulary = None;
+        Ok(())
+    }
+
+    /**
+     * Loads a connection matrix in the text format of MeCab.
+     *
+     * The connections between the entries loaded by `loadEntries()` are looked up in it. The
+     * context IDs of BOS and EOS are 0.
+     *
+     * # Arguments
+     * * `bytes` - The bytes of a connection matrix in the text format of MeCab.
+     *
+     * # Errors
+     * * When the bytes are broken.
+     */
+    #[wasm_bindgen(js_name = loadConnectionMatrixText)]
+    pub fn load_connection_matrix_text(&mut self, bytes: &[u8]) -> Result<(), JsError> {
+        let connection_matrix =
+            ConnectionMatrix::read_text(&mut &*bytes).map_err(|e| JsError::new(&e.to_string()))?;
+        self.connection_matrix = Some(connection_matrix);
+        self.vocabulary = None;
+        Ok(())
+    }
+
+    /**
+     * Segments inputs along the best path.
+     *
+     * # Arguments
+     * * `inputs` - Inputs. Each input makes a step.
+     *
+     * # Returns
+     * The values of the best path.
+     *
+     * # Errors
+     * * When decoding fails.
+     */
+    pub fn segment(&mut self, inputs: Vec<String>) -> Result<Vec<String>, JsError> {
+        let best_path = self.n_best(inputs, 1)?.into_iter().next();
+        Ok(best_path.map(|path| path.values).unwrap_or_default())
+    }
+
+    /**
+     * Decodes inputs into the N-best paths.
+     *
+     * # Arguments
+     * * `inputs` - Inputs. Each input makes a step.
+     * * `count`  - The maximum path count.
+     *
+     * # Returns
+     * The paths.
+     *
+     * # Errors
+     * * When decoding fails.
+     */
+    #[wasm_bindgen(js_name = nBest)]
+    pub fn n_best(&mut self, inputs: Vec<String>, count: usize) -> Result<Vec<WasmPath>, JsError> {
+        let vocabulary = self.vocabulary();
+        let mut lattice = Lattice::new(vocabulary);
+        for input in inputs {
+            lattice
+                .push_back(Box::new(StringInput::new(input)))
+                .map_err(|e| JsError::new(&e.to_string()))?;
+        }
+        let eos_node = lattice.settle().map_err(|e| JsError::new(&e.to_string()))?;
+        let paths = NBestIterator::new(&lattice, eos_node, Box::new(Constraint::new()))
+            .take(count)
+            .map(WasmPath::from)
+            .collect();
+        Ok(paths)
+    }
+
+    fn vocabulary(&mut self) -> &HashMapVocabulary<'static> {
+        self.vocabulary.get_or_insert_with(|| {
+            let entry_mappings = self
+                .entries
+                .iter()
+                .map(|(input, entries)| {
+                    let entries = entries
+                        .iter()
+                        .map(|(key, value, cost, context_ids)| {
+                            let entry = to_entry(key, value, *cost);
+                            match context_ids {
+                                Some((left_id, right_id)) => {
+                                    entry.with_context_ids(*left_id, *right_id)
+                                }
+                                None => entry,
+                            }
+                        })
+                        .collect();
+                    (input.clone(), entries)
+                })
+                .collect();
+            let connections = self
+                .connections
+                .iter()
+                .map(|(from, to, cost)| ((to_key_entry(from), to_key_entry(to)), *cost))
+                .collect();
+            let vocabulary = HashMapVocabulary::new(
+                entry_mappings,
+                connections,
+                &entry_hash_value,
+                &entry_equal,
+            );
+            match &self.connection_matrix {
+                Some(connection_matrix) => vocabulary.connection_matrix(connection_matrix.clone()),
+                None => vocabulary,
+            }
+        })
+    }
+}
+
+fn parse_entries(bytes: &[u8]) -> Result<Vec<(String, EntryValues)>, String> {
+    let text = std::str::from_utf8(bytes).map_err(|e| e.to_string())?;
+    let mut entries = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let fields = line.splitn(5, ',').collect::<Vec<_>>();
+        let (Some(surface), Some(Ok(left_id)), Some(Ok(right_id)), Some(Ok(cost))) = (
+            fields.first(),
+            fields.get(1).map(|field| field.trim().parse::<usize>()),
+            fields.get(2).map(|field| field.trim().parse::<usize>()),
+            fields.get(3).map(|field| field.trim().parse::<i32>()),
+        ) else {
+            return Err(format!("line {} is broken", i + 1));
+        };
+        let value = fields.get(4).unwrap_or(surface);
+        entries.push((
+            surface.to_string(),
+            (
+                surface.to_string(),
+                value.to_string(),
+                cost,
+                Some((left_id, right_id)),
+            ),
+        ));
+    }
+    Ok(entries)
+}
+
+fn to_entry(key: &str, value: &str, cost: i32) -> Entry {
+    Entry::new(
+        Box::new(StringInput::new(key.to_string())),
+        Box::new(value.to_string()),
+        cost,
+    )
+}
+
+fn to_key_entry(key: &Option<String>) -> Entry {
+    key.as_ref()
+        .map_or(Entry::BosEos, |key| to_entry(key, "", 0))
+}
+
+fn entry_hash_value(entry: &Entry) -> u64 {
+    entry.key().map_or(0, |key| key.hash_value())
+}
+
+fn entry_equal(one: &Entry, other: &Entry) -> bool {
+    match (one.key(), other.key()) {
+        (Some(one_key), Some(other_key)) => one_key.equal_to(other_key),
+        (None, None) => true,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_decoder() -> WasmLatticeDecoder {
+        let mut decoder = WasmLatticeDecoder::new();
+        decoder.add_entry(
+            "[HakataTosu]".to_string(),
+            "Hakata-Tosu".to_string(),
+            "kamome".to_string(),
+            840,
+        );
+        decoder.add_entry(
+            "[TosuOmuta]".to_string(),
+            "Tosu-Omuta".to_string(),
+            "local813".to_string(),
+            860,
+        );
+        decoder.add_entry(
+            "[HakataTosu][TosuOmuta]".to_string(),
+            "Hakata-Tosu-Omuta".to_string(),
+            "ariake".to_string(),
+            2150,
+        );
+        decoder.add_connection(None, Some("Hakata-Tosu".to_string()), 800);
+        decoder.add_connection(None, Some("Hakata-Tosu-Omuta".to_string()), 700);
+        decoder.add_connection(
+            Some("Hakata-Tosu".to_string()),
+            Some("Tosu-Omuta".to_string()),
+            600,
+        );
+        decoder.add_connection(Some("Tosu-Omuta".to_string()), None, 3000);
+        decoder.add_connection(Some("Hakata-Tosu-Omuta".to_string()), None, 2000);
+        decoder
+    }
+
+    fn inputs() -> Vec<String> {
+        vec!["[HakataTosu]".to_string(), "[TosuOmuta]".to_string()]
+    }
+
+    #[test]
+    fn new() {
+        let _decoder = WasmLatticeDecoder::new();
+    }
+
+    #[test]
+    fn segment() {
+        let mut decoder = create_decoder();
+
+        assert_eq!(
+            decoder.segment(inputs()).unwrap(),
+            vec!["ariake".to_string()]
+        );
+
+        decoder.add_connection(Some("Hakata-Tosu-Omuta".to_string()), None, 9000);
+        assert_eq!(
+            decoder.segment(inputs()).unwrap(),
+            vec!["kamome".to_string(), "local813".to_string()]
+        );
+    }
+
+    #[test]
+    fn load_entries() {
+        let mut decoder = WasmLatticeDecoder::new();
+        decoder
+            .load_entries(b"a,1,1,100,A\nb,1,1,100,B\n\nab,2,2,50,AB,ab\n")
+            .unwrap();
+        decoder
+            .load_connection_matrix_text(b"3 3\n0 1 10\n0 2 500\n1 1 10\n1 0 10\n2 0 10\n")
+            .unwrap();
+
+        let inputs = vec!["a".to_string(), "b".to_string()];
+        let paths = decoder.n_best(inputs, 5).unwrap();
+        assert_eq!(paths.len(), 2);
+        assert_eq!(paths[0].values(), vec!["A".to_string(), "B".to_string()]);
+        assert_eq!(paths[0].cost(), 10 + 100 + 10 + 100 + 10);
+        assert_eq!(paths[1].values(), vec!["AB,ab".to_string()]);
+        assert_eq!(paths[1].cost(), 500 + 50 + 10);
+    }
+
+    #[test]
+    fn parse_entries() {
+        assert_eq!(
+            super::parse_entries(b"a,1,2,100,A\nb,3,4,-5\n").unwrap(),
+            vec![
+                (
+                    "a".to_string(),
+                    ("a".to_string(), "A".to_string(), 100, Some((1, 2)))
+                ),
+                (
+                    "b".to_string(),
+                    ("b".to_string(), "b".to_string(), -5, Some((3, 4)))
+                ),
+            ]
+        );
+        assert_eq!(
+            super::parse_entries(b"a,1,1\n").unwrap_err(),
+            "line 1 is broken"
+        );
+        assert_eq!(
+            super::parse_entries(b"a,1,1,100\nb,x,1,100\n").unwrap_err(),
+            "line 2 is broken"
+        );
+        assert!(super::parse_entries(&[0xE3, 0x81]).is_err());
+    }
+
+    #[test]
+    fn load_connection_matrix() {
+        let mut decoder = WasmLatticeDecoder::new();
+        decoder.load_entries(b"a,1,1,100\n").unwrap();
+        let mut connection_matrix = ConnectionMatrix::new(2, 2).unwrap();
+        connection_matrix.set_cost(0, 1, 20).unwrap();
+        connection_matrix.set_cost(1, 0, 30).unwrap();
+        let mut bytes = Vec::new();
+        connection_matrix.write(&mut bytes).unwrap();
+        decoder.load_connection_matrix(&bytes).unwrap();
+
+        let paths = decoder.n_best(vec!["a".to_string()], 5).unwrap();
+        assert_eq!(paths.len(), 1);
+        assert_eq!(paths[0].values(), vec!["a".to_string()]);
+        assert_eq!(paths[0].cost(), 20 + 100 + 30);
+    }
+
+    #[test]
+    fn n_best() {
+        let mut decoder = create_decoder();
+
+        let paths = decoder.n_best(inputs(), 5).unwrap();
+        assert_eq!(paths.len(), 2);
+        assert_eq!(paths[0].values(), vec!["ariake".to_string()]);
+        assert_eq!(paths[0].cost(), 4850);
+        assert_eq!(
+            paths[1].values(),
+            vec!["kamome".to_string(), "local813".to_string()]
+        );
+        assert_eq!(paths[1].cost(), 6100);
+    }
+}
@@ -0,0 +1,244 @@
+/*!
+ * An autocomplete server.
+ *
+ * Serves the completions of prefixes over HTTP with worker threads, which share the storage of
+ * one trie. The completions are ranked by the scores of the words.
+ *
+ * Usage: autocomplete_server [words.txt [address]]
+ *
+ * Each line of words.txt is a word, optionally followed by a tab and its score.
+ *
+ * Query it with `curl 'http://127.0.0.1:8080/complete?q=ka&k=5'`.
+ *
+ * Copyright (C) 2023-2025 kaoru  <https://www.tetengo.org/>
+ */
+
+use std::cmp::Reverse;
+use std::env;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::process::exit;
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use anyhow::Result;
+
+use tetengo_trie::{
+    Deserializer, Serializer, SharedStorage, StringDeserializer, StringSerializer, Trie,
+};
+
+fn main() {
+    if let Err(e) = main_core() {
+        eprintln!("Error: {}", e);
+        exit(1);
+    }
+}
+
+fn main_core() -> Result<()> {
+    let args = env::args().collect::<Vec<_>>();
+    let words = match args.get(1) {
+        Some(path) => load_words(path)?,
+        None => DEFAULT_WORDS
+            .iter()
+            .map(|&(word, score)| (String::from(word), score))
+            .collect(),
+    };
+    let address = args.get(2).map_or(DEFAULT_ADDRESS, String::as_str);
+
+    // The workers build their tries on the clones of one shared storage.
+    let storage = SharedStorage::new_with_storage(build_trie(words)?.storage())?;
+
+    // The connections are distributed to the workers through a channel.
+    let (sender, receiver) = mpsc::channel::<TcpStream>();
+    let receiver = Arc::new(Mutex::new(receiver));
+    let worker_count = thread::available_parallelism().map_or(4, |count| count.get());
+    for _ in 0..worker_count {
+        let storage = storage.clone();
+        let receiver = Arc::clone(&receiver);
+        let _worker = thread::spawn(move || serve(storage, &receiver));
+    }
+
+    let listener = TcpListener::bind(address)?;
+    eprintln!("Listening on {} with {} workers...", address, worker_count);
+    for stream in listener.incoming() {
+        sender.send(stream?)?;
+    }
+
+    Ok(())
+}
+
+const DEFAULT_WORDS: &[(&str, u32)] = &[
+    ("gionbashi", 12),
+    ("gofukumachi", 18),
+    ("karashimachou", 25),
+    ("kawaramachi", 9),
+    ("keitokukoumae", 7),
+    ("kumamotoekimae", 42),
+    ("nihongiguchi", 5),
+    ("tasakibashi", 14),
+];
+
+const DEFAULT_ADDRESS: &str = "127.0.0.1:8080";
+
+const DEFAULT_COMPLETION_COUNT: usize = 10;
+
+fn load_words(path: &str) -> Result<Vec<(String, u32)>> {
+    let mut words = Vec::new();
+    for line in BufReader::new(File::open(path)?).lines() {
+        let line = line?;
+        if line.is_empty() {
+            continue;
+        }
+        match line.split_once('\t') {
+            Some((word, score)) => words.push((String::from(word), score.parse()?)),
+            None => words.push((line, 0)),
+        }
+    }
+    Ok(words)
+}
+
+// The values are the scores of the words.
+type WordTrie = Trie<String, u32>;
+
+fn build_trie(words: Vec<(String, u32)>) -> Result<WordTrie> {
+    Ok(WordTrie::builder()
+        .elements(words)
+        .key_serializer(StringSerializer::new(true))
+        .build()?)
+}
+
+fn serve(storage: SharedStorage<u32>, receiver: &Mutex<Receiver<TcpStream>>) {
+    let trie = match WordTrie::builder_with_storage(Box::new(storage))
+        .key_serializer(StringSerializer::new(true))
+        .build()
+    {
+        Ok(trie) => trie,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            return;
+        }
+    };
+    loop {
+        // The lock is released before handling the connection.
+        let stream = {
+            let Ok(receiver) = receiver.lock() else {
+                return;
+            };
+            let Ok(stream) = receiver.recv() else {
+                return;
+            };
+            stream
+        };
+        if let Err(e) = handle(&trie, stream) {
+            eprintln!("Error: {}", e);
+        }
+    }
+}
+
+fn handle(trie: &WordTrie, mut stream: TcpStream) -> Result<()> {
+    let mut request_line = String::new();
+    let _length = BufReader::new(&stream).read_line(&mut request_line)?;
+
+    let response = match parse_request_line(&request_line) {
+        Some((prefix, count)) => {
+            let completions = complete(trie, &prefix, count)?
+                .iter()
+                .map(|word| to_json_string(word))
+                .collect::<Vec<_>>();
+            http_response("200 OK", &format!("[{}]", completions.join(",")))
+        }
+        None => http_response("404 Not Found", "[]"),
+    };
+    stream.write_all(response.as_bytes())?;
+    Ok(())
+}
+
+// Returns the count words with the highest scores among the ones starting with the prefix.
+// The words of the same score are in the lexicographic order.
+fn complete(trie: &WordTrie, prefix: &str, count: usize) -> Result<Vec<String>> {
+    let Some(view) = trie.subtrie_view(&String::from(prefix))? else {
+        return Ok(Vec::new());
+    };
+    let mut ranked = view
+        .iter()
+        .map(|(key, score)| (Reverse(*score), key))
+        .collect::<Vec<_>>();
+    // Only the top count candidates are sorted.
+    if count < ranked.len() {
+        let _ = ranked.select_nth_unstable(count);
+        ranked.truncate(count);
+    }
+    ranked.sort_unstable();
+
+    let key_deserializer = StringDeserializer::new(true);
+    Ok(ranked
+        .iter()
+        .map(|(_, key)| key_deserializer.deserialize(key))
+        .collect::<tetengo_trie::Result<Vec<_>>>()?)
+}
+
+// Parses "GET /complete?q=<prefix>&k=<count> HTTP/1.1".
+fn parse_request_line(request_line: &str) -> Option<(String, usize)> {
+    let mut elements = request_line.split_whitespace();
+    if elements.next()? != "GET" {
+        return None;
+    }
+    let (path, query) = elements.next()?.split_once('?').unwrap_or(("", ""));
+    if path != "/complete" {
+        return None;
+    }
+
+    let mut prefix = String::new();
+    let mut count = DEFAULT_COMPLETION_COUNT;
+    for parameter in query.split('&') {
+        match parameter.split_once('=') {
+            Some(("q", value)) => prefix = percent_decode(value)?,
+            Some(("k", value)) => count = value.parse().ok()?,
+            _ => {}
+        }
+    }
+    Some((prefix, count))
+}
+
+fn percent_decode(encoded: &str) -> Option<String> {
+    let mut decoded = Vec::with_capacity(encoded.len());
+    let mut bytes = encoded.bytes();
+    while let Some(byte) = bytes.next() {
+        match byte {
+            b'%' => {
+                let hex = [bytes.next()?, bytes.next()?];
+                decoded.push(u8::from_str_radix(std::str::from_utf8(&hex).ok()?, 16).ok()?);
+            }
+            b'+' => decoded.push(b' '),
+            _ => decoded.push(byte),
+        }
+    }
+    String::from_utf8(decoded).ok()
+}
+
+fn to_json_string(string: &str) -> String {
+    let mut json = String::with_capacity(string.len() + 2);
+    json.push('"');
+    for c in string.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            c if c.is_control() => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+fn http_response(status: &str, body: &str) -> String {
+    format!(
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: \
+         close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    )
+}
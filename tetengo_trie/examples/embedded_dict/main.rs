@@ -0,0 +1,77 @@
+/*!
+ * A dictionary embedding tool.
+ *
+ * Embeds a prebuilt trie into the executable and serves lookups without reading any file.
+ *
+ * Copyright (C) 2023-2025 kaoru  <https://www.tetengo.org/>
+ */
+
+use std::env;
+use std::io::stdin;
+use std::process::exit;
+use std::rc::Rc;
+
+use anyhow::Result;
+
+use tetengo_trie::{
+    Deserializer, IntegerDeserializer, RandomAccessStorage, Serializer, StringSerializer, Trie,
+    ValueDeserializer,
+};
+
+// The trie of the tram stops serialized by `Storage::serialize()` with the values in 4 bytes.
+static STOPS: &[u8] = include_bytes!("stops.bin");
+
+fn main() {
+    if let Err(e) = main_core() {
+        eprintln!("Error: {}", e);
+        exit(1);
+    }
+}
+
+fn main_core() -> Result<()> {
+    let trie = load_trie()?;
+
+    let keys = env::args().skip(1).collect::<Vec<_>>();
+    if !keys.is_empty() {
+        keys.iter().try_for_each(|key| print_found(&trie, key))?;
+        return Ok(());
+    }
+
+    loop {
+        eprint!(">> ");
+        let mut line = String::new();
+        let read_length = stdin().read_line(&mut line)?;
+        if read_length == 0 {
+            break;
+        }
+        let key = line.trim_end();
+        if key.is_empty() {
+            continue;
+        }
+        print_found(&trie, key)?;
+    }
+
+    Ok(())
+}
+
+type StopTrie = Trie<String, i32>;
+
+fn load_trie() -> Result<StopTrie> {
+    // The storage reads the embedded bytes in place, so that the trie is not copied to the heap.
+    let integer_deserializer = IntegerDeserializer::<i32>::new(false);
+    let value_deserializer = ValueDeserializer::new(Box::new(move |serialized: &[u8]| {
+        integer_deserializer.deserialize(serialized)
+    }));
+    let storage = RandomAccessStorage::builder(Rc::new(STOPS), 0, value_deserializer).build()?;
+    Ok(StopTrie::builder_with_storage(Box::new(storage))
+        .key_serializer(StringSerializer::new(true))
+        .build()?)
+}
+
+fn print_found(trie: &StopTrie, key: &str) -> Result<()> {
+    match trie.find(&String::from(key))? {
+        Some(found) => println!("{}: {}", key, found),
+        None => println!("{}: Not found.", key),
+    }
+    Ok(())
+}
@@ -13,7 +13,10 @@ use std::process::exit;
 
 use anyhow::Result;
 
-use tetengo_trie::{BuldingObserverSet, Serializer, StringSerializer, Trie, ValueSerializer};
+use tetengo_trie::{
+    BuldingObserverSet, FixedSize, FixedSizeSerializer, MultiTrie, Serializer, StringSerializer,
+    Trie, ValueSerializer,
+};
 
 fn main() {
     if let Err(e) = main_core() {
@@ -23,15 +26,21 @@ fn main() {
 }
 
 fn main_core() -> Result<()> {
-    let args = env::args().collect::<Vec<_>>();
+    let (options, args) = env::args().partition::<Vec<_>, _>(|arg| arg.starts_with("--"));
+    let reading_index = options.iter().any(|option| option == "--reading-index");
     if args.len() <= 2 {
-        eprintln!("Usage: make_dict UniDic_lex.csv trie.bin");
+        eprintln!("Usage: make_dict [--reading-index] UniDic_lex.csv trie.bin");
         return Ok(());
     }
 
-    let word_offset_map = load_lex_csv(Path::new(&args[1]))?;
-    let trie = build_trie(word_offset_map)?;
-    serialize_trie(&trie, Path::new(&args[2]))?;
+    let (word_offset_map, reading_offset_map) = load_lex_csv(Path::new(&args[1]), reading_index)?;
+    if reading_index {
+        let multi_trie = build_multi_trie(word_offset_map, reading_offset_map)?;
+        serialize_multi_trie(&multi_trie, Path::new(&args[2]))?;
+    } else {
+        let trie = build_trie(word_offset_map)?;
+        serialize_trie(&trie, Path::new(&args[2]))?;
+    }
 
     Ok(())
 }
@@ -44,10 +53,16 @@ enum DictMakingError {
 
 type WordOffsetMap = HashMap<String, Vec<(usize, usize)>>;
 
-fn load_lex_csv(lex_csv_path: &Path) -> Result<WordOffsetMap> {
+// The readings are put into the second map when reading_index is true, or into the first one
+// with the surfaces otherwise.
+fn load_lex_csv(
+    lex_csv_path: &Path,
+    reading_index: bool,
+) -> Result<(WordOffsetMap, WordOffsetMap)> {
     let file = File::open(lex_csv_path)?;
 
     let mut word_offset_map = WordOffsetMap::new();
+    let mut reading_offset_map = WordOffsetMap::new();
 
     eprintln!("Loading UniDic lex.csv...");
     let mut line_head = 0usize;
@@ -80,7 +95,11 @@ fn load_lex_csv(lex_csv_path: &Path) -> Result<WordOffsetMap> {
                 elements[24],
                 line_head,
                 line.len() + 1,
-                &mut word_offset_map,
+                if reading_index {
+                    &mut reading_offset_map
+                } else {
+                    &mut word_offset_map
+                },
             );
         }
 
@@ -92,7 +111,7 @@ fn load_lex_csv(lex_csv_path: &Path) -> Result<WordOffsetMap> {
     }
     eprintln!("Done.        ");
 
-    Ok(word_offset_map)
+    Ok((word_offset_map, reading_offset_map))
 }
 
 fn split(string: &str, delimiter: char) -> Vec<&str> {
@@ -155,59 +174,95 @@ fn build_trie(word_offset_map: WordOffsetMap) -> Result<DictTrie> {
                 index += 1;
             },
             &mut || {},
-        ));
+        ))?;
     eprintln!("Done.        ");
-    trie
+    Ok(trie)
 }
 
-const SERIALIZED_VALUE_SIZE: usize = size_of::<u32>() * (1 + 4 * 2);
+type DictMultiTrie = MultiTrie<String, Vec<(usize, usize)>>;
+
+fn build_multi_trie(
+    word_offset_map: WordOffsetMap,
+    reading_offset_map: WordOffsetMap,
+) -> Result<DictMultiTrie> {
+    eprintln!("Building multi-trie...");
+    // The same lists of the offsets and the lengths share one value in the pool.
+    let mut values = Vec::new();
+    let mut value_indices = HashMap::new();
+    let mut to_index_elements = |offset_map: WordOffsetMap| {
+        let mut elements = offset_map
+            .into_iter()
+            .map(|(key, value)| {
+                let value_index = *value_indices.entry(value.clone()).or_insert_with(|| {
+                    values.push(value);
+                    values.len() - 1
+                });
+                (key, value_index)
+            })
+            .collect::<Vec<_>>();
+        elements.sort();
+        elements
+    };
+    let surface_elements = to_index_elements(word_offset_map);
+    let reading_elements = to_index_elements(reading_offset_map);
+    let multi_trie = DictMultiTrie::builder()
+        .values(values)
+        .index("surface", surface_elements)
+        .index("reading", reading_elements)
+        .key_serializer(StringSerializer::new(true))
+        .build()?;
+    eprintln!("Done.        ");
+    Ok(multi_trie)
+}
+
+// The number of the elements and the fixed number of pairs of an offset and a length.
+type SerializedValue = (u32, [(u32, u32); VALUE_CAPACITY]);
+
+fn create_value_serializer() -> ValueSerializer<'static, Vec<(usize, usize)>> {
+    let fixed_size_serializer = FixedSizeSerializer::<SerializedValue>::new(false);
+    ValueSerializer::new(
+        Box::new(move |vpus: &Vec<(usize, usize)>| {
+            fixed_size_serializer.serialize(&to_serialized_value(vpus))
+        }),
+        <SerializedValue as FixedSize>::SIZE,
+    )
+}
 
 fn serialize_trie(trie: &DictTrie, trie_bin_path: &Path) -> Result<()> {
     eprintln!("Serializing trie...");
     let file = File::create(trie_bin_path)?;
     let mut buf_writer = BufWriter::new(file);
-    let mut serializer = ValueSerializer::new(Box::new(serialize_value), SERIALIZED_VALUE_SIZE);
-    trie.storage().serialize(&mut buf_writer, &mut serializer)?;
-    eprintln!("Done.        ");
+    let mut serializer = create_value_serializer();
+    trie.storage()
+        .serialize_with_progress(&mut buf_writer, &mut serializer, &mut |progress| {
+            eprint!(
+                "{:5.1}% {:?}: {} bytes    \r",
+                progress.percent(),
+                progress.section(),
+                progress.bytes_written()
+            );
+        })?;
+    eprintln!("Done.                                        ");
     Ok(())
 }
 
-#[allow(clippy::ptr_arg)]
-fn serialize_value(vpus: &Vec<(usize, usize)>) -> Vec<u8> {
-    let mut serialized = Vec::with_capacity(SERIALIZED_VALUE_SIZE);
-
-    let serialized_size = serialize_usize(vpus.len());
-    serialized.extend(serialized_size);
-
-    (0..VALUE_CAPACITY).for_each(|i| {
-        if i < vpus.len() {
-            let serialized_element = serialize_pair_of_usize(&vpus[i]);
-            serialized.extend(serialized_element);
-        } else {
-            let serialized_element = serialize_pair_of_usize(&(0, 0));
-            serialized.extend(serialized_element);
-        }
-    });
-
-    serialized
-}
-
-fn serialize_pair_of_usize(pus: &(usize, usize)) -> Vec<u8> {
-    let mut serialized = Vec::with_capacity(size_of::<usize>() * 2);
-
-    let (offset, length) = pus;
-    serialized.extend(serialize_usize(*offset));
-    serialized.extend(serialize_usize(*length));
-
-    serialized
+fn serialize_multi_trie(multi_trie: &DictMultiTrie, multi_trie_bin_path: &Path) -> Result<()> {
+    eprintln!("Serializing multi-trie...");
+    let file = File::create(multi_trie_bin_path)?;
+    let mut buf_writer = BufWriter::new(file);
+    multi_trie.serialize(&mut buf_writer, &mut create_value_serializer())?;
+    eprintln!("Done.        ");
+    Ok(())
 }
 
-fn serialize_usize(us: usize) -> Vec<u8> {
-    debug_assert!(us <= u32::MAX as usize);
+fn to_serialized_value(vpus: &[(usize, usize)]) -> SerializedValue {
+    debug_assert!(vpus
+        .iter()
+        .all(|&(offset, length)| offset <= u32::MAX as usize && length <= u32::MAX as usize));
 
-    let mut serialized = Vec::from([0u8; size_of::<u32>()]);
-    (0..size_of::<u32>()).for_each(|i| {
-        serialized[i] = ((us >> ((size_of::<u32>() - i - 1) * 8)) & 0xFF) as u8;
-    });
-    serialized
+    let mut pairs = [(0u32, 0u32); VALUE_CAPACITY];
+    vpus.iter()
+        .zip(pairs.iter_mut())
+        .for_each(|(&(offset, length), pair)| *pair = (offset as u32, length as u32));
+    (vpus.len() as u32, pairs)
 }
@@ -13,7 +13,9 @@ use std::process::exit;
 
 use anyhow::Result;
 
-use tetengo_trie::{MemoryStorage, Trie, ValueDeserializer};
+use tetengo_trie::{
+    Deserializer, FixedSizeDeserializer, MemoryStorage, Trie, TrieError, ValueDeserializer,
+};
 
 fn main() {
     if let Err(e) = main_core() {
@@ -89,20 +91,24 @@ fn load_trie(trie_path: &Path) -> Result<DictTrie> {
         &mut file,
         &mut value_deserializer,
     )?);
-    let trie = DictTrie::builder_with_storage(storage).build();
+    let trie = DictTrie::builder_with_storage(storage).build()?;
     Ok(trie)
 }
 
 const VALUE_CAPACITY: usize = 4usize;
 
-fn deserialize_value(bytes: &[u8]) -> Result<Vec<(usize, usize)>> {
-    let mut byte_offset = 0usize;
+// The number of the elements and the fixed number of pairs of an offset and a length.
+type SerializedValue = (u32, [(u32, u32); VALUE_CAPACITY]);
+
+fn deserialize_value(bytes: &[u8]) -> Result<Vec<(usize, usize)>, TrieError> {
+    let (size, pairs) = FixedSizeDeserializer::<SerializedValue>::new(false).deserialize(bytes)?;
+    let size = size as usize;
 
-    let size = deserialize_usize(bytes, &mut byte_offset)?;
     let mut vps = Vec::with_capacity(size);
-    for _ in 0..min(size, VALUE_CAPACITY) {
-        vps.push(deserialize_pair_of_usize(bytes, &mut byte_offset)?);
-    }
+    pairs
+        .iter()
+        .take(min(size, VALUE_CAPACITY))
+        .for_each(|&(offset, length)| vps.push((offset as usize, length as usize)));
     (VALUE_CAPACITY..size).for_each(|_| {
         vps.push((0, 0));
     });
@@ -110,22 +116,6 @@ fn deserialize_value(bytes: &[u8]) -> Result<Vec<(usize, usize)>> {
     Ok(vps)
 }
 
-fn deserialize_pair_of_usize(bytes: &[u8], byte_offset: &mut usize) -> Result<(usize, usize)> {
-    let first = deserialize_usize(bytes, byte_offset)?;
-    let second = deserialize_usize(bytes, byte_offset)?;
-    Ok((first, second))
-}
-
-fn deserialize_usize(bytes: &[u8], byte_offset: &mut usize) -> Result<usize> {
-    let mut value = 0usize;
-    (0..size_of::<u32>()).for_each(|i| {
-        value <<= 8;
-        value |= bytes[*byte_offset + i] as usize;
-    });
-    *byte_offset += size_of::<u32>();
-    Ok(value)
-}
-
 fn substring_view(sv: &str, offset: usize, length: usize) -> &str {
     if offset == 0 && length == 0 {
         return "(truncated)\n";
@@ -0,0 +1,110 @@
+#![doc = "The usage of tetengo_trie."]
+
+/*
+ * Builds a trie, searches it, serializes it into a file and loads it with an mmap storage.
+ *
+ * Copyright (C) 2023-2025 kaoru  <https://www.tetengo.org/>
+ */
+
+use std::fs::File;
+use std::io::{Seek, SeekFrom};
+use std::process::exit;
+use std::rc::Rc;
+
+use tetengo_trie::{
+    Deserializer, FileMapping, IntegerDeserializer, IntegerSerializer, MmapStorage, Result,
+    Serializer, StringSerializer, Trie, ValueDeserializer, ValueSerializer,
+};
+
+fn main() {
+    if let Err(e) = main_core() {
+        eprintln!("Error: {}", e);
+        exit(1);
+    }
+}
+
+fn main_core() -> Result<()> {
+    // Builds a trie with initial elements.
+    let trie = build_trie()?;
+
+    // Creates a subtrie consisting of the elements with the common key prefix,
+    // and enumerates the values in it.
+    let Some(subtrie) = trie.subtrie(&String::from("ka"))? else {
+        unreachable!();
+    };
+    let subtrie_values = subtrie.iter().map(|v| *v).collect::<Vec<_>>();
+    assert_eq!(
+        subtrie_values,
+        [
+            22, // karashimachou
+            14, // kawaramachi
+        ]
+        .to_vec()
+    );
+    println!("ka*: {:?}", subtrie_values);
+
+    // Serializes the trie into a file.
+    let mut file = tempfile::tempfile()?;
+    serialize_trie(&trie, &mut file)?;
+
+    // Loads the trie from the file with an mmap storage.
+    // The values are deserialized lazily when they are accessed.
+    let loaded = load_trie(file)?;
+
+    // Searches the loaded trie.
+    // If a perfect-matching key is found, its value is returned.
+    let found_for_gionbashi = loaded.find(&String::from("gionbashi"))?;
+    assert_eq!(found_for_gionbashi.as_deref(), Some(&5));
+    println!("gionbashi: {:?}", found_for_gionbashi);
+
+    // If not found, None is returned.
+    let found_for_hanabatachou = loaded.find(&String::from("hanabatachou"))?;
+    assert!(found_for_hanabatachou.is_none());
+    println!("hanabatachou: {:?}", found_for_hanabatachou);
+
+    Ok(())
+}
+
+type StopTrie = Trie<String, i32>;
+
+fn build_trie() -> Result<StopTrie> {
+    StopTrie::builder()
+        .elements(
+            [
+                (String::from("tasakibashi"), -5),
+                (String::from("nihongiguchi"), -3),
+                (String::from("kumamotoekimae"), 0),
+                (String::from("gionbashi"), 5),
+                (String::from("gofukumachi"), 10),
+                (String::from("kawaramachi"), 14),
+                (String::from("keitokukoumae"), 18),
+                (String::from("karashimachou"), 22),
+            ]
+            .to_vec(),
+        )
+        .key_serializer(StringSerializer::new(true))
+        .build()
+}
+
+fn serialize_trie(trie: &StopTrie, file: &mut File) -> Result<()> {
+    // An mmap storage requires the values to be serialized in a fixed size.
+    let integer_serializer = IntegerSerializer::<i32>::new(false);
+    let mut value_serializer = ValueSerializer::new(
+        Box::new(move |value: &i32| integer_serializer.serialize(value)),
+        size_of::<i32>(),
+    );
+    trie.storage().serialize(file, &mut value_serializer)?;
+    let _ = file.seek(SeekFrom::Start(0))?;
+    Ok(())
+}
+
+fn load_trie(file: File) -> Result<StopTrie> {
+    let file_size = file.metadata()?.len() as usize;
+    let file_mapping = Rc::new(FileMapping::new(file)?);
+    let integer_deserializer = IntegerDeserializer::<i32>::new(false);
+    let value_deserializer = ValueDeserializer::new(Box::new(move |serialized: &[u8]| {
+        integer_deserializer.deserialize(serialized)
+    }));
+    let storage = MmapStorage::builder(file_mapping, 0, file_size, value_deserializer).build()?;
+    StopTrie::builder_with_storage(Box::new(storage)).build()
+}
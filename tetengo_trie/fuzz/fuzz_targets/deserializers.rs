@@ -0,0 +1,36 @@
+/*!
+ * A fuzz target of the deserializers.
+ *
+ * Feeds arbitrary bytes to the integer, the string and the fixed size deserializers, and to the
+ * parsers of the serialized sections.
+ *
+ * Copyright (C) 2023-2025 kaoru  <https://www.tetengo.org/>
+ */
+
+#![no_main]
+
+use std::io::Cursor;
+
+use libfuzzer_sys::fuzz_target;
+
+use tetengo_trie::{
+    BloomFilter, Deserializer, FixedSizeDeserializer, IntegerDeserializer, IntegerListDeserializer,
+    KeyMetadata, SectionTable, StringDeserializer,
+};
+
+fuzz_target!(|data: &[u8]| {
+    for fe_escape in [false, true] {
+        let _u8 = IntegerDeserializer::<u8>::new(fe_escape).deserialize(data);
+        let _u16 = IntegerDeserializer::<u16>::new(fe_escape).deserialize(data);
+        let _u32 = IntegerDeserializer::<u32>::new(fe_escape).deserialize(data);
+        let _i64 = IntegerDeserializer::<i64>::new(fe_escape).deserialize(data);
+        let _i128 = IntegerDeserializer::<i128>::new(fe_escape).deserialize(data);
+        let _list = IntegerListDeserializer::<u32>::new(fe_escape).deserialize(data);
+    }
+    let _string = StringDeserializer::new(false).deserialize(data);
+    let _fixed_size = FixedSizeDeserializer::<(u32, [(u32, u32); 4])>::new(false).deserialize(data);
+
+    let _bloom_filter = BloomFilter::new_with_bytes(data);
+    let _key_metadata = KeyMetadata::new_with_bytes(data);
+    let _section_table = SectionTable::new_with_reader(&mut Cursor::new(data));
+});
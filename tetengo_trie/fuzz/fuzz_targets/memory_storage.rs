@@ -0,0 +1,51 @@
+/*!
+ * A fuzz target of the memory storage deserialization.
+ *
+ * Feeds arbitrary bytes to `MemoryStorage::new_with_reader()` and
+ * `MemoryStorage::new_with_sections()`, and queries the tries on the loaded storages.
+ *
+ * Copyright (C) 2023-2025 kaoru  <https://www.tetengo.org/>
+ */
+
+#![no_main]
+
+use std::io::Cursor;
+
+use libfuzzer_sys::fuzz_target;
+
+use tetengo_trie::{Deserializer, MemoryStorage, StringDeserializer, Trie, ValueDeserializer};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(storage) = MemoryStorage::new_with_reader(&mut &data[..], &mut value_deserializer()) {
+        if let Ok(trie) = Trie::<&str, String>::builder_with_storage(Box::new(storage)).build() {
+            query(&trie);
+        }
+    }
+    if let Ok(storage) =
+        MemoryStorage::new_with_sections(&mut Cursor::new(data), Some(&mut value_deserializer()))
+    {
+        if let Ok(trie) = Trie::<&str, String>::builder_with_storage(Box::new(storage)).build() {
+            query(&trie);
+        }
+    }
+});
+
+fn value_deserializer() -> ValueDeserializer<String> {
+    ValueDeserializer::new(Box::new(|serialized: &[u8]| {
+        StringDeserializer::new(false).deserialize(serialized)
+    }))
+}
+
+// A memory storage never fails to read, so that the iterators are exercised as well.
+fn query(trie: &Trie<&str, String>) {
+    let _found = trie.find(&"a");
+    let _contained = trie.contains(&"ab");
+    let _scanned = trie.scan(&"abcab");
+    let _count = trie.iter().take(MAX_ITERATION_COUNT).count();
+    let _back_count = trie.iter().rev().take(MAX_ITERATION_COUNT).count();
+    if let Ok(iterator) = trie.take_while_prefix(&"a") {
+        let _prefix_count = iterator.take(MAX_ITERATION_COUNT).count();
+    }
+}
+
+const MAX_ITERATION_COUNT: usize = 1000;
@@ -0,0 +1,43 @@
+/*!
+ * A fuzz target of the random access storage parsing.
+ *
+ * Feeds arbitrary bytes to a random access storage, which parses the same layout as an mmap
+ * storage in place, and queries the trie on it.
+ *
+ * Copyright (C) 2023-2025 kaoru  <https://www.tetengo.org/>
+ */
+
+#![no_main]
+
+use std::rc::Rc;
+
+use libfuzzer_sys::fuzz_target;
+
+use tetengo_trie::{
+    Deserializer, IntegerDeserializer, RandomAccessStorage, Trie, ValueDeserializer,
+};
+
+fuzz_target!(|data: &[u8]| {
+    let value_deserializer = ValueDeserializer::new(Box::new(|serialized: &[u8]| {
+        IntegerDeserializer::<u32>::new(false).deserialize(serialized)
+    }));
+    let Ok(storage) =
+        RandomAccessStorage::builder(Rc::new(data.to_vec()), 0, value_deserializer).build()
+    else {
+        return;
+    };
+    let Ok(trie) = Trie::<&str, u32>::builder_with_storage(Box::new(storage)).build() else {
+        return;
+    };
+
+    // The iterators assert that the storage does not fail in debug builds, so that only the
+    // queries returning errors are exercised.
+    let _found = trie.find(&"a");
+    let _contained = trie.contains(&"ab");
+    let _scanned = trie.scan(&"abcab");
+    for value_index in 0..MAX_VALUE_INDEX {
+        let _value = trie.value_by_index(value_index);
+    }
+});
+
+const MAX_VALUE_INDEX: usize = 16;
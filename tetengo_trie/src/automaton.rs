@@ -0,0 +1,174 @@
+/*!
+ * An automaton.
+ *
+ * Copyright (C) 2023-2025 kaoru  <https://www.tetengo.org/>
+ */
+
+use std::fmt::Debug;
+use std::rc::Rc;
+
+use crate::double_array::KEY_TERMINATOR;
+use crate::error::Result;
+use crate::serializer::Serializer;
+use crate::trie::Trie;
+
+/**
+ * An automaton.
+ *
+ * It is a deterministic automaton over bytes. A trie is an automaton accepting its serialized
+ * keys, so that it can be composed byte by byte with other automata, e.g. intersected with a
+ * regular expression, instead of only by whole-key operations. The states of a trie are the
+ * nodes of `Trie::root_node()` and `Trie::step()`, and the key transform is not applied to the
+ * bytes.
+ */
+pub trait Automaton {
+    /// The state type.
+    type State: Copy + Debug;
+
+    /// The value type of the final states.
+    type Value;
+
+    /**
+     * Returns the start state.
+     *
+     * # Returns
+     * The start state.
+     */
+    fn start(&self) -> Self::State;
+
+    /**
+     * Steps one byte from a state.
+     *
+     * # Arguments
+     * * `state` - A state.
+     * * `byte`  - A byte.
+     *
+     * # Returns
+     * The next state. Or None when the automaton rejects the byte.
+     *
+     * # Errors
+     * * When it fails to step.
+     */
+    fn step(&self, state: Self::State, byte: u8) -> Result<Option<Self::State>>;
+
+    /**
+     * Returns `true` when a state is final.
+     *
+     * # Arguments
+     * * `state` - A state.
+     *
+     * # Returns
+     * `true` when the automaton accepts the bytes stepped so far.
+     *
+     * # Errors
+     * * When it fails to examine the state.
+     */
+    fn is_final(&self, state: Self::State) -> Result<bool>;
+
+    /**
+     * Returns the value of a state.
+     *
+     * # Arguments
+     * * `state` - A state.
+     *
+     * # Returns
+     * The value. Or None when the state is not final.
+     *
+     * # Errors
+     * * When it fails to get the value.
+     */
+    fn value(&self, state: Self::State) -> Result<Option<Self::Value>>;
+}
+
+impl<Key, Value: Clone + Debug + 'static, KeySerializer: Serializer + Clone> Automaton
+    for Trie<Key, Value, KeySerializer>
+{
+    type State = usize;
+
+    type Value = Rc<Value>;
+
+    fn start(&self) -> Self::State {
+        self.root_node()
+    }
+
+    fn step(&self, state: Self::State, byte: u8) -> Result<Option<Self::State>> {
+        // The key terminator leads to no state but to the slot of a value.
+        if byte == KEY_TERMINATOR {
+            return Ok(None);
+        }
+        Trie::step(self, state, byte)
+    }
+
+    fn is_final(&self, state: Self::State) -> Result<bool> {
+        self.is_terminal(state)
+    }
+
+    fn value(&self, state: Self::State) -> Result<Option<Self::Value>> {
+        self.value_at_node(state)
+    }
+}
+
+#[cfg(all(test, feature = "builder"))]
+mod tests {
+    use crate::string_serializer::StrSerializer;
+
+    use super::*;
+
+    // Accepts the byte sequences of lowercase letters ending with 'o'.
+    #[derive(Debug)]
+    struct EndingWithO;
+
+    impl Automaton for EndingWithO {
+        type State = bool;
+
+        type Value = ();
+
+        fn start(&self) -> Self::State {
+            false
+        }
+
+        fn step(&self, _: Self::State, byte: u8) -> Result<Option<Self::State>> {
+            Ok(byte.is_ascii_lowercase().then_some(byte == b'o'))
+        }
+
+        fn is_final(&self, state: Self::State) -> Result<bool> {
+            Ok(state)
+        }
+
+        fn value(&self, state: Self::State) -> Result<Option<Self::Value>> {
+            Ok(state.then_some(()))
+        }
+    }
+
+    #[test]
+    fn automaton() {
+        let trie = Trie::<&str, i32>::builder()
+            .elements([("kumamoto", 42), ("tamana", 24), ("uto", 55), ("Aso", 43)].to_vec())
+            .key_serializer(StrSerializer::new(false))
+            .build()
+            .unwrap();
+
+        let state = trie.start();
+        assert!(!trie.is_final(state).unwrap());
+        let state = Automaton::step(&trie, state, b'u').unwrap().unwrap();
+        let state = Automaton::step(&trie, state, b't').unwrap().unwrap();
+        assert!(!trie.is_final(state).unwrap());
+        assert!(trie.value(state).unwrap().is_none());
+        let state = Automaton::step(&trie, state, b'o').unwrap().unwrap();
+        assert!(trie.is_final(state).unwrap());
+        assert_eq!(*trie.value(state).unwrap().unwrap(), 55);
+        assert!(Automaton::step(&trie, state, b'o').unwrap().is_none());
+        assert!(Automaton::step(&trie, state, 0).unwrap().is_none());
+
+        let results = trie
+            .search_automaton(&EndingWithO)
+            .unwrap()
+            .into_iter()
+            .map(|(bytes, value)| (String::from_utf8(bytes).unwrap(), *value))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            results,
+            vec![(String::from("kumamoto"), 42), (String::from("uto"), 55)]
+        );
+    }
+}
@@ -0,0 +1,215 @@
+/*!
+ * UniFFI bindings.
+ *
+ * Copyright (C) 2023-2025 kaoru  <https://www.tetengo.org/>
+ */
+
+use std::fmt::Debug;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use crate::error::TrieError;
+use crate::trie::Trie;
+
+/**
+ * A binding error.
+ */
+#[derive(Debug, thiserror::Error, uniffi::Error)]
+#[uniffi(flat_error)]
+pub enum BindingError {
+    /**
+     * The trie fails.
+     */
+    #[error("trie fails: {0}")]
+    TrieFailure(TrieError),
+}
+
+/**
+ * A binding element.
+ */
+#[derive(Clone, Debug, uniffi::Record)]
+pub struct BindingElement {
+    /**
+     * A key.
+     */
+    pub key: String,
+
+    /**
+     * A value.
+     */
+    pub value: String,
+}
+
+struct SendTrie(Trie<String, String>);
+
+// SAFETY: The reference counts inside the trie are touched only while the mutex is locked, and no
+// `Rc` derived from it escapes a lookup.
+unsafe impl Send for SendTrie {}
+
+/**
+ * A trie dictionary.
+ */
+#[derive(uniffi::Object)]
+pub struct TrieDictionary {
+    trie: Mutex<SendTrie>,
+}
+
+impl Debug for TrieDictionary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TrieDictionary").finish_non_exhaustive()
+    }
+}
+
+#[uniffi::export]
+impl TrieDictionary {
+    /**
+     * Creates a trie dictionary.
+     *
+     * # Arguments
+     * * `elements` - Elements.
+     *
+     * # Errors
+     * * When it fails to build the trie.
+     */
+    #[uniffi::constructor]
+    pub fn new(elements: Vec<BindingElement>) -> Result<Arc<Self>, BindingError> {
+        let elements = elements
+            .into_iter()
+            .map(|element| (element.key, element.value))
+            .collect::<Vec<_>>();
+        let trie = Trie::<String, String>::builder()
+            .elements(elements)
+            .build()
+            .map_err(BindingError::TrieFailure)?;
+        Ok(Arc::new(Self {
+            trie: Mutex::new(SendTrie(trie)),
+        }))
+    }
+
+    /**
+     * Returns `true` when the trie contains the key.
+     *
+     * # Arguments
+     * * `key` - A key.
+     *
+     * # Errors
+     * * When it fails to access the storage.
+     */
+    pub fn contains(&self, key: String) -> Result<bool, BindingError> {
+        self.lock()
+            .0
+            .contains(&key)
+            .map_err(BindingError::TrieFailure)
+    }
+
+    /**
+     * Finds the value for the key.
+     *
+     * # Arguments
+     * * `key` - A key.
+     *
+     * # Returns
+     * The value. Or `None` when the key is not found.
+     *
+     * # Errors
+     * * When it fails to access the storage.
+     */
+    pub fn find(&self, key: String) -> Result<Option<String>, BindingError> {
+        let found = self
+            .lock()
+            .0
+            .find(&key)
+            .map_err(BindingError::TrieFailure)?;
+        Ok(found.map(|value| value.as_ref().clone()))
+    }
+
+    /**
+     * Enumerates the values whose keys start with the prefix.
+     *
+     * # Arguments
+     * * `key_prefix` - A key prefix.
+     *
+     * # Returns
+     * The values.
+     *
+     * # Errors
+     * * When it fails to access the storage.
+     */
+    pub fn predict(&self, key_prefix: String) -> Result<Vec<String>, BindingError> {
+        let trie = self.lock();
+        let Some(subtrie) = trie
+            .0
+            .subtrie(&key_prefix)
+            .map_err(BindingError::TrieFailure)?
+        else {
+            return Ok(Vec::new());
+        };
+        let values = subtrie.iter().map(|value| value.as_ref().clone()).collect();
+        Ok(values)
+    }
+}
+
+impl TrieDictionary {
+    fn lock(&self) -> MutexGuard<'_, SendTrie> {
+        self.trie
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_dictionary() -> Arc<TrieDictionary> {
+        let elements = [
+            ("kumamoto", "熊本"),
+            ("kumamotoekimae", "熊本駅前"),
+            ("tamana", "玉名"),
+        ]
+        .iter()
+        .map(|&(key, value)| BindingElement {
+            key: key.to_string(),
+            value: value.to_string(),
+        })
+        .collect::<Vec<_>>();
+        TrieDictionary::new(elements).unwrap()
+    }
+
+    #[test]
+    fn new() {
+        let _dictionary = create_dictionary();
+    }
+
+    #[test]
+    fn contains() {
+        let dictionary = create_dictionary();
+
+        assert!(dictionary.contains("tamana".to_string()).unwrap());
+        assert!(!dictionary.contains("kumamo".to_string()).unwrap());
+    }
+
+    #[test]
+    fn find() {
+        let dictionary = create_dictionary();
+
+        assert_eq!(
+            dictionary.find("kumamoto".to_string()).unwrap(),
+            Some("熊本".to_string())
+        );
+        assert!(dictionary.find("kumamo".to_string()).unwrap().is_none());
+    }
+
+    #[test]
+    fn predict() {
+        let dictionary = create_dictionary();
+
+        assert_eq!(
+            dictionary.predict("kuma".to_string()).unwrap(),
+            vec!["熊本".to_string(), "熊本駅前".to_string()]
+        );
+        assert!(dictionary
+            .predict("kagoshima".to_string())
+            .unwrap()
+            .is_empty());
+    }
+}
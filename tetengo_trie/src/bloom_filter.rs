@@ -0,0 +1,356 @@
+/*!
+ * A Bloom filter.
+ *
+ * Copyright (C) 2023-2025 kaoru  <https://www.tetengo.org/>
+ */
+
+use std::io::{Read, Seek};
+
+use crate::error::Result;
+use crate::section_table::SectionTable;
+use crate::storage::SerializationSection;
+
+/**
+ * A Bloom filter error.
+ */
+#[derive(Clone, Copy, Debug, thiserror::Error)]
+pub enum BloomFilterError {
+    /**
+     * The number of the bits per key is invalid.
+     */
+    #[error("the number of the bits per key is invalid")]
+    InvalidBitsPerKey,
+
+    /**
+     * The serialized content is invalid.
+     */
+    #[error("the serialized content is invalid")]
+    InvalidSerializedContent,
+}
+
+/**
+ * A Bloom filter.
+ *
+ * It holds a set of serialized keys approximately. A key not in the set is rejected without
+ * touching the base-check array of a trie, with a small false positive rate. A key in the set is
+ * never rejected.
+ *
+ * It is serialized into its own section, so that it can be loaded with a storage.
+ */
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BloomFilter {
+    hash_count: u32,
+    words: Vec<u64>,
+}
+
+impl BloomFilter {
+    /**
+     * Creates an empty Bloom filter.
+     *
+     * The false positive rate is about 1% with 10 bits per key.
+     *
+     * # Arguments
+     * * `key_count`    - The expected number of the keys.
+     * * `bits_per_key` - The number of the bits per key.
+     *
+     * # Errors
+     * * When the number of the bits per key is 0 or too large.
+     */
+    pub fn new(key_count: usize, bits_per_key: usize) -> Result<Self> {
+        if bits_per_key == 0 || bits_per_key > Self::MAX_BITS_PER_KEY {
+            return Err(BloomFilterError::InvalidBitsPerKey.into());
+        }
+        let bit_count = key_count
+            .saturating_mul(bits_per_key)
+            .max(u64::BITS as usize);
+        Ok(Self {
+            hash_count: Self::hash_count_of(bits_per_key),
+            words: vec![0; bit_count.div_ceil(u64::BITS as usize)],
+        })
+    }
+
+    /**
+     * Creates a Bloom filter with bytes.
+     *
+     * # Arguments
+     * * `bytes` - Bytes serialized by `BloomFilter::serialize()`.
+     *
+     * # Errors
+     * * When the bytes are broken.
+     * * When the number of the hash functions is larger than the one for the largest number of
+     *   the bits per key.
+     */
+    pub fn new_with_bytes(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() < size_of::<u32>() * 2 {
+            return Err(BloomFilterError::InvalidSerializedContent.into());
+        }
+        let (header, body) = bytes.split_at(size_of::<u32>() * 2);
+        let hash_count = read_u32(&header[..size_of::<u32>()]);
+        let word_count = read_u32(&header[size_of::<u32>()..]) as usize;
+        if hash_count == 0
+            || hash_count > Self::hash_count_of(Self::MAX_BITS_PER_KEY)
+            || word_count == 0
+            || body.len() != word_count * size_of::<u64>()
+        {
+            return Err(BloomFilterError::InvalidSerializedContent.into());
+        }
+        let words = body
+            .chunks_exact(size_of::<u64>())
+            .map(|chunk| {
+                let mut word = [0u8; size_of::<u64>()];
+                word.copy_from_slice(chunk);
+                u64::from_be_bytes(word)
+            })
+            .collect();
+        Ok(Self { hash_count, words })
+    }
+
+    /**
+     * Creates a Bloom filter with a reader of a sectioned serialization.
+     *
+     * Only the Bloom filter section is read.
+     *
+     * # Arguments
+     * * `reader`        - The reader from which the section table is read.
+     * * `section_table` - A section table.
+     *
+     * # Returns
+     * The Bloom filter. Or None when there is no Bloom filter section.
+     *
+     * # Errors
+     * * When it fails to read the section.
+     * * When the section is broken.
+     */
+    pub fn new_with_section<R: Read + Seek>(
+        reader: &mut R,
+        section_table: &SectionTable,
+    ) -> Result<Option<Self>> {
+        let Some(bytes) = section_table.read_section(reader, SerializationSection::BloomFilter)?
+        else {
+            return Ok(None);
+        };
+        Ok(Some(Self::new_with_bytes(&bytes)?))
+    }
+
+    /**
+     * Returns the number of the hash functions.
+     *
+     * # Returns
+     * The number of the hash functions.
+     */
+    pub const fn hash_count(&self) -> u32 {
+        self.hash_count
+    }
+
+    /**
+     * Returns the number of the bits.
+     *
+     * # Returns
+     * The number of the bits.
+     */
+    pub fn bit_count(&self) -> usize {
+        self.words.len() * u64::BITS as usize
+    }
+
+    /**
+     * Inserts a serialized key.
+     *
+     * # Arguments
+     * * `serialized_key` - A serialized key.
+     */
+    pub fn insert(&mut self, serialized_key: &[u8]) {
+        for bit in self.bits_of(serialized_key) {
+            self.words[bit / u64::BITS as usize] |= 1 << (bit % u64::BITS as usize);
+        }
+    }
+
+    /**
+     * Returns `true` if the filter may contain a serialized key.
+     *
+     * # Arguments
+     * * `serialized_key` - A serialized key.
+     *
+     * # Returns
+     * `true` if the filter may contain the serialized key. Or `false` when it certainly does
+     * not.
+     */
+    pub fn might_contain(&self, serialized_key: &[u8]) -> bool {
+        self.bits_of(serialized_key).all(|bit| {
+            self.words[bit / u64::BITS as usize] & (1 << (bit % u64::BITS as usize)) != 0
+        })
+    }
+
+    /**
+     * Serializes this filter.
+     *
+     * The bytes are the content of the Bloom filter section.
+     *
+     * # Returns
+     * The serialized bytes.
+     */
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut bytes =
+            Vec::with_capacity(size_of::<u32>() * 2 + self.words.len() * size_of::<u64>());
+        bytes.extend_from_slice(&self.hash_count.to_be_bytes());
+        bytes.extend_from_slice(&(self.words.len() as u32).to_be_bytes());
+        for word in &self.words {
+            bytes.extend_from_slice(&word.to_be_bytes());
+        }
+        bytes
+    }
+
+    const MAX_BITS_PER_KEY: usize = 64;
+
+    const fn hash_count_of(bits_per_key: usize) -> u32 {
+        // The optimal number of the hash functions is bits_per_key * ln(2).
+        let hash_count = (bits_per_key * 69 + 50) / 100;
+        if hash_count == 0 {
+            1
+        } else {
+            hash_count as u32
+        }
+    }
+
+    const OFFSET_BASIS: u64 = 0xCBF29CE484222325;
+
+    const PRIME: u64 = 0x00000100000001B3;
+
+    // The bits are derived from two halves of an FNV-1a hash by double hashing, so that the
+    // filter is identical across platforms.
+    fn bits_of(&self, serialized_key: &[u8]) -> impl Iterator<Item = usize> {
+        let hash = serialized_key
+            .iter()
+            .fold(Self::OFFSET_BASIS, |hash, &byte| {
+                (hash ^ byte as u64).wrapping_mul(Self::PRIME)
+            });
+        let (hash1, hash2) = (hash & 0xFFFFFFFF, (hash >> 32) | 1);
+        let bit_count = self.bit_count() as u64;
+        (0..self.hash_count as u64)
+            .map(move |i| (hash1.wrapping_add(i.wrapping_mul(hash2)) % bit_count) as usize)
+    }
+}
+
+fn read_u32(bytes: &[u8]) -> u32 {
+    let mut value = [0u8; size_of::<u32>()];
+    value.copy_from_slice(bytes);
+    u32::from_be_bytes(value)
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "builder")]
+    use std::io::Cursor;
+
+    use crate::error::TrieError;
+
+    use super::*;
+
+    fn bloom_filter_of(keys: &[&[u8]]) -> BloomFilter {
+        let mut bloom_filter = BloomFilter::new(keys.len(), 10).unwrap();
+        keys.iter().for_each(|key| bloom_filter.insert(key));
+        bloom_filter
+    }
+
+    #[test]
+    fn new() {
+        {
+            let bloom_filter = BloomFilter::new(100, 10).unwrap();
+
+            assert_eq!(bloom_filter.hash_count(), 7);
+            assert_eq!(bloom_filter.bit_count(), 1024);
+            assert!(!bloom_filter.might_contain(b"Kumamoto"));
+        }
+        {
+            let bloom_filter = BloomFilter::new(0, 1).unwrap();
+
+            assert_eq!(bloom_filter.hash_count(), 1);
+            assert_eq!(bloom_filter.bit_count(), 64);
+        }
+        {
+            assert!(matches!(
+                BloomFilter::new(100, 0),
+                Err(TrieError::BloomFilter(BloomFilterError::InvalidBitsPerKey))
+            ));
+        }
+    }
+
+    #[test]
+    fn might_contain() {
+        let keys = (0..1000)
+            .map(|i| format!("key{}", i).into_bytes())
+            .collect::<Vec<_>>();
+        let mut bloom_filter = BloomFilter::new(keys.len(), 10).unwrap();
+        keys.iter().for_each(|key| bloom_filter.insert(key));
+
+        assert!(keys.iter().all(|key| bloom_filter.might_contain(key)));
+        let false_positive_count = (0..1000)
+            .filter(|i| bloom_filter.might_contain(format!("other{}", i).as_bytes()))
+            .count();
+        assert!(false_positive_count < 50);
+    }
+
+    #[test]
+    fn serialize() {
+        let bloom_filter = bloom_filter_of(&[b"Kumamoto", b"Tamana"]);
+
+        let serialized = bloom_filter.serialize();
+
+        assert_eq!(serialized.len(), 4 + 4 + 8);
+        assert_eq!(&serialized[..8], &[0, 0, 0, 7, 0, 0, 0, 1]);
+        let deserialized = BloomFilter::new_with_bytes(&serialized).unwrap();
+        assert_eq!(deserialized, bloom_filter);
+        assert!(deserialized.might_contain(b"Kumamoto"));
+        assert!(deserialized.might_contain(b"Tamana"));
+
+        assert!(BloomFilter::new_with_bytes(&serialized[..12]).is_err());
+        assert!(
+            BloomFilter::new_with_bytes(&[0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0]).is_err()
+        );
+        assert!(BloomFilter::new_with_bytes(&[]).is_err());
+        assert!(
+            BloomFilter::new_with_bytes(&[0, 0, 0, 44, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0]).is_ok()
+        );
+        assert!(
+            BloomFilter::new_with_bytes(&[0, 0, 0, 45, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0])
+                .is_err()
+        );
+        assert!(BloomFilter::new_with_bytes(&[
+            0xFF, 0xFF, 0xFF, 0xFF, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0
+        ])
+        .is_err());
+    }
+
+    #[cfg(feature = "builder")]
+    #[test]
+    fn new_with_section() {
+        let bloom_filter = bloom_filter_of(&[b"Kumamoto"]);
+        {
+            let mut writer = Cursor::new(Vec::new());
+            SectionTable::write(
+                &mut writer,
+                &[(SerializationSection::BloomFilter, &bloom_filter.serialize())],
+            )
+            .unwrap();
+            let mut reader = Cursor::new(writer.into_inner());
+            let section_table = SectionTable::new_with_reader(&mut reader).unwrap();
+
+            let loaded = BloomFilter::new_with_section(&mut reader, &section_table).unwrap();
+
+            assert_eq!(loaded, Some(bloom_filter));
+        }
+        {
+            let mut writer = Cursor::new(Vec::new());
+            SectionTable::write(
+                &mut writer,
+                &[(SerializationSection::Auxiliary(42), &[0x2A])],
+            )
+            .unwrap();
+            let mut reader = Cursor::new(writer.into_inner());
+            let section_table = SectionTable::new_with_reader(&mut reader).unwrap();
+
+            let loaded = BloomFilter::new_with_section(&mut reader, &section_table).unwrap();
+
+            assert!(loaded.is_none());
+        }
+    }
+}
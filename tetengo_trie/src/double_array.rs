@@ -4,46 +4,184 @@
  * Copyright (C) 2023-2025 kaoru  <https://www.tetengo.org/>
  */
 
+#[cfg(feature = "builder")]
 use std::any::type_name_of_val;
+use std::cmp::{max, min};
+use std::collections::VecDeque;
 use std::fmt::{self, Debug, Formatter};
+use std::io::Write;
+#[cfg(feature = "builder")]
 use std::marker::PhantomData;
+use std::str;
 
-use anyhow::Result;
-
+#[cfg(feature = "builder")]
 use crate::double_array_builder;
 use crate::double_array_iterator::DoubleArrayIterator;
+use crate::error::Result;
+use crate::locality_stats::LocalityStats;
 use crate::storage::Storage;
 
+/**
+ * A double array error.
+ */
 #[derive(Clone, Copy, Debug, thiserror::Error)]
-pub(super) enum DoubleArrayError {
+pub enum DoubleArrayError {
+    /**
+     * The density factor is invalid.
+     */
     #[error("density_factor must be greater than 0.")]
     InvalidDensityFactor,
+
+    /**
+     * The elements are not sorted.
+     */
+    #[error("the elements are not sorted at index {index}")]
+    ElementsNotSorted {
+        /// The index of the first element smaller than the preceding one.
+        index: usize,
+    },
+
+    /**
+     * A base plus a character code overflows.
+     */
+    #[error("the base plus a character code overflows")]
+    BaseOverflow,
+
+    /**
+     * A value index is negative.
+     */
+    #[error("the value index is negative")]
+    NegativeValueIndex,
+
+    /**
+     * The building is cancelled.
+     */
+    #[error("the building is cancelled")]
+    Cancelled,
+}
+
+pub(super) fn next_base_check_index(
+    base: i32,
+    c: u8,
+    base_check_size: usize,
+) -> Result<Option<usize>> {
+    let Some(next_base_check_index) = base.checked_add(c as i32) else {
+        return Err(DoubleArrayError::BaseOverflow.into());
+    };
+    Ok(usize::try_from(next_base_check_index)
+        .ok()
+        .filter(|&index| index < base_check_size))
+}
+
+pub(super) fn to_value_index(base: i32) -> Result<usize> {
+    usize::try_from(base).map_err(|_| DoubleArrayError::NegativeValueIndex.into())
+}
+
+enum Utf8Decoding {
+    Complete(u32),
+    Incomplete,
+    Invalid,
+}
+
+fn decode_utf8(bytes: &[u8]) -> Utf8Decoding {
+    let length = match bytes[0] {
+        0x00..=0x7F => 1,
+        0xC2..=0xDF => 2,
+        0xE0..=0xEF => 3,
+        0xF0..=0xF4 => 4,
+        _ => return Utf8Decoding::Invalid,
+    };
+    if bytes.len() < length {
+        return if bytes[1..].iter().all(|&c| c & 0xC0 == 0x80) {
+            Utf8Decoding::Incomplete
+        } else {
+            Utf8Decoding::Invalid
+        };
+    }
+    match str::from_utf8(bytes).ok().and_then(|s| s.chars().next()) {
+        Some(char) => Utf8Decoding::Complete(u32::from(char)),
+        None => Utf8Decoding::Invalid,
+    }
 }
 
+#[cfg(feature = "builder")]
 pub(super) type DoubleArrayElement<'a> = (&'a [u8], i32);
 
+#[cfg(feature = "builder")]
+pub(super) type LocalityObserver<'a> = &'a mut dyn FnMut(&LocalityStats, &LocalityStats);
+
+#[cfg(feature = "builder")]
 pub(super) struct BuildingObserverSet<'a> {
     adding: &'a mut dyn FnMut(&DoubleArrayElement<'_>),
+    rejected: Option<&'a mut dyn FnMut(&DoubleArrayElement<'_>)>,
+    cancelled: Option<&'a mut dyn FnMut() -> bool>,
+    locality: Option<LocalityObserver<'a>>,
     done: &'a mut dyn FnMut(),
 }
 
+#[cfg(feature = "builder")]
 impl<'a> BuildingObserverSet<'a> {
     pub(super) fn new(
         adding: &'a mut dyn FnMut(&DoubleArrayElement<'_>),
         done: &'a mut dyn FnMut(),
     ) -> Self {
-        Self { adding, done }
+        Self {
+            adding,
+            rejected: None,
+            cancelled: None,
+            locality: None,
+            done,
+        }
+    }
+
+    pub(super) fn with_rejected(
+        mut self,
+        rejected: &'a mut dyn FnMut(&DoubleArrayElement<'_>),
+    ) -> Self {
+        self.rejected = Some(rejected);
+        self
+    }
+
+    pub(super) fn with_cancelled(mut self, cancelled: &'a mut dyn FnMut() -> bool) -> Self {
+        self.cancelled = Some(cancelled);
+        self
+    }
+
+    pub(super) fn with_locality(mut self, locality: LocalityObserver<'a>) -> Self {
+        self.locality = Some(locality);
+        self
     }
 
     pub(super) fn adding(&mut self, element: &DoubleArrayElement<'_>) {
         (self.adding)(element);
     }
 
+    pub(super) fn rejected(&mut self, element: &DoubleArrayElement<'_>) {
+        if let Some(rejected) = &mut self.rejected {
+            rejected(element);
+        }
+    }
+
+    pub(super) fn cancelled(&mut self) -> bool {
+        self.cancelled.as_mut().is_some_and(|cancelled| cancelled())
+    }
+
+    pub(super) const fn locality_observed(&self) -> bool {
+        self.locality.is_some()
+    }
+
+    pub(super) fn locality(&mut self, before: &LocalityStats, after: &LocalityStats) {
+        if let Some(locality) = &mut self.locality {
+            locality(before, after);
+        }
+    }
+
     pub(super) fn done(&mut self) {
         (self.done)();
     }
 }
 
+#[cfg(feature = "builder")]
 impl Debug for BuildingObserverSet<'_> {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         f.debug_struct("BuldingObserverSet")
@@ -53,31 +191,53 @@ impl Debug for BuildingObserverSet<'_> {
     }
 }
 
+#[cfg(feature = "builder")]
 pub(super) const DEFAULT_DENSITY_FACTOR: usize = 1000;
 
 pub(super) const KEY_TERMINATOR: u8 = 0;
 
 pub(super) const VACANT_CHECK_VALUE: u8 = 0xFF;
 
+const DEBUG_ROW_COUNT: usize = 8;
+
+#[cfg(feature = "builder")]
 #[derive(Debug)]
 pub(super) struct DoubleArrayBuilder<'a, Value: Debug> {
     elements: Vec<DoubleArrayElement<'a>>,
+    elements_sorted: bool,
     density_factor: usize,
+    breadth_first_depth: usize,
+    base_check_block_size: Option<usize>,
     phantom: PhantomData<Value>,
 }
 
+#[cfg(feature = "builder")]
 impl<'a, Value: Clone + Debug + 'static> DoubleArrayBuilder<'a, Value> {
     pub(super) fn elements(mut self, elements: Vec<DoubleArrayElement<'a>>) -> Self {
         self.elements = elements;
         self
     }
 
+    pub(super) const fn elements_sorted(mut self, elements_sorted: bool) -> Self {
+        self.elements_sorted = elements_sorted;
+        self
+    }
+
     pub(super) const fn density_factor(mut self, density_factor: usize) -> Self {
         self.density_factor = density_factor;
         self
     }
 
-    #[cfg(test)]
+    pub(super) const fn breadth_first_depth(mut self, breadth_first_depth: usize) -> Self {
+        self.breadth_first_depth = breadth_first_depth;
+        self
+    }
+
+    pub(super) const fn base_check_block_size(mut self, base_check_block_size: usize) -> Self {
+        self.base_check_block_size = Some(base_check_block_size);
+        self
+    }
+
     pub(super) fn build(self) -> Result<DoubleArray<Value>> {
         self.build_with_observer_set(&mut BuildingObserverSet::new(&mut |_| {}, &mut || {}))
     }
@@ -86,28 +246,123 @@ impl<'a, Value: Clone + Debug + 'static> DoubleArrayBuilder<'a, Value> {
         self,
         building_observer_set: &mut BuildingObserverSet<'_>,
     ) -> Result<DoubleArray<Value>> {
-        Ok(DoubleArray::new(
+        // The locality before the relocation is measured on a depth-first layout built aside.
+        let before = if self.breadth_first_depth > 0 && building_observer_set.locality_observed() {
+            let depth_first = DoubleArray::<Value>::new(
+                double_array_builder::build::<Value>(
+                    self.elements.clone(),
+                    self.elements_sorted,
+                    &mut BuildingObserverSet::new(&mut |_| {}, &mut || {}),
+                    self.density_factor,
+                    0,
+                    self.base_check_block_size,
+                )?,
+                0,
+            );
+            Some(depth_first.locality_stats(self.breadth_first_depth)?)
+        } else {
+            None
+        };
+
+        let double_array = DoubleArray::new(
             double_array_builder::build::<Value>(
                 self.elements,
+                self.elements_sorted,
                 building_observer_set,
                 self.density_factor,
+                self.breadth_first_depth,
+                self.base_check_block_size,
             )?,
             0,
-        ))
+        );
+
+        if let Some(before) = before {
+            let after = double_array.locality_stats(self.breadth_first_depth)?;
+            building_observer_set.locality(&before, &after);
+        }
+        building_observer_set.done();
+        Ok(double_array)
     }
 }
 
-#[derive(Debug)]
 pub(super) struct DoubleArray<Value: Debug> {
     storage: Box<dyn Storage<Value>>,
     root_base_check_index: usize,
 }
 
+impl<Value: Debug + 'static> DoubleArray<Value> {
+    fn summary(&self) -> Result<(usize, usize, f64)> {
+        Ok((
+            self.storage.base_check_size()?,
+            self.storage.value_count()?,
+            self.storage.filling_rate()?,
+        ))
+    }
+
+    fn base_check_rows(&self, limit: usize) -> Result<Vec<(usize, i32, u8)>> {
+        let row_count = min(self.storage.base_check_size()?, limit);
+        let mut rows = Vec::with_capacity(row_count);
+        for index in 0..row_count {
+            rows.push((
+                index,
+                self.storage.base_at(index)?,
+                self.storage.check_at(index)?,
+            ));
+        }
+        Ok(rows)
+    }
+}
+
+impl<Value: Debug + 'static> Debug for DoubleArray<Value> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let alternate = f.alternate();
+        let mut debug_struct = f.debug_struct("DoubleArray");
+        let _ = debug_struct.field("root_base_check_index", &self.root_base_check_index);
+        match self.summary() {
+            Ok((base_check_size, value_count, filling_rate)) => {
+                let _ = debug_struct
+                    .field("base_check_size", &base_check_size)
+                    .field("value_count", &value_count)
+                    .field("filling_rate", &filling_rate);
+            }
+            Err(e) => {
+                let _ = debug_struct.field("error", &e);
+            }
+        }
+        // The leading rows of the base-check array are shown only in the alternate form, such as
+        // the output of dbg!.
+        if alternate {
+            match self.base_check_rows(DEBUG_ROW_COUNT) {
+                Ok(rows) => {
+                    let _ = debug_struct.field("base_check_rows", &rows);
+                }
+                Err(e) => {
+                    let _ = debug_struct.field("error", &e);
+                }
+            }
+        }
+        debug_struct.finish_non_exhaustive()
+    }
+}
+
+impl<Value: Debug + 'static> Clone for DoubleArray<Value> {
+    fn clone(&self) -> Self {
+        Self {
+            storage: self.storage.clone(),
+            root_base_check_index: self.root_base_check_index,
+        }
+    }
+}
+
 impl<Value: Clone + Debug + 'static> DoubleArray<Value> {
+    #[cfg(feature = "builder")]
     pub(super) const fn builder() -> DoubleArrayBuilder<'static, Value> {
         DoubleArrayBuilder {
             elements: vec![],
+            elements_sorted: false,
             density_factor: DEFAULT_DENSITY_FACTOR,
+            breadth_first_depth: 0,
+            base_check_block_size: None,
             phantom: PhantomData,
         }
     }
@@ -122,23 +377,113 @@ impl<Value: Clone + Debug + 'static> DoubleArray<Value> {
         }
     }
 
-    pub(super) fn find(&self, key: &[u8]) -> Result<Option<i32>> {
-        let mut terminated_key: Vec<u8>;
-        let index = self.traverse({
-            terminated_key = Vec::from(key);
-            terminated_key.push(KEY_TERMINATOR);
-            &terminated_key
-        })?;
-        match index {
-            Some(index) => Ok(Some(self.storage.base_at(index)?)),
-            None => Ok(None),
+    pub(super) fn find(&self, key: &[u8]) -> Result<Option<usize>> {
+        self.find_with_probe_length(key).map(|(index, _)| index)
+    }
+
+    pub(super) fn find_with_probe_length(&self, key: &[u8]) -> Result<(Option<usize>, usize)> {
+        let mut base_check_index = self.root_base_check_index;
+        for (i, c) in key.iter().chain([KEY_TERMINATOR].iter()).enumerate() {
+            let Some(next_base_check_index) = self.step(base_check_index, *c)? else {
+                return Ok((None, i + 1));
+            };
+            base_check_index = next_base_check_index;
+        }
+        let value_index = to_value_index(self.storage.base_at(base_check_index)?)?;
+        Ok((Some(value_index), key.len() + 1))
+    }
+
+    pub(super) fn find_approximately(
+        &self,
+        key: &[u8],
+        max_distance: usize,
+    ) -> Result<Vec<(i32, usize)>> {
+        // The units are the characters when the key is valid UTF-8, and the bytes otherwise.
+        let (key_units, in_chars) = match str::from_utf8(key) {
+            Ok(key) => (key.chars().map(u32::from).collect::<Vec<_>>(), true),
+            Err(_) => (key.iter().map(|&c| u32::from(c)).collect::<Vec<_>>(), false),
+        };
+        let mut found = Vec::new();
+        let mut stack = vec![(
+            self.root_base_check_index,
+            (0..=key_units.len()).collect::<Vec<_>>(),
+            Vec::<u8>::new(),
+        )];
+        let base_check_size = self.storage.base_check_size()?;
+        while let Some((base_check_index, distances, pending_bytes)) = stack.pop() {
+            let base = self.storage.base_at(base_check_index)?;
+            for c in (0..=0xFEu8).rev() {
+                let Some(next_base_check_index) = next_base_check_index(base, c, base_check_size)?
+                else {
+                    continue;
+                };
+                if self.storage.check_at(next_base_check_index)? != c {
+                    continue;
+                }
+
+                if c == KEY_TERMINATOR {
+                    if pending_bytes.is_empty() && distances[key_units.len()] <= max_distance {
+                        found.push((
+                            self.storage.base_at(next_base_check_index)?,
+                            distances[key_units.len()],
+                        ));
+                    }
+                    continue;
+                }
+
+                let (unit, next_pending_bytes) = if in_chars {
+                    let mut next_pending_bytes = pending_bytes.clone();
+                    next_pending_bytes.push(c);
+                    match decode_utf8(&next_pending_bytes) {
+                        Utf8Decoding::Complete(unit) => (Some(unit), Vec::new()),
+                        Utf8Decoding::Incomplete => (None, next_pending_bytes),
+                        Utf8Decoding::Invalid => continue,
+                    }
+                } else {
+                    (Some(u32::from(c)), Vec::new())
+                };
+                let Some(unit) = unit else {
+                    stack.push((next_base_check_index, distances.clone(), next_pending_bytes));
+                    continue;
+                };
+
+                let mut next_distances = Vec::with_capacity(distances.len());
+                next_distances.push(distances[0] + 1);
+                for (i, k) in key_units.iter().enumerate() {
+                    let substitution = distances[i] + if *k == unit { 0 } else { 1 };
+                    let insertion = distances[i + 1] + 1;
+                    let deletion = next_distances[i] + 1;
+                    next_distances.push(substitution.min(insertion).min(deletion));
+                }
+                if next_distances
+                    .iter()
+                    .min()
+                    .is_some_and(|&d| d <= max_distance)
+                {
+                    stack.push((next_base_check_index, next_distances, next_pending_bytes));
+                }
+            }
         }
+        found.sort_by_key(|&(_, distance)| distance);
+        Ok(found)
     }
 
     pub(super) fn iter(&self) -> DoubleArrayIterator<'_, Value> {
         DoubleArrayIterator::new(self.storage.as_ref(), self.root_base_check_index)
     }
 
+    pub(super) fn iter_with_key_prefix(
+        &self,
+        key_prefix: &[u8],
+    ) -> Result<DoubleArrayIterator<'_, Value>> {
+        let index = self.traverse(key_prefix)?;
+        Ok(DoubleArrayIterator::new_with_key_prefix(
+            self.storage.as_ref(),
+            index,
+            key_prefix,
+        ))
+    }
+
     pub(super) fn subtrie(&self, key_prefix: &[u8]) -> Result<Option<Self>> {
         let index = self.traverse(key_prefix)?;
         let Some(index) = index else {
@@ -150,30 +495,116 @@ impl<Value: Clone + Debug + 'static> DoubleArray<Value> {
     fn traverse(&self, key: &[u8]) -> Result<Option<usize>> {
         let mut base_check_index = self.root_base_check_index;
         for c in key {
-            let next_base_check_index =
-                (self.storage.base_at(base_check_index)? + *c as i32) as usize;
-            if next_base_check_index >= self.storage.base_check_size()?
-                || self.storage.check_at(next_base_check_index)? != *c
-            {
+            let Some(next_base_check_index) = self.step(base_check_index, *c)? else {
                 return Ok(None);
-            }
+            };
             base_check_index = next_base_check_index;
         }
 
         Ok(Some(base_check_index))
     }
 
+    pub(super) const fn root(&self) -> usize {
+        self.root_base_check_index
+    }
+
+    pub(super) fn step(&self, base_check_index: usize, c: u8) -> Result<Option<usize>> {
+        let Some(next_base_check_index) = next_base_check_index(
+            self.storage.base_at(base_check_index)?,
+            c,
+            self.storage.base_check_size()?,
+        )?
+        else {
+            return Ok(None);
+        };
+        if self.storage.check_at(next_base_check_index)? != c {
+            return Ok(None);
+        }
+        Ok(Some(next_base_check_index))
+    }
+
+    pub(super) fn locality_stats(&self, depth: usize) -> Result<LocalityStats> {
+        let mut node_count = 0usize;
+        let mut lowest = self.root_base_check_index;
+        let mut highest = self.root_base_check_index;
+        let mut distance_sum = 0usize;
+        let mut child_count = 0usize;
+        let mut nodes = VecDeque::from([(self.root_base_check_index, 0usize)]);
+        while let Some((base_check_index, node_depth)) = nodes.pop_front() {
+            if node_depth >= depth {
+                continue;
+            }
+            node_count += 1;
+            for c in 0..=u8::MAX {
+                let Some(child_index) = self.step(base_check_index, c)? else {
+                    continue;
+                };
+                lowest = min(lowest, child_index);
+                highest = max(highest, child_index);
+                distance_sum += base_check_index.abs_diff(child_index);
+                child_count += 1;
+                if c != KEY_TERMINATOR {
+                    nodes.push_back((child_index, node_depth + 1));
+                }
+            }
+        }
+        let average_distance = if child_count > 0 {
+            distance_sum as f64 / child_count as f64
+        } else {
+            0.0
+        };
+        Ok(LocalityStats::new(
+            depth,
+            node_count,
+            highest - lowest + 1,
+            average_distance,
+        ))
+    }
+
+    pub(super) fn value_index(&self, base_check_index: usize) -> Result<Option<usize>> {
+        let Some(terminator_index) = self.step(base_check_index, KEY_TERMINATOR)? else {
+            return Ok(None);
+        };
+        Ok(Some(to_value_index(
+            self.storage.base_at(terminator_index)?,
+        )?))
+    }
+
+    pub(super) fn dump(&self, writer: &mut dyn Write, limit: usize) -> Result<()> {
+        let base_check_size = self.storage.base_check_size()?;
+        writeln!(
+            writer,
+            "root base-check index: {}",
+            self.root_base_check_index
+        )?;
+        writeln!(writer, "base-check size: {}", base_check_size)?;
+        writeln!(writer, "value count: {}", self.storage.value_count()?)?;
+        writeln!(writer, "filling rate: {:.3}", self.storage.filling_rate()?)?;
+        writeln!(writer, "{:>10} {:>11} {:>5}", "index", "base", "check")?;
+        for (index, base, check) in self.base_check_rows(limit)? {
+            writeln!(writer, "{:>10} {:>11} {:>5}", index, base, check)?;
+        }
+        if base_check_size > limit {
+            writeln!(writer, "... ({} more rows)", base_check_size - limit)?;
+        }
+        Ok(())
+    }
+
     pub(super) fn storage(&self) -> &dyn Storage<Value> {
         self.storage.as_ref()
     }
 
+    #[cfg(feature = "builder")]
     pub(super) fn storage_mut(&mut self) -> &mut dyn Storage<Value> {
         &mut *self.storage
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "builder"))]
 mod tests {
+    use crate::error::TrieError;
+    use crate::memory_storage::MemoryStorage;
+
     use super::*;
 
     #[rustfmt::skip]
@@ -315,6 +746,26 @@ mod tests {
         Ok(array)
     }
 
+    #[test]
+    fn next_base_check_index() {
+        assert_eq!(super::next_base_check_index(1, 2, 4).unwrap(), Some(3));
+        assert_eq!(super::next_base_check_index(1, 3, 4).unwrap(), None);
+        assert_eq!(super::next_base_check_index(-3, 2, 4).unwrap(), None);
+        assert!(matches!(
+            super::next_base_check_index(i32::MAX, 1, 4),
+            Err(TrieError::DoubleArray(DoubleArrayError::BaseOverflow))
+        ));
+    }
+
+    #[test]
+    fn to_value_index() {
+        assert_eq!(super::to_value_index(42).unwrap(), 42);
+        assert!(matches!(
+            super::to_value_index(-1),
+            Err(TrieError::DoubleArray(DoubleArrayError::NegativeValueIndex))
+        ));
+    }
+
     mod building_observer_set {
         use super::*;
 
@@ -517,6 +968,89 @@ mod tests {
             }
         }
 
+        #[test]
+        fn find_approximately() {
+            {
+                let double_array = DoubleArray::<i32>::builder().build().unwrap();
+
+                let found = double_array.find_approximately(b"SETA", 1).unwrap();
+                assert!(found.is_empty());
+            }
+            {
+                let double_array = DoubleArray::<i32>::builder()
+                    .elements(EXPECTED_VALUES3.to_vec())
+                    .build()
+                    .unwrap();
+
+                {
+                    let found = double_array.find_approximately(b"SETA", 0).unwrap();
+                    assert_eq!(found, vec![(42, 0)]);
+                }
+                {
+                    let found = double_array.find_approximately(b"SEDA", 1).unwrap();
+                    assert_eq!(found, vec![(42, 1)]);
+                }
+                {
+                    let found = double_array.find_approximately(b"UTA", 1).unwrap();
+                    assert_eq!(found, vec![(2424, 1)]);
+                }
+                {
+                    let found = double_array.find_approximately(b"UTA", 2).unwrap();
+                    assert_eq!(found, vec![(2424, 1), (42, 2)]);
+                }
+                {
+                    let found = double_array.find_approximately(b"SUIZENJI", 2).unwrap();
+                    assert!(found.is_empty());
+                }
+                {
+                    let found = double_array.find_approximately(b"SET\xFF", 1).unwrap();
+                    assert_eq!(found, vec![(42, 1)]);
+                }
+            }
+            {
+                let double_array = DoubleArray::<i32>::builder()
+                    .elements(EXPECTED_VALUES4.to_vec())
+                    .build()
+                    .unwrap();
+
+                {
+                    let found = double_array
+                        .find_approximately("赤瀬".as_bytes(), 1)
+                        .unwrap();
+                    assert_eq!(found, vec![(24, 0), (42, 1)]);
+                }
+                {
+                    let found = double_array
+                        .find_approximately("青水".as_bytes(), 1)
+                        .unwrap();
+                    assert_eq!(found, vec![(42, 1)]);
+                }
+                {
+                    let found = double_array.find_approximately("赤".as_bytes(), 0).unwrap();
+                    assert!(found.is_empty());
+                }
+            }
+        }
+
+        #[test]
+        fn corrupted() {
+            let mut storage = MemoryStorage::<i32>::new();
+            storage.set_base_at(0, 1).unwrap();
+            storage.set_check_at(1, KEY_TERMINATOR).unwrap();
+            storage.set_base_at(1, -1).unwrap();
+            storage.set_check_at(2, 1).unwrap();
+            storage.set_base_at(2, 0x7FFF00).unwrap();
+            let double_array = DoubleArray::<i32>::new(Box::new(storage), 0);
+
+            assert!(matches!(
+                double_array.find(b""),
+                Err(TrieError::DoubleArray(DoubleArrayError::NegativeValueIndex))
+            ));
+            assert!(double_array.find(&[1]).unwrap().is_none());
+            assert_eq!(double_array.iter().skip(1).count(), 0);
+            assert_eq!(double_array.storage().base_check_size().unwrap(), 3);
+        }
+
         #[test]
         fn iter() {
             {
@@ -4,47 +4,146 @@
  * Copyright (C) 2023-2025 kaoru  <https://www.tetengo.org/>
  */
 
-use std::collections::HashSet;
+use std::cmp::Reverse;
+use std::collections::{HashSet, VecDeque};
 use std::fmt::Debug;
 
-use anyhow::Result;
-
 use crate::double_array::{
     BuildingObserverSet, DoubleArrayElement, DoubleArrayError, KEY_TERMINATOR, VACANT_CHECK_VALUE,
 };
+use crate::error::Result;
 use crate::memory_storage::MemoryStorage;
 use crate::storage::Storage;
 
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip_all, fields(element_count = elements.len()))
+)]
 pub(super) fn build<T: Clone + Debug + 'static>(
     mut elements: Vec<DoubleArrayElement<'_>>,
+    elements_sorted: bool,
     observer: &mut BuildingObserverSet<'_>,
     density_factor: usize,
+    breadth_first_depth: usize,
+    base_check_block_size: Option<usize>,
 ) -> Result<Box<dyn Storage<T>>> {
     if density_factor == 0 {
         return Err(DoubleArrayError::InvalidDensityFactor.into());
     }
 
-    elements.sort_by_key(|(k, _)| *k);
+    // The output depends only on the elements so that it is reproducible across platforms and
+    // runs. The sort is stable, and the base uniquer is only queried and never iterated.
+    if elements_sorted {
+        if let Some(index) = elements.windows(2).position(|pair| pair[0].0 > pair[1].0) {
+            return Err(DoubleArrayError::ElementsNotSorted { index: index + 1 }.into());
+        }
+    } else {
+        elements.sort_by_key(|(k, _)| *k);
+    }
 
-    let mut storage = Box::new(MemoryStorage::<T>::new());
+    let mut storage = MemoryStorage::<T>::new();
+    if let Some(base_check_block_size) = base_check_block_size {
+        storage = storage.base_check_block_size(base_check_block_size);
+    }
+    let mut storage = Box::new(storage);
 
     if !elements.is_empty() {
         let mut base_uniquer = HashSet::new();
-        build_iter(
-            &elements[..],
-            0,
-            storage.as_mut(),
-            0,
-            &mut base_uniquer,
-            observer,
-            density_factor,
-        )?;
+        if breadth_first_depth > 0 {
+            build_breadth_first(
+                &elements[..],
+                storage.as_mut(),
+                &mut base_uniquer,
+                observer,
+                density_factor,
+                breadth_first_depth,
+            )?;
+        } else {
+            build_iter(
+                &elements[..],
+                0,
+                storage.as_mut(),
+                0,
+                &mut base_uniquer,
+                observer,
+                density_factor,
+            )?;
+        }
     }
 
-    observer.done();
     Ok(storage)
 }
 
+// The nodes shallower than the depth are placed in the breadth-first order, so that they lie in
+// contiguous low indices. Among the siblings, the children with more elements are placed first.
+// The deeper nodes are placed depth-first.
+fn build_breadth_first<T: 'static>(
+    elements: &[DoubleArrayElement<'_>],
+    storage: &mut dyn Storage<T>,
+    base_uniquer: &mut HashSet<i32>,
+    observer: &mut BuildingObserverSet<'_>,
+    density_factor: usize,
+    depth: usize,
+) -> Result<()> {
+    let mut nodes = VecDeque::from([(0..elements.len(), 0usize, 0usize)]);
+    while let Some((range, key_offset, base_check_index)) = nodes.pop_front() {
+        let node_elements = &elements[range.clone()];
+        if key_offset >= depth {
+            build_iter(
+                node_elements,
+                key_offset,
+                storage,
+                base_check_index,
+                base_uniquer,
+                observer,
+                density_factor,
+            )?;
+            continue;
+        }
+
+        let children_firsts = children_firsts(node_elements, key_offset);
+        let base = calc_base(
+            children_firsts.as_slice(),
+            node_elements,
+            key_offset,
+            storage,
+            base_check_index,
+            density_factor,
+            base_uniquer,
+        )?;
+        storage.set_base_at(base_check_index, base)?;
+
+        let mut children = Vec::with_capacity(children_firsts.len() - 1);
+        for i in 0..children_firsts.len() - 1 {
+            let children_first = children_firsts[i];
+            let children_last = children_firsts[i + 1];
+            let (element_key, value) = node_elements[children_first];
+            let char_code = char_code_at(element_key, key_offset);
+            let next_base_check_index = (base + char_code as i32) as usize;
+            storage.set_check_at(next_base_check_index, char_code)?;
+            if char_code == KEY_TERMINATOR {
+                if observer.cancelled() {
+                    return Err(DoubleArrayError::Cancelled.into());
+                }
+                observer.adding(&node_elements[children_first]);
+                node_elements[children_first + 1..children_last]
+                    .iter()
+                    .for_each(|element| observer.rejected(element));
+                storage.set_base_at(next_base_check_index, value)?;
+                continue;
+            }
+            children.push((
+                range.start + children_first..range.start + children_last,
+                key_offset + 1,
+                next_base_check_index,
+            ));
+        }
+        children.sort_by_key(|(child_range, _, _)| Reverse(child_range.len()));
+        nodes.extend(children);
+    }
+    Ok(())
+}
+
 fn build_iter<T: 'static>(
     elements: &[DoubleArrayElement<'_>],
     key_offset: usize,
@@ -80,7 +179,14 @@ fn build_iter<T: 'static>(
         let char_code = char_code_at(element_key, key_offset);
         let next_base_check_index = (base + char_code as i32) as usize;
         if char_code == KEY_TERMINATOR {
+            if observer.cancelled() {
+                return Err(DoubleArrayError::Cancelled.into());
+            }
+            // The first one of the duplicate keys is added, and the rest are rejected.
             observer.adding(&elements[children_first]);
+            elements[children_first + 1..children_last]
+                .iter()
+                .for_each(|element| observer.rejected(element));
             storage.set_base_at(next_base_check_index, value)?;
             continue;
         }
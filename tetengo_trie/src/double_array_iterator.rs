@@ -13,76 +13,189 @@ use crate::storage::Storage;
 pub(super) struct DoubleArrayIterator<'a, T: 'static> {
     storage: &'a dyn Storage<T>,
     base_check_index_key_stack: Vec<(usize, Vec<u8>)>,
+    back_base_check_index_key_stack: Vec<(usize, Vec<u8>)>,
+    last_key: Option<Vec<u8>>,
+    last_back_key: Option<Vec<u8>>,
+    max_key_length: Option<usize>,
 }
 
 impl<'a, T> DoubleArrayIterator<'a, T> {
     pub(super) fn new(storage: &'a dyn Storage<T>, root_base_check_index: usize) -> Self {
+        Self::new_with_key_prefix(storage, Some(root_base_check_index), &[])
+    }
+
+    pub(super) fn new_with_key_prefix(
+        storage: &'a dyn Storage<T>,
+        base_check_index: Option<usize>,
+        key_prefix: &[u8],
+    ) -> Self {
+        // The stacks start with the node of the key prefix, so that the traversal never leaves
+        // its subtree. Without the node, they start empty.
+        let base_check_index_key_stack = base_check_index
+            .map(|base_check_index| (base_check_index, key_prefix.to_vec()))
+            .into_iter()
+            .collect::<Vec<_>>();
         Self {
             storage,
-            base_check_index_key_stack: vec![(root_base_check_index, Vec::new())],
+            back_base_check_index_key_stack: base_check_index_key_stack.clone(),
+            base_check_index_key_stack,
+            last_key: None,
+            last_back_key: None,
+            max_key_length: None,
         }
     }
-}
-
-impl<T> Iterator for DoubleArrayIterator<'_, T> {
-    type Item = i32;
 
-    fn next(&mut self) -> Option<Self::Item> {
-        let (base_check_index, key) = self.base_check_index_key_stack.pop()?;
+    pub(super) const fn with_max_key_length(mut self, max_key_length: Option<usize>) -> Self {
+        self.max_key_length = max_key_length;
+        self
+    }
 
-        let base = match self.storage.base_at(base_check_index) {
-            Ok(base) => base,
-            Err(e) => {
-                debug_assert!(false, "{}", e);
-                return None;
-            }
-        };
-        let check = match self.storage.check_at(base_check_index) {
-            Ok(check) => check,
-            Err(e) => {
-                debug_assert!(false, "{}", e);
-                return None;
-            }
-        };
+    fn next_in(
+        storage: &dyn Storage<T>,
+        base_check_index_key_stack: &mut Vec<(usize, Vec<u8>)>,
+        reverse: bool,
+        max_key_length: Option<usize>,
+    ) -> Option<(i32, Vec<u8>)> {
+        loop {
+            let (base_check_index, key) = base_check_index_key_stack.pop()?;
 
-        if check == double_array::KEY_TERMINATOR {
-            return Some(base);
-        }
+            let base = match storage.base_at(base_check_index) {
+                Ok(base) => base,
+                Err(e) => {
+                    debug_assert!(false, "{}", e);
+                    return None;
+                }
+            };
+            let check = match storage.check_at(base_check_index) {
+                Ok(check) => check,
+                Err(e) => {
+                    debug_assert!(false, "{}", e);
+                    return None;
+                }
+            };
 
-        for char_code in (0..=0xFE).rev() {
-            let char_code_as_uint8 = char_code as u8;
-            let next_index = base + char_code_as_uint8 as i32;
-            if next_index < 0 {
-                continue;
+            if check == double_array::KEY_TERMINATOR {
+                return Some((base, key));
             }
-            let check_at_next_index = match self.storage.check_at(next_index as usize) {
-                Ok(check) => check,
+            // The subtree beyond the maximum key length is skipped, so that a corrupted storage
+            // never makes the stack grow without bound.
+            let key_length_exceeded =
+                max_key_length.is_some_and(|max_key_length| key.len() >= max_key_length);
+
+            let base_check_size = match storage.base_check_size() {
+                Ok(size) => size,
                 Err(e) => {
                     debug_assert!(false, "{}", e);
                     return None;
                 }
             };
-            if check_at_next_index == char_code_as_uint8 {
-                let mut next_key_tail = if char_code_as_uint8 != double_array::KEY_TERMINATOR {
-                    vec![char_code_as_uint8]
-                } else {
-                    Vec::new()
+            let mut children = Vec::new();
+            for char_code in 0..=0xFE {
+                let char_code_as_uint8 = char_code as u8;
+                // An index out of the base-check array is skipped, so that a corrupted base never
+                // makes the storage grow or read past the end.
+                let next_index = match double_array::next_base_check_index(
+                    base,
+                    char_code_as_uint8,
+                    base_check_size,
+                ) {
+                    Ok(Some(next_index)) => next_index,
+                    Ok(None) => continue,
+                    Err(e) => {
+                        debug_assert!(false, "{}", e);
+                        return None;
+                    }
                 };
-                let next_key = {
-                    let mut next_key = key.clone();
-                    next_key.append(&mut next_key_tail);
-                    next_key
+                let check_at_next_index = match storage.check_at(next_index) {
+                    Ok(check) => check,
+                    Err(e) => {
+                        debug_assert!(false, "{}", e);
+                        return None;
+                    }
                 };
-                self.base_check_index_key_stack
-                    .push((next_index as usize, next_key));
+                if check_at_next_index == char_code_as_uint8
+                    && (char_code_as_uint8 == double_array::KEY_TERMINATOR || !key_length_exceeded)
+                {
+                    let mut next_key_tail = if char_code_as_uint8 != double_array::KEY_TERMINATOR {
+                        vec![char_code_as_uint8]
+                    } else {
+                        Vec::new()
+                    };
+                    let next_key = {
+                        let mut next_key = key.clone();
+                        next_key.append(&mut next_key_tail);
+                        next_key
+                    };
+                    children.push((next_index, next_key));
+                }
+            }
+            // The stack pops the smallest child first in the lexicographic order, and the largest
+            // child first in the reverse order.
+            if !reverse {
+                children.reverse();
             }
+            base_check_index_key_stack.extend(children);
         }
+    }
+}
 
-        self.next()
+impl<T> DoubleArrayIterator<'_, T> {
+    pub(super) fn next_with_key(&mut self) -> Option<(Vec<u8>, i32)> {
+        let value_index = self.next()?;
+        self.last_key.clone().map(|key| (key, value_index))
+    }
+
+    pub(super) fn next_back_with_key(&mut self) -> Option<(Vec<u8>, i32)> {
+        let value_index = self.next_back()?;
+        self.last_back_key.clone().map(|key| (key, value_index))
     }
 }
 
-#[cfg(test)]
+impl<T> Iterator for DoubleArrayIterator<'_, T> {
+    type Item = i32;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (value_index, key) = Self::next_in(
+            self.storage,
+            &mut self.base_check_index_key_stack,
+            false,
+            self.max_key_length,
+        )?;
+        if self
+            .last_back_key
+            .as_ref()
+            .is_some_and(|last_back_key| key >= *last_back_key)
+        {
+            self.base_check_index_key_stack.clear();
+            return None;
+        }
+        self.last_key = Some(key);
+        Some(value_index)
+    }
+}
+
+impl<T> DoubleEndedIterator for DoubleArrayIterator<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let (value_index, key) = Self::next_in(
+            self.storage,
+            &mut self.back_base_check_index_key_stack,
+            true,
+            self.max_key_length,
+        )?;
+        if self
+            .last_key
+            .as_ref()
+            .is_some_and(|last_key| key <= *last_key)
+        {
+            self.back_base_check_index_key_stack.clear();
+            return None;
+        }
+        self.last_back_key = Some(key);
+        Some(value_index)
+    }
+}
+
+#[cfg(all(test, feature = "builder"))]
 mod tests {
     use crate::double_array::{DoubleArray, DoubleArrayElement};
 
@@ -195,4 +308,50 @@ mod tests {
             assert_eq!(values, vec![84, 48]);
         }
     }
+
+    #[test]
+    fn next_back() {
+        {
+            let double_array = DoubleArray::<i32>::builder().build().unwrap();
+            let mut iterator = double_array.iter();
+
+            assert!(iterator.next_back().is_none());
+        }
+        {
+            let double_array = DoubleArray::<i32>::builder()
+                .elements(EXPECTED_VALUES3.to_vec())
+                .build()
+                .unwrap();
+
+            let values = double_array.iter().rev().collect::<Vec<_>>();
+            assert_eq!(values, vec![2424, 24, 42]);
+        }
+        {
+            let double_array = DoubleArray::<i32>::builder()
+                .elements(EXPECTED_VALUES3.to_vec())
+                .build()
+                .unwrap();
+            let mut iterator = double_array.iter();
+
+            assert_eq!(iterator.next(), Some(42));
+            assert_eq!(iterator.next_back(), Some(2424));
+            assert_eq!(iterator.next_back(), Some(24));
+            assert!(iterator.next().is_none());
+            assert!(iterator.next_back().is_none());
+        }
+        {
+            let elements: &[DoubleArrayElement<'_>] =
+                &[(b"UT", 1), (b"UTO", 2), (b"U", 3), (b"UTOA", 4)];
+            let double_array = DoubleArray::<i32>::builder()
+                .elements(elements.to_vec())
+                .build()
+                .unwrap();
+
+            assert_eq!(double_array.iter().collect::<Vec<_>>(), vec![3, 1, 2, 4]);
+            assert_eq!(
+                double_array.iter().rev().collect::<Vec<_>>(),
+                vec![4, 2, 1, 3]
+            );
+        }
+    }
 }
@@ -0,0 +1,161 @@
+/*!
+ * An error.
+ *
+ * Copyright (C) 2023-2025 kaoru  <https://www.tetengo.org/>
+ */
+
+use std::error;
+use std::io;
+use std::result;
+use std::string::FromUtf8Error;
+
+use crate::bloom_filter::BloomFilterError;
+use crate::double_array::DoubleArrayError;
+use crate::file_mapping::FileMappingError;
+use crate::integer_serializer::IntegerDeserialationError;
+use crate::key_metadata::KeyMetadataError;
+use crate::key_transform::KeyTransformError;
+use crate::memory_storage::MemoryStorageError;
+use crate::mmap_storage::MmapStorageError;
+use crate::multi_trie::MultiTrieError;
+use crate::random_access_storage::RandomAccessStorageError;
+use crate::section_table::SectionTableError;
+use crate::shared_memory::SharedMemoryError;
+#[cfg(feature = "builder")]
+use crate::trie::BuildError;
+use crate::trie::{KeyLengthError, TrieVerificationError};
+#[cfg(feature = "compression")]
+use crate::value_serializer::CompressionError;
+
+/**
+ * A trie error.
+ *
+ * Some variants exist only with their features enabled, so a match on it needs a wildcard arm.
+ */
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum TrieError {
+    /**
+     * A build error.
+     */
+    #[cfg(feature = "builder")]
+    #[error("build error")]
+    Build(#[from] BuildError),
+
+    /**
+     * A Bloom filter error.
+     */
+    #[error("Bloom filter error")]
+    BloomFilter(#[from] BloomFilterError),
+
+    /**
+     * A double array error.
+     */
+    #[error("double array error")]
+    DoubleArray(#[from] DoubleArrayError),
+
+    /**
+     * A file mapping error.
+     */
+    #[error("file mapping error")]
+    FileMapping(#[from] FileMappingError),
+
+    /**
+     * A key length error.
+     */
+    #[error("key length error")]
+    KeyLength(#[from] KeyLengthError),
+
+    /**
+     * A key metadata error.
+     */
+    #[error("key metadata error")]
+    KeyMetadata(#[from] KeyMetadataError),
+
+    /**
+     * A key transform error.
+     */
+    #[error("key transform error")]
+    KeyTransform(#[from] KeyTransformError),
+
+    /**
+     * A memory storage error.
+     */
+    #[error("memory storage error")]
+    MemoryStorage(#[from] MemoryStorageError),
+
+    /**
+     * An mmap storage error.
+     */
+    #[error("mmap storage error")]
+    MmapStorage(#[from] MmapStorageError),
+
+    /**
+     * A multi-trie error.
+     */
+    #[error("multi-trie error")]
+    MultiTrie(#[from] MultiTrieError),
+
+    /**
+     * A random access storage error.
+     */
+    #[error("random access storage error")]
+    RandomAccessStorage(#[from] RandomAccessStorageError),
+
+    /**
+     * A section table error.
+     */
+    #[error("section table error")]
+    SectionTable(#[from] SectionTableError),
+
+    /**
+     * A shared memory error.
+     */
+    #[error("shared memory error")]
+    SharedMemory(#[from] SharedMemoryError),
+
+    /**
+     * A trie verification error.
+     */
+    #[error("trie verification error")]
+    Verification(#[from] TrieVerificationError),
+
+    /**
+     * An integer deserialization error.
+     */
+    #[error("integer deserialization error")]
+    IntegerDeserialization(#[from] IntegerDeserialationError),
+
+    /**
+     * A string deserialization error.
+     */
+    #[error("string deserialization error")]
+    StringDeserialization(#[from] FromUtf8Error),
+
+    /**
+     * A compression error.
+     */
+    #[cfg(feature = "compression")]
+    #[error("compression error")]
+    Compression(#[from] CompressionError),
+
+    /**
+     * An I/O error.
+     */
+    #[error("I/O error")]
+    Io(#[from] io::Error),
+
+    /**
+     * An error from a user-defined storage, serializer or deserializer.
+     */
+    #[error("other error")]
+    Other(#[source] Box<dyn error::Error + Send + Sync>),
+}
+
+/**
+ * A result type.
+ *
+ * # Type Parameters
+ * * `T` - A value type.
+ */
+pub type Result<T> = result::Result<T, TrieError>;
@@ -5,9 +5,10 @@
 use std::fs::File;
 use std::ops::Range;
 
-use anyhow::Result;
 use memmap2::Mmap;
 
+use crate::error::Result;
+
 /**
  * A file mapping error.
  */
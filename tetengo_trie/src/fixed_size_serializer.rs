@@ -0,0 +1,310 @@
+/*!
+ * A fixed-size serializer/deserializer.
+ *
+ * Copyright (C) 2023-2025 kaoru  <https://www.tetengo.org/>
+ */
+
+use std::array;
+use std::marker::PhantomData;
+
+use crate::error::Result;
+use crate::integer_serializer::IntegerDeserialationError;
+use crate::serializer::{escape_fe, unescape_fe, Deserializer, Serializer};
+use crate::value_serializer::{ValueDeserializer, ValueSerializer};
+
+/**
+ * A trait for objects serialized in a fixed size.
+ *
+ * It is implemented for the primitive integers, the arrays and the tuples of up to four elements
+ * of them.
+ */
+pub trait FixedSize: Sized {
+    /**
+     * The serialized size.
+     */
+    const SIZE: usize;
+
+    /**
+     * Writes the bytes of this object.
+     *
+     * # Arguments
+     * * `bytes` - A byte vector to which exactly `SIZE` bytes are appended.
+     */
+    fn write_bytes(&self, bytes: &mut Vec<u8>);
+
+    /**
+     * Reads an object from bytes.
+     *
+     * # Arguments
+     * * `bytes` - Bytes whose length is `SIZE`.
+     *
+     * # Returns
+     * The object.
+     */
+    fn read_bytes(bytes: &[u8]) -> Self;
+}
+
+macro_rules! impl_fixed_size_for_integer {
+    ($($t:ty),*) => {
+        $(
+            impl FixedSize for $t {
+                const SIZE: usize = size_of::<$t>();
+
+                fn write_bytes(&self, bytes: &mut Vec<u8>) {
+                    bytes.extend_from_slice(&self.to_be_bytes());
+                }
+
+                fn read_bytes(bytes: &[u8]) -> Self {
+                    let mut be_bytes = [0u8; size_of::<$t>()];
+                    be_bytes.copy_from_slice(bytes);
+                    <$t>::from_be_bytes(be_bytes)
+                }
+            }
+        )*
+    };
+}
+
+impl_fixed_size_for_integer!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128);
+
+impl<T: FixedSize, const N: usize> FixedSize for [T; N] {
+    const SIZE: usize = T::SIZE * N;
+
+    fn write_bytes(&self, bytes: &mut Vec<u8>) {
+        self.iter().for_each(|element| element.write_bytes(bytes));
+    }
+
+    fn read_bytes(bytes: &[u8]) -> Self {
+        array::from_fn(|i| T::read_bytes(&bytes[i * T::SIZE..(i + 1) * T::SIZE]))
+    }
+}
+
+macro_rules! impl_fixed_size_for_tuple {
+    ($($t:ident: $i:tt),*) => {
+        impl<$($t: FixedSize),*> FixedSize for ($($t,)*) {
+            const SIZE: usize = 0 $(+ $t::SIZE)*;
+
+            fn write_bytes(&self, bytes: &mut Vec<u8>) {
+                $(self.$i.write_bytes(bytes);)*
+            }
+
+            #[allow(unused_assignments)]
+            fn read_bytes(bytes: &[u8]) -> Self {
+                let mut offset = 0;
+                ($({
+                    let element = $t::read_bytes(&bytes[offset..offset + $t::SIZE]);
+                    offset += $t::SIZE;
+                    element
+                },)*)
+            }
+        }
+    };
+}
+
+impl_fixed_size_for_tuple!(A: 0, B: 1);
+impl_fixed_size_for_tuple!(A: 0, B: 1, C: 2);
+impl_fixed_size_for_tuple!(A: 0, B: 1, C: 2, D: 3);
+
+/**
+ * A fixed-size serializer.
+ *
+ * The integers are serialized in big endian.
+ *
+ * When the argument `fe_escape` of the constructor is true, the bytes are escaped in the same way
+ * as `IntegerSerializer`. Note that the serialized size is no longer fixed then.
+ *
+ * # Type Parameters
+ * * `Object` - An object type.
+ */
+#[derive(Debug)]
+pub struct FixedSizeSerializer<Object: FixedSize> {
+    fe_escape: bool,
+    phantom: PhantomData<Object>,
+}
+
+impl<Object: FixedSize> FixedSizeSerializer<Object> {
+    /**
+     * Returns the fixed value size.
+     *
+     * # Returns
+     * The fixed value size. Or 0 if the bytes are escaped.
+     */
+    pub const fn fixed_value_size(&self) -> usize {
+        if self.fe_escape {
+            0
+        } else {
+            Object::SIZE
+        }
+    }
+
+    /**
+     * Converts this serializer into a value serializer.
+     *
+     * The fixed value size of the value serializer is derived from the object type.
+     *
+     * # Returns
+     * A value serializer.
+     */
+    pub fn into_value_serializer<'a>(self) -> ValueSerializer<'a, Object>
+    where
+        Object: 'a,
+    {
+        let fixed_value_size = self.fixed_value_size();
+        ValueSerializer::new(
+            Box::new(move |value: &Object| self.serialize(value)),
+            fixed_value_size,
+        )
+    }
+}
+
+impl<Object: FixedSize> Serializer for FixedSizeSerializer<Object> {
+    type Object<'a> = Object;
+
+    fn new(fe_escape: bool) -> Self {
+        FixedSizeSerializer {
+            fe_escape,
+            phantom: PhantomData,
+        }
+    }
+
+    fn serialize(&self, object: &Self::Object<'_>) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(Object::SIZE);
+        object.write_bytes(&mut bytes);
+        if self.fe_escape {
+            escape_fe(bytes)
+        } else {
+            bytes
+        }
+    }
+}
+
+/**
+ * A fixed-size deserializer.
+ *
+ * # Type Parameters
+ * * `Object` - An object type.
+ */
+#[derive(Debug)]
+pub struct FixedSizeDeserializer<Object: FixedSize> {
+    fe_escape: bool,
+    phantom: PhantomData<Object>,
+}
+
+impl<Object: FixedSize + Clone + 'static> FixedSizeDeserializer<Object> {
+    /**
+     * Converts this deserializer into a value deserializer.
+     *
+     * # Returns
+     * A value deserializer.
+     */
+    pub fn into_value_deserializer(self) -> ValueDeserializer<Object> {
+        ValueDeserializer::new(Box::new(move |serialized: &[u8]| {
+            self.deserialize(serialized)
+        }))
+    }
+}
+
+impl<Object: FixedSize> Deserializer for FixedSizeDeserializer<Object> {
+    type Object = Object;
+
+    fn new(fe_escape: bool) -> Self {
+        FixedSizeDeserializer {
+            fe_escape,
+            phantom: PhantomData,
+        }
+    }
+
+    fn deserialize(&self, serialized: &[u8]) -> Result<Self::Object> {
+        let unescaped;
+        let bytes = if self.fe_escape {
+            unescaped = unescape_fe(serialized)?;
+            unescaped.as_slice()
+        } else {
+            serialized
+        };
+        if bytes.len() != Object::SIZE {
+            return Err(IntegerDeserialationError::InvalidSerializedLength.into());
+        }
+        Ok(Object::read_bytes(bytes))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::double_array::KEY_TERMINATOR;
+
+    use super::*;
+
+    #[test]
+    fn size() {
+        assert_eq!(<u32 as FixedSize>::SIZE, 4);
+        assert_eq!(<[u8; 3] as FixedSize>::SIZE, 3);
+        assert_eq!(<(u32, u32) as FixedSize>::SIZE, 8);
+        assert_eq!(<(u16, [(u32, i8); 2], u64) as FixedSize>::SIZE, 20);
+    }
+
+    #[test]
+    fn serialize() {
+        {
+            let serializer = FixedSizeSerializer::<[u8; 3]>::new(false);
+
+            assert_eq!(serializer.serialize(&[3, 1, 4]), vec![3, 1, 4]);
+            assert_eq!(serializer.fixed_value_size(), 3);
+        }
+        {
+            let serializer = FixedSizeSerializer::<(u32, u32)>::new(false);
+
+            assert_eq!(
+                serializer.serialize(&(0x01234567, 0x89ABCDEF)),
+                vec![0x01, 0x23, 0x45, 0x67, 0x89, 0xAB, 0xCD, 0xEF]
+            );
+            assert_eq!(serializer.fixed_value_size(), 8);
+        }
+        {
+            let serializer = FixedSizeSerializer::<(u8, i16)>::new(true);
+
+            let serialized = serializer.serialize(&(0xFD, 0x00FE));
+            assert_eq!(serialized, vec![0xFD, 0xFD, 0xFE, 0xFD, 0xFE]);
+            assert!(!serialized.contains(&KEY_TERMINATOR));
+            assert_eq!(serializer.fixed_value_size(), 0);
+        }
+    }
+
+    #[test]
+    fn deserialize() {
+        {
+            let deserializer = FixedSizeDeserializer::<(u32, u32)>::new(false);
+
+            let object = deserializer
+                .deserialize(&[0x01, 0x23, 0x45, 0x67, 0x89, 0xAB, 0xCD, 0xEF])
+                .unwrap();
+            assert_eq!(object, (0x01234567, 0x89ABCDEF));
+        }
+        {
+            let deserializer = FixedSizeDeserializer::<(u8, i16)>::new(true);
+
+            let object = deserializer
+                .deserialize(&[0xFD, 0xFD, 0xFE, 0xFD, 0xFE])
+                .unwrap();
+            assert_eq!(object, (0xFD, 0x00FE));
+        }
+        {
+            let deserializer = FixedSizeDeserializer::<(u32, u32)>::new(false);
+
+            assert!(deserializer.deserialize(&[0x01, 0x23, 0x45]).is_err());
+        }
+    }
+
+    #[test]
+    fn into_value_serializer() {
+        let mut serializer =
+            FixedSizeSerializer::<(u32, [i64; 2])>::new(false).into_value_serializer();
+        let mut deserializer =
+            FixedSizeDeserializer::<(u32, [i64; 2])>::new(false).into_value_deserializer();
+
+        assert_eq!(serializer.fixed_value_size(), 20);
+        let value = (42, [-3, 14]);
+        let serialized = serializer.serialize(&value);
+        assert_eq!(serialized.len(), 20);
+        assert_eq!(deserializer.deserialize(&serialized).unwrap(), value);
+    }
+}
@@ -0,0 +1,214 @@
+/*!
+ * An integer list serializer/deserializer.
+ *
+ * Copyright (C) 2023-2025 kaoru  <https://www.tetengo.org/>
+ */
+
+use std::marker::PhantomData;
+
+use crate::error::Result;
+use crate::integer_serializer::IntegerDeserialationError;
+use crate::serializer::{escape_fe, unescape_fe, Deserializer, Serializer};
+
+/**
+ * A trait for list integers.
+ */
+pub trait ListInteger: Copy + Into<u64> + TryFrom<u64> {}
+
+impl<T> ListInteger for T where T: Copy + Into<u64> + TryFrom<u64> {}
+
+/**
+ * An integer list serializer.
+ *
+ * Serializes a list of integers, such as a posting list of document IDs, with the delta encoding
+ * and the variable-length integer encoding. Each element is stored as the difference from the
+ * preceding element, so that a list sorted in ascending order is serialized compactly.
+ * An unsorted list can also be serialized, but less compactly.
+ *
+ * When the argument `fe_escape` of the constructor is true, the bytes are escaped in the same way
+ * as `IntegerSerializer`.
+ *
+ * # Type Parameters
+ * * `Object` - An element type.
+ */
+#[derive(Debug)]
+pub struct IntegerListSerializer<Object: ListInteger> {
+    fe_escape: bool,
+    phantom: PhantomData<Object>,
+}
+
+impl<Object: ListInteger> Serializer for IntegerListSerializer<Object> {
+    type Object<'a> = Vec<Object>;
+
+    fn new(fe_escape: bool) -> Self {
+        IntegerListSerializer {
+            fe_escape,
+            phantom: PhantomData,
+        }
+    }
+
+    fn serialize(&self, object: &Self::Object<'_>) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(object.len());
+        let mut preceding = 0u64;
+        for element in object {
+            let element: u64 = (*element).into();
+            write_varint(&mut bytes, element.wrapping_sub(preceding));
+            preceding = element;
+        }
+        if self.fe_escape {
+            escape_fe(bytes)
+        } else {
+            bytes
+        }
+    }
+}
+
+/**
+ * An integer list deserializer.
+ *
+ * # Type Parameters
+ * * `Object` - An element type.
+ */
+#[derive(Debug)]
+pub struct IntegerListDeserializer<Object: ListInteger> {
+    fe_escape: bool,
+    phantom: PhantomData<Object>,
+}
+
+impl<Object: ListInteger> Deserializer for IntegerListDeserializer<Object> {
+    type Object = Vec<Object>;
+
+    fn new(fe_escape: bool) -> Self {
+        IntegerListDeserializer {
+            fe_escape,
+            phantom: PhantomData,
+        }
+    }
+
+    fn deserialize(&self, serialized: &[u8]) -> Result<Self::Object> {
+        let unescaped;
+        let mut bytes = if self.fe_escape {
+            unescaped = unescape_fe(serialized)?;
+            unescaped.as_slice()
+        } else {
+            serialized
+        };
+        let mut object = Vec::new();
+        let mut preceding = 0u64;
+        while !bytes.is_empty() {
+            let delta = read_varint(&mut bytes)?;
+            preceding = preceding.wrapping_add(delta);
+            let Ok(element) = Object::try_from(preceding) else {
+                return Err(IntegerDeserialationError::InvalidSerializedContent.into());
+            };
+            object.push(element);
+        }
+        Ok(object)
+    }
+}
+
+fn write_varint(bytes: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        bytes.push((value & 0x7F) as u8 | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+fn read_varint(bytes: &mut &[u8]) -> Result<u64> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let Some((&byte, rest)) = bytes.split_first() else {
+            return Err(IntegerDeserialationError::InvalidSerializedLength.into());
+        };
+        *bytes = rest;
+        value |= u64::from(byte & 0x7F) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(IntegerDeserialationError::InvalidSerializedContent.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::double_array::KEY_TERMINATOR;
+    use crate::error::TrieError;
+
+    use super::*;
+
+    #[test]
+    fn serialize() {
+        {
+            let serializer = IntegerListSerializer::<u32>::new(false);
+
+            assert!(serializer.serialize(&Vec::new()).is_empty());
+        }
+        {
+            let serializer = IntegerListSerializer::<u32>::new(false);
+
+            let serialized = serializer.serialize(&vec![3, 14, 159, 300]);
+            assert_eq!(serialized, vec![0x03, 0x0B, 0x91, 0x01, 0x8D, 0x01]);
+        }
+        {
+            let serializer = IntegerListSerializer::<u64>::new(true);
+
+            let serialized = serializer.serialize(&vec![0, 0xFD, 0xFE]);
+            assert_eq!(serialized, vec![0xFE, 0xFD, 0xFD, 0x01, 0x01]);
+            assert!(!serialized.contains(&KEY_TERMINATOR));
+        }
+    }
+
+    #[test]
+    fn deserialize() {
+        {
+            let deserializer = IntegerListDeserializer::<u32>::new(false);
+
+            let object = deserializer
+                .deserialize(&[0x03, 0x0B, 0x91, 0x01, 0x8D, 0x01])
+                .unwrap();
+            assert_eq!(object, vec![3, 14, 159, 300]);
+        }
+        {
+            let deserializer = IntegerListDeserializer::<u64>::new(true);
+
+            let object = deserializer
+                .deserialize(&[0xFE, 0xFD, 0xFD, 0x01, 0x01])
+                .unwrap();
+            assert_eq!(object, vec![0, 0xFD, 0xFE]);
+        }
+        {
+            let deserializer = IntegerListDeserializer::<u32>::new(false);
+
+            let result = deserializer.deserialize(&[0x03, 0x91]);
+            assert!(matches!(
+                result,
+                Err(TrieError::IntegerDeserialization(
+                    IntegerDeserialationError::InvalidSerializedLength
+                ))
+            ));
+        }
+        {
+            let deserializer = IntegerListDeserializer::<u32>::new(false);
+
+            let result = deserializer.deserialize(&[0x80, 0x80, 0x80, 0x80, 0x10]);
+            assert!(result.is_err());
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let serializer = IntegerListSerializer::<u32>::new(true);
+        let deserializer = IntegerListDeserializer::<u32>::new(true);
+
+        for object in [
+            vec![],
+            vec![u32::MAX],
+            vec![1, 2, 3, 1_000_000, u32::MAX],
+            vec![42, 24, 4242],
+        ] {
+            let serialized = serializer.serialize(&object);
+            assert_eq!(deserializer.deserialize(&serialized).unwrap(), object);
+        }
+    }
+}
@@ -7,8 +7,7 @@
 use std::marker::PhantomData;
 use std::ops;
 
-use anyhow::Result;
-
+use crate::error::Result;
 use crate::serializer::{
     DeserializationError, Deserializer, DeserializerOf, Serializer, SerializerOf,
 };
@@ -186,13 +185,13 @@ fn from_bytes_with_escape<Object: Integer<Object>>(serialized: &[u8]) -> Result<
         return Err(IntegerDeserialationError::InvalidSerializedLength.into());
     }
     let mut object = Object::from(0);
+    let mut byte_count = 0usize;
     let mut serialized_iter = serialized.iter();
     while let Some(byte) = serialized_iter.next() {
-        object <<= 8;
         if *byte == 0xFDu8 {
             if let Some(byte2) = serialized_iter.next() {
                 if *byte2 == 0xFDu8 || *byte2 == 0xFEu8 {
-                    object |= Object::from(*byte2);
+                    push_byte(&mut object, *byte2, &mut byte_count)?;
                 } else {
                     return Err(IntegerDeserialationError::InvalidSerializedContent.into());
                 }
@@ -200,26 +199,46 @@ fn from_bytes_with_escape<Object: Integer<Object>>(serialized: &[u8]) -> Result<
                 return Err(IntegerDeserialationError::InvalidSerializedContent.into());
             }
         } else if *byte == 0xFEu8 {
-            object |= Object::from(0x00u8);
+            push_byte(&mut object, 0x00u8, &mut byte_count)?;
         } else {
-            object |= Object::from(*byte);
+            push_byte(&mut object, *byte, &mut byte_count)?;
         }
     }
+    if byte_count < size_of::<Object>() {
+        return Err(IntegerDeserialationError::InvalidSerializedLength.into());
+    }
     Ok(object)
 }
 
 fn from_bytes_without_escape<Object: Integer<Object>>(serialized: &[u8]) -> Result<Object> {
-    if serialized.len() < size_of::<Object>() || 2 * size_of::<Object>() < serialized.len() {
+    if serialized.len() != size_of::<Object>() {
         return Err(IntegerDeserialationError::InvalidSerializedLength.into());
     }
     let mut object = Object::from(0);
+    let mut byte_count = 0usize;
     for byte in serialized {
-        object <<= 8;
-        object |= Object::from(*byte);
+        push_byte(&mut object, *byte, &mut byte_count)?;
     }
     Ok(object)
 }
 
+// The object is not shifted before the first byte, so that a one-byte object never overflows.
+fn push_byte<Object: Integer<Object>>(
+    object: &mut Object,
+    byte: u8,
+    byte_count: &mut usize,
+) -> Result<()> {
+    if *byte_count >= size_of::<Object>() {
+        return Err(IntegerDeserialationError::InvalidSerializedLength.into());
+    }
+    if *byte_count > 0 {
+        *object <<= 8;
+    }
+    *object |= Object::from(byte);
+    *byte_count += 1;
+    Ok(())
+}
+
 impl SerializerOf<u8> for () {
     type Type = IntegerSerializer<u8>;
 }
@@ -287,6 +306,7 @@ impl DeserializerOf<i128> for () {
 #[cfg(test)]
 mod tests {
     use crate::double_array::KEY_TERMINATOR;
+    use crate::error::TrieError;
 
     use super::*;
 
@@ -372,8 +392,10 @@ mod tests {
             let serialized = vec![0x00u8, 0x12u8, 0x34u8];
             assert!(if let Err(e) = deserializer.deserialize(&serialized) {
                 matches!(
-                    e.downcast_ref::<IntegerDeserialationError>(),
-                    Some(IntegerDeserialationError::InvalidSerializedLength)
+                    e,
+                    TrieError::IntegerDeserialization(
+                        IntegerDeserialationError::InvalidSerializedLength
+                    )
                 )
             } else {
                 false
@@ -385,8 +407,10 @@ mod tests {
             let serialized = vec![0x00u8, 0x12u8, 0x34u8];
             assert!(if let Err(e) = deserializer.deserialize(&serialized) {
                 matches!(
-                    e.downcast_ref::<IntegerDeserialationError>(),
-                    Some(IntegerDeserialationError::InvalidSerializedLength)
+                    e,
+                    TrieError::IntegerDeserialization(
+                        IntegerDeserialationError::InvalidSerializedLength
+                    )
                 )
             } else {
                 false
@@ -398,8 +422,10 @@ mod tests {
             let serialized = vec![0xFCu8, 0xFDu8, 0xFCu8, 0xFDu8, 0xFEu8, 0xFFu8];
             assert!(if let Err(e) = deserializer.deserialize(&serialized) {
                 matches!(
-                    e.downcast_ref::<IntegerDeserialationError>(),
-                    Some(IntegerDeserialationError::InvalidSerializedContent)
+                    e,
+                    TrieError::IntegerDeserialization(
+                        IntegerDeserialationError::InvalidSerializedContent
+                    )
                 )
             } else {
                 false
@@ -411,12 +437,44 @@ mod tests {
             let serialized = vec![0xFCu8, 0xFDu8, 0xFDu8, 0xFDu8, 0xFEu8, 0xFDu8];
             assert!(if let Err(e) = deserializer.deserialize(&serialized) {
                 matches!(
-                    e.downcast_ref::<IntegerDeserialationError>(),
-                    Some(IntegerDeserialationError::InvalidSerializedContent)
+                    e,
+                    TrieError::IntegerDeserialization(
+                        IntegerDeserialationError::InvalidSerializedContent
+                    )
                 )
             } else {
                 false
             });
         }
+        {
+            let deserializer = <() as DeserializerOf<u8>>::Type::new(false);
+
+            assert_eq!(deserializer.deserialize(&[0xABu8]).unwrap(), 0xAB);
+            assert!(matches!(
+                deserializer.deserialize(&[0xABu8, 0xCDu8]),
+                Err(TrieError::IntegerDeserialization(
+                    IntegerDeserialationError::InvalidSerializedLength
+                ))
+            ));
+        }
+        {
+            let deserializer = <() as DeserializerOf<u8>>::Type::new(true);
+
+            assert_eq!(deserializer.deserialize(&[0xFEu8]).unwrap(), 0x00);
+            assert_eq!(deserializer.deserialize(&[0xFDu8, 0xFDu8]).unwrap(), 0xFD);
+            assert!(deserializer.deserialize(&[0xABu8, 0xCDu8]).is_err());
+        }
+        {
+            let deserializer = <() as DeserializerOf<u32>>::Type::new(false);
+
+            let serialized = vec![0x00u8, 0x12u8, 0x34u8, 0xABu8, 0xCDu8];
+            assert!(deserializer.deserialize(&serialized).is_err());
+        }
+        {
+            let deserializer = <() as DeserializerOf<u32>>::Type::new(true);
+
+            let serialized = vec![0xFDu8, 0xFDu8, 0xFDu8, 0xFDu8];
+            assert!(deserializer.deserialize(&serialized).is_err());
+        }
     }
 }
@@ -0,0 +1,320 @@
+/*!
+ * A key metadata table.
+ *
+ * Copyright (C) 2023-2025 kaoru  <https://www.tetengo.org/>
+ */
+
+use std::io::{Read, Seek};
+
+use crate::error::Result;
+use crate::section_table::SectionTable;
+use crate::storage::SerializationSection;
+
+/**
+ * A key metadata error.
+ */
+#[derive(Clone, Copy, Debug, thiserror::Error)]
+pub enum KeyMetadataError {
+    /**
+     * The record size is invalid.
+     */
+    #[error("the record size is invalid")]
+    InvalidRecordSize,
+
+    /**
+     * The serialized content is invalid.
+     */
+    #[error("the serialized content is invalid")]
+    InvalidSerializedContent,
+}
+
+/**
+ * A key metadata table.
+ *
+ * It holds a small fixed-size record per key, such as flags or class IDs, indexed by the value
+ * indices of a trie. It is serialized into its own section, so that the records can be
+ * retrieved without deserializing the value objects.
+ *
+ * A record not set is filled with zeros.
+ */
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct KeyMetadata {
+    record_size: usize,
+    records: Vec<u8>,
+}
+
+impl KeyMetadata {
+    /**
+     * Creates a key metadata table.
+     *
+     * # Arguments
+     * * `record_size` - A record size.
+     *
+     * # Errors
+     * * When the record size is 0 or too large.
+     */
+    pub fn new(record_size: usize) -> Result<Self> {
+        if record_size == 0 || record_size > u32::MAX as usize {
+            return Err(KeyMetadataError::InvalidRecordSize.into());
+        }
+        Ok(Self {
+            record_size,
+            records: Vec::new(),
+        })
+    }
+
+    /**
+     * Creates a key metadata table with bytes.
+     *
+     * # Arguments
+     * * `bytes` - Bytes serialized by `KeyMetadata::serialize()`.
+     *
+     * # Errors
+     * * When the bytes are broken.
+     */
+    pub fn new_with_bytes(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() < size_of::<u32>() {
+            return Err(KeyMetadataError::InvalidSerializedContent.into());
+        }
+        let (size_bytes, records) = bytes.split_at(size_of::<u32>());
+        let mut record_size = [0u8; size_of::<u32>()];
+        record_size.copy_from_slice(size_bytes);
+        let record_size = u32::from_be_bytes(record_size) as usize;
+        if record_size == 0 || records.len() % record_size != 0 {
+            return Err(KeyMetadataError::InvalidSerializedContent.into());
+        }
+        Ok(Self {
+            record_size,
+            records: records.to_vec(),
+        })
+    }
+
+    /**
+     * Creates a key metadata table with a reader of a sectioned serialization.
+     *
+     * Only the key metadata section is read.
+     *
+     * # Arguments
+     * * `reader`        - The reader from which the section table is read.
+     * * `section_table` - A section table.
+     *
+     * # Returns
+     * The key metadata table. Or None when there is no key metadata section.
+     *
+     * # Errors
+     * * When it fails to read the section.
+     * * When the section is broken.
+     */
+    pub fn new_with_section<R: Read + Seek>(
+        reader: &mut R,
+        section_table: &SectionTable,
+    ) -> Result<Option<Self>> {
+        let Some(bytes) = section_table.read_section(reader, SerializationSection::KeyMetadata)?
+        else {
+            return Ok(None);
+        };
+        Ok(Some(Self::new_with_bytes(&bytes)?))
+    }
+
+    /**
+     * Returns the record size.
+     *
+     * # Returns
+     * The record size.
+     */
+    pub const fn record_size(&self) -> usize {
+        self.record_size
+    }
+
+    /**
+     * Returns the record count.
+     *
+     * # Returns
+     * The record count.
+     */
+    pub fn record_count(&self) -> usize {
+        self.records.len() / self.record_size
+    }
+
+    /**
+     * Returns the record.
+     *
+     * # Arguments
+     * * `value_index` - A value index.
+     *
+     * # Returns
+     * The record. Or None when the value index is out of the table.
+     */
+    pub fn record(&self, value_index: usize) -> Option<&[u8]> {
+        let offset = value_index.checked_mul(self.record_size)?;
+        self.records
+            .get(offset..offset.checked_add(self.record_size)?)
+    }
+
+    /**
+     * Sets a record.
+     *
+     * # Arguments
+     * * `value_index` - A value index.
+     * * `record`      - A record.
+     *
+     * # Errors
+     * * When the size of the record is not the record size.
+     */
+    pub fn set_record(&mut self, value_index: usize, record: &[u8]) -> Result<()> {
+        if record.len() != self.record_size {
+            return Err(KeyMetadataError::InvalidRecordSize.into());
+        }
+        let offset = value_index * self.record_size;
+        if self.records.len() < offset + self.record_size {
+            self.records.resize(offset + self.record_size, 0);
+        }
+        self.records[offset..offset + self.record_size].copy_from_slice(record);
+        Ok(())
+    }
+
+    /**
+     * Serializes this table.
+     *
+     * The bytes are the content of the key metadata section.
+     *
+     * # Returns
+     * The serialized bytes.
+     */
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(size_of::<u32>() + self.records.len());
+        bytes.extend_from_slice(&(self.record_size as u32).to_be_bytes());
+        bytes.extend_from_slice(&self.records);
+        bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "builder")]
+    use std::io::{Cursor, SeekFrom};
+
+    use crate::error::TrieError;
+    #[cfg(feature = "builder")]
+    use crate::integer_serializer::IntegerSerializer;
+    #[cfg(feature = "builder")]
+    use crate::memory_storage::MemoryStorage;
+    #[cfg(feature = "builder")]
+    use crate::serializer::Serializer;
+    #[cfg(feature = "builder")]
+    use crate::string_serializer::StrSerializer;
+    #[cfg(feature = "builder")]
+    use crate::trie::Trie;
+    #[cfg(feature = "builder")]
+    use crate::value_serializer::ValueSerializer;
+
+    use super::*;
+
+    #[test]
+    fn new() {
+        assert!(KeyMetadata::new(2).is_ok());
+        assert!(matches!(
+            KeyMetadata::new(0),
+            Err(TrieError::KeyMetadata(KeyMetadataError::InvalidRecordSize))
+        ));
+    }
+
+    #[test]
+    fn new_with_bytes() {
+        {
+            let metadata =
+                KeyMetadata::new_with_bytes(&[0x00, 0x00, 0x00, 0x02, 0x12, 0x34, 0x56, 0x78])
+                    .unwrap();
+
+            assert_eq!(metadata.record_size(), 2);
+            assert_eq!(metadata.record_count(), 2);
+            assert_eq!(metadata.record(1), Some([0x56, 0x78].as_slice()));
+        }
+        {
+            assert!(KeyMetadata::new_with_bytes(&[0x00, 0x00]).is_err());
+            assert!(KeyMetadata::new_with_bytes(&[0x00, 0x00, 0x00, 0x02, 0x12]).is_err());
+            assert!(KeyMetadata::new_with_bytes(&[0x00, 0x00, 0x00, 0x00]).is_err());
+        }
+    }
+
+    #[test]
+    fn record() {
+        let mut metadata = KeyMetadata::new(2).unwrap();
+        metadata.set_record(2, &[0x12, 0x34]).unwrap();
+
+        assert_eq!(metadata.record_count(), 3);
+        assert_eq!(metadata.record(0), Some([0x00, 0x00].as_slice()));
+        assert_eq!(metadata.record(2), Some([0x12, 0x34].as_slice()));
+        assert!(metadata.record(3).is_none());
+        assert!(metadata.set_record(0, &[0x12]).is_err());
+    }
+
+    #[test]
+    fn serialize() {
+        let mut metadata = KeyMetadata::new(1).unwrap();
+        metadata.set_record(1, &[0x2A]).unwrap();
+
+        let serialized = metadata.serialize();
+        assert_eq!(serialized, vec![0x00, 0x00, 0x00, 0x01, 0x00, 0x2A]);
+        assert_eq!(KeyMetadata::new_with_bytes(&serialized).unwrap(), metadata);
+    }
+
+    #[cfg(feature = "builder")]
+    #[test]
+    fn new_with_section() {
+        let elements = [("kumamoto", 42), ("tamana", 24), ("uto", 55)];
+        let trie = Trie::<&str, i32>::builder()
+            .elements(elements.to_vec())
+            .key_serializer(StrSerializer::new(true))
+            .build()
+            .unwrap();
+        let mut metadata = KeyMetadata::new(1).unwrap();
+        for (value_index, (key, _)) in elements.iter().enumerate() {
+            metadata
+                .set_record(value_index, &[key.len() as u8])
+                .unwrap();
+        }
+
+        let mut writer = Cursor::new(Vec::new());
+        let storage = trie
+            .storage()
+            .as_any()
+            .downcast_ref::<MemoryStorage<i32>>()
+            .unwrap();
+        let mut value_serializer = ValueSerializer::new(
+            Box::new(|value: &i32| IntegerSerializer::new(false).serialize(value)),
+            size_of::<i32>(),
+        );
+        let serialized_metadata = metadata.serialize();
+        storage
+            .serialize_sections(
+                &mut writer,
+                &mut value_serializer,
+                &[(SerializationSection::KeyMetadata, &serialized_metadata)],
+            )
+            .unwrap();
+
+        let mut reader = Cursor::new(writer.into_inner());
+        let section_table = SectionTable::new_with_reader(&mut reader).unwrap();
+        let loaded = KeyMetadata::new_with_section(&mut reader, &section_table)
+            .unwrap()
+            .unwrap();
+
+        let _ = reader.seek(SeekFrom::Start(0)).unwrap();
+        let loaded_trie = Trie::<&str, i32>::builder_with_storage(Box::new(
+            MemoryStorage::new_with_sections(&mut reader, None).unwrap(),
+        ))
+        .key_serializer(StrSerializer::new(true))
+        .build()
+        .unwrap();
+        assert_eq!(
+            loaded_trie.key_metadata(&"tamana", &loaded).unwrap(),
+            Some([6u8].as_slice())
+        );
+        assert!(loaded_trie
+            .key_metadata(&"kuma", &loaded)
+            .unwrap()
+            .is_none());
+        assert!(loaded_trie.find(&"tamana").unwrap().is_none());
+    }
+}
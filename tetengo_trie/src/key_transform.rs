@@ -0,0 +1,222 @@
+/*!
+ * A key transform.
+ *
+ * Copyright (C) 2023-2025 kaoru  <https://www.tetengo.org/>
+ */
+
+use std::fmt::{self, Debug, Formatter};
+use std::io::{Read, Seek};
+use std::sync::Arc;
+
+use crate::error::Result;
+use crate::section_table::SectionTable;
+use crate::storage::SerializationSection;
+
+/**
+ * A key transform error.
+ */
+#[derive(Clone, Copy, Debug, thiserror::Error)]
+pub enum KeyTransformError {
+    /**
+     * The key transform does not match the one used to build the trie.
+     */
+    #[error("the key transform does not match the one used to build the trie")]
+    Mismatch,
+
+    /**
+     * The serialized content is invalid.
+     */
+    #[error("the serialized content is invalid")]
+    InvalidSerializedContent,
+}
+
+type Transform = Arc<dyn Fn(&[u8]) -> Vec<u8> + Send + Sync>;
+
+/**
+ * A key transform.
+ *
+ * It converts a serialized key into another one, such as a normalized, romanized or lowercased
+ * one. A trie applies it to the keys identically both when building and when looking up.
+ *
+ * The transform is named with an ID. The ID is stored in the key transform section of a
+ * sectioned serialization, so that a mismatched transform is detected on loading.
+ */
+#[derive(Clone)]
+pub struct KeyTransform {
+    id: String,
+    transform: Transform,
+}
+
+impl Debug for KeyTransform {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KeyTransform")
+            .field("id", &self.id)
+            .field("transform", &"Arc<dyn Fn(&[u8]) -> Vec<u8> + Send + Sync>")
+            .finish()
+    }
+}
+
+impl KeyTransform {
+    /**
+     * Creates a key transform.
+     *
+     * # Arguments
+     * * `id`        - An ID.
+     * * `transform` - A function which transforms a serialized key.
+     */
+    pub fn new(id: &str, transform: impl Fn(&[u8]) -> Vec<u8> + Send + Sync + 'static) -> Self {
+        Self {
+            id: String::from(id),
+            transform: Arc::new(transform),
+        }
+    }
+
+    /**
+     * Returns the ID.
+     *
+     * # Returns
+     * The ID.
+     */
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /**
+     * Transforms a serialized key.
+     *
+     * # Arguments
+     * * `serialized_key` - A serialized key.
+     *
+     * # Returns
+     * The transformed serialized key.
+     */
+    pub fn transform(&self, serialized_key: &[u8]) -> Vec<u8> {
+        (self.transform)(serialized_key)
+    }
+
+    /**
+     * Serializes the ID.
+     *
+     * The bytes are the content of the key transform section.
+     *
+     * # Returns
+     * The serialized bytes.
+     */
+    pub fn serialize(&self) -> Vec<u8> {
+        self.id.as_bytes().to_vec()
+    }
+
+    /**
+     * Verifies a key transform against a sectioned serialization.
+     *
+     * The serialization without the key transform section is built without any key transform.
+     *
+     * # Arguments
+     * * `key_transform` - A key transform. Or None when no key transform is used.
+     * * `reader`        - The reader from which the section table is read.
+     * * `section_table` - A section table.
+     *
+     * # Errors
+     * * When the key transform does not match the one used to build the trie.
+     * * When it fails to read the section.
+     * * When the section is broken.
+     */
+    pub fn verify<R: Read + Seek>(
+        key_transform: Option<&Self>,
+        reader: &mut R,
+        section_table: &SectionTable,
+    ) -> Result<()> {
+        Self::verify_id(
+            key_transform,
+            Self::read_id(reader, section_table)?.as_deref(),
+        )
+    }
+
+    pub(crate) fn read_id<R: Read + Seek>(
+        reader: &mut R,
+        section_table: &SectionTable,
+    ) -> Result<Option<String>> {
+        let id = section_table
+            .read_section(reader, SerializationSection::KeyTransform)?
+            .map(String::from_utf8)
+            .transpose()
+            .map_err(|_| KeyTransformError::InvalidSerializedContent)?;
+        Ok(id)
+    }
+
+    pub(crate) fn verify_id(key_transform: Option<&Self>, id: Option<&str>) -> Result<()> {
+        if id != key_transform.map(Self::id) {
+            return Err(KeyTransformError::Mismatch.into());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "builder")]
+    use std::io::Cursor;
+
+    #[cfg(feature = "builder")]
+    use crate::error::TrieError;
+
+    use super::*;
+
+    fn lowercase() -> KeyTransform {
+        KeyTransform::new("lowercase", <[u8]>::to_ascii_lowercase)
+    }
+
+    #[cfg(feature = "builder")]
+    fn section_table_of(sections: &[(SerializationSection, &[u8])]) -> Cursor<Vec<u8>> {
+        let mut writer = Cursor::new(Vec::new());
+        SectionTable::write(&mut writer, sections).unwrap();
+        Cursor::new(writer.into_inner())
+    }
+
+    #[test]
+    fn transform() {
+        let key_transform = lowercase();
+
+        assert_eq!(key_transform.id(), "lowercase");
+        assert_eq!(key_transform.transform(b"KumaMoto"), b"kumamoto");
+        assert_eq!(key_transform.serialize(), b"lowercase");
+    }
+
+    #[cfg(feature = "builder")]
+    #[test]
+    fn verify() {
+        let key_transform = lowercase();
+        {
+            let mut reader = section_table_of(&[(
+                SerializationSection::KeyTransform,
+                &key_transform.serialize(),
+            )]);
+            let section_table = SectionTable::new_with_reader(&mut reader).unwrap();
+
+            assert!(
+                KeyTransform::verify(Some(&key_transform), &mut reader, &section_table).is_ok()
+            );
+            assert!(matches!(
+                KeyTransform::verify(None, &mut reader, &section_table),
+                Err(TrieError::KeyTransform(KeyTransformError::Mismatch))
+            ));
+            assert!(matches!(
+                KeyTransform::verify(
+                    Some(&KeyTransform::new("uppercase", <[u8]>::to_ascii_uppercase)),
+                    &mut reader,
+                    &section_table
+                ),
+                Err(TrieError::KeyTransform(KeyTransformError::Mismatch))
+            ));
+        }
+        {
+            let mut reader = section_table_of(&[(SerializationSection::Auxiliary(42), &[0x2A])]);
+            let section_table = SectionTable::new_with_reader(&mut reader).unwrap();
+
+            assert!(KeyTransform::verify(None, &mut reader, &section_table).is_ok());
+            assert!(
+                KeyTransform::verify(Some(&key_transform), &mut reader, &section_table).is_err()
+            );
+        }
+    }
+}
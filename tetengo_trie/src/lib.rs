@@ -1,35 +1,84 @@
 #![doc = include_str!("../README.md")]
-#![doc = "# Examples"]
-#![doc = "```rust"]
-#![doc = include_str!("../tests/usage.rs")]
-#![doc = "```"]
+#![cfg_attr(feature = "builder", doc = "# Examples")]
+#![cfg_attr(feature = "builder", doc = "```rust")]
+#![cfg_attr(feature = "builder", doc = include_str!("../examples/usage.rs"))]
+#![cfg_attr(feature = "builder", doc = "```")]
 
+pub mod automaton;
+#[cfg(feature = "uniffi")]
+pub mod bindings;
+pub mod bloom_filter;
+pub mod error;
 pub mod file_mapping;
+pub mod fixed_size_serializer;
+pub mod integer_list_serializer;
 pub mod integer_serializer;
+pub mod key_metadata;
+pub mod key_transform;
+pub mod locality_stats;
 pub mod memory_storage;
 pub mod mmap_storage;
+pub mod multi_trie;
+pub mod prefix_group_iterator;
+pub mod query_stats;
+pub mod random_access_storage;
+pub mod section_table;
 pub mod serializer;
+pub mod shared_memory;
 pub mod shared_storage;
 pub mod storage;
 pub mod string_serializer;
+pub mod subtrie_view;
+pub mod suffix_trie;
+#[cfg(feature = "test-util")]
+pub mod test_util;
 pub mod trie;
 pub mod trie_iterator;
 pub mod value_serializer;
+pub mod versioned_trie;
 
 mod double_array;
+#[cfg(feature = "builder")]
 mod double_array_builder;
 mod double_array_iterator;
 
+pub use automaton::Automaton;
+pub use bloom_filter::{BloomFilter, BloomFilterError};
+pub use double_array::DoubleArrayError;
+pub use error::{Result, TrieError};
 pub use file_mapping::{FileMapping, FileMappingError};
-pub use integer_serializer::{IntegerDeserializer, IntegerSerializer};
-pub use memory_storage::MemoryStorage;
+pub use fixed_size_serializer::{FixedSize, FixedSizeDeserializer, FixedSizeSerializer};
+pub use integer_list_serializer::{IntegerListDeserializer, IntegerListSerializer, ListInteger};
+pub use integer_serializer::{IntegerDeserialationError, IntegerDeserializer, IntegerSerializer};
+pub use key_metadata::{KeyMetadata, KeyMetadataError};
+pub use key_transform::{KeyTransform, KeyTransformError};
+pub use locality_stats::LocalityStats;
+pub use memory_storage::{GrowthPolicy, MemoryStorage, MemoryStorageError, ValueArrayLayout};
 pub use mmap_storage::{MmapStorage, MmapStorageError};
+pub use multi_trie::{MultiTrie, MultiTrieError};
+pub use prefix_group_iterator::PrefixGroupIterator;
+pub use query_stats::{QueryStats, QueryStatsSnapshot};
+pub use random_access_storage::{RandomAccess, RandomAccessStorage, RandomAccessStorageError};
+pub use section_table::{SectionTable, SectionTableError};
 pub use serializer::{
     DeserializationError, Deserializer, DeserializerOf, Serializer, SerializerOf,
 };
+pub use shared_memory::{SharedMemory, SharedMemoryError};
 pub use shared_storage::SharedStorage;
-pub use storage::{Storage, StorageError};
+#[cfg(feature = "builder")]
+pub use storage::SerializationProgress;
+pub use storage::{SerializationSection, Storage, StorageError};
 pub use string_serializer::{StrSerializer, StringDeserializer, StringSerializer};
-pub use trie::{BuldingObserverSet, Trie};
-pub use trie_iterator::TrieIterator;
-pub use value_serializer::{ValueDeserializer, ValueSerializer};
+pub use subtrie_view::SubtrieView;
+pub use suffix_trie::SuffixTrie;
+#[cfg(feature = "builder")]
+pub use trie::{BuildError, BuldingObserverSet, RejectionReason};
+pub use trie::{KeyLengthError, Trie, TrieVerificationError};
+pub use trie_iterator::{TrieIterator, TrieKeyValueIterator};
+#[cfg(feature = "compression")]
+pub use value_serializer::CompressionError;
+pub use value_serializer::{BorrowingValueDeserializer, ValueDeserializer, ValueSerializer};
+pub use versioned_trie::{TrieSnapshot, VersionedTrie};
+
+#[cfg(feature = "uniffi")]
+uniffi::setup_scaffolding!();
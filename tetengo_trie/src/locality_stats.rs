@@ -0,0 +1,86 @@
+/*!
+ * A locality stats.
+ *
+ * Copyright (C) 2023-2025 kaoru  <https://www.tetengo.org/>
+ */
+
+/**
+ * A locality stats.
+ *
+ * It measures how closely the shallow nodes of a trie are placed in the double array. The
+ * smaller the span and the average distance are, the fewer pages a shallow prefix query
+ * touches.
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LocalityStats {
+    depth: usize,
+    node_count: usize,
+    span: usize,
+    average_distance: f64,
+}
+
+impl LocalityStats {
+    /**
+     * Creates a locality stats.
+     *
+     * # Arguments
+     * * `depth`            - The depth.
+     * * `node_count`       - The number of the nodes shallower than the depth.
+     * * `span`             - The span of the indices of the nodes.
+     * * `average_distance` - The average distance between the indices of the nodes and their
+     *   children.
+     */
+    pub const fn new(depth: usize, node_count: usize, span: usize, average_distance: f64) -> Self {
+        Self {
+            depth,
+            node_count,
+            span,
+            average_distance,
+        }
+    }
+
+    /**
+     * Returns the depth.
+     *
+     * # Returns
+     * The depth.
+     */
+    pub const fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /**
+     * Returns the number of the nodes shallower than the depth.
+     *
+     * The root is counted, and the key terminators are not.
+     *
+     * # Returns
+     * The number of the nodes.
+     */
+    pub const fn node_count(&self) -> usize {
+        self.node_count
+    }
+
+    /**
+     * Returns the span of the indices of the nodes.
+     *
+     * It is the number of the double array elements from the lowest index to the highest one
+     * among the nodes shallower than the depth and their children.
+     *
+     * # Returns
+     * The span.
+     */
+    pub const fn span(&self) -> usize {
+        self.span
+    }
+
+    /**
+     * Returns the average distance between the indices of the nodes and their children.
+     *
+     * # Returns
+     * The average distance. Or 0.0 when there is no child.
+     */
+    pub const fn average_distance(&self) -> f64 {
+        self.average_distance
+    }
+}
@@ -6,21 +6,250 @@
 
 use std::any::Any;
 use std::cell::RefCell;
+use std::collections::BTreeMap;
 use std::fmt::Debug;
-use std::io::{Read, Write};
+#[cfg(feature = "builder")]
+use std::io::Write;
+use std::io::{self, Cursor, ErrorKind, Read, Seek};
 use std::rc::Rc;
 use std::sync::LazyLock;
 
-use anyhow::Result;
-
 use crate::double_array::VACANT_CHECK_VALUE;
-use crate::integer_serializer::{IntegerDeserializer, IntegerSerializer};
-use crate::serializer::{Deserializer, Serializer};
-use crate::storage::Storage;
-use crate::value_serializer::{ValueDeserializer, ValueSerializer};
+use crate::error::Result;
+use crate::integer_serializer::IntegerDeserializer;
+#[cfg(feature = "builder")]
+use crate::integer_serializer::IntegerSerializer;
+use crate::key_transform::KeyTransform;
+use crate::section_table::{SectionTable, SectionTableError};
+use crate::serializer::Deserializer;
+#[cfg(feature = "builder")]
+use crate::serializer::Serializer;
+#[cfg(feature = "builder")]
+use crate::storage::{ByteCountingWriter, SerializationProgress};
+use crate::storage::{SerializationSection, Storage, StorageError};
+use crate::value_serializer::ValueDeserializer;
+#[cfg(feature = "builder")]
+use crate::value_serializer::ValueSerializer;
 
 type ValueArrayElement<Value> = Option<Rc<Value>>;
 
+/**
+ * A growth policy of the base-check array.
+ *
+ * The base-check array grows when an index out of it is touched.
+ */
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum GrowthPolicy {
+    /// Grows just to the touched index.
+    #[default]
+    Exact,
+
+    /// Grows to at least twice the current size.
+    Exponential,
+
+    /// Grows to a multiple of the chunk size.
+    Chunked(usize),
+}
+
+impl GrowthPolicy {
+    fn grown_size(&self, current_size: usize, required_size: usize) -> usize {
+        match self {
+            GrowthPolicy::Exact => required_size,
+            GrowthPolicy::Exponential => required_size.max(current_size.saturating_mul(2)),
+            GrowthPolicy::Chunked(chunk_size) => {
+                let chunk_size = (*chunk_size).max(1);
+                required_size
+                    .div_ceil(chunk_size)
+                    .saturating_mul(chunk_size)
+            }
+        }
+    }
+}
+
+// The base-check array is held in blocks, so that a huge one needs no huge contiguous
+// allocation. Without a block size, it is held in one block.
+#[derive(Clone, Debug)]
+struct BaseCheckArray {
+    blocks: Vec<Vec<u32>>,
+    block_size: usize,
+    len: usize,
+}
+
+impl BaseCheckArray {
+    fn new(elements: Vec<u32>) -> Self {
+        let len = elements.len();
+        Self {
+            blocks: vec![elements],
+            block_size: usize::MAX,
+            len,
+        }
+    }
+
+    fn with_block_size(self, block_size: usize) -> Self {
+        let mut rechunked = Self {
+            blocks: Vec::new(),
+            block_size: block_size.max(1),
+            len: 0,
+        };
+        for element in self.iter() {
+            rechunked.push(element);
+        }
+        rechunked
+    }
+
+    const fn len(&self) -> usize {
+        self.len
+    }
+
+    fn get(&self, index: usize) -> u32 {
+        self.blocks[index / self.block_size][index % self.block_size]
+    }
+
+    fn get_mut(&mut self, index: usize) -> &mut u32 {
+        &mut self.blocks[index / self.block_size][index % self.block_size]
+    }
+
+    fn iter(&self) -> impl Iterator<Item = u32> + '_ {
+        self.blocks.iter().flatten().copied()
+    }
+
+    fn push(&mut self, element: u32) {
+        match self.blocks.last_mut() {
+            Some(block) if block.len() < self.block_size => block.push(element),
+            _ => {
+                let mut block = Vec::with_capacity(self.block_size.min(Self::INITIAL_CAPACITY));
+                block.push(element);
+                self.blocks.push(block);
+            }
+        }
+        self.len += 1;
+    }
+
+    fn resize(&mut self, size: usize, element: u32) {
+        while self.len < size {
+            match self.blocks.last_mut() {
+                Some(block) if block.len() < self.block_size => {
+                    let block_len = block.len();
+                    let grown_len = block_len + (size - self.len).min(self.block_size - block_len);
+                    block.resize(grown_len, element);
+                    self.len += grown_len - block_len;
+                }
+                _ => self.blocks.push(Vec::new()),
+            }
+        }
+    }
+
+    const INITIAL_CAPACITY: usize = 1024;
+}
+
+impl Default for BaseCheckArray {
+    fn default() -> Self {
+        Self::new(Vec::new())
+    }
+}
+
+/**
+ * A layout of the value array.
+ */
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum ValueArrayLayout {
+    /// Holds a slot for each value index, whether a value is added there or not.
+    #[default]
+    Dense,
+
+    /// Holds only the added values with their indices.
+    Sparse,
+}
+
+#[derive(Clone, Debug)]
+enum ValueArray<Value> {
+    Dense(Vec<ValueArrayElement<Value>>),
+    Sparse {
+        values: BTreeMap<usize, Rc<Value>>,
+        len: usize,
+    },
+}
+
+impl<Value> ValueArray<Value> {
+    fn with_layout(self, layout: ValueArrayLayout) -> Self {
+        match (self, layout) {
+            (Self::Dense(elements), ValueArrayLayout::Sparse) => Self::Sparse {
+                len: elements.len(),
+                values: elements
+                    .into_iter()
+                    .enumerate()
+                    .filter_map(|(i, element)| element.map(|value| (i, value)))
+                    .collect(),
+            },
+            (Self::Sparse { values, len }, ValueArrayLayout::Dense) => {
+                let mut elements = vec![None; len];
+                for (i, value) in values {
+                    elements[i] = Some(value);
+                }
+                Self::Dense(elements)
+            }
+            (value_array, _) => value_array,
+        }
+    }
+
+    fn len(&self) -> usize {
+        match self {
+            Self::Dense(elements) => elements.len(),
+            Self::Sparse { len, .. } => *len,
+        }
+    }
+
+    fn get(&self, index: usize) -> ValueArrayElement<Value> {
+        match self {
+            Self::Dense(elements) => elements.get(index).cloned().flatten(),
+            Self::Sparse { values, .. } => values.get(&index).cloned(),
+        }
+    }
+
+    fn set(&mut self, index: usize, value: Rc<Value>) {
+        match self {
+            Self::Dense(elements) => {
+                if index >= elements.len() {
+                    elements.resize_with(index + 1, || None);
+                }
+                elements[index] = Some(value);
+            }
+            Self::Sparse { values, len } => {
+                let _inserted = values.insert(index, value);
+                *len = (*len).max(index + 1);
+            }
+        }
+    }
+
+    #[cfg(feature = "builder")]
+    fn iter(&self) -> Box<dyn Iterator<Item = Option<&Rc<Value>>> + '_> {
+        match self {
+            Self::Dense(elements) => Box::new(elements.iter().map(Option::as_ref)),
+            Self::Sparse { values, len } => Box::new((0..*len).map(|i| values.get(&i))),
+        }
+    }
+}
+
+impl<Value> Default for ValueArray<Value> {
+    fn default() -> Self {
+        Self::Dense(Vec::new())
+    }
+}
+
+/**
+ * A memory storage error.
+ */
+#[derive(Clone, Copy, Debug, thiserror::Error)]
+pub enum MemoryStorageError {
+    /**
+     * The base-check size exceeds the maximum.
+     */
+    #[error("the base-check size exceeds the maximum")]
+    BaseCheckSizeExceeded,
+}
+
+impl StorageError for MemoryStorageError {}
+
 /**
  * A memory storage.
  *
@@ -29,8 +258,13 @@ type ValueArrayElement<Value> = Option<Rc<Value>>;
  */
 #[derive(Debug, Default)]
 pub struct MemoryStorage<Value: Clone> {
-    base_check_array: RefCell<Vec<u32>>,
-    value_array: Vec<ValueArrayElement<Value>>,
+    base_check_array: RefCell<BaseCheckArray>,
+    value_array: ValueArray<Value>,
+    growth_policy: GrowthPolicy,
+    max_base_check_size: Option<usize>,
+    // The ID of the key transform recorded in the sectioned serialization. None when the storage
+    // is not loaded from a sectioned serialization.
+    key_transform_id: Option<Option<String>>,
 }
 
 impl<Value: Clone + 'static> MemoryStorage<Value> {
@@ -39,8 +273,11 @@ impl<Value: Clone + 'static> MemoryStorage<Value> {
      */
     pub fn new() -> Self {
         Self {
-            base_check_array: RefCell::new(vec![VACANT_CHECK_VALUE as u32]),
-            value_array: Vec::new(),
+            base_check_array: RefCell::new(BaseCheckArray::new(vec![VACANT_CHECK_VALUE as u32])),
+            value_array: ValueArray::default(),
+            growth_policy: GrowthPolicy::default(),
+            max_base_check_size: None,
+            key_transform_id: None,
         }
     }
 
@@ -60,24 +297,221 @@ impl<Value: Clone + 'static> MemoryStorage<Value> {
     ) -> Result<Self> {
         let (base_check_array, value_array) = Self::deserialize(reader, value_deserializer)?;
         Ok(Self {
-            base_check_array: RefCell::new(base_check_array),
-            value_array,
+            base_check_array: RefCell::new(BaseCheckArray::new(base_check_array)),
+            value_array: ValueArray::Dense(value_array),
+            growth_policy: GrowthPolicy::default(),
+            max_base_check_size: None,
+            key_transform_id: None,
         })
     }
 
-    fn serialize_base_check_array(writer: &mut dyn Write, base_check_array: &[u32]) -> Result<()> {
+    /**
+     * Creates a memory storage with a reader of a sectioned serialization.
+     *
+     * The value array section is loaded only when a value deserializer is given. Without it, the
+     * storage can still tell whether the keys exist, but has no value objects.
+     *
+     * # Arguments
+     * * `reader`             - A reader positioned at the section table.
+     * * `value_deserializer` - A deserializer for value objects. Or None not to load the values.
+     *
+     * # Errors
+     * * When a required section is missing.
+     * * When it fails to read the sections.
+     */
+    pub fn new_with_sections<R: Read + Seek>(
+        reader: &mut R,
+        value_deserializer: Option<&mut ValueDeserializer<Value>>,
+    ) -> Result<Self> {
+        let section_table = SectionTable::new_with_reader(reader)?;
+
+        let Some(base_check_section) =
+            section_table.read_section(reader, SerializationSection::BaseCheckArray)?
+        else {
+            return Err(SectionTableError::MissingSection.into());
+        };
+        let base_check_array =
+            Self::deserialize_base_check_array(&mut Cursor::new(base_check_section))?;
+
+        let value_array = if let Some(value_deserializer) = value_deserializer {
+            let Some(value_section) =
+                section_table.read_section(reader, SerializationSection::ValueArray)?
+            else {
+                return Err(SectionTableError::MissingSection.into());
+            };
+            Self::deserialize_value_array(&mut Cursor::new(value_section), value_deserializer)?
+        } else {
+            Vec::new()
+        };
+        let key_transform_id = KeyTransform::read_id(reader, &section_table)?;
+
+        Ok(Self {
+            base_check_array: RefCell::new(BaseCheckArray::new(base_check_array)),
+            value_array: ValueArray::Dense(value_array),
+            growth_policy: GrowthPolicy::default(),
+            max_base_check_size: None,
+            key_transform_id: Some(key_transform_id),
+        })
+    }
+
+    /**
+     * Serializes this storage into sections.
+     *
+     * The base-check array section and the value array section are written adjacently in this
+     * order, followed by the auxiliary sections. The content from the start of the base-check
+     * array section is the same as the one by `Storage::serialize()`, so that an mmap storage
+     * can be created with the position of the base-check array section as its content offset.
+     *
+     * # Arguments
+     * * `writer`             - A writer.
+     * * `value_serializer`   - A serializer for value objects.
+     * * `auxiliary_sections` - Pairs of an auxiliary section and its content.
+     *
+     * # Errors
+     * * When a section appears more than once.
+     * * When it fails to serialize the sections.
+     */
+    #[cfg(feature = "builder")]
+    pub fn serialize_sections(
+        &self,
+        writer: &mut dyn Write,
+        value_serializer: &mut ValueSerializer<'_, Value>,
+        auxiliary_sections: &[(SerializationSection, &[u8])],
+    ) -> Result<()> {
+        let base_check_array = self.base_check_array.borrow();
+        let mut progress = |_: &SerializationProgress| {};
+        let mut progress_reporter = ProgressReporter::new(
+            &mut progress,
+            base_check_array.len() + self.value_array.len(),
+        );
+
+        let mut base_check_section = Vec::new();
+        Self::serialize_base_check_array(
+            &mut ByteCountingWriter::new(&mut base_check_section),
+            &base_check_array,
+            &mut progress_reporter,
+        )?;
+        let mut value_section = Vec::new();
+        Self::serialize_value_array(
+            &mut ByteCountingWriter::new(&mut value_section),
+            value_serializer,
+            &self.value_array,
+            &mut progress_reporter,
+        )?;
+
+        let mut sections = vec![
+            (
+                SerializationSection::BaseCheckArray,
+                base_check_section.as_slice(),
+            ),
+            (SerializationSection::ValueArray, value_section.as_slice()),
+        ];
+        sections.extend_from_slice(auxiliary_sections);
+        SectionTable::write(writer, &sections)
+    }
+
+    /**
+     * Sets a growth policy of the base-check array.
+     *
+     * # Arguments
+     * * `growth_policy` - A growth policy.
+     *
+     * # Returns
+     * This memory storage.
+     */
+    pub fn growth_policy(mut self, growth_policy: GrowthPolicy) -> Self {
+        self.growth_policy = growth_policy;
+        self
+    }
+
+    /**
+     * Sets a maximum base-check size.
+     *
+     * Touching an index at or beyond the maximum causes an error instead of growing the
+     * base-check array, e.g. when a corrupted base value points far past the end.
+     * The grown size by the growth policy is also clamped to the maximum.
+     *
+     * # Arguments
+     * * `max_base_check_size` - A maximum base-check size.
+     *
+     * # Returns
+     * This memory storage.
+     */
+    pub fn max_base_check_size(mut self, max_base_check_size: usize) -> Self {
+        self.max_base_check_size = Some(max_base_check_size);
+        self
+    }
+
+    /**
+     * Sets a block size of the base-check array.
+     *
+     * The base-check array is allocated in blocks of the size, instead of in one contiguous
+     * allocation. It avoids a multi-gigabyte contiguous allocation for a very large trie, which
+     * fails on a fragmented heap of a 32-bit or WASM platform. A block size of 0 is treated as
+     * 1. The base-check array is allocated in one block by default.
+     *
+     * # Arguments
+     * * `base_check_block_size` - A block size of the base-check array.
+     *
+     * # Returns
+     * This memory storage.
+     */
+    pub fn base_check_block_size(self, base_check_block_size: usize) -> Self {
+        let base_check_array = self
+            .base_check_array
+            .into_inner()
+            .with_block_size(base_check_block_size);
+        Self {
+            base_check_array: RefCell::new(base_check_array),
+            ..self
+        }
+    }
+
+    /**
+     * Sets a layout of the value array.
+     *
+     * The sparse layout saves the memory when only a few value indices have values, e.g. when
+     * the values are added at the base-check indices. The value array is dense by default.
+     *
+     * # Arguments
+     * * `value_array_layout` - A layout of the value array.
+     *
+     * # Returns
+     * This memory storage.
+     */
+    pub fn value_array_layout(self, value_array_layout: ValueArrayLayout) -> Self {
+        Self {
+            value_array: self.value_array.with_layout(value_array_layout),
+            ..self
+        }
+    }
+
+    pub(crate) fn key_transform_id(&self) -> Option<Option<&str>> {
+        self.key_transform_id.as_ref().map(Option::as_deref)
+    }
+
+    #[cfg(feature = "builder")]
+    fn serialize_base_check_array(
+        writer: &mut ByteCountingWriter<'_>,
+        base_check_array: &BaseCheckArray,
+        progress_reporter: &mut ProgressReporter<'_>,
+    ) -> Result<()> {
         debug_assert!(base_check_array.len() < u32::MAX as usize);
         Self::write_u32(writer, base_check_array.len() as u32)?;
-        for v in base_check_array {
-            Self::write_u32(writer, *v)?;
+        for v in base_check_array.iter() {
+            Self::write_u32(writer, v)?;
+            progress_reporter.advance(SerializationSection::BaseCheckArray, writer);
         }
+        progress_reporter.report(SerializationSection::BaseCheckArray, writer);
         Ok(())
     }
 
+    #[cfg(feature = "builder")]
     fn serialize_value_array(
-        writer: &mut dyn Write,
+        writer: &mut ByteCountingWriter<'_>,
         value_serializer: &mut ValueSerializer<'_, Value>,
-        value_array: &[ValueArrayElement<Value>],
+        value_array: &ValueArray<Value>,
+        progress_reporter: &mut ProgressReporter<'_>,
     ) -> Result<()> {
         debug_assert!(value_array.len() < u32::MAX as usize);
         Self::write_u32(writer, value_array.len() as u32)?;
@@ -87,31 +521,36 @@ impl<Value: Clone + 'static> MemoryStorage<Value> {
         Self::write_u32(writer, fixed_value_size)?;
 
         if fixed_value_size == 0 {
-            for v in value_array {
+            // The buffer is reused to write the size before each serialized value.
+            let mut serialized = Vec::new();
+            for v in value_array.iter() {
                 if let Some(v) = v {
-                    let serialized = value_serializer.serialize(v);
+                    serialized.clear();
+                    value_serializer.serialize_to(v, &mut serialized)?;
                     debug_assert!(serialized.len() < u32::MAX as usize);
                     Self::write_u32(writer, serialized.len() as u32)?;
                     writer.write_all(&serialized)?;
                 } else {
                     Self::write_u32(writer, 0)?;
                 }
+                progress_reporter.advance(SerializationSection::ValueArray, writer);
             }
         } else {
-            for v in value_array {
+            for v in value_array.iter() {
                 if let Some(v) = v {
-                    let serialized = value_serializer.serialize(v);
-                    debug_assert!(serialized.len() == fixed_value_size as usize);
-                    writer.write_all(&serialized)?;
+                    value_serializer.serialize_to(v, writer)?;
                 } else {
                     let uninitialized = vec![Self::UNINITIALIZED_BYTE; fixed_value_size as usize];
                     writer.write_all(&uninitialized)?;
                 }
+                progress_reporter.advance(SerializationSection::ValueArray, writer);
             }
         }
+        progress_reporter.report(SerializationSection::ValueArray, writer);
         Ok(())
     }
 
+    #[cfg(feature = "builder")]
     fn write_u32(writer: &mut dyn Write, value: u32) -> Result<()> {
         static INTEGER_SERIALIZER: LazyLock<IntegerSerializer<u32>> =
             LazyLock::new(|| IntegerSerializer::new(false));
@@ -132,7 +571,7 @@ impl<Value: Clone + 'static> MemoryStorage<Value> {
 
     fn deserialize_base_check_array(reader: &mut dyn Read) -> Result<Vec<u32>> {
         let size = Self::read_u32(reader)? as usize;
-        let mut base_check_array = Vec::with_capacity(size);
+        let mut base_check_array = Vec::with_capacity(size.min(Self::MAX_PREALLOCATED_COUNT));
         for _ in 0..size {
             base_check_array.push(Self::read_u32(reader)?);
         }
@@ -146,13 +585,12 @@ impl<Value: Clone + 'static> MemoryStorage<Value> {
         let size = Self::read_u32(reader)? as usize;
 
         let fixed_value_size = Self::read_u32(reader)? as usize;
-        let mut value_array = Vec::with_capacity(size);
+        let mut value_array = Vec::with_capacity(size.min(Self::MAX_PREALLOCATED_COUNT));
         if fixed_value_size == 0 {
             for _ in 0..size {
                 let element_size = Self::read_u32(reader)? as usize;
                 if element_size > 0 {
-                    let mut to_deserialize = vec![0; element_size];
-                    reader.read_exact(&mut to_deserialize)?;
+                    let to_deserialize = Self::read_bytes(reader, element_size)?;
                     value_array.push(Some(Rc::new(
                         value_deserializer.deserialize(&to_deserialize)?,
                     )));
@@ -162,8 +600,7 @@ impl<Value: Clone + 'static> MemoryStorage<Value> {
             }
         } else {
             for _ in 0..size {
-                let mut to_deserialize = vec![0; fixed_value_size];
-                reader.read_exact(&mut to_deserialize)?;
+                let to_deserialize = Self::read_bytes(reader, fixed_value_size)?;
                 if to_deserialize
                     .iter()
                     .all(|&e| e == Self::UNINITIALIZED_BYTE)
@@ -179,6 +616,19 @@ impl<Value: Clone + 'static> MemoryStorage<Value> {
         Ok(value_array)
     }
 
+    // The sizes in a broken or malicious serialization can be huge. The memory is allocated as
+    // the bytes are actually read, so that the reading fails before allocating too much.
+    const MAX_PREALLOCATED_COUNT: usize = 0x10000;
+
+    fn read_bytes(reader: &mut dyn Read, size: usize) -> Result<Vec<u8>> {
+        let mut bytes = Vec::with_capacity(size.min(Self::MAX_PREALLOCATED_COUNT));
+        let _read_size = reader.take(size as u64).read_to_end(&mut bytes)?;
+        if bytes.len() < size {
+            return Err(io::Error::from(ErrorKind::UnexpectedEof).into());
+        }
+        Ok(bytes)
+    }
+
     fn read_u32(reader: &mut dyn Read) -> Result<u32> {
         static U32_DESERIALIZER: LazyLock<IntegerDeserializer<u32>> =
             LazyLock::new(|| IntegerDeserializer::new(false));
@@ -190,12 +640,65 @@ impl<Value: Clone + 'static> MemoryStorage<Value> {
 
     const UNINITIALIZED_BYTE: u8 = 0xFF;
 
-    fn ensure_base_check_size(&self, size: usize) {
-        if size > self.base_check_array.borrow().len() {
-            self.base_check_array
-                .borrow_mut()
-                .resize(size, VACANT_CHECK_VALUE as u32);
+    fn ensure_base_check_size(&self, size: usize) -> Result<()> {
+        let current_size = self.base_check_array.borrow().len();
+        if size <= current_size {
+            return Ok(());
+        }
+        if self.max_base_check_size.is_some_and(|max| size > max) {
+            return Err(MemoryStorageError::BaseCheckSizeExceeded.into());
+        }
+        let mut grown_size = self.growth_policy.grown_size(current_size, size);
+        if let Some(max) = self.max_base_check_size {
+            grown_size = grown_size.min(max);
         }
+        self.base_check_array
+            .borrow_mut()
+            .resize(grown_size, VACANT_CHECK_VALUE as u32);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "builder")]
+struct ProgressReporter<'a> {
+    progress: &'a mut dyn FnMut(&SerializationProgress),
+    total_count: usize,
+    done_count: usize,
+    reported_percent: usize,
+}
+
+#[cfg(feature = "builder")]
+impl<'a> ProgressReporter<'a> {
+    fn new(progress: &'a mut dyn FnMut(&SerializationProgress), total_count: usize) -> Self {
+        Self {
+            progress,
+            total_count,
+            done_count: 0,
+            reported_percent: 0,
+        }
+    }
+
+    fn advance(&mut self, section: SerializationSection, writer: &ByteCountingWriter<'_>) {
+        self.done_count += 1;
+        // The progress is reported only when the percentage in integer increases, so that the
+        // callback is called about a hundred times at most.
+        if self.done_count * 100 / self.total_count > self.reported_percent {
+            self.report(section, writer);
+        }
+    }
+
+    fn report(&mut self, section: SerializationSection, writer: &ByteCountingWriter<'_>) {
+        let percent = if self.total_count == 0 {
+            100.0
+        } else {
+            self.done_count as f64 * 100.0 / self.total_count as f64
+        };
+        self.reported_percent = self.done_count * 100 / self.total_count.max(1);
+        (self.progress)(&SerializationProgress::new(
+            writer.bytes_written(),
+            section,
+            percent,
+        ));
     }
 }
 
@@ -205,26 +708,30 @@ impl<Value: Clone + Debug + 'static> Storage<Value> for MemoryStorage<Value> {
     }
 
     fn base_at(&self, base_check_index: usize) -> Result<i32> {
-        self.ensure_base_check_size(base_check_index + 1);
-        Ok(self.base_check_array.borrow()[base_check_index] as i32 >> 8i32)
+        self.ensure_base_check_size(base_check_index + 1)?;
+        Ok(self.base_check_array.borrow().get(base_check_index) as i32 >> 8i32)
     }
 
     fn set_base_at(&mut self, base_check_index: usize, base: i32) -> Result<()> {
-        self.ensure_base_check_size(base_check_index + 1);
-        self.base_check_array.borrow_mut()[base_check_index] &= 0x000000FF;
-        self.base_check_array.borrow_mut()[base_check_index] |= (base as u32) << 8;
+        self.ensure_base_check_size(base_check_index + 1)?;
+        let mut base_check_array = self.base_check_array.borrow_mut();
+        let element = base_check_array.get_mut(base_check_index);
+        *element &= 0x000000FF;
+        *element |= (base as u32) << 8;
         Ok(())
     }
 
     fn check_at(&self, base_check_index: usize) -> Result<u8> {
-        self.ensure_base_check_size(base_check_index + 1);
-        Ok((self.base_check_array.borrow()[base_check_index] & 0xFF) as u8)
+        self.ensure_base_check_size(base_check_index + 1)?;
+        Ok((self.base_check_array.borrow().get(base_check_index) & 0xFF) as u8)
     }
 
     fn set_check_at(&mut self, base_check_index: usize, check: u8) -> Result<()> {
-        self.ensure_base_check_size(base_check_index + 1);
-        self.base_check_array.borrow_mut()[base_check_index] &= 0xFFFFFF00;
-        self.base_check_array.borrow_mut()[base_check_index] |= check as u32;
+        self.ensure_base_check_size(base_check_index + 1)?;
+        let mut base_check_array = self.base_check_array.borrow_mut();
+        let element = base_check_array.get_mut(base_check_index);
+        *element &= 0xFFFFFF00;
+        *element |= check as u32;
         Ok(())
     }
 
@@ -233,17 +740,11 @@ impl<Value: Clone + Debug + 'static> Storage<Value> for MemoryStorage<Value> {
     }
 
     fn value_at(&self, value_index: usize) -> Result<Option<Rc<Value>>> {
-        let Some(value) = self.value_array.get(value_index) else {
-            return Ok(None);
-        };
-        Ok(value.clone())
+        Ok(self.value_array.get(value_index))
     }
 
     fn add_value_at(&mut self, value_index: usize, value: Value) -> Result<()> {
-        if value_index >= self.value_array.len() {
-            self.value_array.resize_with(value_index + 1, || None);
-        }
-        self.value_array[value_index] = Some(Rc::new(value));
+        self.value_array.set(value_index, Rc::new(value));
         Ok(())
     }
 
@@ -252,25 +753,69 @@ impl<Value: Clone + Debug + 'static> Storage<Value> for MemoryStorage<Value> {
             .base_check_array
             .borrow()
             .iter()
-            .filter(|&&e| e == 0x000000FFu32)
+            .filter(|&e| e == 0x000000FFu32)
             .count();
         Ok(1.0 - (empty_count as f64) / (self.base_check_array.borrow().len() as f64))
     }
 
+    #[cfg(feature = "builder")]
     fn serialize(
         &self,
         writer: &mut dyn Write,
         value_serializer: &mut ValueSerializer<'_, Value>,
     ) -> Result<()> {
-        Self::serialize_base_check_array(writer, &self.base_check_array.borrow())?;
-        Self::serialize_value_array(writer, value_serializer, &self.value_array)?;
+        self.serialize_with_progress(writer, value_serializer, &mut |_| {})
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(
+                base_check_size = self.base_check_array.borrow().len(),
+                value_count = self.value_array.len()
+            )
+        )
+    )]
+    #[cfg(feature = "builder")]
+    fn serialize_with_progress(
+        &self,
+        writer: &mut dyn Write,
+        value_serializer: &mut ValueSerializer<'_, Value>,
+        progress: &mut dyn FnMut(&SerializationProgress),
+    ) -> Result<()> {
+        let base_check_array = self.base_check_array.borrow();
+        let mut writer = ByteCountingWriter::new(writer);
+        let mut progress_reporter =
+            ProgressReporter::new(progress, base_check_array.len() + self.value_array.len());
+        Self::serialize_base_check_array(&mut writer, &base_check_array, &mut progress_reporter)?;
+        Self::serialize_value_array(
+            &mut writer,
+            value_serializer,
+            &self.value_array,
+            &mut progress_reporter,
+        )?;
 
         Ok(())
     }
+
+    fn verify_key_transform(&self, key_transform: Option<&KeyTransform>) -> Result<()> {
+        match &self.key_transform_id {
+            Some(key_transform_id) => {
+                KeyTransform::verify_id(key_transform, key_transform_id.as_deref())
+            }
+            None => Ok(()),
+        }
+    }
+
     fn clone_box(&self) -> Box<dyn Storage<Value>> {
         Box::new(Self {
             base_check_array: RefCell::new(self.base_check_array.borrow().clone()),
             value_array: self.value_array.clone(),
+            growth_policy: self.growth_policy,
+            max_base_check_size: self.max_base_check_size,
+            key_transform_id: self.key_transform_id.clone(),
         })
     }
 
@@ -287,8 +832,14 @@ impl<Value: Clone + Debug + 'static> Storage<Value> for MemoryStorage<Value> {
 mod tests {
     use std::io::Cursor;
 
-    use crate::serializer::{Deserializer, Serializer};
-    use crate::string_serializer::{StrSerializer, StringDeserializer};
+    use crate::error::TrieError;
+    use crate::serializer::Deserializer;
+    #[cfg(feature = "builder")]
+    use crate::serializer::Serializer;
+    #[cfg(feature = "builder")]
+    use crate::string_serializer::StrSerializer;
+    use crate::string_serializer::StringDeserializer;
+    #[cfg(feature = "builder")]
     use crate::value_serializer::ValueSerializer;
 
     use super::*;
@@ -402,6 +953,33 @@ mod tests {
             let result = MemoryStorage::new_with_reader(&mut reader, &mut deserializer);
             assert!(result.is_err());
         }
+        {
+            // The huge sizes must not be allocated before the bytes are read.
+            #[rustfmt::skip]
+            let serialized: &[&[u8]] = &[
+                &[0xFFu8, 0xFFu8, 0xFFu8, 0xFFu8, 0x00u8, 0x00u8, 0x2Au8, 0xFFu8],
+                &[
+                    0x00u8, 0x00u8, 0x00u8, 0x00u8,
+                    0x00u8, 0x00u8, 0x00u8, 0x01u8,
+                    0x00u8, 0x00u8, 0x00u8, 0x00u8,
+                    0xFFu8, 0xFFu8, 0xFFu8, 0xFFu8,
+                ],
+                &[
+                    0x00u8, 0x00u8, 0x00u8, 0x00u8,
+                    0x00u8, 0x00u8, 0x00u8, 0x01u8,
+                    0xFFu8, 0xFFu8, 0xFFu8, 0xFFu8,
+                    0x00u8,
+                ],
+            ];
+            for serialized in serialized {
+                let mut deserializer = ValueDeserializer::new(Box::new(|serialized| {
+                    StringDeserializer::new(false).deserialize(serialized)
+                }));
+                let result =
+                    MemoryStorage::new_with_reader(&mut &serialized[..], &mut deserializer);
+                assert!(result.is_err());
+            }
+        }
     }
 
     #[test]
@@ -417,6 +995,135 @@ mod tests {
         }
     }
 
+    #[test]
+    fn growth_policy() {
+        {
+            let storage = MemoryStorage::<u32>::new().growth_policy(GrowthPolicy::Exact);
+            let _ = storage.base_at(42).unwrap();
+            assert_eq!(storage.base_check_size().unwrap(), 43);
+        }
+        {
+            let storage = MemoryStorage::<u32>::new().growth_policy(GrowthPolicy::Exponential);
+            let _ = storage.base_at(1).unwrap();
+            assert_eq!(storage.base_check_size().unwrap(), 2);
+            let _ = storage.base_at(2).unwrap();
+            assert_eq!(storage.base_check_size().unwrap(), 4);
+            let _ = storage.base_at(42).unwrap();
+            assert_eq!(storage.base_check_size().unwrap(), 43);
+        }
+        {
+            let storage = MemoryStorage::<u32>::new().growth_policy(GrowthPolicy::Chunked(16));
+            let _ = storage.base_at(1).unwrap();
+            assert_eq!(storage.base_check_size().unwrap(), 16);
+            let _ = storage.base_at(42).unwrap();
+            assert_eq!(storage.base_check_size().unwrap(), 48);
+            assert_eq!(storage.check_at(47).unwrap(), VACANT_CHECK_VALUE);
+        }
+    }
+
+    #[test]
+    fn max_base_check_size() {
+        let mut storage = MemoryStorage::<u32>::new()
+            .growth_policy(GrowthPolicy::Chunked(16))
+            .max_base_check_size(24);
+
+        storage.set_base_at(20, 42).unwrap();
+        assert_eq!(storage.base_check_size().unwrap(), 24);
+        assert_eq!(storage.base_at(20).unwrap(), 42);
+        assert!(storage.base_at(23).is_ok());
+        assert!(matches!(
+            storage.base_at(24),
+            Err(TrieError::MemoryStorage(
+                MemoryStorageError::BaseCheckSizeExceeded
+            ))
+        ));
+        assert!(storage.set_check_at(0x00FFFFFF, 42).is_err());
+        assert_eq!(storage.base_check_size().unwrap(), 24);
+    }
+
+    #[cfg(feature = "builder")]
+    #[test]
+    fn base_check_block_size() {
+        {
+            let mut reader = create_input_stream();
+            let mut deserializer = ValueDeserializer::new(Box::new(|serialized| {
+                static STRING_DESERIALIZER: LazyLock<StringDeserializer> =
+                    LazyLock::new(|| StringDeserializer::new(false));
+                STRING_DESERIALIZER.deserialize(serialized)
+            }));
+            let storage = MemoryStorage::new_with_reader(&mut reader, &mut deserializer)
+                .unwrap()
+                .base_check_block_size(1);
+
+            assert_eq!(base_check_array_of(&storage), BASE_CHECK_ARRAY);
+        }
+        {
+            let mut storage = MemoryStorage::<u32>::new().base_check_block_size(4);
+
+            storage.set_base_at(9, 42).unwrap();
+            storage.set_check_at(9, 24).unwrap();
+            storage.set_base_at(3, -1).unwrap();
+
+            assert_eq!(storage.base_check_size().unwrap(), 10);
+            assert_eq!(storage.base_at(9).unwrap(), 42);
+            assert_eq!(storage.check_at(9).unwrap(), 24);
+            assert_eq!(storage.base_at(3).unwrap(), -1);
+            assert_eq!(storage.check_at(3).unwrap(), VACANT_CHECK_VALUE);
+            assert_eq!(storage.check_at(4).unwrap(), VACANT_CHECK_VALUE);
+
+            let mut serialized = Vec::new();
+            let mut serializer = ValueSerializer::<u32>::new(Box::new(|_| Vec::new()), 0);
+            storage.serialize(&mut serialized, &mut serializer).unwrap();
+            let mut deserializer = ValueDeserializer::new(Box::new(|_| Ok(0u32)));
+            let loaded =
+                MemoryStorage::new_with_reader(&mut Cursor::new(serialized), &mut deserializer)
+                    .unwrap();
+            assert_eq!(base_check_array_of(&loaded), base_check_array_of(&storage));
+        }
+    }
+
+    #[cfg(feature = "builder")]
+    #[test]
+    fn value_array_layout() {
+        let mut reader = create_input_stream();
+        let mut deserializer = ValueDeserializer::new(Box::new(|serialized| {
+            static STRING_DESERIALIZER: LazyLock<StringDeserializer> =
+                LazyLock::new(|| StringDeserializer::new(false));
+            STRING_DESERIALIZER.deserialize(serialized)
+        }));
+        let mut storage = MemoryStorage::new_with_reader(&mut reader, &mut deserializer)
+            .unwrap()
+            .value_array_layout(ValueArrayLayout::Sparse);
+
+        assert_eq!(storage.value_count().unwrap(), 5);
+        assert!(storage.value_at(0).unwrap().is_none());
+        assert_eq!(storage.value_at(1).unwrap().unwrap().as_ref(), "piyo");
+        assert_eq!(storage.value_at(4).unwrap().unwrap().as_ref(), "hoge");
+        assert!(storage.value_at(5).unwrap().is_none());
+
+        storage.add_value_at(42, String::from("foo")).unwrap();
+        assert_eq!(storage.value_count().unwrap(), 43);
+        assert_eq!(storage.value_at(42).unwrap().unwrap().as_ref(), "foo");
+        assert!(storage.value_at(41).unwrap().is_none());
+
+        let mut serializer = ValueSerializer::<String>::new(
+            Box::new(|value| StrSerializer::new(false).serialize(&value.as_str())),
+            0,
+        );
+        let mut sparse_serialized = Vec::new();
+        storage
+            .serialize(&mut sparse_serialized, &mut serializer)
+            .unwrap();
+        let storage = storage.value_array_layout(ValueArrayLayout::Dense);
+        let mut dense_serialized = Vec::new();
+        storage
+            .serialize(&mut dense_serialized, &mut serializer)
+            .unwrap();
+        assert_eq!(sparse_serialized, dense_serialized);
+        assert_eq!(storage.value_count().unwrap(), 43);
+        assert_eq!(storage.value_at(2).unwrap().unwrap().as_ref(), "fuga");
+    }
+
     #[test]
     fn base_at() {
         let storage = MemoryStorage::<u32>::new();
@@ -511,6 +1218,7 @@ mod tests {
         assert!((storage.filling_rate().unwrap() - 3.0 / 9.0).abs() < 0.1);
     }
 
+    #[cfg(feature = "builder")]
     #[test]
     fn serialize() {
         {
@@ -594,6 +1302,224 @@ mod tests {
             let serialized = writer.get_ref();
             assert_eq!(serialized.as_slice(), EXPECTED);
         }
+        {
+            let mut storage = MemoryStorage::<String>::new();
+
+            storage.set_base_at(0, 42).unwrap();
+            storage.set_base_at(1, 0xFE).unwrap();
+            storage.set_check_at(1, 24).unwrap();
+
+            storage.add_value_at(2, String::from("fuga")).unwrap();
+            storage.add_value_at(1, String::from("piyo")).unwrap();
+
+            let mut writer = Cursor::new(Vec::<u8>::new());
+            let mut serializer = ValueSerializer::<String>::new_streaming(
+                Box::new(|value: &String, writer: &mut dyn Write| {
+                    writer.write_all(value.as_bytes())?;
+                    Ok(())
+                }),
+                0,
+            );
+            let result = storage.serialize(&mut writer, &mut serializer);
+            assert!(result.is_ok());
+
+            #[rustfmt::skip]
+            const EXPECTED: &[u8] = &[
+                0x00u8, 0x00u8, 0x00u8, 0x02u8,
+                0x00u8, 0x00u8, 0x2Au8, 0xFFu8,
+                0x00u8, 0x00u8, 0xFEu8, 0x18u8,
+                0x00u8, 0x00u8, 0x00u8, 0x03u8,
+                0x00u8, 0x00u8, 0x00u8, 0x00u8,
+                0x00u8, 0x00u8, 0x00u8, 0x00u8,
+                0x00u8, 0x00u8, 0x00u8, 0x04u8,
+                0x70u8, 0x69u8, 0x79u8, 0x6Fu8,
+                0x00u8, 0x00u8, 0x00u8, 0x04u8,
+                0x66u8, 0x75u8, 0x67u8, 0x61u8,
+            ];
+            let serialized = writer.get_ref();
+            assert_eq!(serialized.as_slice(), EXPECTED);
+        }
+    }
+
+    #[cfg(feature = "builder")]
+    #[test]
+    fn serialize_with_progress() {
+        let mut storage = MemoryStorage::<u32>::new();
+        storage.set_base_at(0, 42).unwrap();
+        storage.set_base_at(1, 0xFE).unwrap();
+        storage.set_check_at(1, 24).unwrap();
+        storage.add_value_at(4, 3).unwrap();
+        storage.add_value_at(2, 14).unwrap();
+        storage.add_value_at(1, 159).unwrap();
+
+        let mut writer = Cursor::new(Vec::<u8>::new());
+        let mut serializer = ValueSerializer::<u32>::new(
+            Box::new(|value: &u32| IntegerSerializer::<u32>::new(false).serialize(value)),
+            size_of::<u32>(),
+        );
+        let mut progresses = Vec::new();
+        storage
+            .serialize_with_progress(&mut writer, &mut serializer, &mut |progress| {
+                progresses.push(*progress)
+            })
+            .unwrap();
+
+        assert_eq!(
+            progresses.first().unwrap().section(),
+            SerializationSection::BaseCheckArray
+        );
+        assert!(progresses
+            .windows(2)
+            .all(|pair| pair[0].percent() <= pair[1].percent()
+                && pair[0].bytes_written() <= pair[1].bytes_written()));
+        assert_eq!(
+            progresses.last().unwrap(),
+            &SerializationProgress::new(
+                writer.get_ref().len() as u64,
+                SerializationSection::ValueArray,
+                100.0
+            )
+        );
+        assert!(progresses.len() <= 7 + 2);
+    }
+
+    #[cfg(feature = "builder")]
+    fn create_storage_and_value_serializer() -> (MemoryStorage<u32>, ValueSerializer<'static, u32>)
+    {
+        let mut storage = MemoryStorage::<u32>::new();
+        storage.set_base_at(0, 42).unwrap();
+        storage.set_base_at(1, 0xFE).unwrap();
+        storage.set_check_at(1, 24).unwrap();
+        storage.add_value_at(4, 3).unwrap();
+        storage.add_value_at(2, 14).unwrap();
+        storage.add_value_at(1, 159).unwrap();
+        let value_serializer = ValueSerializer::<u32>::new(
+            Box::new(|value: &u32| IntegerSerializer::<u32>::new(false).serialize(value)),
+            size_of::<u32>(),
+        );
+        (storage, value_serializer)
+    }
+
+    #[cfg(feature = "builder")]
+    #[test]
+    fn serialize_sections() {
+        let (storage, mut value_serializer) = create_storage_and_value_serializer();
+        let mut writer = Cursor::new(Vec::<u8>::new());
+        storage
+            .serialize_sections(
+                &mut writer,
+                &mut value_serializer,
+                &[(SerializationSection::Auxiliary(1), &[0x2A])],
+            )
+            .unwrap();
+
+        let mut reader = Cursor::new(writer.into_inner());
+        let table = SectionTable::new_with_reader(&mut reader).unwrap();
+        assert_eq!(
+            table.sections(),
+            vec![
+                SerializationSection::BaseCheckArray,
+                SerializationSection::ValueArray,
+                SerializationSection::Auxiliary(1),
+            ]
+        );
+        let (base_check_position, base_check_size) = table
+            .position_and_size(SerializationSection::BaseCheckArray)
+            .unwrap();
+        let (value_position, value_size) = table
+            .position_and_size(SerializationSection::ValueArray)
+            .unwrap();
+        assert_eq!(base_check_position + base_check_size, value_position);
+        let mut plain = Cursor::new(Vec::<u8>::new());
+        storage
+            .serialize(&mut plain, &mut value_serializer)
+            .unwrap();
+        assert_eq!(
+            &reader.get_ref()[base_check_position as usize..(value_position + value_size) as usize],
+            plain.get_ref().as_slice()
+        );
+        assert_eq!(
+            table
+                .read_section(&mut reader, SerializationSection::Auxiliary(1))
+                .unwrap(),
+            Some(vec![0x2A])
+        );
+    }
+
+    #[cfg(feature = "builder")]
+    #[test]
+    fn new_with_sections() {
+        let (storage, mut value_serializer) = create_storage_and_value_serializer();
+        let mut writer = Cursor::new(Vec::<u8>::new());
+        storage
+            .serialize_sections(&mut writer, &mut value_serializer, &[])
+            .unwrap();
+        let serialized = writer.into_inner();
+
+        {
+            let mut deserializer = ValueDeserializer::new(Box::new(|serialized: &[u8]| {
+                IntegerDeserializer::<u32>::new(false).deserialize(serialized)
+            }));
+            let loaded = MemoryStorage::new_with_sections(
+                &mut Cursor::new(serialized.as_slice()),
+                Some(&mut deserializer),
+            )
+            .unwrap();
+
+            assert_eq!(base_check_array_of(&loaded), BASE_CHECK_ARRAY);
+            assert_eq!(*loaded.value_at(2).unwrap().unwrap(), 14);
+        }
+        {
+            let loaded = MemoryStorage::<u32>::new_with_sections(
+                &mut Cursor::new(serialized.as_slice()),
+                None,
+            )
+            .unwrap();
+
+            assert_eq!(base_check_array_of(&loaded), BASE_CHECK_ARRAY);
+            assert_eq!(loaded.value_count().unwrap(), 0);
+        }
+        {
+            let mut writer = Cursor::new(Vec::<u8>::new());
+            SectionTable::write(&mut writer, &[]).unwrap();
+
+            assert!(MemoryStorage::<u32>::new_with_sections(
+                &mut Cursor::new(writer.into_inner()),
+                None
+            )
+            .is_err());
+        }
+    }
+
+    #[cfg(feature = "builder")]
+    #[test]
+    fn verify_key_transform() {
+        let lowercase = KeyTransform::new("lowercase", <[u8]>::to_ascii_lowercase);
+        {
+            let storage = MemoryStorage::<u32>::new();
+
+            assert!(storage.verify_key_transform(None).is_ok());
+            assert!(storage.verify_key_transform(Some(&lowercase)).is_ok());
+        }
+        {
+            let (storage, mut value_serializer) = create_storage_and_value_serializer();
+            let mut writer = Cursor::new(Vec::<u8>::new());
+            storage
+                .serialize_sections(&mut writer, &mut value_serializer, &[])
+                .unwrap();
+            let loaded = MemoryStorage::<u32>::new_with_sections(
+                &mut Cursor::new(writer.into_inner()),
+                None,
+            )
+            .unwrap();
+
+            assert!(loaded.verify_key_transform(None).is_ok());
+            assert!(loaded.verify_key_transform(Some(&lowercase)).is_err());
+            assert!(loaded
+                .clone_box()
+                .verify_key_transform(Some(&lowercase))
+                .is_err());
+        }
     }
 
     #[test]
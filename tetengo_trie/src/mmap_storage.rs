@@ -5,46 +5,50 @@
  */
 
 use std::any::Any;
+use std::borrow::Cow;
 use std::cell::RefCell;
 use std::fmt::Debug;
+#[cfg(feature = "builder")]
 use std::io::Write;
 use std::rc::Rc;
 use std::sync::LazyLock;
 
-use anyhow::Result;
 use hashlink::LinkedHashMap;
 use tempfile as _;
 
+use crate::error::Result;
 use crate::file_mapping::FileMapping;
 use crate::integer_serializer::IntegerDeserializer;
 use crate::serializer::Deserializer;
 use crate::storage::{Storage, StorageError};
-use crate::value_serializer::{ValueDeserializer, ValueSerializer};
+#[cfg(feature = "builder")]
+use crate::value_serializer::ValueSerializer;
+use crate::value_serializer::{BorrowingValueDeserializer, ValueDeserializer};
 
 #[derive(Clone, Debug)]
-struct ValueCache<Value> {
+pub(super) struct ValueCache<Value> {
     cache_capacity: usize,
     map: LinkedHashMap<usize, Option<Rc<Value>>>,
 }
 
 impl<Value> ValueCache<Value> {
-    fn new(cache_capacity: usize) -> Self {
+    pub(super) fn new(cache_capacity: usize) -> Self {
         Self {
             cache_capacity,
             map: LinkedHashMap::new(),
         }
     }
 
-    fn has(&self, index: usize) -> bool {
+    pub(super) fn has(&self, index: usize) -> bool {
         self.map.contains_key(&index)
     }
 
-    fn at(&mut self, index: usize) -> Option<&Option<Rc<Value>>> {
+    pub(super) fn at(&mut self, index: usize) -> Option<&Option<Rc<Value>>> {
         let _ = self.map.to_back(&index);
         self.map.get(&index)
     }
 
-    fn insert(&mut self, index: usize, value: Option<Rc<Value>>) {
+    pub(super) fn insert(&mut self, index: usize, value: Option<Rc<Value>>) {
         debug_assert!(!self.has(index));
 
         while self.map.len() >= self.cache_capacity {
@@ -187,19 +191,72 @@ impl<Value: Clone + Debug + 'static> MmapStorage<Value> {
         }
     }
 
-    fn ensure_value_cached(&self, value_index: usize) -> Result<()> {
-        if self.value_cache.borrow().has(value_index) {
-            return Ok(());
-        }
-
+    /**
+     * Returns the serialized value object.
+     *
+     * The serialized value borrows from the file mapping.
+     *
+     * # Arguments
+     * * `value_index` - A value index.
+     *
+     * # Returns
+     * The serialized value object. Or None when there is no corresponding value object.
+     *
+     * # Errors
+     * * When it fails to read the value object.
+     */
+    pub fn serialized_value_at(&self, value_index: usize) -> Result<Option<&[u8]>> {
         let base_check_count = self.base_check_size()?;
         let fixed_value_size =
             self.read_u32(size_of::<u32>() * (1 + base_check_count + 1))? as usize;
-        let offset = size_of::<u32>() * (1 + base_check_count + 2) + fixed_value_size * value_index;
+        // A broken value index must not make the offset overflow.
+        let offset = fixed_value_size
+            .checked_mul(value_index)
+            .and_then(|offset| offset.checked_add(size_of::<u32>() * (1 + base_check_count + 2)))
+            .ok_or(MmapStorageError::MmapRegionOutOfFileSize)?;
         let serialized = self.read_bytes(offset, fixed_value_size)?;
-        if serialized == vec![Self::UNINITIALIZED_BYTE; fixed_value_size] {
-            self.value_cache.borrow_mut().insert(value_index, None);
+        if serialized.iter().all(|&b| b == Self::UNINITIALIZED_BYTE) {
+            Ok(None)
         } else {
+            Ok(Some(serialized))
+        }
+    }
+
+    /**
+     * Returns the value object borrowing from the file mapping.
+     *
+     * The value is neither cached nor copied unless the deserializer produces an owned one.
+     *
+     * # Type Parameters
+     * * `Borrowed` - A borrowed value type.
+     *
+     * # Arguments
+     * * `value_index`  - A value index.
+     * * `deserializer` - A borrowing deserializer for value objects.
+     *
+     * # Returns
+     * The value object. Or None when there is no corresponding value object.
+     *
+     * # Errors
+     * * When it fails to read the value object.
+     */
+    pub fn borrowed_value_at<Borrowed: ?Sized + ToOwned + 'static>(
+        &self,
+        value_index: usize,
+        deserializer: &BorrowingValueDeserializer<Borrowed>,
+    ) -> Result<Option<Cow<'_, Borrowed>>> {
+        let Some(serialized) = self.serialized_value_at(value_index)? else {
+            return Ok(None);
+        };
+        Ok(Some(deserializer.deserialize(serialized)?))
+    }
+
+    fn ensure_value_cached(&self, value_index: usize) -> Result<()> {
+        if self.value_cache.borrow().has(value_index) {
+            return Ok(());
+        }
+
+        if let Some(serialized) = self.serialized_value_at(value_index)? {
             let value = self
                 .value_deserializer
                 .borrow_mut()
@@ -207,6 +264,8 @@ impl<Value: Clone + Debug + 'static> MmapStorage<Value> {
             self.value_cache
                 .borrow_mut()
                 .insert(value_index, Some(Rc::new(value)));
+        } else {
+            self.value_cache.borrow_mut().insert(value_index, None);
         }
         Ok(())
     }
@@ -214,7 +273,10 @@ impl<Value: Clone + Debug + 'static> MmapStorage<Value> {
     const UNINITIALIZED_BYTE: u8 = 0xFF;
 
     fn read_bytes(&self, offset: usize, size: usize) -> Result<&[u8]> {
-        if offset + size > self.file_size {
+        if offset
+            .checked_add(size)
+            .is_none_or(|end| end > self.file_size)
+        {
             return Err(MmapStorageError::MmapRegionOutOfFileSize.into());
         }
 
@@ -282,6 +344,7 @@ impl<Value: Clone + Debug + 'static> Storage<Value> for MmapStorage<Value> {
         Ok(1.0 - (empty_count as f64) / (base_check_count as f64))
     }
 
+    #[cfg(feature = "builder")]
     fn serialize(&self, _: &mut dyn Write, _: &mut ValueSerializer<'_, Value>) -> Result<()> {
         unreachable!("Unsupported operation.");
     }
@@ -314,6 +377,7 @@ mod tests {
 
     use tempfile::tempfile;
 
+    #[cfg(feature = "builder")]
     use crate::serializer::Serializer;
 
     use super::*;
@@ -403,10 +467,13 @@ mod tests {
     }
 
     mod mmap_storage {
+        #[cfg(feature = "builder")]
         use std::io::Cursor;
 
         use crate::double_array::VACANT_CHECK_VALUE;
-        use crate::integer_serializer::{IntegerDeserializer, IntegerSerializer};
+        use crate::integer_serializer::IntegerDeserializer;
+        #[cfg(feature = "builder")]
+        use crate::integer_serializer::IntegerSerializer;
         use crate::serializer::Deserializer;
         use crate::value_serializer::ValueDeserializer;
 
@@ -718,6 +785,37 @@ mod tests {
             }
         }
 
+        #[test]
+        fn borrowed_value_at() {
+            let file = make_temporary_file(SERIALIZED_FIXED_VALUE_SIZE);
+            let file_size = file_size_of(&file);
+            let file_mapping = Rc::new(FileMapping::new(file).unwrap());
+            let deserializer = ValueDeserializer::<u32>::new(Box::new(|serialized| {
+                static INTEGER_DESERIALIZER: LazyLock<IntegerDeserializer<u32>> =
+                    LazyLock::new(|| IntegerDeserializer::new(false));
+                INTEGER_DESERIALIZER.deserialize(serialized)
+            }));
+            let storage = MmapStorage::builder(file_mapping, 0, file_size, deserializer)
+                .build()
+                .unwrap();
+            let borrowing_deserializer =
+                BorrowingValueDeserializer::<[u8]>::new(|serialized| Ok(Cow::Borrowed(serialized)));
+
+            assert!(storage
+                .borrowed_value_at(0, &borrowing_deserializer)
+                .unwrap()
+                .is_none());
+            let value = storage
+                .borrowed_value_at(1, &borrowing_deserializer)
+                .unwrap()
+                .unwrap();
+            assert!(matches!(value, Cow::Borrowed(&[0x00, 0x00, 0x00, 0x9F])));
+            assert_eq!(
+                storage.serialized_value_at(4).unwrap(),
+                Some([0x00u8, 0x00, 0x00, 0x03].as_slice())
+            );
+        }
+
         #[test]
         #[should_panic]
         fn add_value_at() {
@@ -754,6 +852,7 @@ mod tests {
             assert!((storage.filling_rate().unwrap() - 1.0 / 2.0).abs() < 0.1);
         }
 
+        #[cfg(feature = "builder")]
         #[test]
         #[should_panic]
         fn serialize() {
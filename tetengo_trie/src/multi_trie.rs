@@ -0,0 +1,593 @@
+/*!
+ * A multi-trie.
+ *
+ * Copyright (C) 2023-2025 kaoru  <https://www.tetengo.org/>
+ */
+
+use std::fmt::{self, Debug, Formatter};
+#[cfg(feature = "builder")]
+use std::io::Write;
+use std::io::{Read, Seek};
+#[cfg(feature = "builder")]
+use std::marker::PhantomData;
+use std::rc::Rc;
+
+use crate::error::Result;
+use crate::integer_serializer::IntegerDeserializer;
+#[cfg(feature = "builder")]
+use crate::integer_serializer::IntegerSerializer;
+use crate::memory_storage::MemoryStorage;
+use crate::section_table::SectionTable;
+use crate::serializer::{Deserializer, Serializer, SerializerOf};
+use crate::storage::SerializationSection;
+use crate::trie::Trie;
+use crate::value_serializer::ValueDeserializer;
+#[cfg(feature = "builder")]
+use crate::value_serializer::ValueSerializer;
+
+/**
+ * A multi-trie error.
+ */
+#[derive(Clone, Copy, Debug, thiserror::Error)]
+pub enum MultiTrieError {
+    /**
+     * The trie name appears more than once.
+     */
+    #[error("the trie name appears more than once")]
+    DuplicateName,
+
+    /**
+     * No trie has the name.
+     */
+    #[error("no trie has the name")]
+    UnknownName,
+
+    /**
+     * The value index is out of the value pool.
+     */
+    #[error("the value index is out of the value pool")]
+    ValueIndexOutOfRange,
+
+    /**
+     * There are too many tries.
+     */
+    #[error("there are too many tries")]
+    TooManyTries,
+
+    /**
+     * The serialized content is invalid.
+     */
+    #[error("the serialized content is invalid")]
+    InvalidSerializedContent,
+}
+
+#[cfg(feature = "builder")]
+type IndexElements<KeySerializer> = Vec<(<KeySerializer as Serializer>::Object<'static>, usize)>;
+
+/**
+ * A multi-trie builder.
+ *
+ * # Type Parameters
+ * * `Key`           - A key type.
+ * * `Value`         - A value type.
+ * * `KeySerializer` - A key serializer type.
+ */
+#[cfg(feature = "builder")]
+pub struct MultiTrieBuilder<Key, Value, KeySerializer: Serializer> {
+    values: Vec<Value>,
+    indices: Vec<(String, IndexElements<KeySerializer>)>,
+    key_serializer: KeySerializer,
+    phantom: PhantomData<Key>,
+}
+
+#[cfg(feature = "builder")]
+impl<Key, Value: Clone + Debug + 'static, KeySerializer: Serializer + Clone>
+    MultiTrieBuilder<Key, Value, KeySerializer>
+{
+    /**
+     * Sets values of the shared value pool.
+     */
+    pub fn values(mut self, values: Vec<Value>) -> Self {
+        self.values = values;
+        self
+    }
+
+    /**
+     * Adds an index, from which a trie is built.
+     *
+     * # Arguments
+     * * `name`     - A trie name.
+     * * `elements` - Pairs of a key and an index of the shared value pool.
+     */
+    pub fn index(mut self, name: &str, elements: IndexElements<KeySerializer>) -> Self {
+        self.indices.push((String::from(name), elements));
+        self
+    }
+
+    /**
+     * Sets a key serializer.
+     */
+    pub fn key_serializer(mut self, key_serializer: KeySerializer) -> Self {
+        self.key_serializer = key_serializer;
+        self
+    }
+
+    /**
+     * Builds a multi-trie.
+     *
+     * # Returns
+     * A multi-trie.
+     *
+     * # Errors
+     * * When a trie name appears more than once.
+     * * When a value index is out of the value pool.
+     * * When there are too many tries.
+     * * When it fails to build a trie.
+     */
+    pub fn build(self) -> Result<MultiTrie<Key, Value, KeySerializer>> {
+        if self.indices.len() > u16::MAX as usize + 1 {
+            return Err(MultiTrieError::TooManyTries.into());
+        }
+
+        let mut names = Vec::<String>::with_capacity(self.indices.len());
+        let mut tries = Vec::with_capacity(self.indices.len());
+        for (name, elements) in self.indices {
+            if names.contains(&name) {
+                return Err(MultiTrieError::DuplicateName.into());
+            }
+            let mut trie_elements = Vec::with_capacity(elements.len());
+            for (key, value_index) in elements {
+                let value_index = u32::try_from(value_index)
+                    .ok()
+                    .filter(|&value_index| (value_index as usize) < self.values.len())
+                    .ok_or(MultiTrieError::ValueIndexOutOfRange)?;
+                trie_elements.push((key, value_index));
+            }
+            tries.push(
+                Trie::builder()
+                    .elements(trie_elements)
+                    .key_serializer(self.key_serializer.clone())
+                    .build()?,
+            );
+            names.push(name);
+        }
+
+        Ok(MultiTrie {
+            names,
+            tries,
+            values: self.values.into_iter().map(Rc::new).collect(),
+        })
+    }
+}
+
+#[cfg(feature = "builder")]
+impl<Key, Value: Debug, KeySerializer: Serializer> Debug
+    for MultiTrieBuilder<Key, Value, KeySerializer>
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MultiTrieBuilder")
+            .field("values", &self.values)
+            .field(
+                "indices",
+                &self
+                    .indices
+                    .iter()
+                    .map(|(name, elements)| (name, elements.len()))
+                    .collect::<Vec<_>>(),
+            )
+            .field("key_serializer", &"KeySerializer")
+            .finish()
+    }
+}
+
+/**
+ * A multi-trie.
+ *
+ * It is a set of named tries which index the same records by different kinds of keys, such as
+ * surface forms and readings. The tries share one value pool, and each trie maps its keys to
+ * the indices of the pool.
+ *
+ * A multi-trie is serialized into one sectioned serialization. The value pool is in the value
+ * array section, the trie names are in the trie names section, and each trie is in its own
+ * trie section.
+ *
+ * # Type Parameters
+ * * `Key`           - A key type.
+ * * `Value`         - A value type.
+ * * `KeySerializer` - A key serializer type.
+ */
+pub struct MultiTrie<Key, Value: Debug, KeySerializer: Serializer = <() as SerializerOf<Key>>::Type>
+{
+    names: Vec<String>,
+    tries: Vec<Trie<Key, u32, KeySerializer>>,
+    values: Vec<Rc<Value>>,
+}
+
+impl<Key, Value: Debug, KeySerializer: Serializer + Debug> Debug
+    for MultiTrie<Key, Value, KeySerializer>
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MultiTrie")
+            .field("names", &self.names)
+            .field("tries", &self.tries)
+            .field("values", &self.values)
+            .finish()
+    }
+}
+
+impl<Key, Value: Clone + Debug + 'static, KeySerializer: Serializer + Clone>
+    MultiTrie<Key, Value, KeySerializer>
+{
+    /**
+     * Creates a multi-trie builder.
+     *
+     * # Returns
+     * A multi-trie builder.
+     */
+    #[cfg(feature = "builder")]
+    pub fn builder() -> MultiTrieBuilder<Key, Value, KeySerializer> {
+        MultiTrieBuilder {
+            values: Vec::new(),
+            indices: Vec::new(),
+            key_serializer: KeySerializer::new(true),
+            phantom: PhantomData,
+        }
+    }
+
+    /**
+     * Creates a multi-trie with a reader.
+     *
+     * # Arguments
+     * * `reader`             - A reader.
+     * * `value_deserializer` - A deserializer for value objects.
+     * * `key_serializer`     - A key serializer.
+     *
+     * # Errors
+     * * When the serialization is broken.
+     * * When it fails to read.
+     */
+    pub fn new_with_reader<R: Read + Seek>(
+        reader: &mut R,
+        value_deserializer: &mut ValueDeserializer<Value>,
+        key_serializer: KeySerializer,
+    ) -> Result<Self> {
+        let section_table = SectionTable::new_with_reader(reader)?;
+
+        let names = deserialize_names(&read_section(
+            reader,
+            &section_table,
+            SerializationSection::TrieNames,
+        )?)?;
+        let values = deserialize_values(
+            &read_section(reader, &section_table, SerializationSection::ValueArray)?,
+            value_deserializer,
+        )?;
+
+        let mut index_deserializer = ValueDeserializer::new(Box::new(|serialized: &[u8]| {
+            IntegerDeserializer::<u32>::new(false).deserialize(serialized)
+        }));
+        let mut tries = Vec::with_capacity(names.len());
+        for i in 0..names.len() {
+            let serialized =
+                read_section(reader, &section_table, SerializationSection::Trie(i as u16))?;
+            let storage = MemoryStorage::new_with_reader(
+                &mut serialized.as_slice(),
+                &mut index_deserializer,
+            )?;
+            tries.push(
+                Trie::builder_with_storage(Box::new(storage))
+                    .key_serializer(key_serializer.clone())
+                    .build()?,
+            );
+        }
+
+        Ok(Self {
+            names,
+            tries,
+            values,
+        })
+    }
+
+    /**
+     * Returns the trie names.
+     *
+     * # Returns
+     * The trie names in the order they are added.
+     */
+    pub fn names(&self) -> Vec<&str> {
+        self.names.iter().map(String::as_str).collect()
+    }
+
+    /**
+     * Returns the trie.
+     *
+     * The values of the trie are the indices of the shared value pool.
+     *
+     * # Arguments
+     * * `name` - A trie name.
+     *
+     * # Returns
+     * The trie. Or None when no trie has the name.
+     */
+    pub fn trie(&self, name: &str) -> Option<&Trie<Key, u32, KeySerializer>> {
+        self.names
+            .iter()
+            .position(|other| other == name)
+            .map(|index| &self.tries[index])
+    }
+
+    /**
+     * Returns the value count of the shared value pool.
+     *
+     * # Returns
+     * The value count.
+     */
+    pub fn value_count(&self) -> usize {
+        self.values.len()
+    }
+
+    /**
+     * Returns the value in the shared value pool.
+     *
+     * # Arguments
+     * * `value_index` - A value index.
+     *
+     * # Returns
+     * The value. Or None when the value index is out of the pool.
+     */
+    pub fn value_at(&self, value_index: usize) -> Option<Rc<Value>> {
+        self.values.get(value_index).cloned()
+    }
+
+    /**
+     * Finds the value object by a key in a trie.
+     *
+     * # Arguments
+     * * `name` - A trie name.
+     * * `key`  - A key.
+     *
+     * # Returns
+     * The value object. Or None when the trie does not have the key.
+     *
+     * # Errors
+     * * When no trie has the name.
+     * * When the value index is out of the value pool.
+     * * When it fails to access the storage.
+     */
+    pub fn find(&self, name: &str, key: &KeySerializer::Object<'_>) -> Result<Option<Rc<Value>>> {
+        let trie = self.trie(name).ok_or(MultiTrieError::UnknownName)?;
+        let Some(value_index) = trie.find(key)? else {
+            return Ok(None);
+        };
+        let value = self
+            .value_at(*value_index as usize)
+            .ok_or(MultiTrieError::ValueIndexOutOfRange)?;
+        Ok(Some(value))
+    }
+
+    /**
+     * Serializes the multi-trie.
+     *
+     * # Arguments
+     * * `writer`           - A writer.
+     * * `value_serializer` - A serializer for value objects.
+     *
+     * # Errors
+     * * When it fails to serialize.
+     */
+    #[cfg(feature = "builder")]
+    pub fn serialize(
+        &self,
+        writer: &mut dyn Write,
+        value_serializer: &mut ValueSerializer<'_, Value>,
+    ) -> Result<()> {
+        let serialized_names = serialize_names(&self.names);
+        let serialized_values = serialize_values(&self.values, value_serializer)?;
+        let mut index_serializer = ValueSerializer::new(
+            Box::new(|value_index: &u32| IntegerSerializer::new(false).serialize(value_index)),
+            size_of::<u32>(),
+        );
+        let mut serialized_tries = Vec::with_capacity(self.tries.len());
+        for trie in &self.tries {
+            let mut serialized = Vec::new();
+            trie.storage()
+                .serialize(&mut serialized, &mut index_serializer)?;
+            serialized_tries.push(serialized);
+        }
+
+        let mut sections =
+            Vec::<(SerializationSection, &[u8])>::with_capacity(2 + self.tries.len());
+        sections.push((SerializationSection::TrieNames, &serialized_names));
+        sections.push((SerializationSection::ValueArray, &serialized_values));
+        for (i, serialized) in serialized_tries.iter().enumerate() {
+            sections.push((SerializationSection::Trie(i as u16), serialized));
+        }
+        SectionTable::write(writer, &sections)
+    }
+}
+
+fn read_section<R: Read + Seek>(
+    reader: &mut R,
+    section_table: &SectionTable,
+    section: SerializationSection,
+) -> Result<Vec<u8>> {
+    section_table
+        .read_section(reader, section)?
+        .ok_or_else(|| MultiTrieError::InvalidSerializedContent.into())
+}
+
+#[cfg(feature = "builder")]
+fn serialize_names(names: &[String]) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(&(names.len() as u32).to_be_bytes());
+    for name in names {
+        bytes.extend_from_slice(&(name.len() as u32).to_be_bytes());
+        bytes.extend_from_slice(name.as_bytes());
+    }
+    bytes
+}
+
+fn deserialize_names(mut bytes: &[u8]) -> Result<Vec<String>> {
+    let count = read_u32(&mut bytes)? as usize;
+    let mut names = Vec::with_capacity(count.min(bytes.len()));
+    for _ in 0..count {
+        let name = read_bytes(&mut bytes)?;
+        names.push(
+            String::from_utf8(name.to_vec())
+                .map_err(|_| MultiTrieError::InvalidSerializedContent)?,
+        );
+    }
+    Ok(names)
+}
+
+#[cfg(feature = "builder")]
+fn serialize_values<Value>(
+    values: &[Rc<Value>],
+    value_serializer: &mut ValueSerializer<'_, Value>,
+) -> Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(&(values.len() as u32).to_be_bytes());
+    let mut serialized = Vec::new();
+    for value in values {
+        serialized.clear();
+        value_serializer.serialize_to(value, &mut serialized)?;
+        bytes.extend_from_slice(&(serialized.len() as u32).to_be_bytes());
+        bytes.extend_from_slice(&serialized);
+    }
+    Ok(bytes)
+}
+
+fn deserialize_values<Value: Clone>(
+    mut bytes: &[u8],
+    value_deserializer: &mut ValueDeserializer<Value>,
+) -> Result<Vec<Rc<Value>>> {
+    let count = read_u32(&mut bytes)? as usize;
+    let mut values = Vec::with_capacity(count.min(bytes.len()));
+    for _ in 0..count {
+        let serialized = read_bytes(&mut bytes)?;
+        values.push(Rc::new(value_deserializer.deserialize(serialized)?));
+    }
+    Ok(values)
+}
+
+fn read_u32(bytes: &mut &[u8]) -> Result<u32> {
+    let Some((head, rest)) = bytes.split_first_chunk::<{ size_of::<u32>() }>() else {
+        return Err(MultiTrieError::InvalidSerializedContent.into());
+    };
+    *bytes = rest;
+    Ok(u32::from_be_bytes(*head))
+}
+
+fn read_bytes<'a>(bytes: &mut &'a [u8]) -> Result<&'a [u8]> {
+    let size = read_u32(bytes)? as usize;
+    if bytes.len() < size {
+        return Err(MultiTrieError::InvalidSerializedContent.into());
+    }
+    let (head, rest) = bytes.split_at(size);
+    *bytes = rest;
+    Ok(head)
+}
+
+#[cfg(all(test, feature = "builder"))]
+mod tests {
+    use std::io::Cursor;
+
+    use crate::error::TrieError;
+    use crate::string_serializer::{StrSerializer, StringDeserializer, StringSerializer};
+
+    use super::*;
+
+    fn create_multi_trie() -> MultiTrie<&'static str, String> {
+        MultiTrie::<&str, String>::builder()
+            .values(vec![String::from("熊本"), String::from("玉名")])
+            .index("surface", vec![("熊本", 0), ("玉名", 1)])
+            .index(
+                "reading",
+                vec![("くまもと", 0), ("たまな", 1), ("クマモト", 0)],
+            )
+            .key_serializer(StrSerializer::new(true))
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn build() {
+        {
+            let multi_trie = create_multi_trie();
+
+            assert_eq!(multi_trie.names(), vec!["surface", "reading"]);
+            assert_eq!(multi_trie.value_count(), 2);
+            assert_eq!(multi_trie.trie("reading").unwrap().len().unwrap(), 3);
+            assert!(multi_trie.trie("romaji").is_none());
+        }
+        {
+            let result = MultiTrie::<&str, String>::builder()
+                .values(vec![String::from("熊本")])
+                .index("surface", vec![("熊本", 0)])
+                .index("surface", vec![("玉名", 0)])
+                .build();
+
+            assert!(matches!(
+                result,
+                Err(TrieError::MultiTrie(MultiTrieError::DuplicateName))
+            ));
+        }
+        {
+            let result = MultiTrie::<&str, String>::builder()
+                .values(vec![String::from("熊本")])
+                .index("surface", vec![("玉名", 1)])
+                .build();
+
+            assert!(matches!(
+                result,
+                Err(TrieError::MultiTrie(MultiTrieError::ValueIndexOutOfRange))
+            ));
+        }
+    }
+
+    #[test]
+    fn find() {
+        let multi_trie = create_multi_trie();
+
+        let by_surface = multi_trie.find("surface", &"熊本").unwrap().unwrap();
+        let by_reading = multi_trie.find("reading", &"クマモト").unwrap().unwrap();
+        assert_eq!(*by_surface, "熊本");
+        assert!(Rc::ptr_eq(&by_surface, &by_reading));
+        assert!(multi_trie.find("surface", &"くまもと").unwrap().is_none());
+        assert!(matches!(
+            multi_trie.find("romaji", &"kumamoto"),
+            Err(TrieError::MultiTrie(MultiTrieError::UnknownName))
+        ));
+    }
+
+    #[test]
+    fn serialize() {
+        let multi_trie = create_multi_trie();
+
+        let mut writer = Cursor::new(Vec::new());
+        let mut value_serializer = ValueSerializer::new(
+            Box::new(|value: &String| StringSerializer::new(false).serialize(value)),
+            0,
+        );
+        multi_trie
+            .serialize(&mut writer, &mut value_serializer)
+            .unwrap();
+
+        let mut reader = Cursor::new(writer.into_inner());
+        let mut value_deserializer = ValueDeserializer::new(Box::new(|serialized: &[u8]| {
+            StringDeserializer::new(false).deserialize(serialized)
+        }));
+        let loaded = MultiTrie::<&str, String>::new_with_reader(
+            &mut reader,
+            &mut value_deserializer,
+            StrSerializer::new(true),
+        )
+        .unwrap();
+
+        assert_eq!(loaded.names(), vec!["surface", "reading"]);
+        assert_eq!(loaded.value_count(), 2);
+        assert_eq!(*loaded.find("reading", &"たまな").unwrap().unwrap(), "玉名");
+        assert_eq!(*loaded.find("surface", &"熊本").unwrap().unwrap(), "熊本");
+        assert!(loaded.find("reading", &"うと").unwrap().is_none());
+    }
+}
@@ -0,0 +1,174 @@
+/*!
+ * A prefix group iterator.
+ *
+ * Copyright (C) 2023-2025 kaoru  <https://www.tetengo.org/>
+ */
+
+use std::fmt::Debug;
+
+use crate::double_array::{DoubleArray, KEY_TERMINATOR};
+use crate::double_array_iterator::DoubleArrayIterator;
+use crate::error::Result;
+use crate::trie_iterator::TrieIterator;
+
+/**
+ * A prefix group iterator.
+ *
+ * It yields the pairs of the prefixes of the serialized keys and the iterators of the values
+ * whose keys start with the prefixes, in the lexicographic order of the prefixes.
+ * A prefix is the first bytes of a serialized key with a given length, or the bytes up to and
+ * including a given delimiter. A key shorter than the prefix length or without the delimiter
+ * forms a group by itself, whose prefix is the whole key.
+ *
+ * The groups are found by traversing the double array, without enumerating all the keys.
+ */
+#[derive(Debug)]
+pub struct PrefixGroupIterator<'a, Value: Debug + 'static> {
+    double_array: &'a DoubleArray<Value>,
+    prefix_length: usize,
+    delimiter: Option<u8>,
+    base_check_index_prefix_stack: Vec<(usize, Vec<u8>, bool)>,
+}
+
+impl<'a, Value: Clone + Debug + 'static> PrefixGroupIterator<'a, Value> {
+    pub(super) fn new(
+        double_array: &'a DoubleArray<Value>,
+        prefix_length: usize,
+        delimiter: Option<u8>,
+    ) -> Self {
+        Self {
+            double_array,
+            prefix_length,
+            delimiter,
+            base_check_index_prefix_stack: vec![(double_array.root(), Vec::new(), false)],
+        }
+    }
+
+    fn next_group(&mut self) -> Result<Option<(Vec<u8>, usize)>> {
+        while let Some((base_check_index, prefix, terminal)) =
+            self.base_check_index_prefix_stack.pop()
+        {
+            let completed = terminal
+                || prefix.len() >= self.prefix_length
+                || self
+                    .delimiter
+                    .is_some_and(|delimiter| prefix.last() == Some(&delimiter));
+            if completed {
+                return Ok(Some((prefix, base_check_index)));
+            }
+
+            // The stack pops the smallest child first. The key terminating here is the smallest,
+            // and forms a group by itself.
+            for c in (0..=0xFEu8).rev() {
+                let Some(next_base_check_index) = self.double_array.step(base_check_index, c)?
+                else {
+                    continue;
+                };
+                let mut next_prefix = prefix.clone();
+                if c != KEY_TERMINATOR {
+                    next_prefix.push(c);
+                }
+                self.base_check_index_prefix_stack.push((
+                    next_base_check_index,
+                    next_prefix,
+                    c == KEY_TERMINATOR,
+                ));
+            }
+        }
+        Ok(None)
+    }
+}
+
+impl<'a, Value: Clone + Debug + 'static> Iterator for PrefixGroupIterator<'a, Value> {
+    type Item = (Vec<u8>, TrieIterator<'a, Value>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.next_group() {
+            Ok(Some((prefix, base_check_index))) => {
+                let storage = self.double_array.storage();
+                Some((
+                    prefix,
+                    TrieIterator::new(DoubleArrayIterator::new(storage, base_check_index), storage),
+                ))
+            }
+            Ok(None) => None,
+            Err(e) => {
+                debug_assert!(false, "{}", e);
+                None
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "builder"))]
+mod tests {
+    use crate::serializer::Serializer;
+    use crate::string_serializer::StrSerializer;
+    use crate::trie::Trie;
+
+    use super::*;
+
+    fn create_trie() -> Trie<&'static str, i32> {
+        Trie::<&str, i32>::builder()
+            .elements(
+                [
+                    ("ka/mi", 1),
+                    ("ka/mo", 2),
+                    ("ki", 3),
+                    ("ki/ku", 4),
+                    ("ko/ke/ko", 5),
+                    ("k", 6),
+                ]
+                .to_vec(),
+            )
+            .key_serializer(StrSerializer::new(false))
+            .build()
+            .unwrap()
+    }
+
+    fn groups_of(iterator: PrefixGroupIterator<'_, i32>) -> Vec<(String, Vec<i32>)> {
+        iterator
+            .map(|(prefix, values)| {
+                (
+                    String::from_utf8(prefix).unwrap(),
+                    values.map(|v| *v).collect(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn length() {
+        let trie = create_trie();
+
+        assert_eq!(
+            groups_of(trie.prefix_groups_by_length(2)),
+            vec![
+                (String::from("k"), vec![6]),
+                (String::from("ka"), vec![1, 2]),
+                (String::from("ki"), vec![3, 4]),
+                (String::from("ko"), vec![5]),
+            ]
+        );
+        assert_eq!(
+            groups_of(trie.prefix_groups_by_length(0)),
+            vec![(String::new(), vec![6, 1, 2, 3, 4, 5])]
+        );
+    }
+
+    #[test]
+    fn delimiter() {
+        let trie = create_trie();
+
+        assert_eq!(
+            groups_of(trie.prefix_groups_by_delimiter(b'/')),
+            vec![
+                (String::from("k"), vec![6]),
+                (String::from("ka/"), vec![1, 2]),
+                (String::from("ki"), vec![3]),
+                (String::from("ki/"), vec![4]),
+                (String::from("ko/"), vec![5]),
+            ]
+        );
+    }
+}
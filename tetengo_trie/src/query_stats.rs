@@ -0,0 +1,162 @@
+/*!
+ * A query stats.
+ *
+ * Copyright (C) 2023-2025 kaoru  <https://www.tetengo.org/>
+ */
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/**
+ * A query stats.
+ *
+ * It counts the queries to a trie for the observability of the dictionary behavior in
+ * production. Pass it to a trie builder to enable the counting. A trie without it counts
+ * nothing.
+ *
+ * The counters are shared with `Arc` among the trie, its clones and its subtries, so that the
+ * counts can be snapshotted while the trie is used, even in other threads.
+ */
+#[derive(Debug, Default)]
+pub struct QueryStats {
+    lookup_count: AtomicU64,
+    probe_count: AtomicU64,
+    value_decode_count: AtomicU64,
+}
+
+impl QueryStats {
+    /**
+     * Creates a query stats.
+     */
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /**
+     * Takes a snapshot of the counters.
+     *
+     * # Returns
+     * A snapshot.
+     */
+    pub fn snapshot(&self) -> QueryStatsSnapshot {
+        QueryStatsSnapshot {
+            lookup_count: self.lookup_count.load(Ordering::Relaxed),
+            probe_count: self.probe_count.load(Ordering::Relaxed),
+            value_decode_count: self.value_decode_count.load(Ordering::Relaxed),
+        }
+    }
+
+    /**
+     * Resets the counters.
+     */
+    pub fn reset(&self) {
+        self.lookup_count.store(0, Ordering::Relaxed);
+        self.probe_count.store(0, Ordering::Relaxed);
+        self.value_decode_count.store(0, Ordering::Relaxed);
+    }
+
+    pub(crate) fn lookup(&self, probe_length: usize) {
+        let _ = self.lookup_count.fetch_add(1, Ordering::Relaxed);
+        let _ = self
+            .probe_count
+            .fetch_add(probe_length as u64, Ordering::Relaxed);
+    }
+
+    pub(crate) fn value_decode(&self) {
+        let _ = self.value_decode_count.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/**
+ * A snapshot of a query stats.
+ */
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct QueryStatsSnapshot {
+    lookup_count: u64,
+    probe_count: u64,
+    value_decode_count: u64,
+}
+
+impl QueryStatsSnapshot {
+    /**
+     * Returns the number of the lookups.
+     *
+     * A lookup is a whole-key query such as `Trie::find()` and `Trie::contains()`.
+     *
+     * # Returns
+     * The number of the lookups.
+     */
+    pub const fn lookup_count(&self) -> u64 {
+        self.lookup_count
+    }
+
+    /**
+     * Returns the total number of the base-check array probes made by the lookups.
+     *
+     * # Returns
+     * The total number of the probes.
+     */
+    pub const fn probe_count(&self) -> u64 {
+        self.probe_count
+    }
+
+    /**
+     * Returns the average probe length of the lookups.
+     *
+     * # Returns
+     * The average probe length. Or 0.0 when no lookup is made.
+     */
+    pub fn average_probe_length(&self) -> f64 {
+        if self.lookup_count == 0 {
+            return 0.0;
+        }
+        self.probe_count as f64 / self.lookup_count as f64
+    }
+
+    /**
+     * Returns the number of the value objects fetched from the storage.
+     *
+     * The storage deserializes a value object when it is fetched for the first time or evicted
+     * from the cache.
+     *
+     * # Returns
+     * The number of the value objects fetched.
+     */
+    pub const fn value_decode_count(&self) -> u64 {
+        self.value_decode_count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snapshot() {
+        let query_stats = QueryStats::new();
+        assert_eq!(query_stats.snapshot(), QueryStatsSnapshot::default());
+
+        query_stats.lookup(3);
+        query_stats.lookup(6);
+        query_stats.value_decode();
+        let snapshot = query_stats.snapshot();
+        query_stats.lookup(1);
+
+        assert_eq!(snapshot.lookup_count(), 2);
+        assert_eq!(snapshot.probe_count(), 9);
+        assert!((snapshot.average_probe_length() - 4.5).abs() < f64::EPSILON);
+        assert_eq!(snapshot.value_decode_count(), 1);
+        assert_eq!(query_stats.snapshot().lookup_count(), 3);
+    }
+
+    #[test]
+    fn reset() {
+        let query_stats = QueryStats::new();
+        query_stats.lookup(3);
+        query_stats.value_decode();
+
+        query_stats.reset();
+
+        assert_eq!(query_stats.snapshot(), QueryStatsSnapshot::default());
+        assert!(query_stats.snapshot().average_probe_length().abs() < f64::EPSILON);
+    }
+}
@@ -0,0 +1,531 @@
+/*!
+ * A random access storage.
+ *
+ * Copyright (C) 2023-2025 kaoru  <https://www.tetengo.org/>
+ */
+
+use std::any::Any;
+use std::cell::RefCell;
+use std::fmt::Debug;
+#[cfg(feature = "builder")]
+use std::io::Write;
+use std::rc::Rc;
+
+use crate::error::Result;
+use crate::file_mapping::FileMapping;
+use crate::mmap_storage::ValueCache;
+use crate::storage::{Storage, StorageError};
+use crate::value_serializer::ValueDeserializer;
+#[cfg(feature = "builder")]
+use crate::value_serializer::ValueSerializer;
+
+/**
+ * A random access.
+ *
+ * It is a minimal read-only interface of a byte sequence, such as a file, an object in an object
+ * storage, an encrypted container or a file in a virtual file system.
+ */
+pub trait RandomAccess: Debug {
+    /**
+     * Reads bytes at an offset.
+     *
+     * The whole buffer must be filled.
+     *
+     * # Arguments
+     * * `offset` - An offset.
+     * * `buffer` - A buffer.
+     *
+     * # Errors
+     * * When it fails to read the bytes.
+     */
+    fn read_at(&self, offset: u64, buffer: &mut [u8]) -> Result<()>;
+
+    /**
+     * Returns the length.
+     *
+     * # Returns
+     * The length.
+     *
+     * # Errors
+     * * When it fails to get the length.
+     */
+    fn len(&self) -> Result<u64>;
+
+    /**
+     * Returns `true` if the byte sequence is empty.
+     *
+     * # Returns
+     * `true` if the byte sequence is empty.
+     *
+     * # Errors
+     * * When it fails to get the length.
+     */
+    fn is_empty(&self) -> Result<bool> {
+        Ok(self.len()? == 0)
+    }
+}
+
+impl RandomAccess for Vec<u8> {
+    fn read_at(&self, offset: u64, buffer: &mut [u8]) -> Result<()> {
+        read_bytes_at(self, offset, buffer)
+    }
+
+    fn len(&self) -> Result<u64> {
+        Ok(<[u8]>::len(self) as u64)
+    }
+}
+
+/**
+ * A random access to static bytes.
+ *
+ * It reads the bytes in place, e.g. the ones embedded with `include_bytes!`, so that a storage
+ * needs no copy of them.
+ */
+impl RandomAccess for &'static [u8] {
+    fn read_at(&self, offset: u64, buffer: &mut [u8]) -> Result<()> {
+        read_bytes_at(self, offset, buffer)
+    }
+
+    fn len(&self) -> Result<u64> {
+        Ok(<[u8]>::len(self) as u64)
+    }
+}
+
+fn read_bytes_at(bytes: &[u8], offset: u64, buffer: &mut [u8]) -> Result<()> {
+    let start = usize::try_from(offset).map_err(|_| RandomAccessStorageError::RegionOutOfRange)?;
+    let region = start
+        .checked_add(buffer.len())
+        .and_then(|end| bytes.get(start..end))
+        .ok_or(RandomAccessStorageError::RegionOutOfRange)?;
+    buffer.copy_from_slice(region);
+    Ok(())
+}
+
+impl RandomAccess for FileMapping {
+    fn read_at(&self, offset: u64, buffer: &mut [u8]) -> Result<()> {
+        let start =
+            usize::try_from(offset).map_err(|_| RandomAccessStorageError::RegionOutOfRange)?;
+        buffer.copy_from_slice(self.region(start..start + buffer.len())?);
+        Ok(())
+    }
+
+    fn len(&self) -> Result<u64> {
+        Ok(self.size() as u64)
+    }
+}
+
+/**
+ * A random access storage error.
+ */
+#[derive(Clone, Copy, Debug, thiserror::Error)]
+pub enum RandomAccessStorageError {
+    /**
+     * The value size is not fixed.
+     */
+    #[error("the value size is not fixed")]
+    ValueSizeNotFixed,
+
+    /**
+     * The region is out of the random access.
+     */
+    #[error("the region is out of the random access")]
+    RegionOutOfRange,
+}
+
+impl StorageError for RandomAccessStorageError {}
+
+/**
+ * A random access storage builder.
+ *
+ * # Type Parameters
+ * * `Value` - A value type.
+ */
+#[derive(Debug)]
+pub struct RandomAccessStorageBuilder<Value: Clone + Debug> {
+    random_access: Rc<dyn RandomAccess>,
+    content_offset: u64,
+    value_deserializer: ValueDeserializer<Value>,
+    value_cache_capacity: usize,
+}
+
+impl<Value: Clone + Debug + 'static> RandomAccessStorageBuilder<Value> {
+    /**
+     * Sets a value cache capacity.
+     *
+     * # Arguments
+     * * `value` - A value cache capacity.
+     */
+    pub const fn value_cache_capacity(mut self, value: usize) -> Self {
+        self.value_cache_capacity = value;
+        self
+    }
+
+    /**
+     * Builds a random access storage.
+     *
+     * # Returns
+     * A random access storage.
+     *
+     * # Errors
+     * * When the value size is not fixed.
+     * * When it fails to read.
+     */
+    pub fn build(self) -> Result<RandomAccessStorage<Value>> {
+        let self_ = RandomAccessStorage::<Value> {
+            random_access: self.random_access,
+            content_offset: self.content_offset,
+            value_deserializer: Rc::new(RefCell::new(self.value_deserializer)),
+            value_cache: RefCell::new(ValueCache::new(self.value_cache_capacity)),
+        };
+
+        if self_.fixed_value_size()? == 0 {
+            return Err(RandomAccessStorageError::ValueSizeNotFixed.into());
+        }
+
+        Ok(self_)
+    }
+}
+
+/**
+ * A random access storage.
+ *
+ * It reads a serialized storage through a user-provided random access, instead of loading the
+ * whole content. Like an mmap storage, the values must be serialized in a fixed size, and the
+ * storage is read-only.
+ *
+ * # Type Parameters
+ * * `Value` - A value type.
+ */
+#[derive(Debug)]
+pub struct RandomAccessStorage<Value: Clone + Debug> {
+    random_access: Rc<dyn RandomAccess>,
+    content_offset: u64,
+    value_deserializer: Rc<RefCell<ValueDeserializer<Value>>>,
+    value_cache: RefCell<ValueCache<Value>>,
+}
+
+impl<Value: Clone + Debug + 'static> RandomAccessStorage<Value> {
+    /// A default value cache capacity.
+    pub const DEFAULT_VALUE_CACHE_CAPACITY: usize = 10000;
+
+    /**
+     * Creates a random access storage builder.
+     *
+     * # Arguments
+     * * `random_access`      - A random access.
+     * * `content_offset`     - A content offset in the random access.
+     * * `value_deserializer` - A deserializer for value objects.
+     *
+     * # Returns
+     * A random access storage builder.
+     */
+    pub fn builder(
+        random_access: Rc<dyn RandomAccess>,
+        content_offset: u64,
+        value_deserializer: ValueDeserializer<Value>,
+    ) -> RandomAccessStorageBuilder<Value> {
+        RandomAccessStorageBuilder::<Value> {
+            random_access,
+            content_offset,
+            value_deserializer,
+            value_cache_capacity: Self::DEFAULT_VALUE_CACHE_CAPACITY,
+        }
+    }
+
+    fn fixed_value_size(&self) -> Result<usize> {
+        let base_check_count = self.base_check_size()?;
+        self.read_u32(size_of::<u32>() * (1 + base_check_count + 1))
+            .map(|v| v as usize)
+    }
+
+    fn ensure_value_cached(&self, value_index: usize) -> Result<()> {
+        if self.value_cache.borrow().has(value_index) {
+            return Ok(());
+        }
+
+        let base_check_count = self.base_check_size()?;
+        let fixed_value_size = self.fixed_value_size()?;
+        // A broken value index or value size must neither make the offset overflow nor allocate
+        // a buffer larger than the random access.
+        let offset = fixed_value_size
+            .checked_mul(value_index)
+            .and_then(|offset| offset.checked_add(size_of::<u32>() * (1 + base_check_count + 2)))
+            .ok_or(RandomAccessStorageError::RegionOutOfRange)?;
+        if (offset as u64).saturating_add(fixed_value_size as u64) > self.random_access.len()? {
+            return Err(RandomAccessStorageError::RegionOutOfRange.into());
+        }
+        let mut serialized = vec![0u8; fixed_value_size];
+        self.read_bytes(offset, &mut serialized)?;
+        if serialized.iter().all(|&b| b == Self::UNINITIALIZED_BYTE) {
+            self.value_cache.borrow_mut().insert(value_index, None);
+        } else {
+            let value = self
+                .value_deserializer
+                .borrow_mut()
+                .deserialize(&serialized)?;
+            self.value_cache
+                .borrow_mut()
+                .insert(value_index, Some(Rc::new(value)));
+        }
+        Ok(())
+    }
+
+    const UNINITIALIZED_BYTE: u8 = 0xFF;
+
+    fn read_bytes(&self, offset: usize, buffer: &mut [u8]) -> Result<()> {
+        let end = offset
+            .checked_add(buffer.len())
+            .and_then(|end| self.content_offset.checked_add(end as u64))
+            .ok_or(RandomAccessStorageError::RegionOutOfRange)?;
+        if end > self.random_access.len()? {
+            return Err(RandomAccessStorageError::RegionOutOfRange.into());
+        }
+        self.random_access
+            .read_at(self.content_offset + offset as u64, buffer)
+    }
+
+    fn read_u32(&self, offset: usize) -> Result<u32> {
+        let mut bytes = [0u8; size_of::<u32>()];
+        self.read_bytes(offset, &mut bytes)?;
+        Ok(u32::from_be_bytes(bytes))
+    }
+}
+
+impl<Value: Clone + Debug + 'static> Storage<Value> for RandomAccessStorage<Value> {
+    fn base_check_size(&self) -> Result<usize> {
+        self.read_u32(0).map(|v| v as usize)
+    }
+
+    fn base_at(&self, base_check_index: usize) -> Result<i32> {
+        let base_check = self.read_u32(size_of::<u32>() * (1 + base_check_index))?;
+        Ok((base_check as i32) >> 8)
+    }
+
+    fn set_base_at(&mut self, _: usize, _: i32) -> Result<()> {
+        unreachable!("Unsupported operation.");
+    }
+
+    fn check_at(&self, base_check_index: usize) -> Result<u8> {
+        let base_check = self.read_u32(size_of::<u32>() * (1 + base_check_index))?;
+        Ok((base_check & 0xFF) as u8)
+    }
+
+    fn set_check_at(&mut self, _: usize, _: u8) -> Result<()> {
+        unreachable!("Unsupported operation.");
+    }
+
+    fn value_count(&self) -> Result<usize> {
+        let base_check_count = self.base_check_size()?;
+        self.read_u32(size_of::<u32>() * (1 + base_check_count))
+            .map(|v| v as usize)
+    }
+
+    fn value_at(&self, value_index: usize) -> Result<Option<Rc<Value>>> {
+        self.ensure_value_cached(value_index)?;
+        let mut cache_ref = self.value_cache.borrow_mut();
+        let Some(value) = cache_ref.at(value_index) else {
+            unreachable!("The value must be cached.")
+        };
+        Ok(value.clone())
+    }
+
+    fn add_value_at(&mut self, _: usize, _: Value) -> Result<()> {
+        unreachable!("Unsupported operation.");
+    }
+
+    fn filling_rate(&self) -> Result<f64> {
+        let base_check_count = self.base_check_size()?;
+        let mut empty_count = 0usize;
+        for i in 0..base_check_count {
+            let base_check = self.read_u32(size_of::<u32>() * (1 + i))?;
+            if base_check == 0x000000FF {
+                empty_count += 1;
+            }
+        }
+        Ok(1.0 - (empty_count as f64) / (base_check_count as f64))
+    }
+
+    #[cfg(feature = "builder")]
+    fn serialize(&self, _: &mut dyn Write, _: &mut ValueSerializer<'_, Value>) -> Result<()> {
+        unreachable!("Unsupported operation.");
+    }
+
+    fn clone_box(&self) -> Box<dyn Storage<Value>> {
+        Box::new(Self {
+            random_access: self.random_access.clone(),
+            content_offset: self.content_offset,
+            value_deserializer: self.value_deserializer.clone(),
+            value_cache: RefCell::new(self.value_cache.borrow().clone()),
+        })
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use crate::error::TrieError;
+    use crate::integer_serializer::IntegerDeserializer;
+    use crate::serializer::Deserializer;
+
+    use super::*;
+
+    #[rustfmt::skip]
+    const SERIALIZED_FIXED_VALUE_SIZE: &[u8] = &[
+        0x00u8, 0x00u8, 0x00u8, 0x02u8,
+        0x00u8, 0x00u8, 0x2Au8, 0xFFu8,
+        0x00u8, 0x00u8, 0xFEu8, 0x18u8,
+        0x00u8, 0x00u8, 0x00u8, 0x05u8,
+        0x00u8, 0x00u8, 0x00u8, 0x04u8,
+        0xFFu8, 0xFFu8, 0xFFu8, 0xFFu8,
+        0x00u8, 0x00u8, 0x00u8, 0x9Fu8,
+        0x00u8, 0x00u8, 0x00u8, 0x0Eu8,
+        0xFFu8, 0xFFu8, 0xFFu8, 0xFFu8,
+        0x00u8, 0x00u8, 0x00u8, 0x03u8,
+    ];
+
+    #[rustfmt::skip]
+    const SERIALIZED: &[u8] = &[
+        0x00u8, 0x00u8, 0x00u8, 0x02u8,
+        0x00u8, 0x00u8, 0x2Au8, 0xFFu8,
+        0x00u8, 0x00u8, 0xFEu8, 0x18u8,
+        0x00u8, 0x00u8, 0x00u8, 0x01u8,
+        0x00u8, 0x00u8, 0x00u8, 0x00u8,
+        0x00u8, 0x00u8, 0x00u8, 0x04u8,
+        0x70u8, 0x69u8, 0x79u8, 0x6Fu8,
+    ];
+
+    #[derive(Debug)]
+    struct XoredBytes {
+        bytes: Vec<u8>,
+        key: u8,
+        read_count: Cell<usize>,
+    }
+
+    impl XoredBytes {
+        fn new(plain: &[u8], key: u8) -> Self {
+            Self {
+                bytes: plain.iter().map(|b| b ^ key).collect(),
+                key,
+                read_count: Cell::new(0),
+            }
+        }
+    }
+
+    impl RandomAccess for XoredBytes {
+        fn read_at(&self, offset: u64, buffer: &mut [u8]) -> Result<()> {
+            self.read_count.set(self.read_count.get() + 1);
+            self.bytes.read_at(offset, buffer)?;
+            buffer.iter_mut().for_each(|b| *b ^= self.key);
+            Ok(())
+        }
+
+        fn len(&self) -> Result<u64> {
+            RandomAccess::len(&self.bytes)
+        }
+    }
+
+    fn value_deserializer() -> ValueDeserializer<u32> {
+        ValueDeserializer::new(Box::new(|serialized| {
+            IntegerDeserializer::<u32>::new(false).deserialize(serialized)
+        }))
+    }
+
+    #[test]
+    fn builder() {
+        {
+            let storage = RandomAccessStorage::builder(
+                Rc::new(SERIALIZED_FIXED_VALUE_SIZE.to_vec()),
+                0,
+                value_deserializer(),
+            )
+            .value_cache_capacity(2)
+            .build();
+
+            assert!(storage.is_ok());
+        }
+        {
+            let storage = RandomAccessStorage::builder(
+                Rc::new(SERIALIZED_FIXED_VALUE_SIZE),
+                0,
+                value_deserializer(),
+            )
+            .build()
+            .unwrap();
+
+            assert_eq!(storage.base_check_size().unwrap(), 2);
+            assert_eq!(*storage.value_at(4).unwrap().unwrap(), 3);
+        }
+        {
+            let storage =
+                RandomAccessStorage::builder(Rc::new(SERIALIZED.to_vec()), 0, value_deserializer())
+                    .build();
+
+            assert!(matches!(
+                storage,
+                Err(TrieError::RandomAccessStorage(
+                    RandomAccessStorageError::ValueSizeNotFixed
+                ))
+            ));
+        }
+        {
+            let storage = RandomAccessStorage::builder(
+                Rc::new(SERIALIZED_FIXED_VALUE_SIZE[..6].to_vec()),
+                0,
+                value_deserializer(),
+            )
+            .build();
+
+            assert!(storage.is_err());
+        }
+    }
+
+    #[test]
+    fn base_check() {
+        let storage = RandomAccessStorage::builder(
+            Rc::new(SERIALIZED_FIXED_VALUE_SIZE.to_vec()),
+            0,
+            value_deserializer(),
+        )
+        .build()
+        .unwrap();
+
+        assert_eq!(storage.base_check_size().unwrap(), 2);
+        assert_eq!(storage.base_at(0).unwrap(), 42);
+        assert_eq!(storage.check_at(0).unwrap(), 0xFF);
+        assert_eq!(storage.base_at(1).unwrap(), 0xFE);
+        assert_eq!(storage.check_at(1).unwrap(), 0x18);
+        assert!((storage.filling_rate().unwrap() - 1.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn value_at() {
+        let mut plain = vec![0x01, 0x23];
+        plain.extend_from_slice(SERIALIZED_FIXED_VALUE_SIZE);
+        let random_access = Rc::new(XoredBytes::new(&plain, 0x5A));
+        let storage = RandomAccessStorage::builder(random_access.clone(), 2, value_deserializer())
+            .build()
+            .unwrap();
+
+        assert_eq!(storage.value_count().unwrap(), 5);
+        assert!(storage.value_at(0).unwrap().is_none());
+        assert_eq!(*storage.value_at(1).unwrap().unwrap(), 159);
+        assert_eq!(*storage.value_at(2).unwrap().unwrap(), 14);
+        assert!(storage.value_at(3).unwrap().is_none());
+        assert_eq!(*storage.value_at(4).unwrap().unwrap(), 3);
+        assert!(storage.value_at(5).is_err());
+        assert!(storage.value_at(usize::MAX).is_err());
+
+        let read_count = random_access.read_count.get();
+        assert_eq!(*storage.value_at(1).unwrap().unwrap(), 159);
+        assert_eq!(random_access.read_count.get(), read_count);
+    }
+}
@@ -0,0 +1,373 @@
+/*!
+ * A section table.
+ *
+ * Copyright (C) 2023-2025 kaoru  <https://www.tetengo.org/>
+ */
+
+#[cfg(feature = "builder")]
+use std::io::Write;
+use std::io::{Read, Seek, SeekFrom};
+
+use crate::error::Result;
+use crate::storage::SerializationSection;
+
+/**
+ * A section table error.
+ */
+#[derive(Clone, Copy, Debug, thiserror::Error)]
+pub enum SectionTableError {
+    /**
+     * The magic number is invalid.
+     */
+    #[error("the magic number is invalid")]
+    InvalidMagic,
+
+    /**
+     * The format version is not supported.
+     */
+    #[error("the format version is not supported")]
+    UnsupportedVersion,
+
+    /**
+     * The section ID is unknown.
+     */
+    #[error("the section ID is unknown")]
+    UnknownSection,
+
+    /**
+     * The section appears more than once.
+     */
+    #[error("the section appears more than once")]
+    DuplicateSection,
+
+    /**
+     * A required section is missing.
+     */
+    #[error("a required section is missing")]
+    MissingSection,
+}
+
+/**
+ * A section table.
+ *
+ * A sectioned serialization starts with the table, followed by the sections. The table consists
+ * of a magic number, a format version, a section count and the entries of the sections. An entry
+ * is a section ID, an offset and a size of the section. The offset is counted from the start of
+ * the table. All the integers are in big endian.
+ *
+ * The sections are addressed by the offsets, so that the readers can load only the sections
+ * they need.
+ */
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SectionTable {
+    origin: u64,
+    entries: Vec<(SerializationSection, u64, u64)>,
+}
+
+impl SectionTable {
+    const MAGIC: &'static [u8; 4] = b"TTSC";
+
+    const VERSION: u32 = 1;
+
+    #[cfg(feature = "builder")]
+    const HEADER_SIZE: u64 = 4 + 4 + 4;
+
+    #[cfg(feature = "builder")]
+    const ENTRY_SIZE: u64 = 4 + 8 + 8;
+
+    /**
+     * Writes sections with a section table.
+     *
+     * The sections are written in the given order.
+     *
+     * # Arguments
+     * * `writer`   - A writer.
+     * * `sections` - Pairs of a section and its content.
+     *
+     * # Errors
+     * * When a section appears more than once.
+     * * When it fails to write.
+     */
+    #[cfg(feature = "builder")]
+    pub fn write(writer: &mut dyn Write, sections: &[(SerializationSection, &[u8])]) -> Result<()> {
+        for (i, (section, _)) in sections.iter().enumerate() {
+            if sections[..i].iter().any(|(other, _)| other == section) {
+                return Err(SectionTableError::DuplicateSection.into());
+            }
+        }
+
+        writer.write_all(Self::MAGIC)?;
+        writer.write_all(&Self::VERSION.to_be_bytes())?;
+        writer.write_all(&(sections.len() as u32).to_be_bytes())?;
+        let mut offset = Self::HEADER_SIZE + Self::ENTRY_SIZE * sections.len() as u64;
+        for (section, content) in sections {
+            writer.write_all(&section_id_of(*section).to_be_bytes())?;
+            writer.write_all(&offset.to_be_bytes())?;
+            writer.write_all(&(content.len() as u64).to_be_bytes())?;
+            offset += content.len() as u64;
+        }
+        for (_, content) in sections {
+            writer.write_all(content)?;
+        }
+        Ok(())
+    }
+
+    /**
+     * Creates a section table.
+     *
+     * The table starts at the current position of the reader.
+     *
+     * # Arguments
+     * * `reader` - A reader.
+     *
+     * # Errors
+     * * When the table is broken.
+     * * When it fails to read.
+     */
+    pub fn new_with_reader<R: Read + Seek>(reader: &mut R) -> Result<Self> {
+        let origin = reader.stream_position()?;
+
+        let mut magic = [0u8; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != Self::MAGIC {
+            return Err(SectionTableError::InvalidMagic.into());
+        }
+        if read_u32(reader)? != Self::VERSION {
+            return Err(SectionTableError::UnsupportedVersion.into());
+        }
+
+        let count = read_u32(reader)? as usize;
+        let mut entries = Vec::<(SerializationSection, u64, u64)>::with_capacity(count);
+        for _ in 0..count {
+            let section = section_of(read_u32(reader)?)?;
+            if entries.iter().any(|&(other, _, _)| other == section) {
+                return Err(SectionTableError::DuplicateSection.into());
+            }
+            let offset = read_u64(reader)?;
+            let size = read_u64(reader)?;
+            entries.push((section, offset, size));
+        }
+        Ok(Self { origin, entries })
+    }
+
+    /**
+     * Returns the sections.
+     *
+     * # Returns
+     * The sections in the order they are written.
+     */
+    pub fn sections(&self) -> Vec<SerializationSection> {
+        self.entries
+            .iter()
+            .map(|&(section, _, _)| section)
+            .collect()
+    }
+
+    /**
+     * Returns the position and the size of a section.
+     *
+     * # Arguments
+     * * `section` - A section.
+     *
+     * # Returns
+     * The pair of the position in the reader and the size of the section. Or None when the table
+     * does not have the section.
+     */
+    pub fn position_and_size(&self, section: SerializationSection) -> Option<(u64, u64)> {
+        self.entries
+            .iter()
+            .find(|&&(other, _, _)| other == section)
+            .map(|&(_, offset, size)| (self.origin + offset, size))
+    }
+
+    /**
+     * Reads the content of a section.
+     *
+     * # Arguments
+     * * `reader`  - The reader from which this table is read.
+     * * `section` - A section.
+     *
+     * # Returns
+     * The content of the section. Or None when the table does not have the section.
+     *
+     * # Errors
+     * * When it fails to read.
+     */
+    pub fn read_section<R: Read + Seek>(
+        &self,
+        reader: &mut R,
+        section: SerializationSection,
+    ) -> Result<Option<Vec<u8>>> {
+        let Some((position, size)) = self.position_and_size(section) else {
+            return Ok(None);
+        };
+        let _ = reader.seek(SeekFrom::Start(position))?;
+        let mut content = vec![0u8; size as usize];
+        reader.read_exact(&mut content)?;
+        Ok(Some(content))
+    }
+}
+
+const AUXILIARY_SECTION_ID_BASE: u32 = 0x00010000;
+
+const TRIE_SECTION_ID_BASE: u32 = 0x00020000;
+
+#[cfg(feature = "builder")]
+const fn section_id_of(section: SerializationSection) -> u32 {
+    match section {
+        SerializationSection::BaseCheckArray => 1,
+        SerializationSection::ValueArray => 2,
+        SerializationSection::KeyMetadata => 3,
+        SerializationSection::KeyTransform => 4,
+        SerializationSection::TrieNames => 5,
+        SerializationSection::BloomFilter => 6,
+        SerializationSection::Trie(index) => TRIE_SECTION_ID_BASE | index as u32,
+        SerializationSection::Auxiliary(id) => AUXILIARY_SECTION_ID_BASE | id as u32,
+    }
+}
+
+fn section_of(section_id: u32) -> Result<SerializationSection> {
+    match section_id {
+        1 => Ok(SerializationSection::BaseCheckArray),
+        2 => Ok(SerializationSection::ValueArray),
+        3 => Ok(SerializationSection::KeyMetadata),
+        4 => Ok(SerializationSection::KeyTransform),
+        5 => Ok(SerializationSection::TrieNames),
+        6 => Ok(SerializationSection::BloomFilter),
+        _ if section_id & !0xFFFF == TRIE_SECTION_ID_BASE => {
+            Ok(SerializationSection::Trie((section_id & 0xFFFF) as u16))
+        }
+        _ if section_id & !0xFFFF == AUXILIARY_SECTION_ID_BASE => Ok(
+            SerializationSection::Auxiliary((section_id & 0xFFFF) as u16),
+        ),
+        _ => Err(SectionTableError::UnknownSection.into()),
+    }
+}
+
+fn read_u32(reader: &mut dyn Read) -> Result<u32> {
+    let mut bytes = [0u8; size_of::<u32>()];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_be_bytes(bytes))
+}
+
+fn read_u64(reader: &mut dyn Read) -> Result<u64> {
+    let mut bytes = [0u8; size_of::<u64>()];
+    reader.read_exact(&mut bytes)?;
+    Ok(u64::from_be_bytes(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use crate::error::TrieError;
+
+    use super::*;
+
+    #[rustfmt::skip]
+    const SERIALIZED: &[u8] = &[
+        b'T', b'T', b'S', b'C',
+        0x00, 0x00, 0x00, 0x01,
+        0x00, 0x00, 0x00, 0x02,
+        0x00, 0x00, 0x00, 0x01,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x34,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02,
+        0x00, 0x01, 0x00, 0x2A,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x36,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x03,
+        0x12, 0x34,
+        0x56, 0x78, 0x9A,
+    ];
+
+    #[cfg(feature = "builder")]
+    #[test]
+    fn write() {
+        {
+            let mut writer = Cursor::new(Vec::new());
+            SectionTable::write(
+                &mut writer,
+                &[
+                    (SerializationSection::BaseCheckArray, &[0x12, 0x34]),
+                    (SerializationSection::Auxiliary(42), &[0x56, 0x78, 0x9A]),
+                ],
+            )
+            .unwrap();
+
+            assert_eq!(writer.get_ref().as_slice(), SERIALIZED);
+        }
+        {
+            let mut writer = Cursor::new(Vec::new());
+            let result = SectionTable::write(
+                &mut writer,
+                &[
+                    (SerializationSection::ValueArray, &[0x12]),
+                    (SerializationSection::ValueArray, &[0x34]),
+                ],
+            );
+
+            assert!(matches!(
+                result,
+                Err(TrieError::SectionTable(SectionTableError::DuplicateSection))
+            ));
+        }
+    }
+
+    #[test]
+    fn new_with_reader() {
+        {
+            let mut reader = Cursor::new([&[0xFF, 0xFF], SERIALIZED].concat());
+            let _ = reader.seek(SeekFrom::Start(2)).unwrap();
+            let table = SectionTable::new_with_reader(&mut reader).unwrap();
+
+            assert_eq!(
+                table.sections(),
+                vec![
+                    SerializationSection::BaseCheckArray,
+                    SerializationSection::Auxiliary(42)
+                ]
+            );
+            assert_eq!(
+                table.position_and_size(SerializationSection::Auxiliary(42)),
+                Some((0x38, 3))
+            );
+            assert!(table
+                .position_and_size(SerializationSection::ValueArray)
+                .is_none());
+        }
+        {
+            let mut reader = Cursor::new(&SERIALIZED[..SERIALIZED.len() - 5]);
+            let mut broken = SERIALIZED.to_vec();
+            broken[0] = b'X';
+
+            assert!(SectionTable::new_with_reader(&mut reader).is_ok());
+            assert!(matches!(
+                SectionTable::new_with_reader(&mut Cursor::new(broken)),
+                Err(TrieError::SectionTable(SectionTableError::InvalidMagic))
+            ));
+            assert!(SectionTable::new_with_reader(&mut Cursor::new(&SERIALIZED[..20])).is_err());
+        }
+    }
+
+    #[test]
+    fn read_section() {
+        let mut reader = Cursor::new(SERIALIZED);
+        let table = SectionTable::new_with_reader(&mut reader).unwrap();
+
+        assert_eq!(
+            table
+                .read_section(&mut reader, SerializationSection::Auxiliary(42))
+                .unwrap(),
+            Some(vec![0x56, 0x78, 0x9A])
+        );
+        assert_eq!(
+            table
+                .read_section(&mut reader, SerializationSection::BaseCheckArray)
+                .unwrap(),
+            Some(vec![0x12, 0x34])
+        );
+        assert!(table
+            .read_section(&mut reader, SerializationSection::ValueArray)
+            .unwrap()
+            .is_none());
+    }
+}
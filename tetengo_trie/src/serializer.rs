@@ -6,7 +6,8 @@
 
 use std::error;
 
-use anyhow::Result;
+use crate::error::Result;
+use crate::integer_serializer::IntegerDeserialationError;
 
 /**
  * A serializer.
@@ -95,3 +96,36 @@ pub trait DeserializerOf<Object> {
     /// The deserializer type.
     type Type;
 }
+
+pub(crate) fn escape_fe(bytes: Vec<u8>) -> Vec<u8> {
+    bytes
+        .into_iter()
+        .flat_map(|b| {
+            if b == 0x00u8 {
+                vec![0xFEu8]
+            } else if b == 0xFDu8 || b == 0xFEu8 {
+                vec![0xFDu8, b]
+            } else {
+                vec![b]
+            }
+        })
+        .collect()
+}
+
+pub(crate) fn unescape_fe(serialized: &[u8]) -> Result<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(serialized.len());
+    let mut serialized_iter = serialized.iter();
+    while let Some(byte) = serialized_iter.next() {
+        if *byte == 0xFDu8 {
+            match serialized_iter.next() {
+                Some(byte2) if *byte2 == 0xFDu8 || *byte2 == 0xFEu8 => bytes.push(*byte2),
+                _ => return Err(IntegerDeserialationError::InvalidSerializedContent.into()),
+            }
+        } else if *byte == 0xFEu8 {
+            bytes.push(0x00u8);
+        } else {
+            bytes.push(*byte);
+        }
+    }
+    Ok(bytes)
+}
@@ -0,0 +1,289 @@
+/*!
+ * A shared memory.
+ *
+ * Copyright (C) 2023-2025 kaoru  <https://www.tetengo.org/>
+ */
+
+use std::env;
+use std::fmt::Debug;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use tempfile::NamedTempFile;
+
+use crate::error::Result;
+use crate::file_mapping::FileMapping;
+use crate::mmap_storage::{MmapStorage, MmapStorageBuilder};
+use crate::value_serializer::ValueDeserializer;
+
+/**
+ * A shared memory error.
+ */
+#[derive(Clone, Copy, Debug, thiserror::Error)]
+pub enum SharedMemoryError {
+    /**
+     * The name is invalid.
+     */
+    #[error("the name is invalid")]
+    InvalidName,
+}
+
+/**
+ * A shared memory.
+ *
+ * It is a named segment in which a serialized trie is placed, so that the processes on the same
+ * host can share one copy of the trie through mmap storages. The segment is a file in the
+ * shared memory file system (`/dev/shm`) when it is available, or in the temporary directory
+ * otherwise.
+ *
+ * The segment created by `SharedMemory::create()` is removed when the creator is dropped. The
+ * processes which have already mapped it can continue to use it.
+ */
+#[derive(Debug)]
+pub struct SharedMemory {
+    name: String,
+    path: PathBuf,
+    file_mapping: Rc<FileMapping>,
+    owner: bool,
+}
+
+impl SharedMemory {
+    /**
+     * Creates a shared memory.
+     *
+     * The content is written to a temporary file and then published with the name at once, so
+     * that the other processes never open a partially written segment.
+     *
+     * # Arguments
+     * * `name`  - A name.
+     * * `write` - A function which writes the content, e.g. a serialized storage.
+     *
+     * # Errors
+     * * When the name is invalid.
+     * * When a segment with the name already exists.
+     * * When it fails to write the content.
+     */
+    pub fn create(name: &str, write: impl FnOnce(&mut dyn Write) -> Result<()>) -> Result<Self> {
+        let path = Self::path_of(name)?;
+
+        let temporary_file = NamedTempFile::new_in(Self::directory())?;
+        {
+            let mut writer = BufWriter::new(temporary_file.as_file());
+            write(&mut writer)?;
+            writer.flush()?;
+        }
+        let file = temporary_file
+            .persist_noclobber(&path)
+            .map_err(|e| e.error)?;
+
+        Ok(Self {
+            name: String::from(name),
+            path,
+            file_mapping: Rc::new(FileMapping::new(file)?),
+            owner: true,
+        })
+    }
+
+    /**
+     * Opens a shared memory.
+     *
+     * # Arguments
+     * * `name` - A name.
+     *
+     * # Errors
+     * * When the name is invalid.
+     * * When it fails to open the segment.
+     */
+    pub fn open(name: &str) -> Result<Self> {
+        let path = Self::path_of(name)?;
+        let file = File::open(&path)?;
+        Ok(Self {
+            name: String::from(name),
+            path,
+            file_mapping: Rc::new(FileMapping::new(file)?),
+            owner: false,
+        })
+    }
+
+    /**
+     * Returns the name.
+     *
+     * # Returns
+     * The name.
+     */
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /**
+     * Returns the path of the segment.
+     *
+     * # Returns
+     * The path of the segment.
+     */
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /**
+     * Returns the file mapping.
+     *
+     * # Returns
+     * The file mapping.
+     */
+    pub fn file_mapping(&self) -> Rc<FileMapping> {
+        self.file_mapping.clone()
+    }
+
+    /**
+     * Returns a builder of an mmap storage on the whole segment.
+     *
+     * # Type Parameters
+     * * `Value` - A value type.
+     *
+     * # Arguments
+     * * `value_deserializer` - A deserializer for value objects.
+     *
+     * # Returns
+     * A builder of an mmap storage.
+     */
+    pub fn storage_builder<Value: Clone + Debug + 'static>(
+        &self,
+        value_deserializer: ValueDeserializer<Value>,
+    ) -> MmapStorageBuilder<Value> {
+        MmapStorage::builder(
+            self.file_mapping(),
+            0,
+            self.file_mapping.size(),
+            value_deserializer,
+        )
+    }
+
+    fn directory() -> PathBuf {
+        let shm = Path::new("/dev/shm");
+        if shm.is_dir() {
+            shm.to_path_buf()
+        } else {
+            env::temp_dir()
+        }
+    }
+
+    fn path_of(name: &str) -> Result<PathBuf> {
+        if name.is_empty()
+            || name.starts_with('.')
+            || name.contains(|c: char| c == '/' || c == '\\' || c.is_control())
+        {
+            return Err(SharedMemoryError::InvalidName.into());
+        }
+        Ok(Self::directory().join(name))
+    }
+}
+
+impl Drop for SharedMemory {
+    fn drop(&mut self) {
+        if self.owner {
+            // The segment may have been removed by another process.
+            fs::remove_file(&self.path).unwrap_or_default();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "builder")]
+    use std::process;
+
+    use crate::error::TrieError;
+    #[cfg(feature = "builder")]
+    use crate::integer_serializer::{IntegerDeserializer, IntegerSerializer};
+    #[cfg(feature = "builder")]
+    use crate::serializer::{Deserializer, Serializer};
+    #[cfg(feature = "builder")]
+    use crate::string_serializer::StrSerializer;
+    #[cfg(feature = "builder")]
+    use crate::trie::Trie;
+    #[cfg(feature = "builder")]
+    use crate::value_serializer::ValueSerializer;
+
+    use super::*;
+
+    #[cfg(feature = "builder")]
+    fn unique_name(suffix: &str) -> String {
+        format!("tetengo_trie_test_{}_{}", process::id(), suffix)
+    }
+
+    #[cfg(feature = "builder")]
+    fn create_shared_memory(name: &str) -> Result<SharedMemory> {
+        let trie = Trie::<&str, i32>::builder()
+            .elements([("kumamoto", 42), ("tamana", 24)].to_vec())
+            .key_serializer(StrSerializer::new(true))
+            .build()?;
+        SharedMemory::create(name, |writer| {
+            let mut value_serializer = ValueSerializer::new(
+                Box::new(|value: &i32| IntegerSerializer::new(false).serialize(value)),
+                size_of::<i32>(),
+            );
+            trie.storage().serialize(writer, &mut value_serializer)
+        })
+    }
+
+    #[cfg(feature = "builder")]
+    fn value_deserializer() -> ValueDeserializer<i32> {
+        ValueDeserializer::new(Box::new(|serialized: &[u8]| {
+            IntegerDeserializer::new(false).deserialize(serialized)
+        }))
+    }
+
+    #[cfg(feature = "builder")]
+    #[test]
+    fn create() {
+        let name = unique_name("create");
+        let shared_memory = create_shared_memory(&name).unwrap();
+
+        assert_eq!(shared_memory.name(), name);
+        assert!(shared_memory.path().exists());
+        assert!(create_shared_memory(&name).is_err());
+
+        let path = shared_memory.path().to_path_buf();
+        drop(shared_memory);
+        assert!(!path.exists());
+    }
+
+    #[cfg(feature = "builder")]
+    #[test]
+    fn open() {
+        let name = unique_name("open");
+        let created = create_shared_memory(&name).unwrap();
+
+        {
+            let opened = SharedMemory::open(&name).unwrap();
+            let storage = opened
+                .storage_builder(value_deserializer())
+                .build()
+                .unwrap();
+            let trie = Trie::<&str, i32>::builder_with_storage(Box::new(storage))
+                .key_serializer(StrSerializer::new(true))
+                .build()
+                .unwrap();
+
+            assert_eq!(*trie.find(&"tamana").unwrap().unwrap(), 24);
+            assert!(trie.find(&"uto").unwrap().is_none());
+        }
+        assert!(created.path().exists());
+        drop(created);
+
+        assert!(SharedMemory::open(&name).is_err());
+    }
+
+    #[test]
+    fn invalid_name() {
+        for name in ["", "..", "foo/bar", ".hidden"] {
+            assert!(matches!(
+                SharedMemory::open(name),
+                Err(TrieError::SharedMemory(SharedMemoryError::InvalidName))
+            ));
+        }
+    }
+}
@@ -6,35 +6,71 @@
 
 use std::any::Any;
 use std::fmt::Debug;
-use std::io::{Read, Write};
+use std::io::Read;
+#[cfg(feature = "builder")]
+use std::io::Write;
 use std::rc::Rc;
+use std::sync::Arc;
 
-use anyhow::Result;
-
+use crate::double_array::VACANT_CHECK_VALUE;
+use crate::error::Result;
+use crate::key_transform::KeyTransform;
 use crate::memory_storage::MemoryStorage;
+#[cfg(feature = "builder")]
+use crate::storage::SerializationProgress;
 use crate::storage::Storage;
-use crate::value_serializer::{ValueDeserializer, ValueSerializer};
+use crate::value_serializer::ValueDeserializer;
+#[cfg(feature = "builder")]
+use crate::value_serializer::ValueSerializer;
 
 /**
  * A shared storage.
  *
+ * It holds a copy of a storage, and its clones share the copy instead of copying the whole
+ * base-check array, so that the tries cloned from one loaded dictionary are cheap.
+ * The copy is copied on write: a modification of a shared storage copies it once, and does not
+ * affect the others.
+ *
+ * The copy is shared with `Arc`, and a shared storage is `Send` and `Sync` when the value type
+ * is, so that its clones can be moved to other threads and a trie can be built on each of them.
+ * Each call to `value_at()` returns a clone of the value object.
+ *
  * # Type Parameters
  * * `Value` - A value type.
  */
-#[derive(Debug, Default)]
+#[derive(Clone, Debug)]
 pub struct SharedStorage<Value: Clone> {
-    entity: Rc<MemoryStorage<Value>>,
+    entity: Arc<Entity<Value>>,
 }
 
-impl<Value: Clone + 'static> SharedStorage<Value> {
+impl<Value: Clone + Debug + 'static> SharedStorage<Value> {
     /**
      * Creates a shared storage.
      */
     pub fn new() -> Self {
-        let entity = MemoryStorage::<Value>::new();
         Self {
-            entity: Rc::new(entity),
+            entity: Arc::new(Entity::new()),
+        }
+    }
+
+    /**
+     * Creates a shared storage with a copy of a storage.
+     *
+     * When the storage is a shared storage, the copy is not made but shared.
+     *
+     * # Arguments
+     * * `storage` - A storage.
+     *
+     * # Errors
+     * * When it fails to read the storage.
+     */
+    pub fn new_with_storage(storage: &dyn Storage<Value>) -> Result<Self> {
+        if let Some(shared_storage) = storage.downcast_ref::<Self>() {
+            return Ok(shared_storage.clone());
         }
+        Ok(Self {
+            entity: Arc::new(Entity::new_with_storage(storage)?),
+        })
     }
 
     /**
@@ -51,65 +87,123 @@ impl<Value: Clone + 'static> SharedStorage<Value> {
         reader: &mut dyn Read,
         value_deserializer: &mut ValueDeserializer<Value>,
     ) -> Result<Self> {
-        let entity = MemoryStorage::<Value>::new_with_reader(reader, value_deserializer)?;
-        Ok(Self {
-            entity: Rc::new(entity),
-        })
+        let storage = MemoryStorage::<Value>::new_with_reader(reader, value_deserializer)?;
+        Self::new_with_storage(&storage)
+    }
+
+    /**
+     * Returns true when the copy is shared with other shared storages.
+     *
+     * # Returns
+     * True when the copy is shared.
+     */
+    pub fn is_shared(&self) -> bool {
+        Arc::strong_count(&self.entity) > 1
+    }
+
+    fn entity_mut(&mut self) -> &mut Entity<Value> {
+        Arc::make_mut(&mut self.entity)
+    }
+}
+
+impl<Value: Clone + Debug + 'static> Default for SharedStorage<Value> {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
 impl<Value: Clone + Debug + 'static> Storage<Value> for SharedStorage<Value> {
     fn base_check_size(&self) -> Result<usize> {
-        self.entity.base_check_size()
+        Ok(self.entity.base_check_array.len())
     }
 
     fn base_at(&self, base_check_index: usize) -> Result<i32> {
-        self.entity.base_at(base_check_index)
+        Ok(self.entity.base_check_at(base_check_index) as i32 >> 8i32)
     }
 
     fn set_base_at(&mut self, base_check_index: usize, base: i32) -> Result<()> {
-        let entity = Rc::get_mut(&mut self.entity).unwrap();
-        entity.set_base_at(base_check_index, base)
+        let element = self.entity_mut().base_check_at_mut(base_check_index);
+        *element &= 0x000000FF;
+        *element |= (base as u32) << 8;
+        Ok(())
     }
 
     fn check_at(&self, base_check_index: usize) -> Result<u8> {
-        self.entity.check_at(base_check_index)
+        Ok((self.entity.base_check_at(base_check_index) & 0xFF) as u8)
     }
 
     fn set_check_at(&mut self, base_check_index: usize, check: u8) -> Result<()> {
-        let entity = Rc::get_mut(&mut self.entity).unwrap();
-        entity.set_check_at(base_check_index, check)
+        let element = self.entity_mut().base_check_at_mut(base_check_index);
+        *element &= 0xFFFFFF00;
+        *element |= check as u32;
+        Ok(())
     }
 
     fn value_count(&self) -> Result<usize> {
-        self.entity.value_count()
+        Ok(self.entity.value_array.len())
     }
 
     fn value_at(&self, value_index: usize) -> Result<Option<Rc<Value>>> {
-        self.entity.value_at(value_index)
+        Ok(self
+            .entity
+            .value_array
+            .get(value_index)
+            .and_then(Option::as_ref)
+            .map(|value| Rc::new(value.clone())))
     }
 
     fn add_value_at(&mut self, value_index: usize, value: Value) -> Result<()> {
-        let entity = Rc::get_mut(&mut self.entity).unwrap();
-        entity.add_value_at(value_index, value)
+        let value_array = &mut self.entity_mut().value_array;
+        if value_index >= value_array.len() {
+            value_array.resize(value_index + 1, None);
+        }
+        value_array[value_index] = Some(value);
+        Ok(())
     }
 
     fn filling_rate(&self) -> Result<f64> {
-        self.entity.filling_rate()
+        let base_check_array = &self.entity.base_check_array;
+        let empty_count = base_check_array
+            .iter()
+            .filter(|&&e| e == 0x000000FFu32)
+            .count();
+        Ok(1.0 - (empty_count as f64) / (base_check_array.len() as f64))
     }
 
+    #[cfg(feature = "builder")]
     fn serialize(
         &self,
         writer: &mut dyn Write,
         value_serializer: &mut ValueSerializer<'_, Value>,
     ) -> Result<()> {
-        self.entity.serialize(writer, value_serializer)
+        self.entity
+            .to_memory_storage()?
+            .serialize(writer, value_serializer)
+    }
+
+    #[cfg(feature = "builder")]
+    fn serialize_with_progress(
+        &self,
+        writer: &mut dyn Write,
+        value_serializer: &mut ValueSerializer<'_, Value>,
+        progress: &mut dyn FnMut(&SerializationProgress),
+    ) -> Result<()> {
+        self.entity
+            .to_memory_storage()?
+            .serialize_with_progress(writer, value_serializer, progress)
+    }
+
+    fn verify_key_transform(&self, key_transform: Option<&KeyTransform>) -> Result<()> {
+        match &self.entity.key_transform_id {
+            Some(key_transform_id) => {
+                KeyTransform::verify_id(key_transform, key_transform_id.as_deref())
+            }
+            None => Ok(()),
+        }
     }
 
     fn clone_box(&self) -> Box<dyn Storage<Value>> {
-        Box::new(Self {
-            entity: self.entity.clone(),
-        })
+        Box::new(self.clone())
     }
 
     fn as_any(&self) -> &dyn Any {
@@ -121,14 +215,88 @@ impl<Value: Clone + Debug + 'static> Storage<Value> for SharedStorage<Value> {
     }
 }
 
+// The copy of a storage. It owns plain arrays, so that it can be shared among threads.
+#[derive(Clone, Debug)]
+struct Entity<Value> {
+    base_check_array: Vec<u32>,
+    value_array: Vec<Option<Value>>,
+    key_transform_id: Option<Option<String>>,
+}
+
+impl<Value: Clone + Debug + 'static> Entity<Value> {
+    fn new() -> Self {
+        Self {
+            base_check_array: vec![VACANT_CHECK_VALUE as u32],
+            value_array: Vec::new(),
+            key_transform_id: None,
+        }
+    }
+
+    fn new_with_storage(storage: &dyn Storage<Value>) -> Result<Self> {
+        let base_check_array = (0..storage.base_check_size()?)
+            .map(|i| Ok(((storage.base_at(i)? as u32) << 8) | storage.check_at(i)? as u32))
+            .collect::<Result<Vec<_>>>()?;
+        let value_array = (0..storage.value_count()?)
+            .map(|i| Ok(storage.value_at(i)?.map(|value| value.as_ref().clone())))
+            .collect::<Result<Vec<_>>>()?;
+        let key_transform_id = storage
+            .downcast_ref::<MemoryStorage<Value>>()
+            .and_then(MemoryStorage::key_transform_id)
+            .map(|key_transform_id| key_transform_id.map(str::to_owned));
+        Ok(Self {
+            base_check_array,
+            value_array,
+            key_transform_id,
+        })
+    }
+
+    // An element out of the array is vacant, as the one of a grown memory storage is.
+    fn base_check_at(&self, base_check_index: usize) -> u32 {
+        self.base_check_array
+            .get(base_check_index)
+            .copied()
+            .unwrap_or(VACANT_CHECK_VALUE as u32)
+    }
+
+    fn base_check_at_mut(&mut self, base_check_index: usize) -> &mut u32 {
+        if base_check_index >= self.base_check_array.len() {
+            self.base_check_array
+                .resize(base_check_index + 1, VACANT_CHECK_VALUE as u32);
+        }
+        &mut self.base_check_array[base_check_index]
+    }
+
+    #[cfg(feature = "builder")]
+    fn to_memory_storage(&self) -> Result<MemoryStorage<Value>> {
+        let mut storage = MemoryStorage::new();
+        for (i, &element) in self.base_check_array.iter().enumerate() {
+            storage.set_base_at(i, element as i32 >> 8i32)?;
+            storage.set_check_at(i, (element & 0xFF) as u8)?;
+        }
+        for (i, value) in self.value_array.iter().enumerate() {
+            if let Some(value) = value {
+                storage.add_value_at(i, value.clone())?;
+            }
+        }
+        Ok(storage)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::io::Cursor;
     use std::sync::LazyLock;
-
-    use crate::double_array::VACANT_CHECK_VALUE;
-    use crate::serializer::{Deserializer, Serializer};
-    use crate::string_serializer::{StrSerializer, StringDeserializer};
+    #[cfg(feature = "builder")]
+    use std::thread;
+
+    use crate::serializer::Deserializer;
+    #[cfg(feature = "builder")]
+    use crate::serializer::Serializer;
+    #[cfg(feature = "builder")]
+    use crate::string_serializer::StrSerializer;
+    use crate::string_serializer::StringDeserializer;
+    #[cfg(feature = "builder")]
+    use crate::trie::Trie;
 
     use super::*;
 
@@ -218,8 +386,8 @@ mod tests {
             assert!(storage.base_check_size().unwrap() >= 1);
         }
         {
-            let storage = SharedStorage::<u32>::new();
-            let _ = storage.base_at(42).unwrap();
+            let mut storage = SharedStorage::<u32>::new();
+            storage.set_base_at(42, 0).unwrap();
             assert!(storage.base_check_size().unwrap() >= 43);
         }
     }
@@ -318,6 +486,7 @@ mod tests {
         assert!((storage.filling_rate().unwrap() - 3.0 / 9.0).abs() < 0.1);
     }
 
+    #[cfg(feature = "builder")]
     #[test]
     fn serialize() {
         let mut storage = SharedStorage::<String>::new();
@@ -362,9 +531,9 @@ mod tests {
         assert_eq!(serialized, &EXPECTED);
     }
 
-    impl<Value: Clone> SharedStorage<Value> {
+    impl<Value: Clone + Debug + 'static> SharedStorage<Value> {
         fn shared_with(&self, another: &SharedStorage<Value>) -> bool {
-            Rc::ptr_eq(&self.entity, &another.entity)
+            Arc::ptr_eq(&self.entity, &another.entity)
         }
     }
 
@@ -376,25 +545,102 @@ mod tests {
         storage.set_base_at(1, 0xFE).unwrap();
         storage.set_check_at(1, 24).unwrap();
 
-        let clone = storage.clone_box();
+        let mut clone = storage.clone_box();
 
         let clone_as_shared_storage = clone.downcast_ref::<SharedStorage<u32>>().unwrap();
         assert!(clone_as_shared_storage.shared_with(&storage));
+        assert!(storage.is_shared());
 
         let base_check_array = base_check_array_of(clone.as_ref());
 
         const EXPECTED: &[u32] = &[0x00002AFFu32, 0x0000FE18u32];
         assert_eq!(base_check_array, EXPECTED);
 
-        // Rust forbids to modify the object shared with others.
-        // clone.set_base_at(0, 2424);
-        // clone.set_check_at(5, 42);
+        clone.set_base_at(0, 2424).unwrap();
+        clone.set_check_at(5, 42).unwrap();
+
+        let clone_as_shared_storage = clone.downcast_ref::<SharedStorage<u32>>().unwrap();
+        assert!(!clone_as_shared_storage.shared_with(&storage));
+        assert!(!storage.is_shared());
+
+        assert_eq!(clone.base_at(0).unwrap(), 2424);
+        assert_eq!(clone.check_at(5).unwrap(), 42);
+
+        assert_eq!(storage.base_at(0).unwrap(), 42);
+        assert_eq!(storage.check_at(5).unwrap(), VACANT_CHECK_VALUE);
+    }
+
+    #[test]
+    fn new_with_storage() {
+        let mut inner = MemoryStorage::<u32>::new();
+        inner.set_base_at(0, 42).unwrap();
+        inner.add_value_at(3, 24).unwrap();
+
+        let storage = SharedStorage::new_with_storage(&inner).unwrap();
 
-        // assert_eq!(clone.base_at(0), 2424);
-        // assert_eq!(clone.check_at(5), 42);
+        assert_eq!(storage.base_at(0).unwrap(), 42);
+        assert_eq!(*storage.value_at(3).unwrap().unwrap(), 24);
+        assert!(!storage.is_shared());
+
+        let another = SharedStorage::new_with_storage(&storage).unwrap();
+
+        assert!(another.shared_with(&storage));
+    }
+
+    #[cfg(feature = "builder")]
+    #[test]
+    fn verify_key_transform() {
+        let lowercase = KeyTransform::new("lowercase", <[u8]>::to_ascii_lowercase);
+        {
+            let storage = SharedStorage::<u32>::new();
+
+            assert!(storage.verify_key_transform(None).is_ok());
+            assert!(storage.verify_key_transform(Some(&lowercase)).is_ok());
+        }
+        {
+            let mut inner = MemoryStorage::<u32>::new();
+            inner.set_base_at(0, 42).unwrap();
+            let mut value_serializer =
+                ValueSerializer::<u32>::new(Box::new(|_| vec![0x00, 0x00, 0x00, 0x00]), 4);
+            let mut writer = Cursor::new(Vec::<u8>::new());
+            inner
+                .serialize_sections(&mut writer, &mut value_serializer, &[])
+                .unwrap();
+            let loaded = MemoryStorage::<u32>::new_with_sections(
+                &mut Cursor::new(writer.into_inner()),
+                None,
+            )
+            .unwrap();
+
+            let storage = SharedStorage::new_with_storage(&loaded).unwrap();
+
+            assert!(storage.verify_key_transform(None).is_ok());
+            assert!(storage.verify_key_transform(Some(&lowercase)).is_err());
+        }
+    }
+
+    #[cfg(feature = "builder")]
+    #[test]
+    fn clone_into_thread() {
+        let trie = Trie::<&str, u32>::builder()
+            .elements(vec![("kumamoto", 42), ("tamana", 24)])
+            .build()
+            .unwrap();
+        let storage = SharedStorage::new_with_storage(trie.storage()).unwrap();
+
+        let clone = storage.clone();
+        assert!(storage.is_shared());
+        let found = thread::spawn(move || {
+            let trie = Trie::<&str, u32>::builder_with_storage(Box::new(clone))
+                .build()
+                .unwrap();
+            *trie.find(&"tamana").unwrap().unwrap()
+        })
+        .join()
+        .unwrap();
 
-        // assert_eq!(storage.base_at(0), 2424);
-        // assert_eq!(storage.check_at(5), 42);
+        assert_eq!(found, 24);
+        assert!(!storage.is_shared());
     }
 
     #[test]
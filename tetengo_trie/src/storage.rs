@@ -7,11 +7,13 @@
 use std::any::Any;
 use std::error;
 use std::fmt::Debug;
-use std::io::Write;
+#[cfg(feature = "builder")]
+use std::io::{self, Write};
 use std::rc::Rc;
 
-use anyhow::Result;
-
+use crate::error::Result;
+use crate::key_transform::KeyTransform;
+#[cfg(feature = "builder")]
 use crate::value_serializer::ValueSerializer;
 
 /**
@@ -19,6 +21,96 @@ use crate::value_serializer::ValueSerializer;
  */
 pub trait StorageError: error::Error {}
 
+/**
+ * A section of a serialized storage.
+ */
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SerializationSection {
+    /// The base-check array.
+    BaseCheckArray,
+
+    /// The value array.
+    ValueArray,
+
+    /// The key metadata.
+    KeyMetadata,
+
+    /// The ID of the key transform.
+    KeyTransform,
+
+    /// The names of the tries in a multi-trie.
+    TrieNames,
+
+    /// The Bloom filter over the keys.
+    BloomFilter,
+
+    /// The storage of a trie in a multi-trie.
+    Trie(u16),
+
+    /// An auxiliary section with an application-defined ID.
+    Auxiliary(u16),
+}
+
+/**
+ * A serialization progress.
+ */
+#[cfg(feature = "builder")]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SerializationProgress {
+    bytes_written: u64,
+    section: SerializationSection,
+    percent: f64,
+}
+
+#[cfg(feature = "builder")]
+impl SerializationProgress {
+    /**
+     * Creates a serialization progress.
+     *
+     * # Arguments
+     * * `bytes_written` - The number of the bytes written so far.
+     * * `section`       - The section being written.
+     * * `percent`       - The percentage of the whole serialization done so far.
+     */
+    pub const fn new(bytes_written: u64, section: SerializationSection, percent: f64) -> Self {
+        Self {
+            bytes_written,
+            section,
+            percent,
+        }
+    }
+
+    /**
+     * Returns the number of the bytes written so far.
+     *
+     * # Returns
+     * The number of the bytes written so far.
+     */
+    pub const fn bytes_written(&self) -> u64 {
+        self.bytes_written
+    }
+
+    /**
+     * Returns the section being written.
+     *
+     * # Returns
+     * The section being written.
+     */
+    pub const fn section(&self) -> SerializationSection {
+        self.section
+    }
+
+    /**
+     * Returns the percentage of the whole serialization done so far.
+     *
+     * # Returns
+     * The percentage, from 0.0 to 100.0.
+     */
+    pub const fn percent(&self) -> f64 {
+        self.percent
+    }
+}
+
 /**
  * A storage.
  *
@@ -147,12 +239,60 @@ pub trait Storage<Value: 'static>: Debug + 'static {
      * # Errors
      * * When it fails to serialize the content.
      */
+    #[cfg(feature = "builder")]
     fn serialize(
         &self,
         writer: &mut dyn Write,
         value_serializer: &mut ValueSerializer<'_, Value>,
     ) -> Result<()>;
 
+    /**
+     * Serializes this storage reporting the progress.
+     *
+     * The default implementation reports the progress only once when the serialization is done.
+     *
+     * # Arguments
+     * * `writer`           - A writer.
+     * * `value_serializer` - A serializer for value objects.
+     * * `progress`         - A callback called with the progress.
+     *
+     * # Errors
+     * * When it fails to serialize the content.
+     */
+    #[cfg(feature = "builder")]
+    fn serialize_with_progress(
+        &self,
+        writer: &mut dyn Write,
+        value_serializer: &mut ValueSerializer<'_, Value>,
+        progress: &mut dyn FnMut(&SerializationProgress),
+    ) -> Result<()> {
+        let mut counting_writer = ByteCountingWriter::new(writer);
+        self.serialize(&mut counting_writer, value_serializer)?;
+        progress(&SerializationProgress::new(
+            counting_writer.bytes_written(),
+            SerializationSection::ValueArray,
+            100.0,
+        ));
+        Ok(())
+    }
+
+    /**
+     * Verifies a key transform against the one recorded in this storage.
+     *
+     * Only a storage loaded from a sectioned serialization records a key transform, and the
+     * default implementation accepts any key transform.
+     *
+     * # Arguments
+     * * `key_transform` - A key transform. Or None when no key transform is used.
+     *
+     * # Errors
+     * * When the key transform does not match the recorded one.
+     */
+    fn verify_key_transform(&self, key_transform: Option<&KeyTransform>) -> Result<()> {
+        let _ = key_transform;
+        Ok(())
+    }
+
     /**
      * Clones this storage as `Box`.
      *
@@ -210,6 +350,45 @@ impl<Value: 'static> dyn Storage<Value> {
     }
 }
 
+impl<Value: 'static> Clone for Box<dyn Storage<Value>> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
+
+#[cfg(feature = "builder")]
+pub(super) struct ByteCountingWriter<'a> {
+    writer: &'a mut dyn Write,
+    bytes_written: u64,
+}
+
+#[cfg(feature = "builder")]
+impl<'a> ByteCountingWriter<'a> {
+    pub(super) fn new(writer: &'a mut dyn Write) -> Self {
+        Self {
+            writer,
+            bytes_written: 0,
+        }
+    }
+
+    pub(super) const fn bytes_written(&self) -> u64 {
+        self.bytes_written
+    }
+}
+
+#[cfg(feature = "builder")]
+impl Write for ByteCountingWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.writer.write(buf)?;
+        self.bytes_written += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -254,6 +433,7 @@ mod tests {
             unimplemented!()
         }
 
+        #[cfg(feature = "builder")]
         fn serialize(&self, _: &mut dyn Write, _: &mut ValueSerializer<'_, i32>) -> Result<()> {
             unimplemented!()
         }
@@ -311,6 +491,7 @@ mod tests {
             unimplemented!()
         }
 
+        #[cfg(feature = "builder")]
         fn serialize(&self, _: &mut dyn Write, _: &mut ValueSerializer<'_, i32>) -> Result<()> {
             unimplemented!()
         }
@@ -4,8 +4,7 @@
  * Copyright (C) 2023-2025 kaoru  <https://www.tetengo.org/>
  */
 
-use anyhow::Result;
-
+use crate::error::Result;
 use crate::serializer::{Deserializer, DeserializerOf, Serializer, SerializerOf};
 
 /**
@@ -76,7 +75,7 @@ impl DeserializerOf<String> for () {
 
 #[cfg(test)]
 mod tests {
-    use std::string::FromUtf8Error;
+    use crate::error::TrieError;
 
     use super::*;
 
@@ -123,7 +122,7 @@ mod tests {
 
             let serialized = &[0xFFu8, 0xFFu8, 0xFFu8];
             assert!(if let Err(e) = deserializer.deserialize(serialized) {
-                e.downcast_ref::<FromUtf8Error>().is_some()
+                matches!(e, TrieError::StringDeserialization(_))
             } else {
                 false
             });
@@ -0,0 +1,157 @@
+/*!
+ * A subtrie view.
+ *
+ * Copyright (C) 2023-2025 kaoru  <https://www.tetengo.org/>
+ */
+
+use crate::double_array_iterator::DoubleArrayIterator;
+use crate::storage::Storage;
+use crate::trie_iterator::TrieKeyValueIterator;
+
+/**
+ * A subtrie view.
+ *
+ * It is rooted at the node of a key prefix of a trie, and borrows the storage of the trie
+ * instead of cloning it. It suits a predictive search, e.g. an autocomplete, which enumerates
+ * only the keys with a key prefix.
+ */
+#[derive(Clone, Debug)]
+pub struct SubtrieView<'a, T: 'static> {
+    storage: &'a dyn Storage<T>,
+    root_node: usize,
+    key_prefix: Vec<u8>,
+    max_key_length: Option<usize>,
+}
+
+impl<'a, T> SubtrieView<'a, T> {
+    /**
+     * Creates a subtrie view.
+     *
+     * # Arguments
+     * * `storage`        - A storage.
+     * * `root_node`      - A root node.
+     * * `key_prefix`     - A serialized key prefix.
+     * * `max_key_length` - A maximum key length.
+     */
+    pub(super) const fn new(
+        storage: &'a dyn Storage<T>,
+        root_node: usize,
+        key_prefix: Vec<u8>,
+        max_key_length: Option<usize>,
+    ) -> Self {
+        Self {
+            storage,
+            root_node,
+            key_prefix,
+            max_key_length,
+        }
+    }
+
+    /**
+     * Returns the root node.
+     *
+     * It can be passed to `Trie::step()` and `Trie::value_at_node()` of the trie.
+     *
+     * # Returns
+     * The root node.
+     */
+    pub const fn root_node(&self) -> usize {
+        self.root_node
+    }
+
+    /**
+     * Returns the key prefix.
+     *
+     * # Returns
+     * The serialized key prefix, which is transformed when a key transform is set.
+     */
+    pub fn key_prefix(&self) -> &[u8] {
+        self.key_prefix.as_slice()
+    }
+
+    /**
+     * Returns an iterator of the keys and the values.
+     *
+     * The keys longer than the maximum key length are skipped.
+     *
+     * # Returns
+     * A trie key-value iterator. The serialized keys include the key prefix, and are in the
+     * lexicographic order.
+     */
+    pub fn iter(&self) -> TrieKeyValueIterator<'a, T> {
+        TrieKeyValueIterator::new(
+            DoubleArrayIterator::new_with_key_prefix(
+                self.storage,
+                Some(self.root_node),
+                &self.key_prefix,
+            )
+            .with_max_key_length(self.max_key_length),
+            self.storage,
+        )
+    }
+}
+
+#[cfg(all(test, feature = "builder"))]
+mod tests {
+    use crate::trie::Trie;
+
+    fn create_trie() -> Trie<&'static str, i32> {
+        Trie::<&str, i32>::builder()
+            .elements([("kuma", 1), ("kumamoto", 2), ("kumagaya", 3), ("moto", 4)].to_vec())
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn root_node() {
+        let trie = create_trie();
+        let view = trie.subtrie_view(&"kumam").unwrap().unwrap();
+
+        let mut node = view.root_node();
+        for byte in b"oto" {
+            node = trie.step(node, *byte).unwrap().unwrap();
+        }
+        assert_eq!(*trie.value_at_node(node).unwrap().unwrap(), 2);
+    }
+
+    #[test]
+    fn key_prefix() {
+        let trie = create_trie();
+        let view = trie.subtrie_view(&"kuma").unwrap().unwrap();
+
+        assert_eq!(view.key_prefix(), b"kuma");
+        assert!(trie.subtrie_view(&"kumaz").unwrap().is_none());
+    }
+
+    #[test]
+    fn iter() {
+        let trie = create_trie();
+        {
+            let view = trie.subtrie_view(&"kuma").unwrap().unwrap();
+
+            let found = view
+                .iter()
+                .map(|(key, value)| (String::from_utf8(key).unwrap(), *value))
+                .collect::<Vec<_>>();
+            assert_eq!(
+                found,
+                vec![
+                    (String::from("kuma"), 1),
+                    (String::from("kumagaya"), 3),
+                    (String::from("kumamoto"), 2),
+                ]
+            );
+        }
+        {
+            let view = trie.subtrie_view(&"").unwrap().unwrap();
+
+            assert_eq!(view.iter().count(), 4);
+        }
+        {
+            let view = trie.subtrie_view(&"mot").unwrap().unwrap();
+
+            let found = view.iter().map(|(_, value)| *value).collect::<Vec<_>>();
+            assert_eq!(found, vec![4]);
+        }
+    }
+}
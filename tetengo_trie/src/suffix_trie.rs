@@ -0,0 +1,283 @@
+/*!
+ * A suffix trie.
+ *
+ * Copyright (C) 2023-2025 kaoru  <https://www.tetengo.org/>
+ */
+
+use std::fmt::{self, Debug, Formatter};
+use std::io::Read;
+#[cfg(feature = "builder")]
+use std::io::Write;
+use std::rc::Rc;
+
+use crate::double_array::DoubleArray;
+use crate::double_array_iterator::DoubleArrayIterator;
+use crate::error::Result;
+use crate::memory_storage::MemoryStorage;
+use crate::serializer::{Serializer, SerializerOf};
+use crate::trie::Trie;
+use crate::value_serializer::ValueDeserializer;
+#[cfg(feature = "builder")]
+use crate::value_serializer::ValueSerializer;
+
+/**
+ * A suffix trie.
+ *
+ * It is a pair of a trie and its companion double array whose keys are the reversed serialized
+ * keys of the trie, so that the values can be searched by the suffixes of the keys.
+ * The companion double array does not have any values but the value indices of the trie.
+ *
+ * # Type Parameters
+ * * `Key`           - A key type.
+ * * `Value`         - A value type.
+ * * `KeySerializer` - A key serializer type.
+ */
+pub struct SuffixTrie<
+    Key,
+    Value: Debug,
+    KeySerializer: Serializer = <() as SerializerOf<Key>>::Type,
+> {
+    trie: Trie<Key, Value, KeySerializer>,
+    reversed_double_array: DoubleArray<Value>,
+}
+
+impl<Key, Value: Debug + 'static, KeySerializer: Serializer + Debug> Debug
+    for SuffixTrie<Key, Value, KeySerializer>
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SuffixTrie")
+            .field("trie", &self.trie)
+            .field("reversed_double_array", &self.reversed_double_array)
+            .finish()
+    }
+}
+
+impl<Key, Value: Debug + 'static, KeySerializer: Serializer + Clone> Clone
+    for SuffixTrie<Key, Value, KeySerializer>
+{
+    fn clone(&self) -> Self {
+        Self {
+            trie: self.trie.clone(),
+            reversed_double_array: self.reversed_double_array.clone(),
+        }
+    }
+}
+
+impl<Key, Value: Clone + Debug + 'static, KeySerializer: Serializer + Clone>
+    SuffixTrie<Key, Value, KeySerializer>
+{
+    /**
+     * Creates a suffix trie.
+     *
+     * The trie should not be a subtrie, whose keys are the ones without the prefix.
+     *
+     * # Arguments
+     * * `trie` - A trie.
+     *
+     * # Errors
+     * * When it fails to access the storage.
+     */
+    #[cfg(feature = "builder")]
+    pub fn new(trie: Trie<Key, Value, KeySerializer>) -> Result<Self> {
+        let mut reversed_keys = Vec::new();
+        let mut iterator = trie.double_array().iter();
+        while let Some((mut key, value_index)) = iterator.next_with_key() {
+            key.reverse();
+            reversed_keys.push((key, value_index));
+        }
+
+        let reversed_double_array = DoubleArray::<Value>::builder()
+            .elements(
+                reversed_keys
+                    .iter()
+                    .map(|(key, value_index)| (key.as_slice(), *value_index))
+                    .collect(),
+            )
+            .build()?;
+        Ok(Self {
+            trie,
+            reversed_double_array,
+        })
+    }
+
+    /**
+     * Creates a suffix trie with a reader.
+     *
+     * # Arguments
+     * * `reader`             - A reader.
+     * * `value_deserializer` - A deserializer for value objects.
+     * * `key_serializer`     - A key serializer.
+     *
+     * # Errors
+     * * When it fails to read the storages.
+     */
+    pub fn new_with_reader(
+        reader: &mut dyn Read,
+        value_deserializer: &mut ValueDeserializer<Value>,
+        key_serializer: KeySerializer,
+    ) -> Result<Self> {
+        let storage = MemoryStorage::new_with_reader(reader, value_deserializer)?;
+        let reversed_storage = MemoryStorage::new_with_reader(reader, value_deserializer)?;
+        Ok(Self {
+            trie: Trie::builder_with_storage(Box::new(storage))
+                .key_serializer(key_serializer)
+                .build()?,
+            reversed_double_array: DoubleArray::new(Box::new(reversed_storage), 0),
+        })
+    }
+
+    /**
+     * Returns the trie.
+     *
+     * # Returns
+     * The trie.
+     */
+    pub const fn trie(&self) -> &Trie<Key, Value, KeySerializer> {
+        &self.trie
+    }
+
+    /**
+     * Finds the value objects whose keys end with the given suffix.
+     *
+     * The suffix is compared with the serialized keys byte by byte.
+     *
+     * # Arguments
+     * * `suffix` - A suffix.
+     *
+     * # Returns
+     * The value objects, in the lexicographic order of the reversed serialized keys.
+     *
+     * # Errors
+     * * When it fails to access the storage.
+     */
+    pub fn ends_with(&self, suffix: &KeySerializer::Object<'_>) -> Result<Vec<Rc<Value>>> {
+        let mut reversed_suffix = self.trie.serialize_key(suffix)?;
+        reversed_suffix.reverse();
+
+        let mut base_check_index = self.reversed_double_array.root();
+        for c in reversed_suffix {
+            let Some(next_base_check_index) =
+                self.reversed_double_array.step(base_check_index, c)?
+            else {
+                return Ok(Vec::new());
+            };
+            base_check_index = next_base_check_index;
+        }
+
+        let mut values = Vec::new();
+        for value_index in
+            DoubleArrayIterator::new(self.reversed_double_array.storage(), base_check_index)
+        {
+            if let Some(value) = self.trie.storage().value_at(value_index as usize)? {
+                values.push(value);
+            }
+        }
+        Ok(values)
+    }
+
+    /**
+     * Serializes the trie and the companion double array.
+     *
+     * # Arguments
+     * * `writer`           - A writer.
+     * * `value_serializer` - A serializer for value objects.
+     *
+     * # Errors
+     * * When it fails to serialize the storages.
+     */
+    #[cfg(feature = "builder")]
+    pub fn serialize(
+        &self,
+        writer: &mut dyn Write,
+        value_serializer: &mut ValueSerializer<'_, Value>,
+    ) -> Result<()> {
+        self.trie.storage().serialize(writer, value_serializer)?;
+        self.reversed_double_array
+            .storage()
+            .serialize(writer, value_serializer)
+    }
+}
+
+#[cfg(all(test, feature = "builder"))]
+mod tests {
+    use std::io::Cursor;
+
+    use crate::serializer::Deserializer;
+    use crate::string_serializer::{StringDeserializer, StringSerializer};
+
+    use super::*;
+
+    fn create_suffix_trie() -> SuffixTrie<&'static str, String> {
+        let trie = Trie::<&str, String>::builder()
+            .elements(
+                [
+                    ("kumamoto", String::from("Kumamoto")),
+                    ("yatsushiro", String::from("Yatsushiro")),
+                    ("hitoyoshi", String::from("Hitoyoshi")),
+                    ("ushibuka", String::from("Ushibuka")),
+                    ("kikuchi", String::from("Kikuchi")),
+                    ("yamaga", String::from("Yamaga")),
+                    ("koshi", String::from("Koshi")),
+                ]
+                .to_vec(),
+            )
+            .build()
+            .unwrap();
+        SuffixTrie::new(trie).unwrap()
+    }
+
+    fn values_of(values: Vec<Rc<String>>) -> Vec<String> {
+        values.iter().map(|v| v.as_ref().clone()).collect()
+    }
+
+    #[test]
+    fn ends_with() {
+        let suffix_trie = create_suffix_trie();
+
+        assert_eq!(
+            values_of(suffix_trie.ends_with(&"shi").unwrap()),
+            vec![String::from("Koshi"), String::from("Hitoyoshi")]
+        );
+        assert_eq!(
+            values_of(suffix_trie.ends_with(&"ga").unwrap()),
+            vec![String::from("Yamaga")]
+        );
+        assert!(suffix_trie.ends_with(&"kuma").unwrap().is_empty());
+        assert_eq!(suffix_trie.ends_with(&"").unwrap().len(), 7);
+        assert_eq!(
+            *suffix_trie.trie().find(&"kikuchi").unwrap().unwrap(),
+            "Kikuchi"
+        );
+    }
+
+    #[test]
+    fn serialize() {
+        let suffix_trie = create_suffix_trie();
+
+        let mut writer = Cursor::new(Vec::new());
+        let mut value_serializer = ValueSerializer::new(
+            Box::new(|value: &String| StringSerializer::new(false).serialize(value)),
+            0,
+        );
+        suffix_trie
+            .serialize(&mut writer, &mut value_serializer)
+            .unwrap();
+
+        let mut reader = Cursor::new(writer.into_inner());
+        let mut value_deserializer = ValueDeserializer::new(Box::new(|serialized: &[u8]| {
+            StringDeserializer::new(false).deserialize(serialized)
+        }));
+        let loaded = SuffixTrie::<&str, String>::new_with_reader(
+            &mut reader,
+            &mut value_deserializer,
+            <() as SerializerOf<&str>>::Type::new(true),
+        )
+        .unwrap();
+
+        assert_eq!(
+            values_of(loaded.ends_with(&"shi").unwrap()),
+            vec![String::from("Koshi"), String::from("Hitoyoshi")]
+        );
+        assert_eq!(*loaded.trie().find(&"yamaga").unwrap().unwrap(), "Yamaga");
+    }
+}
@@ -0,0 +1,270 @@
+/*!
+ * A test support.
+ *
+ * It is available with the feature `test-util`, so that downstream crates can verify their
+ * custom serializers against the contract of a trie.
+ *
+ * Copyright (C) 2023-2025 kaoru  <https://www.tetengo.org/>
+ */
+
+use std::collections::HashSet;
+use std::fmt::Debug;
+
+use crate::serializer::Serializer;
+use crate::trie::Trie;
+use crate::value_serializer::{ValueDeserializer, ValueSerializer};
+
+/**
+ * An element generator.
+ *
+ * It generates random keys and elements with a seed. The same seed generates the same keys on
+ * any platform, so that a failed case can be reproduced.
+ */
+#[derive(Clone, Debug)]
+pub struct ElementGenerator {
+    state: u64,
+    alphabet: Vec<char>,
+    max_key_length: usize,
+}
+
+impl ElementGenerator {
+    /**
+     * Creates an element generator.
+     *
+     * The default alphabet has some ASCII letters and some multibyte characters.
+     *
+     * # Arguments
+     * * `seed` - A seed.
+     */
+    pub fn new(seed: u64) -> Self {
+        Self {
+            state: seed,
+            alphabet: Self::DEFAULT_ALPHABET.chars().collect(),
+            max_key_length: Self::DEFAULT_MAX_KEY_LENGTH,
+        }
+    }
+
+    /**
+     * Sets an alphabet of the keys.
+     *
+     * # Arguments
+     * * `alphabet` - An alphabet.
+     *
+     * # Panics
+     * * When the alphabet is empty.
+     */
+    pub fn alphabet(mut self, alphabet: &str) -> Self {
+        assert!(!alphabet.is_empty(), "the alphabet is empty");
+        self.alphabet = alphabet.chars().collect();
+        self
+    }
+
+    /**
+     * Sets a maximum length of the keys in characters.
+     *
+     * # Arguments
+     * * `max_key_length` - A maximum length.
+     *
+     * # Panics
+     * * When the maximum length is 0.
+     */
+    pub fn max_key_length(mut self, max_key_length: usize) -> Self {
+        assert!(max_key_length > 0, "the maximum key length is 0");
+        self.max_key_length = max_key_length;
+        self
+    }
+
+    /**
+     * Generates a random number.
+     *
+     * # Returns
+     * A random number.
+     */
+    pub fn next_u64(&mut self) -> u64 {
+        // SplitMix64
+        self.state = self.state.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
+    }
+
+    /**
+     * Generates a random key.
+     *
+     * # Returns
+     * A key with 1 to the maximum length characters.
+     */
+    pub fn next_key(&mut self) -> String {
+        let length = 1 + self.below(self.max_key_length);
+        (0..length)
+            .map(|_| {
+                let index = self.below(self.alphabet.len());
+                self.alphabet[index]
+            })
+            .collect()
+    }
+
+    /**
+     * Generates random keys.
+     *
+     * # Arguments
+     * * `count` - The number of the keys.
+     *
+     * # Returns
+     * Distinct keys in the generated order. Fewer keys are returned when the alphabet and the
+     * maximum length do not have enough keys.
+     */
+    pub fn keys(&mut self, count: usize) -> Vec<String> {
+        let mut keys = Vec::with_capacity(count);
+        let mut unique_keys = HashSet::with_capacity(count);
+        let mut attempt_count = 0;
+        while keys.len() < count && attempt_count < count.saturating_mul(Self::MAX_ATTEMPT_FACTOR) {
+            let key = self.next_key();
+            if unique_keys.insert(key.clone()) {
+                keys.push(key);
+            }
+            attempt_count += 1;
+        }
+        keys
+    }
+
+    /**
+     * Generates random elements.
+     *
+     * # Arguments
+     * * `count`    - The number of the elements.
+     * * `value_of` - A function generating a value from a key and a random number.
+     *
+     * # Returns
+     * Elements with distinct keys.
+     */
+    pub fn elements<Value>(
+        &mut self,
+        count: usize,
+        mut value_of: impl FnMut(&str, u64) -> Value,
+    ) -> Vec<(String, Value)> {
+        self.keys(count)
+            .into_iter()
+            .map(|key| {
+                let value = value_of(&key, self.next_u64());
+                (key, value)
+            })
+            .collect()
+    }
+
+    const DEFAULT_ALPHABET: &'static str = "abcdeやまもと熊本";
+
+    const DEFAULT_MAX_KEY_LENGTH: usize = 8;
+
+    const MAX_ATTEMPT_FACTOR: usize = 16;
+
+    fn below(&mut self, bound: usize) -> usize {
+        (self.next_u64() % bound as u64) as usize
+    }
+}
+
+/**
+ * Asserts that a trie satisfies its contract through a round trip.
+ *
+ * It builds a trie with the elements, confirms that every key resolves to its value, serializes
+ * the storage, deserializes it and confirms the same again.
+ *
+ * # Arguments
+ * * `elements`           - Elements with distinct keys.
+ * * `key_serializer`     - A key serializer.
+ * * `value_serializer`   - A serializer for value objects.
+ * * `value_deserializer` - A deserializer for value objects.
+ *
+ * # Panics
+ * * When the trie does not satisfy its contract.
+ */
+pub fn assert_round_trip<Value, KeySerializer>(
+    elements: Vec<(KeySerializer::Object<'static>, Value)>,
+    key_serializer: KeySerializer,
+    value_serializer: &mut ValueSerializer<'_, Value>,
+    value_deserializer: &mut ValueDeserializer<Value>,
+) where
+    Value: Clone + Debug + PartialEq + 'static,
+    KeySerializer: Serializer + Clone,
+    KeySerializer::Object<'static>: Clone,
+{
+    let trie = match Trie::<KeySerializer::Object<'static>, Value, KeySerializer>::builder()
+        .elements(elements.clone())
+        .key_serializer(key_serializer)
+        .build()
+    {
+        Ok(trie) => trie,
+        Err(e) => panic!("failed to build the trie: {}", e),
+    };
+    if let Err(e) = trie.verify_against(&elements) {
+        panic!("the built trie does not match the elements: {}", e);
+    }
+    if let Err(e) = trie.verify_serialization(value_serializer, value_deserializer) {
+        panic!("the deserialized trie does not match the elements: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::integer_serializer::{IntegerDeserializer, IntegerSerializer};
+    use crate::serializer::Deserializer;
+    use crate::string_serializer::StringSerializer;
+
+    use super::*;
+
+    #[test]
+    fn keys() {
+        let keys = ElementGenerator::new(42).keys(100);
+        assert_eq!(keys.len(), 100);
+        assert_eq!(keys.iter().collect::<HashSet<_>>().len(), 100);
+        assert!(keys
+            .iter()
+            .all(|key| (1..=8).contains(&key.chars().count())));
+        assert_eq!(keys, ElementGenerator::new(42).keys(100));
+        assert_ne!(keys, ElementGenerator::new(24).keys(100));
+
+        let keys = ElementGenerator::new(42)
+            .alphabet("ab")
+            .max_key_length(2)
+            .keys(100);
+        assert_eq!(keys.len(), 6);
+    }
+
+    #[test]
+    fn assert_round_trip() {
+        let elements = ElementGenerator::new(42).elements(300, |_, random| random as u32);
+
+        let mut value_serializer = ValueSerializer::new(
+            Box::new(|value| IntegerSerializer::new(false).serialize(value)),
+            size_of::<u32>(),
+        );
+        let mut value_deserializer = ValueDeserializer::new(Box::new(|serialized| {
+            IntegerDeserializer::new(false).deserialize(serialized)
+        }));
+        super::assert_round_trip(
+            elements,
+            StringSerializer::new(true),
+            &mut value_serializer,
+            &mut value_deserializer,
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn assert_round_trip_with_broken_serializer() {
+        let elements = ElementGenerator::new(42).elements(10, |_, random| random as u32);
+
+        let mut value_serializer = ValueSerializer::new(
+            Box::new(|value| IntegerSerializer::new(false).serialize(value)),
+            size_of::<u32>(),
+        );
+        let mut value_deserializer = ValueDeserializer::new(Box::new(|_: &[u8]| Ok(0xDEADBEEFu32)));
+        super::assert_round_trip(
+            elements,
+            StringSerializer::new(true),
+            &mut value_serializer,
+            &mut value_deserializer,
+        );
+    }
+}
@@ -4,27 +4,71 @@
  * Copyright (C) 2023-2025 kaoru  <https://www.tetengo.org/>
  */
 
+#[cfg(feature = "builder")]
 use std::any::type_name_of_val;
 use std::cell::RefCell;
+use std::collections::HashMap;
+#[cfg(feature = "builder")]
+use std::collections::HashSet;
 use std::fmt::{self, Debug, Formatter};
+#[cfg(feature = "builder")]
+use std::io;
+use std::io::Write;
 use std::marker::PhantomData;
 use std::rc::Rc;
-
-use anyhow::Result;
-
-use crate::double_array::{self, DoubleArray, DEFAULT_DENSITY_FACTOR};
+use std::sync::Arc;
+
+use crate::automaton::Automaton;
+use crate::bloom_filter::BloomFilter;
+use crate::double_array::DoubleArray;
+#[cfg(feature = "builder")]
+use crate::double_array::{self, DoubleArrayError, DEFAULT_DENSITY_FACTOR};
+use crate::error::Result;
+#[cfg(feature = "builder")]
+use crate::error::TrieError;
+use crate::key_metadata::KeyMetadata;
+use crate::key_transform::KeyTransform;
+use crate::locality_stats::LocalityStats;
+#[cfg(feature = "builder")]
+use crate::memory_storage::MemoryStorage;
+use crate::prefix_group_iterator::PrefixGroupIterator;
+use crate::query_stats::QueryStats;
 use crate::serializer::{Serializer, SerializerOf};
 use crate::storage::Storage;
-use crate::trie_iterator::TrieIterator;
+use crate::subtrie_view::SubtrieView;
+use crate::trie_iterator::{TrieIterator, TrieKeyValueIterator};
+#[cfg(feature = "builder")]
+use crate::value_serializer::{ValueDeserializer, ValueSerializer};
+
+/**
+ * A reason why an element is rejected.
+ */
+#[cfg(feature = "builder")]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RejectionReason {
+    /// The key is the same as the one of a preceding element.
+    DuplicateKey,
+}
+
+#[cfg(feature = "builder")]
+type RejectedObserver<'a> = &'a mut dyn FnMut(&[u8], RejectionReason);
+
+#[cfg(feature = "builder")]
+type LocalityObserver<'a> = &'a mut dyn FnMut(&LocalityStats, &LocalityStats);
 
 /**
  * A building observer set.
  */
+#[cfg(feature = "builder")]
 pub struct BuldingObserverSet<'a> {
     adding: &'a mut dyn FnMut(&[u8]),
+    rejected: Option<RejectedObserver<'a>>,
+    cancelled: Option<&'a mut dyn FnMut() -> bool>,
+    locality: Option<LocalityObserver<'a>>,
     done: &'a mut dyn FnMut(),
 }
 
+#[cfg(feature = "builder")]
 impl<'a> BuldingObserverSet<'a> {
     /**
      * Creates a building observer set.
@@ -34,7 +78,55 @@ impl<'a> BuldingObserverSet<'a> {
      * * `done` - A done observer.
      */
     pub fn new(adding: &'a mut dyn FnMut(&[u8]), done: &'a mut dyn FnMut()) -> Self {
-        Self { adding, done }
+        Self {
+            adding,
+            rejected: None,
+            cancelled: None,
+            locality: None,
+            done,
+        }
+    }
+
+    /**
+     * Sets a rejected observer.
+     *
+     * It is called for each element dropped from the trie, with its serialized key and the
+     * reason.
+     *
+     * # Arguments
+     * * `rejected` - A rejected observer.
+     */
+    pub fn with_rejected(mut self, rejected: RejectedObserver<'a>) -> Self {
+        self.rejected = Some(rejected);
+        self
+    }
+
+    /**
+     * Sets a cancelled observer.
+     *
+     * It is called before each element is added. When it returns `true`, the building is
+     * cancelled.
+     *
+     * # Arguments
+     * * `cancelled` - A cancelled observer.
+     */
+    pub fn with_cancelled(mut self, cancelled: &'a mut dyn FnMut() -> bool) -> Self {
+        self.cancelled = Some(cancelled);
+        self
+    }
+
+    /**
+     * Sets a locality observer.
+     *
+     * It is called once before `done` with the locality stats of the depth-first layout and the
+     * ones of the built layout, when a breadth-first depth is set to the builder.
+     *
+     * # Arguments
+     * * `locality` - A locality observer.
+     */
+    pub fn with_locality(mut self, locality: LocalityObserver<'a>) -> Self {
+        self.locality = Some(locality);
+        self
     }
 
     /**
@@ -47,6 +139,52 @@ impl<'a> BuldingObserverSet<'a> {
         (self.adding)(serialized_key);
     }
 
+    /**
+     * Calls `rejected` if it is set.
+     *
+     * # Arguments
+     * * `serialized_key` - A serialized key.
+     * * `reason`         - A reason.
+     */
+    pub fn rejected(&mut self, serialized_key: &[u8], reason: RejectionReason) {
+        if let Some(rejected) = &mut self.rejected {
+            rejected(serialized_key, reason);
+        }
+    }
+
+    /**
+     * Calls `cancelled` if it is set.
+     *
+     * # Returns
+     * `true` if the building is cancelled.
+     */
+    pub fn cancelled(&mut self) -> bool {
+        self.cancelled.as_mut().is_some_and(|cancelled| cancelled())
+    }
+
+    /**
+     * Returns `true` if `locality` is set.
+     *
+     * # Returns
+     * `true` if `locality` is set.
+     */
+    pub const fn locality_observed(&self) -> bool {
+        self.locality.is_some()
+    }
+
+    /**
+     * Calls `locality` if it is set.
+     *
+     * # Arguments
+     * * `before` - The locality stats of the depth-first layout.
+     * * `after`  - The locality stats of the built layout.
+     */
+    pub fn locality(&mut self, before: &LocalityStats, after: &LocalityStats) {
+        if let Some(locality) = &mut self.locality {
+            locality(before, after);
+        }
+    }
+
     /**
      * Calls `done`.
      */
@@ -55,18 +193,137 @@ impl<'a> BuldingObserverSet<'a> {
     }
 }
 
+#[cfg(feature = "builder")]
 impl Debug for BuldingObserverSet<'_> {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         f.debug_struct("BuldingObserverSet")
             .field("adding", &type_name_of_val(&self.adding))
+            .field("rejected", &self.rejected.as_ref().map(type_name_of_val))
+            .field("cancelled", &self.cancelled.as_ref().map(type_name_of_val))
+            .field("locality", &self.locality.as_ref().map(type_name_of_val))
             .field("done", &type_name_of_val(&self.done))
             .finish()
     }
 }
 
 /// The default double array density factor.
+#[cfg(feature = "builder")]
 const DEFAULT_DOUBLE_ARRAY_DENSITY_FACTOR: usize = DEFAULT_DENSITY_FACTOR;
 
+/**
+ * A trie verification error.
+ */
+#[derive(Clone, Copy, Debug, thiserror::Error)]
+pub enum TrieVerificationError {
+    /**
+     * A key is not found.
+     */
+    #[error("a key is not found")]
+    KeyNotFound,
+
+    /**
+     * A value is not equal to the expected one.
+     */
+    #[error("a value is not equal to the expected one")]
+    ValueMismatch,
+
+    /**
+     * An unexpected key is found.
+     */
+    #[error("an unexpected key is found")]
+    UnexpectedKey,
+}
+
+/**
+ * A key length error.
+ */
+#[derive(Clone, Copy, Debug, thiserror::Error)]
+pub enum KeyLengthError {
+    /**
+     * A serialized key is longer than the maximum key length.
+     */
+    #[error("the key length {length} exceeds the maximum {max_length}")]
+    TooLong {
+        /// The length of the serialized key.
+        length: usize,
+
+        /// The maximum key length.
+        max_length: usize,
+    },
+}
+
+/**
+ * A build error.
+ *
+ * `InvalidDensityFactor`, `ElementsNotSorted`, `DuplicateKey` and `KeyTooLong` are caused by the
+ * given elements or settings, while `Storage` is an internal failure.
+ */
+#[cfg(feature = "builder")]
+#[derive(Debug, thiserror::Error)]
+pub enum BuildError {
+    /**
+     * The double array density factor is invalid.
+     */
+    #[error("the double array density factor is invalid")]
+    InvalidDensityFactor,
+
+    /**
+     * The elements are set sorted but they are not sorted.
+     */
+    #[error("the elements are not sorted at index {index}")]
+    ElementsNotSorted {
+        /// The index of the first element smaller than the preceding one.
+        index: usize,
+    },
+
+    /**
+     * A key is duplicate while the duplicate keys are not allowed.
+     */
+    #[error("the key at index {index} is duplicate")]
+    DuplicateKey {
+        /// The index of the element whose key is the same as the one of a preceding element.
+        index: usize,
+    },
+
+    /**
+     * A key is longer than the maximum key length.
+     */
+    #[error("the key at index {index} is too long")]
+    KeyTooLong {
+        /// The index of the element whose serialized key is too long.
+        index: usize,
+    },
+
+    /**
+     * The building is cancelled by the building observer set.
+     */
+    #[error("the building is cancelled")]
+    Cancelled,
+
+    /**
+     * It fails to access the storage.
+     */
+    #[error("storage error")]
+    Storage(#[source] Box<TrieError>),
+}
+
+#[cfg(feature = "builder")]
+impl BuildError {
+    fn from_trie_error(error: TrieError) -> Self {
+        match error {
+            TrieError::Build(build_error) => build_error,
+            TrieError::DoubleArray(DoubleArrayError::InvalidDensityFactor) => {
+                Self::InvalidDensityFactor
+            }
+            TrieError::DoubleArray(DoubleArrayError::ElementsNotSorted { index }) => {
+                Self::ElementsNotSorted { index }
+            }
+            TrieError::DoubleArray(DoubleArrayError::Cancelled) => Self::Cancelled,
+            error => Self::Storage(Box::new(error)),
+        }
+    }
+}
+
 /**
  * A trie builder.
  *
@@ -75,14 +332,24 @@ const DEFAULT_DOUBLE_ARRAY_DENSITY_FACTOR: usize = DEFAULT_DENSITY_FACTOR;
  * * `Value`         - A value type.
  * * `KeySerializer` - A key serializer type.
  */
+#[cfg(feature = "builder")]
 #[derive(Debug)]
 pub struct TrieBuilder<Key, Value, KeySerializer: Serializer> {
     phantom: PhantomData<Key>,
     elements: Vec<(KeySerializer::Object<'static>, Value)>,
+    elements_sorted: bool,
+    duplicate_keys_allowed: bool,
     key_serializer: KeySerializer,
+    key_transform: Option<KeyTransform>,
     double_array_density_factor: usize,
+    breadth_first_depth: usize,
+    base_check_block_size: Option<usize>,
+    bloom_filter_bits_per_key: usize,
+    max_key_length: Option<usize>,
+    query_stats: Option<Arc<QueryStats>>,
 }
 
+#[cfg(feature = "builder")]
 impl<Key, Value: Clone + Debug + 'static, KeySerializer: Serializer>
     TrieBuilder<Key, Value, KeySerializer>
 {
@@ -94,6 +361,28 @@ impl<Key, Value: Clone + Debug + 'static, KeySerializer: Serializer>
         self
     }
 
+    /**
+     * Sets whether the elements are already sorted.
+     *
+     * The elements are sorted by the serialized keys when building a trie. When this is set
+     * true, the sort is skipped and the order is validated instead.
+     */
+    pub fn elements_sorted(mut self, elements_sorted: bool) -> Self {
+        self.elements_sorted = elements_sorted;
+        self
+    }
+
+    /**
+     * Sets whether the duplicate keys are allowed.
+     *
+     * When they are allowed, the first element of the duplicate keys is added and the rest are
+     * rejected. Otherwise, the building fails. They are allowed by default.
+     */
+    pub fn duplicate_keys_allowed(mut self, duplicate_keys_allowed: bool) -> Self {
+        self.duplicate_keys_allowed = duplicate_keys_allowed;
+        self
+    }
+
     /**
      * Sets a key serializer.
      */
@@ -102,6 +391,17 @@ impl<Key, Value: Clone + Debug + 'static, KeySerializer: Serializer>
         self
     }
 
+    /**
+     * Sets a key transform.
+     *
+     * The serialized keys are transformed when building, and so are the ones given to the
+     * built trie.
+     */
+    pub fn key_transform(mut self, key_transform: KeyTransform) -> Self {
+        self.key_transform = Some(key_transform);
+        self
+    }
+
     /**
      * Sets a double array density factor.
      */
@@ -110,14 +410,78 @@ impl<Key, Value: Clone + Debug + 'static, KeySerializer: Serializer>
         self
     }
 
+    /**
+     * Sets a breadth-first depth.
+     *
+     * The nodes shallower than the depth are placed in the breadth-first order, and the
+     * top-level subtrees with more keys are placed at lower indices. It improves the locality of
+     * shallow prefix queries. The deeper nodes are placed depth-first. When it is 0, the
+     * default, all the nodes are placed depth-first.
+     *
+     * Note that the `adding` observer is not called in the order of the keys when it is set.
+     */
+    pub fn breadth_first_depth(mut self, breadth_first_depth: usize) -> Self {
+        self.breadth_first_depth = breadth_first_depth;
+        self
+    }
+
+    /**
+     * Sets a block size of the base-check array.
+     *
+     * The base-check array of the built trie is allocated in blocks of the size, so that a very
+     * large trie can be built on a fragmented heap. See `MemoryStorage::base_check_block_size()`.
+     */
+    pub fn base_check_block_size(mut self, base_check_block_size: usize) -> Self {
+        self.base_check_block_size = Some(base_check_block_size);
+        self
+    }
+
+    /**
+     * Sets a maximum key length.
+     *
+     * The building fails when a serialized key is longer than it, and so do the lookups of the
+     * built trie. It guards the traversals and the iterations against pathologically long keys.
+     * There is no limit by default.
+     */
+    pub fn max_key_length(mut self, max_key_length: usize) -> Self {
+        self.max_key_length = Some(max_key_length);
+        self
+    }
+
+    /**
+     * Sets the number of the bits per key of a Bloom filter.
+     *
+     * When it is not 0, a Bloom filter over the serialized keys is built with the trie, so that
+     * the lookups of the absent keys are rejected without touching the base-check array. Use
+     * `Trie::bloom_filter()` to serialize it into the Bloom filter section. It is 0 by default.
+     */
+    pub fn bloom_filter_bits_per_key(mut self, bloom_filter_bits_per_key: usize) -> Self {
+        self.bloom_filter_bits_per_key = bloom_filter_bits_per_key;
+        self
+    }
+
+    /**
+     * Sets a query stats.
+     *
+     * The built trie counts the queries to it.
+     */
+    pub fn query_stats(mut self, query_stats: Arc<QueryStats>) -> Self {
+        self.query_stats = Some(query_stats);
+        self
+    }
+
     /**
      * Builds a trie.
      *
+     * The storage of the built trie is identical across platforms and runs for identical
+     * elements, key serializer, density factor and breadth-first depth. Use `Trie::digest()` to
+     * verify it.
+     *
      * # Returns
      * A trie.
      *
      * # Errors
-     * * When it fails to access the storage.
+     * * When the building fails. The error is a `TrieError::Build`.
      */
     pub fn build(self) -> Result<Trie<Key, Value, KeySerializer>> {
         self.build_with_observer_set(&mut BuldingObserverSet::new(&mut |_| {}, &mut || {}))
@@ -130,18 +494,50 @@ impl<Key, Value: Clone + Debug + 'static, KeySerializer: Serializer>
      * A trie.
      *
      * # Errors
-     * * When it fails to access the storage.
+     * * When the building fails. The error is a `TrieError::Build`.
      */
     pub fn build_with_observer_set(
         self,
         building_observer_set: &mut BuldingObserverSet<'_>,
+    ) -> Result<Trie<Key, Value, KeySerializer>> {
+        self.build_core(building_observer_set)
+            .map_err(|e| BuildError::from_trie_error(e).into())
+    }
+
+    fn build_core(
+        self,
+        building_observer_set: &mut BuldingObserverSet<'_>,
     ) -> Result<Trie<Key, Value, KeySerializer>> {
         let mut double_array_content_keys = Vec::<Vec<u8>>::with_capacity(self.elements.len());
         for element in &self.elements {
             let (key, _) = &element;
-            let serialized_key = self.key_serializer.serialize(key);
+            let serialized_key =
+                serialize_key(&self.key_serializer, self.key_transform.as_ref(), key);
+            if self
+                .max_key_length
+                .is_some_and(|max_key_length| serialized_key.len() > max_key_length)
+            {
+                return Err(BuildError::KeyTooLong {
+                    index: double_array_content_keys.len(),
+                }
+                .into());
+            }
             double_array_content_keys.push(serialized_key);
         }
+        let mut unique_keys = HashSet::<&[u8]>::with_capacity(double_array_content_keys.len());
+        for (index, key) in double_array_content_keys.iter().enumerate() {
+            if !unique_keys.insert(key) && !self.duplicate_keys_allowed {
+                return Err(BuildError::DuplicateKey { index }.into());
+            }
+        }
+        let len = unique_keys.len();
+        let bloom_filter = if self.bloom_filter_bits_per_key > 0 {
+            let mut bloom_filter = BloomFilter::new(len, self.bloom_filter_bits_per_key)?;
+            unique_keys.iter().for_each(|key| bloom_filter.insert(key));
+            Some(Arc::new(bloom_filter))
+        } else {
+            None
+        };
         let mut double_array_contents = Vec::<(&[u8], i32)>::with_capacity(self.elements.len());
         for (i, _) in self.elements.iter().enumerate() {
             double_array_contents.push((&double_array_content_keys[i], i as i32));
@@ -151,15 +547,38 @@ impl<Key, Value: Clone + Debug + 'static, KeySerializer: Serializer>
         let adding = &mut |&(key, _): &(&[u8], i32)| {
             building_observer_set_ref_cell.borrow_mut().adding(key);
         };
+        let rejected = &mut |&(key, _): &(&[u8], i32)| {
+            building_observer_set_ref_cell
+                .borrow_mut()
+                .rejected(key, RejectionReason::DuplicateKey);
+        };
+        let cancelled = &mut || building_observer_set_ref_cell.borrow_mut().cancelled();
+        let locality = &mut |before: &LocalityStats, after: &LocalityStats| {
+            building_observer_set_ref_cell
+                .borrow_mut()
+                .locality(before, after);
+        };
+        let locality_observed = building_observer_set_ref_cell.borrow().locality_observed();
         let done = &mut || {
             building_observer_set_ref_cell.borrow_mut().done();
         };
-        let observer_set = &mut double_array::BuildingObserverSet::new(adding, done);
+        let mut observer_set = double_array::BuildingObserverSet::new(adding, done)
+            .with_rejected(rejected)
+            .with_cancelled(cancelled);
+        if locality_observed {
+            observer_set = observer_set.with_locality(locality);
+        }
 
-        let mut double_array = DoubleArray::<Value>::builder()
+        let mut double_array_builder = DoubleArray::<Value>::builder()
             .elements(double_array_contents)
+            .elements_sorted(self.elements_sorted)
             .density_factor(self.double_array_density_factor)
-            .build_with_observer_set(observer_set)?;
+            .breadth_first_depth(self.breadth_first_depth);
+        if let Some(base_check_block_size) = self.base_check_block_size {
+            double_array_builder =
+                double_array_builder.base_check_block_size(base_check_block_size);
+        }
+        let mut double_array = double_array_builder.build_with_observer_set(&mut observer_set)?;
 
         for (i, element) in self.elements.into_iter().enumerate() {
             let (_, value) = element;
@@ -170,6 +589,12 @@ impl<Key, Value: Clone + Debug + 'static, KeySerializer: Serializer>
             phantom: PhantomData,
             double_array,
             key_serializer: self.key_serializer,
+            key_transform: self.key_transform,
+            len: Some(len),
+            hit_counts: RefCell::default(),
+            bloom_filter,
+            max_key_length: self.max_key_length,
+            query_stats: self.query_stats,
         })
     }
 }
@@ -186,6 +611,10 @@ pub struct TrieStorageBuilder<Key, Value: Clone, KeySerializer: Serializer> {
     phantom_key: PhantomData<Key>,
     storage: Box<dyn Storage<Value>>,
     key_serializer: KeySerializer,
+    key_transform: Option<KeyTransform>,
+    bloom_filter: Option<Arc<BloomFilter>>,
+    max_key_length: Option<usize>,
+    query_stats: Option<Arc<QueryStats>>,
 }
 
 impl<Key, Value: Clone + Debug + 'static, KeySerializer: Serializer>
@@ -199,18 +628,76 @@ impl<Key, Value: Clone + Debug + 'static, KeySerializer: Serializer>
         self
     }
 
+    /**
+     * Sets a key transform.
+     *
+     * It must be the same as the one used to build the storage. When the storage is loaded from a
+     * sectioned serialization, `build()` verifies it against the recorded one.
+     */
+    pub fn key_transform(mut self, key_transform: KeyTransform) -> Self {
+        self.key_transform = Some(key_transform);
+        self
+    }
+
+    /**
+     * Sets a maximum key length.
+     *
+     * The lookups fail when a serialized key is longer than it, and the iterations skip such
+     * keys. It guards the traversals and the iterations against pathologically long keys,
+     * e.g. in a corrupted storage. There is no limit by default.
+     */
+    pub fn max_key_length(mut self, max_key_length: usize) -> Self {
+        self.max_key_length = Some(max_key_length);
+        self
+    }
+
+    /**
+     * Sets a Bloom filter.
+     *
+     * It must be the one built with the storage. Use `BloomFilter::new_with_section()` to load
+     * it from a sectioned serialization.
+     */
+    pub fn bloom_filter(mut self, bloom_filter: BloomFilter) -> Self {
+        self.bloom_filter = Some(Arc::new(bloom_filter));
+        self
+    }
+
+    /**
+     * Sets a query stats.
+     *
+     * The built trie counts the queries to it.
+     */
+    pub fn query_stats(mut self, query_stats: Arc<QueryStats>) -> Self {
+        self.query_stats = Some(query_stats);
+        self
+    }
+
     /**
      * Builds a trie.
      *
+     * The storage of the built trie is identical across platforms and runs for identical
+     * elements, key serializer and density factor. Use `Trie::digest()` to verify it.
+     *
      * # Returns
      * A trie.
+     *
+     * # Errors
+     * * When the key transform does not match the one recorded in the storage.
      */
-    pub fn build(self) -> Trie<Key, Value, KeySerializer> {
-        Trie {
+    pub fn build(self) -> Result<Trie<Key, Value, KeySerializer>> {
+        self.storage
+            .verify_key_transform(self.key_transform.as_ref())?;
+        Ok(Trie {
             phantom: PhantomData,
             double_array: DoubleArray::new(self.storage, 0),
             key_serializer: self.key_serializer,
-        }
+            key_transform: self.key_transform,
+            len: None,
+            hit_counts: RefCell::default(),
+            bloom_filter: self.bloom_filter,
+            max_key_length: self.max_key_length,
+            query_stats: self.query_stats,
+        })
     }
 }
 
@@ -221,10 +708,27 @@ impl<Key, Value: Clone, KeySerializer: Serializer> Debug
         f.debug_struct("_TrieStorageBuilder")
             .field("storage", &"Box<dyn Storage<Value>>")
             .field("key_serializer", &"KeySerializer")
+            .field("key_transform", &self.key_transform)
+            .field("bloom_filter", &self.bloom_filter)
+            .field("max_key_length", &self.max_key_length)
+            .field("query_stats", &self.query_stats)
             .finish()
     }
 }
 
+// The settings of a trie other than its storage. A versioned trie keeps them to make the tries of
+// its snapshots.
+#[derive(Clone, Debug)]
+pub(crate) struct TrieSettings<KeySerializer> {
+    root: usize,
+    key_serializer: KeySerializer,
+    key_transform: Option<KeyTransform>,
+    len: Option<usize>,
+    bloom_filter: Option<Arc<BloomFilter>>,
+    max_key_length: Option<usize>,
+    query_stats: Option<Arc<QueryStats>>,
+}
+
 /**
  * A trie.
  *
@@ -233,11 +737,48 @@ impl<Key, Value: Clone, KeySerializer: Serializer> Debug
  * * `Value`         - A value type.
  * * `KeySerializer` - A key serializer type.
  */
-#[derive(Debug)]
 pub struct Trie<Key, Value: Debug, KeySerializer: Serializer = <() as SerializerOf<Key>>::Type> {
     phantom: PhantomData<Key>,
     double_array: DoubleArray<Value>,
     key_serializer: KeySerializer,
+    key_transform: Option<KeyTransform>,
+    len: Option<usize>,
+    hit_counts: RefCell<HashMap<usize, u64>>,
+    bloom_filter: Option<Arc<BloomFilter>>,
+    max_key_length: Option<usize>,
+    query_stats: Option<Arc<QueryStats>>,
+}
+
+impl<Key, Value: Debug + 'static, KeySerializer: Serializer + Debug> Debug
+    for Trie<Key, Value, KeySerializer>
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Trie")
+            .field("double_array", &self.double_array)
+            .field("key_serializer", &self.key_serializer)
+            .field("key_transform", &self.key_transform)
+            .field("len", &self.len)
+            .field("hit_counts", &self.hit_counts)
+            .finish()
+    }
+}
+
+impl<Key, Value: Debug + 'static, KeySerializer: Serializer + Clone> Clone
+    for Trie<Key, Value, KeySerializer>
+{
+    fn clone(&self) -> Self {
+        Self {
+            phantom: PhantomData,
+            double_array: self.double_array.clone(),
+            key_serializer: self.key_serializer.clone(),
+            key_transform: self.key_transform.clone(),
+            len: self.len,
+            hit_counts: self.hit_counts.clone(),
+            bloom_filter: self.bloom_filter.clone(),
+            max_key_length: self.max_key_length,
+            query_stats: self.query_stats.clone(),
+        }
+    }
 }
 
 impl<Key, Value: Clone + Debug + 'static, KeySerializer: Serializer + Clone>
@@ -249,12 +790,21 @@ impl<Key, Value: Clone + Debug + 'static, KeySerializer: Serializer + Clone>
      * # Returns
      * A trie builder.
      */
+    #[cfg(feature = "builder")]
     pub fn builder() -> TrieBuilder<Key, Value, KeySerializer> {
         TrieBuilder {
             phantom: PhantomData,
             elements: Vec::new(),
+            elements_sorted: false,
+            duplicate_keys_allowed: true,
             key_serializer: KeySerializer::new(true),
+            key_transform: None,
             double_array_density_factor: DEFAULT_DOUBLE_ARRAY_DENSITY_FACTOR,
+            breadth_first_depth: 0,
+            base_check_block_size: None,
+            bloom_filter_bits_per_key: 0,
+            max_key_length: None,
+            query_stats: None,
         }
     }
 
@@ -271,6 +821,10 @@ impl<Key, Value: Clone + Debug + 'static, KeySerializer: Serializer + Clone>
             phantom_key: PhantomData,
             storage,
             key_serializer: KeySerializer::new(true),
+            key_transform: None,
+            bloom_filter: None,
+            max_key_length: None,
+            query_stats: None,
         }
     }
 
@@ -284,12 +838,35 @@ impl<Key, Value: Clone + Debug + 'static, KeySerializer: Serializer + Clone>
      * * When it fails to access the storage.
      */
     pub fn is_empty(&self) -> Result<bool> {
-        Ok(self.double_array.storage().value_count()? == 0)
+        Ok(self.iter().next().is_none())
+    }
+
+    /**
+     * Returns the number of the keys in the trie.
+     *
+     * The number is tracked when the trie is built from elements.
+     * Otherwise, such as when the trie is built with a storage or is a subtrie, the keys are
+     * counted by enumerating them.
+     *
+     * # Returns
+     * The number of the keys.
+     *
+     * # Errors
+     * * When it fails to access the storage.
+     */
+    pub fn len(&self) -> Result<usize> {
+        match self.len {
+            Some(len) => Ok(len),
+            None => Ok(self.double_array.iter().count()),
+        }
     }
 
     /**
      * Returns the size of the trie.
      *
+     * It is the number of the value slots in the storage, which may include gaps and is shared
+     * among the subtries. Use `len()` for the number of the keys.
+     *
      * # Returns
      * The size.
      *
@@ -313,8 +890,7 @@ impl<Key, Value: Clone + Debug + 'static, KeySerializer: Serializer + Clone>
      * * When it fails to access the storage.
      */
     pub fn contains(&self, key: &KeySerializer::Object<'_>) -> Result<bool> {
-        let serialized_key = self.key_serializer.serialize(key);
-        Ok(self.double_array.find(&serialized_key)?.is_some())
+        Ok(self.lookup(key)?.is_some())
     }
 
     /**
@@ -330,70 +906,929 @@ impl<Key, Value: Clone + Debug + 'static, KeySerializer: Serializer + Clone>
      * * When it fails to access the storage.
      */
     pub fn find(&self, key: &KeySerializer::Object<'_>) -> Result<Option<Rc<Value>>> {
-        let serialized_key = self.key_serializer.serialize(key);
-        let index = self.double_array.find(&serialized_key)?;
+        let index = self.lookup(key)?;
         let Some(index) = index else {
             return Ok(None);
         };
 
-        self.double_array.storage().value_at(index as usize)
+        self.value_at(index)
     }
 
     /**
-     * Returns an iterator.
+     * Returns the value index of the given key.
+     *
+     * The value index of a key is the position of the key in the elements passed to the builder.
+     * When a key appears more than once, the position of the first one is used. The value
+     * indices are kept by the serialization, so that side tables can be keyed by them across
+     * serialization and deserialization. They are renumbered only by `Trie::rebuild()`.
+     *
+     * # Arguments
+     * * `key` - A key.
      *
      * # Returns
-     * A double array iterator.
+     * The value index. Or None when the trie does not have the given key.
+     *
+     * # Errors
+     * * When it fails to access the storage.
      */
-    pub fn iter(&self) -> TrieIterator<'_, Value> {
-        TrieIterator::new(self.double_array.iter(), self.double_array.storage())
+    pub fn value_index(&self, key: &KeySerializer::Object<'_>) -> Result<Option<usize>> {
+        self.lookup(key)
     }
 
     /**
-     * Returns a subtrie.
+     * Returns the value object at the given value index.
      *
      * # Arguments
-     * * `key_prefix` - A key prefix.
+     * * `value_index` - A value index.
      *
      * # Returns
-     * A subtrie. Or None when the trie does not have the given key prefix.
+     * The value object. Or None when no value is at the given value index.
      *
      * # Errors
      * * When it fails to access the storage.
      */
-    pub fn subtrie(&self, key_prefix: &KeySerializer::Object<'_>) -> Result<Option<Self>> {
-        let serialized_key_prefix = self.key_serializer.serialize(key_prefix);
-        let subdouble_array = self.double_array.subtrie(&serialized_key_prefix)?;
-        let Some(subdouble_array) = subdouble_array else {
+    pub fn value_by_index(&self, value_index: usize) -> Result<Option<Rc<Value>>> {
+        if value_index >= self.double_array.storage().value_count()? {
             return Ok(None);
-        };
-        Ok(Some(Self {
-            phantom: PhantomData,
-            double_array: subdouble_array,
-            key_serializer: self.key_serializer.clone(),
-        }))
+        }
+        self.value_at(value_index)
+    }
+
+    /**
+     * Returns the metadata record of the given key.
+     *
+     * The value object is not deserialized.
+     *
+     * # Arguments
+     * * `key`          - A key.
+     * * `key_metadata` - A key metadata table.
+     *
+     * # Returns
+     * The metadata record. Or None when the trie does not have the given key or the table does
+     * not have the record.
+     *
+     * # Errors
+     * * When it fails to access the storage.
+     */
+    pub fn key_metadata<'m>(
+        &self,
+        key: &KeySerializer::Object<'_>,
+        key_metadata: &'m KeyMetadata,
+    ) -> Result<Option<&'m [u8]>> {
+        Ok(self
+            .value_index(key)?
+            .and_then(|value_index| key_metadata.record(value_index)))
+    }
+
+    /**
+     * Finds the value objects whose keys are within the given edit distance from the given key.
+     *
+     * The edit distance is the Levenshtein distance in characters between the serialized keys
+     * when the serialized key is valid UTF-8, as the one by `StringSerializer` is, and in bytes
+     * otherwise. The keys which are not valid UTF-8 are not found in characters.
+     *
+     * # Arguments
+     * * `key`          - A key.
+     * * `max_distance` - A maximum edit distance.
+     *
+     * # Returns
+     * The pairs of the value objects and the edit distances, in ascending order of the distances.
+     *
+     * # Errors
+     * * When it fails to access the storage.
+     */
+    pub fn find_approximately(
+        &self,
+        key: &KeySerializer::Object<'_>,
+        max_distance: usize,
+    ) -> Result<Vec<(Rc<Value>, usize)>> {
+        let serialized_key = self.serialize_key(key)?;
+        let found = self
+            .double_array
+            .find_approximately(&serialized_key, max_distance)?;
+
+        let mut values = Vec::with_capacity(found.len());
+        for (index, distance) in found {
+            if let Some(value) = self.double_array.storage().value_at(index as usize)? {
+                values.push((value, distance));
+            }
+        }
+        Ok(values)
+    }
+
+    /**
+     * Finds the keys which are prefixes of an input.
+     *
+     * One common prefix search walks the trie along the serialized input, so that all the keys
+     * starting at the beginning of a text can be found at once.
+     *
+     * # Arguments
+     * * `input` - An input.
+     *
+     * # Returns
+     * The pairs of the lengths of the keys in the serialized input and the value objects,
+     * where the serialized input is transformed when a key transform is set,
+     * in ascending order of the lengths.
+     *
+     * # Errors
+     * * When it fails to access the storage.
+     */
+    pub fn find_prefixes(
+        &self,
+        input: &KeySerializer::Object<'_>,
+    ) -> Result<Vec<(usize, Rc<Value>)>> {
+        // The input is not a key, so that it is not limited by the maximum key length.
+        let serialized_input =
+            serialize_key(&self.key_serializer, self.key_transform.as_ref(), input);
+        self.find_serialized_prefixes(&serialized_input)
+    }
+
+    /**
+     * Scans a text for the keys.
+     *
+     * A common prefix search is launched at each byte position of the serialized text, and every
+     * key found in the text is reported.
+     *
+     * # Arguments
+     * * `text` - A text.
+     *
+     * # Returns
+     * The triples of the start and end byte offsets in the serialized text and the value objects,
+     * where the serialized text is transformed when a key transform is set,
+     * in ascending order of the start offsets and then the end offsets.
+     *
+     * # Errors
+     * * When it fails to access the storage.
+     */
+    pub fn scan(&self, text: &KeySerializer::Object<'_>) -> Result<Vec<(usize, usize, Rc<Value>)>> {
+        // The text is not a key, so that it is not limited by the maximum key length.
+        let serialized_text =
+            serialize_key(&self.key_serializer, self.key_transform.as_ref(), text);
+        let mut hits = Vec::new();
+        for start in 0..serialized_text.len() {
+            hits.extend(
+                self.find_serialized_prefixes(&serialized_text[start..])?
+                    .into_iter()
+                    .map(|(length, value)| (start, start + length, value)),
+            );
+        }
+        Ok(hits)
+    }
+
+    fn find_serialized_prefixes(&self, serialized_input: &[u8]) -> Result<Vec<(usize, Rc<Value>)>> {
+        let mut found = Vec::new();
+        let mut node = self.double_array.root();
+        for (i, c) in serialized_input.iter().enumerate() {
+            let Some(next_node) = self.double_array.step(node, *c)? else {
+                break;
+            };
+            node = next_node;
+            if let Some(value) = self.value_at_node(node)? {
+                found.push((i + 1, value));
+            }
+        }
+        Ok(found)
+    }
+
+    /**
+     * Searches the keys accepted by an automaton.
+     *
+     * The trie and the automaton are stepped together byte by byte, and a subtree is skipped
+     * as soon as the automaton rejects a byte. So the wildcard and fuzzy queries can be made
+     * with a caller-provided automaton. The key transform is not applied to the automaton.
+     * The keys longer than the maximum key length are skipped.
+     *
+     * # Arguments
+     * * `automaton` - An automaton. Its values are not used.
+     *
+     * # Returns
+     * The pairs of a serialized key and a value object, accepted by both the trie and the
+     * automaton, in the lexicographic order of the serialized keys.
+     *
+     * # Errors
+     * * When it fails to access the storage.
+     * * When the automaton fails.
+     */
+    pub fn search_automaton<A: Automaton + ?Sized>(
+        &self,
+        automaton: &A,
+    ) -> Result<Vec<(Vec<u8>, Rc<Value>)>> {
+        let mut found = Vec::new();
+        let mut stack = vec![(self.double_array.root(), automaton.start(), Vec::new())];
+        while let Some((node, state, key)) = stack.pop() {
+            if automaton.is_final(state)? {
+                if let Some(value) = self.value_at_node(node)? {
+                    found.push((key.clone(), value));
+                }
+            }
+            if self
+                .max_key_length
+                .is_some_and(|max_key_length| key.len() >= max_key_length)
+            {
+                continue;
+            }
+            // The children are pushed in the reverse order so that they are popped in order.
+            for byte in (1..=u8::MAX).rev() {
+                let Some(next_node) = self.double_array.step(node, byte)? else {
+                    continue;
+                };
+                let Some(next_state) = automaton.step(state, byte)? else {
+                    continue;
+                };
+                let mut next_key = key.clone();
+                next_key.push(byte);
+                stack.push((next_node, next_state, next_key));
+            }
+        }
+        Ok(found)
+    }
+
+    /**
+     * Returns an iterator.
+     *
+     * The keys longer than the maximum key length are skipped.
+     *
+     * # Returns
+     * A double array iterator.
+     */
+    pub fn iter(&self) -> TrieIterator<'_, Value> {
+        TrieIterator::new(
+            self.double_array
+                .iter()
+                .with_max_key_length(self.max_key_length),
+            self.double_array.storage(),
+        )
+    }
+
+    /**
+     * Returns an iterator of the keys and the values.
+     *
+     * The keys are reconstructed during the traversal. They are serialized, and can be
+     * deserialized with the deserializer paired with the key serializer, e.g.
+     * `StringDeserializer` for `StringSerializer`, when no key transform is set.
+     * The keys longer than the maximum key length are skipped.
+     *
+     * # Returns
+     * A trie key-value iterator. The serialized keys are in the lexicographic order.
+     */
+    pub fn iter_with_keys(&self) -> TrieKeyValueIterator<'_, Value> {
+        TrieKeyValueIterator::new(
+            self.double_array
+                .iter()
+                .with_max_key_length(self.max_key_length),
+            self.double_array.storage(),
+        )
+    }
+
+    /**
+     * Returns an iterator of the values of the keys with a key prefix.
+     *
+     * The iteration starts at the node of the key prefix and stops the moment the traversal
+     * leaves its subtree. It takes the time proportional to the number of the results, unlike
+     * filtering `iter()`, and does not clone the storage, unlike `subtrie()`.
+     *
+     * # Arguments
+     * * `key_prefix` - A key prefix.
+     *
+     * # Returns
+     * A trie iterator. It is empty when the trie does not have the given key prefix.
+     *
+     * # Errors
+     * * When the serialized key prefix is longer than the maximum key length.
+     * * When it fails to access the storage.
+     */
+    pub fn take_while_prefix(
+        &self,
+        key_prefix: &KeySerializer::Object<'_>,
+    ) -> Result<TrieIterator<'_, Value>> {
+        let serialized_key_prefix = self.serialize_key(key_prefix)?;
+        Ok(TrieIterator::new(
+            self.double_array
+                .iter_with_key_prefix(&serialized_key_prefix)?
+                .with_max_key_length(self.max_key_length),
+            self.double_array.storage(),
+        ))
+    }
+
+    /**
+     * Returns an iterator of the groups of the values by the first bytes of the serialized keys.
+     *
+     * # Arguments
+     * * `prefix_length` - A prefix length.
+     *
+     * # Returns
+     * A prefix group iterator.
+     */
+    pub fn prefix_groups_by_length(&self, prefix_length: usize) -> PrefixGroupIterator<'_, Value> {
+        PrefixGroupIterator::new(&self.double_array, prefix_length, None)
+    }
+
+    /**
+     * Returns an iterator of the groups of the values by the bytes of the serialized keys up to
+     * a delimiter.
+     *
+     * # Arguments
+     * * `delimiter` - A delimiter.
+     *
+     * # Returns
+     * A prefix group iterator.
+     */
+    pub fn prefix_groups_by_delimiter(&self, delimiter: u8) -> PrefixGroupIterator<'_, Value> {
+        PrefixGroupIterator::new(&self.double_array, usize::MAX, Some(delimiter))
+    }
+
+    /**
+     * Returns a subtrie.
+     *
+     * # Arguments
+     * * `key_prefix` - A key prefix.
+     *
+     * # Returns
+     * A subtrie. Or None when the trie does not have the given key prefix.
+     *
+     * # Errors
+     * * When it fails to access the storage.
+     */
+    pub fn subtrie(&self, key_prefix: &KeySerializer::Object<'_>) -> Result<Option<Self>> {
+        let serialized_key_prefix = self.serialize_key(key_prefix)?;
+        let subdouble_array = self.double_array.subtrie(&serialized_key_prefix)?;
+        let Some(subdouble_array) = subdouble_array else {
+            return Ok(None);
+        };
+        Ok(Some(Self {
+            phantom: PhantomData,
+            double_array: subdouble_array,
+            key_serializer: self.key_serializer.clone(),
+            key_transform: self.key_transform.clone(),
+            len: None,
+            hit_counts: RefCell::default(),
+            bloom_filter: None,
+            max_key_length: self.max_key_length,
+            query_stats: self.query_stats.clone(),
+        }))
+    }
+
+    /**
+     * Returns a subtrie view.
+     *
+     * Unlike `subtrie()`, it borrows the storage instead of cloning it.
+     *
+     * # Arguments
+     * * `key_prefix` - A key prefix.
+     *
+     * # Returns
+     * A subtrie view. Or None when the trie does not have the given key prefix.
+     *
+     * # Errors
+     * * When the serialized key prefix is longer than the maximum key length.
+     * * When it fails to access the storage.
+     */
+    pub fn subtrie_view(
+        &self,
+        key_prefix: &KeySerializer::Object<'_>,
+    ) -> Result<Option<SubtrieView<'_, Value>>> {
+        let serialized_key_prefix = self.serialize_key(key_prefix)?;
+        let mut node = self.double_array.root();
+        for byte in &serialized_key_prefix {
+            let Some(next_node) = self.double_array.step(node, *byte)? else {
+                return Ok(None);
+            };
+            node = next_node;
+        }
+        Ok(Some(SubtrieView::new(
+            self.double_array.storage(),
+            node,
+            serialized_key_prefix,
+            self.max_key_length,
+        )))
+    }
+
+    /**
+     * Records a hit of a key.
+     *
+     * The hit counts are accumulated in this trie object, apart from the storage, until they are
+     * folded into the values by `rebuild()`.
+     *
+     * # Arguments
+     * * `key` - A key.
+     *
+     * # Returns
+     * `true` if the trie contains the given key. Or `false` otherwise, when no hit is recorded.
+     *
+     * # Errors
+     * * When it fails to access the storage.
+     */
+    pub fn record_hit(&self, key: &KeySerializer::Object<'_>) -> Result<bool> {
+        let serialized_key = self.serialize_key(key)?;
+        let Some(index) = self.double_array.find(&serialized_key)? else {
+            return Ok(false);
+        };
+        *self.hit_counts.borrow_mut().entry(index).or_default() += 1;
+        Ok(true)
+    }
+
+    /**
+     * Returns the hit count of a key.
+     *
+     * # Arguments
+     * * `key` - A key.
+     *
+     * # Returns
+     * The hit count recorded since the trie was built or rebuilt.
+     *
+     * # Errors
+     * * When it fails to access the storage.
+     */
+    pub fn hit_count(&self, key: &KeySerializer::Object<'_>) -> Result<u64> {
+        let serialized_key = self.serialize_key(key)?;
+        let Some(index) = self.double_array.find(&serialized_key)? else {
+            return Ok(0);
+        };
+        Ok(self
+            .hit_counts
+            .borrow()
+            .get(&index)
+            .copied()
+            .unwrap_or_default())
+    }
+
+    /**
+     * Rebuilds the trie folding the hit counts into the values.
+     *
+     * The trie is rebuilt on a memory storage with the values returned by `fold`, and the hit
+     * counts are reset. The value indices are renumbered in the lexicographic order of the
+     * serialized keys.
+     * For a subtrie, the keys of the rebuilt trie are the ones without the prefix.
+     *
+     * # Arguments
+     * * `fold` - A function which takes a value and its hit count, and returns a new value.
+     *
+     * # Errors
+     * * When it fails to access the storage.
+     */
+    #[cfg(feature = "builder")]
+    pub fn rebuild(&mut self, mut fold: impl FnMut(&Value, u64) -> Value) -> Result<()> {
+        let mut keys = Vec::new();
+        let mut values = Vec::new();
+        {
+            let hit_counts = self.hit_counts.borrow();
+            let mut iterator = self.double_array.iter();
+            while let Some((key, index)) = iterator.next_with_key() {
+                let Some(value) = self.double_array.storage().value_at(index as usize)? else {
+                    continue;
+                };
+                let hit_count = hit_counts
+                    .get(&(index as usize))
+                    .copied()
+                    .unwrap_or_default();
+                keys.push(key);
+                values.push(fold(&value, hit_count));
+            }
+        }
+
+        let mut double_array = DoubleArray::<Value>::builder()
+            .elements(
+                keys.iter()
+                    .enumerate()
+                    .map(|(i, key)| (key.as_slice(), i as i32))
+                    .collect(),
+            )
+            .build()?;
+        for (i, value) in values.into_iter().enumerate() {
+            double_array.storage_mut().add_value_at(i, value)?;
+        }
+
+        self.double_array = double_array;
+        self.len = Some(keys.len());
+        self.hit_counts.borrow_mut().clear();
+        Ok(())
+    }
+
+    /**
+     * Returns the maximum key length.
+     *
+     * # Returns
+     * The maximum key length. Or None when there is no limit.
+     */
+    pub const fn max_key_length(&self) -> Option<usize> {
+        self.max_key_length
+    }
+
+    /**
+     * Returns the Bloom filter.
+     *
+     * # Returns
+     * The Bloom filter. Or None when the trie does not have one.
+     */
+    pub fn bloom_filter(&self) -> Option<&BloomFilter> {
+        self.bloom_filter.as_deref()
+    }
+
+    /**
+     * Returns the query stats.
+     *
+     * # Returns
+     * The query stats. Or None when the queries are not counted.
+     */
+    pub fn query_stats(&self) -> Option<&QueryStats> {
+        self.query_stats.as_deref()
+    }
+
+    /**
+     * Returns the locality stats.
+     *
+     * # Arguments
+     * * `depth` - A depth. The nodes shallower than it are measured.
+     *
+     * # Returns
+     * The locality stats.
+     *
+     * # Errors
+     * * When it fails to access the storage.
+     */
+    pub fn locality_stats(&self, depth: usize) -> Result<LocalityStats> {
+        self.double_array.locality_stats(depth)
     }
 
     /**
      * Returns the storage.
      *
      * # Returns
-     * The storage.
+     * The storage.
+     */
+    pub fn storage(&self) -> &dyn Storage<Value> {
+        self.double_array.storage()
+    }
+
+    pub(crate) fn new_with_settings(
+        storage: Box<dyn Storage<Value>>,
+        settings: TrieSettings<KeySerializer>,
+    ) -> Self {
+        Self {
+            phantom: PhantomData,
+            double_array: DoubleArray::new(storage, settings.root),
+            key_serializer: settings.key_serializer,
+            key_transform: settings.key_transform,
+            len: settings.len,
+            hit_counts: RefCell::default(),
+            bloom_filter: settings.bloom_filter,
+            max_key_length: settings.max_key_length,
+            query_stats: settings.query_stats,
+        }
+    }
+
+    pub(crate) fn settings(&self) -> TrieSettings<KeySerializer> {
+        TrieSettings {
+            root: self.double_array.root(),
+            key_serializer: self.key_serializer.clone(),
+            key_transform: self.key_transform.clone(),
+            len: self.len,
+            bloom_filter: self.bloom_filter.clone(),
+            max_key_length: self.max_key_length,
+            query_stats: self.query_stats.clone(),
+        }
+    }
+
+    #[cfg(feature = "builder")]
+    pub(super) const fn double_array(&self) -> &DoubleArray<Value> {
+        &self.double_array
+    }
+
+    /**
+     * Returns the key transform.
+     *
+     * # Returns
+     * The key transform. Or None when no key transform is set.
+     */
+    pub const fn key_transform(&self) -> Option<&KeyTransform> {
+        self.key_transform.as_ref()
+    }
+
+    pub(super) fn serialize_key(&self, key: &KeySerializer::Object<'_>) -> Result<Vec<u8>> {
+        let serialized_key = serialize_key(&self.key_serializer, self.key_transform.as_ref(), key);
+        if let Some(max_key_length) = self.max_key_length {
+            if serialized_key.len() > max_key_length {
+                return Err(KeyLengthError::TooLong {
+                    length: serialized_key.len(),
+                    max_length: max_key_length,
+                }
+                .into());
+            }
+        }
+        Ok(serialized_key)
+    }
+
+    /**
+     * Verifies the trie against elements.
+     *
+     * It confirms that every key of the elements resolves to the value, and that the iteration
+     * returns exactly the keys of the elements. When a key appears more than once, the first
+     * value is expected, as the builder does.
+     *
+     * # Arguments
+     * * `elements` - Elements.
+     *
+     * # Errors
+     * * When the trie does not match the elements.
+     * * When it fails to access the storage.
+     */
+    pub fn verify_against(&self, elements: &[(KeySerializer::Object<'_>, Value)]) -> Result<()>
+    where
+        Value: PartialEq,
+    {
+        let mut expected = HashMap::with_capacity(elements.len());
+        for (key, value) in elements {
+            let _ = expected.entry(self.serialize_key(key)?).or_insert(value);
+        }
+        self.verify_entries(&expected)
+    }
+
+    /**
+     * Verifies that the trie survives a serialization round trip.
+     *
+     * It serializes the storage into a memory buffer, deserializes it, and confirms that the
+     * deserialized trie has the same keys and values as this trie.
+     *
+     * # Arguments
+     * * `value_serializer`   - A serializer for value objects.
+     * * `value_deserializer` - A deserializer for value objects.
+     *
+     * # Errors
+     * * When the deserialized trie does not match this trie.
+     * * When it fails to serialize or deserialize the storage.
+     */
+    #[cfg(feature = "builder")]
+    pub fn verify_serialization(
+        &self,
+        value_serializer: &mut ValueSerializer<'_, Value>,
+        value_deserializer: &mut ValueDeserializer<Value>,
+    ) -> Result<()>
+    where
+        Value: PartialEq,
+    {
+        let mut entries = Vec::new();
+        let mut iterator = self.double_array.iter();
+        while let Some((key, value_index)) = iterator.next_with_key() {
+            if let Some(value) = self.double_array.storage().value_at(value_index as usize)? {
+                entries.push((key, value));
+            }
+        }
+        let expected = entries
+            .iter()
+            .map(|(key, value)| (key.clone(), value.as_ref()))
+            .collect::<HashMap<_, _>>();
+
+        let mut serialized = Vec::new();
+        self.storage()
+            .serialize(&mut serialized, value_serializer)?;
+        let storage =
+            MemoryStorage::new_with_reader(&mut serialized.as_slice(), value_deserializer)?;
+        let deserialized = Self {
+            phantom: PhantomData,
+            double_array: DoubleArray::new(Box::new(storage), self.double_array.root()),
+            key_serializer: self.key_serializer.clone(),
+            key_transform: self.key_transform.clone(),
+            len: None,
+            hit_counts: RefCell::default(),
+            bloom_filter: None,
+            max_key_length: self.max_key_length,
+            query_stats: None,
+        };
+        deserialized.verify_entries(&expected)
+    }
+
+    fn verify_entries(&self, expected: &HashMap<Vec<u8>, &Value>) -> Result<()>
+    where
+        Value: PartialEq,
+    {
+        for (serialized_key, value) in expected {
+            let Some(index) = self.double_array.find(serialized_key)? else {
+                return Err(TrieVerificationError::KeyNotFound.into());
+            };
+            match self.double_array.storage().value_at(index)? {
+                Some(found) if *found == **value => {}
+                _ => return Err(TrieVerificationError::ValueMismatch.into()),
+            }
+        }
+        let mut iterator = self.double_array.iter();
+        while let Some((serialized_key, _)) = iterator.next_with_key() {
+            if !expected.contains_key(&serialized_key) {
+                return Err(TrieVerificationError::UnexpectedKey.into());
+            }
+        }
+        Ok(())
+    }
+
+    /**
+     * Calculates the digest of the serialized storage.
+     *
+     * The digest is the 64-bit FNV-1a hash of the bytes written by `Storage::serialize()`, which
+     * does not depend on the platform. It is useful to verify that dictionary artifacts are
+     * reproduced without writing them.
+     *
+     * # Arguments
+     * * `value_serializer` - A serializer for value objects.
+     *
+     * # Returns
+     * The digest.
+     *
+     * # Errors
+     * * When it fails to serialize the storage.
      */
-    pub fn storage(&self) -> &dyn Storage<Value> {
-        self.double_array.storage()
+    #[cfg(feature = "builder")]
+    pub fn digest(&self, value_serializer: &mut ValueSerializer<'_, Value>) -> Result<u64> {
+        let mut digest_writer = DigestWriter::new();
+        self.storage()
+            .serialize(&mut digest_writer, value_serializer)?;
+        Ok(digest_writer.digest())
+    }
+
+    /**
+     * Dumps the internal structure of the trie.
+     *
+     * It writes the sizes and the filling rate of the storage, and the leading rows of the
+     * base-check array.
+     *
+     * # Arguments
+     * * `writer` - A writer.
+     * * `limit`  - The maximum number of the rows of the base-check array to write.
+     *
+     * # Errors
+     * * When it fails to access the storage or to write.
+     */
+    pub fn dump(&self, writer: &mut dyn Write, limit: usize) -> Result<()> {
+        self.double_array.dump(writer, limit)
+    }
+
+    /**
+     * Returns the root node for a traversal.
+     *
+     * A node is an index of the base-check array of the storage.
+     * Starting with the root node, a traversal steps byte by byte along a serialized key.
+     *
+     * # Returns
+     * The root node.
+     */
+    pub const fn root_node(&self) -> usize {
+        self.double_array.root()
+    }
+
+    /**
+     * Steps one byte from a node.
+     *
+     * # Arguments
+     * * `node` - A node.
+     * * `byte` - A byte of a serialized key.
+     *
+     * # Returns
+     * The next node. Or None when no key continues with the byte.
+     *
+     * # Errors
+     * * When it fails to access the storage.
+     */
+    pub fn step(&self, node: usize, byte: u8) -> Result<Option<usize>> {
+        self.double_array.step(node, byte)
+    }
+
+    /**
+     * Returns `true` when a key terminates at a node.
+     *
+     * # Arguments
+     * * `node` - A node.
+     *
+     * # Returns
+     * `true` when the bytes stepped so far form a whole serialized key.
+     *
+     * # Errors
+     * * When it fails to access the storage.
+     */
+    pub fn is_terminal(&self, node: usize) -> Result<bool> {
+        Ok(self.double_array.value_index(node)?.is_some())
+    }
+
+    /**
+     * Returns the value index of the key terminating at a node.
+     *
+     * The value index can be passed to `Trie::value_by_index()`.
+     *
+     * # Arguments
+     * * `node` - A node.
+     *
+     * # Returns
+     * The value index. Or None when no key terminates at the node.
+     *
+     * # Errors
+     * * When it fails to access the storage.
+     */
+    pub fn value_index_at(&self, node: usize) -> Result<Option<usize>> {
+        self.double_array.value_index(node)
+    }
+
+    /**
+     * Returns the value object of the key terminating at a node.
+     *
+     * # Arguments
+     * * `node` - A node.
+     *
+     * # Returns
+     * The value object. Or None when no key terminates at the node.
+     *
+     * # Errors
+     * * When it fails to access the storage.
+     */
+    pub fn value_at_node(&self, node: usize) -> Result<Option<Rc<Value>>> {
+        let Some(value_index) = self.double_array.value_index(node)? else {
+            return Ok(None);
+        };
+        self.value_at(value_index)
+    }
+
+    fn lookup(&self, key: &KeySerializer::Object<'_>) -> Result<Option<usize>> {
+        let serialized_key = self.serialize_key(key)?;
+        let (index, probe_length) = if self
+            .bloom_filter
+            .as_ref()
+            .is_some_and(|bloom_filter| !bloom_filter.might_contain(&serialized_key))
+        {
+            (None, 0)
+        } else {
+            self.double_array.find_with_probe_length(&serialized_key)?
+        };
+        if let Some(query_stats) = &self.query_stats {
+            query_stats.lookup(probe_length);
+        }
+        Ok(index)
+    }
+
+    fn value_at(&self, value_index: usize) -> Result<Option<Rc<Value>>> {
+        if let Some(query_stats) = &self.query_stats {
+            query_stats.value_decode();
+        }
+        self.double_array.storage().value_at(value_index)
+    }
+}
+
+#[cfg(feature = "builder")]
+struct DigestWriter {
+    digest: u64,
+}
+
+#[cfg(feature = "builder")]
+impl DigestWriter {
+    const OFFSET_BASIS: u64 = 0xCBF29CE484222325;
+
+    const PRIME: u64 = 0x00000100000001B3;
+
+    const fn new() -> Self {
+        Self {
+            digest: Self::OFFSET_BASIS,
+        }
+    }
+
+    const fn digest(&self) -> u64 {
+        self.digest
+    }
+}
+
+#[cfg(feature = "builder")]
+impl Write for DigestWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        for byte in buf {
+            self.digest ^= *byte as u64;
+            self.digest = self.digest.wrapping_mul(Self::PRIME);
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
     }
 }
 
-#[cfg(test)]
+fn serialize_key<KeySerializer: Serializer>(
+    key_serializer: &KeySerializer,
+    key_transform: Option<&KeyTransform>,
+    key: &KeySerializer::Object<'_>,
+) -> Vec<u8> {
+    let serialized_key = key_serializer.serialize(key);
+    match key_transform {
+        Some(key_transform) => key_transform.transform(&serialized_key),
+        None => serialized_key,
+    }
+}
+
+#[cfg(all(test, feature = "builder"))]
 mod tests {
-    use std::io::Cursor;
+    use std::cell::Cell;
+    use std::io::{Cursor, Seek, SeekFrom};
     use std::sync::LazyLock;
 
-    use crate::memory_storage::MemoryStorage;
+    use crate::integer_serializer::{IntegerDeserializer, IntegerSerializer};
+    use crate::key_transform::KeyTransformError;
+    use crate::section_table::SectionTable;
     use crate::serializer::Deserializer;
+    use crate::storage::SerializationSection;
     use crate::string_serializer::{StrSerializer, StringDeserializer};
-    use crate::value_serializer::{ValueDeserializer, ValueSerializer};
 
     use super::*;
 
@@ -432,7 +1867,7 @@ mod tests {
         0xE7u8, 0x8Eu8, 0x89u8, 0xE5u8, 0x90u8, 0x8Du8,
     ];
 
-    fn create_input_stream() -> Box<dyn std::io::Read> {
+    fn create_input_stream() -> Box<dyn io::Read> {
         Box::new(Cursor::new(SERIALIZED))
     }
 
@@ -498,55 +1933,561 @@ mod tests {
                 ))
                 .unwrap();
 
-            let key_deserializer = StringDeserializer::new(true);
-            assert_eq!(added_serialized_keys.len(), 2);
-            assert_eq!(
-                key_deserializer
-                    .deserialize(added_serialized_keys[0].as_ref())
-                    .unwrap(),
-                "Kumamoto"
-            );
-            assert_eq!(
-                key_deserializer
-                    .deserialize(added_serialized_keys[1].as_ref())
-                    .unwrap(),
-                "Tamana"
-            );
-            assert!(done);
+            let key_deserializer = StringDeserializer::new(true);
+            assert_eq!(added_serialized_keys.len(), 2);
+            assert_eq!(
+                key_deserializer
+                    .deserialize(added_serialized_keys[0].as_ref())
+                    .unwrap(),
+                "Kumamoto"
+            );
+            assert_eq!(
+                key_deserializer
+                    .deserialize(added_serialized_keys[1].as_ref())
+                    .unwrap(),
+                "Tamana"
+            );
+            assert!(done);
+        }
+
+        {
+            let mut added_serialized_keys = Vec::<Vec<u8>>::new();
+            let mut done = false;
+            let _trie = Trie::<&str, i32>::builder()
+                .elements([("Kumamoto", 42), ("Tamana", 24)].to_vec())
+                .key_serializer(StrSerializer::new(true))
+                .double_array_density_factor(DEFAULT_DOUBLE_ARRAY_DENSITY_FACTOR)
+                .build_with_observer_set(&mut BuldingObserverSet::new(
+                    &mut |serialized_keys| {
+                        added_serialized_keys.push(serialized_keys.to_vec());
+                    },
+                    &mut || {
+                        done = true;
+                    },
+                ))
+                .unwrap();
+
+            let key_deserializer = StringDeserializer::new(true);
+            assert_eq!(added_serialized_keys.len(), 2);
+            assert_eq!(
+                key_deserializer
+                    .deserialize(added_serialized_keys[0].as_ref())
+                    .unwrap(),
+                "Kumamoto"
+            );
+            assert_eq!(
+                key_deserializer
+                    .deserialize(added_serialized_keys[1].as_ref())
+                    .unwrap(),
+                "Tamana"
+            );
+            assert!(done);
+        }
+    }
+
+    #[test]
+    fn rejected() {
+        let mut added_serialized_keys = Vec::<Vec<u8>>::new();
+        let mut rejected_serialized_keys = Vec::<(Vec<u8>, RejectionReason)>::new();
+        let mut adding =
+            |serialized_key: &[u8]| added_serialized_keys.push(serialized_key.to_vec());
+        let mut rejected = |serialized_key: &[u8], reason| {
+            rejected_serialized_keys.push((serialized_key.to_vec(), reason))
+        };
+        let mut done = || {};
+        let trie = Trie::<&str, i32>::builder()
+            .elements([("Uto", 55), ("Kumamoto", 42), ("Uto", 56), ("Uto", 57)].to_vec())
+            .key_serializer(StrSerializer::new(false))
+            .build_with_observer_set(
+                &mut BuldingObserverSet::new(&mut adding, &mut done).with_rejected(&mut rejected),
+            )
+            .unwrap();
+
+        assert_eq!(*trie.find(&"Uto").unwrap().unwrap(), 55);
+        assert_eq!(
+            added_serialized_keys,
+            vec![b"Kumamoto".to_vec(), b"Uto".to_vec()]
+        );
+        assert_eq!(
+            rejected_serialized_keys,
+            vec![
+                (b"Uto".to_vec(), RejectionReason::DuplicateKey),
+                (b"Uto".to_vec(), RejectionReason::DuplicateKey),
+            ]
+        );
+    }
+
+    #[test]
+    fn base_check_block_size() {
+        let elements = [
+            ("kumamoto", 42),
+            ("tamana", 24),
+            ("uto", 55),
+            ("yatsushiro", 52),
+        ]
+        .to_vec();
+        let contiguous = Trie::<&str, i32>::builder()
+            .elements(elements.clone())
+            .build()
+            .unwrap();
+
+        let chunked = Trie::<&str, i32>::builder()
+            .elements(elements.clone())
+            .base_check_block_size(3)
+            .build()
+            .unwrap();
+
+        for (key, value) in &elements {
+            assert_eq!(*chunked.find(key).unwrap().unwrap(), *value);
+        }
+        assert!(chunked.find(&"kuma").unwrap().is_none());
+        let mut value_serializer = ValueSerializer::new(
+            Box::new(|value: &i32| IntegerSerializer::new(false).serialize(value)),
+            size_of::<i32>(),
+        );
+        assert_eq!(
+            chunked.digest(&mut value_serializer).unwrap(),
+            contiguous.digest(&mut value_serializer).unwrap()
+        );
+    }
+
+    #[test]
+    fn breadth_first_depth() {
+        let elements = [
+            ("kumamoto", 42),
+            ("kumagaya", 11),
+            ("kurume", 40),
+            ("tamana", 24),
+            ("tamano", 33),
+            ("uto", 55),
+            ("ube", 35),
+            ("yatsushiro", 52),
+        ]
+        .to_vec();
+        let depth_first = Trie::<&str, i32>::builder()
+            .elements(elements.clone())
+            .build()
+            .unwrap();
+
+        let mut localities = Vec::new();
+        let mut added_count = 0usize;
+        let mut adding = |_: &[u8]| added_count += 1;
+        let mut locality =
+            |before: &LocalityStats, after: &LocalityStats| localities.push((*before, *after));
+        let mut done = || {};
+        let breadth_first = Trie::<&str, i32>::builder()
+            .elements(elements.clone())
+            .breadth_first_depth(2)
+            .build_with_observer_set(
+                &mut BuldingObserverSet::new(&mut adding, &mut done).with_locality(&mut locality),
+            )
+            .unwrap();
+
+        for (key, value) in &elements {
+            assert_eq!(*breadth_first.find(key).unwrap().unwrap(), *value);
+        }
+        assert!(breadth_first.find(&"kuma").unwrap().is_none());
+        assert_eq!(
+            breadth_first.iter().collect::<Vec<_>>(),
+            depth_first.iter().collect::<Vec<_>>()
+        );
+        assert_eq!(added_count, elements.len());
+
+        assert_eq!(localities.len(), 1);
+        let (before, after) = localities[0];
+        assert_eq!(before, depth_first.locality_stats(2).unwrap());
+        assert_eq!(after, breadth_first.locality_stats(2).unwrap());
+        assert_eq!(before.depth(), 2);
+        assert_eq!(before.node_count(), 5);
+        assert_eq!(after.node_count(), before.node_count());
+        assert!(after.span() < before.span());
+        assert!(after.average_distance() < before.average_distance());
+    }
+
+    #[test]
+    fn query_stats() {
+        let query_stats = Arc::new(QueryStats::new());
+        let trie = Trie::<&str, i32>::builder()
+            .elements([("kumamoto", 42), ("tamana", 24)].to_vec())
+            .query_stats(query_stats.clone())
+            .build()
+            .unwrap();
+
+        assert!(trie.find(&"kumamoto").unwrap().is_some());
+        assert!(trie.contains(&"tamana").unwrap());
+        assert!(trie.find(&"uto").unwrap().is_none());
+        assert!(trie.value_by_index(1).unwrap().is_some());
+
+        let snapshot = trie.query_stats().unwrap().snapshot();
+        assert_eq!(snapshot.lookup_count(), 3);
+        assert_eq!(snapshot.probe_count(), 9 + 7 + 1);
+        assert!((snapshot.average_probe_length() - 17.0 / 3.0).abs() < f64::EPSILON);
+        assert_eq!(snapshot.value_decode_count(), 2);
+
+        let clone = trie.clone();
+        assert!(clone.contains(&"kumamoto").unwrap());
+        assert_eq!(query_stats.snapshot().lookup_count(), 4);
+
+        let storage = trie.storage().clone_box();
+        let loaded = Trie::<&str, i32>::builder_with_storage(storage)
+            .build()
+            .unwrap();
+        assert!(loaded.query_stats().is_none());
+        assert!(loaded.contains(&"kumamoto").unwrap());
+        assert_eq!(query_stats.snapshot().lookup_count(), 4);
+    }
+
+    #[test]
+    fn build_error() {
+        {
+            let result = Trie::<&str, i32>::builder()
+                .elements([("Kumamoto", 42)].to_vec())
+                .double_array_density_factor(0)
+                .build();
+
+            assert!(matches!(
+                result,
+                Err(TrieError::Build(BuildError::InvalidDensityFactor))
+            ));
+        }
+        {
+            let result = Trie::<&str, i32>::builder()
+                .elements([("Kumamoto", 42), ("Tamana", 24), ("Kumamoto", 43)].to_vec())
+                .duplicate_keys_allowed(false)
+                .build();
+
+            assert!(matches!(
+                result,
+                Err(TrieError::Build(BuildError::DuplicateKey { index: 2 }))
+            ));
+        }
+        {
+            let added_count = Cell::new(0);
+            let mut adding = |_: &[u8]| added_count.set(added_count.get() + 1);
+            let mut cancelled = || added_count.get() >= 1;
+            let result = Trie::<&str, i32>::builder()
+                .elements([("Kumamoto", 42), ("Tamana", 24), ("Uto", 55)].to_vec())
+                .build_with_observer_set(
+                    &mut BuldingObserverSet::new(&mut adding, &mut || {})
+                        .with_cancelled(&mut cancelled),
+                );
+
+            assert!(matches!(
+                result,
+                Err(TrieError::Build(BuildError::Cancelled))
+            ));
+        }
+    }
+
+    #[test]
+    fn key_transform() {
+        let lowercase = KeyTransform::new("lowercase", <[u8]>::to_ascii_lowercase);
+        let trie = Trie::<&str, i32>::builder()
+            .elements([("Kumamoto", 42), ("TAMANA", 24)].to_vec())
+            .key_serializer(StrSerializer::new(true))
+            .key_transform(lowercase.clone())
+            .build()
+            .unwrap();
+
+        assert_eq!(trie.key_transform().unwrap().id(), "lowercase");
+        assert_eq!(*trie.find(&"kumamoto").unwrap().unwrap(), 42);
+        assert_eq!(*trie.find(&"KUMAMOTO").unwrap().unwrap(), 42);
+        assert_eq!(*trie.find(&"Tamana").unwrap().unwrap(), 24);
+
+        let mut writer = Cursor::new(Vec::new());
+        let mut value_serializer = ValueSerializer::new(
+            Box::new(|value: &i32| IntegerSerializer::new(false).serialize(value)),
+            size_of::<i32>(),
+        );
+        trie.storage()
+            .as_any()
+            .downcast_ref::<MemoryStorage<i32>>()
+            .unwrap()
+            .serialize_sections(
+                &mut writer,
+                &mut value_serializer,
+                &[(SerializationSection::KeyTransform, &lowercase.serialize())],
+            )
+            .unwrap();
+
+        let mut reader = Cursor::new(writer.into_inner());
+        let section_table = SectionTable::new_with_reader(&mut reader).unwrap();
+        assert!(KeyTransform::verify(Some(&lowercase), &mut reader, &section_table).is_ok());
+        assert!(KeyTransform::verify(None, &mut reader, &section_table).is_err());
+        let mut load = |key_transform: Option<KeyTransform>| {
+            let _ = reader.seek(SeekFrom::Start(0)).unwrap();
+            let storage = MemoryStorage::new_with_sections(
+                &mut reader,
+                Some(&mut ValueDeserializer::new(Box::new(
+                    |serialized: &[u8]| IntegerDeserializer::new(false).deserialize(serialized),
+                ))),
+            )
+            .unwrap();
+            let builder = Trie::<&str, i32>::builder_with_storage(Box::new(storage))
+                .key_serializer(StrSerializer::new(true));
+            match key_transform {
+                Some(key_transform) => builder.key_transform(key_transform).build(),
+                None => builder.build(),
+            }
+        };
+        {
+            let loaded = load(Some(lowercase)).unwrap();
+            assert_eq!(*loaded.find(&"TaMaNa").unwrap().unwrap(), 24);
+        }
+        {
+            let uppercase = KeyTransform::new("uppercase", <[u8]>::to_ascii_uppercase);
+            let error = load(Some(uppercase)).unwrap_err();
+            assert!(matches!(
+                error,
+                TrieError::KeyTransform(KeyTransformError::Mismatch)
+            ));
+        }
+        {
+            let error = load(None).unwrap_err();
+            assert!(matches!(
+                error,
+                TrieError::KeyTransform(KeyTransformError::Mismatch)
+            ));
+        }
+    }
+
+    #[test]
+    fn bloom_filter() {
+        let query_stats = Arc::new(QueryStats::new());
+        let trie = Trie::<&str, i32>::builder()
+            .elements([("Kumamoto", 42), ("Tamana", 24), ("Uto", 55)].to_vec())
+            .key_serializer(StrSerializer::new(true))
+            .bloom_filter_bits_per_key(10)
+            .query_stats(query_stats.clone())
+            .build()
+            .unwrap();
+
+        let bloom_filter = trie.bloom_filter().unwrap();
+        assert!(bloom_filter.might_contain(b"Kumamoto"));
+        assert_eq!(*trie.find(&"Tamana").unwrap().unwrap(), 24);
+        assert!(trie.contains(&"Uto").unwrap());
+        let mut rejected_count = 0;
+        for key in ["Kikuchi", "Yatsushiro", "Aso", "Hitoyoshi", "Minamata"] {
+            let probe_count = query_stats.snapshot().probe_count();
+            assert!(trie.find(&key).unwrap().is_none());
+            if !bloom_filter.might_contain(key.as_bytes()) {
+                assert_eq!(query_stats.snapshot().probe_count(), probe_count);
+                rejected_count += 1;
+            }
+        }
+        assert!(rejected_count > 0);
+        assert_eq!(query_stats.snapshot().lookup_count(), 7);
+        assert!(trie
+            .subtrie(&"Ku")
+            .unwrap()
+            .unwrap()
+            .bloom_filter()
+            .is_none());
+        assert!(Trie::<&str, i32>::builder()
+            .elements([("Kumamoto", 42)].to_vec())
+            .build()
+            .unwrap()
+            .bloom_filter()
+            .is_none());
+
+        let mut writer = Cursor::new(Vec::new());
+        let mut value_serializer = ValueSerializer::new(
+            Box::new(|value: &i32| IntegerSerializer::new(false).serialize(value)),
+            size_of::<i32>(),
+        );
+        trie.storage()
+            .as_any()
+            .downcast_ref::<MemoryStorage<i32>>()
+            .unwrap()
+            .serialize_sections(
+                &mut writer,
+                &mut value_serializer,
+                &[(SerializationSection::BloomFilter, &bloom_filter.serialize())],
+            )
+            .unwrap();
+
+        let mut reader = Cursor::new(writer.into_inner());
+        let section_table = SectionTable::new_with_reader(&mut reader).unwrap();
+        let loaded_bloom_filter = BloomFilter::new_with_section(&mut reader, &section_table)
+            .unwrap()
+            .unwrap();
+        assert_eq!(&loaded_bloom_filter, bloom_filter);
+        let _ = reader.seek(SeekFrom::Start(0)).unwrap();
+        let storage = MemoryStorage::new_with_sections(
+            &mut reader,
+            Some(&mut ValueDeserializer::new(Box::new(
+                |serialized: &[u8]| IntegerDeserializer::new(false).deserialize(serialized),
+            ))),
+        )
+        .unwrap();
+        let loaded = Trie::<&str, i32>::builder_with_storage(Box::new(storage))
+            .key_serializer(StrSerializer::new(true))
+            .bloom_filter(loaded_bloom_filter)
+            .build()
+            .unwrap();
+        assert_eq!(*loaded.find(&"Uto").unwrap().unwrap(), 55);
+        assert!(loaded.find(&"Aso").unwrap().is_none());
+    }
+
+    #[test]
+    fn max_key_length() {
+        {
+            let result = Trie::<&str, i32>::builder()
+                .elements([("Kumamoto", 42), ("Tamana", 24), ("Yatsushiro", 52)].to_vec())
+                .max_key_length(8)
+                .build();
+
+            assert!(matches!(
+                result,
+                Err(TrieError::Build(BuildError::KeyTooLong { index: 2 }))
+            ));
+        }
+        {
+            let trie = Trie::<&str, i32>::builder()
+                .elements([("Kumamoto", 42), ("Tamana", 24)].to_vec())
+                .max_key_length(8)
+                .build()
+                .unwrap();
+
+            assert_eq!(trie.max_key_length(), Some(8));
+            assert_eq!(*trie.find(&"Kumamoto").unwrap().unwrap(), 42);
+            assert!(matches!(
+                trie.find(&"Yatsushiro"),
+                Err(TrieError::KeyLength(KeyLengthError::TooLong {
+                    length: 10,
+                    max_length: 8
+                }))
+            ));
+            assert!(trie.contains(&"Yatsushiro").is_err());
+            assert!(trie.subtrie(&"Yatsushiro").is_err());
+            assert_eq!(trie.scan(&"Kumamoto-Tamana").unwrap().len(), 2);
+        }
+        {
+            let trie = Trie::<&str, i32>::builder()
+                .elements([("Kumamoto", 42), ("Tamana", 24), ("Yatsushiro", 52)].to_vec())
+                .build()
+                .unwrap();
+            let limited = Trie::<&str, i32>::builder_with_storage(trie.storage().clone_box())
+                .max_key_length(8)
+                .build()
+                .unwrap();
+
+            assert_eq!(
+                limited.iter().map(|value| *value).collect::<Vec<_>>(),
+                vec![42, 24]
+            );
+            assert_eq!(
+                limited.iter().rev().map(|value| *value).collect::<Vec<_>>(),
+                vec![24, 42]
+            );
+        }
+    }
+
+    #[test]
+    fn search_automaton() {
+        // Matches a pattern in which '?' matches any one byte.
+        #[derive(Debug)]
+        struct Wildcard(&'static [u8]);
+
+        impl Automaton for Wildcard {
+            type State = usize;
+
+            type Value = ();
+
+            fn start(&self) -> Self::State {
+                0
+            }
+
+            fn step(&self, state: Self::State, byte: u8) -> Result<Option<Self::State>> {
+                Ok(self
+                    .0
+                    .get(state)
+                    .filter(|&&c| c == b'?' || c == byte)
+                    .map(|_| state + 1))
+            }
+
+            fn is_final(&self, state: Self::State) -> Result<bool> {
+                Ok(state == self.0.len())
+            }
+
+            fn value(&self, state: Self::State) -> Result<Option<Self::Value>> {
+                Ok((state == self.0.len()).then_some(()))
+            }
+        }
+
+        let trie = Trie::<&str, i32>::builder()
+            .elements(
+                [
+                    ("tamana", 24),
+                    ("tamano", 33),
+                    ("tamanoura", 81),
+                    ("kumamoto", 42),
+                    ("tamina", 0),
+                ]
+                .to_vec(),
+            )
+            .key_serializer(StrSerializer::new(false))
+            .build()
+            .unwrap();
+
+        {
+            let found = trie.search_automaton(&Wildcard(b"tam?n?")).unwrap();
+
+            assert_eq!(
+                found
+                    .iter()
+                    .map(|(key, value)| (key.as_slice(), **value))
+                    .collect::<Vec<_>>(),
+                vec![
+                    (b"tamana".as_slice(), 24),
+                    (b"tamano".as_slice(), 33),
+                    (b"tamina".as_slice(), 0),
+                ]
+            );
+        }
+        {
+            let automaton: &dyn Automaton<State = usize, Value = ()> = &Wildcard(b"????????");
+
+            let found = trie.search_automaton(automaton).unwrap();
+
+            assert_eq!(found.len(), 1);
+            assert_eq!(found[0].0, b"kumamoto");
+        }
+        {
+            assert!(trie.search_automaton(&Wildcard(b"uto")).unwrap().is_empty());
+        }
+    }
+
+    #[test]
+    fn elements_sorted() {
+        {
+            let trie = Trie::<&str, i32>::builder()
+                .elements([("Kumamoto", 42), ("Tamana", 24), ("Uto", 55)].to_vec())
+                .elements_sorted(true)
+                .build()
+                .unwrap();
+
+            assert_eq!(*trie.find(&"Tamana").unwrap().unwrap(), 24);
+            assert_eq!(trie.value_index(&"Uto").unwrap(), Some(2));
         }
+        {
+            let result = Trie::<&str, i32>::builder()
+                .elements([("Kumamoto", 42), ("Uto", 55), ("Tamana", 24)].to_vec())
+                .elements_sorted(true)
+                .build();
 
+            assert!(matches!(
+                result,
+                Err(TrieError::Build(BuildError::ElementsNotSorted { index: 2 }))
+            ));
+        }
         {
-            let mut added_serialized_keys = Vec::<Vec<u8>>::new();
-            let mut done = false;
-            let _trie = Trie::<&str, i32>::builder()
-                .elements([("Kumamoto", 42), ("Tamana", 24)].to_vec())
-                .key_serializer(StrSerializer::new(true))
-                .double_array_density_factor(DEFAULT_DOUBLE_ARRAY_DENSITY_FACTOR)
-                .build_with_observer_set(&mut BuldingObserverSet::new(
-                    &mut |serialized_keys| {
-                        added_serialized_keys.push(serialized_keys.to_vec());
-                    },
-                    &mut || {
-                        done = true;
-                    },
-                ))
+            let trie = Trie::<&str, i32>::builder()
+                .elements([("Kumamoto", 42), ("Uto", 55), ("Tamana", 24)].to_vec())
+                .build()
                 .unwrap();
 
-            let key_deserializer = StringDeserializer::new(true);
-            assert_eq!(added_serialized_keys.len(), 2);
-            assert_eq!(
-                key_deserializer
-                    .deserialize(added_serialized_keys[0].as_ref())
-                    .unwrap(),
-                "Kumamoto"
-            );
-            assert_eq!(
-                key_deserializer
-                    .deserialize(added_serialized_keys[1].as_ref())
-                    .unwrap(),
-                "Tamana"
-            );
-            assert!(done);
+            assert_eq!(*trie.find(&"Tamana").unwrap().unwrap(), 24);
         }
     }
 
@@ -562,7 +2503,9 @@ mod tests {
             let storage = Box::new(
                 MemoryStorage::new_with_reader(&mut reader, &mut value_deserializer).unwrap(),
             );
-            let _trie = Trie::<&str, String>::builder_with_storage(storage).build();
+            let _trie = Trie::<&str, String>::builder_with_storage(storage)
+                .build()
+                .unwrap();
         }
 
         {
@@ -577,7 +2520,8 @@ mod tests {
             );
             let _trie = Trie::<&str, String>::builder_with_storage(storage)
                 .key_serializer(StrSerializer::new(true))
-                .build();
+                .build()
+                .unwrap();
         }
     }
 
@@ -612,6 +2556,64 @@ mod tests {
         }
     }
 
+    #[test]
+    fn len() {
+        {
+            let trie = Trie::<&str, String>::builder().build().unwrap();
+
+            assert_eq!(trie.len().unwrap(), 0);
+        }
+        {
+            let trie = Trie::<&str, String>::builder()
+                .elements(
+                    [
+                        (KUMAMOTO, KUMAMOTO.to_string()),
+                        (TAMANA, TAMANA.to_string()),
+                        (KUMAMOTO, TAMANA.to_string()),
+                    ]
+                    .to_vec(),
+                )
+                .build()
+                .unwrap();
+
+            assert_eq!(trie.len().unwrap(), 2);
+            assert_eq!(trie.size().unwrap(), 3);
+        }
+        {
+            let trie = Trie::<&str, String>::builder()
+                .elements(
+                    [
+                        (TAMANA, TAMANA.to_string()),
+                        (TAMARAI, TAMARAI.to_string()),
+                        (KUMAMOTO, KUMAMOTO.to_string()),
+                    ]
+                    .to_vec(),
+                )
+                .build()
+                .unwrap();
+            let subtrie = trie.subtrie(&TAMA).unwrap().unwrap();
+
+            assert_eq!(subtrie.len().unwrap(), 2);
+            assert!(!subtrie.is_empty().unwrap());
+        }
+        {
+            let mut reader = create_input_stream();
+            let mut value_deserializer = ValueDeserializer::new(Box::new(|serialized| {
+                static STRING_DESERIALIZER: LazyLock<StringDeserializer> =
+                    LazyLock::new(|| StringDeserializer::new(false));
+                STRING_DESERIALIZER.deserialize(serialized)
+            }));
+            let storage = Box::new(
+                MemoryStorage::new_with_reader(&mut reader, &mut value_deserializer).unwrap(),
+            );
+            let trie = Trie::<&str, String>::builder_with_storage(storage)
+                .build()
+                .unwrap();
+
+            assert_eq!(trie.len().unwrap(), 2);
+        }
+    }
+
     #[test]
     fn size() {
         {
@@ -703,6 +2705,166 @@ mod tests {
         }
     }
 
+    #[test]
+    fn value_index() {
+        let trie = Trie::<&str, i32>::builder()
+            .elements([("Kumamoto", 42), ("Tamana", 24), ("Kumamoto", 4242)].to_vec())
+            .build()
+            .unwrap();
+
+        assert_eq!(trie.value_index(&"Kumamoto").unwrap(), Some(0));
+        assert_eq!(trie.value_index(&"Tamana").unwrap(), Some(1));
+        assert!(trie.value_index(&"Uto").unwrap().is_none());
+        assert_eq!(*trie.value_by_index(1).unwrap().unwrap(), 24);
+        assert!(trie.value_by_index(3).unwrap().is_none());
+
+        let mut serialized = Vec::new();
+        let mut value_serializer = ValueSerializer::new(
+            Box::new(|value: &i32| IntegerSerializer::new(false).serialize(value)),
+            size_of::<i32>(),
+        );
+        trie.storage()
+            .serialize(&mut serialized, &mut value_serializer)
+            .unwrap();
+        let mut value_deserializer = ValueDeserializer::new(Box::new(|serialized: &[u8]| {
+            IntegerDeserializer::<i32>::new(false).deserialize(serialized)
+        }));
+        let storage =
+            MemoryStorage::new_with_reader(&mut serialized.as_slice(), &mut value_deserializer)
+                .unwrap();
+        let deserialized = Trie::<&str, i32>::builder_with_storage(Box::new(storage))
+            .build()
+            .unwrap();
+
+        assert_eq!(deserialized.value_index(&"Tamana").unwrap(), Some(1));
+        assert_eq!(*deserialized.value_by_index(1).unwrap().unwrap(), 24);
+    }
+
+    #[test]
+    fn find_approximately() {
+        {
+            let trie = Trie::<&str, String>::builder().build().unwrap();
+
+            let found = trie.find_approximately(&KUMAMOTO, 1).unwrap();
+            assert!(found.is_empty());
+        }
+        {
+            let trie = Trie::<&str, String>::builder()
+                .elements(
+                    [
+                        (TAMANA, TAMANA.to_string()),
+                        (TAMARAI, TAMARAI.to_string()),
+                        (UTO, UTO.to_string()),
+                    ]
+                    .to_vec(),
+                )
+                .build()
+                .unwrap();
+
+            {
+                let found = trie.find_approximately(&TAMANA, 0).unwrap();
+                assert_eq!(found.len(), 1);
+                assert_eq!(*found[0].0, TAMANA.to_string());
+                assert_eq!(found[0].1, 0);
+            }
+            {
+                // "玉名" and "玉来" differ in one character, which is three bytes in UTF-8.
+                let found = trie.find_approximately(&TAMANA, 1).unwrap();
+                assert_eq!(found.len(), 2);
+                assert_eq!(*found[0].0, TAMANA.to_string());
+                assert_eq!(found[0].1, 0);
+                assert_eq!(*found[1].0, TAMARAI.to_string());
+                assert_eq!(found[1].1, 1);
+            }
+            {
+                let found = trie.find_approximately(&TAMANA, 2).unwrap();
+                assert_eq!(found.len(), 3);
+                assert_eq!(*found[2].0, UTO.to_string());
+                assert_eq!(found[2].1, 2);
+            }
+            {
+                let found = trie.find_approximately(&KUMAMOTO, 1).unwrap();
+                assert!(found.is_empty());
+            }
+        }
+    }
+
+    #[test]
+    fn find_prefixes() {
+        {
+            let trie = Trie::<&str, &str>::builder()
+                .elements(
+                    [
+                        ("ku", "Ku"),
+                        ("kuma", "Kuma"),
+                        ("kumamoto", "Kumamoto"),
+                        ("moto", "Moto"),
+                    ]
+                    .to_vec(),
+                )
+                .build()
+                .unwrap();
+
+            let found = trie
+                .find_prefixes(&"kumamotoshi")
+                .unwrap()
+                .into_iter()
+                .map(|(length, value)| (length, *value))
+                .collect::<Vec<_>>();
+            assert_eq!(found, vec![(2, "Ku"), (4, "Kuma"), (8, "Kumamoto")]);
+            assert!(trie.find_prefixes(&"").unwrap().is_empty());
+            assert!(trie.find_prefixes(&"akumamoto").unwrap().is_empty());
+        }
+        {
+            let trie = Trie::<&str, &str>::builder()
+                .elements([("kuma", "Kuma")].to_vec())
+                .key_transform(KeyTransform::new("lowercase", |key: &[u8]| {
+                    key.to_ascii_lowercase()
+                }))
+                .build()
+                .unwrap();
+
+            let found = trie.find_prefixes(&"KUMAMOTO").unwrap();
+            assert_eq!(found.len(), 1);
+            assert_eq!(found[0].0, 4);
+            assert_eq!(*found[0].1, "Kuma");
+        }
+    }
+
+    #[test]
+    fn scan() {
+        let trie = Trie::<&str, &str>::builder()
+            .elements(
+                [
+                    ("kuma", "Kuma"),
+                    ("kumamoto", "Kumamoto"),
+                    ("moto", "Moto"),
+                    ("to", "To"),
+                ]
+                .to_vec(),
+            )
+            .build()
+            .unwrap();
+
+        let hits = trie
+            .scan(&"akumamotoshi")
+            .unwrap()
+            .into_iter()
+            .map(|(start, end, value)| (start, end, *value))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            hits,
+            vec![
+                (1, 5, "Kuma"),
+                (1, 9, "Kumamoto"),
+                (5, 9, "Moto"),
+                (7, 9, "To"),
+            ]
+        );
+        assert!(trie.scan(&"").unwrap().is_empty());
+        assert!(trie.scan(&"kum").unwrap().is_empty());
+    }
+
     #[test]
     fn iter() {
         {
@@ -726,6 +2888,101 @@ mod tests {
         }
     }
 
+    #[test]
+    fn iter_with_keys() {
+        {
+            let trie = Trie::<&str, i32>::builder().build().unwrap();
+
+            assert!(trie.iter_with_keys().next().is_none());
+        }
+        {
+            let trie = Trie::<&str, i32>::builder()
+                .elements([(KUMAMOTO, 42), (TAMANA, 24)].to_vec())
+                .build()
+                .unwrap();
+            let key_deserializer = StringDeserializer::new(true);
+
+            let found = trie
+                .iter_with_keys()
+                .map(|(key, value)| (key_deserializer.deserialize(&key).unwrap(), *value))
+                .collect::<Vec<_>>();
+            assert_eq!(
+                found,
+                vec![(KUMAMOTO.to_string(), 42), (TAMANA.to_string(), 24)]
+            );
+        }
+        {
+            let trie = Trie::<&str, i32>::builder()
+                .elements([("ab", 1), ("abc", 2), ("b", 3)].to_vec())
+                .build()
+                .unwrap();
+
+            let found = trie
+                .iter_with_keys()
+                .rev()
+                .map(|(key, value)| (key, *value))
+                .collect::<Vec<_>>();
+            assert_eq!(
+                found,
+                vec![
+                    (b"b".to_vec(), 3),
+                    (b"abc".to_vec(), 2),
+                    (b"ab".to_vec(), 1)
+                ]
+            );
+        }
+    }
+
+    #[test]
+    fn take_while_prefix() {
+        {
+            let trie = Trie::<&str, String>::builder().build().unwrap();
+
+            let mut iterator = trie.take_while_prefix(&TAMA).unwrap();
+            assert!(iterator.next().is_none());
+        }
+        {
+            let trie = Trie::<&str, String>::builder()
+                .elements(
+                    [
+                        (KUMAMOTO, KUMAMOTO.to_string()),
+                        (TAMANA, TAMANA.to_string()),
+                        (TAMARAI, TAMARAI.to_string()),
+                        (UTO, UTO.to_string()),
+                    ]
+                    .to_vec(),
+                )
+                .build()
+                .unwrap();
+
+            let mut iterator = trie.take_while_prefix(&TAMA).unwrap();
+            assert_eq!(*iterator.next().unwrap(), TAMANA.to_string());
+            assert_eq!(*iterator.next().unwrap(), TAMARAI.to_string());
+            assert!(iterator.next().is_none());
+
+            let mut iterator = trie.take_while_prefix(&TAMA).unwrap();
+            assert_eq!(*iterator.next_back().unwrap(), TAMARAI.to_string());
+            assert_eq!(*iterator.next().unwrap(), TAMANA.to_string());
+            assert!(iterator.next().is_none());
+
+            assert_eq!(trie.take_while_prefix(&TAMANA).unwrap().count(), 1);
+            assert_eq!(trie.take_while_prefix(&"").unwrap().count(), 4);
+            assert!(trie.take_while_prefix(&"X").unwrap().next().is_none());
+        }
+        {
+            let trie = Trie::<&str, i32>::builder()
+                .elements([("Kuma", 42), ("Kumagaya", 11), ("Kurume", 40)].to_vec())
+                .build()
+                .unwrap();
+            let values = trie
+                .take_while_prefix(&"Kuma")
+                .unwrap()
+                .map(|value| *value)
+                .collect::<Vec<_>>();
+            assert_eq!(values, vec![42, 11]);
+        }
+    }
+
     #[test]
     fn subtrie() {
         {
@@ -833,6 +3090,213 @@ mod tests {
         }
     }
 
+    #[test]
+    fn verify_against() {
+        let elements = [("Kumamoto", 42), ("Tamana", 24), ("Kumamoto", 4242)].to_vec();
+        let trie = Trie::<&str, i32>::builder()
+            .elements(elements.clone())
+            .build()
+            .unwrap();
+
+        assert!(trie.verify_against(&elements).is_ok());
+        assert!(matches!(
+            trie.verify_against(&[("Kumamoto", 42), ("Tamana", 24), ("Uto", 0)]),
+            Err(TrieError::Verification(TrieVerificationError::KeyNotFound))
+        ));
+        assert!(matches!(
+            trie.verify_against(&[("Kumamoto", 42), ("Tamana", 0)]),
+            Err(TrieError::Verification(
+                TrieVerificationError::ValueMismatch
+            ))
+        ));
+        assert!(matches!(
+            trie.verify_against(&[("Kumamoto", 42)]),
+            Err(TrieError::Verification(
+                TrieVerificationError::UnexpectedKey
+            ))
+        ));
+    }
+
+    #[test]
+    fn verify_serialization() {
+        let trie = Trie::<&str, i32>::builder()
+            .elements([("Kumamoto", 42), ("Tamana", 24)].to_vec())
+            .build()
+            .unwrap();
+        let mut value_deserializer = ValueDeserializer::new(Box::new(|serialized: &[u8]| {
+            IntegerDeserializer::<i32>::new(false).deserialize(serialized)
+        }));
+
+        {
+            let mut value_serializer = ValueSerializer::new(
+                Box::new(|value: &i32| IntegerSerializer::new(false).serialize(value)),
+                size_of::<i32>(),
+            );
+            assert!(trie
+                .verify_serialization(&mut value_serializer, &mut value_deserializer)
+                .is_ok());
+        }
+        {
+            let mut broken_value_serializer = ValueSerializer::new(
+                Box::new(|value: &i32| IntegerSerializer::new(false).serialize(&(value + 1))),
+                size_of::<i32>(),
+            );
+            assert!(matches!(
+                trie.verify_serialization(&mut broken_value_serializer, &mut value_deserializer),
+                Err(TrieError::Verification(
+                    TrieVerificationError::ValueMismatch
+                ))
+            ));
+        }
+    }
+
+    #[test]
+    fn digest() {
+        let build = || {
+            Trie::<&str, i32>::builder()
+                .elements([("Kumamoto", 42), ("Tamana", 24), ("Kumanoshou", 4242)].to_vec())
+                .build()
+                .unwrap()
+        };
+        let digest_of = |trie: &Trie<&str, i32>| {
+            let mut value_serializer = ValueSerializer::new(
+                Box::new(|value: &i32| IntegerSerializer::new(false).serialize(value)),
+                size_of::<i32>(),
+            );
+            trie.digest(&mut value_serializer).unwrap()
+        };
+
+        // The digest must be the same on every platform.
+        let digest = digest_of(&build());
+        assert_eq!(digest, 0x6D828333DB24883A);
+        assert_eq!(digest_of(&build()), digest);
+
+        let mut serialized = Vec::new();
+        let mut value_serializer = ValueSerializer::new(
+            Box::new(|value: &i32| IntegerSerializer::new(false).serialize(value)),
+            size_of::<i32>(),
+        );
+        build()
+            .storage()
+            .serialize(&mut serialized, &mut value_serializer)
+            .unwrap();
+        let mut digest_writer = DigestWriter::new();
+        digest_writer.write_all(&serialized).unwrap();
+        assert_eq!(digest_writer.digest(), digest);
+
+        let other = Trie::<&str, i32>::builder()
+            .elements([("Kumamoto", 42), ("Tamana", 24)].to_vec())
+            .build()
+            .unwrap();
+        assert_ne!(digest_of(&other), digest);
+    }
+
+    #[test]
+    fn dump() {
+        let trie = Trie::<&str, i32>::builder()
+            .elements([("Kumamoto", 42), ("Tamana", 24)].to_vec())
+            .build()
+            .unwrap();
+
+        let mut dumped = Vec::new();
+        trie.dump(&mut dumped, 2).unwrap();
+        let dumped = String::from_utf8(dumped).unwrap();
+        let lines = dumped.lines().collect::<Vec<_>>();
+        assert_eq!(lines[0], "root base-check index: 0");
+        assert_eq!(
+            lines[1],
+            format!(
+                "base-check size: {}",
+                trie.storage().base_check_size().unwrap()
+            )
+        );
+        assert_eq!(lines[2], "value count: 2");
+        assert!(lines[3].starts_with("filling rate: "));
+        assert_eq!(lines.len(), 4 + 1 + 2 + 1);
+        assert!(lines[7].ends_with("more rows)"));
+
+        let debug = format!("{:?}", trie);
+        assert!(debug.contains("value_count: 2"));
+        assert!(!debug.contains("base_check_rows"));
+        let alternate_debug = format!("{:#?}", trie);
+        assert!(alternate_debug.contains("base_check_rows"));
+    }
+
+    #[test]
+    fn traversal() {
+        let trie = Trie::<&str, i32>::builder()
+            .elements([("Kumamoto", 42), ("Kumanoshou", 24)].to_vec())
+            .build()
+            .unwrap();
+
+        let mut node = trie.root_node();
+        for byte in "Kuma".as_bytes() {
+            node = trie.step(node, *byte).unwrap().unwrap();
+        }
+        assert!(!trie.is_terminal(node).unwrap());
+        assert!(trie.value_index_at(node).unwrap().is_none());
+        assert!(trie.step(node, b'X').unwrap().is_none());
+
+        for byte in "moto".as_bytes() {
+            node = trie.step(node, *byte).unwrap().unwrap();
+        }
+        assert!(trie.is_terminal(node).unwrap());
+        let value_index = trie.value_index_at(node).unwrap().unwrap();
+        assert_eq!(*trie.storage().value_at(value_index).unwrap().unwrap(), 42);
+    }
+
+    #[test]
+    fn clone() {
+        let trie = Trie::<&str, i32>::builder()
+            .elements([("Kumamoto", 42), ("Tamana", 24)].to_vec())
+            .build()
+            .unwrap();
+        assert!(trie.record_hit(&"Tamana").unwrap());
+
+        let mut cloned = trie.clone();
+        assert_eq!(*cloned.find(&"Kumamoto").unwrap().unwrap(), 42);
+        assert_eq!(cloned.len().unwrap(), 2);
+        assert_eq!(cloned.hit_count(&"Tamana").unwrap(), 1);
+
+        cloned.rebuild(|value, _| value + 1).unwrap();
+        assert_eq!(*cloned.find(&"Kumamoto").unwrap().unwrap(), 43);
+        assert_eq!(*trie.find(&"Kumamoto").unwrap().unwrap(), 42);
+        assert_eq!(trie.hit_count(&"Tamana").unwrap(), 1);
+
+        let subtrie = trie.subtrie(&"Tama").unwrap().unwrap();
+        let cloned_subtrie = subtrie.clone();
+        assert_eq!(
+            cloned_subtrie.iter().map(|v| *v).collect::<Vec<_>>(),
+            vec![24]
+        );
+    }
+
+    #[test]
+    fn record_hit_and_rebuild() {
+        let mut trie = Trie::<&str, i32>::builder()
+            .elements([("Kumamoto", 10), ("Kumanoshou", 20), ("Tamana", 30)].to_vec())
+            .build()
+            .unwrap();
+
+        assert!(trie.record_hit(&"Kumamoto").unwrap());
+        assert!(trie.record_hit(&"Kumamoto").unwrap());
+        assert!(trie.record_hit(&"Tamana").unwrap());
+        assert!(!trie.record_hit(&"Kuma").unwrap());
+        assert_eq!(trie.hit_count(&"Kumamoto").unwrap(), 2);
+        assert_eq!(trie.hit_count(&"Kumanoshou").unwrap(), 0);
+        assert_eq!(trie.hit_count(&"Kuma").unwrap(), 0);
+
+        trie.rebuild(|value, hit_count| value + hit_count as i32 * 100)
+            .unwrap();
+
+        assert_eq!(*trie.find(&"Kumamoto").unwrap().unwrap(), 210);
+        assert_eq!(*trie.find(&"Kumanoshou").unwrap().unwrap(), 20);
+        assert_eq!(*trie.find(&"Tamana").unwrap().unwrap(), 130);
+        assert!(trie.find(&"Kuma").unwrap().is_none());
+        assert_eq!(trie.len().unwrap(), 3);
+        assert_eq!(trie.hit_count(&"Kumamoto").unwrap(), 0);
+    }
+
     #[test]
     fn storage() {
         {
@@ -861,7 +3325,9 @@ mod tests {
             let storage = Box::new(
                 MemoryStorage::new_with_reader(&mut reader, &mut value_deserializer).unwrap(),
             );
-            let trie = Trie::<&str, String>::builder_with_storage(storage).build();
+            let trie = Trie::<&str, String>::builder_with_storage(storage)
+                .build()
+                .unwrap();
 
             let storage = trie.storage();
 
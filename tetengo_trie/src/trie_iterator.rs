@@ -53,7 +53,79 @@ impl<T> Iterator for TrieIterator<'_, T> {
     }
 }
 
-#[cfg(test)]
+impl<T> DoubleEndedIterator for TrieIterator<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let value_index = self.double_array_iterator.next_back()?;
+        match self.storage.value_at(value_index as usize) {
+            Ok(value) => value,
+            Err(e) => {
+                debug_assert!(false, "{}", e);
+                None
+            }
+        }
+    }
+}
+
+/**
+ * A trie key-value iterator.
+ *
+ * The items are the pairs of the serialized keys and the value objects, where the serialized
+ * keys are transformed when a key transform is set.
+ */
+#[derive(Clone, Debug)]
+pub struct TrieKeyValueIterator<'a, T: 'static> {
+    double_array_iterator: DoubleArrayIterator<'a, T>,
+    storage: &'a dyn Storage<T>,
+}
+
+impl<'a, T> TrieKeyValueIterator<'a, T> {
+    /**
+     * Creates an iterator.
+     *
+     * # Arguments
+     * * `double_array_iterator` - A double array iterator.
+     * * `storage`               - A storage.
+     */
+    pub(super) const fn new(
+        double_array_iterator: DoubleArrayIterator<'a, T>,
+        storage: &'a dyn Storage<T>,
+    ) -> Self {
+        Self {
+            double_array_iterator,
+            storage,
+        }
+    }
+}
+
+impl<T> Iterator for TrieKeyValueIterator<'_, T> {
+    type Item = (Vec<u8>, Rc<T>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (key, value_index) = self.double_array_iterator.next_with_key()?;
+        match self.storage.value_at(value_index as usize) {
+            Ok(value) => value.map(|value| (key, value)),
+            Err(e) => {
+                debug_assert!(false, "{}", e);
+                None
+            }
+        }
+    }
+}
+
+impl<T> DoubleEndedIterator for TrieKeyValueIterator<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let (key, value_index) = self.double_array_iterator.next_back_with_key()?;
+        match self.storage.value_at(value_index as usize) {
+            Ok(value) => value.map(|value| (key, value)),
+            Err(e) => {
+                debug_assert!(false, "{}", e);
+                None
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "builder"))]
 mod tests {
     use crate::trie::Trie;
 
@@ -119,4 +191,47 @@ mod tests {
             assert!(iterator.next().is_none());
         }
     }
+
+    #[test]
+    fn next_back() {
+        {
+            let trie = Trie::<&str, String>::builder().build().unwrap();
+            let mut iterator = trie.iter();
+
+            assert!(iterator.next_back().is_none());
+        }
+        {
+            let trie = Trie::<&str, String>::builder()
+                .elements(vec![
+                    (KUMAMOTO, KUMAMOTO.to_string()),
+                    (TAMANA, TAMANA.to_string()),
+                ])
+                .build()
+                .unwrap();
+            let mut iterator = trie.iter();
+
+            assert_eq!(*iterator.next_back().unwrap().as_ref(), TAMANA.to_string());
+            assert_eq!(
+                *iterator.next_back().unwrap().as_ref(),
+                KUMAMOTO.to_string()
+            );
+            assert!(iterator.next_back().is_none());
+        }
+        {
+            let trie = Trie::<&str, i32>::builder()
+                .elements(vec![
+                    ("ab", 1),
+                    ("abc", 2),
+                    ("abd", 3),
+                    ("abe", 4),
+                    ("b", 5),
+                ])
+                .build()
+                .unwrap();
+            let subtrie = trie.subtrie(&"ab").unwrap().unwrap();
+
+            let last_two = subtrie.iter().rev().take(2).map(|v| *v).collect::<Vec<_>>();
+            assert_eq!(last_two, vec![4, 3]);
+        }
+    }
 }
@@ -5,15 +5,27 @@
  */
 
 use std::any::type_name_of_val;
+use std::borrow::Cow;
 use std::fmt::{self, Debug, Formatter};
+use std::io::Write;
 
-use anyhow::Result;
+use crate::error::Result;
 
 /**
  * A serialize function type
  */
 pub type Serialize<'a, Value> = Box<dyn FnMut(&Value) -> Vec<u8> + 'a>;
 
+/**
+ * A streaming serialize function type
+ */
+pub type SerializeTo<'a, Value> = Box<dyn FnMut(&Value, &mut dyn Write) -> Result<()> + 'a>;
+
+enum SerializeFunction<'a, Value: ?Sized> {
+    Buffered(Serialize<'a, Value>),
+    Streaming(SerializeTo<'a, Value>),
+}
+
 /**
  * A value serializer.
  *
@@ -21,8 +33,10 @@ pub type Serialize<'a, Value> = Box<dyn FnMut(&Value) -> Vec<u8> + 'a>;
  * * `Value` - A value type.
  */
 pub struct ValueSerializer<'a, Value: ?Sized> {
-    serialize: Serialize<'a, Value>,
+    serialize: SerializeFunction<'a, Value>,
     fixed_value_size: usize,
+    #[cfg(feature = "compression")]
+    compression_threshold: Option<usize>,
 }
 
 impl<'a, Value: ?Sized> ValueSerializer<'a, Value> {
@@ -35,11 +49,54 @@ impl<'a, Value: ?Sized> ValueSerializer<'a, Value> {
      */
     pub fn new(serialize: Serialize<'a, Value>, fixed_value_size: usize) -> Self {
         Self {
-            serialize,
+            serialize: SerializeFunction::Buffered(serialize),
             fixed_value_size,
+            #[cfg(feature = "compression")]
+            compression_threshold: None,
         }
     }
 
+    /**
+     * Creates a streaming value serializer.
+     *
+     * The serializing function writes a value directly into a writer, so that no buffer is
+     * allocated for each value.
+     *
+     * # Arguments
+     * * `serialize_to`     - A streaming serializing function.
+     * * `fixed_value_size` - The value size if it is fixed. Or 0 if the size is variable.
+     */
+    pub fn new_streaming(serialize_to: SerializeTo<'a, Value>, fixed_value_size: usize) -> Self {
+        Self {
+            serialize: SerializeFunction::Streaming(serialize_to),
+            fixed_value_size,
+            #[cfg(feature = "compression")]
+            compression_threshold: None,
+        }
+    }
+
+    /**
+     * Makes this serializer compress the serialized values.
+     *
+     * Each serialized value is prefixed with a header byte. The values whose serialized sizes are
+     * not less than the threshold are compressed with LZ4, unless the compression makes them
+     * larger.
+     *
+     * Since the compressed values have variable sizes, the fixed value size is reset to 0.
+     *
+     * # Arguments
+     * * `threshold` - A threshold of the serialized value size.
+     *
+     * # Returns
+     * This serializer.
+     */
+    #[cfg(feature = "compression")]
+    pub fn compressed(mut self, threshold: usize) -> Self {
+        self.fixed_value_size = 0;
+        self.compression_threshold = Some(threshold);
+        self
+    }
+
     /**
      * Serializes a value.
      *
@@ -50,7 +107,49 @@ impl<'a, Value: ?Sized> ValueSerializer<'a, Value> {
      * The serialized value.
      */
     pub fn serialize(&mut self, value: &Value) -> Vec<u8> {
-        (self.serialize)(value)
+        #[cfg(feature = "compression")]
+        if let Some(threshold) = self.compression_threshold {
+            return compress(self.serialize_uncompressed(value), threshold);
+        }
+        self.serialize_uncompressed(value)
+    }
+
+    fn serialize_uncompressed(&mut self, value: &Value) -> Vec<u8> {
+        match &mut self.serialize {
+            SerializeFunction::Buffered(serialize) => serialize(value),
+            SerializeFunction::Streaming(serialize_to) => {
+                let mut serialized = Vec::with_capacity(self.fixed_value_size);
+                if let Err(e) = serialize_to(value, &mut serialized) {
+                    debug_assert!(false, "{}", e);
+                }
+                serialized
+            }
+        }
+    }
+
+    /**
+     * Serializes a value into a writer.
+     *
+     * # Arguments
+     * * `value`  - A value.
+     * * `writer` - A writer.
+     *
+     * # Errors
+     * * When it fails to write the serialized value.
+     */
+    pub fn serialize_to(&mut self, value: &Value, writer: &mut dyn Write) -> Result<()> {
+        #[cfg(feature = "compression")]
+        if self.compression_threshold.is_some() {
+            writer.write_all(&self.serialize(value))?;
+            return Ok(());
+        }
+        match &mut self.serialize {
+            SerializeFunction::Buffered(serialize) => {
+                writer.write_all(&serialize(value))?;
+                Ok(())
+            }
+            SerializeFunction::Streaming(serialize_to) => serialize_to(value, writer),
+        }
     }
 
     /**
@@ -64,12 +163,72 @@ impl<'a, Value: ?Sized> ValueSerializer<'a, Value> {
     }
 }
 
+#[cfg(feature = "compression")]
+const UNCOMPRESSED_HEADER: u8 = 0x00;
+
+#[cfg(feature = "compression")]
+const COMPRESSED_HEADER: u8 = 0x01;
+
+#[cfg(feature = "compression")]
+fn compress(serialized: Vec<u8>, threshold: usize) -> Vec<u8> {
+    if serialized.len() >= threshold {
+        let compressed = lz4_flex::compress_prepend_size(&serialized);
+        if compressed.len() < serialized.len() {
+            let mut result = Vec::with_capacity(1 + compressed.len());
+            result.push(COMPRESSED_HEADER);
+            result.extend(compressed);
+            return result;
+        }
+    }
+    let mut result = Vec::with_capacity(1 + serialized.len());
+    result.push(UNCOMPRESSED_HEADER);
+    result.extend(serialized);
+    result
+}
+
+#[cfg(feature = "compression")]
+fn decompress(serialized: &[u8]) -> Result<Cow<'_, [u8]>> {
+    match serialized.split_first() {
+        Some((&UNCOMPRESSED_HEADER, body)) => Ok(Cow::Borrowed(body)),
+        Some((&COMPRESSED_HEADER, body)) => Ok(Cow::Owned(
+            lz4_flex::decompress_size_prepended(body).map_err(CompressionError::from)?,
+        )),
+        _ => Err(CompressionError::InvalidHeader.into()),
+    }
+}
+
+/**
+ * A compression error.
+ */
+#[cfg(feature = "compression")]
+#[derive(Debug, thiserror::Error)]
+pub enum CompressionError {
+    /**
+     * Invalid header.
+     */
+    #[error("invalid header")]
+    InvalidHeader,
+
+    /**
+     * Decompression failure.
+     */
+    #[error("decompression failure")]
+    Decompression(#[from] lz4_flex::block::DecompressError),
+}
+
 impl<Value: ?Sized> Debug for ValueSerializer<'_, Value> {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        f.debug_struct("ValueSerializer")
-            .field("serialize", &type_name_of_val(&self.serialize))
-            .field("fixed_value_size", &self.fixed_value_size)
-            .finish()
+        let serialize = match &self.serialize {
+            SerializeFunction::Buffered(serialize) => type_name_of_val(serialize),
+            SerializeFunction::Streaming(serialize_to) => type_name_of_val(serialize_to),
+        };
+        let mut debug_struct = f.debug_struct("ValueSerializer");
+        let _ = debug_struct
+            .field("serialize", &serialize)
+            .field("fixed_value_size", &self.fixed_value_size);
+        #[cfg(feature = "compression")]
+        let _ = debug_struct.field("compression_threshold", &self.compression_threshold);
+        debug_struct.finish()
     }
 }
 
@@ -86,6 +245,8 @@ pub type Deserialize<Value> = Box<dyn FnMut(&[u8]) -> Result<Value>>;
  */
 pub struct ValueDeserializer<Value: Clone> {
     deserialize: Deserialize<Value>,
+    #[cfg(feature = "compression")]
+    compressed: bool,
 }
 
 impl<Value: Clone> ValueDeserializer<Value> {
@@ -96,7 +257,25 @@ impl<Value: Clone> ValueDeserializer<Value> {
      * * `deserialize` - A deserializing function.
      */
     pub fn new(deserialize: Deserialize<Value>) -> Self {
-        Self { deserialize }
+        Self {
+            deserialize,
+            #[cfg(feature = "compression")]
+            compressed: false,
+        }
+    }
+
+    /**
+     * Makes this deserializer decompress the serialized values.
+     *
+     * The values must be serialized by a serializer made by `ValueSerializer::compressed`.
+     *
+     * # Returns
+     * This deserializer.
+     */
+    #[cfg(feature = "compression")]
+    pub fn compressed(mut self) -> Self {
+        self.compressed = true;
+        self
     }
 
     /**
@@ -112,13 +291,75 @@ impl<Value: Clone> ValueDeserializer<Value> {
      * * When it fails to deserialize the value.
      */
     pub fn deserialize(&mut self, serialized: &[u8]) -> Result<Value> {
+        #[cfg(feature = "compression")]
+        if self.compressed {
+            return (self.deserialize)(&decompress(serialized)?);
+        }
         (self.deserialize)(serialized)
     }
 }
 
 impl<Value: Clone> Debug for ValueDeserializer<Value> {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        f.debug_struct("ValueDeserializer")
+        let mut debug_struct = f.debug_struct("ValueDeserializer");
+        let _ = debug_struct.field("deserialize", &type_name_of_val(&self.deserialize));
+        #[cfg(feature = "compression")]
+        let _ = debug_struct.field("compressed", &self.compressed);
+        debug_struct.finish()
+    }
+}
+
+/**
+ * A borrowing deserialize function type
+ */
+pub type DeserializeBorrowed<Value> = Box<dyn for<'b> Fn(&'b [u8]) -> Result<Cow<'b, Value>>>;
+
+/**
+ * A borrowing value deserializer.
+ *
+ * It produces values borrowing from the serialized buffer when possible, so that a storage
+ * backed by a file mapping can hand out values such as `&str` or `&[u8]` without copying them.
+ *
+ * # Type Parameters
+ * * `Value` - A value type.
+ */
+pub struct BorrowingValueDeserializer<Value: ?Sized + ToOwned + 'static> {
+    deserialize: DeserializeBorrowed<Value>,
+}
+
+impl<Value: ?Sized + ToOwned + 'static> BorrowingValueDeserializer<Value> {
+    /**
+     * Creates a borrowing value deserializer.
+     *
+     * # Arguments
+     * * `deserialize` - A deserializing function.
+     */
+    pub fn new(deserialize: impl for<'b> Fn(&'b [u8]) -> Result<Cow<'b, Value>> + 'static) -> Self {
+        Self {
+            deserialize: Box::new(deserialize),
+        }
+    }
+
+    /**
+     * Deserializes a value.
+     *
+     * # Arguments
+     * * `serialized` - A serialized value.
+     *
+     * # Returns
+     * A value, which may borrow from `serialized`.
+     *
+     * # Errors
+     * * When it fails to deserialize the value.
+     */
+    pub fn deserialize<'b>(&self, serialized: &'b [u8]) -> Result<Cow<'b, Value>> {
+        (self.deserialize)(serialized)
+    }
+}
+
+impl<Value: ?Sized + ToOwned + 'static> Debug for BorrowingValueDeserializer<Value> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BorrowingValueDeserializer")
             .field("deserialize", &type_name_of_val(&self.deserialize))
             .finish()
     }
@@ -183,6 +424,32 @@ mod tests {
             }
         }
 
+        #[test]
+        fn serialize_to() {
+            {
+                let mut serializer = ValueSerializer::new(Box::new(|_: &str| vec![3, 1, 4]), 0);
+
+                let mut serialized = Vec::new();
+                serializer.serialize_to("hoge", &mut serialized).unwrap();
+                assert_eq!(serialized, vec![3, 1, 4]);
+            }
+            {
+                let mut serializer = ValueSerializer::new_streaming(
+                    Box::new(|value: &str, writer: &mut dyn Write| {
+                        writer.write_all(value.as_bytes())?;
+                        Ok(())
+                    }),
+                    0,
+                );
+
+                let mut serialized = Vec::new();
+                serializer.serialize_to("hoge", &mut serialized).unwrap();
+                serializer.serialize_to("fuga", &mut serialized).unwrap();
+                assert_eq!(serialized, b"hogefuga");
+                assert_eq!(serializer.serialize("piyo"), b"piyo");
+            }
+        }
+
         #[test]
         fn fixed_value_size() {
             {
@@ -201,6 +468,49 @@ mod tests {
         }
     }
 
+    #[cfg(feature = "compression")]
+    mod compression {
+        use crate::error::TrieError;
+
+        use super::super::*;
+
+        #[test]
+        fn compressed() {
+            let mut serializer =
+                ValueSerializer::new(Box::new(|value: &str| value.as_bytes().to_vec()), 0)
+                    .compressed(16);
+            let mut deserializer = ValueDeserializer::new(Box::new(|serialized: &[u8]| {
+                String::from_utf8(serialized.to_vec()).map_err(TrieError::from)
+            }))
+            .compressed();
+            assert_eq!(serializer.fixed_value_size(), 0);
+
+            let short = "hoge";
+            let serialized_short = serializer.serialize(short);
+            assert_eq!(serialized_short, b"\x00hoge");
+            assert_eq!(deserializer.deserialize(&serialized_short).unwrap(), short);
+
+            let long = "hoge".repeat(64);
+            let serialized_long = serializer.serialize(&long);
+            assert_eq!(serialized_long[0], 0x01);
+            assert!(serialized_long.len() < long.len());
+            assert_eq!(deserializer.deserialize(&serialized_long).unwrap(), long);
+
+            let mut written = Vec::new();
+            serializer.serialize_to(&long, &mut written).unwrap();
+            assert_eq!(written, serialized_long);
+
+            assert!(matches!(
+                deserializer.deserialize(&[0x02, 0x00]),
+                Err(TrieError::Compression(CompressionError::InvalidHeader))
+            ));
+            assert!(matches!(
+                deserializer.deserialize(&[0x01, 0xFF]),
+                Err(TrieError::Compression(CompressionError::Decompression(_)))
+            ));
+        }
+    }
+
     mod value_deserializer {
         use crate::integer_serializer::{IntegerDeserializer, IntegerSerializer};
         use crate::serializer::{Deserializer, Serializer};
@@ -242,4 +552,27 @@ mod tests {
             }
         }
     }
+
+    mod borrowing_value_deserializer {
+        use std::str;
+
+        use crate::error::TrieError;
+
+        use super::super::*;
+
+        #[test]
+        fn deserialize() {
+            let deserializer = BorrowingValueDeserializer::<str>::new(|serialized| {
+                str::from_utf8(serialized)
+                    .map(Cow::Borrowed)
+                    .map_err(|e| TrieError::Other(Box::new(e)))
+            });
+
+            let serialized = b"hoge".to_vec();
+            let value = deserializer.deserialize(&serialized).unwrap();
+            assert!(matches!(value, Cow::Borrowed("hoge")));
+
+            assert!(deserializer.deserialize(&[0xFF]).is_err());
+        }
+    }
 }
@@ -0,0 +1,365 @@
+/*!
+ * A versioned trie.
+ *
+ * Copyright (C) 2023-2025 kaoru  <https://www.tetengo.org/>
+ */
+
+use std::fmt::{self, Debug, Formatter};
+use std::marker::PhantomData;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use arc_swap::ArcSwap;
+
+use crate::error::Result;
+use crate::serializer::{Serializer, SerializerOf};
+use crate::shared_storage::SharedStorage;
+use crate::trie::{Trie, TrieSettings};
+
+/**
+ * A versioned trie.
+ *
+ * It holds the current version of a trie and publishes new versions in the manner of RCU.
+ * A reader takes a snapshot, which stays unchanged while a writer makes and publishes a new
+ * version. The snapshot is released when the last reader drops it.
+ *
+ * A new version is published with an atomic swap of an `Arc`, so that taking a snapshot never
+ * blocks, and the version number is published together with the trie.
+ * The storage of a version is a shared storage, so that a versioned trie can be shared among
+ * threads. Each snapshot has its own trie on the shared storage.
+ * The writers are serialized, so that an update is not lost by a concurrent one.
+ *
+ * # Type Parameters
+ * * `Key`           - A key type.
+ * * `Value`         - A value type.
+ * * `KeySerializer` - A key serializer type.
+ */
+pub struct VersionedTrie<
+    Key,
+    Value: Clone,
+    KeySerializer: Serializer = <() as SerializerOf<Key>>::Type,
+> {
+    current: ArcSwap<Version<Key, Value, KeySerializer>>,
+    writer: Mutex<()>,
+}
+
+impl<Key, Value: Clone + Debug, KeySerializer: Serializer + Debug> Debug
+    for VersionedTrie<Key, Value, KeySerializer>
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let current = self.current.load();
+        f.debug_struct("VersionedTrie")
+            .field("version", &current.number)
+            .field("storage", &current.storage)
+            .field("settings", &current.settings)
+            .finish()
+    }
+}
+
+impl<Key, Value: Clone + Debug + 'static, KeySerializer: Serializer + Clone>
+    VersionedTrie<Key, Value, KeySerializer>
+{
+    /**
+     * Creates a versioned trie.
+     *
+     * The version of the given trie is 0.
+     *
+     * # Arguments
+     * * `trie` - A trie.
+     *
+     * # Errors
+     * * When it fails to read the storage of the trie.
+     */
+    pub fn new(trie: Trie<Key, Value, KeySerializer>) -> Result<Self> {
+        Ok(Self {
+            current: ArcSwap::from_pointee(Version::new(0, &trie)?),
+            writer: Mutex::new(()),
+        })
+    }
+
+    /**
+     * Returns a snapshot of the current version.
+     *
+     * # Returns
+     * The snapshot.
+     */
+    pub fn snapshot(&self) -> TrieSnapshot<Key, Value, KeySerializer> {
+        let current = self.current.load();
+        TrieSnapshot {
+            version: current.number,
+            trie: Trie::new_with_settings(
+                Box::new(current.storage.clone()),
+                current.settings.clone(),
+            ),
+        }
+    }
+
+    /**
+     * Returns the current version.
+     *
+     * # Returns
+     * The current version.
+     */
+    pub fn version(&self) -> u64 {
+        self.current.load().number
+    }
+
+    /**
+     * Publishes a new version.
+     *
+     * The snapshots taken before are not affected.
+     *
+     * # Arguments
+     * * `trie` - A trie of the new version.
+     *
+     * # Returns
+     * The new version.
+     *
+     * # Errors
+     * * When it fails to read the storage of the trie.
+     */
+    pub fn publish(&self, trie: Trie<Key, Value, KeySerializer>) -> Result<u64> {
+        let _writer = self.lock_writer();
+        self.swap_in(&trie)
+    }
+
+    /**
+     * Makes and publishes a new version from the current one.
+     *
+     * When the update fails, the current version is kept.
+     *
+     * # Arguments
+     * * `update` - A function which makes a new version from the current one.
+     *
+     * # Returns
+     * The new version.
+     *
+     * # Errors
+     * * When the update fails.
+     * * When it fails to read the storage of the updated trie.
+     */
+    pub fn update<Update>(&self, update: Update) -> Result<u64>
+    where
+        Update: FnOnce(&Trie<Key, Value, KeySerializer>) -> Result<Trie<Key, Value, KeySerializer>>,
+    {
+        let _writer = self.lock_writer();
+        let updated = update(self.snapshot().trie())?;
+        self.swap_in(&updated)
+    }
+
+    fn lock_writer(&self) -> MutexGuard<'_, ()> {
+        self.writer
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn swap_in(&self, trie: &Trie<Key, Value, KeySerializer>) -> Result<u64> {
+        let number = self.current.load().number + 1;
+        self.current.store(Arc::new(Version::new(number, trie)?));
+        Ok(number)
+    }
+}
+
+/**
+ * A snapshot of a versioned trie.
+ *
+ * # Type Parameters
+ * * `Key`           - A key type.
+ * * `Value`         - A value type.
+ * * `KeySerializer` - A key serializer type.
+ */
+pub struct TrieSnapshot<
+    Key,
+    Value: Debug,
+    KeySerializer: Serializer = <() as SerializerOf<Key>>::Type,
+> {
+    version: u64,
+    trie: Trie<Key, Value, KeySerializer>,
+}
+
+impl<Key, Value: Debug + 'static, KeySerializer: Serializer + Debug> Debug
+    for TrieSnapshot<Key, Value, KeySerializer>
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TrieSnapshot")
+            .field("version", &self.version)
+            .field("trie", &self.trie)
+            .finish()
+    }
+}
+
+impl<Key, Value: Debug, KeySerializer: Serializer> TrieSnapshot<Key, Value, KeySerializer> {
+    /**
+     * Returns the version.
+     *
+     * # Returns
+     * The version.
+     */
+    pub const fn version(&self) -> u64 {
+        self.version
+    }
+
+    /**
+     * Returns the trie.
+     *
+     * # Returns
+     * The trie.
+     */
+    pub const fn trie(&self) -> &Trie<Key, Value, KeySerializer> {
+        &self.trie
+    }
+
+    /**
+     * Returns the trie, consuming the snapshot.
+     *
+     * # Returns
+     * The trie.
+     */
+    pub fn into_trie(self) -> Trie<Key, Value, KeySerializer> {
+        self.trie
+    }
+}
+
+struct Version<Key, Value: Clone, KeySerializer> {
+    number: u64,
+    storage: SharedStorage<Value>,
+    settings: TrieSettings<KeySerializer>,
+    phantom: PhantomData<fn() -> Key>,
+}
+
+impl<Key, Value: Clone + Debug + 'static, KeySerializer: Serializer + Clone>
+    Version<Key, Value, KeySerializer>
+{
+    fn new(number: u64, trie: &Trie<Key, Value, KeySerializer>) -> Result<Self> {
+        Ok(Self {
+            number,
+            storage: SharedStorage::new_with_storage(trie.storage())?,
+            settings: trie.settings(),
+            phantom: PhantomData,
+        })
+    }
+}
+
+#[cfg(all(test, feature = "builder"))]
+mod tests {
+    use std::thread;
+
+    use crate::error::TrieError;
+    use crate::string_serializer::StrSerializer;
+
+    use super::*;
+
+    fn create_trie(elements: &[(&'static str, i32)]) -> Result<Trie<&'static str, i32>> {
+        Trie::<&str, i32>::builder()
+            .elements(elements.to_vec())
+            .key_serializer(StrSerializer::new(true))
+            .build()
+    }
+
+    #[test]
+    fn new() {
+        let versioned = VersionedTrie::new(create_trie(&[("kumamoto", 42)]).unwrap()).unwrap();
+
+        assert_eq!(versioned.version(), 0);
+        let snapshot = versioned.snapshot();
+        assert_eq!(snapshot.version(), 0);
+        assert_eq!(*snapshot.trie().find(&"kumamoto").unwrap().unwrap(), 42);
+    }
+
+    #[test]
+    fn publish() {
+        let versioned = VersionedTrie::new(create_trie(&[("kumamoto", 42)]).unwrap()).unwrap();
+        let old_snapshot = versioned.snapshot();
+
+        let version = versioned
+            .publish(create_trie(&[("tamana", 24)]).unwrap())
+            .unwrap();
+
+        assert_eq!(version, 1);
+        assert_eq!(versioned.version(), 1);
+        assert_eq!(old_snapshot.version(), 0);
+        assert_eq!(*old_snapshot.trie().find(&"kumamoto").unwrap().unwrap(), 42);
+        assert!(old_snapshot.trie().find(&"tamana").unwrap().is_none());
+        let new_snapshot = versioned.snapshot();
+        assert_eq!(new_snapshot.version(), 1);
+        assert!(new_snapshot.trie().find(&"kumamoto").unwrap().is_none());
+        assert_eq!(*new_snapshot.trie().find(&"tamana").unwrap().unwrap(), 24);
+    }
+
+    #[test]
+    fn update() {
+        let versioned = VersionedTrie::new(create_trie(&[("kumamoto", 42)]).unwrap()).unwrap();
+
+        {
+            let version = versioned
+                .update(|current| {
+                    let kumamoto = *current.find(&"kumamoto")?.unwrap();
+                    create_trie(&[("kumamoto", kumamoto), ("uto", 55)])
+                })
+                .unwrap();
+
+            assert_eq!(version, 1);
+            let snapshot = versioned.snapshot();
+            assert_eq!(*snapshot.trie().find(&"kumamoto").unwrap().unwrap(), 42);
+            assert_eq!(*snapshot.trie().find(&"uto").unwrap().unwrap(), 55);
+        }
+        {
+            let result = versioned.update(|_| Err(TrieError::Other(Box::from("failed to update"))));
+
+            assert!(result.is_err());
+            assert_eq!(versioned.version(), 1);
+            assert!(versioned.snapshot().trie().contains(&"uto").unwrap());
+        }
+    }
+
+    #[test]
+    fn snapshot_while_updating() {
+        const WRITER_COUNT: i32 = 2;
+        const UPDATE_COUNT: i32 = 50;
+        let versioned = VersionedTrie::new(create_trie(&[("kumamoto", 0)]).unwrap()).unwrap();
+
+        thread::scope(|scope| {
+            let readers = (0..4)
+                .map(|_| {
+                    scope.spawn(|| {
+                        let mut last_value = 0;
+                        while last_value < WRITER_COUNT * UPDATE_COUNT {
+                            let snapshot = versioned.snapshot();
+                            let value = *snapshot.trie().find(&"kumamoto").unwrap().unwrap();
+                            assert_eq!(value as u64, snapshot.version());
+                            assert!(value >= last_value);
+                            last_value = value;
+                        }
+                    })
+                })
+                .collect::<Vec<_>>();
+            let writers = (0..WRITER_COUNT)
+                .map(|_| {
+                    scope.spawn(|| {
+                        for _ in 0..UPDATE_COUNT {
+                            let _version = versioned
+                                .update(|current| {
+                                    let kumamoto = *current.find(&"kumamoto")?.unwrap();
+                                    create_trie(&[("kumamoto", kumamoto + 1)])
+                                })
+                                .unwrap();
+                        }
+                    })
+                })
+                .collect::<Vec<_>>();
+            for handle in readers.into_iter().chain(writers) {
+                handle.join().unwrap();
+            }
+        });
+
+        assert_eq!(versioned.version(), (WRITER_COUNT * UPDATE_COUNT) as u64);
+        assert_eq!(
+            *versioned
+                .snapshot()
+                .trie()
+                .find(&"kumamoto")
+                .unwrap()
+                .unwrap(),
+            WRITER_COUNT * UPDATE_COUNT
+        );
+    }
+}